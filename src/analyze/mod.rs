@@ -10,10 +10,11 @@ use gel_tokio::raw::Description;
 
 use crate::classify;
 use crate::cli::env::Env;
-use crate::commands::parser::Analyze;
+use crate::commands::parser::{Analyze, AnalyzeFormat};
 use crate::connect::Connection;
 use crate::interactive::QueryError;
 use crate::platform::tmp_file_path;
+use crate::print::msg;
 use crate::repl::{self, LastAnalyze};
 use crate::variables::input_variables;
 
@@ -43,7 +44,7 @@ pub async fn interactive(prompt: &mut repl::State, query: &str) -> anyhow::Resul
             {
                 Ok(input) => input,
                 Err(e) => {
-                    eprintln!("{e:#}");
+                    msg!("{e:#}");
                     prompt.last_error = Some(e);
                     return Err(QueryError)?;
                 }
@@ -84,6 +85,23 @@ fn render_explain(explain: &Analysis) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Runs `analyze` for the query and returns the execution time reported by
+/// the server, in milliseconds.
+pub async fn server_time(cli: &mut Connection, query: &str) -> anyhow::Result<Option<f64>> {
+    let query = if classify::is_analyze(query) {
+        Cow::Borrowed(query)
+    } else {
+        Cow::Owned(format!("analyze {query}"))
+    };
+    let data = cli.query_required_single::<String, _>(&query, &()).await?;
+    let jd = &mut serde_json::Deserializer::from_str(&data);
+    let output: model::AnalysisData =
+        serde_path_to_error::deserialize(jd).context("parsing explain output")?;
+    Ok(output
+        .coarse_grained
+        .and_then(|shape| shape.cost.actual_total_time))
+}
+
 #[fn_error_context::context("cannot lookup path {:?}", path)]
 async fn is_special(path: &Path) -> anyhow::Result<bool> {
     match fs::metadata(path).await {
@@ -113,42 +131,70 @@ pub async fn command(cli: &mut Connection, options: &Analyze) -> anyhow::Result<
         cli.query_required_single::<String, _>(&query, &()).await?
     };
     if let Some(out_path) = &options.debug_output_file {
-        if out_path == Path::new("-") {
-            let mut out = io::stdout();
+        return write_output(out_path, &data).await;
+    }
+    if options.output_file.is_some()
+        && !matches!(
+            options.format,
+            AnalyzeFormat::Json | AnalyzeFormat::Flamegraph
+        )
+    {
+        anyhow::bail!("`--output-file` requires `--format=json` or `--format=flamegraph`");
+    }
+    if options.format == AnalyzeFormat::Json {
+        let path = options.output_file.as_deref().unwrap_or(Path::new("-"));
+        return write_output(path, &data).await;
+    }
+
+    let jd = &mut serde_json::Deserializer::from_str(&data);
+    let output = serde_path_to_error::deserialize(jd).context("parsing explain output")?;
+    let output = contexts::preprocess(output);
+
+    match options.format {
+        AnalyzeFormat::Tree => render_explain(&output)?,
+        AnalyzeFormat::Table => {
+            contexts::print(&output);
+            tree::print_shape_table(&output);
+        }
+        AnalyzeFormat::Flamegraph => {
+            let path = options.output_file.as_deref().unwrap_or(Path::new("-"));
+            return write_output(path, &tree::folded_stacks(&output)).await;
+        }
+        AnalyzeFormat::Json => unreachable!(),
+    }
+    if options.expand {
+        println!();
+        render_expanded_explain(&output).await?;
+    }
+    Ok(())
+}
+
+/// Writes data to the file atomically, or to stdout if path is `-`
+async fn write_output(out_path: &Path, data: &str) -> anyhow::Result<()> {
+    if out_path == Path::new("-") {
+        let mut out = io::stdout();
+        out.write_all(data.as_bytes()).await?;
+        out.flush().await?;
+    } else if is_special(out_path).await? {
+        async {
+            let mut out = fs::File::create(&out_path).await?;
             out.write_all(data.as_bytes()).await?;
-            out.flush().await?;
-        } else if is_special(out_path).await? {
-            async {
-                let mut out = fs::File::create(&out_path).await?;
-                out.write_all(data.as_bytes()).await?;
-                out.flush().await
-            }
-            .await
-            .with_context(|| format!("error writing to {out_path:?}"))?;
-        } else {
-            let tmp = tmp_file_path(out_path);
-            async {
-                let mut out = fs::File::create(&tmp).await?;
-                out.write_all(data.as_bytes()).await?;
-                out.flush().await
-            }
-            .await
-            .with_context(|| format!("error writing to {tmp:?}"))?;
-            fs::rename(&tmp, &out_path)
-                .await
-                .with_context(|| format!("rename error {tmp:?} -> {out_path:?}"))?;
+            out.flush().await
         }
+        .await
+        .with_context(|| format!("error writing to {out_path:?}"))?;
     } else {
-        let jd = &mut serde_json::Deserializer::from_str(&data);
-        let output = serde_path_to_error::deserialize(jd)
-            .with_context(|| format!("parsing explain output"))?;
-        let output = contexts::preprocess(output);
-
-        render_explain(&output)?;
-        if options.expand {
-            println!();
-            render_expanded_explain(&output).await?;
+        let tmp = tmp_file_path(out_path);
+        async {
+            let mut out = fs::File::create(&tmp).await?;
+            out.write_all(data.as_bytes()).await?;
+            out.flush().await
         }
+        .await
+        .with_context(|| format!("error writing to {tmp:?}"))?;
+        fs::rename(&tmp, &out_path)
+            .await
+            .with_context(|| format!("rename error {tmp:?} -> {out_path:?}"))?;
     }
     Ok(())
 }
@@ -138,6 +138,88 @@ fn visit_subshape<'x>(
     }
 }
 
+/// Prints the coarse-grained plan as a flat table, a row per shape element
+/// named by its path from the root
+pub fn print_shape_table(explain: &Analysis) {
+    if let Some(shape) = &explain.coarse_grained {
+        let mut header = Vec::with_capacity(8);
+        header.push(Box::new("Path".emphasize()) as Box<_>);
+        cost_header(&mut header, &explain.arguments);
+        header.push(Box::new("Relations".emphasize()) as Box<_>);
+
+        let mut rows = vec![header];
+        visit_shape_path(&mut rows, explain, "root".into(), shape);
+
+        table::render(Some("Coarse-grained Query Plan"), &rows);
+    }
+}
+
+fn visit_shape_path<'x>(
+    result: &mut Vec<Vec<Box<dyn table::Contents + 'x>>>,
+    explain: &Analysis,
+    path: String,
+    node: &'x Shape,
+) {
+    let mut row = Vec::with_capacity(8);
+    let context = explain.context(&node.contexts);
+    row.push(Box::new(format!("{context}{path}")) as Box<_>);
+    cost_columns(&mut row, &node.cost, &explain.arguments);
+    row.push(Box::new(table::WordList(Relations(&node.relations))));
+    result.push(row);
+
+    for ch in &node.children {
+        let path = format!("{path}{}", child_segment(&ch.name));
+        visit_shape_path(result, explain, path, &ch.node);
+    }
+}
+
+/// Formats the coarse-grained plan as folded stacks: a line per shape
+/// element with its own time in microseconds (or planner cost if the query
+/// was not executed), as consumed by flame graph viewers
+pub fn folded_stacks(explain: &Analysis) -> String {
+    let mut out = String::new();
+    if let Some(shape) = &explain.coarse_grained {
+        let mut stack = vec!["root".to_string()];
+        visit_folded(&mut out, &mut stack, shape, &explain.arguments);
+    }
+    out
+}
+
+fn visit_folded(out: &mut String, stack: &mut Vec<String>, node: &Shape, args: &Arguments) {
+    let children: f64 = node
+        .children
+        .iter()
+        .map(|ch| flame_weight(&ch.node.cost, args))
+        .sum();
+    let own = (flame_weight(&node.cost, args) - children).max(0.).round();
+    if own > 0. {
+        writeln!(out, "{} {own}", stack.join(";")).expect("can write to string");
+    }
+    for ch in &node.children {
+        let segment = child_segment(&ch.name).replace([';', ' '], "_");
+        stack.push(segment);
+        visit_folded(out, stack, &ch.node, args);
+        stack.pop();
+    }
+}
+
+fn flame_weight(cost: &Cost, args: &Arguments) -> f64 {
+    if args.execute {
+        let loops = cost.actual_loops.unwrap_or(1.);
+        cost.actual_total_time.unwrap_or(0.) * loops * 1000.
+    } else {
+        cost.total_cost
+    }
+}
+
+fn child_segment(name: &ChildName) -> String {
+    match name {
+        ChildName::Pointer { name } => format!(".{name}"),
+        ChildName::Filter => "(filter)".into(),
+        ChildName::Other => "(other)".into(),
+    }
+}
+
 pub fn print_expanded_tree(explain: &Analysis) {
     if let Some(node) = &explain.fine_grained {
         let mut header = Vec::with_capacity(9);
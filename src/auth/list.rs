@@ -0,0 +1,130 @@
+use crate::auth::{self, provider::ProviderKind};
+use crate::commands::Options;
+use crate::print::msg;
+use crate::table::{self, Cell, Row, Table};
+
+const CONFIG_QUERY: &str = r###"
+    SELECT <str><json>(
+        SELECT cfg::Config.extensions[IS ext::auth::AuthConfig] {
+            app_name,
+            allowed_redirect_urls,
+            token_time_to_live := <str>.token_time_to_live,
+            providers: {
+                name,
+                [IS ext::auth::OAuthProviderConfig].client_id,
+                [IS ext::auth::OAuthProviderConfig].additional_scope,
+                [IS ext::auth::EmailPasswordProviderConfig].require_verification,
+                [IS ext::auth::WebAuthnProviderConfig].relying_party_origin,
+            } ORDER BY .name,
+        } LIMIT 1
+    )
+"###;
+
+/// List configured authentication providers and settings
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct AuthConfig {
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub allowed_redirect_urls: Vec<String>,
+    pub token_time_to_live: Option<String>,
+    #[serde(default)]
+    pub providers: Vec<Provider>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct Provider {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional_scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_verification: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relying_party_origin: Option<String>,
+}
+
+pub async fn run(cmd: &Command, options: &Options) -> anyhow::Result<()> {
+    let mut conn = options.conn_params.connect().await?;
+    auth::ensure_extension(&mut conn).await?;
+    let data = conn.query_single::<String, _>(CONFIG_QUERY, &()).await?;
+    let config: AuthConfig = match data {
+        Some(data) => serde_json::from_str(&data)?,
+        None => AuthConfig::default(),
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    table::settings(&[
+        (
+            "App name",
+            config.app_name.clone().unwrap_or_else(|| "-".into()),
+        ),
+        (
+            "Token time to live",
+            config
+                .token_time_to_live
+                .clone()
+                .unwrap_or_else(|| "-".into()),
+        ),
+        (
+            "Allowed redirect URLs",
+            if config.allowed_redirect_urls.is_empty() {
+                "-".into()
+            } else {
+                config.allowed_redirect_urls.join("\n")
+            },
+        ),
+    ]);
+
+    if config.providers.is_empty() {
+        msg!("No providers configured.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Provider", "Name", "Details"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for provider in &config.providers {
+        let kind = ProviderKind::from_name(&provider.name);
+        table.add_row(Row::new(vec![
+            Cell::new(kind.map(|k| k.as_str()).unwrap_or("?")),
+            Cell::new(&provider.name),
+            Cell::new(&provider.details()),
+        ]));
+    }
+    table.printstd();
+    Ok(())
+}
+
+impl Provider {
+    fn details(&self) -> String {
+        let mut details = Vec::new();
+        if let Some(client_id) = &self.client_id {
+            details.push(format!("client id: {client_id}"));
+        }
+        if let Some(scope) = &self.additional_scope {
+            details.push(format!("additional scope: {scope}"));
+        }
+        if let Some(verification) = self.require_verification {
+            details.push(format!("require verification: {verification}"));
+        }
+        if let Some(origin) = &self.relying_party_origin {
+            details.push(format!("relying party origin: {origin}"));
+        }
+        details.join("\n")
+    }
+}
@@ -0,0 +1,204 @@
+mod list;
+mod provider;
+mod set;
+mod smtp;
+
+use edgeql_parser::helpers::quote_string;
+
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::{ExitCode, Options};
+use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::options::ConnectionOptions;
+use crate::portable::exit_codes;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn run(options: &Options, cmd: &Command) -> anyhow::Result<()> {
+    match &cmd.subcommand {
+        Subcommand::List(c) => list::run(c, options).await,
+        Subcommand::Provider(c) => provider::run(c, options).await,
+        Subcommand::Set(c) => set::run(c, options).await,
+        Subcommand::Smtp(c) => smtp::run(c, options).await,
+    }
+}
+
+/// Inspect and configure the `auth` extension of the current branch.
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    List(list::Command),
+    Provider(provider::Command),
+    Set(set::Command),
+    Smtp(smtp::Command),
+}
+
+impl Subcommand {
+    /// Whether the command changes configuration of the branch
+    pub fn is_modifying(&self) -> bool {
+        match self {
+            Subcommand::List(_) => false,
+            Subcommand::Provider(c) => !c.apply().print_edgeql,
+            Subcommand::Set(c) => !c.apply.print_edgeql,
+            Subcommand::Smtp(c) => !c.apply.print_edgeql,
+        }
+    }
+}
+
+/// Options of the commands that change configuration
+#[derive(clap::Args, Debug, Clone)]
+pub struct ApplyOptions {
+    /// Print EdgeQL statements instead of executing them
+    #[arg(long)]
+    pub print_edgeql: bool,
+
+    /// Do not ask questions; fail if required values are not specified
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+/// Configuration statements, with secrets masked in the displayed copy
+#[derive(Debug, Default)]
+pub struct Script {
+    statements: Vec<String>,
+    display: Vec<String>,
+}
+
+impl Script {
+    fn add(&mut self, statement: String) {
+        self.display.push(statement.clone());
+        self.statements.push(statement);
+    }
+
+    /// Adds a statement built from the quoted `secret`
+    fn add_secret(&mut self, secret: &str, statement: impl Fn(&str) -> String) {
+        self.statements.push(statement(&quote_string(secret)));
+        self.display.push(statement("'********'"));
+    }
+}
+
+/// Name of the configuration scope used in `configure` statements
+pub async fn scope(conn: &mut Connection) -> anyhow::Result<&'static str> {
+    let version = conn.get_version().await?;
+    if version.specific().major >= 5 {
+        Ok("current branch")
+    } else {
+        Ok("current database")
+    }
+}
+
+pub async fn ensure_extension(conn: &mut Connection) -> anyhow::Result<()> {
+    let installed = conn
+        .query_required_single::<bool, _>(
+            "SELECT EXISTS(SELECT schema::Extension FILTER .name = 'auth')",
+            &(),
+        )
+        .await?;
+    if !installed {
+        return Err(anyhow::anyhow!(
+            "the `auth` extension is not enabled on branch {:?}",
+            conn.branch()
+        ))
+        .with_hint(|| {
+            format!(
+                "Add `using extension auth;` to the schema and run \
+                 `{BRANDING_CLI_CMD} migration create` and `{BRANDING_CLI_CMD} migrate`"
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Prints the statements, or executes them after confirmation
+pub async fn apply(
+    conn: &mut Connection,
+    options: &ApplyOptions,
+    script: &Script,
+) -> anyhow::Result<()> {
+    if options.print_edgeql {
+        for statement in &script.statements {
+            println!("{statement};");
+        }
+        return Ok(());
+    }
+    if !options.non_interactive {
+        msg!("The following statements will be executed:");
+        for statement in &script.display {
+            msg!("  {};", statement.emphasize());
+        }
+        let q = question::Confirm::new("Apply these changes?");
+        if !conn.ping_while(q.async_ask()).await? {
+            print::error!("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+    for statement in &script.statements {
+        conn.execute(statement, &()).await?;
+    }
+    print::success!("Auth configuration updated.");
+    Ok(())
+}
+
+/// Returns the value of the option, asking for it if it was not specified
+pub fn required(
+    value: &Option<String>,
+    option: &str,
+    question: &str,
+    non_interactive: bool,
+) -> anyhow::Result<String> {
+    if let Some(value) = value {
+        return Ok(value.clone());
+    }
+    if non_interactive {
+        anyhow::bail!("`{option}` is required in non-interactive mode");
+    }
+    loop {
+        let value = question::String::new(question).ask()?;
+        if !value.is_empty() {
+            return Ok(value);
+        }
+        print::error!("Value is required.");
+    }
+}
+
+/// Converts a human-readable duration (e.g. `15m`, `2 weeks`) to EdgeQL
+pub fn duration_literal(value: &str) -> anyhow::Result<String> {
+    let duration = humantime::parse_duration(value)
+        .map_err(|e| anyhow::anyhow!("invalid duration {value:?}: {e}"))?;
+    Ok(format!("<duration>'{} seconds'", duration.as_secs()))
+}
+
+/// Checks that the value is an absolute HTTP(S) URL
+pub fn validate_url(value: &str) -> anyhow::Result<url::Url> {
+    let url = url::Url::parse(value).map_err(|e| anyhow::anyhow!("invalid URL {value:?}: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("URL {value:?} must use `http` or `https` scheme");
+    }
+    Ok(url)
+}
+
+#[test]
+fn duration() {
+    assert_eq!(duration_literal("15m").unwrap(), "<duration>'900 seconds'");
+    assert_eq!(
+        duration_literal("2 weeks").unwrap(),
+        "<duration>'1209600 seconds'"
+    );
+    assert!(duration_literal("soon").is_err());
+}
+
+#[test]
+fn url() {
+    assert!(validate_url("https://example.com/callback").is_ok());
+    assert!(validate_url("ftp://example.com").is_err());
+    assert!(validate_url("example.com").is_err());
+}
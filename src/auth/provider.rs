@@ -0,0 +1,302 @@
+use edgeql_parser::helpers::quote_string;
+
+use crate::auth::{self, ApplyOptions, Script};
+use crate::commands::Options;
+use crate::tty_password;
+
+const PROVIDER_EXISTS: &str = r###"
+    SELECT EXISTS(
+        SELECT cfg::Config.extensions[IS ext::auth::AuthConfig].providers
+        FILTER .name = <str>$0
+    )
+"###;
+
+/// Add or remove authentication providers
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    Add(Add),
+    Remove(Remove),
+}
+
+/// Enable an authentication provider, replacing its configuration if it
+/// is already enabled
+#[derive(clap::Args, Debug, Clone)]
+pub struct Add {
+    pub kind: ProviderKind,
+
+    /// OAuth client ID issued by the identity provider
+    #[arg(long)]
+    pub client_id: Option<String>,
+
+    /// OAuth client secret. Asked interactively if not specified.
+    #[arg(long)]
+    pub secret: Option<String>,
+
+    /// Additional OAuth scopes, separated by spaces
+    #[arg(long)]
+    pub additional_scope: Option<String>,
+
+    /// Require email verification before sign in (`email-password` and
+    /// `webauthn` only)
+    #[arg(long)]
+    pub require_verification: bool,
+
+    /// Origin of the application, e.g. `https://example.com`
+    /// (`webauthn` only)
+    #[arg(long)]
+    pub relying_party_origin: Option<String>,
+
+    /// Time to live of sign in links, e.g. `15m` (`magic-link` only)
+    #[arg(long)]
+    pub token_time_to_live: Option<String>,
+
+    #[command(flatten)]
+    pub apply: ApplyOptions,
+}
+
+/// Disable an authentication provider
+#[derive(clap::Args, Debug, Clone)]
+pub struct Remove {
+    pub kind: ProviderKind,
+
+    #[command(flatten)]
+    pub apply: ApplyOptions,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    Apple,
+    Azure,
+    Discord,
+    Github,
+    Google,
+    Slack,
+    EmailPassword,
+    Webauthn,
+    MagicLink,
+}
+
+const ALL_KINDS: &[ProviderKind] = &[
+    ProviderKind::Apple,
+    ProviderKind::Azure,
+    ProviderKind::Discord,
+    ProviderKind::Github,
+    ProviderKind::Google,
+    ProviderKind::Slack,
+    ProviderKind::EmailPassword,
+    ProviderKind::Webauthn,
+    ProviderKind::MagicLink,
+];
+
+impl Command {
+    pub fn apply(&self) -> &ApplyOptions {
+        match &self.subcommand {
+            Subcommand::Add(c) => &c.apply,
+            Subcommand::Remove(c) => &c.apply,
+        }
+    }
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        use ProviderKind::*;
+        match self {
+            Apple => "apple",
+            Azure => "azure",
+            Discord => "discord",
+            Github => "github",
+            Google => "google",
+            Slack => "slack",
+            EmailPassword => "email-password",
+            Webauthn => "webauthn",
+            MagicLink => "magic-link",
+        }
+    }
+
+    /// Name of the provider in the configuration
+    pub fn name(&self) -> &'static str {
+        use ProviderKind::*;
+        match self {
+            Apple => "builtin::oauth_apple",
+            Azure => "builtin::oauth_azure",
+            Discord => "builtin::oauth_discord",
+            Github => "builtin::oauth_github",
+            Google => "builtin::oauth_google",
+            Slack => "builtin::oauth_slack",
+            EmailPassword => "builtin::local_emailpassword",
+            Webauthn => "builtin::local_webauthn",
+            MagicLink => "builtin::local_magic_link",
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        use ProviderKind::*;
+        match self {
+            Apple => "ext::auth::AppleOAuthProvider",
+            Azure => "ext::auth::AzureOAuthProvider",
+            Discord => "ext::auth::DiscordOAuthProvider",
+            Github => "ext::auth::GitHubOAuthProvider",
+            Google => "ext::auth::GoogleOAuthProvider",
+            Slack => "ext::auth::SlackOAuthProvider",
+            EmailPassword => "ext::auth::EmailPasswordProviderConfig",
+            Webauthn => "ext::auth::WebAuthnProviderConfig",
+            MagicLink => "ext::auth::MagicLinkProviderConfig",
+        }
+    }
+
+    fn is_oauth(&self) -> bool {
+        use ProviderKind::*;
+        matches!(self, Apple | Azure | Discord | Github | Google | Slack)
+    }
+
+    pub fn from_name(name: &str) -> Option<ProviderKind> {
+        ALL_KINDS.iter().find(|k| k.name() == name).copied()
+    }
+}
+
+pub async fn run(cmd: &Command, options: &Options) -> anyhow::Result<()> {
+    // ask all questions before connecting
+    let (kind, insert, apply) = match &cmd.subcommand {
+        Subcommand::Add(c) => {
+            let fields = fields(c)?;
+            let secret = if c.kind.is_oauth() {
+                Some(secret(c)?)
+            } else {
+                None
+            };
+            (c.kind, Some((fields, secret)), &c.apply)
+        }
+        Subcommand::Remove(c) => (c.kind, None, &c.apply),
+    };
+    let mut conn = options.conn_params.connect().await?;
+    auth::ensure_extension(&mut conn).await?;
+    let scope = auth::scope(&mut conn).await?;
+    let exists = conn
+        .query_required_single::<bool, _>(PROVIDER_EXISTS, &(kind.name(),))
+        .await?;
+
+    let mut script = Script::default();
+    if exists {
+        script.add(format!(
+            "CONFIGURE {scope} RESET ext::auth::ProviderConfig FILTER .name = {}",
+            quote_string(kind.name()),
+        ));
+    } else if insert.is_none() {
+        anyhow::bail!("provider {:?} is not configured", kind.as_str());
+    }
+    if let Some((fields, secret)) = insert {
+        let insert = |secret: Option<&str>| {
+            let mut items = fields.clone();
+            if let Some(secret) = secret {
+                items.push(format!("secret := {secret}"));
+            }
+            format!(
+                "CONFIGURE {scope} INSERT {} {{ {} }}",
+                kind.type_name(),
+                items.join(", "),
+            )
+        };
+        match secret {
+            Some(secret) => script.add_secret(&secret, |secret| insert(Some(secret))),
+            None => script.add(insert(None)),
+        }
+    }
+    auth::apply(&mut conn, apply, &script).await
+}
+
+/// Validates options and converts them to fields of the `INSERT`,
+/// except the secret
+fn fields(cmd: &Add) -> anyhow::Result<Vec<String>> {
+    use ProviderKind::*;
+
+    let kind = cmd.kind;
+    let unsupported = |option: &str| {
+        anyhow::anyhow!(
+            "`{option}` is not supported by the {:?} provider",
+            kind.as_str()
+        )
+    };
+    if !kind.is_oauth() {
+        if cmd.client_id.is_some() {
+            return Err(unsupported("--client-id"));
+        }
+        if cmd.secret.is_some() {
+            return Err(unsupported("--secret"));
+        }
+        if cmd.additional_scope.is_some() {
+            return Err(unsupported("--additional-scope"));
+        }
+    }
+    if cmd.require_verification && !matches!(kind, EmailPassword | Webauthn) {
+        return Err(unsupported("--require-verification"));
+    }
+    if cmd.relying_party_origin.is_some() && kind != Webauthn {
+        return Err(unsupported("--relying-party-origin"));
+    }
+    if cmd.token_time_to_live.is_some() && kind != MagicLink {
+        return Err(unsupported("--token-time-to-live"));
+    }
+
+    let non_interactive = cmd.apply.non_interactive;
+    let mut fields = Vec::new();
+    match kind {
+        Apple | Azure | Discord | Github | Google | Slack => {
+            let client_id =
+                auth::required(&cmd.client_id, "--client-id", "Client ID", non_interactive)?;
+            fields.push(format!("client_id := {}", quote_string(&client_id)));
+            if let Some(scope) = &cmd.additional_scope {
+                fields.push(format!("additional_scope := {}", quote_string(scope)));
+            }
+        }
+        EmailPassword => {
+            fields.push(format!(
+                "require_verification := {}",
+                cmd.require_verification
+            ));
+        }
+        Webauthn => {
+            let origin = auth::required(
+                &cmd.relying_party_origin,
+                "--relying-party-origin",
+                "Relying party origin (e.g. https://example.com)",
+                non_interactive,
+            )?;
+            let url = auth::validate_url(&origin)?;
+            if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+                anyhow::bail!("relying party origin {origin:?} must not contain a path");
+            }
+            let origin = url.origin().ascii_serialization();
+            fields.push(format!("relying_party_origin := {}", quote_string(&origin)));
+            fields.push(format!(
+                "require_verification := {}",
+                cmd.require_verification
+            ));
+        }
+        MagicLink => {
+            if let Some(ttl) = &cmd.token_time_to_live {
+                fields.push(format!(
+                    "token_time_to_live := {}",
+                    auth::duration_literal(ttl)?
+                ));
+            }
+        }
+    }
+    Ok(fields)
+}
+
+fn secret(cmd: &Add) -> anyhow::Result<String> {
+    if let Some(secret) = &cmd.secret {
+        return Ok(secret.clone());
+    }
+    if cmd.apply.non_interactive {
+        anyhow::bail!("`--secret` is required in non-interactive mode");
+    }
+    tty_password::read("Client secret: ")
+}
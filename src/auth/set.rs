@@ -0,0 +1,129 @@
+use edgeql_parser::helpers::quote_string;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+use crate::auth::{self, ApplyOptions, Script};
+use crate::commands::Options;
+use crate::hint::HintExt;
+
+/// Minimum length of the signing key accepted by the server
+const SIGNING_KEY_MIN_LENGTH: usize = 32;
+const SIGNING_KEY_LENGTH: usize = 64;
+
+/// Change settings of the `auth` extension
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Name of the application shown in the built-in UI and emails
+    #[arg(long)]
+    pub app_name: Option<String>,
+
+    /// URL of the logo shown in the built-in UI
+    #[arg(long)]
+    pub logo_url: Option<String>,
+
+    /// URL of the logo for the dark theme of the built-in UI
+    #[arg(long)]
+    pub dark_logo_url: Option<String>,
+
+    /// Brand color of the built-in UI, e.g. `1f8aed`
+    #[arg(long)]
+    pub brand_color: Option<String>,
+
+    /// URL the server is allowed to redirect to after authentication.
+    /// Can be specified multiple times, replaces the existing list.
+    #[arg(long, value_name = "url")]
+    pub allowed_redirect_url: Vec<String>,
+
+    /// Key used to sign authentication tokens (at least 32 characters)
+    #[arg(long, conflicts_with = "generate_signing_key")]
+    pub signing_key: Option<String>,
+
+    /// Generate a random signing key
+    #[arg(long)]
+    pub generate_signing_key: bool,
+
+    /// Time to live of authentication tokens, e.g. `336h` or `2 weeks`
+    #[arg(long)]
+    pub token_time_to_live: Option<String>,
+
+    #[command(flatten)]
+    pub apply: ApplyOptions,
+}
+
+pub async fn run(cmd: &Command, options: &Options) -> anyhow::Result<()> {
+    let settings = settings(cmd)?;
+    let signing_key = if cmd.generate_signing_key {
+        Some(generate_signing_key())
+    } else {
+        cmd.signing_key.clone()
+    };
+    if let Some(key) = &signing_key {
+        if key.len() < SIGNING_KEY_MIN_LENGTH {
+            anyhow::bail!("signing key must be at least {SIGNING_KEY_MIN_LENGTH} characters long");
+        }
+    }
+    if settings.is_empty() && signing_key.is_none() {
+        return Err(anyhow::anyhow!("nothing to change"))
+            .hint("Specify at least one setting, see `--help` for the list")?;
+    }
+
+    let mut conn = options.conn_params.connect().await?;
+    auth::ensure_extension(&mut conn).await?;
+    let scope = auth::scope(&mut conn).await?;
+
+    let mut script = Script::default();
+    for (name, value) in settings {
+        script.add(format!(
+            "CONFIGURE {scope} SET ext::auth::AuthConfig::{name} := {value}"
+        ));
+    }
+    if let Some(key) = signing_key {
+        script.add_secret(&key, |key| {
+            format!("CONFIGURE {scope} SET ext::auth::AuthConfig::auth_signing_key := {key}")
+        });
+    }
+    auth::apply(&mut conn, &cmd.apply, &script).await
+}
+
+/// Validates settings and converts them to EdgeQL values
+fn settings(cmd: &Command) -> anyhow::Result<Vec<(&'static str, String)>> {
+    let mut settings = Vec::new();
+    if let Some(name) = &cmd.app_name {
+        settings.push(("app_name", quote_string(name)));
+    }
+    if let Some(url) = &cmd.logo_url {
+        auth::validate_url(url)?;
+        settings.push(("logo_url", quote_string(url)));
+    }
+    if let Some(url) = &cmd.dark_logo_url {
+        auth::validate_url(url)?;
+        settings.push(("dark_logo_url", quote_string(url)));
+    }
+    if let Some(color) = &cmd.brand_color {
+        let color = color.trim_start_matches('#');
+        if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("brand color must be a hex RGB color, e.g. `1f8aed`");
+        }
+        settings.push(("brand_color", quote_string(color)));
+    }
+    if !cmd.allowed_redirect_url.is_empty() {
+        let urls = cmd
+            .allowed_redirect_url
+            .iter()
+            .map(|url| auth::validate_url(url).map(|_| quote_string(url)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        settings.push(("allowed_redirect_urls", format!("{{{}}}", urls.join(", "))));
+    }
+    if let Some(ttl) = &cmd.token_time_to_live {
+        settings.push(("token_time_to_live", auth::duration_literal(ttl)?));
+    }
+    Ok(settings)
+}
+
+fn generate_signing_key() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SIGNING_KEY_LENGTH)
+        .map(char::from)
+        .collect()
+}
@@ -0,0 +1,149 @@
+use edgeql_parser::helpers::quote_string;
+
+use crate::auth::{self, ApplyOptions, Script};
+use crate::commands::Options;
+use crate::tty_password;
+
+/// Name of the email provider created on servers with
+/// `cfg::SMTPProviderConfig` (6.0+)
+const PROVIDER_NAME: &str = "auth_smtp";
+
+/// Configure SMTP server used to send verification and sign in emails
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Address emails are sent from, e.g. `no-reply@example.com`
+    #[arg(long)]
+    pub sender: Option<String>,
+
+    /// Host name of the SMTP server
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Port of the SMTP server. Default depends on `--security`.
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// User name to authenticate with
+    #[arg(long)]
+    pub username: Option<String>,
+
+    /// Read password from stdin (asked interactively otherwise, if
+    /// `--username` is specified)
+    #[arg(long)]
+    pub password_from_stdin: bool,
+
+    /// Connection security
+    #[arg(long, value_enum, default_value = "starttls-or-plain-text")]
+    pub security: Security,
+
+    /// Do not validate TLS certificates of the server
+    #[arg(long)]
+    pub no_validate_certs: bool,
+
+    #[command(flatten)]
+    pub apply: ApplyOptions,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "kebab-case")]
+pub enum Security {
+    PlainText,
+    Tls,
+    Starttls,
+    StarttlsOrPlainText,
+}
+
+impl Security {
+    fn as_edgeql(&self) -> &'static str {
+        match self {
+            Security::PlainText => "PlainText",
+            Security::Tls => "TLS",
+            Security::Starttls => "STARTTLS",
+            Security::StarttlsOrPlainText => "STARTTLSOrPlainText",
+        }
+    }
+}
+
+pub async fn run(cmd: &Command, options: &Options) -> anyhow::Result<()> {
+    // ask all questions before connecting
+    let non_interactive = cmd.apply.non_interactive;
+    let sender = auth::required(&cmd.sender, "--sender", "Sender address", non_interactive)?;
+    if !sender.contains('@') {
+        anyhow::bail!("sender {sender:?} is not an email address");
+    }
+    let host = auth::required(&cmd.host, "--host", "SMTP host", non_interactive)?;
+    let password = if cmd.password_from_stdin {
+        Some(tty_password::read_stdin()?)
+    } else if cmd.username.is_some() && !non_interactive {
+        Some(tty_password::read("SMTP password: ")?)
+    } else {
+        None
+    };
+
+    let mut fields = vec![
+        ("sender", quote_string(&sender)),
+        ("host", quote_string(&host)),
+    ];
+    if let Some(port) = cmd.port {
+        fields.push(("port", format!("<int32>{port}")));
+    }
+    if let Some(username) = &cmd.username {
+        fields.push(("username", quote_string(username)));
+    }
+    fields.push(("validate_certs", (!cmd.no_validate_certs).to_string()));
+
+    let mut conn = options.conn_params.connect().await?;
+    auth::ensure_extension(&mut conn).await?;
+    let scope = auth::scope(&mut conn).await?;
+    let version = conn.get_version().await?.specific();
+
+    let mut script = Script::default();
+    if version.major >= 6 {
+        fields.insert(0, ("name", quote_string(PROVIDER_NAME)));
+        fields.push((
+            "security",
+            format!("<cfg::SMTPSecurity>'{}'", cmd.security.as_edgeql()),
+        ));
+        let insert = |password: Option<&str>| {
+            let mut items = fields
+                .iter()
+                .map(|(name, value)| format!("{name} := {value}"))
+                .collect::<Vec<_>>();
+            if let Some(password) = password {
+                items.push(format!("password := {password}"));
+            }
+            format!(
+                "CONFIGURE {scope} INSERT cfg::SMTPProviderConfig {{ {} }}",
+                items.join(", ")
+            )
+        };
+        script.add(format!(
+            "CONFIGURE {scope} RESET cfg::EmailProviderConfig FILTER .name = {}",
+            quote_string(PROVIDER_NAME),
+        ));
+        match &password {
+            Some(password) => script.add_secret(password, |p| insert(Some(p))),
+            None => script.add(insert(None)),
+        }
+        script.add(format!(
+            "CONFIGURE {scope} SET current_email_provider_name := {}",
+            quote_string(PROVIDER_NAME),
+        ));
+    } else {
+        fields.push((
+            "security",
+            format!("<ext::auth::SMTPSecurity>'{}'", cmd.security.as_edgeql()),
+        ));
+        for (name, value) in &fields {
+            script.add(format!(
+                "CONFIGURE {scope} SET ext::auth::SMTPConfig::{name} := {value}"
+            ));
+        }
+        if let Some(password) = &password {
+            script.add_secret(password, |p| {
+                format!("CONFIGURE {scope} SET ext::auth::SMTPConfig::password := {p}")
+            });
+        }
+    }
+    auth::apply(&mut conn, &cmd.apply, &script).await
+}
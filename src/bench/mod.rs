@@ -0,0 +1,198 @@
+use std::cell::Cell;
+use std::str;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use bytes::BytesMut;
+use futures_util::future::try_join_all;
+use gel_protocol::value::Value;
+use prettytable::{row, Table};
+use tokio::fs;
+use tokio::io::{self, AsyncRead};
+
+use edgeql_parser::preparser::is_empty;
+
+use crate::analyze;
+use crate::commands::parser::Bench;
+use crate::commands::Options;
+use crate::connect::Connection;
+use crate::print::msg;
+use crate::statement::{read_statement, EndOfFile};
+use crate::table;
+
+mod stats;
+
+use stats::Latency;
+
+#[derive(Debug, serde::Serialize)]
+struct Report {
+    query: String,
+    iterations: usize,
+    concurrency: usize,
+    /// Queries per second over the whole run
+    throughput: f64,
+    latency: Latency,
+    /// Execution time reported by `analyze`
+    server_time_ms: Option<f64>,
+}
+
+pub async fn command(cli: &mut Connection, options: &Options, cmd: &Bench) -> anyhow::Result<()> {
+    let queries = match (&cmd.query, &cmd.file) {
+        (Some(query), _) => vec![query.clone()],
+        (None, Some(path)) if path.to_str() == Some("-") => read_queries(&mut io::stdin()).await?,
+        (None, Some(path)) => {
+            let mut file = fs::File::open(path)
+                .await
+                .with_context(|| format!("cannot open {path:?}"))?;
+            read_queries(&mut file)
+                .await
+                .with_context(|| format!("cannot read queries from {path:?}"))?
+        }
+        (None, None) => anyhow::bail!("Query argument is required"),
+    };
+    if queries.is_empty() {
+        anyhow::bail!("No queries to benchmark");
+    }
+
+    let mut extra = Vec::with_capacity(cmd.concurrency.get() - 1);
+    for _ in 1..cmd.concurrency.get() {
+        extra.push(options.conn_params.connect().await?);
+    }
+
+    let mut reports = Vec::with_capacity(queries.len());
+    for query in &queries {
+        if !cmd.json {
+            msg!("Benchmarking: {}", query.trim());
+        }
+        let mut conns = Vec::with_capacity(cmd.concurrency.get());
+        conns.push(&mut *cli);
+        conns.extend(extra.iter_mut());
+        let (elapsed, samples) = run(&mut conns, query, cmd.warmup, cmd.iterations.get()).await?;
+        let server_time_ms = if cmd.no_server_time {
+            None
+        } else {
+            match analyze::server_time(cli, query).await {
+                Ok(time) => time,
+                Err(e) => {
+                    log::warn!("Cannot analyze query: {:#}", e);
+                    None
+                }
+            }
+        };
+        reports.push(Report {
+            query: query.trim().to_string(),
+            iterations: samples.len(),
+            concurrency: cmd.concurrency.get(),
+            throughput: samples.len() as f64 / elapsed.as_secs_f64(),
+            latency: Latency::from_samples(samples),
+            server_time_ms,
+        });
+    }
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        print_reports(&reports);
+    }
+    Ok(())
+}
+
+async fn read_queries(input: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<Vec<String>> {
+    let mut inbuf = BytesMut::with_capacity(8192);
+    let mut queries = Vec::new();
+    loop {
+        let stmt = match read_statement(&mut inbuf, input).await {
+            Ok(chunk) => chunk,
+            Err(e) if e.is::<EndOfFile>() => break,
+            Err(e) => return Err(e),
+        };
+        let stmt = str::from_utf8(&stmt[..]).context("can't decode statement")?;
+        if !is_empty(stmt) {
+            queries.push(stmt.to_string());
+        }
+    }
+    Ok(queries)
+}
+
+/// Runs `query` `iterations` times spread over all connections.
+///
+/// Returns total wall-clock time and latency of each run.
+async fn run(
+    conns: &mut [&mut Connection],
+    query: &str,
+    warmup: usize,
+    iterations: usize,
+) -> anyhow::Result<(Duration, Vec<Duration>)> {
+    let pending = Cell::new(warmup);
+    try_join_all(conns.iter_mut().map(|conn| worker(conn, query, &pending))).await?;
+
+    let pending = Cell::new(iterations);
+    let start = Instant::now();
+    let samples = try_join_all(conns.iter_mut().map(|conn| worker(conn, query, &pending))).await?;
+    let elapsed = start.elapsed();
+    Ok((elapsed, samples.into_iter().flatten().collect()))
+}
+
+async fn worker(
+    conn: &mut Connection,
+    query: &str,
+    pending: &Cell<usize>,
+) -> anyhow::Result<Vec<Duration>> {
+    let mut samples = Vec::new();
+    while pending.get() > 0 {
+        pending.set(pending.get() - 1);
+        let start = Instant::now();
+        conn.query::<Value, _>(query, &()).await?;
+        samples.push(start.elapsed());
+    }
+    Ok(samples)
+}
+
+fn print_reports(reports: &[Report]) {
+    let mut out = Table::new();
+    out.set_format(*table::FORMAT);
+    out.set_titles(row![
+        "Query",
+        "Runs",
+        "Queries/s",
+        "Min",
+        "Mean",
+        "p50",
+        "p90",
+        "p99",
+        "Max",
+        "Server"
+    ]);
+    for report in reports {
+        let lat = &report.latency;
+        let server = report
+            .server_time_ms
+            .map(|ms| format!("{ms:.2}"))
+            .unwrap_or_else(|| "-".into());
+        out.add_row(row![
+            truncate(&report.query),
+            report.iterations,
+            format!("{:.1}", report.throughput),
+            format!("{:.2}", lat.min_ms),
+            format!("{:.2}", lat.mean_ms),
+            format!("{:.2}", lat.p50_ms),
+            format!("{:.2}", lat.p90_ms),
+            format!("{:.2}", lat.p99_ms),
+            format!("{:.2}", lat.max_ms),
+            server,
+        ]);
+    }
+    out.printstd();
+    msg!("Times are in milliseconds.");
+}
+
+fn truncate(query: &str) -> String {
+    const MAX_LEN: usize = 40;
+    let line = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= MAX_LEN {
+        return line;
+    }
+    let mut short: String = line.chars().take(MAX_LEN - 3).collect();
+    short.push_str("...");
+    short
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+/// Latency distribution of a single benchmarked query.
+#[derive(Debug, serde::Serialize)]
+pub struct Latency {
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Nearest-rank percentile of sorted `samples`.
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Latency {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Latency {
+        assert!(!samples.is_empty());
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        Latency {
+            min_ms: millis(samples[0]),
+            mean_ms: millis(total) / samples.len() as f64,
+            p50_ms: millis(percentile(&samples, 50.0)),
+            p90_ms: millis(percentile(&samples, 90.0)),
+            p99_ms: millis(percentile(&samples, 99.0)),
+            max_ms: millis(samples[samples.len() - 1]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let samples = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        let latency = Latency::from_samples(samples);
+        assert_eq!(latency.min_ms, 1.0);
+        assert_eq!(latency.p50_ms, 50.0);
+        assert_eq!(latency.p90_ms, 90.0);
+        assert_eq!(latency.p99_ms, 99.0);
+        assert_eq!(latency.max_ms, 100.0);
+        assert_eq!(latency.mean_ms, 50.5);
+
+        let latency = Latency::from_samples(vec![Duration::from_millis(7)]);
+        assert_eq!(latency.p50_ms, 7.0);
+        assert_eq!(latency.p99_ms, 7.0);
+    }
+}
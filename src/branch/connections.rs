@@ -2,6 +2,7 @@ use std::ops::Deref;
 
 use crate::commands::Options;
 use crate::connect::{Connection, ConnectionError, Connector};
+use crate::hint::HintExt;
 use crate::print;
 use gel_errors::UnknownDatabaseError;
 use uuid::Uuid;
@@ -59,6 +60,35 @@ pub async fn connect_if_branch_exists(connector: &Connector) -> anyhow::Result<O
     }
 }
 
+/// Connects to the branch of the connector, failing with a list of the
+/// existing branches if there is no such branch
+pub async fn connect_to_existing_branch(connector: &Connector) -> anyhow::Result<Connection> {
+    if let Some(connection) = connect_if_branch_exists(connector).await? {
+        return Ok(connection);
+    }
+    let branch = connector.get()?.branch().to_string();
+    let mut default = connector.clone();
+    let branches: Vec<String> = match connect_if_branch_exists(default.branch("__default__")?).await
+    {
+        Ok(Some(mut connection)) => {
+            connection
+                .query(
+                    "SELECT (SELECT sys::Database FILTER NOT .builtin).name",
+                    &(),
+                )
+                .await?
+        }
+        Ok(None) | Err(_) => Vec::new(),
+    };
+    let err = anyhow::anyhow!("branch {branch:?} does not exist");
+    if branches.is_empty() {
+        return Err(err);
+    }
+    Err(err
+        .with_hint(|| format!("Available branches: {}", branches.join(", ")))
+        .into())
+}
+
 pub async fn get_connection_to_modify<'a>(
     branch: &str,
     options: &'a Options,
@@ -4,11 +4,9 @@ use crate::branding::BRANDING_CLOUD;
 use crate::commands::Options;
 use crate::connect::Connection;
 use crate::credentials;
-use crate::platform::tmp_file_path;
 use crate::portable::options::InstanceName;
 use crate::portable::project;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 pub struct Context {
@@ -84,6 +82,13 @@ impl Context {
         })
     }
 
+    /// Makes the branch of the connection the "current" one, for REPL
+    /// sessions that may have connected to a different branch than the
+    /// one recorded for the instance.
+    pub fn use_connection_branch(&mut self) {
+        self.current_branch = None;
+    }
+
     /// Returns the "current" branch. Connection must not have its branch param modified.
     pub async fn get_current_branch(&self, connection: &mut Connection) -> anyhow::Result<String> {
         if let Some(b) = &self.current_branch {
@@ -101,6 +106,10 @@ impl Context {
         Ok(connection.get_current_branch().await?.to_string())
     }
 
+    pub fn project_dir(&self) -> Option<&Path> {
+        self.project_dir.as_deref()
+    }
+
     pub fn can_update_current_branch(&self) -> bool {
         // we can update the current branch only if we know the instance, so we can write the credentials
         self.instance_name.is_some()
@@ -120,6 +129,15 @@ impl Context {
 
                 credentials::write_async(&path, &credentials).await?;
 
+                // the branch stored in the project takes precedence over
+                // credentials, so it must be kept in sync
+                if let Some(project_dir) = &self.project_dir {
+                    let stash_dir = get_stash_path(project_dir)?;
+                    if project::database_name(&stash_dir)?.is_some() {
+                        project::write_database_name(&stash_dir, branch)?;
+                    }
+                }
+
                 Ok(())
             }
             InstanceName::Cloud {
@@ -127,13 +145,8 @@ impl Context {
                 name: _name,
             } if self.project_dir.is_some() => {
                 // only place to store the branch is the database file in the project
-                let stash_path =
-                    get_stash_path(self.project_dir.as_ref().unwrap())?.join("database");
-
-                // ensure that the temp file is created in the same directory as the 'database' file
-                let tmp = tmp_file_path(&stash_path);
-                fs::write(&tmp, branch)?;
-                fs::rename(&tmp, &stash_path)?;
+                let stash_dir = get_stash_path(self.project_dir.as_ref().unwrap())?;
+                project::write_database_name(&stash_dir, branch)?;
 
                 Ok(())
             }
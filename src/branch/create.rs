@@ -1,13 +1,39 @@
+use std::path::PathBuf;
+
 use crate::branch::context::Context;
+use crate::branch::import::restore_file;
+use crate::commands::Options;
 use crate::connect::Connection;
-use crate::print;
+use crate::print::{self, msg};
 
 pub async fn run(
     cmd: &Command,
     context: &Context,
     connection: &mut Connection,
+    options: &Options,
 ) -> anyhow::Result<()> {
-    eprintln!("Creating branch '{}'...", cmd.name);
+    msg!("Creating branch '{}'...", cmd.name);
+
+    if let Some(dump) = &cmd.from_dump {
+        create_branch(connection, &cmd.name, "", true, false).await?;
+        let mut connector = options.conn_params.clone();
+        let restored = match connector.branch(&cmd.name)?.connect().await {
+            Ok(mut branch) => restore_file(&mut branch, options, dump).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = restored {
+            // don't leave a half-restored branch behind
+            let drop = format!(
+                "drop branch {}",
+                edgeql_parser::helpers::quote_name(&cmd.name)
+            );
+            if let Err(e) = connection.execute(&drop, &()).await {
+                log::warn!("Cannot drop branch {:?}: {:#}", cmd.name, e);
+            }
+            return Err(e);
+        }
+        return Ok(());
+    }
 
     let from = if let Some(from) = &cmd.from {
         from.clone()
@@ -36,6 +62,11 @@ pub struct Command {
     /// Copy data from the 'base' branch.
     #[arg(alias = "cp", long)]
     pub copy_data: bool,
+
+    /// Create the branch from a dump file, e.g. one written by
+    /// `branch export`.
+    #[arg(long, value_name = "file", conflicts_with_all = ["from", "empty", "copy_data"])]
+    pub from_dump: Option<PathBuf>,
 }
 
 pub async fn create_branch(
@@ -49,7 +80,7 @@ pub async fn create_branch(
 
     let query = if empty {
         if copy_data {
-            eprintln!("WARNING: when --empty is used, --copy-data will be ignored");
+            msg!("WARNING: when --empty is used, --copy-data will be ignored");
         }
 
         format!("create empty branch {new_branch}")
@@ -2,6 +2,7 @@ use termimad::crossterm::style::Stylize;
 
 use crate::branch::context::Context;
 use crate::connect::Connection;
+use crate::print::msg;
 
 pub async fn run(
     options: &Command,
@@ -13,7 +14,7 @@ pub async fn run(
     if options.plain {
         println!("{current_branch}");
     } else {
-        eprintln!("The current branch is '{}'", current_branch.green());
+        msg!("The current branch is '{}'", current_branch.green());
     }
     Ok(())
 }
@@ -1,18 +1,27 @@
+use anyhow::Context as _;
+
 use crate::branch::context::Context;
+use crate::branch::select::{self, Selection};
 use crate::branding::BRANDING_CLI_CMD;
 use crate::commands::ExitCode;
-use crate::connect::Connection;
+use crate::connect::{Connection, Connector};
 use crate::portable::exit_codes;
-use crate::{print, question};
+use crate::print::{self, msg, Highlight};
+use crate::question;
 
 pub async fn main(
     options: &Command,
     context: &Context,
     connection: &mut Connection,
+    connector: &Connector,
 ) -> anyhow::Result<()> {
     let current_branch = context.get_current_branch(connection).await?;
 
-    if current_branch == options.target_branch {
+    let Some(target_branch) = &options.target_branch else {
+        return drop_selected(options, &current_branch, connection, connector).await;
+    };
+
+    if current_branch == *target_branch {
         anyhow::bail!(
             "Dropping the currently active branch is not supported, please switch to a \
             different branch to drop this one with `{BRANDING_CLI_CMD} branch switch <branch>`"
@@ -21,8 +30,7 @@ pub async fn main(
 
     if !options.non_interactive {
         let q = question::Confirm::new_dangerous(format!(
-            "Do you really want to drop the branch {:?}?",
-            options.target_branch
+            "Do you really want to drop the branch {target_branch:?}?"
         ));
         if !connection.ping_while(q.async_ask()).await? {
             print::error!("Canceled by user.");
@@ -30,27 +38,85 @@ pub async fn main(
         }
     }
 
-    let mut statement = format!(
-        "drop branch {}",
-        edgeql_parser::helpers::quote_name(&options.target_branch)
-    );
+    let status = drop_branch(target_branch, options.force, connection).await?;
 
-    if options.force {
-        statement = format!("{} force", &statement);
-    }
+    print::completion(status);
 
-    let (status, _warnings) = connection.execute(&statement, &()).await?;
+    Ok(())
+}
 
-    print::completion(status);
+async fn drop_selected(
+    options: &Command,
+    current_branch: &str,
+    connection: &mut Connection,
+    connector: &Connector,
+) -> anyhow::Result<()> {
+    let mut branches = select::select(&options.selection, connection, connector).await?;
+    if branches.iter().any(|b| b == current_branch) {
+        msg!(
+            "Skipping the currently active branch {}.",
+            current_branch.emphasize()
+        );
+        branches.retain(|b| b != current_branch);
+    }
+    if branches.is_empty() {
+        msg!("No branches to drop.");
+        return Ok(());
+    }
 
+    if !options.non_interactive {
+        let prompt = format!("Do you really want to drop {} branches?", branches.len());
+        if !select::confirm(prompt, &branches, connection).await? {
+            print::error!("Canceled by user.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+
+    for (idx, branch) in branches.iter().enumerate() {
+        drop_branch(branch, options.force, connection)
+            .await
+            .with_context(|| {
+                format!(
+                    "cannot drop branch {branch:?}, {idx} of {} branches were dropped",
+                    branches.len()
+                )
+            })?;
+        msg!("Dropped {}", branch.emphasize());
+    }
+    print::success!("Dropped {} branches.", branches.len());
     Ok(())
 }
 
+async fn drop_branch(
+    branch: &str,
+    force: bool,
+    connection: &mut Connection,
+) -> anyhow::Result<bytes::Bytes> {
+    let mut statement = format!("drop branch {}", edgeql_parser::helpers::quote_name(branch));
+
+    if force {
+        statement = format!("{} force", &statement);
+    }
+
+    let (status, _warnings) = connection.execute(&statement, &()).await?;
+    Ok(status)
+}
+
 /// Drops an existing branch, removing it and its data.
+///
+/// Multiple branches can be dropped at once by selecting them with
+/// `--pattern`, `--all-except` or `--older-than` instead of naming one.
 #[derive(clap::Args, Debug, Clone)]
 pub struct Command {
     /// The branch to drop.
-    pub target_branch: String,
+    #[arg(
+        required_unless_present_any = ["pattern", "all_except", "older_than"],
+        conflicts_with_all = ["pattern", "all_except", "older_than"],
+    )]
+    pub target_branch: Option<String>,
+
+    #[command(flatten)]
+    pub selection: Selection,
 
     /// Drop the branch without asking for confirmation.
     #[arg(long)]
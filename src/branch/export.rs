@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use crate::branch::connections::connect_if_branch_exists;
+use crate::branch::context::Context;
+use crate::commands::{dump_db, Options};
+use crate::connect::{Connection, Connector};
+
+pub async fn run(
+    cmd: &Command,
+    context: &Context,
+    connection: &mut Connection,
+    connector: &mut Connector,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let branch = match &cmd.name {
+        Some(name) => name.clone(),
+        None => context.get_current_branch(connection).await?,
+    };
+    let Some(mut connection) = connect_if_branch_exists(connector.branch(&branch)?).await? else {
+        anyhow::bail!("Branch '{}' doesn't exist", branch)
+    };
+    dump_db(
+        &mut connection,
+        options,
+        &cmd.file,
+        cmd.include_secrets,
+        true,
+        None,
+    )
+    .await
+}
+
+/// Writes the schema and data of a branch into a dump file.
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// The branch to export. Defaults to the current branch.
+    pub name: Option<String>,
+
+    /// Path to the dump file. Use dash `-` to write to stdout.
+    #[arg(long, short = 'f', value_hint=clap::ValueHint::FilePath)]
+    pub file: PathBuf,
+
+    /// Include secret configuration variables in the dump.
+    #[arg(long)]
+    pub include_secrets: bool,
+}
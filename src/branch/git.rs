@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use tokio::process::Command;
+
+/// Returns the git branch checked out in `dir`.
+///
+/// Returns `None` if `dir` is not inside a git work tree or `HEAD` is
+/// detached.
+pub async fn current_branch(dir: &Path) -> anyhow::Result<Option<String>> {
+    let Some(output) = git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"]).await? else {
+        return Ok(None);
+    };
+    let branch = output.trim();
+    if branch.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(branch.to_string()))
+}
+
+/// Converts a git branch name into a database branch name.
+///
+/// Characters other than letters, digits, `_` and `-` (such as `/` in
+/// `feature/x`) are replaced by `_`, and leading underscores are dropped
+/// because names starting with `__` are reserved. Returns `None` if
+/// nothing is left.
+pub fn branch_name(git_branch: &str) -> Option<String> {
+    let name = git_branch
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let name = name.trim_start_matches('_');
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Path to the `post-checkout` hook of the repository containing `dir`.
+pub async fn post_checkout_hook(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let Some(output) = git(dir, &["rev-parse", "--git-path", "hooks/post-checkout"]).await? else {
+        return Ok(None);
+    };
+    Ok(Some(dir.join(output.trim())))
+}
+
+async fn git(dir: &Path, args: &[&str]) -> anyhow::Result<Option<String>> {
+    let output = match Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!("`git` executable not found in PATH")
+        }
+        Err(e) => return Err(e).context("cannot run git"),
+    };
+    if !output.status.success() {
+        log::debug!(
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return Ok(None);
+    }
+    let text = String::from_utf8(output.stdout).context("git produced invalid utf-8")?;
+    Ok(Some(text))
+}
+
+#[test]
+fn sanitize_branch_name() {
+    assert_eq!(branch_name("main").as_deref(), Some("main"));
+    assert_eq!(branch_name("fix-123").as_deref(), Some("fix-123"));
+    assert_eq!(branch_name("feature/x").as_deref(), Some("feature_x"));
+    assert_eq!(
+        branch_name("user/feature.v2@{1}").as_deref(),
+        Some("user_feature_v2__1_")
+    );
+    assert_eq!(branch_name("__x").as_deref(), Some("x"));
+    assert_eq!(branch_name("/"), None);
+}
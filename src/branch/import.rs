@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use crate::branch::connections::connect_if_branch_exists;
+use crate::branch::context::Context;
+use crate::commands::parser::Restore as RestoreCmd;
+use crate::commands::{restore_db, Options};
+use crate::connect::{Connection, Connector};
+use crate::hint::HintExt;
+
+pub async fn run(
+    cmd: &Command,
+    _context: &Context,
+    connector: &mut Connector,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let Some(mut connection) = connect_if_branch_exists(connector.branch(&cmd.name)?).await? else {
+        return Err(anyhow::anyhow!("Branch '{}' doesn't exist", cmd.name)
+            .with_hint(|| {
+                format!(
+                    "Use `branch create {} --from-dump {}` to create it.",
+                    cmd.name,
+                    cmd.file.display()
+                )
+            })
+            .into());
+    };
+    restore_file(&mut connection, options, &cmd.file).await
+}
+
+/// Restores a dump of a single branch into the branch of the connection
+pub async fn restore_file(
+    connection: &mut Connection,
+    options: &Options,
+    file: &Path,
+) -> anyhow::Result<()> {
+    let params = RestoreCmd {
+        conn: None,
+        path: file.into(),
+        all: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        force_overwrite: false,
+        into_new_branch: None,
+        non_interactive: false,
+        verbose: false,
+    };
+    restore_db(connection, options, &params).await
+}
+
+/// Restores a dump file, created by `branch export` or `dump`, into an
+/// existing empty branch.
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// The branch to import into.
+    pub name: String,
+
+    /// Path to the dump file. Use dash `-` to read from stdin.
+    #[arg(long, short = 'f', value_hint=clap::ValueHint::FilePath)]
+    pub file: PathBuf,
+}
@@ -2,10 +2,12 @@ use crate::branch::connections::connect_if_branch_exists;
 use crate::branch::context::Context;
 use crate::commands::Options;
 use crate::connect::Connection;
+use crate::hooks::{self, Action};
 use crate::migrations;
 use crate::migrations::merge::{
     apply_merge_migration_files, get_merge_migrations, write_merge_migrations,
 };
+use crate::print::msg;
 
 pub async fn main(
     cmd: &Command,
@@ -33,8 +35,15 @@ pub async fn main(
     let migration_context = migrations::Context::for_project(&project)?;
     let mut merge_migrations =
         get_merge_migrations(source_connection, &mut target_connection).await?;
+    let merged = merge_migrations
+        .target_migrations
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    let hooks = migration_context.hooks.as_ref();
+    hooks::run(hooks, Action::BranchMergeBefore, source_connection, &merged).await?;
 
-    eprintln!(
+    msg!(
         "Merging {} migration(s) into '{}'...",
         merge_migrations.target_migrations.len(),
         source_connection.database()
@@ -43,12 +52,14 @@ pub async fn main(
     write_merge_migrations(&migration_context, &mut merge_migrations).await?;
 
     if !cmd.no_apply {
-        eprintln!("Applying migrations...");
+        msg!("Applying migrations...");
         apply_merge_migration_files(&merge_migrations, &migration_context, source_connection)
             .await?;
     }
 
-    eprintln!("Done!");
+    hooks::run(hooks, Action::BranchMergeAfter, source_connection, &merged).await?;
+
+    msg!("Done!");
 
     Ok(())
 }
@@ -1,12 +1,16 @@
-mod connections;
+pub mod connections;
 pub mod context;
 pub mod create;
 pub mod current;
 pub mod drop;
+pub mod export;
+mod git;
+pub mod import;
 pub mod list;
 pub mod merge;
 pub mod rebase;
 pub mod rename;
+pub mod select;
 pub mod switch;
 pub mod wipe;
 
@@ -26,13 +30,18 @@ pub async fn do_run(
     options: &Options,
     connection: Option<&mut Connection>,
 ) -> anyhow::Result<CommandResult> {
-    let context = context::Context::new(options).await?;
+    let mut context = context::Context::new(options).await?;
+    if connection.is_some() {
+        context.use_connection_branch();
+    }
 
     let mut connector: Connector = options.conn_params.clone();
 
     // commands that don't need existing connection
     match &cmd {
-        Subcommand::Switch(switch) => return switch::run(switch, &context, &mut connector).await,
+        Subcommand::Switch(switch) => {
+            return switch::run(switch, &context, &mut connector, connection).await
+        }
         Subcommand::Wipe(wipe) => {
             wipe::main(wipe, &context, &mut connector).await?;
             return Ok(CommandResult::default());
@@ -53,12 +62,16 @@ pub async fn do_run(
 
     match cmd {
         Subcommand::Current(cmd) => current::run(cmd, &context, conn_ref).await?,
-        Subcommand::Create(cmd) => create::run(cmd, &context, conn_ref).await?,
-        Subcommand::Drop(cmd) => drop::main(cmd, &context, conn_ref).await?,
+        Subcommand::Create(cmd) => create::run(cmd, &context, conn_ref, options).await?,
+        Subcommand::Drop(cmd) => drop::main(cmd, &context, conn_ref, &connector).await?,
         Subcommand::List(cmd) => list::main(cmd, &context, conn_ref).await?,
         Subcommand::Rename(cmd) => return rename::run(cmd, &context, conn_ref, options).await,
         Subcommand::Rebase(cmd) => rebase::main(cmd, &context, conn_ref, options).await?,
         Subcommand::Merge(cmd) => merge::main(cmd, &context, conn_ref, options).await?,
+        Subcommand::Export(cmd) => {
+            export::run(cmd, &context, conn_ref, &mut connector, options).await?
+        }
+        Subcommand::Import(cmd) => import::run(cmd, &context, &mut connector, options).await?,
 
         // handled earlier
         Subcommand::Switch(_) | Subcommand::Wipe(_) => unreachable!(),
@@ -92,6 +105,8 @@ pub enum Subcommand {
     Rename(rename::Command),
     Drop(drop::Command),
     Wipe(wipe::Command),
+    Export(export::Command),
+    Import(import::Command),
 }
 
 pub async fn verify_server_can_use_branches(connection: &mut Connection) -> anyhow::Result<()> {
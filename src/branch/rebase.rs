@@ -8,6 +8,7 @@ use crate::migrations::rebase::{
     do_rebase, get_diverging_migrations, write_rebased_migration_files,
 };
 use crate::portable::project;
+use crate::print::msg;
 use crate::{migrations, print};
 use uuid::Uuid;
 
@@ -46,7 +47,7 @@ pub async fn main(
         Err(e) => {
             print::error!("{e}");
 
-            eprintln!("Cleaning up cloned branch...");
+            msg!("Cleaning up cloned branch...");
             let mut rename_connection =
                 get_connection_to_modify(&temp_branch, cli_opts, source_connection).await?;
             let (status, _warnings) = rename_connection
@@ -101,7 +102,7 @@ async fn rebase(
     }
 
     // drop source branch
-    eprintln!("\nReplacing '{current_branch}' with rebased version...");
+    msg!("\nReplacing '{current_branch}' with rebased version...");
     let (status, _warnings) = target_connection
         .execute(
             &format!(
@@ -114,7 +115,7 @@ async fn rebase(
     print::completion(status);
     rename_temp_to_source(branch, current_branch, cli_opts, target_connection).await?;
 
-    eprintln!("Done!");
+    msg!("Done!");
     anyhow::Ok(())
 }
 
@@ -146,7 +147,7 @@ async fn rename_temp_to_source(
 }
 
 async fn clone_target_branch(branch: &str, connection: &mut Connection) -> anyhow::Result<String> {
-    eprintln!("Cloning target branch '{}' for rebase...", branch.green());
+    msg!("Cloning target branch '{}' for rebase...", branch.green());
 
     let temp_branch_name = Uuid::new_v4().to_string();
 
@@ -3,7 +3,7 @@ use crate::branch::connections::get_connection_to_modify;
 use crate::branch::context::Context;
 use crate::commands::Options;
 use crate::connect::Connection;
-use crate::print;
+use crate::print::{self, msg};
 
 pub async fn run(
     options: &Command,
@@ -23,9 +23,10 @@ pub async fn run(
         rename(connection, options).await?;
     }
 
-    eprintln!(
+    msg!(
         "Renamed branch {} to {}",
-        options.old_name, options.new_name
+        options.old_name,
+        options.new_name
     );
 
     if connection.database() == options.old_name {
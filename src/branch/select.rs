@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::branch::connections::connect_if_branch_exists;
+use crate::connect::{Connection, Connector};
+use crate::print::{msg, Highlight};
+use crate::question;
+
+/// Options selecting multiple branches, used by `drop` and `wipe`
+#[derive(clap::Args, Debug, Clone)]
+pub struct Selection {
+    /// Select branches with names matching the pattern, where `*` matches
+    /// any characters and `?` a single one, e.g. `ci-*`.
+    #[arg(long, value_name = "pattern")]
+    pub pattern: Option<String>,
+
+    /// Select all branches except the given one. Can be repeated.
+    #[arg(long, value_name = "branch")]
+    pub all_except: Vec<String>,
+
+    /// Select branches with no migrations applied for at least the given
+    /// time, e.g. `30d`. Requires a server that records migration times.
+    #[arg(long, value_name = "duration", value_parser = humantime::parse_duration)]
+    pub older_than: Option<Duration>,
+}
+
+/// Returns the branches matching all the criteria of the selection
+pub async fn select(
+    selection: &Selection,
+    connection: &mut Connection,
+    connector: &Connector,
+) -> anyhow::Result<Vec<String>> {
+    let mut branches: Vec<String> = connection
+        .query(
+            "SELECT (SELECT sys::Database FILTER NOT .builtin).name",
+            &(),
+        )
+        .await?;
+    if let Some(pattern) = &selection.pattern {
+        branches.retain(|b| matches_pattern(pattern, b));
+    }
+    branches.retain(|b| !selection.all_except.contains(b));
+
+    if let Some(older_than) = selection.older_than {
+        let has_times: bool = connection
+            .query_required_single(
+                "SELECT EXISTS (
+                    SELECT schema::ObjectType
+                    FILTER .name = 'schema::Migration'
+                        AND 'created_at' IN .pointers.name
+                )",
+                &(),
+            )
+            .await?;
+        if !has_times {
+            anyhow::bail!(
+                "`--older-than` is not supported by server version {}, \
+                 as it doesn't record the time migrations are applied",
+                connection.get_version().await?
+            );
+        }
+        let mut selected = Vec::with_capacity(branches.len());
+        for branch in branches {
+            match last_migration_age(&branch, connector).await? {
+                Some(age) if age >= older_than => selected.push(branch),
+                Some(_) => {}
+                None => msg!(
+                    "Skipping branch {}: it has no migrations to tell its age.",
+                    branch.emphasize()
+                ),
+            }
+        }
+        branches = selected;
+    }
+    Ok(branches)
+}
+
+async fn last_migration_age(
+    branch: &str,
+    connector: &Connector,
+) -> anyhow::Result<Option<Duration>> {
+    let mut connector = connector.clone();
+    let Some(mut connection) = connect_if_branch_exists(connector.branch(branch)?).await? else {
+        // dropped concurrently
+        return Ok(None);
+    };
+    let (seconds, _) = connection
+        .query_single::<f64, _>(
+            "SELECT duration_get(
+                datetime_of_statement() - max(schema::Migration.created_at),
+                'totalseconds'
+            )",
+            &(),
+        )
+        .await?;
+    Ok(seconds.map(|s| Duration::from_secs_f64(s.max(0.0))))
+}
+
+/// Lists the selected branches and asks the prompt to confirm the action
+pub async fn confirm(
+    prompt: String,
+    branches: &[String],
+    connection: &mut Connection,
+) -> anyhow::Result<bool> {
+    msg!("Selected branches:");
+    for branch in branches {
+        msg!("  {branch}");
+    }
+    let q = question::Confirm::new_dangerous(prompt);
+    connection.ping_while(q.async_ask()).await
+}
+
+/// Matches a name against a pattern with `*` and `?` wildcards
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` and of the name when it was reached
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[test]
+fn patterns() {
+    assert!(matches_pattern("ci-*", "ci-1234"));
+    assert!(matches_pattern("ci-*", "ci-"));
+    assert!(!matches_pattern("ci-*", "main"));
+    assert!(matches_pattern("*-tmp", "feature-tmp"));
+    assert!(!matches_pattern("*-tmp", "feature-tmp2"));
+    assert!(matches_pattern("pr-?", "pr-1"));
+    assert!(!matches_pattern("pr-?", "pr-12"));
+    assert!(matches_pattern("a*b*c", "aXbYbZc"));
+    assert!(matches_pattern("main", "main"));
+    assert!(!matches_pattern("main", "main2"));
+}
@@ -1,86 +1,232 @@
+use std::env;
+use std::fs;
+
 use crate::branch;
 use crate::branch::connections::connect_if_branch_exists;
 use crate::branch::context::Context;
 use crate::branch::create::create_branch;
-use crate::connect::Connector;
+use crate::branch::git;
+use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
+use crate::connect::{Connection, Connector};
+use crate::hint::HintExt;
+use crate::hooks::{self, Action, Env, Hooks};
+use crate::print::{self, msg};
 
+/// Switches the current branch of the instance.
+///
+/// With `session` (the connection of a REPL) the existing connection is
+/// used, and if the instance is unknown only the session is switched.
 pub async fn run(
     options: &Command,
     context: &Context,
     connector: &mut Connector,
+    session: Option<&mut Connection>,
 ) -> anyhow::Result<branch::CommandResult> {
-    if !context.can_update_current_branch() {
-        eprintln!("Cannot switch branches without specifying the instance");
-        eprintln!("Either change directory to a project with a linked instance or use --instance argument.");
+    if !context.can_update_current_branch() && session.is_none() {
+        msg!("Cannot switch branches without specifying the instance");
+        msg!("Either change directory to a project with a linked instance or use --instance argument.");
         anyhow::bail!("");
     }
 
-    let current_branch = if let Some(mut connection) = connect_if_branch_exists(connector).await? {
-        let current_branch = context.get_current_branch(&mut connection).await?;
-        if current_branch == options.target_branch {
-            anyhow::bail!("Already on '{}'", options.target_branch);
+    let (target_branch, from_git) = resolve_target_branch(options, context).await?;
+    // branches derived from git are created on demand
+    let create = options.create || from_git;
+    if !create && (options.empty || options.copy_data || options.from.is_some()) {
+        anyhow::bail!("`--empty`, `--copy-data` and `--from` can only be used with `--create`");
+    }
+
+    let mut own_connection = None;
+    let current_branch_connection = match session {
+        Some(connection) => Some(connection),
+        None => {
+            own_connection = connect_if_branch_exists(connector).await?;
+            own_connection.as_mut()
         }
+    };
+    // `Some(connection)` to the current branch if the target branch must be
+    // created from it
+    let (current_branch, instance, create_from) =
+        if let Some(connection) = current_branch_connection {
+            let current_branch = context.get_current_branch(connection).await?;
+            if current_branch == target_branch {
+                if from_git {
+                    msg!("Already on '{target_branch}'");
+                    return Ok(branch::CommandResult::default());
+                }
+                anyhow::bail!("Already on '{}'", target_branch);
+            }
 
-        branch::verify_server_can_use_branches(&mut connection).await?;
+            branch::verify_server_can_use_branches(connection).await?;
 
-        // verify the branch exists
-        let branches: Vec<String> = connection
-            .query(
-                "SELECT (SELECT sys::Database FILTER NOT .builtin).name",
-                &(),
-            )
-            .await?;
-
-        if !branches.contains(&options.target_branch) {
-            if options.create {
-                eprintln!("Creating '{}'...", &options.target_branch);
-                create_branch(
-                    &mut connection,
-                    &options.target_branch,
-                    options.from.as_ref().unwrap_or(&current_branch),
-                    options.empty,
-                    options.copy_data,
+            // verify the branch exists
+            let branches: Vec<String> = connection
+                .query(
+                    "SELECT (SELECT sys::Database FILTER NOT .builtin).name",
+                    &(),
                 )
                 .await?;
+
+            let instance = connection.instance_name().map(|n| n.to_string());
+            if branches.contains(&target_branch) {
+                (current_branch, instance, None)
+            } else if create {
+                (current_branch, instance, Some(connection))
             } else {
-                anyhow::bail!("Branch '{}' doesn't exist", options.target_branch)
+                anyhow::bail!("Branch '{}' doesn't exist", target_branch)
             }
-        }
-        current_branch
-    } else {
-        // try to connect to the target branch
-        let target_branch_connector = connector.branch(&options.target_branch)?;
-        match connect_if_branch_exists(target_branch_connector).await? {
-            Some(mut connection) => {
-                branch::verify_server_can_use_branches(&mut connection).await?;
+        } else {
+            // try to connect to the target branch
+            let target_branch_connector = connector.branch(&target_branch)?;
+            match connect_if_branch_exists(target_branch_connector).await? {
+                Some(mut connection) => {
+                    branch::verify_server_can_use_branches(&mut connection).await?;
 
-                context.get_current_branch(&mut connection).await?
+                    let instance = connection.instance_name().map(|n| n.to_string());
+                    let current_branch = context.get_current_branch(&mut connection).await?;
+                    (current_branch, instance, None)
+                }
+                None => anyhow::bail!("The target branch doesn't exist."),
             }
-            None => anyhow::bail!("The target branch doesn't exist."),
-        }
-    };
+        };
 
-    eprintln!(
-        "Switching from '{}' to '{}'",
-        current_branch, options.target_branch
-    );
+    let project = context.get_project().await?;
+    let hooks = project.as_ref().and_then(Hooks::for_project);
+    hooks::run_with_env(
+        hooks.as_ref(),
+        Action::BranchSwitchBefore,
+        &hook_env(&current_branch, instance.as_deref()),
+    )?;
 
-    context
-        .update_current_branch(&options.target_branch)
+    if let Some(connection) = create_from {
+        msg!("Creating '{}'...", &target_branch);
+        create_branch(
+            connection,
+            &target_branch,
+            options.from.as_ref().unwrap_or(&current_branch),
+            options.empty,
+            options.copy_data,
+        )
         .await?;
+    }
+
+    msg!("Switching from '{}' to '{}'", current_branch, target_branch);
+
+    if context.can_update_current_branch() {
+        context.update_current_branch(&target_branch).await?;
+    } else {
+        msg!(
+            "Instance is unknown, so only this session is switched; \
+             its default branch is unchanged"
+        );
+    }
+
+    hooks::run_with_env(
+        hooks.as_ref(),
+        Action::BranchSwitchAfter,
+        &hook_env(&target_branch, instance.as_deref()),
+    )?;
+
+    if from_git {
+        suggest_hook(context).await;
+    }
 
     Ok(branch::CommandResult {
-        new_branch: Some(options.target_branch.clone()),
+        new_branch: Some(target_branch),
     })
 }
 
+fn hook_env<'a>(branch: &'a str, instance: Option<&'a str>) -> Env<'a> {
+    Env {
+        branch: Some(branch),
+        instance,
+        migrations: &[],
+    }
+}
+
+/// Returns the name of the branch to switch to and whether it was derived
+/// from the current git branch.
+async fn resolve_target_branch(
+    options: &Command,
+    context: &Context,
+) -> anyhow::Result<(String, bool)> {
+    if let Some(target_branch) = &options.target_branch {
+        return Ok((target_branch.clone(), false));
+    }
+    let from_git = options.from_git
+        || context
+            .get_project()
+            .await?
+            .map(|p| p.manifest.project().branch_from_git)
+            .unwrap_or(false);
+    if !from_git {
+        return Err(anyhow::anyhow!("Target branch is required").with_hint(|| {
+            format!(
+                "Specify the branch name, pass `--from-git` or set \
+                 `branch-from-git = true` in the [project] table of \
+                 {MANIFEST_FILE_DISPLAY_NAME}"
+            )
+        }))?;
+    }
+
+    let dir = match context.project_dir() {
+        Some(dir) => dir.to_owned(),
+        None => env::current_dir()?,
+    };
+    let Some(git_branch) = git::current_branch(&dir).await? else {
+        anyhow::bail!(
+            "Cannot determine the current git branch in {}: \
+             not a git repository or HEAD is detached",
+            dir.display()
+        );
+    };
+    let Some(target_branch) = git::branch_name(&git_branch) else {
+        return Err(anyhow::anyhow!(
+            "Git branch {git_branch:?} cannot be used as a branch name"
+        ))
+        .hint("Specify the branch name explicitly")?;
+    };
+    if target_branch != git_branch {
+        msg!("Using branch '{target_branch}' for git branch '{git_branch}'");
+    }
+    Ok((target_branch, true))
+}
+
+async fn suggest_hook(context: &Context) {
+    let dir = match context.project_dir() {
+        Some(dir) => dir.to_owned(),
+        None => match env::current_dir() {
+            Ok(dir) => dir,
+            Err(_) => return,
+        },
+    };
+    let Ok(Some(hook)) = git::post_checkout_hook(&dir).await else {
+        return;
+    };
+    if fs::read_to_string(&hook).map_or(false, |text| text.contains("--from-git")) {
+        return;
+    }
+    print::warn!(
+        "To switch branches automatically on `git checkout`, \
+         add the following to {}:\n    {BRANDING_CLI_CMD} branch switch --from-git",
+        hook.display()
+    );
+}
+
 /// Switch the current branch.
 #[derive(clap::Args, Debug, Clone)]
 pub struct Command {
     /// The branch to switch to.
-    pub target_branch: String,
+    #[arg(conflicts_with = "from_git")]
+    pub target_branch: Option<String>,
+
+    /// Use the name of the current git branch as the target branch,
+    /// creating it if it doesn't exist. Enabled by default when
+    /// `branch-from-git = true` is set in the project manifest.
+    #[arg(long)]
+    pub from_git: bool,
 
-    /// Create the branch if it doesn't exist.
+    /// Create the branch if it doesn't exist. Hooks of `branch.switch` are
+    /// run once around both creating and switching.
     #[arg(short = 'c', long)]
     pub create: bool,
 
@@ -1,19 +1,28 @@
+use anyhow::Context as _;
+use bytes::Bytes;
+
 use crate::branch::connections::connect_if_branch_exists;
 use crate::branch::context::Context;
+use crate::branch::select::{self, Selection};
 use crate::commands::ExitCode;
-use crate::connect::Connector;
+use crate::connect::{Connection, Connector};
 use crate::portable::exit_codes;
-use crate::{print, question};
+use crate::print::{self, msg, Highlight};
+use crate::question;
 
 pub async fn main(
     cmd: &Command,
     _context: &Context,
     connector: &mut Connector,
 ) -> anyhow::Result<()> {
-    let connection = connect_if_branch_exists(connector.branch(&cmd.target_branch)?).await?;
+    let Some(target_branch) = &cmd.target_branch else {
+        return wipe_selected(cmd, connector).await;
+    };
+
+    let connection = connect_if_branch_exists(connector.branch(target_branch)?).await?;
 
     if connection.is_none() {
-        anyhow::bail!("Branch '{}' doesn't exist", &cmd.target_branch)
+        anyhow::bail!("Branch '{}' doesn't exist", target_branch)
     }
 
     let mut connection = connection.unwrap();
@@ -22,7 +31,7 @@ pub async fn main(
         let q = question::Confirm::new_dangerous(format!(
             "Do you really want to wipe \
                     the contents of the branch {:?}?",
-            cmd.target_branch
+            target_branch
         ));
         if !connection.ping_while(q.async_ask()).await? {
             print::error!("Canceled by user.");
@@ -30,18 +39,75 @@ pub async fn main(
         }
     }
 
-    let (status, _warnings) = connection.execute("RESET SCHEMA TO initial", &()).await?;
+    let status = reset(&mut connection).await?;
 
     print::completion(status);
 
     Ok(())
 }
 
+/// Removes the schema and all data of the connected branch
+pub async fn reset(connection: &mut Connection) -> anyhow::Result<Bytes> {
+    let (status, _warnings) = connection.execute("RESET SCHEMA TO initial", &()).await?;
+    Ok(status)
+}
+
+async fn wipe_selected(cmd: &Command, connector: &Connector) -> anyhow::Result<()> {
+    let mut connection = connector.connect().await?;
+    let branches = select::select(&cmd.selection, &mut connection, connector).await?;
+    if branches.is_empty() {
+        msg!("No branches to wipe.");
+        return Ok(());
+    }
+
+    if !cmd.non_interactive {
+        let prompt = format!(
+            "Do you really want to wipe the contents of {} branches?",
+            branches.len()
+        );
+        if !select::confirm(prompt, &branches, &mut connection).await? {
+            print::error!("Canceled by user.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+
+    for (idx, branch) in branches.iter().enumerate() {
+        wipe_branch(branch, connector).await.with_context(|| {
+            format!(
+                "cannot wipe branch {branch:?}, {idx} of {} branches were wiped",
+                branches.len()
+            )
+        })?;
+        msg!("Wiped {}", branch.emphasize());
+    }
+    print::success!("Wiped {} branches.", branches.len());
+    Ok(())
+}
+
+async fn wipe_branch(branch: &str, connector: &Connector) -> anyhow::Result<()> {
+    let mut connector = connector.clone();
+    let Some(mut connection) = connect_if_branch_exists(connector.branch(branch)?).await? else {
+        anyhow::bail!("Branch '{}' doesn't exist", branch);
+    };
+    reset(&mut connection).await?;
+    Ok(())
+}
+
 /// Wipes all data within a branch.
+///
+/// Multiple branches can be wiped at once by selecting them with
+/// `--pattern`, `--all-except` or `--older-than` instead of naming one.
 #[derive(clap::Args, Debug, Clone)]
 pub struct Command {
     /// The branch to wipe.
-    pub target_branch: String,
+    #[arg(
+        required_unless_present_any = ["pattern", "all_except", "older_than"],
+        conflicts_with_all = ["pattern", "all_except", "older_than"],
+    )]
+    pub target_branch: Option<String>,
+
+    #[command(flatten)]
+    pub selection: Selection,
 
     /// Wipe without asking for confirmation.
     #[arg(long)]
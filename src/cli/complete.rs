@@ -0,0 +1,131 @@
+//! Dynamic values for shell completions
+//!
+//! Generated completion scripts call the hidden `_complete` subcommand to
+//! get instance and branch names, which can't be known at generation time.
+//! Errors are never reported: completion just yields no candidates.
+
+use std::time::Duration;
+
+use crate::commands::get_databases;
+use crate::credentials;
+use crate::options::Options;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Complete {
+    /// Kind of values to print, one per line
+    #[arg(value_enum)]
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Kind {
+    /// Names of the local and linked instances
+    Instance,
+    /// Branches of the instance the CLI connects to
+    Branch,
+}
+
+pub fn run(cmd: &Complete, options: &Options) -> anyhow::Result<()> {
+    let values = match cmd.kind {
+        Kind::Instance => instance_names(),
+        Kind::Branch => branch_names(options),
+    };
+    match values {
+        Ok(values) => {
+            for value in values {
+                println!("{value}");
+            }
+        }
+        Err(e) => log::debug!("Cannot complete {:?}: {:#}", cmd.kind, e),
+    }
+    Ok(())
+}
+
+fn instance_names() -> anyhow::Result<Vec<String>> {
+    Ok(credentials::all_instance_names()?.into_iter().collect())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn branch_names(options: &Options) -> anyhow::Result<Vec<String>> {
+    let connector = options.create_connector().await?;
+    let mut cli = tokio::time::timeout(CONNECT_TIMEOUT, connector.connect()).await??;
+    get_databases(&mut cli).await
+}
+
+pub const BASH_DYNAMIC: &str = r#"
+_edgedb_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$prev" in
+        -I|--instance)
+            COMPREPLY=($(compgen -W "$(edgedb _complete instance 2>/dev/null)" -- "$cur"))
+            return 0
+            ;;
+    esac
+    if [[ $COMP_CWORD -eq 3 && "${COMP_WORDS[1]}" == branch ]]; then
+        case "${COMP_WORDS[2]}" in
+            switch|merge|rebase|drop)
+                COMPREPLY=($(compgen -W "$(edgedb _complete branch 2>/dev/null)" -- "$cur"))
+                return 0
+                ;;
+        esac
+    fi
+    _edgedb "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _edgedb_dynamic -o nosort -o bashdefault -o default edgedb
+else
+    complete -F _edgedb_dynamic -o bashdefault -o default edgedb
+fi
+"#;
+
+pub const ZSH_DYNAMIC: &str = r#"
+_edgedb_dynamic() {
+    local -a values
+    if [[ "${words[CURRENT-1]}" == (-I|--instance) ]]; then
+        values=(${(f)"$(edgedb _complete instance 2>/dev/null)"})
+        compadd -a values
+    elif [[ $CURRENT -eq 4 && "${words[2]}" == branch
+            && "${words[3]}" == (switch|merge|rebase|drop) ]]; then
+        values=(${(f)"$(edgedb _complete branch 2>/dev/null)"})
+        compadd -a values
+    else
+        _edgedb "$@"
+    fi
+}
+
+"#;
+
+pub const FISH_DYNAMIC: &str = r#"
+complete -c edgedb -s I -l instance -x -a '(edgedb _complete instance 2>/dev/null)'
+complete -c edgedb -n '__fish_seen_subcommand_from branch; and __fish_seen_subcommand_from switch merge rebase drop' -f -a '(edgedb _complete branch 2>/dev/null)'
+"#;
+
+/// Hooks dynamic completion into the script generated by `clap_complete`
+pub fn zsh_dynamic(script: &str) -> String {
+    const DISPATCH: &str = "if [ \"$funcstack[1]\" = \"_edgedb\" ]; then";
+    match script.rfind(DISPATCH) {
+        Some(pos) => {
+            let tail = script[pos..]
+                .replace("_edgedb \"$@\"", "_edgedb_dynamic \"$@\"")
+                .replace("compdef _edgedb edgedb", "compdef _edgedb_dynamic edgedb");
+            format!("{}{ZSH_DYNAMIC}{tail}", &script[..pos])
+        }
+        None => format!("{script}{ZSH_DYNAMIC}compdef _edgedb_dynamic edgedb\n"),
+    }
+}
+
+#[test]
+fn zsh_dispatch() {
+    let script = "#compdef edgedb\n_edgedb() {\n}\n\
+        if [ \"$funcstack[1]\" = \"_edgedb\" ]; then\n    \
+            _edgedb \"$@\"\nelse\n    compdef _edgedb edgedb\nfi\n";
+    let result = zsh_dynamic(script);
+    assert!(result.contains("    _edgedb_dynamic \"$@\"\nelse"));
+    assert!(result.contains("compdef _edgedb_dynamic edgedb\nfi\n"));
+    assert!(result.find("_edgedb_dynamic() {") < result.find("if [ \"$funcstack"));
+}
@@ -34,6 +34,12 @@ define_env! {
     #[env(GEL_CLOUD_SECRET_KEY, EDGEDB_CLOUD_SECRET_KEY)]
     cloud_secret_key: String,
 
+    /// Where passwords and Cloud secret keys are stored: `file` (default)
+    /// or `keychain`. Existing secrets are moved to the keychain with
+    /// `cli migrate-credentials`.
+    #[env(GEL_CREDENTIAL_STORE, EDGEDB_CREDENTIAL_STORE)]
+    credential_store: CredentialStore,
+
     /// Cloud API endpoint URL
     #[env(GEL_CLOUD_API_ENDPOINT, EDGEDB_CLOUD_API_ENDPOINT)]
     cloud_api_endpoint: String,
@@ -54,6 +60,16 @@ define_env! {
     #[env(GEL_PKG_ROOT, EDGEDB_PKG_ROOT)]
     pkg_root: String,
 
+    /// Proxy URL for HTTP requests to Cloud, package repository and
+    /// other external services
+    #[env(GEL_HTTP_PROXY, EDGEDB_HTTP_PROXY)]
+    http_proxy: String,
+
+    /// PEM file with extra CA certificates for HTTP requests to external
+    /// services
+    #[env(GEL_HTTP_CA_FILE, EDGEDB_HTTP_CA_FILE)]
+    http_ca_file: PathBuf,
+
     /// System editor
     #[env(EDITOR)]
     system_editor: String,
@@ -85,6 +101,24 @@ impl std::str::FromStr for VersionCheck {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialStore {
+    File,
+    Keychain,
+}
+
+impl std::str::FromStr for CredentialStore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(Self::File),
+            "keychain" => Ok(Self::Keychain),
+            _ => Err(format!("Invalid value: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum InstallInDocker {
     Forbid,
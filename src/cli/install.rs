@@ -20,7 +20,7 @@ use crate::branding::BRANDING_CLI_CMD_ALT_FILE;
 use crate::branding::BRANDING_CLI_CMD_FILE;
 use crate::branding::{BRANDING, BRANDING_CLI, BRANDING_CLI_CMD};
 use crate::cli::logo::print_logo;
-use crate::cli::{migrate, upgrade};
+use crate::cli::{complete, migrate, upgrade};
 use crate::commands::ExitCode;
 use crate::options::Options;
 use crate::platform::{binary_path, config_dir, current_exe, home_dir};
@@ -381,7 +381,7 @@ pub fn main(options: &CliInstall) -> anyhow::Result<()> {
             {
                 // This is needed so user can read the message if console
                 // was open just for this process
-                eprintln!("Press the Enter key to continue");
+                msg!("Press the Enter key to continue");
                 read_choice()?;
             }
             Ok(())
@@ -394,8 +394,8 @@ pub fn main(options: &CliInstall) -> anyhow::Result<()> {
             {
                 // This is needed so user can read the message if console
                 // was open just for this process
-                eprintln!("edgedb error: {e:#}");
-                eprintln!("Press the Enter key to continue");
+                msg!("edgedb error: {e:#}");
+                msg!("Press the Enter key to continue");
                 read_choice()?;
                 exit(1);
             }
@@ -466,9 +466,14 @@ fn try_project_init(new_layout: bool) -> anyhow::Result<InitResult> {
             server_version: None,
             server_instance: None,
             database: None,
+            branch: None,
+            cloud_tier: None,
+            cloud_region: None,
+            cloud_compute_size: None,
             non_interactive: false,
             no_migrations: false,
             link: false,
+            unattended_verify: false,
             server_start_conf: None,
             cloud_opts: options.clone(),
         };
@@ -578,7 +583,7 @@ fn _main(options: &CliInstall) -> anyhow::Result<()> {
 
     let base = home_dir()?.join(".edgedb");
     let new_layout = if base.exists() {
-        eprintln!(
+        msg!(
             "\
                 {BRANDING_CLI} no longer uses '{}' to store data \
                 and now uses standard locations of your OS. \
@@ -641,7 +646,7 @@ fn copy_to_installation_path<P: AsRef<Path>>(installation_path: P) -> anyhow::Re
     Ok(())
 }
 
-fn copy_to_alternative_executable<P: AsRef<Path>>(installation_path: P) -> anyhow::Result<()> {
+pub fn copy_to_alternative_executable<P: AsRef<Path>>(installation_path: P) -> anyhow::Result<()> {
     let path = installation_path.as_ref().join(BRANDING_CLI_CMD_FILE);
     let alt_path = installation_path.as_ref().join(BRANDING_CLI_CMD_ALT_FILE);
 
@@ -848,7 +853,9 @@ fn write_completion(path: &Path, shell: Shell) -> anyhow::Result<()> {
     if let Some(dir) = path.parent() {
         fs::create_dir_all(dir)?;
     }
-    shell.generate(&mut BufWriter::new(fs::File::create(path)?));
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    shell.generate(&mut out)?;
+    out.flush()?;
     Ok(())
 }
 
@@ -868,7 +875,7 @@ pub fn write_completions_home() -> anyhow::Result<()> {
 
 pub fn gen_completions(options: &GenCompletions) -> anyhow::Result<()> {
     if let Some(shell) = options.shell {
-        shell.generate(&mut stdout());
+        shell.generate(&mut stdout())?;
     } else if let Some(prefix) = &options.prefix {
         write_completion(
             &prefix.join("share/bash-completion/completions/edgedb"),
@@ -932,18 +939,30 @@ impl FromStr for Shell {
 }
 
 impl Shell {
-    fn generate(&self, buf: &mut dyn Write) {
+    fn generate(&self, buf: &mut dyn Write) -> anyhow::Result<()> {
         use Shell::*;
 
         let mut app = Options::command();
         let n = "edgedb";
         match self {
-            Bash => generate(shells::Bash, &mut app, n, buf),
+            Bash => {
+                generate(shells::Bash, &mut app, n, buf);
+                buf.write_all(complete::BASH_DYNAMIC.as_bytes())?;
+            }
             Elvish => generate(shells::Elvish, &mut app, n, buf),
-            Fish => generate(shells::Fish, &mut app, n, buf),
+            Fish => {
+                generate(shells::Fish, &mut app, n, buf);
+                buf.write_all(complete::FISH_DYNAMIC.as_bytes())?;
+            }
             PowerShell => generate(shells::PowerShell, &mut app, n, buf),
-            Zsh => generate(shells::Zsh, &mut app, n, buf),
+            Zsh => {
+                let mut script = Vec::new();
+                generate(shells::Zsh, &mut app, n, &mut script);
+                let script = String::from_utf8(script)?;
+                buf.write_all(complete::zsh_dynamic(&script).as_bytes())?;
+            }
         }
+        Ok(())
     }
 }
 
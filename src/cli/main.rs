@@ -3,13 +3,16 @@ use crate::cli::migrate;
 use crate::cli::options::CliCommand;
 use crate::cli::options::Command;
 use crate::cli::upgrade;
+use crate::keychain;
 
 pub fn main(cmd: &CliCommand) -> anyhow::Result<()> {
     use Command::*;
 
     match &cmd.subcommand {
         Upgrade(s) => upgrade::main(s),
+        Rollback(s) => upgrade::rollback(s),
         Install(s) => install::main(s),
         Migrate(s) => migrate::main(s),
+        MigrateCredentials(s) => keychain::migrate(s),
     }
 }
@@ -13,7 +13,7 @@ use crate::credentials;
 use crate::platform::binary_path;
 use crate::platform::{config_dir, home_dir, symlink_dir, tmp_file_path};
 use crate::portable::project;
-use crate::print;
+use crate::print::{self, msg};
 use crate::print_markdown;
 use crate::question;
 
@@ -271,7 +271,7 @@ pub fn migrate(base: &Path, dry_run: bool) -> anyhow::Result<()> {
             let new_bin_path = binary_path()?;
             try_move_bin(&exe_path, &new_bin_path).inspect_err(|_| {
                 print::error!("Cannot move executable to new location.");
-                eprintln!("  Try `{BRANDING_CLI_CMD} cli upgrade` instead.");
+                msg!("  Try `{BRANDING_CLI_CMD} cli upgrade` instead.");
             })?;
             update_path(base, &new_bin_path)?;
         }
@@ -337,7 +337,7 @@ pub fn migrate(base: &Path, dry_run: bool) -> anyhow::Result<()> {
     remove_dir_all(&base.join("cache"), dry_run)?;
 
     if !dry_run && dir_is_non_empty(base)? {
-        eprintln!(
+        msg!(
             "\
             Directory {base:?} is no longer used by {BRANDING} tools and must be \
             removed to finish migration, but some files or directories \
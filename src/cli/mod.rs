@@ -1,3 +1,4 @@
+pub mod complete;
 pub mod directory_check;
 pub mod env;
 pub mod install;
@@ -3,6 +3,7 @@ use crate::branding::BRANDING_CLI_CMD;
 use crate::cli::install;
 use crate::cli::migrate;
 use crate::cli::upgrade;
+use crate::keychain;
 
 #[derive(clap::Args, Clone, Debug)]
 #[command(version = "help_expand")]
@@ -16,10 +17,16 @@ pub struct CliCommand {
 pub enum Command {
     /// Upgrade the [`BRANDING_CLI_CMD`] command-line tool
     Upgrade(upgrade::CliUpgrade),
+    /// Switch back to the version of the [`BRANDING_CLI_CMD`] command-line
+    /// tool used before the last upgrade (running it again switches forward)
+    Rollback(upgrade::CliRollback),
     /// Install the [`BRANDING_CLI_CMD`] command-line tool
     #[command(hide = true)]
     Install(install::CliInstall),
     /// Migrate files from `~/.edgedb` to the new directory layout
     #[command(hide = true)]
     Migrate(migrate::CliMigrate),
+    /// Move instance passwords and Cloud secret keys from credential files
+    /// to the OS keychain (used when `GEL_CREDENTIAL_STORE=keychain` is set)
+    MigrateCredentials(keychain::MigrateCredentials),
 }
@@ -7,6 +7,9 @@ use fn_error_context::context;
 use fs_err as fs;
 use indicatif::{ProgressBar, ProgressStyle};
 
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLI_CMD_ALT_FILE};
+use crate::cli::install;
+use crate::hint::HintExt;
 use crate::platform::{binary_path, current_exe, old_binary_path, tmp_file_path};
 use crate::portable::platform;
 use crate::portable::repository::{self, download, Channel};
@@ -39,10 +42,23 @@ pub struct CliUpgrade {
     #[arg(long)]
     #[arg(conflicts_with_all=&["to_stable", "to_nightly", "to_channel"])]
     pub to_testing: bool,
-    /// Upgrade specified instance to specified channel
-    #[arg(long, value_enum)]
+    /// Upgrade to the latest version in the specified channel
+    #[arg(long, value_enum, visible_alias = "channel")]
     #[arg(conflicts_with_all=&["to_stable", "to_nightly", "to_testing"])]
     pub to_channel: Option<Channel>,
+    /// Install specified version, e.g. `5.4.0` or `5.4`. Downgrades are
+    /// allowed. The channel is detected from the version unless
+    /// `--channel` is specified.
+    #[arg(long, value_name = "version")]
+    #[arg(conflicts_with_all=&["to_stable", "to_nightly", "to_testing"])]
+    pub to_version: Option<String>,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct CliRollback {
+    /// Disable progress output
+    #[arg(short = 'q', long)]
+    pub quiet: bool,
 }
 
 pub fn can_upgrade() -> bool {
@@ -89,7 +105,7 @@ pub fn unpack_file(
             }
             let mut tgt_f = opt.open(tgt)?;
 
-            let bar = ProgressBar::new(src.metadata()?.len());
+            let bar = print::progress(ProgressBar::new(src.metadata()?.len()));
             bar.set_style(
                 ProgressStyle::default_bar()
                     .template("Unpacking [{bar}] {bytes:>7.dim}/{total_bytes:7}")
@@ -151,6 +167,7 @@ pub fn upgrade_to_arm64() -> anyhow::Result<()> {
             to_stable: false,
             to_testing: false,
             to_channel: None,
+            to_version: None,
         },
         binary_path()?,
     )
@@ -160,6 +177,8 @@ fn _main(options: &CliUpgrade, path: PathBuf) -> anyhow::Result<()> {
     let cur_channel = channel();
     let channel = if let Some(channel) = options.to_channel {
         channel
+    } else if let Some(version) = &options.to_version {
+        channel_of(version)
     } else if options.to_stable {
         Channel::Stable
     } else if options.to_nightly {
@@ -182,11 +201,31 @@ fn _main(options: &CliUpgrade, path: PathBuf) -> anyhow::Result<()> {
         force = true;
     }
 
-    let pkg = repository::get_platform_cli_packages(channel, target_plat, INDEX_TIMEOUT)?
-        .into_iter()
-        .max_by(|a, b| a.version.cmp(&b.version))
-        .context("cannot find new version")?;
-    if !force && pkg.version <= self_version()? {
+    let packages = repository::get_platform_cli_packages(channel, target_plat, INDEX_TIMEOUT)?;
+    let up_to_date;
+    let pkg = if let Some(version) = &options.to_version {
+        let pkg = packages
+            .into_iter()
+            .filter(|pkg| version_matches(&pkg.version, version))
+            .max_by(|a, b| a.version.cmp(&b.version));
+        let Some(pkg) = pkg else {
+            return Err(anyhow::anyhow!(
+                "version {version:?} is not found in the {} channel",
+                channel.as_str()
+            ))
+            .hint("Use `--channel` to pick the channel the version is published in")?;
+        };
+        up_to_date = pkg.version == self_version()?;
+        pkg
+    } else {
+        let pkg = packages
+            .into_iter()
+            .max_by(|a, b| a.version.cmp(&b.version))
+            .context("cannot find new version")?;
+        up_to_date = pkg.version <= self_version()?;
+        pkg
+    };
+    if !force && up_to_date {
         log::info!("Version is identical; no update needed.");
         if !options.quiet {
             print::success!("Already up to date.");
@@ -205,15 +244,18 @@ fn _main(options: &CliUpgrade, path: PathBuf) -> anyhow::Result<()> {
     download(&down_path, &pkg.url, options.quiet)?;
     unpack_file(&down_path, &tmp_path, pkg.compression)?;
 
-    let backup_path = path.with_extension("backup");
+    // backups used to be named `<binary>.backup`
+    fs::remove_file(path.with_extension("backup")).ok();
+    let previous_path = previous_binary_path(&path);
     if cfg!(unix) {
-        fs::remove_file(&backup_path).ok();
-        fs::hard_link(&path, &backup_path)
-            .map_err(|e| log::warn!("Cannot keep a backup file: {:#}", e))
+        fs::remove_file(&previous_path).ok();
+        fs::hard_link(&path, &previous_path)
+            .map_err(|e| log::warn!("Cannot keep the previous version: {:#}", e))
             .ok();
     } else if cfg!(windows) {
-        fs::remove_file(&backup_path).ok();
-        fs::rename(&path, &backup_path)?;
+        // running executable can't be replaced, but can be renamed
+        fs::remove_file(&previous_path).ok();
+        fs::rename(&path, &previous_path)?;
     } else {
         anyhow::bail!("unknown OS");
     }
@@ -229,3 +271,93 @@ fn _main(options: &CliUpgrade, path: PathBuf) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// Path where the binary replaced by the last upgrade is kept,
+/// e.g. `edgedb-previous`
+pub fn previous_binary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push("-previous");
+    if let Some(ext) = path.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    path.with_file_name(name)
+}
+
+/// Whether `version` is the `requested` one or, if the latter is partial
+/// (e.g. `5.4`), starts with it
+fn version_matches(version: &ver::Semver, requested: &str) -> bool {
+    let version = version.to_string();
+    let requested = requested.trim_start_matches('v');
+    version == requested
+        || [".", "-", "+"]
+            .iter()
+            .any(|sep| version.starts_with(&format!("{requested}{sep}")))
+}
+
+pub fn rollback(options: &CliRollback) -> anyhow::Result<()> {
+    let path = binary_path()?;
+    if !_can_upgrade(&path)? {
+        anyhow::bail!("Only binary installed at {:?} can be rolled back", path);
+    }
+    let previous_path = previous_binary_path(&path);
+    if !previous_path.exists() {
+        return Err(anyhow::anyhow!(
+            "no previous version found at {previous_path:?}"
+        ))
+        .with_hint(|| format!("Previous version is kept by `{BRANDING_CLI_CMD} cli upgrade`"))?;
+    }
+    let version = process::Native::new("version", "cli", &previous_path)
+        .arg("--version")
+        .get_stdout_text()
+        .map_err(|e| log::warn!("Cannot determine previous version: {:#}", e))
+        .ok()
+        .and_then(|out| out.split_whitespace().last().map(|v| v.to_owned()));
+
+    // Swap the binaries using renames only: on Windows the running
+    // executable can't be overwritten or removed, but can be renamed.
+    let tmp_path = tmp_file_path(&path);
+    fs::remove_file(&tmp_path).ok();
+    fs::rename(&path, &tmp_path)?;
+    if let Err(e) = fs::rename(&previous_path, &path) {
+        fs::rename(&tmp_path, &path).ok();
+        return Err(e.into());
+    }
+    fs::rename(&tmp_path, &previous_path)?;
+
+    // The alternative executable is a link to (or a copy of) the binary
+    // and may be the one running, so move it away before replacing.
+    let alt_path = path.with_file_name(BRANDING_CLI_CMD_ALT_FILE);
+    if alt_path.exists() {
+        let alt_tmp_path = tmp_file_path(&alt_path);
+        fs::remove_file(&alt_tmp_path).ok();
+        fs::rename(&alt_path, &alt_tmp_path)?;
+        install::copy_to_alternative_executable(
+            path.parent().context("binary path has no parent")?,
+        )?;
+        // fails on Windows if it is running; removed on the next rollback
+        fs::remove_file(&alt_tmp_path).ok();
+    }
+
+    if !options.quiet {
+        match version {
+            Some(version) => msg!("Rolled back to version {}", version.emphasize()),
+            None => msg!("Rolled back to the previous version"),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_version_matches() {
+    let version = "5.4.1".parse().unwrap();
+    assert!(version_matches(&version, "5.4.1"));
+    assert!(version_matches(&version, "v5.4.1"));
+    assert!(version_matches(&version, "5.4"));
+    assert!(version_matches(&version, "5"));
+    assert!(!version_matches(&version, "5.4.10"));
+    assert!(!version_matches(&version, "5.41"));
+    let version = "6.0.0-dev.1234".parse().unwrap();
+    assert!(version_matches(&version, "6.0.0"));
+    assert!(version_matches(&version, "6.0.0-dev.1234"));
+}
@@ -15,6 +15,7 @@ use crate::cloud::client::{
 use crate::cloud::options;
 use crate::cloud::secret_keys::{CreateSecretKeyInput, SecretKey};
 use crate::commands::ExitCode;
+use crate::keychain::{self, Account};
 use crate::options::CloudOptions;
 use crate::portable::exit_codes;
 use crate::portable::local::write_json;
@@ -109,12 +110,17 @@ pub async fn _do_login(client: &mut CloudClient) -> anyhow::Result<()> {
                     )
                     .await?;
 
+                let profile = client.profile.as_deref().unwrap_or("default");
+                let mut secret_key = key.secret_key;
+                if let Some(key) = &secret_key {
+                    if keychain::set(&Account::CloudProfile(profile), key) {
+                        secret_key = None;
+                    }
+                }
                 write_json(
                     &cloud_config_file(&client.profile)?,
                     "cloud config",
-                    &CloudConfig {
-                        secret_key: key.secret_key,
-                    },
+                    &CloudConfig { secret_key },
                 )?;
                 client.set_secret_key(None)?;
 
@@ -210,6 +216,7 @@ pub fn logout(c: &options::Logout, options: &CloudOptions) -> anyhow::Result<()>
             }
             removed = true;
             fs::remove_file(cloud_creds.join(item.file_name()))?;
+            keychain::delete(&Account::CloudProfile(profile));
             print::success!("You are now logged out from {BRANDING_CLOUD} profile {profile:?}.");
         }
     } else {
@@ -236,6 +243,7 @@ pub fn logout(c: &options::Logout, options: &CloudOptions) -> anyhow::Result<()>
             }
             if removed {
                 fs::remove_file(path).with_context(|| "failed to log out")?;
+                keychain::delete(&Account::CloudProfile(profile));
                 print::success!(
                     "You are now logged out from {BRANDING_CLOUD} for profile \"{}\".",
                     client.profile.as_deref().unwrap_or("default")
@@ -12,8 +12,10 @@ use base64::Engine;
 use anyhow::Context;
 use reqwest::{header, StatusCode};
 
-use crate::branding::BRANDING_CLI_CMD;
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
 use crate::cli::env::Env;
+use crate::http;
+use crate::keychain::{self, Account};
 use crate::options::CloudOptions;
 use crate::platform::config_dir;
 
@@ -97,7 +99,10 @@ impl CloudClient {
                 Ok(data) if data.is_empty() => None,
                 Ok(data) => {
                     let config: CloudConfig = serde_json::from_str(&data)?;
-                    config.secret_key
+                    config.secret_key.or_else(|| {
+                        let profile = profile.as_deref().unwrap_or("default");
+                        keychain::get(&Account::CloudProfile(profile))
+                    })
                 }
                 Err(e) if e.kind() == io::ErrorKind::NotFound => None,
                 Err(e) => {
@@ -106,7 +111,7 @@ impl CloudClient {
             }
         };
         let mut builder =
-            reqwest::Client::builder().timeout(Duration::from_secs(EDGEDB_CLOUD_API_TIMEOUT));
+            http::client_builder()?.timeout(Duration::from_secs(EDGEDB_CLOUD_API_TIMEOUT));
         let is_logged_in;
         let dns_zone;
         if let Some(secret_key) = secret_key.clone() {
@@ -225,7 +230,11 @@ impl CloudClient {
         &self,
         req: reqwest_middleware::RequestBuilder,
     ) -> anyhow::Result<T> {
-        let resp = req.send().await.map_err(Self::create_error)?;
+        let resp = req
+            .send()
+            .await
+            .map_err(Self::create_error)
+            .with_context(|| format!("{BRANDING_CLOUD} API request failed"))?;
         if resp.status().is_success() {
             let full = resp.text().await?;
             serde_json::from_str(&full).with_context(|| {
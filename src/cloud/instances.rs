@@ -0,0 +1,144 @@
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::cloud::client::CloudClient;
+use crate::cloud::ops::{self, CloudInstance};
+use crate::cloud::options;
+use crate::cloud::options::InstanceCommand;
+use crate::cloud::orgs;
+use crate::commands::ExitCode;
+use crate::hint::HintExt;
+use crate::options::CloudOptions;
+use crate::portable::exit_codes;
+use crate::portable::options::InstanceName;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+use crate::table::{self, Cell, Row, Table};
+
+pub fn main(cmd: &InstanceCommand, options: &CloudOptions) -> anyhow::Result<()> {
+    use crate::cloud::options::InstanceSubCommand::*;
+    match &cmd.subcommand {
+        List(c) => list(c, options),
+        Get(c) => get(c, options),
+        Delete(c) => delete(c, options),
+    }
+}
+
+/// Returns organization and name of the instance, using the default
+/// organization if the name is given without `<org>/`.
+fn cloud_name(name: &InstanceName, client: &CloudClient) -> anyhow::Result<(String, String)> {
+    match name {
+        InstanceName::Cloud { org_slug, name } => Ok((org_slug.clone(), name.clone())),
+        InstanceName::Local(name) => match orgs::default_org(client)? {
+            Some(org) => Ok((org, name.clone())),
+            None => Err(anyhow::anyhow!(
+                "{BRANDING_CLOUD} instance name must be in the `<org>/<name>` format"
+            )
+            .with_hint(|| {
+                format!(
+                    "set the default organization with \
+                     `{BRANDING_CLI_CMD} cloud org switch <org>`"
+                )
+            })
+            .into()),
+        },
+    }
+}
+
+pub fn list(c: &options::ListInstances, options: &CloudOptions) -> anyhow::Result<()> {
+    let client = CloudClient::new(options)?;
+    client.ensure_authenticated()?;
+    do_list(c, &client)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn do_list(c: &options::ListInstances, client: &CloudClient) -> anyhow::Result<()> {
+    let mut instances = ops::get_instances(client).await?;
+    if let Some(org) = &c.org {
+        instances.retain(|inst| &inst.org_slug == org);
+    }
+    instances.sort_by(|a, b| (&a.org_slug, &a.name).cmp(&(&b.org_slug, &b.name)));
+
+    if c.json {
+        println!("{}", serde_json::to_string_pretty(&instances)?);
+    } else {
+        print_table(instances.into_iter());
+    }
+
+    Ok(())
+}
+
+fn print_table(items: impl Iterator<Item = CloudInstance>) {
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Name", "Status", "Version", "Tier", "Region"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for inst in items {
+        table.add_row(Row::new(vec![
+            Cell::new(&format!("{}/{}", inst.org_slug, inst.name)),
+            Cell::new(&inst.status),
+            Cell::new(&inst.version),
+            Cell::new(&inst.tier.to_string()),
+            Cell::new(&inst.region),
+        ]));
+    }
+    if !table.is_empty() {
+        table.printstd();
+    } else {
+        println!("No instances found.")
+    }
+}
+
+pub fn get(c: &options::GetInstance, options: &CloudOptions) -> anyhow::Result<()> {
+    let client = CloudClient::new(options)?;
+    let (org_slug, name) = cloud_name(&c.instance, &client)?;
+    client.ensure_authenticated()?;
+    let Some(inst) = ops::find_cloud_instance_by_name(&name, &org_slug, &client)? else {
+        print::error!("{BRANDING_CLOUD} instance {org_slug}/{name} not found.");
+        return Err(ExitCode::new(exit_codes::INSTANCE_NOT_FOUND).into());
+    };
+
+    if c.json {
+        println!("{}", serde_json::to_string_pretty(&inst)?);
+        return Ok(());
+    }
+
+    println!("{}/{}:", inst.org_slug, inst.name);
+    println!("  ID: {}", inst.id);
+    println!("  Status: {}", inst.status);
+    println!("  Version: {}", inst.version);
+    println!("  Tier: {}", inst.tier);
+    println!("  Region: {}", inst.region);
+    if let Some(url) = &inst.ui_url {
+        println!("  Web UI: {url}");
+    }
+    for res in &inst.billables {
+        println!(
+            "  {}: {} {}",
+            res.display_name, res.display_quota, res.display_unit
+        );
+    }
+    Ok(())
+}
+
+pub fn delete(c: &options::DeleteInstance, options: &CloudOptions) -> anyhow::Result<()> {
+    let (org_slug, name) = cloud_name(&c.instance, &CloudClient::new(options)?)?;
+    let full_name = format!("{org_slug}/{name}");
+    if !c.non_interactive {
+        let q = question::Confirm::new_dangerous(format!(
+            "Do you really want to delete {BRANDING_CLOUD} instance {full_name:?}?"
+        ));
+        if !q.ask()? {
+            print::error!("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+    ops::destroy_cloud_instance(&name, &org_slug, options)?;
+    msg!(
+        "{BRANDING_CLOUD} instance {} is successfully deleted.",
+        full_name.emphasize()
+    );
+    Ok(())
+}
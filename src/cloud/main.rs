@@ -1,5 +1,7 @@
 use crate::cloud::auth;
+use crate::cloud::instances;
 use crate::cloud::options::CloudCommand;
+use crate::cloud::orgs;
 use crate::cloud::secret_keys;
 use crate::options::CloudOptions;
 
@@ -10,5 +12,7 @@ pub fn cloud_main(cmd: &CloudCommand, options: &CloudOptions) -> anyhow::Result<
         Login(c) => auth::login(c, options),
         Logout(c) => auth::logout(c, options),
         SecretKey(c) => secret_keys::main(c, options),
+        Instance(c) => instances::main(c, options),
+        Org(c) => orgs::main(c, options),
     }
 }
@@ -1,8 +1,10 @@
 pub mod auth;
 pub mod backups;
 pub mod client;
+pub mod instances;
 pub mod main;
 pub mod ops;
 pub mod options;
+pub mod orgs;
 pub mod secret_keys;
 pub mod versions;
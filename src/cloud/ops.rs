@@ -14,6 +14,7 @@ use crate::cloud::client::{CloudClient, ErrorResponse};
 use crate::collect::Collector;
 use crate::options::CloudOptions;
 use crate::portable::instance::status::{RemoteStatus, RemoteType};
+use crate::print;
 use crate::question;
 
 const OPERATION_WAIT_TIME: Duration = Duration::from_secs(20 * 60);
@@ -23,8 +24,8 @@ const SPINNER_TICK: Duration = Duration::from_millis(100);
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct CloudInstance {
     pub id: String,
-    name: String,
-    org_slug: String,
+    pub name: String,
+    pub org_slug: String,
     dsn: String,
     pub status: String,
     pub version: String,
@@ -76,11 +77,13 @@ impl RemoteStatus {
             connection: None,
             instance_status: Some(cloud_instance.status.clone()),
             location: format!("\u{2601}\u{FE0F} {}", cloud_instance.region),
+            projects: Vec::new(),
+            last_reachable: None,
         })
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[allow(dead_code)]
 pub struct Org {
     pub id: String,
@@ -257,12 +260,16 @@ pub async fn get_org(org: &str, client: &CloudClient) -> anyhow::Result<Org> {
     client.get(format!("orgs/{org}")).await
 }
 
+pub async fn get_orgs(client: &CloudClient) -> anyhow::Result<Vec<Org>> {
+    client.get("orgs/").await
+}
+
 pub(crate) async fn wait_for_operation(
     mut operation: CloudOperation,
     client: &CloudClient,
 ) -> anyhow::Result<()> {
-    let spinner =
-        ProgressBar::new_spinner().with_message(format!("Monitoring {}...", operation.description));
+    let spinner = print::progress(ProgressBar::new_spinner())
+        .with_message(format!("Monitoring {}...", operation.description));
     spinner.enable_steady_tick(SPINNER_TICK);
 
     let mut url = format!("operations/{}", operation.id);
@@ -404,7 +411,7 @@ pub async fn destroy_cloud_instance(
     Ok(())
 }
 
-async fn get_instances(client: &CloudClient) -> anyhow::Result<Vec<CloudInstance>> {
+pub(crate) async fn get_instances(client: &CloudClient) -> anyhow::Result<Vec<CloudInstance>> {
     timeout(Duration::from_secs(30), client.get("instances/"))
         .await
         .or_else(|_| anyhow::bail!("{BRANDING_CLOUD} instances API timed out"))?
@@ -1,4 +1,5 @@
 use crate::options::CloudOptions;
+use crate::portable::options::InstanceName;
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct CloudCommand {
@@ -18,6 +19,10 @@ pub enum Command {
     /// Secret key management.
     #[command(name = "secretkey")]
     SecretKey(SecretKeyCommand),
+    /// Cloud instance management.
+    Instance(InstanceCommand),
+    /// Cloud organization management.
+    Org(OrgCommand),
 }
 
 #[derive(clap::Args, Debug, Clone)]
@@ -68,6 +73,15 @@ pub struct CreateSecretKey {
     /// Output results as JSON.
     #[arg(long)]
     pub json: bool,
+    /// Output format: `default` prints the key, `json` prints the key
+    /// with its metadata and `env` prints a line suitable for `.env` files.
+    #[arg(long, value_enum, conflicts_with = "json")]
+    pub output_format: Option<SecretKeyOutputFormat>,
+    /// Replace an existing key: create a new key with the same name,
+    /// description, scopes and lifetime (unless overridden), then revoke
+    /// the old one.
+    #[arg(long, value_name = "SECRET_KEY_ID")]
+    pub regenerate: Option<String>,
     /// Friendly key name.
     #[arg(short = 'n', long)]
     pub name: Option<String>,
@@ -96,17 +110,19 @@ pub struct CreateSecretKey {
 
     /// Do not ask questions, assume default answers to all inputs
     /// that have a default.  Requires key TTL and scopes to be explicitly
-    /// specified via `--ttl` or `--no-expiration`, and `--scopes` or
-    /// `--inherit-scopes`.
+    /// specified via `--expires`, and `--scopes` or `--inherit-scopes`,
+    /// unless `--regenerate` is used.
     #[arg(short = 'y', long)]
-    #[arg(requires_ifs(
-        ["expires", "key_scopes"].iter().map(
-            |id| (clap::builder::ArgPredicate::IsPresent, id)
-        )
-    ))]
     pub non_interactive: bool,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretKeyOutputFormat {
+    Default,
+    Json,
+    Env,
+}
+
 #[derive(clap::Args, Debug, Clone)]
 pub struct RevokeSecretKey {
     /// Output results as JSON.
@@ -119,3 +135,81 @@ pub struct RevokeSecretKey {
     #[arg(short = 'y', long)]
     pub non_interactive: bool,
 }
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct InstanceCommand {
+    #[command(subcommand)]
+    pub subcommand: InstanceSubCommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum InstanceSubCommand {
+    /// List instances of all organizations.
+    List(ListInstances),
+    /// Show details of an instance.
+    Get(GetInstance),
+    /// Delete an instance.
+    Delete(DeleteInstance),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ListInstances {
+    /// Only list instances of this organization.
+    #[arg(long)]
+    pub org: Option<String>,
+    /// Output results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct GetInstance {
+    /// Instance name in the `<org>/<name>` format, or `<name>` for
+    /// the default organization.
+    pub instance: InstanceName,
+    /// Output results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct DeleteInstance {
+    /// Instance name in the `<org>/<name>` format, or `<name>` for
+    /// the default organization.
+    pub instance: InstanceName,
+    /// Delete instance without asking for confirmation.
+    #[arg(short = 'y', long)]
+    pub non_interactive: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct OrgCommand {
+    #[command(subcommand)]
+    pub subcommand: OrgSubCommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum OrgSubCommand {
+    /// List organizations the current user belongs to.
+    List(ListOrgs),
+    /// Set the default organization of the current Cloud profile, used
+    /// for instance names given without `<org>/`.
+    Switch(SwitchOrg),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ListOrgs {
+    /// Output results as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SwitchOrg {
+    /// Organization name.
+    #[arg(required_unless_present = "unset")]
+    pub org: Option<String>,
+    /// Remove the default organization.
+    #[arg(long, conflicts_with = "org")]
+    pub unset: bool,
+}
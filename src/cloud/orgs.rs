@@ -0,0 +1,89 @@
+use crate::branding::BRANDING_CLOUD;
+use crate::cloud::client::CloudClient;
+use crate::cloud::ops;
+use crate::cloud::options;
+use crate::cloud::options::OrgCommand;
+use crate::config;
+use crate::options::CloudOptions;
+use crate::print::{self, msg, Highlight};
+use crate::table::{self, Cell, Row, Table};
+
+pub fn main(cmd: &OrgCommand, options: &CloudOptions) -> anyhow::Result<()> {
+    use crate::cloud::options::OrgSubCommand::*;
+    match &cmd.subcommand {
+        List(c) => list(c, options),
+        Switch(c) => switch(c, options),
+    }
+}
+
+fn profile(client: &CloudClient) -> &str {
+    client.profile.as_deref().unwrap_or("default")
+}
+
+/// Organization used for instance names given without `<org>/`.
+pub fn default_org(client: &CloudClient) -> anyhow::Result<Option<String>> {
+    config::default_cloud_org(profile(client))
+}
+
+pub fn list(c: &options::ListOrgs, options: &CloudOptions) -> anyhow::Result<()> {
+    let client = CloudClient::new(options)?;
+    client.ensure_authenticated()?;
+    do_list(c, &client)
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn do_list(c: &options::ListOrgs, client: &CloudClient) -> anyhow::Result<()> {
+    let mut orgs = ops::get_orgs(client).await?;
+    orgs.sort_by(|a, b| a.name.cmp(&b.name));
+    let default = default_org(client)?;
+
+    if c.json {
+        println!("{}", serde_json::to_string_pretty(&orgs)?);
+        return Ok(());
+    }
+    if orgs.is_empty() {
+        println!("No organizations found.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Name", "Default"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for org in &orgs {
+        let is_default = default.as_ref() == Some(&org.name);
+        table.add_row(Row::new(vec![
+            Cell::new(&org.name),
+            Cell::new(if is_default { "yes" } else { "" }),
+        ]));
+    }
+    table.printstd();
+    Ok(())
+}
+
+pub fn switch(c: &options::SwitchOrg, options: &CloudOptions) -> anyhow::Result<()> {
+    let client = CloudClient::new(options)?;
+    let path = config::global_config_path()?;
+    let tables = ["cloud-profile", profile(&client)];
+    let Some(org) = &c.org else {
+        config::save_setting(&path, &tables, "default-org", None)?;
+        msg!(
+            "Removed the default {BRANDING_CLOUD} organization of profile {}.",
+            profile(&client).emphasize()
+        );
+        return Ok(());
+    };
+    client.ensure_authenticated()?;
+    // fails if the organization doesn't exist or is not accessible
+    let org = ops::get_org(org, &client)?;
+    config::save_setting(&path, &tables, "default-org", Some(org.name.clone().into()))?;
+    print::success!(
+        "Default {BRANDING_CLOUD} organization of profile {} is now {}.",
+        profile(&client).emphasize(),
+        org.name.emphasize()
+    );
+    Ok(())
+}
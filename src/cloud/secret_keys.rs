@@ -4,7 +4,7 @@ use colorful::Colorful;
 use crate::branding::BRANDING_CLOUD;
 use crate::cloud::client::CloudClient;
 use crate::cloud::options;
-use crate::cloud::options::SecretKeyCommand;
+use crate::cloud::options::{SecretKeyCommand, SecretKeyOutputFormat};
 use crate::commands::ExitCode;
 use crate::options::CloudOptions;
 
@@ -15,6 +15,13 @@ use crate::table::{self, Cell, Row, Table};
 use crate::print::{self, msg, Highlight};
 use crate::question;
 
+/// Environment variable used by client libraries to pick up the key
+const SECRET_KEY_ENV: &str = if cfg!(feature = "gel") {
+    "GEL_SECRET_KEY"
+} else {
+    "EDGEDB_SECRET_KEY"
+};
+
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub struct SecretKey {
     pub id: String,
@@ -108,54 +115,123 @@ pub async fn do_create(c: &options::CreateSecretKey, client: &CloudClient) -> an
 }
 
 pub async fn _do_create(c: &options::CreateSecretKey, client: &CloudClient) -> anyhow::Result<()> {
+    let format = match c.output_format {
+        Some(format) => format,
+        None if c.json => SecretKeyOutputFormat::Json,
+        None => SecretKeyOutputFormat::Default,
+    };
+    let old_key = match &c.regenerate {
+        Some(id) => Some(find_key(client, id).await?),
+        None => None,
+    };
+
     let mut params = CreateSecretKeyInput {
         name: c.name.clone(),
         description: c.description.clone(),
         scopes: c.scopes.clone(),
         ttl: c.expires.clone(),
     };
+    if let Some(old) = &old_key {
+        params.name = params.name.or_else(|| old.name.clone());
+        params.description = params.description.or_else(|| old.description.clone());
+        if params.scopes.is_none() && !c.inherit_scopes {
+            params.scopes = Some(old.scopes.clone());
+        }
+        if params.ttl.is_none() {
+            params.ttl = Some(old.ttl().unwrap_or_else(|| "never".into()));
+        }
+    }
 
     if !c.non_interactive {
-        if c.expires.is_none() {
+        if params.ttl.is_none() {
             params.ttl = _ask_ttl()?;
         }
-        if c.scopes.is_none() && !c.inherit_scopes {
+        if params.scopes.is_none() && !c.inherit_scopes {
             params.scopes = _ask_scopes()?;
         }
+    } else if params.ttl.is_none() || (params.scopes.is_none() && !c.inherit_scopes) {
+        anyhow::bail!(
+            "`--expires` and either `--scopes` or `--inherit-scopes` \
+             are required in non-interactive mode"
+        );
     }
 
     params.ttl = match params.ttl {
         None => None,
         Some(ref s) if s == "never" => None,
-        Some(s) => Some(s),
+        Some(s) => Some(
+            humantime::parse_duration(&s)
+                .map(|d| humantime::format_duration(d).to_string())
+                .with_context(|| format!("invalid key expiration {s:?}"))?,
+        ),
     };
 
     let key: SecretKey = create_secret_key(client, &params).await?;
 
-    if c.json {
-        println!("{}", serde_json::to_string_pretty(&key)?);
-    } else {
-        let sk = key
-            .secret_key
-            .context("no valid secret key returned from server")?;
-        if c.non_interactive {
-            print!("{sk}");
-        } else {
-            msg!(
-                "\nYour new {} {}",
-                BRANDING_CLOUD,
-                " secret key is printed below. \
-                 Be sure to copy and store it securely, as you will \
-                 not be able to see it again.\n"
-                    .green()
-            );
-            msg!("{}", sk.emphasize());
+    if let Some(old) = &old_key {
+        let _: SecretKey = client
+            .delete(format!("secretkeys/{}", old.id))
+            .await
+            .with_context(|| {
+                format!(
+                    "new key {:?} is created, but the old key {:?} could not be revoked",
+                    key.id, old.id
+                )
+            })?;
+        if format == SecretKeyOutputFormat::Default && !c.non_interactive {
+            msg!("Secret key {:?} has been revoked.", old.id);
+        }
+    }
+
+    match format {
+        SecretKeyOutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&key)?);
+        }
+        SecretKeyOutputFormat::Env => {
+            let sk = key
+                .secret_key
+                .context("no valid secret key returned from server")?;
+            println!("{SECRET_KEY_ENV}={sk}");
+        }
+        SecretKeyOutputFormat::Default => {
+            let sk = key
+                .secret_key
+                .context("no valid secret key returned from server")?;
+            if c.non_interactive {
+                print!("{sk}");
+            } else {
+                msg!(
+                    "\nYour new {} {}",
+                    BRANDING_CLOUD,
+                    " secret key is printed below. \
+                     Be sure to copy and store it securely, as you will \
+                     not be able to see it again.\n"
+                        .green()
+                );
+                msg!("{}", sk.emphasize());
+            }
         }
     }
 
     Ok(())
 }
 
+async fn find_key(client: &CloudClient, id: &str) -> anyhow::Result<SecretKey> {
+    let keys: Vec<SecretKey> = client.get("secretkeys/").await?;
+    keys.into_iter()
+        .find(|k| k.id == id)
+        .with_context(|| format!("secret key {id:?} not found"))
+}
+
+impl SecretKey {
+    /// Original lifetime of the key, in the format accepted by the API
+    fn ttl(&self) -> Option<String> {
+        let expires = self.expires_on?;
+        let ttl = expires.duration_since(self.created_on).ok()?;
+        Some(humantime::format_duration(ttl).to_string())
+    }
+}
+
 pub async fn create_secret_key(
     client: &CloudClient,
     params: &CreateSecretKeyInput,
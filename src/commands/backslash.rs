@@ -1,7 +1,9 @@
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::str::FromStr;
 
+use anyhow::Context;
 use clap::{CommandFactory, FromArgMatches};
 use const_format::concatcp;
 use once_cell::sync::Lazy;
@@ -15,10 +17,16 @@ use gel_protocol::model::Duration;
 use crate::analyze;
 use crate::branding::BRANDING;
 use crate::commands::execute;
-use crate::commands::parser::{Backslash, BackslashCmd, Setting, StateParam};
+use crate::commands::parser::{
+    Backslash, BackslashCmd, Common as CommonCmd, DescribeCmd, ListTables, Setting, StateParam,
+};
 use crate::commands::Options;
-use crate::print;
+use crate::config;
+use crate::hint::HintExt;
+use crate::options::connector_for;
+use crate::platform;
 use crate::print::style::Styler;
+use crate::print::{self, AsRelativeToCurrentDir};
 use crate::prompt;
 use crate::repl;
 use crate::table;
@@ -29,6 +37,7 @@ pub enum ExecuteResult {
     Skip,
     Quit,
     Input(String),
+    Query(String),
 }
 
 const HELP: &str = concatcp!(
@@ -54,24 +63,45 @@ Introspection
   \lc [-c]   [PATTERN]      List casts              (alias: \list casts)
   \li [-vsc] [PATTERN]      List indexes            (alias: \list indexes)
 
+SQL mode (\set language sql)
+  \d NAME                   Describe table columns
+  \dt [-sc] [PATTERN]       List tables             (alias: \list-tables)
+
 Operations
   \dump FILENAME            Create dump of current database as a file
   \restore FILENAME         Restore database from file into current database
   \expand                   Print expanded output of last `analyze` operation
+  \view [-e]                Open full result of last query in $PAGER
+                            (or $EDITOR with -e)
   \E, \last-error           More information on most recent error
 
 Editing
-  \s, \history              Show history
+  \s, \history [PATTERN]    Show numbered history, optionally only entries
+                            containing PATTERN. Ctrl+R searches history
+                            (fuzzy when the line is not empty).
   \e, \edit [N]             Spawn $EDITOR to edit the last used query, using
                             the editor output as input in the REPL.
                             Defaults to vi (Notepad in Windows).
 
 Connection
   \c, \connect [DBNAME]     Connect to database/branch DBNAME
+  \c -I INSTANCE [DBNAME]   Connect to another instance
+  \c --dsn DSN [DBNAME]     Connect to DSN
+
+Branches
+  \b, \branch list          List branches, marking the current one
+  \branch switch [-c] NAME  Switch the session (and the instance's current
+                            branch, if known) to NAME, creating it with -c
+  \branch create NAME       Create branch NAME from the current branch
 
 Settings
   \set [OPTION [VALUE]]     Show/change settings. Type \set to list
                             all available options
+  \set --save OPTION VALUE  Change setting and save it to the global
+                            configuration file (or --save-project to save
+                            it for the current project only)
+  \settings                 List settings, where their values come from
+                            and allowed values
 
 Help
   \?, \h, \help             Show help on backslash commands
@@ -334,6 +364,7 @@ impl CommandCache {
         aliases.insert("la", &["list", "aliases"]);
         aliases.insert("lc", &["list", "casts"]);
         aliases.insert("li", &["list", "indexes"]);
+        aliases.insert("dt", &["list-tables"]);
         aliases.insert("s", &["history"]);
         aliases.insert("e", &["edit"]);
         aliases.insert("c", &["connect"]);
@@ -545,6 +576,8 @@ pub fn get_setting(s: &Setting, prompt: &repl::State) -> Cow<'static, str> {
         DisplayTypenames(_) => bool_str(prompt.display_typenames).into(),
         ExpandStrings(_) => bool_str(prompt.print.expand_strings).into(),
         PrintStats(_) => prompt.print_stats.as_str().into(),
+        Pager(_) => prompt.pager.as_str().into(),
+        Stats(_) => bool_str(prompt.stats).into(),
     }
 }
 
@@ -567,6 +600,78 @@ fn list_settings(prompt: &mut repl::State) {
     table.printstd();
 }
 
+fn list_settings_origin(prompt: &repl::State) {
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Setting", "Current", "Origin", "Allowed Values"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for setting in CMD_CACHE.settings.values() {
+        let origin = prompt
+            .setting_origins
+            .get(setting.name)
+            .map(|o| &o[..])
+            .unwrap_or("default");
+        let allowed = match &setting.values {
+            Some(values) => values.join(", "),
+            None => format!("<{}>", setting.value_name),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(setting.name),
+            Cell::new(&get_setting(setting.setting, prompt)),
+            Cell::new(origin),
+            Cell::new(&textwrap::fill(&allowed, 30)),
+        ]));
+    }
+    table.printstd();
+}
+
+/// Value of the setting as written to the configuration file
+fn config_value(setting: &Setting) -> Option<toml_edit::Value> {
+    use Setting::*;
+
+    let value: toml_edit::Value = match setting {
+        Language(v) => v.value?.as_str().into(),
+        InputMode(v) => v.value?.as_str().into(),
+        OutputFormat(v) => v.value?.as_str().into(),
+        PrintStats(v) => v.value?.as_str().into(),
+        Pager(v) => v.value?.as_str().into(),
+        IdleTransactionTimeout(v) => v.value.as_deref()?.into(),
+        Limit(v) => (v.value? as i64).into(),
+        HistorySize(v) => (v.value? as i64).into(),
+        ImplicitProperties(b)
+        | VerboseErrors(b)
+        | DisplayTypenames(b)
+        | ExpandStrings(b)
+        | Stats(b) => {
+            b.value.as_ref()?;
+            b.unwrap_value().into()
+        }
+        VectorDisplayLength(_) => return None,
+    };
+    Some(value)
+}
+
+/// Saves the setting to the global or project configuration file and
+/// returns the path of the file
+fn save_setting(setting: &Setting, project: bool) -> anyhow::Result<String> {
+    let (Some(key), Some(value)) = (setting.config_key(), config_value(setting)) else {
+        anyhow::bail!("setting `{}` cannot be saved", setting.name());
+    };
+    let path = if project {
+        config::project_settings_path()?
+            .context("no initialized project found")
+            .hint("Run `\\set --save` to save the setting globally.")?
+    } else {
+        config::global_config_path()?
+    };
+    config::save_shell_setting(&path, key, value)?;
+    Ok(path.as_relative().display().to_string())
+}
+
 pub async fn execute(
     cmd: &BackslashCmd,
     prompt: &mut repl::State,
@@ -587,6 +692,14 @@ pub async fn execute(
             Ok(Skip)
         }
         Common(ref cmd) => {
+            if prompt.input_language == repl::InputLanguage::Sql {
+                // `\d NAME` describes a table in SQL mode
+                if let CommonCmd::Describe(d) = &**cmd {
+                    if let DescribeCmd::Object(obj) = &d.subcommand {
+                        return Ok(Query(describe_table_query(&obj.name)));
+                    }
+                }
+            }
             prompt.soft_reconnect().await?;
             let cli = prompt.connection.as_mut().expect("connection established");
             let result = execute::common(cli, cmd, &options).await?;
@@ -597,18 +710,21 @@ pub async fn execute(
 
             Ok(Skip)
         }
-        Set(SetCommand { setting: None }) => {
+        Set(SetCommand { setting: None, .. }) => {
             list_settings(prompt);
             Ok(Skip)
         }
         Set(SetCommand {
             setting: Some(ref cmd),
+            ..
         }) if cmd.is_show() => {
             println!("{}: {}", cmd.name(), get_setting(cmd, prompt));
             Ok(Skip)
         }
         Set(SetCommand {
             setting: Some(ref cmd),
+            save,
+            save_project,
         }) => {
             match cmd {
                 InputMode(m) => {
@@ -659,16 +775,46 @@ pub async fn execute(
                 PrintStats(v) => {
                     prompt.print_stats = v.value.expect("only writes here");
                 }
+                Pager(v) => {
+                    prompt.pager = v.value.expect("only writes here");
+                }
+                Stats(b) => {
+                    prompt.stats = b.unwrap_value();
+                }
             }
+            let origin = if *save || *save_project {
+                let path = save_setting(cmd, *save_project)?;
+                print::success!("Saved `{}` to {path}.", cmd.name());
+                path
+            } else {
+                "session".into()
+            };
+            prompt.setting_origins.insert(cmd.name(), origin);
+            Ok(Skip)
+        }
+        Settings => {
+            list_settings_origin(prompt);
             Ok(Skip)
         }
         Connect(c) => {
             if prompt.in_transaction() {
                 print::warn!("WARNING: Transaction canceled.");
             }
-            prompt
-                .try_connect(&c.database_name)
-                .await
+            let result = if c.instance.is_some() || c.dsn.is_some() {
+                match connector_for(c.instance.as_ref(), c.dsn.as_deref()).await {
+                    Ok(conn_params) => {
+                        prompt
+                            .switch_connector(conn_params, c.database_name.as_deref())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                }
+            } else if let Some(branch) = &c.database_name {
+                prompt.try_connect(branch).await
+            } else {
+                Err(anyhow::anyhow!("specify a branch, an instance or a DSN"))
+            };
+            result
                 .map_err(|e| {
                     print::error!("Cannot connect: {e:#}");
                 })
@@ -694,6 +840,26 @@ pub async fn execute(
             }
             Ok(Skip)
         }
+        View(c) => {
+            let Some(ref last) = prompt.last_result else {
+                eprintln!("== no previous query result ==");
+                return Ok(Skip);
+            };
+            let mut cfg = prompt.print.clone();
+            cfg.colors(false).max_items(None);
+            let (ext, text) = last.render(&cfg).await?;
+            let file = tempfile::Builder::new()
+                .prefix("query-result-")
+                .suffix(&format!(".{ext}"))
+                .tempfile()?;
+            fs::write(file.path(), text)?;
+            if c.editor {
+                platform::spawn_editor(file.path()).await?;
+            } else {
+                platform::spawn_pager(file.path()).await?;
+            }
+            Ok(Skip)
+        }
         DebugState(StateParam { base }) => {
             let (desc_id, value) = if *base {
                 prompt.get_state_as_value()?
@@ -726,22 +892,75 @@ pub async fn execute(
             eprintln!("Codec: {:#?}", typedesc.build_codec()?);
             Ok(Skip)
         }
-        History => {
-            prompt.show_history().await?;
+        History(c) => {
+            prompt.show_history(c.pattern.clone()).await?;
             Ok(Skip)
         }
         Edit(c) => match prompt.spawn_editor(c.entry).await? {
             prompt::Input::Text(text) => Ok(Input(text)),
             prompt::Input::Interrupt | prompt::Input::Eof => Ok(Skip),
         },
+        ListTables(cmd) => {
+            if prompt.input_language != repl::InputLanguage::Sql {
+                return Err(anyhow::anyhow!("`\\dt` is only supported in SQL mode")
+                    .hint("switch language with `\\set language sql`")
+                    .into());
+            }
+            Ok(Query(list_tables_query(cmd)))
+        }
         Exit => Ok(Quit),
     }
 }
 
+fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn list_tables_query(cmd: &ListTables) -> String {
+    let mut filters = vec!["table_type IN ('BASE TABLE', 'VIEW')".to_string()];
+    if !cmd.system {
+        filters.push("table_schema NOT IN ('pg_catalog', 'information_schema')".into());
+    }
+    if let Some(pattern) = &cmd.pattern {
+        let op = if cmd.case_sensitive { "LIKE" } else { "ILIKE" };
+        filters.push(format!(
+            "table_schema || '.' || table_name {op} {}",
+            quote_literal(&pattern.replace('*', "%"))
+        ));
+    }
+    format!(
+        "SELECT table_schema AS schema, table_name AS name, table_type AS type \
+         FROM information_schema.tables WHERE {} \
+         ORDER BY table_schema, table_name;",
+        filters.join(" AND ")
+    )
+}
+
+fn describe_table_query(name: &str) -> String {
+    let unquote = |s: &str| s.trim_matches('"').to_string();
+    let (schema, table) = match name.rsplit_once('.') {
+        Some((schema, table)) => (Some(unquote(schema)), unquote(table)),
+        None => (None, unquote(name)),
+    };
+    let schema_filter = match schema {
+        Some(schema) => format!("table_schema = {}", quote_literal(&schema)),
+        None => "table_schema NOT IN ('pg_catalog', 'information_schema')".into(),
+    };
+    format!(
+        "SELECT column_name AS column, data_type AS type, \
+         is_nullable AS nullable, column_default AS default \
+         FROM information_schema.columns \
+         WHERE {schema_filter} AND table_name = {} \
+         ORDER BY table_schema, ordinal_position;",
+        quote_literal(&table)
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::Item::{self, *};
-    use super::Parser;
+    use super::{describe_table_query, list_tables_query, Parser};
+    use crate::commands::parser::ListTables;
 
     fn tok_values(s: &str) -> Vec<Item<'_>> {
         Parser::new(s).map(|tok| tok.item).collect::<Vec<_>>()
@@ -760,4 +979,19 @@ mod test {
             [Command("\\describe"), Argument("schema::`Object`")]
         );
     }
+
+    #[test]
+    fn sql_queries() {
+        let query = list_tables_query(&ListTables {
+            pattern: Some("public.user*".into()),
+            case_sensitive: false,
+            system: false,
+        });
+        assert!(query.contains("ILIKE 'public.user%'"));
+        assert!(query.contains("NOT IN ('pg_catalog', 'information_schema')"));
+
+        let query = describe_table_query(r#""my schema"."it's""#);
+        assert!(query.contains("table_schema = 'my schema'"));
+        assert!(query.contains("table_name = 'it''s'"));
+    }
 }
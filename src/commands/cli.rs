@@ -1,15 +1,26 @@
 use is_terminal::IsTerminal;
 
+use crate::auth;
+use crate::branch::connections::connect_to_existing_branch;
+use crate::branch::Subcommand as BranchCmd;
 use crate::cli::directory_check;
 use crate::cloud::main::cloud_main;
 use crate::commands;
-use crate::commands::parser::Common;
+use crate::commands::parser::{BranchingCmd, Common, DatabaseCmd};
+use crate::commands::ExitCode;
+use crate::config;
+use crate::doctor;
+use crate::hooks;
+use crate::lsp_proxy;
 use crate::migrations;
 use crate::migrations::options::{Migration, MigrationCmd as M};
 use crate::non_interactive;
 use crate::options::{Command, Options};
 use crate::portable;
-use crate::print::style::Styler;
+use crate::portable::exit_codes;
+use crate::print::{self, style::Styler};
+use crate::question;
+use crate::seed;
 use crate::watch;
 use crate::{branch, cli};
 
@@ -19,7 +30,14 @@ async fn common_cmd(
     cmdopt: commands::Options,
     cmd: &Common,
 ) -> Result<(), anyhow::Error> {
-    let mut conn = cmdopt.conn_params.connect().await?;
+    let mut conn = match cmd {
+        // fail early with a list of branches, before reading any files
+        Common::Dump(dump) if !dump.all => connect_to_existing_branch(&cmdopt.conn_params).await?,
+        Common::Restore(restore) if !restore.all => {
+            connect_to_existing_branch(&cmdopt.conn_params).await?
+        }
+        _ => cmdopt.conn_params.connect().await?,
+    };
     commands::execute::common(&mut conn, cmd, &cmdopt).await?;
     Ok(())
 }
@@ -27,6 +45,9 @@ async fn common_cmd(
 pub fn main(options: &Options) -> Result<(), anyhow::Error> {
     match options.subcommand.as_ref().expect("subcommand is present") {
         Command::Common(cmd) => {
+            if is_destructive(cmd) {
+                confirm_environment(options)?;
+            }
             let cmdopt = init_command_opts(options)?;
             directory_check::check_and_warn();
             match cmd.as_migration() {
@@ -49,7 +70,7 @@ pub fn main(options: &Options) -> Result<(), anyhow::Error> {
         }
         Command::Server(cmd) => {
             directory_check::check_and_error()?;
-            portable::server::run(cmd)
+            portable::server::run(cmd, options)
         }
         Command::Extension(cmd) => {
             directory_check::check_and_error()?;
@@ -59,6 +80,10 @@ pub fn main(options: &Options) -> Result<(), anyhow::Error> {
             directory_check::check_and_error()?;
             portable::instance::run(cmd, options)
         }
+        Command::Sandbox(cmd) => {
+            directory_check::check_and_error()?;
+            portable::sandbox::run(cmd, options)
+        }
         Command::Project(cmd) => {
             directory_check::check_and_error()?;
             portable::project::run(cmd, options)
@@ -69,16 +94,43 @@ pub fn main(options: &Options) -> Result<(), anyhow::Error> {
         }
         Command::_SelfInstall(s) => cli::install::main(s),
         Command::_GenCompletions(s) => cli::install::gen_completions(s),
+        Command::_Complete(c) => cli::complete::run(c, options),
         Command::Cli(c) => cli::main(c),
         Command::Info(info) => commands::info(options, info),
         Command::UI(c) => commands::show_ui(c, options),
         Command::Cloud(c) => cloud_main(c, &options.cloud_options),
         Command::Watch(c) => watch::watch(options, c),
+        Command::LspProxy(c) => lsp_proxy::run(options, c),
+        Command::Config(c) => config::run(c),
+        Command::Connection(c) => doctor::run(options, c),
+        Command::Hook(c) => hooks::command::run(options, c),
         Command::Branch(c) => {
+            if matches!(
+                c.subcommand,
+                BranchCmd::Drop(_)
+                    | BranchCmd::Wipe(_)
+                    | BranchCmd::Rebase(_)
+                    | BranchCmd::Merge(_)
+                    | BranchCmd::Import(_)
+            ) {
+                confirm_environment(options)?;
+            }
             let opts = init_command_opts(options)?;
             branch::run(&opts, c)?;
             Ok(())
         }
+        Command::Auth(c) => {
+            if c.subcommand.is_modifying() {
+                confirm_environment(options)?;
+            }
+            let opts = init_command_opts(options)?;
+            auth::run(&opts, c)
+        }
+        Command::Seed(c) => {
+            directory_check::check_and_warn();
+            let opts = init_command_opts(options)?;
+            seed::run(&opts, c)
+        }
         Command::HashPassword(cmd) => {
             println!("{}", portable::password_hash(&cmd.password));
             Ok(())
@@ -97,3 +149,32 @@ fn init_command_opts(options: &Options) -> Result<commands::Options, anyhow::Err
         conn_params: options.block_on_create_connector()?,
     })
 }
+
+fn is_destructive(cmd: &Common) -> bool {
+    match cmd {
+        Common::Restore(_) | Common::Migrate(_) | Common::Configure(_) => true,
+        Common::Migration(m) => matches!(m.subcommand, M::Apply(_)),
+        Common::Database(d) => matches!(d.subcommand, DatabaseCmd::Drop(_) | DatabaseCmd::Wipe(_)),
+        Common::Branching(b) => {
+            matches!(b.subcommand, BranchingCmd::Drop(_) | BranchingCmd::Wipe(_))
+        }
+        _ => false,
+    }
+}
+
+/// Asks for confirmation before modifying a project instance that has
+/// `confirm = true` in its `[instances.<name>]` table.
+fn confirm_environment(options: &Options) -> anyhow::Result<()> {
+    let Some(env) = options.environment.as_ref().filter(|env| env.confirm) else {
+        return Ok(());
+    };
+    let q = question::Confirm::new_dangerous(format!(
+        "This command will modify the {:?} instance. Continue?",
+        env.name
+    ));
+    if !q.ask()? {
+        print::error!("Canceled.");
+        return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+    }
+    Ok(())
+}
@@ -1,11 +1,179 @@
 use std::fmt::Display;
 
-use crate::commands::parser::{AuthParameter, ConfigStr, ConfigStrs, Configure, ListenAddresses};
+use prettytable::{Cell, Row, Table};
+
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::parser::{
+    AuthParameter, ConfigStr, ConfigStrs, Configure, ConfigureGet, ConfigureList, ListenAddresses,
+};
 use crate::commands::Options;
 use crate::connect::Connection;
-use crate::print;
+use crate::hint::HintExt;
+use crate::print::{self, msg};
+use crate::table;
 use edgeql_parser::helpers::{quote_name, quote_string};
 
+#[derive(Debug, serde::Deserialize)]
+struct Property {
+    name: String,
+    target: String,
+    multi: bool,
+    default: Option<String>,
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Annotation {
+    name: String,
+    #[serde(rename = "@value")]
+    value: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Setting {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    value: serde_json::Value,
+    default: Option<String>,
+    requires_restart: bool,
+    description: Option<String>,
+}
+
+const SETTINGS_QUERY: &str = r###"
+    WITH MODULE schema
+    SELECT <str><json>array_agg((
+        SELECT ObjectType FILTER .name = 'cfg::AbstractConfig'
+    ).pointers[IS Property] {
+        name,
+        target := .target.name,
+        multi := .cardinality = Cardinality.Many,
+        default,
+        annotations: { name, @value },
+    } FILTER .name != 'id')
+"###;
+
+impl Property {
+    fn annotation(&self, name: &str) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|a| a.name == name)
+            .and_then(|a| a.value.as_deref())
+    }
+}
+
+/// Reads all (non-internal) settings of the instance, sorted by name
+async fn settings(cli: &mut Connection) -> anyhow::Result<Vec<Setting>> {
+    let data = cli
+        .query_required_single::<String, _>(SETTINGS_QUERY, &())
+        .await?;
+    let mut properties: Vec<Property> = serde_json::from_str(&data)?;
+    properties.retain(|p| p.annotation("cfg::internal") != Some("true"));
+    properties.sort_by(|a, b| a.name.cmp(&b.name));
+    if properties.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let shape = properties
+        .iter()
+        .map(|p| quote_name(&p.name).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let data = cli
+        .query_required_single::<String, _>(
+            &format!("SELECT <str><json>(SELECT cfg::Config {{ {shape} }} LIMIT 1)"),
+            &(),
+        )
+        .await?;
+    let mut values: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&data)?;
+
+    Ok(properties
+        .into_iter()
+        .map(|p| {
+            let requires_restart = p.annotation("cfg::requires_restart") == Some("true");
+            let description = p.annotation("std::description").map(|d| d.to_string());
+            let type_name = if p.multi {
+                format!("multi {}", p.target)
+            } else {
+                p.target
+            };
+            Setting {
+                value: values.remove(&p.name).unwrap_or_default(),
+                name: p.name,
+                type_name,
+                default: p.default,
+                requires_restart,
+                description,
+            }
+        })
+        .collect())
+}
+
+fn format_value(value: &serde_json::Value) -> String {
+    use serde_json::Value as V;
+
+    match value {
+        V::Null => "{}".into(),
+        V::String(s) => s.clone(),
+        V::Array(items) if items.is_empty() => "{}".into(),
+        V::Array(items) => items
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => value.to_string(),
+    }
+}
+
+pub async fn list(cli: &mut Connection, cmd: &ConfigureList) -> anyhow::Result<()> {
+    let settings = settings(cli).await?;
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&settings)?);
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Name", "Value", "Default", "Type", "Restart"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for setting in &settings {
+        table.add_row(Row::new(vec![
+            Cell::new(&setting.name),
+            Cell::new(&format_value(&setting.value)),
+            Cell::new(setting.default.as_deref().unwrap_or("")),
+            Cell::new(&setting.type_name),
+            Cell::new(if setting.requires_restart { "yes" } else { "" }),
+        ]));
+    }
+    if table.is_empty() {
+        msg!("No configuration settings found.");
+    } else {
+        table.printstd();
+    }
+    Ok(())
+}
+
+pub async fn get(cli: &mut Connection, cmd: &ConfigureGet) -> anyhow::Result<()> {
+    let Some(setting) = settings(cli)
+        .await?
+        .into_iter()
+        .find(|s| s.name == cmd.name)
+    else {
+        return Err(anyhow::anyhow!("unknown setting {:?}", cmd.name)
+            .with_hint(|| format!("run `{BRANDING_CLI_CMD} configure list` to see all settings"))
+            .into());
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&setting)?);
+    } else {
+        println!("{}", format_value(&setting.value));
+    }
+    Ok(())
+}
+
 async fn set(
     cli: &mut Connection,
     name: &str,
@@ -31,6 +199,8 @@ pub async fn configure(
     use crate::commands::parser::ListParameter as I;
     use crate::commands::parser::ValueParameter as S;
     match &cfg.command {
+        C::List(cmd) => list(cli, cmd).await,
+        C::Get(cmd) => get(cli, cmd).await,
         C::Insert(Ins {
             parameter: I::Auth(param),
         }) => {
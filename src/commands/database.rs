@@ -6,7 +6,7 @@ use crate::commands::{ExitCode, Options};
 use crate::connect::Connection;
 use crate::hint::HintExt;
 use crate::portable::exit_codes;
-use crate::print;
+use crate::print::{self, msg};
 use crate::question;
 
 pub async fn create(
@@ -15,7 +15,7 @@ pub async fn create(
     _: &Options,
 ) -> Result<(), anyhow::Error> {
     if cli.get_version().await?.specific().major >= 5 {
-        eprintln!("'database create' is deprecated in {BRANDING} 5+. Please use 'branch create'");
+        msg!("'database create' is deprecated in {BRANDING} 5+. Please use 'branch create'");
     }
 
     let (status, _warnings) = cli
@@ -34,7 +34,7 @@ pub async fn drop(
     _: &Options,
 ) -> Result<(), anyhow::Error> {
     if cli.get_version().await?.specific().major >= 5 {
-        eprintln!("'database drop' is deprecated in {BRANDING} 5+. Please use 'branch drop'");
+        msg!("'database drop' is deprecated in {BRANDING} 5+. Please use 'branch drop'");
     }
 
     if !options.non_interactive {
@@ -63,7 +63,7 @@ pub async fn wipe(
     _: &Options,
 ) -> Result<(), anyhow::Error> {
     if cli.get_version().await?.specific().major >= 5 {
-        eprintln!("'database wipe' is deprecated in {BRANDING} 5+. Please use 'branch wipe'");
+        msg!("'database wipe' is deprecated in {BRANDING} 5+. Please use 'branch wipe'");
     }
 
     if cli.get_version().await?.specific() < "3.0-alpha.2".parse().unwrap() {
@@ -1,13 +1,119 @@
+use prettytable::{Cell, Row, Table};
+
 use crate::commands::helpers::quote_namespaced;
+use crate::commands::parser::DescribeFormat;
 use crate::commands::Options;
 use crate::connect::Connection;
 use crate::highlight;
+use crate::hint::HintExt;
+use crate::table;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct ObjectType {
+    name: String,
+    #[serde(rename = "abstract")]
+    is_abstract: bool,
+    bases: Vec<String>,
+    properties: Vec<Pointer>,
+    links: Vec<Pointer>,
+    constraints: Vec<Constraint>,
+    indexes: Vec<Index>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Pointer {
+    name: String,
+    target: String,
+    required: bool,
+    cardinality: String,
+    readonly: bool,
+    constraints: Vec<Constraint>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Constraint {
+    name: String,
+    expr: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Index {
+    expr: String,
+}
+
+const OBJECT_TYPE_QUERY: &str = r###"
+    WITH
+        MODULE schema,
+        T := (
+            SELECT ObjectType
+            FILTER .name = <str>$0 OR .name = 'default::' ++ <str>$0
+            LIMIT 1
+        )
+    SELECT <str><json>(SELECT T {
+        name,
+        `abstract`,
+        bases := array_agg(.bases.name),
+        properties := (
+            SELECT .pointers[IS Property] {
+                name,
+                target := .target.name,
+                required,
+                cardinality := <str>.cardinality,
+                readonly,
+                constraints := (
+                    SELECT .constraints { name, expr := .finalexpr }
+                ),
+            }
+            ORDER BY .name
+        ),
+        links := (
+            SELECT .pointers[IS Link] {
+                name,
+                target := .target.name,
+                required,
+                cardinality := <str>.cardinality,
+                readonly,
+                constraints := (
+                    SELECT .constraints { name, expr := .finalexpr }
+                ),
+            }
+            FILTER .name != '__type__'
+            ORDER BY .name
+        ),
+        constraints := (
+            SELECT .constraints { name, expr := .finalexpr }
+        ),
+        indexes := (SELECT .indexes { expr }),
+    })
+"###;
 
 pub async fn describe(
     cli: &mut Connection,
     options: &Options,
     name: &str,
     verbose: bool,
+    format: DescribeFormat,
+) -> Result<(), anyhow::Error> {
+    match format {
+        DescribeFormat::Sdl => describe_sdl(cli, options, name, verbose).await,
+        DescribeFormat::Json => {
+            let object = object_type(cli, name).await?;
+            println!("{}", serde_json::to_string_pretty(&object)?);
+            Ok(())
+        }
+        DescribeFormat::Table => {
+            let object = object_type(cli, name).await?;
+            print_table(&object);
+            Ok(())
+        }
+    }
+}
+
+async fn describe_sdl(
+    cli: &mut Connection,
+    options: &Options,
+    name: &str,
+    verbose: bool,
 ) -> Result<(), anyhow::Error> {
     let items = cli
         .query::<String, _>(
@@ -30,3 +136,92 @@ pub async fn describe(
     }
     Ok(())
 }
+
+async fn object_type(cli: &mut Connection, name: &str) -> anyhow::Result<ObjectType> {
+    let data = cli
+        .query_single::<String, _>(OBJECT_TYPE_QUERY, &(name,))
+        .await?;
+    let Some(data) = data else {
+        return Err(anyhow::anyhow!("object type {name:?} not found")
+            .hint(
+                "Only object types can be described as `json` or `table`, \
+                 use `--format=sdl` for other objects.",
+            )
+            .into());
+    };
+    Ok(serde_json::from_str(&data)?)
+}
+
+fn print_table(object: &ObjectType) {
+    let kind = if object.is_abstract {
+        "Abstract object type"
+    } else {
+        "Object type"
+    };
+    if object.bases.is_empty() {
+        println!("{kind} {}", object.name);
+    } else {
+        println!(
+            "{kind} {} extending {}",
+            object.name,
+            object.bases.join(", ")
+        );
+    }
+
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Kind", "Name", "Type", "Cardinality", "Constraints"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    let pointers = object
+        .properties
+        .iter()
+        .map(|p| ("property", p))
+        .chain(object.links.iter().map(|p| ("link", p)));
+    for (kind, pointer) in pointers {
+        let mut cardinality = String::new();
+        if pointer.readonly {
+            cardinality.push_str("readonly ");
+        }
+        if pointer.required {
+            cardinality.push_str("required ");
+        }
+        cardinality.push_str(&pointer.cardinality.to_lowercase());
+        table.add_row(Row::new(vec![
+            Cell::new(kind),
+            Cell::new(&pointer.name),
+            Cell::new(&pointer.target),
+            Cell::new(&cardinality),
+            Cell::new(
+                &pointer
+                    .constraints
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        ]));
+    }
+    for constraint in &object.constraints {
+        table.add_row(Row::new(vec![
+            Cell::new("constraint"),
+            Cell::new(&constraint.name),
+            Cell::new(""),
+            Cell::new(""),
+            Cell::new(constraint.expr.as_deref().unwrap_or("")),
+        ]));
+    }
+    for index in &object.indexes {
+        table.add_row(Row::new(vec![
+            Cell::new("index"),
+            Cell::new(&index.expr),
+            Cell::new(""),
+            Cell::new(""),
+            Cell::new(""),
+        ]));
+    }
+    table.printstd();
+}
@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
-use indicatif::{HumanBytes, ProgressBar};
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressDrawTarget};
 use sha1::Digest;
 use tokio::fs::{self, OpenOptions};
 use tokio::io::{self, AsyncWrite, AsyncWriteExt};
@@ -17,17 +17,32 @@ use crate::commands::Options;
 use crate::connect::Connection;
 use crate::hint::HintExt;
 use crate::platform::tmp_file_name;
+use crate::print::{self, msg};
+use crate::upload::{self, Upload};
 
 type Output = Box<dyn AsyncWrite + Unpin + Send>;
 
+#[derive(Default)]
 pub struct Guard {
     filenames: Option<(PathBuf, PathBuf, bool)>,
+    upload: Option<Upload>,
 }
 
 impl Guard {
     async fn open(filename: &Path, overwrite_existing: bool) -> anyhow::Result<(Output, Guard)> {
         if filename.to_str() == Some("-") {
-            Ok((Box::new(io::stdout()), Guard { filenames: None }))
+            Ok((Box::new(io::stdout()), Guard::default()))
+        } else if upload::is_url(filename) {
+            let (output, upload) = upload::start(filename, overwrite_existing)
+                .await
+                .with_context(|| format!("cannot upload to {}", filename.display()))?;
+            Ok((
+                Box::new(output),
+                Guard {
+                    filenames: None,
+                    upload: Some(upload),
+                },
+            ))
         } else if cfg!(windows) || filename.starts_with("/dev/") || filename.file_name().is_none() {
             let file = OpenOptions::new()
                 .write(true)
@@ -37,7 +52,7 @@ impl Guard {
                 .open(&filename)
                 .await
                 .context(filename.display().to_string())?;
-            Ok((Box::new(file), Guard { filenames: None }))
+            Ok((Box::new(file), Guard::default()))
         } else {
             if !overwrite_existing && fs::metadata(&filename).await.is_ok() {
                 anyhow::bail!(
@@ -56,12 +71,17 @@ impl Guard {
                 Box::new(tmp_file),
                 Guard {
                     filenames: Some((tmp_path, filename.to_owned(), overwrite_existing)),
+                    upload: None,
                 },
             ))
         }
     }
 
+    /// Output must be shut down before commit
     async fn commit(self) -> anyhow::Result<()> {
+        if let Some(upload) = self.upload {
+            upload.commit().await?;
+        }
         if let Some((tmp_filename, filename, overwrite_existing)) = self.filenames {
             if overwrite_existing {
                 fs::rename(tmp_filename, filename).await?;
@@ -93,7 +113,14 @@ pub async fn dump(
         } else {
             anyhow::bail!("`--format=dir` is required when using `--all`");
         }
-        dump_all(cli, general, options.path.as_ref(), options.include_secrets).await
+        dump_all(
+            cli,
+            general,
+            options.path.as_ref(),
+            options.include_secrets,
+            options.concurrency.get(),
+        )
+        .await
     } else {
         if options.format.is_some() {
             anyhow::bail!("`--format` is reserved for dump using `--all`");
@@ -104,24 +131,26 @@ pub async fn dump(
             options.path.as_ref(),
             options.include_secrets,
             options.overwrite_existing,
+            None,
         )
         .await
     }
 }
 
-async fn dump_db(
+pub async fn dump_db(
     cli: &mut Connection,
     _options: &Options,
     filename: &Path,
     mut include_secrets: bool,
     overwrite_existing: bool,
+    progress: Option<&MultiProgress>,
 ) -> Result<(), anyhow::Error> {
     if cli.get_version().await?.specific() < "4.0-alpha.2".parse().unwrap() {
         include_secrets = false;
     }
 
     let dbname = cli.database().to_string();
-    eprintln!("Starting dump for database `{dbname}`...");
+    msg!("Starting dump for database `{dbname}`...");
 
     let (mut output, guard) = Guard::open(filename, overwrite_existing).await?;
     output
@@ -144,7 +173,10 @@ async fn dump_db(
     output.write_all(&header_buf).await?;
     output.write_all(&header.data).await?;
 
-    let bar = ProgressBar::new_spinner();
+    let bar = match progress {
+        Some(progress) => progress.add(ProgressBar::new_spinner()),
+        None => print::progress(ProgressBar::new_spinner()),
+    };
     let mut processed = 0;
 
     while let Some(packet) = blocks.next().await.transpose()? {
@@ -167,6 +199,7 @@ async fn dump_db(
         output.write_all(&header_buf).await?;
         output.write_all(&packet.data).await?;
     }
+    output.shutdown().await?;
     guard.commit().await?;
     bar.abandon_with_message(format!(
         "Finished dump for `{dbname}`. Total size: {}",
@@ -180,6 +213,7 @@ pub async fn dump_all(
     options: &Options,
     dir: &Path,
     include_secrets: bool,
+    concurrency: usize,
 ) -> Result<(), anyhow::Error> {
     let databases = get_databases(cli).await?;
     let config: String = cli
@@ -187,9 +221,11 @@ pub async fn dump_all(
         .await?;
     let roles: String = cli.query_required_single("DESCRIBE ROLES", &()).await?;
 
-    fs::create_dir_all(dir).await?;
+    if !upload::is_url(dir) {
+        fs::create_dir_all(dir).await?;
+    }
 
-    let (mut init, guard) = Guard::open(&dir.join("init.edgeql"), true).await?;
+    let (mut init, guard) = Guard::open(&upload::join(dir, "init.edgeql"), true).await?;
     if !config.trim().is_empty() {
         init.write_all(b"# DESCRIBE SYSTEM CONFIG\n").await?;
         init.write_all(config.as_bytes()).await?;
@@ -200,26 +236,60 @@ pub async fn dump_all(
         init.write_all(roles.as_bytes()).await?;
         init.write_all(b"\n").await?;
     }
+    init.shutdown().await?;
     guard.commit().await?;
 
+    if concurrency <= 1 {
+        for database in &databases {
+            dump_branch(options, dir, database, include_secrets, None).await?;
+        }
+    } else {
+        // Every branch is dumped over its own connection, so each of the
+        // files is still a consistent snapshot of the respective branch.
+        let progress = MultiProgress::new();
+        if print::structured::is_json() {
+            progress.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        let dumps = tokio_stream::iter(&databases)
+            .map(|database| dump_branch(options, dir, database, include_secrets, Some(&progress)));
+        let mut dumps = futures_util::StreamExt::buffer_unordered(dumps, concurrency);
+        while let Some(result) = dumps.next().await {
+            result?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn dump_branch(
+    options: &Options,
+    dir: &Path,
+    database: &str,
+    include_secrets: bool,
+    progress: Option<&MultiProgress>,
+) -> Result<(), anyhow::Error> {
     let mut conn_params = options.conn_params.clone();
-    for database in &databases {
-        match conn_params.branch(database)?.connect().await {
-            Ok(mut db_conn) => {
-                let filename = dir.join(&(urlencoding::encode(database) + ".dump")[..]);
-                dump_db(&mut db_conn, options, &filename, include_secrets, true).await?;
-            }
-            Err(err) => {
-                if let Some(e) = err.downcast_ref::<gel_errors::Error>() {
-                    if e.is::<UnknownDatabaseError>() {
-                        eprintln!("Database {database} no longer exists, skipping...");
-                        continue;
-                    }
+    match conn_params.branch(database)?.connect().await {
+        Ok(mut db_conn) => {
+            let filename = upload::join(dir, &(urlencoding::encode(database) + ".dump"));
+            dump_db(
+                &mut db_conn,
+                options,
+                &filename,
+                include_secrets,
+                true,
+                progress,
+            )
+            .await
+        }
+        Err(err) => {
+            if let Some(e) = err.downcast_ref::<gel_errors::Error>() {
+                if e.is::<UnknownDatabaseError>() {
+                    msg!("Database {database} no longer exists, skipping...");
+                    return Ok(());
                 }
-                return Err(err);
             }
+            Err(err)
         }
     }
-
-    Ok(())
 }
@@ -2,6 +2,7 @@ use crate::connect::Connection;
 use gel_tokio::server_params::{PostgresAddress, PostgresDsn};
 
 use crate::analyze;
+use crate::bench;
 use crate::branch;
 use crate::branding::BRANDING;
 use crate::commands;
@@ -68,6 +69,12 @@ pub async fn common(
         Analyze(c) => {
             analyze::command(cli, c).await?;
         }
+        Bench(c) => {
+            bench::command(cli, options, c).await?;
+        }
+        Statistics(c) => {
+            commands::statistics(cli, options, c).await?;
+        }
         Pgaddr => match cli.get_server_param::<PostgresAddress>() {
             Some(addr) => {
                 // < 6.x
@@ -88,7 +95,7 @@ pub async fn common(
         }
         Describe(c) => match &c.subcommand {
             DescribeCmd::Object(c) => {
-                commands::describe(cli, options, &c.name, c.verbose).await?;
+                commands::describe(cli, options, &c.name, c.verbose, c.format).await?;
             }
             DescribeCmd::Schema(_) => {
                 commands::describe_schema(cli, options).await?;
@@ -146,6 +153,9 @@ pub async fn common(
             MigrationCmd::UpgradeFormat(params) => {
                 migrations::upgrade_format(cli, options, params).await?;
             }
+            MigrationCmd::RebaseFiles(params) => {
+                migrations::rebase_files(cli, options, params).await?;
+            }
         },
     }
     Ok(branch::CommandResult::default())
@@ -6,6 +6,7 @@ use is_terminal::IsTerminal;
 use crate::commands::filter;
 use crate::commands::Options;
 use crate::connect::Connection;
+use crate::print::msg;
 use crate::table;
 
 #[derive(Queryable)]
@@ -90,11 +91,11 @@ pub async fn list_aliases(
         }
         if table.is_empty() {
             if let Some(pattern) = pattern {
-                eprintln!("No aliases found matching {pattern:?}");
+                msg!("No aliases found matching {pattern:?}");
             } else if !system {
-                eprintln!("No user-defined expression aliases found.");
+                msg!("No user-defined expression aliases found.");
             } else {
-                eprintln!("No aliases found.");
+                msg!("No aliases found.");
             }
         } else {
             table.printstd();
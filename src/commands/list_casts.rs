@@ -6,6 +6,7 @@ use is_terminal::IsTerminal;
 use crate::commands::filter;
 use crate::commands::Options;
 use crate::connect::Connection;
+use crate::print::msg;
 use crate::table;
 
 #[derive(Queryable)]
@@ -66,7 +67,7 @@ pub async fn list_casts<'x>(
         }
         if table.is_empty() {
             if let Some(pattern) = pattern {
-                eprintln!("No casts found matching {pattern:?}");
+                msg!("No casts found matching {pattern:?}");
             }
         } else {
             table.printstd();
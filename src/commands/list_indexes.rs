@@ -6,6 +6,7 @@ use is_terminal::IsTerminal;
 use crate::commands::filter;
 use crate::commands::Options;
 use crate::connect::Connection;
+use crate::print::msg;
 use crate::table;
 
 #[derive(Queryable)]
@@ -106,15 +107,15 @@ pub async fn list_indexes(
         }
         if table.is_empty() {
             if let Some(pattern) = pattern {
-                eprintln!("No indexes found matching {pattern:?}");
+                msg!("No indexes found matching {pattern:?}");
             } else if !verbose {
                 if options.command_line {
-                    eprintln!("No explicit indexes found. Try --verbose");
+                    msg!("No explicit indexes found. Try --verbose");
                 } else {
-                    eprintln!("No explicit indexes found. Try \\li -v");
+                    msg!("No explicit indexes found. Try \\li -v");
                 }
             } else {
-                eprintln!("No indexes found.");
+                msg!("No indexes found.");
             }
         } else {
             table.printstd();
@@ -7,6 +7,7 @@ use terminal_size::{terminal_size, Width};
 use crate::commands::filter;
 use crate::commands::Options;
 use crate::connect::Connection;
+use crate::print::msg;
 use crate::table;
 
 #[derive(Queryable)]
@@ -70,9 +71,9 @@ pub async fn list_object_types(
         }
         if table.is_empty() {
             if let Some(pattern) = pattern {
-                eprintln!("No object types found matching {pattern:?}");
+                msg!("No object types found matching {pattern:?}");
             } else if !system {
-                eprintln!(
+                msg!(
                     "No user-defined object types found. {}",
                     if options.command_line {
                         "Try --system"
@@ -24,18 +24,19 @@ pub mod options;
 pub mod parser;
 mod psql;
 mod restore;
+mod statistics;
 mod ui;
 
 pub use self::configure::configure;
 pub use self::describe::describe;
 pub use self::describe_schema::describe_schema;
-pub use self::dump::{dump, dump_all};
+pub use self::dump::{dump, dump_all, dump_branch, dump_db};
 pub use self::exit::ExitCode;
 pub use self::info::info;
 pub use self::list_aliases::list_aliases;
 pub use self::list_branches::list_branches;
 pub use self::list_casts::list_casts;
-pub use self::list_databases::list_databases;
+pub use self::list_databases::{get_databases, list_databases};
 pub use self::list_indexes::list_indexes;
 pub use self::list_modules::list_modules;
 pub use self::list_object_types::list_object_types;
@@ -43,5 +44,6 @@ pub use self::list_roles::list_roles;
 pub use self::list_scalar_types::list_scalar_types;
 pub use self::options::Options;
 pub use self::psql::psql;
-pub use self::restore::{restore, restore_all};
+pub use self::restore::{restore, restore_all, restore_all_resumable, restore_db};
+pub use self::statistics::statistics;
 pub use self::ui::show_ui;
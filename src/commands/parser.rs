@@ -1,8 +1,10 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 use crate::branding::BRANDING_CLI_CMD;
 use crate::migrations::options::{Migrate, Migration};
 use crate::options::ConnectionOptions;
+use crate::portable::options::InstanceName;
 use crate::repl::{self, VectorLimit};
 
 use const_format::concatcp;
@@ -33,6 +35,10 @@ pub enum Common {
     List(List),
     /// Analyze performance of query in quotes (e.g. `"select 9;"`)
     Analyze(Analyze),
+    /// Measure latency and throughput of queries
+    Bench(Bench),
+    /// Show object counts and approximate disk usage of the current branch
+    Statistics(Statistics),
     /// Show PostgreSQL address. Works on dev-mode database only.
     #[command(hide = true)]
     Pgaddr,
@@ -98,6 +104,81 @@ pub struct Analyze {
     /// Show detailed output of analyze command
     #[arg(long)]
     pub expand: bool,
+
+    /// Output format: `tree` (default), `table` (a flat list of shape
+    /// elements), `json` (raw output of the server) or `flamegraph`
+    /// (folded stacks of the coarse-grained plan for flame graph viewers)
+    #[arg(long, value_enum, default_value = "tree")]
+    #[arg(conflicts_with = "debug_output_file")]
+    pub format: AnalyzeFormat,
+
+    /// Write output to specified file instead of stdout
+    /// (`--format=json` and `flamegraph` only)
+    #[arg(long, conflicts_with = "debug_output_file")]
+    pub output_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnalyzeFormat {
+    Tree,
+    Table,
+    Json,
+    Flamegraph,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Bench {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Query to benchmark
+    #[arg(required_unless_present = "file")]
+    pub query: Option<String>,
+
+    /// Benchmark each query from the file separately.
+    /// Pass `--file -` to read queries from stdin.
+    #[arg(short = 'f', long, conflicts_with = "query")]
+    pub file: Option<PathBuf>,
+
+    /// Number of times each query is run
+    #[arg(short = 'n', long, default_value = "100")]
+    pub iterations: NonZeroUsize,
+
+    /// Number of connections running queries concurrently
+    #[arg(long, default_value = "1")]
+    pub concurrency: NonZeroUsize,
+
+    /// Number of runs of each query before measurements start
+    #[arg(long, default_value = "0")]
+    pub warmup: usize,
+
+    /// Do not run `analyze` to get the execution time reported by the server
+    #[arg(long)]
+    pub no_server_time: bool,
+
+    /// Print results as JSON, e.g. for tracking regressions in CI
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Statistics {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Only show object types matching the pattern
+    pub pattern: Option<String>,
+
+    #[arg(long, short = 'c')]
+    pub case_sensitive: bool,
+
+    /// Include system object types
+    #[arg(long, short = 's')]
+    pub system: bool,
+
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Subcommand, Clone, Debug)]
@@ -187,15 +268,36 @@ pub enum BackslashCmd {
     Help,
     LastError,
     Expand,
+    View(View),
     DebugState(StateParam),
     DebugStateDesc(StateParam),
-    History,
+    History(ShowHistory),
     Connect(Connect),
     Edit(Edit),
     Set(SetCommand),
+    /// List settings with the origin of their values
+    Settings,
+    /// List tables (SQL mode only)
+    ListTables(ListTables),
     Exit,
 }
 
+#[derive(clap::Args, Clone, Debug)]
+pub struct View {
+    /// Open the result in `$EDITOR` instead of `$PAGER`
+    #[arg(short = 'e', long)]
+    pub editor: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ListTables {
+    pub pattern: Option<String>,
+    #[arg(long, short = 'c')]
+    pub case_sensitive: bool,
+    #[arg(long, short = 's')]
+    pub system: bool,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct StateParam {
     /// Show base state (before transaction) instead of current transaction
@@ -208,6 +310,12 @@ pub struct StateParam {
 
 #[derive(clap::Args, Clone, Debug)]
 pub struct SetCommand {
+    /// Also save the value to the global configuration file
+    #[arg(long, conflicts_with = "save_project")]
+    pub save: bool,
+    /// Also save the value to the settings of the current project
+    #[arg(long)]
+    pub save_project: bool,
     #[command(subcommand)]
     pub setting: Option<Setting>,
 }
@@ -239,11 +347,29 @@ pub enum Setting {
     HistorySize(SettingUsize),
     /// Print statistics on each query
     PrintStats(PrintStats),
+    /// Show long query results in a pager (`$PAGER` or `less`).
+    /// One of: on, off, always
+    Pager(Pager),
+    /// Show round-trip time, rows returned and bytes received after
+    /// each query
+    Stats(SettingBool),
     /// Set idle transaction timeout in Duration format.
     /// Default is 5 minutes; specify 0 to disable.
     IdleTransactionTimeout(IdleTransactionTimeout),
 }
 
+impl Setting {
+    /// Key of the setting in the `[shell]` table of the configuration
+    /// files, if it can be configured there
+    pub fn config_key(&self) -> Option<&'static str> {
+        match self {
+            Setting::Language(_) => Some("input-language"),
+            Setting::VectorDisplayLength(_) => None,
+            _ => Some(self.name()),
+        }
+    }
+}
+
 #[derive(clap::Args, Clone, Debug, Default)]
 pub struct Language {
     #[arg(value_name = "lang")]
@@ -302,9 +428,31 @@ pub struct PrintStats {
     pub value: Option<repl::PrintStats>,
 }
 
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct Pager {
+    #[arg(value_name = "mode")]
+    pub value: Option<repl::Pager>,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ShowHistory {
+    /// Show only entries containing this text
+    pub pattern: Option<String>,
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct Connect {
-    pub database_name: String,
+    /// Instance to connect to
+    #[arg(short = 'I', long)]
+    pub instance: Option<InstanceName>,
+
+    /// DSN to connect to
+    #[arg(long, conflicts_with = "instance")]
+    pub dsn: Option<String>,
+
+    /// Branch to connect to. When connecting to another instance, its
+    /// default branch is used if not specified
+    pub database_name: Option<String>,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -384,6 +532,17 @@ pub struct DescribeObject {
     pub name: String,
     #[arg(long, short = 'v')]
     pub verbose: bool,
+    /// Output format. `json` and `table` are supported for object types
+    /// only and list their properties, links, constraints and indexes
+    #[arg(long, value_enum, default_value = "sdl")]
+    pub format: DescribeFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DescribeFormat {
+    Sdl,
+    Json,
+    Table,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -395,12 +554,19 @@ pub enum DumpFormat {
 }
 
 #[derive(clap::Args, Clone, Debug)]
+#[command(mut_arg("branch", |arg| arg
+    .hide(false)
+    .help("Branch to dump instead of the current one")
+    .conflicts_with("all")))]
 pub struct Dump {
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
     /// Path to file write dump to (or directory if `--all` is specified).
-    /// Use dash `-` to write to stdout (latter does not work in `--all` mode)
+    /// Use dash `-` to write to stdout (latter does not work in `--all` mode).
+    /// An `s3://bucket/path` or `gs://bucket/path` URL uploads the dump to
+    /// object storage, credentials are read from `AWS_ACCESS_KEY_ID` and
+    /// `AWS_SECRET_ACCESS_KEY` (or `GCS_HMAC_ACCESS_ID` and `GCS_HMAC_SECRET`)
     #[arg(value_hint=clap::ValueHint::AnyPath)]
     pub path: PathBuf,
     /// Dump all databases and server configuration. `path` is a directory
@@ -422,6 +588,15 @@ pub struct Dump {
     /// to `true`.
     #[arg(long, default_value = "true")]
     pub overwrite_existing: bool,
+
+    /// Number of branches to dump in parallel when `--all` is specified.
+    ///
+    /// Only whole branches are dumped in parallel: each branch is still
+    /// dumped over a single connection, as a consistent snapshot of that
+    /// branch. Dumps of different branches are not taken at the same
+    /// point in time.
+    #[arg(long, default_value = "1", requires = "all")]
+    pub concurrency: NonZeroUsize,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -430,6 +605,11 @@ pub struct Dump {
      Pre 5.0: ", BRANDING_CLI_CMD, " restore -d <database-name> <path>\n    \
      >=5.0:   ", BRANDING_CLI_CMD, " restore -b <branch-name> <path>"
 )))]
+#[command(mut_arg("branch", |arg| arg
+    .hide(false)
+    .help("Branch to restore into instead of the current one. It must \
+           exist and be empty unless `--force-overwrite` is given")
+    .conflicts_with("all")))]
 pub struct Restore {
     #[command(flatten)]
     pub conn: Option<ConnectionOptions>,
@@ -444,6 +624,33 @@ pub struct Restore {
     #[arg(long)]
     pub all: bool,
 
+    /// Restore data only for the given object types. Schema is restored
+    /// in full. Accepts type names (`default::User`) or whole modules
+    /// (`default::*`); can be specified multiple times
+    #[arg(long, value_name = "type")]
+    pub include: Vec<String>,
+
+    /// Skip data of the given object types, e.g. large log tables. Schema
+    /// is restored in full. Links pointing to skipped objects are left
+    /// dangling. Accepts the same patterns as `--include`
+    #[arg(long, value_name = "type")]
+    pub exclude: Vec<String>,
+
+    /// Wipe the target branch before restoring if it is not empty.
+    /// Asks for confirmation unless `--non-interactive` is given
+    #[arg(long, conflicts_with_all = ["all", "into_new_branch"])]
+    pub force_overwrite: bool,
+
+    /// Create a new empty branch with the given name and restore into it,
+    /// leaving the current branch untouched
+    #[arg(long, value_name = "name", conflicts_with_all = ["all", "branch"])]
+    pub into_new_branch: Option<String>,
+
+    /// Do not ask for confirmation when wiping the target branch with
+    /// `--force-overwrite`
+    #[arg(long, requires = "force_overwrite")]
+    pub non_interactive: bool,
+
     /// Verbose output
     #[arg(long, short = 'v')]
     pub verbose: bool,
@@ -466,6 +673,10 @@ pub enum ConfigureCommand {
     Reset(ConfigureReset),
     /// Set scalar configuration value
     Set(ConfigureSet),
+    /// List configuration settings with their current and default values
+    List(ConfigureList),
+    /// Show current value of a configuration setting
+    Get(ConfigureGet),
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -486,6 +697,23 @@ pub struct ConfigureSet {
     pub parameter: ValueParameter,
 }
 
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigureList {
+    /// Output settings as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct ConfigureGet {
+    /// Name of the setting, e.g. `query_execution_timeout`
+    pub name: String,
+
+    /// Output the setting (with its type, default and description) as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum ListParameter {
     /// Insert a client authentication rule
@@ -9,7 +9,7 @@ use gel_tokio::server_params::{PostgresAddress, PostgresDsn};
 
 use crate::commands::Options;
 use crate::interrupt;
-use crate::print;
+use crate::print::{self, msg};
 
 pub async fn psql<'x>(cli: &mut Connection, _options: &Options) -> Result<(), anyhow::Error> {
     let mut cmd = Command::new("psql");
@@ -20,12 +20,12 @@ pub async fn psql<'x>(cli: &mut Connection, _options: &Options) -> Result<(), an
         if let Some(dir) = option_env!("PSQL_DEFAULT_PATH") {
             let psql_path = Path::new(dir).join("psql");
             if !psql_path.exists() {
-                eprintln!("WARNING: {} does not exist", psql_path.display());
+                msg!("WARNING: {} does not exist", psql_path.display());
             }
             let npath = if let Some(path) = env::var_os("PATH") {
                 env::join_paths(iter::once(PathBuf::from(dir)).chain(env::split_paths(&path)))
                     .unwrap_or_else(|e| {
-                        eprintln!("PSQL_DEFAULT_PATH error: {e}");
+                        msg!("PSQL_DEFAULT_PATH error: {e}");
                         path
                     })
             } else {
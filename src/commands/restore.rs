@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 use std::convert::TryInto;
 use std::ffi::OsString;
 use std::future::Future;
@@ -11,25 +11,36 @@ use std::time::Duration;
 use anyhow::Context as _;
 use bytes::{Bytes, BytesMut};
 use fn_error_context::context;
+use is_terminal::IsTerminal;
 use tokio::fs;
 use tokio::io::{self, AsyncRead, AsyncReadExt};
 use tokio_stream::Stream;
+use uuid::Uuid;
 
 use edgeql_parser::helpers::quote_name;
 use edgeql_parser::preparser::is_empty;
 use gel_errors::{Error, ErrorKind, UserError};
 
+use crate::branch::create::create_branch;
+use crate::branch::wipe;
 use crate::branding::BRANDING;
 use crate::commands::list_databases;
 use crate::commands::parser::Restore as RestoreCmd;
-use crate::commands::Options;
+use crate::commands::{ExitCode, Options};
 use crate::connect::Connection;
+use crate::hint::HintExt;
+use crate::portable::exit_codes;
+use crate::print::{self, msg, Highlight};
+use crate::question;
 use crate::statement::{read_statement, EndOfFile};
 
 type Input = Box<dyn AsyncRead + Unpin + Send>;
 
 const MAX_SUPPORTED_DUMP_VER: i64 = 1;
 
+/// Attribute of a data block containing the id of the dumped object
+const BLOCK_ID: u16 = 110;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PacketType {
     Header,
@@ -39,6 +50,118 @@ pub enum PacketType {
 pub struct Packets<'a> {
     input: &'a mut Input,
     buf: BytesMut,
+    /// Data blocks of these objects are not sent to the server
+    skip: HashSet<Uuid>,
+}
+
+/// Reader for the parts of dump packets needed to filter data blocks.
+struct Reader(Bytes);
+
+impl Reader {
+    fn bytes(&mut self, len: usize) -> anyhow::Result<Bytes> {
+        if self.0.len() < len {
+            anyhow::bail!("unexpected end of dump packet");
+        }
+        Ok(self.0.split_to(len))
+    }
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes(2)?[..].try_into().unwrap()))
+    }
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(4)?[..].try_into().unwrap()))
+    }
+    fn uuid(&mut self) -> anyhow::Result<Uuid> {
+        Ok(Uuid::from_slice(&self.bytes(16)?)?)
+    }
+    fn string(&mut self) -> anyhow::Result<String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8(self.bytes(len)?.to_vec())?)
+    }
+    fn attributes(&mut self) -> anyhow::Result<Vec<(u16, Bytes)>> {
+        let num = self.u16()?;
+        let mut result = Vec::with_capacity(num.into());
+        for _ in 0..num {
+            let code = self.u16()?;
+            let len = self.u32()? as usize;
+            result.push((code, self.bytes(len)?));
+        }
+        Ok(result)
+    }
+}
+
+fn type_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix("::*") {
+        Some(module) => name
+            .strip_prefix(module)
+            .map_or(false, |rest| rest.starts_with("::")),
+        None if pattern.contains("::") => pattern == name,
+        None => name.strip_prefix("default::") == Some(pattern),
+    }
+}
+
+/// Finds objects in the dump header whose data must be skipped.
+///
+/// Object types are selected by name, data of links and properties stored
+/// separately is skipped along with the types they depend on.
+#[context("cannot read dump header")]
+fn skipped_objects(header: &Bytes, params: &RestoreCmd) -> anyhow::Result<HashSet<Uuid>> {
+    let mut reader = Reader(header.clone());
+    reader.attributes()?;
+    let _major_ver = reader.u16()?;
+    let _minor_ver = reader.u16()?;
+    let _schema_ddl = reader.string()?;
+    let num_types = reader.u32()?;
+    let mut types = Vec::with_capacity(num_types as usize);
+    for _ in 0..num_types {
+        let name = reader.string()?;
+        let _class = reader.string()?;
+        let id = reader.uuid()?;
+        types.push((id, name));
+    }
+    let num_descriptors = reader.u32()?;
+    let mut descriptors = Vec::with_capacity(num_descriptors as usize);
+    for _ in 0..num_descriptors {
+        let id = reader.uuid()?;
+        let len = reader.u32()? as usize;
+        reader.bytes(len)?;
+        let num_deps = reader.u16()?;
+        let deps = (0..num_deps)
+            .map(|_| reader.uuid())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        descriptors.push((id, deps));
+    }
+
+    let dumped: HashSet<_> = descriptors.iter().map(|(id, _)| *id).collect();
+    let types: Vec<_> = types
+        .into_iter()
+        .filter(|(id, _)| dumped.contains(id))
+        .collect();
+    for pattern in params.include.iter().chain(&params.exclude) {
+        if !types.iter().any(|(_, name)| type_matches(pattern, name)) {
+            print::warn!("No object type in the dump matches {pattern:?}");
+        }
+    }
+    let excluded: HashSet<_> = types
+        .iter()
+        .filter(|(_, name)| {
+            let matches = |p: &String| type_matches(p, name);
+            (!params.include.is_empty() && !params.include.iter().any(matches))
+                || params.exclude.iter().any(matches)
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    let type_ids: HashSet<_> = types.iter().map(|(id, _)| *id).collect();
+    Ok(descriptors
+        .into_iter()
+        .filter(|(id, deps)| {
+            if type_ids.contains(id) {
+                excluded.contains(id)
+            } else {
+                deps.iter().any(|dep| excluded.contains(dep))
+            }
+        })
+        .map(|(id, _)| id)
+        .collect())
 }
 
 async fn read_packet(
@@ -98,10 +221,33 @@ async fn read_packet(
 
 impl Packets<'_> {
     async fn next(&mut self) -> Option<Result<Bytes, Error>> {
-        read_packet(self.input, &mut self.buf, PacketType::Block)
-            .await
-            .map_err(UserError::with_source_ref)
-            .transpose()
+        loop {
+            let packet = read_packet(self.input, &mut self.buf, PacketType::Block)
+                .await
+                .map_err(UserError::with_source_ref)
+                .transpose();
+            match packet {
+                Some(Ok(data)) if self.is_skipped(&data) => continue,
+                packet => return packet,
+            }
+        }
+    }
+
+    fn is_skipped(&self, block: &Bytes) -> bool {
+        if self.skip.is_empty() {
+            return false;
+        }
+        let attributes = match Reader(block.clone()).attributes() {
+            Ok(attributes) => attributes,
+            Err(e) => {
+                log::warn!("Cannot read dump block attributes: {e:#}");
+                return false;
+            }
+        };
+        attributes
+            .iter()
+            .filter(|(code, _)| *code == BLOCK_ID)
+            .any(|(_, id)| Uuid::from_slice(id).map_or(false, |id| self.skip.contains(&id)))
     }
 }
 
@@ -136,19 +282,116 @@ async fn is_non_empty_db(cli: &mut Connection) -> Result<bool, anyhow::Error> {
     return Ok(non_empty);
 }
 
+/// What to do when the target branch is not empty
+#[derive(Debug, Clone, Copy)]
+enum Conflict {
+    Overwrite,
+    NewBranch,
+    Abort,
+}
+
 pub async fn restore<'x>(
     cli: &mut Connection,
     options: &Options,
     params: &RestoreCmd,
 ) -> Result<(), anyhow::Error> {
     if params.all {
-        restore_all(cli, options, params).await
+        return restore_all(cli, options, params).await;
+    }
+    let new_branch_name = if params.into_new_branch.is_some() {
+        params.into_new_branch.clone()
+    } else if is_non_empty_db(cli).await? {
+        resolve_conflict(cli, params).await?
     } else {
-        restore_db(cli, options, params).await
+        None
+    };
+    let mut new_branch;
+    let cli = match new_branch_name {
+        Some(name) => {
+            new_branch = create_target_branch(cli, options, &name).await?;
+            &mut new_branch
+        }
+        None => cli,
+    };
+    restore_db(cli, options, params).await
+}
+
+/// Wipes the non-empty branch of the connection or picks a new branch to
+/// restore into, returning the name of the latter
+async fn resolve_conflict(
+    cli: &mut Connection,
+    params: &RestoreCmd,
+) -> anyhow::Result<Option<String>> {
+    let branch = cli.get_current_branch().await?.to_string();
+    // stdin is busy with the dump itself when reading it from `-`
+    let interactive = params.path.to_str() != Some("-") && std::io::stdin().is_terminal();
+    let choice = if params.force_overwrite {
+        Conflict::Overwrite
+    } else if interactive {
+        let mut q = question::Choice::new(format!(
+            "Branch {branch:?} is not empty. What do you want to do?"
+        ));
+        q.option(
+            Conflict::Overwrite,
+            &["w", "wipe"],
+            "wipe the branch and restore into it",
+        );
+        q.option(
+            Conflict::NewBranch,
+            &["n", "new"],
+            "create a new branch and restore into it",
+        );
+        q.option(Conflict::Abort, &["a", "abort"], "abort the restore");
+        cli.ping_while(q.async_ask()).await?
+    } else {
+        return Err(anyhow::anyhow!(
+            "cannot restore: the branch {branch:?} is not empty"
+        ))
+        .with_hint(|| {
+            "use `--force-overwrite` to wipe the branch first or \
+             `--into-new-branch <name>` to restore into a new branch"
+                .into()
+        })?;
+    };
+    match choice {
+        Conflict::Overwrite => {
+            if !params.non_interactive {
+                let q = question::Confirm::new_dangerous(format!(
+                    "Do you really want to wipe the contents of the branch {branch:?}?"
+                ));
+                if !cli.ping_while(q.async_ask()).await? {
+                    print::error!("Canceled by user.");
+                    return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+                }
+            }
+            wipe::reset(cli).await?;
+            msg!("Wiped branch {}", branch.emphasize());
+            Ok(None)
+        }
+        Conflict::NewBranch => {
+            let q = question::String::new("Name of the new branch");
+            Ok(Some(cli.ping_while(q.async_ask()).await?))
+        }
+        Conflict::Abort => {
+            print::error!("Canceled by user.");
+            Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into())
+        }
     }
 }
 
-async fn restore_db<'x>(
+/// Creates an empty branch and connects to it
+async fn create_target_branch(
+    cli: &mut Connection,
+    options: &Options,
+    name: &str,
+) -> anyhow::Result<Connection> {
+    msg!("Creating branch '{name}'...");
+    create_branch(cli, name, "", true, false).await?;
+    let mut connector = options.conn_params.clone();
+    connector.branch(name)?.connect().await
+}
+
+pub async fn restore_db<'x>(
     cli: &mut Connection,
     _options: &Options,
     params: &RestoreCmd,
@@ -157,6 +400,11 @@ async fn restore_db<'x>(
     let RestoreCmd {
         path: ref filename,
         all: _,
+        include: _,
+        exclude: _,
+        force_overwrite: _,
+        into_new_branch: _,
+        non_interactive: _,
         verbose: _,
         conn: _,
     } = *params;
@@ -173,7 +421,7 @@ async fn restore_db<'x>(
     } else {
         let file = fs::File::open(filename).await.with_context(file_ctx)?;
         let file_size = file.metadata().await?.len();
-        eprintln!(
+        msg!(
             "\nRestoring database from file `{}`. Total size: {:.02} MB",
             filename.display(),
             file_size as f64 / 1048576.0
@@ -202,11 +450,19 @@ async fn restore_db<'x>(
         .with_context(file_ctx)?
         .ok_or_else(|| anyhow::anyhow!("Dump is empty"))
         .with_context(file_ctx)?;
+    let skip = if params.include.is_empty() && params.exclude.is_empty() {
+        HashSet::new()
+    } else {
+        let skip = skipped_objects(&header, params).with_context(file_ctx)?;
+        msg!("Skipping data of {} object(s) in the dump", skip.len());
+        skip
+    };
     cli.restore(
         header,
         Packets {
             input: &mut input,
             buf,
+            skip,
         },
     )
     .await?;
@@ -248,12 +504,30 @@ pub async fn restore_all<'x>(
     cli: &mut Connection,
     options: &Options,
     params: &RestoreCmd,
+) -> anyhow::Result<()> {
+    restore_all_resumable(cli, options, params, &[], |_| Ok(())).await
+}
+
+/// Restores all databases except `completed` ones, calling `on_restored`
+/// after each database is restored.
+///
+/// When `completed` is not empty, the init script is assumed to be applied
+/// already, and databases that exist but are not completed are recreated,
+/// as their restore was interrupted.
+pub async fn restore_all_resumable(
+    cli: &mut Connection,
+    options: &Options,
+    params: &RestoreCmd,
+    completed: &[String],
+    mut on_restored: impl FnMut(&str) -> anyhow::Result<()>,
 ) -> anyhow::Result<()> {
     let dir = &params.path;
-    let filename = dir.join("init.edgeql");
-    apply_init(cli, filename.as_ref())
-        .await
-        .with_context(|| format!("error applying init file {filename:?}"))?;
+    if completed.is_empty() {
+        let filename = dir.join("init.edgeql");
+        apply_init(cli, filename.as_ref())
+            .await
+            .with_context(|| format!("error applying init file {filename:?}"))?;
+    }
 
     let mut conn_params = options.conn_params.clone();
     conn_params.wait_until_available(Duration::from_secs(300));
@@ -269,8 +543,26 @@ pub async fn restore_all<'x>(
             continue;
         }
         let database = path_to_database_name(&path)?;
+        if completed.contains(&database) {
+            log::debug!("Database {:?} is already restored", database);
+            continue;
+        }
         log::debug!("Restoring database {:?}", database);
-        if !existing.contains(&database) {
+        if existing.contains(&database) && !completed.is_empty() {
+            // Might be the database `cli` is connected to, so drop it
+            // from one that is known to be restored
+            conn_params.branch(&completed[0])?;
+            let mut other = conn_params.connect().await?;
+            for stmt in [
+                format!("DROP DATABASE {}", quote_name(&database)),
+                format!("CREATE DATABASE {}", quote_name(&database)),
+            ] {
+                other
+                    .execute(&stmt, &())
+                    .await
+                    .with_context(|| format!("error recreating database {database:?}"))?;
+            }
+        } else if !existing.contains(&database) {
             let stmt = format!("CREATE DATABASE {}", quote_name(&database));
             cli.execute(&stmt, &())
                 .await
@@ -285,6 +577,7 @@ pub async fn restore_all<'x>(
         restore_db(&mut db_conn, options, &params)
             .await
             .with_context(|| format!("restoring database {database:?}"))?;
+        on_restored(&database)?;
     }
     Ok(())
 }
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use edgeql_parser::helpers::quote_name;
+use gel_derive::Queryable;
+use gel_protocol::common::{
+    Capabilities, Cardinality, CompilationOptions, InputLanguage, IoFormat,
+};
+use gel_protocol::value::Value;
+use is_terminal::IsTerminal;
+use prettytable::{Cell, Row, Table};
+
+use crate::commands::filter;
+use crate::commands::parser::Statistics;
+use crate::commands::Options;
+use crate::connect::Connection;
+use crate::print::msg;
+use crate::table;
+
+#[derive(Queryable)]
+struct TypeRow {
+    name: String,
+    indexes: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct TypeStats {
+    name: String,
+    objects: i64,
+    indexes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    table_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_size: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BranchStats {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<i64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JsonStats {
+    branch: BranchStats,
+    types: Vec<TypeStats>,
+}
+
+struct Sizes {
+    branch: i64,
+    relations: HashMap<String, (i64, i64)>,
+}
+
+pub async fn statistics(
+    cli: &mut Connection,
+    options: &Options,
+    cmd: &Statistics,
+) -> Result<(), anyhow::Error> {
+    let mut filters = vec!["NOT .is_compound_type AND NOT .is_from_alias"];
+    if !cmd.system {
+        filters.push(r#"NOT re_test("^(?:std|schema|math|sys|cfg|cal|stdgraphql|ext)::", .name)"#);
+    }
+    if cmd.pattern.is_some() {
+        filters.push("re_test(<str>$0, .name)");
+    }
+    let query = format!(
+        r###"
+        WITH MODULE schema
+        SELECT ObjectType {{
+            name,
+            indexes := count(.indexes),
+        }}
+        FILTER ({filter})
+        ORDER BY .name;
+    "###,
+        filter = filters.join(") AND (")
+    );
+    let types = filter::query::<TypeRow>(cli, &query, &cmd.pattern, cmd.case_sensitive).await?;
+
+    let sizes = match read_sizes(cli).await {
+        Ok(sizes) => Some(sizes),
+        Err(e) => {
+            log::info!("Cannot read disk usage: {e:#}");
+            None
+        }
+    };
+
+    let mut stats = Vec::with_capacity(types.len());
+    for item in types {
+        let objects = cli
+            .query_required_single::<i64, _>(
+                &format!(
+                    "SELECT count((SELECT {} FILTER .__type__.name = <str>$0))",
+                    quote_type_name(&item.name)
+                ),
+                &(&item.name,),
+            )
+            .await?;
+        let (table_size, index_size) = sizes
+            .as_ref()
+            .and_then(|s| s.relations.get(&item.name))
+            .map(|&(t, i)| (Some(t), Some(i)))
+            .unwrap_or_default();
+        stats.push(TypeStats {
+            name: item.name,
+            objects,
+            indexes: item.indexes,
+            table_size,
+            index_size,
+        });
+    }
+    let branch = BranchStats {
+        name: cli.get_current_branch().await?.into_owned(),
+        size: sizes.as_ref().map(|s| s.branch),
+    };
+
+    if cmd.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&JsonStats {
+                branch,
+                types: stats,
+            })?
+        );
+    } else if !options.command_line || std::io::stdout().is_terminal() {
+        let mut table = Table::new();
+        table.set_format(*table::FORMAT);
+        table.set_titles(Row::new(
+            ["Type", "Objects", "Indexes", "Table Size", "Index Size"]
+                .iter()
+                .map(|x| table::header_cell(x))
+                .collect(),
+        ));
+        for item in &stats {
+            table.add_row(Row::new(vec![
+                Cell::new(&item.name),
+                Cell::new(&item.objects.to_string()).style_spec("r"),
+                Cell::new(&item.indexes.to_string()).style_spec("r"),
+                Cell::new(&format_size(item.table_size)).style_spec("r"),
+                Cell::new(&format_size(item.index_size)).style_spec("r"),
+            ]));
+        }
+        if table.is_empty() {
+            if let Some(pattern) = &cmd.pattern {
+                msg!("No object types found matching {pattern:?}");
+            } else {
+                msg!("No object types found.");
+            }
+        } else {
+            table.printstd();
+        }
+        println!(
+            "Branch {:?}: {}",
+            branch.name,
+            match branch.size {
+                Some(size) => format_size(Some(size)),
+                None => "disk usage is not available".into(),
+            }
+        );
+    } else {
+        for item in &stats {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                item.name,
+                item.objects,
+                item.indexes,
+                item.table_size.map(|x| x.to_string()).unwrap_or_default(),
+                item.index_size.map(|x| x.to_string()).unwrap_or_default(),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn quote_type_name(name: &str) -> String {
+    name.split("::")
+        .map(quote_name)
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Reads approximate disk usage through the SQL interface. Only servers
+/// supporting SQL over the native protocol (6.0+) provide this.
+async fn read_sizes(cli: &mut Connection) -> anyhow::Result<Sizes> {
+    let rows = query_sql(
+        cli,
+        r###"
+        SELECT
+            n.nspname || '::' || c.relname,
+            pg_table_size(c.oid),
+            pg_indexes_size(c.oid)
+        FROM pg_catalog.pg_class c
+        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind = 'r'
+        "###,
+    )
+    .await?;
+    let mut relations = HashMap::with_capacity(rows.len());
+    for row in rows {
+        if let [Some(Value::Str(name)), Some(Value::Int64(table)), Some(Value::Int64(index))] =
+            &row[..]
+        {
+            relations.insert(name.clone(), (*table, *index));
+        }
+    }
+    let branch = query_sql(cli, "SELECT pg_database_size(current_database())")
+        .await?
+        .into_iter()
+        .find_map(|row| match row.first() {
+            Some(Some(Value::Int64(size))) => Some(*size),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow::anyhow!("no database size returned"))?;
+    Ok(Sizes { branch, relations })
+}
+
+async fn query_sql(cli: &mut Connection, query: &str) -> anyhow::Result<Vec<Vec<Option<Value>>>> {
+    let flags = CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: true,
+        allow_capabilities: Capabilities::empty(),
+        input_language: InputLanguage::SQL,
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+    };
+    let desc = cli.parse(&flags, query).await?;
+    let mut items = cli
+        .execute_stream::<Value, _>(&flags, query, &desc, &())
+        .await?;
+    let mut rows = Vec::new();
+    while let Some(item) = items.next_element().await {
+        if let Value::Object { fields, .. } = item {
+            rows.push(fields);
+        }
+    }
+    items.complete().await?;
+    Ok(rows)
+}
+
+fn format_size(size: Option<i64>) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let Some(size) = size else {
+        return "-".into();
+    };
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
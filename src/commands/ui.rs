@@ -1,11 +1,14 @@
 use std::io::{stdout, Write};
 
 use anyhow::Context;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 
 use crate::branding::{BRANDING, BRANDING_CLI_CMD};
 use crate::browser::open_link;
 use crate::cloud;
 use crate::commands::ExitCode;
+use crate::connect;
 use crate::options::{Options, UI};
 use crate::portable::local;
 use crate::portable::repository::USER_AGENT;
@@ -62,9 +65,20 @@ fn get_cloud_ui_url(
 }
 
 fn get_local_ui_url(cmd: &UI, cfg: &gel_tokio::Config) -> anyhow::Result<String> {
-    let secret_key = _get_local_ui_secret_key(cfg)?;
+    let mut secret_key = _get_local_ui_secret_key(cfg)?;
     let mut url = _get_local_ui_url(cmd, cfg)?;
 
+    if secret_key.is_none() {
+        // remote instance without a secret key, exchange the password
+        // for a token so that the UI doesn't ask to log in
+        secret_key = get_remote_ui_token(cfg)
+            .map_err(|e| {
+                log::warn!("Cannot obtain authToken: {:#}", e);
+            })
+            .ok()
+            .flatten();
+    }
+
     if let Some(secret_key) = secret_key {
         url = format!("{url}?authToken={secret_key}");
     }
@@ -87,7 +101,7 @@ fn _get_local_ui_url(cmd: &UI, cfg: &gel_tokio::Config) -> anyhow::Result<String
                 .http_url(true)
                 .map(|u| u + "/ui")
                 .context("connected via unix socket")?;
-            match open_url(&https_url).map(|r| r.status()) {
+            match open_url(&https_url, use_proxy(cfg)).map(|r| r.status()) {
                 Ok(reqwest::StatusCode::OK) => {
                     url = https_url;
                     use_https = true;
@@ -101,7 +115,7 @@ fn _get_local_ui_url(cmd: &UI, cfg: &gel_tokio::Config) -> anyhow::Result<String
             }
         }
         if !use_https {
-            match open_url(&url).map(|r| r.status()) {
+            match open_url(&url, use_proxy(cfg)).map(|r| r.status()) {
                 Ok(reqwest::StatusCode::OK) => {}
                 Ok(reqwest::StatusCode::NOT_FOUND) => {
                     print::error!("Web UI not served correctly by specified {BRANDING} server.");
@@ -162,12 +176,21 @@ fn _get_local_ui_secret_key(cfg: &gel_tokio::Config) -> anyhow::Result<Option<St
     }
 }
 
+/// Remote instances are often reachable only through a proxy, while local
+/// ones must never go through it
+fn use_proxy(cfg: &gel_tokio::Config) -> bool {
+    !connect::is_loopback(cfg.host().unwrap_or("localhost"))
+}
+
 #[tokio::main(flavor = "current_thread")]
-async fn open_url(url: &str) -> Result<reqwest::Response, reqwest::Error> {
-    reqwest::Client::builder()
+async fn open_url(url: &str, use_proxy: bool) -> Result<reqwest::Response, reqwest::Error> {
+    let mut builder = reqwest::Client::builder()
         .danger_accept_invalid_certs(true)
-        .danger_accept_invalid_hostnames(true)
-        .no_proxy()
+        .danger_accept_invalid_hostnames(true);
+    if !use_proxy {
+        builder = builder.no_proxy();
+    }
+    builder
         .build()?
         .get(url)
         .header(reqwest::header::USER_AGENT, USER_AGENT)
@@ -175,6 +198,96 @@ async fn open_url(url: &str) -> Result<reqwest::Response, reqwest::Error> {
         .await
 }
 
+/// Obtains a token using HTTP SCRAM authentication of the server
+#[tokio::main(flavor = "current_thread")]
+async fn get_remote_ui_token(cfg: &gel_tokio::Config) -> anyhow::Result<Option<String>> {
+    use gel_tokio::credentials::TlsSecurity;
+
+    let creds = cfg.as_credentials()?;
+    let Some(password) = &creds.password else {
+        return Ok(None);
+    };
+    let base = cfg.http_url(true).context("connected via unix socket")?;
+    let url = format!("{base}/auth/token");
+
+    let mut builder = reqwest::Client::builder();
+    if let Some(ca) = &creds.tls_ca {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(ca.as_bytes())?);
+    }
+    if matches!(creds.tls_security, TlsSecurity::Insecure) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if !use_proxy(cfg) {
+        builder = builder.no_proxy();
+    }
+    let client = builder.build()?;
+
+    let scram = scram::ScramClient::new(&creds.user, password, None);
+    let (scram, client_first) = scram.client_first();
+    let resp = client
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .header(
+            reqwest::header::AUTHORIZATION,
+            format!("SCRAM-SHA-256 data={}", STANDARD.encode(client_first)),
+        )
+        .send()
+        .await?;
+    let (sid, server_first) = parse_scram_header(
+        resp.headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)
+            .context("server does not support HTTP authentication")?,
+    )?;
+    let scram = scram
+        .handle_server_first(&server_first)
+        .context("SCRAM authentication failed")?;
+    let (scram, client_final) = scram.client_final();
+    let resp = client
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .header(
+            reqwest::header::AUTHORIZATION,
+            format!(
+                "SCRAM-SHA-256 sid={sid}, data={}",
+                STANDARD.encode(client_final)
+            ),
+        )
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        anyhow::bail!("authentication failed with status {status}");
+    }
+    let (_, server_final) = parse_scram_header(
+        resp.headers()
+            .get("authentication-info")
+            .context("no authentication info in response")?,
+    )?;
+    scram
+        .handle_server_final(&server_final)
+        .context("server signature is invalid")?;
+    Ok(Some(resp.text().await?))
+}
+
+/// Parses `SCRAM-SHA-256 sid=..., data=...` header into sid and decoded data
+fn parse_scram_header(header: &reqwest::header::HeaderValue) -> anyhow::Result<(String, String)> {
+    let text = header.to_str()?;
+    let params = text.strip_prefix("SCRAM-SHA-256").unwrap_or(text);
+    let mut sid = None;
+    let mut data = None;
+    for param in params.split(',') {
+        match param.trim().split_once('=') {
+            Some(("sid", value)) => sid = Some(value.to_string()),
+            Some(("data", value)) => data = Some(String::from_utf8(STANDARD.decode(value)?)?),
+            _ => {}
+        }
+    }
+    match (sid, data) {
+        (Some(sid), Some(data)) => Ok((sid, data)),
+        _ => anyhow::bail!("invalid authentication header: {text:?}"),
+    }
+}
+
 mod jwt {
 
     use base64::engine::general_purpose::URL_SAFE_NO_PAD;
@@ -1,19 +1,55 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use anyhow::Context as _;
 use fn_error_context::context;
 use gel_protocol::model::Duration;
 
 use crate::platform::config_dir;
+use crate::portable::project;
+use crate::print;
 use crate::repl;
+use crate::table;
 
 #[derive(Debug, Clone, Default, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Config {
     #[serde(skip, default)]
     pub file_name: Option<PathBuf>,
+    /// Project manifest whose `[cli]` table was applied on top of the
+    /// global configuration.
+    #[serde(skip, default)]
+    pub project_file: Option<PathBuf>,
+    /// File each of the `shell` values was read from, by key.
+    #[serde(skip, default)]
+    pub origins: BTreeMap<&'static str, PathBuf>,
     pub shell: ShellConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Settings of the Cloud profiles (`[cloud-profile.<name>]` tables).
+    #[serde(default)]
+    pub cloud_profile: BTreeMap<String, CloudProfileConfig>,
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Command {
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    /// Show effective CLI configuration.
+    Show(Show),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Show {
+    /// Show the file each value comes from.
+    #[arg(long)]
+    pub origin: bool,
 }
 
 #[derive(Debug, Clone, Default, serde::Deserialize)]
@@ -39,12 +75,97 @@ pub struct ShellConfig {
     pub display_typenames: Option<bool>,
     #[serde(with = "serde_str::opt", default)]
     pub print_stats: Option<repl::PrintStats>,
+    #[serde(with = "serde_str::opt", default)]
+    pub pager: Option<repl::Pager>,
+    #[serde(default)]
+    pub stats: Option<bool>,
     #[serde(default)]
     pub verbose_errors: Option<bool>,
 }
 
-pub fn get_config() -> anyhow::Result<Config> {
-    let path = config_dir()?.join("cli.toml");
+/// Settings for HTTP requests to external services, see [`crate::http`].
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub no_proxy: Option<bool>,
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CloudProfileConfig {
+    /// Organization of Cloud instance names given without `<org>/`,
+    /// set by `cloud org switch`.
+    #[serde(default)]
+    pub default_org: Option<String>,
+}
+
+impl ShellConfig {
+    /// Returns config with values from `over` taking precedence.
+    pub fn merge(self, over: ShellConfig) -> ShellConfig {
+        ShellConfig {
+            expand_strings: over.expand_strings.or(self.expand_strings),
+            history_size: over.history_size.or(self.history_size),
+            implicit_properties: over.implicit_properties.or(self.implicit_properties),
+            input_mode: over.input_mode.or(self.input_mode),
+            limit: over.limit.or(self.limit),
+            idle_transaction_timeout: over
+                .idle_transaction_timeout
+                .or(self.idle_transaction_timeout),
+            input_language: over.input_language.or(self.input_language),
+            output_format: over.output_format.or(self.output_format),
+            display_typenames: over.display_typenames.or(self.display_typenames),
+            print_stats: over.print_stats.or(self.print_stats),
+            pager: over.pager.or(self.pager),
+            stats: over.stats.or(self.stats),
+            verbose_errors: over.verbose_errors.or(self.verbose_errors),
+        }
+    }
+
+    pub fn values(&self) -> Vec<(&'static str, Option<String>)> {
+        vec![
+            ("expand-strings", self.expand_strings.map(|v| v.to_string())),
+            ("history-size", self.history_size.map(|v| v.to_string())),
+            (
+                "implicit-properties",
+                self.implicit_properties.map(|v| v.to_string()),
+            ),
+            ("input-mode", self.input_mode.map(|v| v.as_str().into())),
+            ("limit", self.limit.map(|v| v.to_string())),
+            (
+                "idle-transaction-timeout",
+                self.idle_transaction_timeout.map(|v| v.to_string()),
+            ),
+            (
+                "input-language",
+                self.input_language.map(|v| v.as_str().into()),
+            ),
+            (
+                "output-format",
+                self.output_format.map(|v| v.as_str().into()),
+            ),
+            (
+                "display-typenames",
+                self.display_typenames.map(|v| v.to_string()),
+            ),
+            ("print-stats", self.print_stats.map(|v| v.as_str().into())),
+            ("pager", self.pager.map(|v| v.as_str().into())),
+            ("stats", self.stats.map(|v| v.to_string())),
+            ("verbose-errors", self.verbose_errors.map(|v| v.to_string())),
+        ]
+    }
+}
+
+pub fn global_config_path() -> anyhow::Result<PathBuf> {
+    Ok(config_dir()?.join("cli.toml"))
+}
+
+fn get_global_config() -> anyhow::Result<Config> {
+    let path = global_config_path()?;
     if path.exists() {
         read_config(&path)
     } else {
@@ -52,6 +173,181 @@ pub fn get_config() -> anyhow::Result<Config> {
     }
 }
 
+/// Reads the `[cli]` table of the project manifest, if running inside
+/// a project.
+fn get_project_config() -> anyhow::Result<Option<(PathBuf, ShellConfig)>> {
+    let Some(location) = project::find_project(None)? else {
+        return Ok(None);
+    };
+    let cli = project::manifest::read_cli(&location.manifest)?;
+    Ok(cli.map(|cli| (location.manifest, cli)))
+}
+
+/// Settings file of the initialized project in the current directory,
+/// written by `\set --save-project`. Unlike the `[cli]` table of the
+/// manifest it is local to the machine.
+pub fn project_settings_path() -> anyhow::Result<Option<PathBuf>> {
+    let Some(location) = project::find_project(None)? else {
+        return Ok(None);
+    };
+    let stash_dir = gel_tokio::get_stash_path(&location.root)?;
+    if !stash_dir.exists() {
+        return Ok(None);
+    }
+    Ok(Some(stash_dir.join("cli.toml")))
+}
+
+fn get_project_settings() -> anyhow::Result<Option<(PathBuf, ShellConfig)>> {
+    match project_settings_path()? {
+        Some(path) if path.exists() => {
+            let config = read_config(&path)?;
+            Ok(Some((path, config.shell)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns global configuration, overridden by the project manifest and
+/// then by the project settings file.
+///
+/// Sources which can't be read are skipped, so an error in one file
+/// doesn't discard the others. Their errors are returned with the config.
+pub fn get_config() -> (Config, Vec<anyhow::Error>) {
+    combine(
+        get_global_config(),
+        get_project_config(),
+        get_project_settings(),
+    )
+}
+
+type Source = anyhow::Result<Option<(PathBuf, ShellConfig)>>;
+
+fn combine(
+    global: anyhow::Result<Config>,
+    project: Source,
+    settings: Source,
+) -> (Config, Vec<anyhow::Error>) {
+    let mut errors = Vec::new();
+    let mut config = global.unwrap_or_else(|e| {
+        errors.push(e);
+        Config::default()
+    });
+    if let Some(path) = &config.file_name {
+        record_origins(&mut config.origins, &config.shell, path);
+    }
+    match project {
+        Ok(Some((path, cli))) => {
+            record_origins(&mut config.origins, &cli, &path);
+            config.shell = config.shell.merge(cli);
+            config.project_file = Some(path);
+        }
+        Ok(None) => {}
+        Err(e) => errors.push(e),
+    }
+    match settings {
+        Ok(Some((path, shell))) => {
+            record_origins(&mut config.origins, &shell, &path);
+            config.shell = config.shell.merge(shell);
+        }
+        Ok(None) => {}
+        Err(e) => errors.push(e),
+    }
+    (config, errors)
+}
+
+fn record_origins(origins: &mut BTreeMap<&'static str, PathBuf>, shell: &ShellConfig, path: &Path) {
+    for (name, value) in shell.values() {
+        if value.is_some() {
+            origins.insert(name, path.to_path_buf());
+        }
+    }
+}
+
+/// Default organization of the Cloud `profile` from the global
+/// configuration file.
+pub fn default_cloud_org(profile: &str) -> anyhow::Result<Option<String>> {
+    let config = get_global_config()?;
+    Ok(config
+        .cloud_profile
+        .get(profile)
+        .and_then(|p| p.default_org.clone()))
+}
+
+/// Sets `key` in the `[shell]` table of the configuration file, keeping
+/// the rest of the file intact.
+pub fn save_shell_setting(path: &Path, key: &str, value: toml_edit::Value) -> anyhow::Result<()> {
+    save_setting(path, &["shell"], key, Some(value))
+}
+
+/// Sets (or removes if `value` is `None`) `key` in the nested table
+/// `tables` of the configuration file, keeping the rest of the file intact.
+#[context("cannot write {:?}", path)]
+pub fn save_setting(
+    path: &Path,
+    tables: &[&str],
+    key: &str,
+    value: Option<toml_edit::Value>,
+) -> anyhow::Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut doc: toml_edit::DocumentMut = text.parse()?;
+    let mut table = doc.as_table_mut();
+    for name in tables {
+        table = table
+            .entry(name)
+            .or_insert_with(|| {
+                // only print the header of the innermost table
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(true);
+                toml_edit::Item::Table(table)
+            })
+            .as_table_mut()
+            .with_context(|| format!("`{name}` is not a table"))?;
+    }
+    match value {
+        Some(value) => table[key] = toml_edit::value(value),
+        None => {
+            table.remove(key);
+        }
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, doc.to_string())?;
+    Ok(())
+}
+
+pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    match &cmd.subcommand {
+        Subcommand::Show(c) => show(c),
+    }
+}
+
+fn show(cmd: &Show) -> anyhow::Result<()> {
+    let (config, errors) = get_config();
+    for e in &errors {
+        print::warn!("Skipped config: {e:#}");
+    }
+    let mut rows = Vec::new();
+    for (name, value) in config.shell.values() {
+        let origin = config.origins.get(name).map(|p| p.display().to_string());
+        let value = value.unwrap_or_else(|| "(default)".into());
+        if cmd.origin {
+            rows.push((
+                name,
+                format!("{value}  [{}]", origin.as_deref().unwrap_or("default")),
+            ));
+        } else {
+            rows.push((name, value));
+        }
+    }
+    table::settings(rows.as_slice());
+    Ok(())
+}
+
 #[context("reading file {:?}", path.as_ref())]
 fn read_config(path: impl AsRef<Path>) -> anyhow::Result<Config> {
     let text = fs::read_to_string(&path)?;
@@ -77,3 +373,82 @@ where
         Ok(Some(rv))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{combine, Config, ShellConfig};
+
+    fn shell(text: &str) -> ShellConfig {
+        toml::from_str(text).unwrap()
+    }
+
+    fn global(text: &str) -> Config {
+        Config {
+            file_name: Some("cli.toml".into()),
+            ..toml::from_str(text).unwrap()
+        }
+    }
+
+    fn origin<'a>(config: &'a Config, name: &str) -> Option<&'a Path> {
+        config.origins.get(name).map(|p| p.as_path())
+    }
+
+    #[test]
+    fn precedence() {
+        let (config, errors) = combine(
+            Ok(global("[shell]\nlimit = 10\nexpand-strings = true\n")),
+            Ok(Some((
+                PathBuf::from("gel.toml"),
+                shell("limit = 20\nimplicit-properties = true\n"),
+            ))),
+            Ok(Some((
+                PathBuf::from("stash/cli.toml"),
+                shell("limit = 30\n"),
+            ))),
+        );
+        assert!(errors.is_empty());
+        assert_eq!(config.shell.limit, Some(30));
+        assert_eq!(config.shell.expand_strings, Some(true));
+        assert_eq!(config.shell.implicit_properties, Some(true));
+        assert_eq!(config.shell.verbose_errors, None);
+        assert_eq!(origin(&config, "limit"), Some(Path::new("stash/cli.toml")));
+        assert_eq!(
+            origin(&config, "expand-strings"),
+            Some(Path::new("cli.toml"))
+        );
+        assert_eq!(
+            origin(&config, "implicit-properties"),
+            Some(Path::new("gel.toml"))
+        );
+        assert_eq!(origin(&config, "verbose-errors"), None);
+        assert_eq!(config.project_file.as_deref(), Some(Path::new("gel.toml")));
+    }
+
+    #[test]
+    fn errors_keep_other_sources() {
+        let (config, errors) = combine(
+            Ok(global("[shell]\nlimit = 10\nexpand-strings = true\n")),
+            Err(anyhow::anyhow!("error reading project config `gel.toml`")),
+            Ok(Some((
+                PathBuf::from("stash/cli.toml"),
+                shell("limit = 30\n"),
+            ))),
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("gel.toml"));
+        assert_eq!(config.shell.limit, Some(30));
+        assert_eq!(config.shell.expand_strings, Some(true));
+        assert_eq!(config.project_file, None);
+
+        let (config, errors) = combine(
+            Err(anyhow::anyhow!("reading file \"cli.toml\"")),
+            Ok(Some((PathBuf::from("gel.toml"), shell("limit = 20\n")))),
+            Ok(None),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(config.shell.limit, Some(20));
+        assert_eq!(origin(&config, "limit"), Some(Path::new("gel.toml")));
+    }
+}
@@ -1,17 +1,22 @@
 use std::borrow::Cow;
 use std::error::Error as StdError;
+use std::fmt;
 use std::future::{pending, Future};
 use std::mem;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::Duration;
 
+use anyhow::Context as _;
 use bytes::Bytes;
 
 use tokio::time::sleep;
 use tokio_stream::Stream;
 
+use gel_errors::{
+    AuthenticationError, ClientConnectionTimeoutError, UnsupportedProtocolVersionError,
+};
 use gel_errors::{ClientError, NoDataError, ProtocolEncodingError};
 use gel_errors::{Error, ErrorKind, ResultExt};
 use gel_protocol::annotations::Warning;
@@ -27,13 +32,16 @@ use gel_protocol::server_message::RawPacket;
 use gel_protocol::server_message::TransactionState;
 use gel_protocol::value::Value;
 use gel_protocol::QueryResult;
+use gel_tokio::credentials::TlsSecurity;
 use gel_tokio::raw::{self, PoolState, Response};
 use gel_tokio::server_params::ServerParam;
 use gel_tokio::Config;
 
-use crate::branding::{BRANDING, BRANDING_CLOUD, QUERY_TAG, REPL_QUERY_TAG};
-use crate::hint::ArcError;
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD, QUERY_TAG, REPL_QUERY_TAG};
+use crate::hint::{ArcError, HintExt};
+use crate::portable::repository::USER_AGENT;
 use crate::portable::ver;
+use crate::print::msg;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConnectionError {
@@ -44,6 +52,46 @@ pub enum ConnectionError {
         your OS's firewall or any other firewalls you have installed"
     )]
     PermissionError(Error),
+    #[error("{kind}: {error}")]
+    Network {
+        kind: NetworkFailure,
+        error: Error,
+        hint: String,
+    },
+}
+
+/// Classified reason of a failed connection attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkFailure {
+    Dns,
+    Refused,
+    Timeout,
+    Tls,
+    Authentication,
+    Protocol,
+}
+
+impl ConnectionError {
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            ConnectionError::Network { hint, .. } => Some(hint),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NetworkFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use NetworkFailure::*;
+        f.write_str(match self {
+            Dns => "Cannot resolve host name",
+            Refused => "Connection refused",
+            Timeout => "Connection timed out",
+            Tls => "TLS handshake failed",
+            Authentication => "Authentication failed",
+            Protocol => "Protocol mismatch",
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -51,6 +99,29 @@ pub struct Connector {
     config: Result<Config, ArcError>,
 }
 
+/// Connection over the EdgeQL-over-HTTP endpoint of the server.
+///
+/// Useful in restricted environments where only HTTP(S) ports are reachable.
+/// Only JSON results are supported by the endpoint.
+pub struct HttpConnection {
+    client: reqwest::Client,
+    url: String,
+    secret_key: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct HttpQueryError {
+    message: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct HttpQueryResponse {
+    data: Option<Vec<serde_json::Value>>,
+    error: Option<HttpQueryError>,
+}
+
 pub struct Connection {
     inner: raw::Connection,
     server_version: Option<ver::Build>,
@@ -206,7 +277,7 @@ impl Connector {
         if interactive {
             eprint!("{msg}");
         } else {
-            eprintln!("{msg}");
+            msg!("{msg}");
         }
         pending().await
     }
@@ -214,6 +285,13 @@ impl Connector {
         self.config.as_ref().map_err(Clone::clone)
     }
 
+    /// Prepares a connection that runs queries over the EdgeQL-over-HTTP
+    /// endpoint of the server instead of the binary protocol.
+    pub fn connect_http(&self, tls: bool) -> anyhow::Result<HttpConnection> {
+        let cfg = self.config.as_ref().map_err(Clone::clone)?;
+        HttpConnection::new(cfg, tls)
+    }
+
     pub async fn run_single_query<R>(self, query: &str) -> Result<Vec<R>, anyhow::Error>
     where
         R: QueryResult,
@@ -228,10 +306,12 @@ impl Connection {
     pub async fn connect(cfg: &Config, tag: impl ToString) -> Result<Connection, ConnectionError> {
         let mut annotations = Annotations::new();
         annotations.insert("tag".to_string(), tag.to_string());
+        let inner = match raw::Connection::connect(cfg).await {
+            Ok(inner) => inner,
+            Err(err) => return Err(Self::map_connection_err(cfg, err).await),
+        };
         Ok(Connection {
-            inner: raw::Connection::connect(cfg)
-                .await
-                .map_err(Self::map_connection_err)?,
+            inner,
             state: State::empty(),
             server_version: None,
             config: cfg.clone(),
@@ -239,7 +319,7 @@ impl Connection {
         })
     }
 
-    fn map_connection_err(err: Error) -> ConnectionError {
+    async fn map_connection_err(cfg: &Config, err: Error) -> ConnectionError {
         if let Some(io_error) = err
             .source()
             .and_then(|v| v.downcast_ref::<std::io::Error>())
@@ -251,7 +331,24 @@ impl Connection {
             }
         }
 
-        ConnectionError::Error(err)
+        let kind = match classify_connection_err(&err) {
+            Some(kind) => Some(kind),
+            None if io_error(&err).is_some() && !host_resolves(cfg).await => {
+                Some(NetworkFailure::Dns)
+            }
+            None => None,
+        };
+        match kind {
+            Some(kind) => {
+                let hint = network_hint(cfg, kind);
+                ConnectionError::Network {
+                    kind,
+                    error: err,
+                    hint,
+                }
+            }
+            None => ConnectionError::Error(err),
+        }
     }
 
     pub fn database(&self) -> &str {
@@ -260,6 +357,9 @@ impl Connection {
     pub fn branch(&self) -> &str {
         self.config.branch()
     }
+    pub fn instance_name(&self) -> Option<&str> {
+        self.config.local_instance_name()
+    }
     pub fn set_ignore_error_state(&mut self) -> State {
         let new_state = make_ignore_error_state(self.inner.state_descriptor());
         mem::replace(&mut self.state, new_state)
@@ -530,6 +630,35 @@ impl Connection {
     }
 }
 
+/// Checks that an admin connection can go over TCP, used where the admin
+/// unix socket of the instance is unreachable (i.e. WSL instances on
+/// Windows).
+///
+/// Unlike the unix socket, loopback TCP is reachable by any local user,
+/// so the regular authentication is kept and only loopback hosts are
+/// allowed.
+pub fn check_admin_over_tcp(cfg: &Config) -> anyhow::Result<()> {
+    let host = cfg.host().unwrap_or("localhost");
+    if !is_loopback(host) {
+        return Err(anyhow::anyhow!(
+            "The --admin option over TCP is only supported for local connections, \
+             but the host is {host:?}"
+        )
+        .hint("Use `--unix-path` to connect to the admin socket instead.")
+        .into());
+    }
+    Ok(())
+}
+
+pub fn is_loopback(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .map_or(false, |ip| ip.is_loopback())
+}
+
 fn make_ignore_error_state(desc: &RawTypedesc) -> State {
     _make_ignore_error_state(desc).unwrap_or(State::empty())
 }
@@ -547,3 +676,350 @@ fn _make_ignore_error_state(desc: &RawTypedesc) -> Option<State> {
         .encode(desc)
         .ok()
 }
+
+impl HttpConnection {
+    fn new(cfg: &Config, tls: bool) -> anyhow::Result<HttpConnection> {
+        let base = cfg
+            .http_url(tls)
+            .ok_or_else(|| anyhow::anyhow!("HTTP transport is not supported for unix sockets"))?;
+        // The `db` path is supported by all server versions, unlike `branch`.
+        let branch = match cfg.branch() {
+            "__default__" => "main",
+            branch => branch,
+        };
+        let url = format!("{base}/db/{}/edgeql", urlencoding::encode(branch));
+        // the certificate is verified the same way as for the binary protocol
+        let creds = cfg.as_credentials()?;
+        let mut client = reqwest::Client::builder();
+        if let Some(pem) = &creds.tls_ca {
+            for cert in reqwest::Certificate::from_pem_bundle(pem.as_bytes())? {
+                client = client.add_root_certificate(cert);
+            }
+        }
+        client = match creds.tls_security {
+            TlsSecurity::Insecure => client.danger_accept_invalid_certs(true),
+            TlsSecurity::NoHostVerification => client.danger_accept_invalid_hostnames(true),
+            TlsSecurity::Default if creds.tls_ca.is_some() => {
+                client.danger_accept_invalid_hostnames(true)
+            }
+            _ => client,
+        };
+        let client = client.build()?;
+        Ok(HttpConnection {
+            client,
+            url,
+            secret_key: cfg.secret_key().map(|k| k.to_owned()),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn query_json(&self, query: &str) -> anyhow::Result<Vec<serde_json::Value>> {
+        let mut req = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .json(&serde_json::json!({ "query": query }));
+        if let Some(key) = &self.secret_key {
+            req = req.bearer_auth(key);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(anyhow::anyhow!("server rejected credentials ({status})")
+                .hint("HTTP transport authenticates using a secret key, pass it via `--secret-key`")
+                .into());
+        }
+        let body: HttpQueryResponse = resp
+            .json()
+            .await
+            .with_context(|| format!("unexpected response from {} ({status})", self.url))?;
+        match (body.data, body.error) {
+            (_, Some(err)) => match err.kind {
+                Some(kind) => anyhow::bail!("{kind}: {}", err.message),
+                None => anyhow::bail!("{}", err.message),
+            },
+            (Some(data), None) => Ok(data),
+            (None, None) => anyhow::bail!("server returned neither data nor error"),
+        }
+    }
+}
+
+fn classify_connection_err(err: &Error) -> Option<NetworkFailure> {
+    use std::io::ErrorKind as Io;
+
+    if err.is::<AuthenticationError>() {
+        return Some(NetworkFailure::Authentication);
+    }
+    if err.is::<UnsupportedProtocolVersionError>() {
+        return Some(NetworkFailure::Protocol);
+    }
+    if err.is::<ClientConnectionTimeoutError>() {
+        return Some(NetworkFailure::Timeout);
+    }
+    let mut source = err.source();
+    while let Some(cur) = source {
+        if cur.is::<rustls::Error>() {
+            return Some(NetworkFailure::Tls);
+        }
+        if let Some(io) = cur.downcast_ref::<std::io::Error>() {
+            if io
+                .get_ref()
+                .map_or(false, |inner| inner.is::<rustls::Error>())
+            {
+                return Some(NetworkFailure::Tls);
+            }
+            match io.kind() {
+                Io::ConnectionRefused => return Some(NetworkFailure::Refused),
+                Io::TimedOut => return Some(NetworkFailure::Timeout),
+                _ => {}
+            }
+        }
+        source = cur.source();
+    }
+    None
+}
+
+fn io_error(err: &Error) -> Option<&std::io::Error> {
+    let mut source = err.source();
+    while let Some(cur) = source {
+        if let Some(io) = cur.downcast_ref::<std::io::Error>() {
+            return Some(io);
+        }
+        source = cur.source();
+    }
+    None
+}
+
+/// Resolver errors have no error kind of their own, so an I/O error which
+/// is not classified otherwise is checked by resolving the host again.
+async fn host_resolves(cfg: &Config) -> bool {
+    let (Some(host), Some(port)) = (cfg.host(), cfg.port()) else {
+        return true;
+    };
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(mut addrs) => addrs.next().is_some(),
+        Err(e) => {
+            log::debug!("Cannot resolve {host:?}: {e}");
+            false
+        }
+    }
+}
+
+pub fn network_hint(cfg: &Config, kind: NetworkFailure) -> String {
+    use NetworkFailure::*;
+
+    let addr = cfg.display_addr().to_string();
+    let instance = cfg.local_instance_name().map(|n| n.to_string());
+    match (kind, instance) {
+        (Dns, _) => format!(
+            "Check that the host name in `{addr}` is correct \
+            and that your network and DNS settings work."
+        ),
+        (Refused, Some(name)) => format!(
+            "Instance `{name}` is not running or listens on a different port. \
+            Start it with `{BRANDING_CLI_CMD} instance start -I {name}` \
+            or check `{BRANDING_CLI_CMD} instance status -I {name}`."
+        ),
+        (Refused, None) => {
+            format!("Make sure the server is running and accepts connections at {addr}.")
+        }
+        (Timeout, _) => format!(
+            "The server at {addr} did not respond. Check firewall settings, \
+            or increase the timeout with `--wait-until-available`."
+        ),
+        (Tls, Some(name)) => format!(
+            "The certificate of instance `{name}` doesn't match \
+            the stored credentials. If the instance was re-created, \
+            re-link it with `{BRANDING_CLI_CMD} instance link {name} \
+            --overwrite --trust-tls-cert`."
+        ),
+        (Tls, None) => format!(
+            "If you trust the server at {addr}, pass its certificate \
+            with `--tls-ca-file` or use `--tls-security insecure` \
+            for testing. Run `{BRANDING_CLI_CMD} connection doctor` to see \
+            the certificate fingerprint."
+        ),
+        (Authentication, Some(name)) => format!(
+            "Credentials of instance `{name}` were rejected. Reset the \
+            password with `{BRANDING_CLI_CMD} instance reset-password -I {name}`."
+        ),
+        (Authentication, None) => format!(
+            "Check the user `{}` and the password or secret key.",
+            cfg.user()
+        ),
+        (Protocol, _) => format!(
+            "The server at {addr} uses an unsupported protocol version. \
+            Upgrade the server, or the CLI with `{BRANDING_CLI_CMD} cli upgrade`."
+        ),
+    }
+}
+
+/// Connects to `addr` with verification disabled, and returns the
+/// fingerprint of the certificate presented by the server. The result is
+/// only meant to be shown to the user for manual comparison, and the
+/// connection is closed right after the handshake.
+pub async fn server_cert_fingerprint(addr: &str) -> anyhow::Result<String> {
+    let addr = addr.to_string();
+    tokio::task::spawn_blocking(move || _server_cert_fingerprint(&addr)).await?
+}
+
+fn _server_cert_fingerprint(addr: &str) -> anyhow::Result<String> {
+    use std::net::{TcpStream, ToSocketAddrs};
+
+    use rustls::pki_types::ServerName;
+
+    let (host, port) = addr.rsplit_once(':').context("address has no port")?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port: u16 = port.parse()?;
+    let sock_addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .context("cannot resolve address")?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = Arc::new(CaptureCert {
+        cert: Mutex::new(None),
+        provider: provider.clone(),
+    });
+    let mut config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"gel-binary".to_vec(), b"edgedb-binary".to_vec()];
+    let mut conn =
+        rustls::ClientConnection::new(Arc::new(config), ServerName::try_from(host.to_string())?)?;
+    let timeout = Duration::from_secs(5);
+    let mut sock = TcpStream::connect_timeout(&sock_addr, timeout)?;
+    sock.set_read_timeout(Some(timeout))?;
+    sock.set_write_timeout(Some(timeout))?;
+    while conn.is_handshaking() {
+        if conn.complete_io(&mut sock).is_err() {
+            break;
+        }
+    }
+    let cert = verifier
+        .cert
+        .lock()
+        .unwrap()
+        .take()
+        .context("server presented no certificate")?;
+    let digest = ring::digest::digest(&ring::digest::SHA256, &cert);
+    Ok(digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+/// Certificate verifier that accepts any certificate and remembers it.
+#[derive(Debug)]
+struct CaptureCert {
+    cert: Mutex<Option<Vec<u8>>>,
+    provider: Arc<rustls::crypto::CryptoProvider>,
+}
+
+impl rustls::client::danger::ServerCertVerifier for CaptureCert {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        *self.cert.lock().unwrap() = Some(end_entity.to_vec());
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[test]
+fn classify() {
+    use gel_errors::{ClientConnectionError, ClientConnectionFailedError};
+    use std::io;
+
+    let io_err = |kind| ClientConnectionError::with_source(io::Error::from(kind));
+    assert_eq!(
+        classify_connection_err(&io_err(io::ErrorKind::ConnectionRefused)),
+        Some(NetworkFailure::Refused)
+    );
+    assert_eq!(
+        classify_connection_err(&io_err(io::ErrorKind::TimedOut)),
+        Some(NetworkFailure::Timeout)
+    );
+    assert_eq!(
+        classify_connection_err(&ClientConnectionTimeoutError::with_message("timeout")),
+        Some(NetworkFailure::Timeout)
+    );
+    assert_eq!(
+        classify_connection_err(&AuthenticationError::with_message("bad password")),
+        Some(NetworkFailure::Authentication)
+    );
+    assert_eq!(
+        classify_connection_err(&UnsupportedProtocolVersionError::with_message("0.1")),
+        Some(NetworkFailure::Protocol)
+    );
+    let tls = rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer);
+    assert_eq!(
+        classify_connection_err(&ClientConnectionFailedError::with_source(tls.clone())),
+        Some(NetworkFailure::Tls)
+    );
+    assert_eq!(
+        classify_connection_err(&ClientConnectionError::with_source(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tls
+        ))),
+        Some(NetworkFailure::Tls)
+    );
+
+    // resolver errors are only recognized by resolving the host again
+    let dns = ClientConnectionError::with_source(io::Error::new(
+        io::ErrorKind::Other,
+        "failed to lookup address information",
+    ));
+    assert_eq!(classify_connection_err(&dns), None);
+    assert!(io_error(&dns).is_some());
+    assert_eq!(
+        classify_connection_err(&ClientError::with_message("other")),
+        None
+    );
+    assert!(io_error(&ClientError::with_message("other")).is_none());
+}
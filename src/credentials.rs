@@ -9,7 +9,8 @@ use fs_err as fs;
 use gel_tokio::credentials::Credentials;
 use gel_tokio::Config;
 
-use crate::platform::{config_dir, tmp_file_name};
+use crate::keychain::{self, Account};
+use crate::platform::{config_dir, lock, tmp_file_name};
 use crate::portable::local::is_valid_local_instance_name;
 use crate::question;
 
@@ -49,22 +50,66 @@ pub async fn write(path: &Path, credentials: &Credentials) -> anyhow::Result<()>
     Ok(())
 }
 
+/// Writes credentials, the password goes to the keychain instead if it's
+/// enabled
 #[context("cannot write credentials file {}", path.display())]
 pub async fn write_async(path: &Path, credentials: &Credentials) -> anyhow::Result<()> {
-    use tokio::fs;
+    let mut data = serde_json::to_value(credentials)?;
+    if let (Some(name), Some(password)) = (instance_name(path), &credentials.password) {
+        if keychain::set(&Account::Instance(name), password) {
+            if let Some(data) = data.as_object_mut() {
+                data.remove("password");
+            }
+        }
+    }
+    let data = serde_json::to_vec_pretty(&data)?;
+    let path = path.to_owned();
+    // the lock may be waited for, so don't block the runtime
+    tokio::task::spawn_blocking(move || write_locked(&path, &data)).await?
+}
 
-    fs::create_dir_all(path.parent().unwrap()).await?;
-    let tmp_path = path.with_file_name(tmp_file_name(path));
-    fs::write(&tmp_path, serde_json::to_vec_pretty(&credentials)?).await?;
-    fs::rename(&tmp_path, path).await?;
-    Ok(())
+/// Writes credentials file as is, without using the keychain
+#[context("cannot write credentials file {}", path.display())]
+pub fn write_file(path: &Path, credentials: &Credentials) -> anyhow::Result<()> {
+    write_locked(path, &serde_json::to_vec_pretty(&credentials)?)
 }
 
+/// Replaces the file atomically, holding a lock so that concurrent writers
+/// don't clobber the temporary file of each other
+fn write_locked(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    lock::with_lock(path, || {
+        fs::create_dir_all(path.parent().unwrap())?;
+        let tmp_path = path.with_file_name(tmp_file_name(path));
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    })
+}
+
+/// Reads credentials, filling in the password from the keychain if it's
+/// enabled and the file has none
 pub async fn read(path: &Path) -> anyhow::Result<Credentials> {
     use tokio::fs;
 
     let text = fs::read_to_string(path).await?;
-    Ok(serde_json::from_str(&text)?)
+    let mut credentials: Credentials = serde_json::from_str(&text)?;
+    if credentials.password.is_none() {
+        if let Some(name) = instance_name(path) {
+            credentials.password = keychain::get(&Account::Instance(name));
+        }
+    }
+    Ok(credentials)
+}
+
+/// Name of the instance the credentials file in the default location
+/// belongs to
+fn instance_name(path: &Path) -> Option<&str> {
+    if path.parent()? != base_dir().ok()? {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()
+        .filter(|name| is_valid_local_instance_name(name))
 }
 
 pub fn maybe_update_credentials_file(config: &Config, ask: bool) -> anyhow::Result<()> {
@@ -11,8 +11,33 @@ use termcolor::{ColorChoice, StandardStream};
 
 use gel_errors::{Error, InternalServerError};
 
-use crate::branding::BRANDING_CLI_CMD;
+use crate::branding::{BRANDING, BRANDING_CLI_CMD};
+use crate::commands::ExitCode;
+use crate::connect::ConnectionError;
+use crate::interrupt::InterruptError;
+use crate::options::UsageError;
 use crate::print::{self, msg};
+use crate::{bug, hint};
+
+/// Error written to stderr with `--error-format=json`
+#[derive(Debug, serde::Serialize)]
+struct ErrorReport {
+    /// One of `general`, `usage`, `connection`, `server`, `interrupted`,
+    /// `bug` or `exit` (the error was already reported)
+    category: &'static str,
+    /// Server error class name, e.g. `InvalidReferenceError`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    message: String,
+    causes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_traceback: Option<String>,
+    exit_code: i32,
+}
 
 pub fn print_query_error(
     err: &Error,
@@ -29,6 +54,64 @@ pub fn print_query_error(
             return Ok(());
         }
     };
+    emit_query_error(err, query, verbose, source_name, pstart..pend)
+}
+
+/// Same as `print_query_error` for queries in the SQL input language
+///
+/// Positions of SQL errors are character offsets, and Postgres reports
+/// only the start of the error, so the word at the start is underlined.
+pub fn print_sql_query_error(
+    err: &Error,
+    query: &str,
+    verbose: bool,
+    source_name: &str,
+) -> Result<(), anyhow::Error> {
+    let Some(range) = sql_error_range(query, err.position_start(), err.position_end()) else {
+        print::edgedb_error(err, verbose);
+        return Ok(());
+    };
+    emit_query_error(err, query, verbose, source_name, range)
+}
+
+/// Converts character positions of an SQL error into a byte range
+fn sql_error_range(
+    query: &str,
+    start: Option<usize>,
+    end: Option<usize>,
+) -> Option<std::ops::Range<usize>> {
+    let byte_offset = |chars: usize| {
+        query
+            .char_indices()
+            .nth(chars)
+            .map_or(query.len(), |(idx, _)| idx)
+    };
+    let start = byte_offset(start?);
+    let end = match end.map(byte_offset) {
+        Some(end) if end > start => end,
+        _ => {
+            let tail = &query[start..];
+            let word = tail
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(tail.len());
+            let len = if word > 0 {
+                word
+            } else {
+                tail.chars().next().map_or(0, |c| c.len_utf8())
+            };
+            start + len
+        }
+    };
+    Some(start..end)
+}
+
+fn emit_query_error(
+    err: &Error,
+    query: &str,
+    verbose: bool,
+    source_name: &str,
+    range: std::ops::Range<usize>,
+) -> Result<(), anyhow::Error> {
     let hint = err.hint().unwrap_or("error");
     let detail = err.details().map(|s| s.into());
     let files = SimpleFile::new(source_name, query);
@@ -47,7 +130,7 @@ pub fn print_query_error(
         .with_labels(vec![Label {
             file_id: (),
             style: LabelStyle::Primary,
-            range: pstart..pend,
+            range,
             message: hint.into(),
         }])
         .with_notes(detail.into_iter().collect());
@@ -61,9 +144,9 @@ pub fn print_query_error(
 
     if err.is::<InternalServerError>() || verbose {
         if let Some(traceback) = err.server_traceback() {
-            eprintln!("  Server traceback:");
+            msg!("  Server traceback:");
             for line in traceback.lines() {
-                eprintln!("      {line}");
+                msg!("      {line}");
             }
         }
     }
@@ -82,6 +165,10 @@ pub fn print_query_warning(
     source: &str,
     source_file: Option<&str>,
 ) -> Result<(), anyhow::Error> {
+    if print::structured::is_json() {
+        print::structured::write_record("warning", warning);
+        return Ok(());
+    }
     let Some((start, end)) = warning.start.zip(warning.end) else {
         print_query_warning_plain(warning);
         return Ok(());
@@ -121,3 +208,81 @@ fn print_query_warning_plain(warning: &Warning) {
 
     msg!("{marker} {warning}");
 }
+
+/// Prints the error as a single JSON object to stderr and returns the exit
+/// code of the process
+pub fn print_error_json(err: &anyhow::Error) -> i32 {
+    let err = match err.downcast_ref::<hint::ArcError>() {
+        Some(arc) => arc.inner(),
+        None => err,
+    };
+    let mut chain = err.chain();
+    let mut report = ErrorReport {
+        category: "general",
+        kind: None,
+        message: chain
+            .next()
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "<empty error message>".into()),
+        causes: chain.map(|e| e.to_string()).collect(),
+        hint: None,
+        details: None,
+        server_traceback: None,
+        exit_code: 1,
+    };
+    for item in err.chain() {
+        if let Some(e) = item.downcast_ref::<hint::HintedError>() {
+            report.hint.get_or_insert_with(|| e.hint.to_string());
+        } else if let Some(e) = item.downcast_ref::<ExitCode>() {
+            report.category = "exit";
+            report.exit_code = e.code();
+        } else if let Some(e) = item.downcast_ref::<UsageError>() {
+            report.category = "usage";
+            report.message = e.message().to_string();
+            report.exit_code = 2;
+        } else if let Some(e) = item.downcast_ref::<Error>() {
+            report.category = "server";
+            report.kind = Some(e.kind_name().to_string());
+            report.message = e.initial_message().unwrap_or_default().to_string();
+            report.hint = report.hint.or_else(|| e.hint().map(|h| h.to_string()));
+            report.details = e.details().map(|d| d.to_string());
+            report.server_traceback = e.server_traceback().map(|t| t.to_string());
+        } else if let Some(e) = item.downcast_ref::<ConnectionError>() {
+            report.category = "connection";
+            report.hint = report.hint.or_else(|| e.hint().map(|h| h.to_string()));
+        } else if item.is::<InterruptError>() {
+            report.category = "interrupted";
+        } else if item.is::<bug::Bug>() {
+            report.category = "bug";
+            report.hint.get_or_insert_with(|| {
+                format!(
+                    "This is most likely a bug in {BRANDING} or command-line \
+                     tools. Please consider opening an issue at \
+                     https://github.com/edgedb/edgedb-cli/issues/new\
+                     ?template=bug_report.md"
+                )
+            });
+            report.exit_code = 13;
+        }
+    }
+    match serde_json::to_string(&report) {
+        Ok(line) => eprintln!("{line}"),
+        Err(e) => eprintln!("{BRANDING_CLI_CMD} error: {err:#} (cannot serialize: {e})"),
+    }
+    report.exit_code
+}
+
+#[test]
+fn sql_error_positions() {
+    let query = "SELECT foo FROM bar";
+    assert_eq!(sql_error_range(query, Some(7), None), Some(7..10));
+    assert_eq!(sql_error_range(query, Some(7), Some(7)), Some(7..10));
+    assert_eq!(sql_error_range(query, Some(16), Some(19)), Some(16..19));
+    assert_eq!(sql_error_range(query, None, None), None);
+    assert_eq!(
+        sql_error_range("SELECT 'ö', x;", Some(12), None),
+        Some(13..14)
+    );
+    assert_eq!(sql_error_range("SELECT 1 +;", Some(10), None), Some(10..11));
+    assert_eq!(sql_error_range("SELECT", Some(6), None), Some(6..6));
+}
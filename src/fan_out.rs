@@ -0,0 +1,206 @@
+//! `--instances <pattern>`: runs a command on every named instance matching
+//! the pattern.
+//!
+//! The command is executed by running the CLI itself once per instance
+//! with `--instance <name>` instead of the pattern, so each run behaves
+//! exactly like the command run by hand. With `--parallel` the output of
+//! each instance is collected and printed when it finishes, to keep the
+//! output of different instances apart.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use tokio::process::Command;
+
+use crate::branch::select::matches_pattern;
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::parser::Common;
+use crate::commands::ExitCode;
+use crate::credentials;
+use crate::hint::HintExt;
+use crate::migrations::options::MigrationCmd;
+use crate::options::{self, Options};
+use crate::platform::current_exe;
+use crate::portable::exit_codes;
+use crate::print::{self, msg, Highlight};
+use crate::table::{self, Cell, Row, Table};
+
+struct Outcome {
+    instance: String,
+    result: anyhow::Result<ExitStatus>,
+    elapsed: Duration,
+}
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn run(options: &Options, pattern: &str) -> anyhow::Result<()> {
+    if !is_supported(options.subcommand.as_ref()) {
+        anyhow::bail!(
+            "`--instances` is only supported by `query`, `migrate` and \
+             `migration apply`"
+        );
+    }
+    let instances: Vec<String> = credentials::all_instance_names()?
+        .into_iter()
+        .filter(|name| matches_pattern(pattern, name))
+        .collect();
+    if instances.is_empty() {
+        return Err(anyhow::anyhow!("no instances match {pattern:?}"))
+            .with_hint(|| format!("use `{BRANDING_CLI_CMD} instance list` to list instances"))?;
+    }
+    msg!(
+        "Running on {} instances: {}",
+        instances.len(),
+        instances.join(", ")
+    );
+
+    let exe = current_exe()?;
+    let args = instance_args(std::env::args_os().skip(1));
+    let parallel = options.parallel.get();
+    let mut outcomes = Vec::with_capacity(instances.len());
+    if parallel == 1 {
+        for instance in &instances {
+            msg!("{}", format!("=== {instance} ===").emphasize());
+            outcomes.push(run_one(&exe, &args, instance, false).await);
+        }
+    } else {
+        let runs = tokio_stream::iter(&instances)
+            .map(|instance| run_one(&exe, &args, instance, true))
+            .buffer_unordered(parallel);
+        tokio::pin!(runs);
+        while let Some(outcome) = runs.next().await {
+            outcomes.push(outcome);
+        }
+        outcomes.sort_by(|a, b| a.instance.cmp(&b.instance));
+    }
+    summary(&outcomes)
+}
+
+fn is_supported(cmd: Option<&options::Command>) -> bool {
+    match cmd {
+        Some(options::Command::Query(_)) => true,
+        Some(options::Command::Common(Common::Migrate(_))) => true,
+        Some(options::Command::Common(cmd)) => cmd
+            .as_migration()
+            .map_or(false, |m| matches!(m.subcommand, MigrationCmd::Apply(_))),
+        _ => false,
+    }
+}
+
+/// Removes `--instances` and `--parallel` from the command line
+fn instance_args(mut args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut result = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--instances" | "--parallel") => {
+                args.next();
+            }
+            Some(a) if a.starts_with("--instances=") || a.starts_with("--parallel=") => {}
+            _ => result.push(arg),
+        }
+    }
+    result
+}
+
+async fn run_one(
+    exe: &std::path::Path,
+    args: &[OsString],
+    instance: &str,
+    capture: bool,
+) -> Outcome {
+    let mut cmd = Command::new(exe);
+    cmd.arg("--instance")
+        .arg(instance)
+        .arg("--no-cli-update-check")
+        .args(args);
+    let start = Instant::now();
+    let result = if capture {
+        cmd.stdin(Stdio::null());
+        match cmd.output().await {
+            Ok(output) => {
+                msg!("{}", format!("=== {instance} ===").emphasize());
+                std::io::stdout().write_all(&output.stdout).ok();
+                std::io::stderr().write_all(&output.stderr).ok();
+                Ok(output.status)
+            }
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        cmd.status().await.map_err(Into::into)
+    };
+    Outcome {
+        instance: instance.to_string(),
+        result,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn summary(outcomes: &[Outcome]) -> anyhow::Result<()> {
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.add_row(Row::new(vec![
+        table::header_cell("Instance"),
+        table::header_cell("Result"),
+        table::header_cell("Time"),
+    ]));
+    let mut failed = 0;
+    for outcome in outcomes {
+        let result = match &outcome.result {
+            Ok(status) if status.success() => "ok".to_string(),
+            Ok(status) => {
+                failed += 1;
+                match status.code() {
+                    Some(code) => format!("failed, exit code {code}"),
+                    None => format!("failed, {status}"),
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                format!("cannot run: {e:#}")
+            }
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&outcome.instance),
+            Cell::new(&result),
+            Cell::new(&format!("{:.1}s", outcome.elapsed.as_secs_f64())),
+        ]));
+    }
+    // results of the command itself may be piped from stdout
+    eprintln!();
+    table.print(&mut std::io::stderr())?;
+
+    if failed == 0 {
+        print::success!("Succeeded on all {} instances.", outcomes.len());
+        Ok(())
+    } else if failed == outcomes.len() {
+        print::error!("Failed on all {} instances.", outcomes.len());
+        Err(ExitCode::new(1).into())
+    } else {
+        print::error!("Failed on {failed} of {} instances.", outcomes.len());
+        Err(ExitCode::new(exit_codes::PARTIAL_SUCCESS).into())
+    }
+}
+
+#[test]
+fn strip_instances() {
+    let args = |list: &[&str]| list.iter().map(OsString::from).collect::<Vec<_>>();
+    assert_eq!(
+        instance_args(
+            args(&[
+                "--instances",
+                "tenant-*",
+                "--parallel=4",
+                "query",
+                "SELECT 1"
+            ])
+            .into_iter()
+        ),
+        args(&["query", "SELECT 1"]),
+    );
+    assert_eq!(
+        instance_args(args(&["migrate", "--instances=t-?", "--parallel", "2"]).into_iter()),
+        args(&["migrate"]),
+    );
+}
@@ -0,0 +1,101 @@
+//! Downloading of query files given as URLs
+//!
+//! Each supported scheme resolves to an `https://` URL which is fetched
+//! with certificate verification and a size limit. `s3://bucket/key` is
+//! resolved to the public endpoint of the bucket, so only objects readable
+//! without credentials can be fetched.
+
+use std::time::Duration;
+
+use url::Url;
+
+use crate::hint::HintExt;
+use crate::http;
+use crate::portable::repository::USER_AGENT;
+
+/// Maximum size of the downloaded file
+pub const MAX_SIZE: u64 = 16 << 20;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+type Resolver = fn(&Url) -> anyhow::Result<Url>;
+
+const SCHEMES: &[(&str, Resolver)] = &[("https", resolve_https), ("s3", resolve_s3)];
+
+/// Returns true if `location` should be downloaded rather than opened as
+/// a local file
+pub fn is_url(location: &str) -> bool {
+    match Url::parse(location) {
+        // single letter schemes are drive letters on windows
+        Ok(url) => url.scheme() == "http" || SCHEMES.iter().any(|(s, _)| *s == url.scheme()),
+        Err(_) => false,
+    }
+}
+
+fn resolve_https(url: &Url) -> anyhow::Result<Url> {
+    Ok(url.clone())
+}
+
+fn resolve_s3(url: &Url) -> anyhow::Result<Url> {
+    let bucket = url
+        .host_str()
+        .filter(|b| !b.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("no bucket name in {url}"))?;
+    let mut resolved = Url::parse(&format!("https://{bucket}.s3.amazonaws.com"))?;
+    resolved.set_path(url.path());
+    Ok(resolved)
+}
+
+pub async fn fetch(location: &str) -> anyhow::Result<Vec<u8>> {
+    let url = Url::parse(location)?;
+    if url.scheme() == "http" {
+        return Err(
+            anyhow::anyhow!("refusing to download {url} over plain HTTP")
+                .hint("Use an `https://` URL.")
+                .into(),
+        );
+    }
+    let (_, resolve) = SCHEMES
+        .iter()
+        .find(|(s, _)| *s == url.scheme())
+        .ok_or_else(|| anyhow::anyhow!("unsupported URL scheme {:?}", url.scheme()))?;
+    let url = resolve(&url)?;
+    log::info!("Downloading {}", url);
+
+    let client = http::client_builder()?
+        .https_only(true)
+        .timeout(TIMEOUT)
+        .build()?;
+    let mut resp = client
+        .get(url.clone())
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?;
+    if resp.content_length().is_some_and(|len| len > MAX_SIZE) {
+        anyhow::bail!("{url} is larger than {MAX_SIZE} bytes");
+    }
+    let mut data = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if data.len() as u64 + chunk.len() as u64 > MAX_SIZE {
+            anyhow::bail!("{url} is larger than {MAX_SIZE} bytes");
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+#[test]
+fn urls() {
+    assert!(is_url("https://example.com/x.edgeql"));
+    assert!(is_url("http://example.com/x.edgeql"));
+    assert!(is_url("s3://bucket/x.edgeql"));
+    assert!(!is_url("x.edgeql"));
+    assert!(!is_url("C:\\scripts\\x.edgeql"));
+    assert!(!is_url("-"));
+
+    let url = Url::parse("s3://bucket/dir/x.edgeql").unwrap();
+    assert_eq!(
+        resolve_s3(&url).unwrap().as_str(),
+        "https://bucket.s3.amazonaws.com/dir/x.edgeql"
+    );
+}
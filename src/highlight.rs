@@ -10,6 +10,104 @@ use crate::print::style::{Style, Styler};
 static UNRESERVED_KEYWORDS: Lazy<HashSet<&'static str>> =
     Lazy::new(|| keywords::UNRESERVED_KEYWORDS.iter().copied().collect());
 
+/// Commonly used SQL keywords, lowercase and sorted. Unlike in EdgeQL,
+/// most of the SQL keywords are valid identifiers, so this list doesn't
+/// try to be complete.
+const SQL_KEYWORDS: &[&str] = &[
+    "all",
+    "alter",
+    "analyze",
+    "and",
+    "any",
+    "as",
+    "asc",
+    "begin",
+    "between",
+    "by",
+    "case",
+    "cast",
+    "check",
+    "commit",
+    "constraint",
+    "copy",
+    "create",
+    "cross",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "do",
+    "drop",
+    "else",
+    "end",
+    "except",
+    "exists",
+    "explain",
+    "fetch",
+    "filter",
+    "first",
+    "for",
+    "foreign",
+    "from",
+    "full",
+    "function",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "index",
+    "inner",
+    "insert",
+    "intersect",
+    "into",
+    "is",
+    "join",
+    "key",
+    "last",
+    "lateral",
+    "left",
+    "like",
+    "limit",
+    "not",
+    "null",
+    "nulls",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "over",
+    "partition",
+    "primary",
+    "recursive",
+    "references",
+    "reset",
+    "returning",
+    "right",
+    "rollback",
+    "row",
+    "rows",
+    "select",
+    "set",
+    "show",
+    "start",
+    "table",
+    "then",
+    "to",
+    "transaction",
+    "union",
+    "unique",
+    "update",
+    "using",
+    "values",
+    "view",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
 pub fn edgeql(outbuf: &mut String, text: &str, styler: &Styler) {
     let mut pos = 0;
     let mut token_stream = Tokenizer::new(text);
@@ -34,6 +132,131 @@ pub fn edgeql(outbuf: &mut String, text: &str, styler: &Styler) {
     emit_insignificant(outbuf, styler, &text[pos..]);
 }
 
+pub fn sql(outbuf: &mut String, text: &str, styler: &Styler) {
+    let mut pos = 0;
+    while pos < text.len() {
+        let (end, style) = sql_token(text, pos);
+        match style {
+            Some(st) => styler.write(st, &text[pos..end], outbuf),
+            None => outbuf.push_str(&text[pos..end]),
+        }
+        pos = end;
+    }
+}
+
+fn is_sql_ident(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80
+}
+
+/// Returns the end and the style of the SQL token at `pos`. Unterminated
+/// strings and comments span till the end of the text.
+fn sql_token(text: &str, pos: usize) -> (usize, Option<Style>) {
+    let data = text.as_bytes();
+    let tail = &data[pos..];
+    let ident_len = tail.iter().take_while(|&&c| is_sql_ident(c)).count();
+    match tail[0] {
+        b'-' if tail.get(1) == Some(&b'-') => {
+            // newline must be unstyled to work well at the end of input
+            let end = tail.iter().position(|&c| c == b'\n').unwrap_or(tail.len());
+            (pos + end, Some(Style::Comment))
+        }
+        b'/' if tail.get(1) == Some(&b'*') => {
+            let mut depth = 0;
+            let mut idx = 0;
+            while idx + 1 < tail.len() {
+                match &tail[idx..idx + 2] {
+                    b"/*" => {
+                        depth += 1;
+                        idx += 2;
+                    }
+                    b"*/" => {
+                        depth -= 1;
+                        idx += 2;
+                        if depth == 0 {
+                            return (pos + idx, Some(Style::Comment));
+                        }
+                    }
+                    _ => idx += 1,
+                }
+            }
+            (data.len(), Some(Style::Comment))
+        }
+        b'\'' => (sql_quoted_end(data, pos, b'\'', false), Some(Style::String)),
+        b'"' => (sql_quoted_end(data, pos, b'"', false), None),
+        b'E' | b'e' if tail.get(1) == Some(&b'\'') => (
+            sql_quoted_end(data, pos + 1, b'\'', true),
+            Some(Style::String),
+        ),
+        b'$' => {
+            let tag_len = tail[1..].iter().take_while(|&&c| is_sql_ident(c)).count();
+            if tail.get(1).map_or(false, |c| c.is_ascii_digit()) {
+                // positional parameter
+                (pos + 1 + tag_len, Some(Style::Decorator))
+            } else if tail.get(tag_len + 1) == Some(&b'$') {
+                let tag = &tail[..tag_len + 2];
+                let end = tail[tag.len()..]
+                    .windows(tag.len())
+                    .position(|w| w == tag)
+                    .map_or(data.len(), |idx| pos + tag.len() * 2 + idx);
+                (end, Some(Style::String))
+            } else {
+                (pos + 1, Some(Style::Operator))
+            }
+        }
+        c if c.is_ascii_digit() => {
+            let len = tail
+                .iter()
+                .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'.' || c == b'_')
+                .count();
+            (pos + len, Some(Style::Number))
+        }
+        _ if ident_len > 0 => {
+            let word = text[pos..pos + ident_len].to_lowercase();
+            let style = match &word[..] {
+                "true" | "false" => Some(Style::Boolean),
+                w if SQL_KEYWORDS.binary_search(&w).is_ok() => Some(Style::Keyword),
+                _ => None,
+            };
+            (pos + ident_len, style)
+        }
+        b',' | b';' | b'.' => (pos + 1, Some(Style::Punctuation)),
+        b'(' | b')' | b'[' | b']' => (pos + 1, None),
+        c if c.is_ascii_whitespace() => {
+            let len = tail.iter().take_while(|c| c.is_ascii_whitespace()).count();
+            (pos + len, None)
+        }
+        c if c.is_ascii_punctuation() => {
+            let mut len = 1;
+            while len < tail.len()
+                && b"+-*/<>=~!@#%^&|:".contains(&tail[len])
+                && !matches!(&tail[len..], [b'-', b'-', ..] | [b'/', b'*', ..])
+            {
+                len += 1;
+            }
+            (pos + len, Some(Style::Operator))
+        }
+        _ => {
+            let len = text[pos..].chars().next().map_or(1, |c| c.len_utf8());
+            (pos + len, None)
+        }
+    }
+}
+
+/// Returns the end of the string or identifier quoted with `quote` at
+/// `pos`, where the quote is escaped by doubling it
+fn sql_quoted_end(data: &[u8], pos: usize, quote: u8, escapes: bool) -> usize {
+    let mut idx = pos + 1;
+    while idx < data.len() {
+        match data[idx] {
+            b'\\' if escapes => idx += 2,
+            c if c == quote && data.get(idx + 1) == Some(&quote) => idx += 2,
+            c if c == quote => return idx + 1,
+            _ => idx += 1,
+        }
+    }
+    data.len()
+}
+
 pub fn backslash(outbuf: &mut String, text: &str, styler: &Styler) {
     use crate::commands::backslash;
 
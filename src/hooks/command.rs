@@ -0,0 +1,84 @@
+//! `hook run`: runs a project hook manually, for debugging hooks
+
+use crate::branding::MANIFEST_FILE_DISPLAY_NAME;
+use crate::hooks::{self, Action, Env, Hooks};
+use crate::options::Options;
+use crate::portable::project;
+use crate::print::{msg, Highlight};
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Command {
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    /// Run the hook of an action with the environment the CLI would
+    /// provide, even if `--skip-hooks` is given.
+    Run(Run),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Run {
+    /// Name of the hook, e.g. `migration.apply.after`
+    #[arg(value_enum)]
+    pub action: Action,
+
+    /// Migration ids passed in `GEL_MIGRATIONS`. Can be repeated.
+    #[arg(long = "migration", value_name = "id")]
+    pub migrations: Vec<String>,
+
+    /// Don't connect to the instance; `GEL_BRANCH` and `GEL_INSTANCE`
+    /// are not set.
+    #[arg(long)]
+    pub no_connect: bool,
+}
+
+pub fn run(options: &Options, cmd: &Command) -> anyhow::Result<()> {
+    match &cmd.subcommand {
+        Subcommand::Run(c) => run_hook(options, c),
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn run_hook(options: &Options, cmd: &Run) -> anyhow::Result<()> {
+    let project = project::ensure_ctx(None).await?;
+    let command = match cmd.action {
+        // configured in the `[sync]` table
+        Action::ProjectSyncAfter => project
+            .manifest
+            .sync
+            .as_ref()
+            .and_then(|s| s.post_sync.clone()),
+        action => {
+            Hooks::for_project(&project).and_then(|h| h.command(action).map(|c| c.to_string()))
+        }
+    };
+    let Some(command) = command else {
+        msg!(
+            "No {} hook is configured in {MANIFEST_FILE_DISPLAY_NAME}.",
+            cmd.action.name().emphasize()
+        );
+        return Ok(());
+    };
+
+    let (branch, instance) = if cmd.no_connect {
+        (None, None)
+    } else {
+        let mut cli = options.create_connector().await?.connect().await?;
+        let instance = cli.instance_name().map(|n| n.to_string());
+        let branch = cli.get_current_branch().await?.to_string();
+        (Some(branch), instance)
+    };
+    hooks::spawn(
+        &command,
+        &project.location.root,
+        cmd.action,
+        &Env {
+            branch: branch.as_deref(),
+            instance: instance.as_deref(),
+            migrations: &cmd.migrations,
+        },
+    )
+}
@@ -0,0 +1,192 @@
+//! Project hooks: shell commands from the `[hooks]` table of the manifest,
+//! run around the CLI actions.
+//!
+//! Hooks receive the context of the action via environment variables:
+//!
+//! * `GEL_HOOK_ACTION` -- name of the hook, e.g. `migration.apply.after`
+//! * `GEL_BRANCH` -- branch the action is performed on (for `branch.switch`
+//!   hooks, the branch switched from in `before` and the one switched to in
+//!   `after`)
+//! * `GEL_INSTANCE` -- name of the instance, if connected to a named one
+//! * `GEL_MIGRATIONS` -- space-separated ids of the migrations created,
+//!   applied or merged by the action
+//!
+//! All hooks are skipped with the global `--skip-hooks` flag.
+
+pub mod command;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::commands::ExitCode;
+use crate::connect::Connection;
+use crate::portable::project;
+use crate::portable::project::manifest::HooksConfig;
+use crate::print::{self, msg, Highlight};
+
+static SKIP: AtomicBool = AtomicBool::new(false);
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    #[value(name = "migration.create.before")]
+    MigrationCreateBefore,
+    #[value(name = "migration.create.after")]
+    MigrationCreateAfter,
+    #[value(name = "migration.apply.before")]
+    MigrationApplyBefore,
+    #[value(name = "migration.apply.after")]
+    MigrationApplyAfter,
+    #[value(name = "branch.merge.before")]
+    BranchMergeBefore,
+    #[value(name = "branch.merge.after")]
+    BranchMergeAfter,
+    #[value(name = "branch.switch.before")]
+    BranchSwitchBefore,
+    #[value(name = "branch.switch.after")]
+    BranchSwitchAfter,
+    #[value(name = "project.sync.after")]
+    ProjectSyncAfter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    project_dir: PathBuf,
+    config: HooksConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct Env<'a> {
+    pub branch: Option<&'a str>,
+    pub instance: Option<&'a str>,
+    pub migrations: &'a [String],
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        use Action::*;
+
+        match self {
+            MigrationCreateBefore => "migration.create.before",
+            MigrationCreateAfter => "migration.create.after",
+            MigrationApplyBefore => "migration.apply.before",
+            MigrationApplyAfter => "migration.apply.after",
+            BranchMergeBefore => "branch.merge.before",
+            BranchMergeAfter => "branch.merge.after",
+            BranchSwitchBefore => "branch.switch.before",
+            BranchSwitchAfter => "branch.switch.after",
+            ProjectSyncAfter => "project.sync.after",
+        }
+    }
+}
+
+impl Hooks {
+    pub fn for_project(project: &project::Context) -> Option<Hooks> {
+        project.manifest.hooks.clone().map(|config| Hooks {
+            project_dir: project.location.root.clone(),
+            config,
+        })
+    }
+
+    fn command(&self, action: Action) -> Option<&str> {
+        use Action::*;
+
+        let cfg = &self.config;
+        match action {
+            MigrationCreateBefore => cfg.migration.create.before.as_deref(),
+            MigrationCreateAfter => cfg.migration.create.after.as_deref(),
+            MigrationApplyBefore => cfg.migration.apply.before.as_deref(),
+            MigrationApplyAfter => cfg.migration.apply.after.as_deref(),
+            BranchMergeBefore => cfg.branch.merge.before.as_deref(),
+            BranchMergeAfter => cfg.branch.merge.after.as_deref(),
+            BranchSwitchBefore => cfg.branch.switch.before.as_deref(),
+            BranchSwitchAfter => cfg.branch.switch.after.as_deref(),
+            // configured in the `[sync]` table
+            ProjectSyncAfter => None,
+        }
+    }
+}
+
+/// Runs the hook of the action, if configured, with branch and instance
+/// of the connection
+pub async fn run(
+    hooks: Option<&Hooks>,
+    action: Action,
+    cli: &mut Connection,
+    migrations: &[String],
+) -> anyhow::Result<()> {
+    if hooks.and_then(|h| h.command(action)).is_none() {
+        return Ok(());
+    }
+    let instance = cli.instance_name().map(|n| n.to_string());
+    let branch = cli.get_current_branch().await?.to_string();
+    run_with_env(
+        hooks,
+        action,
+        &Env {
+            branch: Some(&branch),
+            instance: instance.as_deref(),
+            migrations,
+        },
+    )
+}
+
+/// Runs the hook of the action, if configured, with the environment given
+pub fn run_with_env(hooks: Option<&Hooks>, action: Action, env: &Env) -> anyhow::Result<()> {
+    let Some(hooks) = hooks else {
+        return Ok(());
+    };
+    let Some(command) = hooks.command(action) else {
+        return Ok(());
+    };
+    run_command(command, &hooks.project_dir, action, env)
+}
+
+/// Disables all the hooks, must be called before any command is run
+pub fn set_skip(skip: bool) {
+    SKIP.store(skip, Ordering::Relaxed);
+}
+
+pub fn run_command(
+    command: &str,
+    project_dir: &Path,
+    action: Action,
+    env: &Env,
+) -> anyhow::Result<()> {
+    if SKIP.load(Ordering::Relaxed) {
+        msg!("Skipping {} hook.", action.name());
+        return Ok(());
+    }
+    spawn(command, project_dir, action, env)
+}
+
+/// Runs the command of the hook regardless of `--skip-hooks`
+fn spawn(command: &str, project_dir: &Path, action: Action, env: &Env) -> anyhow::Result<()> {
+    msg!("Running {} hook: {}", action.name(), command.emphasize());
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command)
+        .current_dir(project_dir)
+        .env("GEL_HOOK_ACTION", action.name())
+        .env("GEL_MIGRATIONS", env.migrations.join(" "));
+    if let Some(branch) = env.branch {
+        cmd.env("GEL_BRANCH", branch);
+    }
+    if let Some(instance) = env.instance {
+        cmd.env("GEL_INSTANCE", instance);
+    }
+    let status = cmd
+        .status()
+        .map_err(|e| anyhow::anyhow!("cannot run {} hook: {e}", action.name()))?;
+    if !status.success() {
+        print::error!("Hook {} failed: {status}", action.name());
+        return Err(ExitCode::new(status.code().unwrap_or(1)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Proxy and CA settings shared by HTTP clients talking to external
+//! services (Cloud API, package repository, remote fetches).
+//!
+//! Standard `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY`
+//! variables are honored by default. On top of that, a proxy and extra CA
+//! certificates can be set in the `[http]` table of `cli.toml`, or with
+//! `GEL_HTTP_PROXY` and `GEL_HTTP_CA_FILE` (which take precedence). The
+//! global `--no-proxy` option disables proxies altogether.
+//!
+//! Connections to database servers are not affected.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use once_cell::sync::OnceCell;
+
+use crate::cli::env::Env;
+use crate::config::HttpConfig;
+
+static SETTINGS: OnceCell<Settings> = OnceCell::new();
+
+#[derive(Debug, Default)]
+struct Settings {
+    no_proxy: bool,
+    proxy: Option<String>,
+    ca_file: Option<PathBuf>,
+}
+
+/// Must be called once, before any HTTP client is created
+pub fn init(no_proxy: bool, config: &HttpConfig) -> anyhow::Result<()> {
+    let settings = Settings {
+        no_proxy: no_proxy || config.no_proxy.unwrap_or(false),
+        proxy: Env::http_proxy()?.or_else(|| config.proxy.clone()),
+        ca_file: Env::http_ca_file()?.or_else(|| config.ca_file.clone()),
+    };
+    log::debug!("HTTP settings: {settings:?}");
+    SETTINGS
+        .set(settings)
+        .map_err(|_| anyhow::anyhow!("HTTP settings are already initialized"))
+}
+
+/// Returns client builder with proxy and CA settings applied
+pub fn client_builder() -> anyhow::Result<reqwest::ClientBuilder> {
+    let default = Settings::default();
+    let settings = SETTINGS.get().unwrap_or(&default);
+    let mut builder = reqwest::Client::builder();
+    if settings.no_proxy {
+        builder = builder.no_proxy();
+    } else if let Some(proxy) = &settings.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .with_context(|| format!("invalid HTTP proxy URL {proxy:?}"))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &settings.ca_file {
+        let data = fs_err::read(path).context("cannot read HTTP CA file")?;
+        let certs = reqwest::Certificate::from_pem_bundle(&data)
+            .with_context(|| format!("invalid certificates in {path:?}"))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder)
+}
+
+/// Returns client with proxy and CA settings applied
+pub fn client() -> anyhow::Result<reqwest::Client> {
+    Ok(client_builder()?.build()?)
+}
@@ -1,11 +1,13 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::str;
 use std::time::Instant;
 
 use anyhow::Context;
 use colorful::Colorful;
+use indicatif::HumanBytes;
 use is_terminal::IsTerminal;
 use terminal_size::{terminal_size, Width};
-use tokio::io::{stdout, AsyncWriteExt};
 use tokio::sync::mpsc::channel;
 use tokio_stream::StreamExt;
 
@@ -23,17 +25,20 @@ use gel_tokio::raw::Description;
 use crate::analyze;
 use crate::classify;
 use crate::cli::logo::print_logo;
+use crate::commands::parser::Setting;
 use crate::commands::{backslash, ExitCode};
 use crate::config::Config;
 use crate::credentials;
-use crate::error_display::print_query_error;
+use crate::error_display::{print_query_error, print_sql_query_error};
 use crate::interrupt::{Interrupt, InterruptError};
 use crate::options::Options;
-use crate::outputs::tab_separated;
+use crate::outputs::{csv, tab_separated};
+use crate::portable::project;
 use crate::print::Highlight;
-use crate::print::{self, msg, PrintError};
+use crate::print::{self, msg, AsRelativeToCurrentDir, PrintError};
 use crate::prompt;
 use crate::repl::{self, VectorLimit};
+use crate::sql_statement;
 use crate::variables::input_variables;
 
 #[derive(Debug, thiserror::Error)]
@@ -50,6 +55,7 @@ pub struct RetryStateError;
 
 struct ToDo<'a> {
     tail: &'a str,
+    language: repl::InputLanguage,
 }
 
 #[derive(Debug, PartialEq)]
@@ -60,11 +66,25 @@ pub enum ToDoItem<'a> {
 }
 
 impl ToDo<'_> {
-    fn new(source: &str) -> ToDo {
+    fn new(source: &str, language: repl::InputLanguage) -> ToDo {
         ToDo {
             tail: source.trim(),
+            language,
         }
     }
+    fn is_empty(&self, data: &str) -> bool {
+        match self.language {
+            repl::InputLanguage::EdgeQl => preparser::is_empty(data),
+            repl::InputLanguage::Sql => sql_statement::is_empty(data),
+        }
+    }
+    fn statement_len(&self, data: &str) -> usize {
+        match self.language {
+            repl::InputLanguage::EdgeQl => full_statement(data.as_bytes(), None).ok(),
+            repl::InputLanguage::Sql => sql_statement::full_statement(data),
+        }
+        .unwrap_or(data.len())
+    }
 }
 
 impl<'a> Iterator for ToDo<'a> {
@@ -76,16 +96,17 @@ impl<'a> Iterator for ToDo<'a> {
                 let len = backslash::full_statement(tail);
                 self.tail = &tail[len..];
                 return Some(ToDoItem::Backslash(&tail[..len]));
-            } else if preparser::is_empty(tail) {
+            } else if self.is_empty(tail) {
                 return None;
             } else {
-                let len = full_statement(tail.as_bytes(), None).unwrap_or(tail.len());
+                let len = self.statement_len(tail);
                 let query = &tail[..len];
                 self.tail = &tail[len..];
-                if preparser::is_empty(query) {
+                if self.is_empty(query) {
                     continue;
                 }
-                if classify::is_analyze(query) {
+                // `ANALYZE` in SQL collects table statistics
+                if self.language == repl::InputLanguage::EdgeQl && classify::is_analyze(query) {
                     return Some(ToDoItem::Explain(query));
                 } else {
                     return Some(ToDoItem::Query(query));
@@ -121,6 +142,7 @@ pub fn main(options: Options, cfg: Config) -> Result<(), anyhow::Error> {
         verbose_errors: cfg.shell.verbose_errors.unwrap_or(false),
         last_error: None,
         last_analyze: None,
+        last_result: None,
         implicit_limit,
         idle_transaction_timeout: idle_tx_timeout,
         input_language: options
@@ -134,7 +156,13 @@ pub fn main(options: Options, cfg: Config) -> Result<(), anyhow::Error> {
         display_typenames: cfg.shell.display_typenames.unwrap_or(true),
         input_mode: cfg.shell.input_mode.unwrap_or(repl::InputMode::Emacs),
         print_stats: cfg.shell.print_stats.unwrap_or(repl::PrintStats::Off),
-        history_limit: cfg.shell.history_size.unwrap_or(10000),
+        pager: cfg.shell.pager.unwrap_or(repl::Pager::On),
+        stats: cfg.shell.stats.unwrap_or(false),
+        history_limit: options
+            .history_size
+            .or(cfg.shell.history_size)
+            .unwrap_or(10000),
+        setting_origins: setting_origins(&options, &cfg),
         branch: conn_config.database().into(),
         conn_params: conn,
         last_version: None,
@@ -149,11 +177,47 @@ pub fn main(options: Options, cfg: Config) -> Result<(), anyhow::Error> {
         .enable_all()
         .build()?;
     let handle = runtime.spawn(_main(options, state, cfg));
-    prompt::main(control_rd)?;
+    prompt::main(control_rd, project_history_file())?;
     runtime.block_on(handle)??;
     Ok(())
 }
 
+fn setting_origins(options: &Options, cfg: &Config) -> BTreeMap<&'static str, String> {
+    let mut origins = BTreeMap::new();
+    for setting in Setting::all_items() {
+        let Some(key) = setting.config_key() else {
+            continue;
+        };
+        if let Some(path) = cfg.origins.get(key) {
+            origins.insert(setting.name(), path.as_relative().display().to_string());
+        }
+    }
+    let from_command_line = [
+        ("language", options.input_language.is_some()),
+        ("output-format", options.output_format.is_some()),
+        ("history-size", options.history_size.is_some()),
+    ];
+    for (name, is_set) in from_command_line {
+        if is_set {
+            origins.insert(name, "command line".into());
+        }
+    }
+    origins
+}
+
+/// History file of the project in the current directory, if it is
+/// initialized.
+fn project_history_file() -> Option<PathBuf> {
+    let location = project::find_project(None)
+        .map_err(|e| log::warn!("Cannot find project: {:#}", e))
+        .ok()??;
+    let stash_dir = gel_tokio::get_stash_path(&location.root).ok()?;
+    if !stash_dir.exists() {
+        return None;
+    }
+    Some(stash_dir.join("edgeql.history"))
+}
+
 pub async fn _main(options: Options, mut state: repl::State, cfg: Config) -> anyhow::Result<()> {
     state.connect().await?;
     if let Some(config_path) = &cfg.file_name {
@@ -162,6 +226,12 @@ pub async fn _main(options: Options, mut state: repl::State, cfg: Config) -> any
             format_args!("Applied {} configuration file", config_path.display(),).fade()
         );
     }
+    if let Some(project_path) = &cfg.project_file {
+        msg!(
+            "{}",
+            format_args!("Applied [cli] overrides from {}", project_path.display(),).fade()
+        );
+    }
     msg!("{}", r#"Type \help for help, \quit to quit."#.light_gray());
     state.set_history_limit(state.history_limit).await?;
     match _interactive_main(&options, &mut state).await {
@@ -221,7 +291,11 @@ fn check_json_limit(json: &serde_json::Value, path: &str, limit: usize) -> bool
     true
 }
 
-async fn execute_backslash(state: &mut repl::State, text: &str) -> anyhow::Result<()> {
+async fn execute_backslash(
+    options: &Options,
+    state: &mut repl::State,
+    text: &str,
+) -> anyhow::Result<()> {
     use backslash::ExecuteResult::*;
 
     let cmd = match backslash::parse(text) {
@@ -245,6 +319,10 @@ async fn execute_backslash(state: &mut repl::State, text: &str) -> anyhow::Resul
             return Err(CleanShutdown)?;
         }
         Ok(Input(text)) => state.initial_text = text,
+        Ok(Query(query)) => {
+            state.soft_reconnect().await?;
+            execute_query(options, state, &query).await?;
+        }
         Err(e) => {
             if e.is::<ExitCode>() {
                 // It's expected that command already printed all required
@@ -260,13 +338,68 @@ async fn execute_backslash(state: &mut repl::State, text: &str) -> anyhow::Resul
     Ok(())
 }
 
+/// Measurements shown after each query with `\set stats on`
+struct QueryStats {
+    start: Instant,
+    input_duration: std::time::Duration,
+    first_row: Option<std::time::Duration>,
+    rows: usize,
+    /// Size of the received data, known only for the text (JSON) formats
+    bytes: Option<usize>,
+}
+
+impl QueryStats {
+    fn new(start: Instant, input_duration: std::time::Duration) -> QueryStats {
+        QueryStats {
+            start,
+            input_duration,
+            first_row: None,
+            rows: 0,
+            bytes: None,
+        }
+    }
+    fn elapsed(&self) -> std::time::Duration {
+        self.start.elapsed().saturating_sub(self.input_duration)
+    }
+    fn add(&mut self, rows: usize, bytes: Option<usize>) {
+        if self.first_row.is_none() {
+            self.first_row = Some(self.elapsed());
+        }
+        self.rows += rows;
+        if let Some(bytes) = bytes {
+            *self.bytes.get_or_insert(0) += bytes;
+        }
+    }
+    fn summary(&self) -> String {
+        let mut text = format!("Rows: {}", self.rows);
+        if let Some(bytes) = self.bytes {
+            text += &format!(", received: {}", HumanBytes(bytes as u64));
+        }
+        if let Some(first_row) = self.first_row {
+            text += &format!(", first row: {first_row:?}");
+        }
+        text += &format!(", round trip: {:?}", self.elapsed());
+        text
+    }
+}
+
 async fn write_out(data: &str) -> anyhow::Result<()> {
-    let mut out = stdout();
-    out.write_all(data.as_bytes()).await?;
-    out.flush().await?;
+    print::write_stdout(data)?;
     Ok(())
 }
 
+fn print_error(
+    err: &gel_errors::Error,
+    statement: &str,
+    lang: repl::InputLanguage,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    match lang {
+        repl::InputLanguage::EdgeQl => print_query_error(err, statement, verbose, "<query>"),
+        repl::InputLanguage::Sql => print_sql_query_error(err, statement, verbose, "<query>"),
+    }
+}
+
 async fn execute_query(
     options: &Options,
     state: &mut repl::State,
@@ -338,23 +471,32 @@ async fn execute_query(
                     return Err(RetryStateError)?;
                 }
                 Err(e) => {
-                    print_query_error(&e, statement, state.verbose_errors, "<query>")?;
+                    print_error(&e, statement, state.input_language, state.verbose_errors)?;
                     return Err(QueryError)?;
                 }
             }
         }
         Err(e) if e.is::<StateMismatchError>() => return Err(RetryStateError)?,
         Err(e) => {
-            print_query_error(&e, statement, state.verbose_errors, "<query>")?;
+            print_error(&e, statement, state.input_language, state.verbose_errors)?;
             return Err(QueryError)?;
         }
     };
 
     print::warnings(items.warnings(), statement)?;
 
+    let mut stats = QueryStats::new(start, input_duration);
     if !items.can_contain_data() {
         match items.complete().await {
-            Ok(res) => print::completion(&res.status_data),
+            Ok(res) => {
+                print::completion(&res.status_data);
+                if state.stats {
+                    eprintln!(
+                        "{}",
+                        format!("Round trip: {:?}", stats.elapsed()).dark_gray()
+                    );
+                }
+            }
             Err(e) if e.is::<StateMismatchError>() => {
                 return Err(RetryStateError)?;
             }
@@ -372,8 +514,14 @@ async fn execute_query(
         // update max_width each time
         cfg.max_width(w.into());
     }
+    let mut result_rows = Vec::new();
+    let pager = match state.pager {
+        repl::Pager::Off => None,
+        repl::Pager::On => print::start_pager(false),
+        repl::Pager::Always => print::start_pager(true),
+    };
     match state.output_format {
-        TabSeparated => {
+        TabSeparated | Csv => {
             let mut index = 0;
             while let Some(row) = items.next().await.transpose()? {
                 if index == 0 && state.print_stats == Detailed {
@@ -394,7 +542,21 @@ async fn execute_query(
                         return Err(QueryError)?;
                     }
                 }
-                let mut text = match tab_separated::format_row(&row) {
+                stats.add(1, None);
+                result_rows.push(row.clone());
+                let mut text = String::new();
+                if index == 0 && state.output_format == Csv && cfg.header {
+                    if let Some(header) = csv::format_header(&row) {
+                        text += &header;
+                        text += "\n";
+                    }
+                }
+                let row = if state.output_format == Csv {
+                    csv::format_row(&row, &cfg.null_as)
+                } else {
+                    tab_separated::format_row(&row, &cfg.null_as)
+                };
+                text += &match row {
                     Ok(text) => text,
                     Err(e) => {
                         eprintln!("Error: {e}");
@@ -411,14 +573,26 @@ async fn execute_query(
             }
         }
         Default => {
-            match print::native_to_stdout(&mut items, &cfg).await {
+            let rows = StreamExt::map(&mut items, |row| {
+                stats.add(1, None);
+                if let Ok(row) = &row {
+                    result_rows.push(row.clone());
+                }
+                row
+            });
+            match print::native_to_stdout(rows, &cfg).await {
                 Ok(()) => {}
                 Err(e) => {
                     match e {
                         PrintError::StreamErr {
                             source: ref error, ..
                         } => {
-                            print_query_error(error, statement, state.verbose_errors, "<query>")?;
+                            print_error(
+                                error,
+                                statement,
+                                state.input_language,
+                                state.verbose_errors,
+                            )?;
                         }
                         _ => eprintln!("{e:#?}"),
                     }
@@ -426,7 +600,7 @@ async fn execute_query(
                     return Err(QueryError)?;
                 }
             }
-            println!();
+            write_out("\n").await?;
         }
         Json => {
             let mut index = 0;
@@ -438,6 +612,7 @@ async fn execute_query(
                     );
                 }
                 index += 1;
+                result_rows.push(row.clone());
                 let text = match row {
                     Value::Str(s) => s,
                     _ => {
@@ -449,6 +624,10 @@ async fn execute_query(
 
                 let jitems: serde_json::Value =
                     serde_json::from_str(&text).context("cannot decode json result")?;
+                stats.add(
+                    jitems.as_array().map(|a| a.len()).unwrap_or(0),
+                    Some(text.len()),
+                );
                 if let Some(limit) = state.implicit_limit {
                     if !check_json_limit(&jitems, "", limit) {
                         items.complete().await?;
@@ -476,6 +655,7 @@ async fn execute_query(
                         format!("First row: {:?}", start.elapsed()).dark_gray()
                     );
                 }
+                result_rows.push(row.clone());
                 let mut text = match row {
                     Value::Str(s) => s,
                     _ => {
@@ -487,6 +667,7 @@ async fn execute_query(
 
                 let value: serde_json::Value =
                     serde_json::from_str(&text).context("cannot decode json result")?;
+                stats.add(1, Some(text.len()));
                 let path = format!(".[{index}]");
                 if let Some(limit) = state.implicit_limit {
                     if index >= limit {
@@ -513,9 +694,56 @@ async fn execute_query(
                 }
             }
         }
+        Table | Html => {
+            let mut rows = Vec::new();
+            while let Some(row) = items.next().await.transpose()? {
+                if rows.is_empty() && state.print_stats == Detailed {
+                    eprintln!(
+                        "{}",
+                        format!("First row: {:?}", start.elapsed()).dark_gray()
+                    );
+                }
+                result_rows.push(row.clone());
+                let text = match row {
+                    Value::Str(s) => s,
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "server returned a non-string value in JSON mode"
+                        ))
+                    }
+                };
+                let value: serde_json::Value =
+                    serde_json::from_str(&text).context("cannot decode json result")?;
+                stats.add(1, Some(text.len()));
+                if let Some(limit) = state.implicit_limit {
+                    let path = format!(".[{}]", rows.len());
+                    if rows.len() >= limit {
+                        print_json_limit_error(&path);
+                        items.complete().await?;
+                        return Err(QueryError)?;
+                    }
+                    if !check_json_limit(&value, &path, limit) {
+                        items.complete().await?;
+                        return Err(QueryError)?;
+                    }
+                }
+                rows.push(value);
+            }
+            let text = if state.output_format == Html {
+                print::json_html_to_string(&rows, &cfg)
+            } else {
+                print::json_table_to_string(&rows, &cfg)
+            };
+            write_out(&text).await?;
+        }
     }
 
     let _ = items.complete().await?;
+    drop(pager);
+    state.last_result = Some(repl::LastResult {
+        format: state.output_format,
+        rows: result_rows,
+    });
 
     if state.print_stats != Off {
         eprintln!(
@@ -527,6 +755,9 @@ async fn execute_query(
             .dark_gray()
         );
     }
+    if state.stats {
+        eprintln!("{}", stats.summary().dark_gray());
+    }
     state.last_error = None;
     Ok(())
 }
@@ -555,12 +786,12 @@ async fn _interactive_main(
             }
             prompt::Input::Text(inp) => inp,
         };
-        'todo: for item in ToDo::new(&inp) {
+        'todo: for item in ToDo::new(&inp, state.input_language) {
             'retry: loop {
                 let result = match item {
                     ToDoItem::Backslash(text) => {
                         tokio::select!(
-                            res = execute_backslash(state, text) => res,
+                            res = execute_backslash(options, state, text) => res,
                             res = ctrlc.wait_result() => res,
                         )
                     }
@@ -623,12 +854,26 @@ async fn _interactive_main(
 #[cfg(test)]
 mod test {
     use super::{ToDo, ToDoItem};
+    use crate::repl::InputLanguage;
 
     #[test]
     fn double_semicolon() {
         assert_eq!(
-            ToDo::new("SELECT 1;;SELECT 2").collect::<Vec<_>>(),
+            ToDo::new("SELECT 1;;SELECT 2", InputLanguage::EdgeQl).collect::<Vec<_>>(),
             &[ToDoItem::Query("SELECT 1;"), ToDoItem::Query("SELECT 2"),]
         );
     }
+
+    #[test]
+    fn sql_statements() {
+        assert_eq!(
+            ToDo::new("SELECT ';' -- x;\n;; ANALYZE t;\\dt", InputLanguage::Sql)
+                .collect::<Vec<_>>(),
+            &[
+                ToDoItem::Query("SELECT ';' -- x;\n;"),
+                ToDoItem::Query("ANALYZE t;"),
+                ToDoItem::Backslash("\\dt"),
+            ]
+        );
+    }
 }
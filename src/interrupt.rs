@@ -249,6 +249,14 @@ impl Interrupt {
     }
 }
 
+/// Forgets signals received while a child process, like a pager, was
+/// running in the foreground
+pub fn discard_pending() {
+    if let Some(state) = CUR_INTERRUPT.load_full() {
+        state.event.clear();
+    }
+}
+
 impl Drop for Interrupt {
     fn drop(&mut self) {
         let old = CUR_INTERRUPT.swap(None::<Arc<_>>).expect("Interrupt set");
@@ -0,0 +1,242 @@
+//! Storing instance passwords and Cloud secret keys in the OS keychain
+//! (macOS Keychain, Windows Credential Manager or Secret Service on Linux)
+//! instead of plaintext files.
+//!
+//! The keychain is opt-in: it's only used when `GEL_CREDENTIAL_STORE` is set
+//! to `keychain`. Secrets are still written to the files if the keychain is
+//! not available (e.g. on headless systems without a Secret Service daemon).
+//!
+//! Secrets already written to the files stay there until they are moved
+//! explicitly with `cli migrate-credentials` (and back with `--to-file`).
+//! Files are not rewritten behind the user's back, as other tools and
+//! older CLI versions read passwords from them.
+
+use std::io;
+
+use fs_err as fs;
+
+use gel_tokio::credentials::Credentials;
+
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::cli::env::{CredentialStore, Env};
+use crate::cloud::client::{cloud_config_dir, CloudConfig};
+use crate::credentials;
+use crate::portable::local::write_json;
+use crate::print::{self, msg, Highlight};
+
+const SERVICE: &str = "edgedb-cli";
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct MigrateCredentials {
+    /// Move secrets from the keychain back to the credential files
+    #[arg(long)]
+    pub to_file: bool,
+
+    /// Only show what would be moved
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+pub enum Account<'a> {
+    Instance(&'a str),
+    CloudProfile(&'a str),
+}
+
+impl Account<'_> {
+    fn key(&self) -> String {
+        match self {
+            Account::Instance(name) => format!("instance:{name}"),
+            Account::CloudProfile(profile) => format!("cloud-profile:{profile}"),
+        }
+    }
+}
+
+pub fn enabled() -> bool {
+    match Env::credential_store() {
+        Ok(store) => store == Some(CredentialStore::Keychain),
+        Err(e) => {
+            log::warn!("{e:#}");
+            false
+        }
+    }
+}
+
+/// Runs an operation on the keychain entry in a separate thread: the
+/// Secret Service client blocks on its own tokio runtime, which can't be
+/// done in a thread already running one.
+fn with_entry<T: Send>(
+    account: &Account,
+    f: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send,
+) -> keyring::Result<T> {
+    let key = account.key();
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| f(&keyring::Entry::new(SERVICE, &key)?))
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    })
+}
+
+/// Returns secret from the keychain, `None` if the keychain is not enabled,
+/// not available, or has no secret for the account
+pub fn get(account: &Account) -> Option<String> {
+    if !enabled() {
+        return None;
+    }
+    match with_entry(account, |e| e.get_password()) {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            log::warn!("Cannot read {:?} from keychain: {e}", account.key());
+            None
+        }
+    }
+}
+
+/// Stores secret in the keychain, returns false if it should be written to
+/// the file instead
+pub fn set(account: &Account, secret: &str) -> bool {
+    if !enabled() {
+        return false;
+    }
+    match with_entry(account, |e| e.set_password(secret)) {
+        Ok(()) => true,
+        Err(e) => {
+            print::warn!(
+                "Cannot store {:?} in keychain, writing it to file instead: {e}",
+                account.key()
+            );
+            false
+        }
+    }
+}
+
+/// Removes secret from the keychain if the keychain is enabled
+///
+/// The keychain is not touched for users who haven't opted in: it may be
+/// slow or hang on systems without a Secret Service daemon. Secrets left
+/// there after switching back to files are moved by
+/// `cli migrate-credentials --to-file`.
+pub fn delete(account: &Account) {
+    if enabled() {
+        remove(account);
+    }
+}
+
+fn remove(account: &Account) {
+    match with_entry(account, |e| e.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => log::debug!("Cannot delete {:?} from keychain: {e}", account.key()),
+    }
+}
+
+pub fn migrate(cmd: &MigrateCredentials) -> anyhow::Result<()> {
+    if !cmd.to_file && !enabled() {
+        anyhow::bail!(
+            "Set GEL_CREDENTIAL_STORE=keychain in your environment first, \
+             otherwise {BRANDING_CLI_CMD} won't look for secrets in the keychain"
+        );
+    }
+    let mut moved = 0;
+    for name in credentials::all_instance_names()? {
+        let path = credentials::path(&name)?;
+        let text = fs::read_to_string(&path)?;
+        let mut creds: Credentials = serde_json::from_str(&text)?;
+        let account = Account::Instance(&name);
+        if cmd.to_file {
+            if creds.password.is_some() {
+                continue;
+            }
+            let Ok(password) = with_entry(&account, |e| e.get_password()) else {
+                continue;
+            };
+            msg!("Moving password of instance {} to file", name.emphasize());
+            if !cmd.dry_run {
+                creds.password = Some(password);
+                credentials::write_file(&path, &creds)?;
+                remove(&account);
+            }
+        } else {
+            let Some(password) = creds.password.take() else {
+                continue;
+            };
+            msg!(
+                "Moving password of instance {} to keychain",
+                name.emphasize()
+            );
+            if !cmd.dry_run {
+                with_entry(&account, |e| e.set_password(&password))?;
+                credentials::write_file(&path, &creds)?;
+            }
+        }
+        moved += 1;
+    }
+
+    let dir = cloud_config_dir()?;
+    let dir_entries = match fs::read_dir(&dir) {
+        Ok(d) => Some(d),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e)?,
+    };
+    for item in dir_entries.into_iter().flatten() {
+        let path = item?.path();
+        let Some(profile) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let text = fs::read_to_string(&path)?;
+        let config: CloudConfig = if text.is_empty() {
+            CloudConfig { secret_key: None }
+        } else {
+            serde_json::from_str(&text)?
+        };
+        let account = Account::CloudProfile(profile);
+        if cmd.to_file {
+            if config.secret_key.is_some() {
+                continue;
+            }
+            let Ok(secret_key) = with_entry(&account, |e| e.get_password()) else {
+                continue;
+            };
+            msg!(
+                "Moving secret key of {BRANDING_CLOUD} profile {} to file",
+                profile.emphasize()
+            );
+            if !cmd.dry_run {
+                write_json(
+                    &path,
+                    "cloud config",
+                    &CloudConfig {
+                        secret_key: Some(secret_key),
+                    },
+                )?;
+                remove(&account);
+            }
+        } else {
+            let Some(secret_key) = config.secret_key else {
+                continue;
+            };
+            msg!(
+                "Moving secret key of {BRANDING_CLOUD} profile {} to keychain",
+                profile.emphasize()
+            );
+            if !cmd.dry_run {
+                with_entry(&account, |e| e.set_password(&secret_key))?;
+                write_json(&path, "cloud config", &CloudConfig { secret_key: None })?;
+            }
+        }
+        moved += 1;
+    }
+
+    if moved == 0 {
+        msg!("No {BRANDING} secrets to move.");
+    } else if cmd.dry_run {
+        msg!("Dry run: {moved} secret(s) would be moved.");
+    } else {
+        print::success!("Moved {moved} secret(s).");
+    }
+    Ok(())
+}
@@ -5,6 +5,7 @@ use crate::options::{Command, Options};
 use crate::portable::instance;
 use crate::portable::project::Subcommands as Project;
 use crate::portable::server::Subcommands as Server;
+use crate::print::structured::{self, LogFormat};
 use std::io::Write;
 
 pub fn init(builder: &mut env_logger::Builder, opt: &Options) {
@@ -61,4 +62,14 @@ pub fn init(builder: &mut env_logger::Builder, opt: &Options) {
     }
     // we have custom logging infrastructure for edgedb warnings
     builder.filter_module("gel_tokio::warning", log::LevelFilter::Error);
+    if opt.log_format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let level = record.level().as_str().to_lowercase();
+            writeln!(
+                buf,
+                "{}",
+                structured::format_record(&level, Some(record.target()), record.args())
+            )
+        });
+    }
 }
@@ -0,0 +1,263 @@
+use anyhow::Context as _;
+use notify::{RecursiveMode, Watcher};
+use serde_json::{json, Value};
+use tokio::io::{self, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, watch};
+
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::ExitCode;
+use crate::connect::Connector;
+use crate::lsp_proxy::options::LspProxyCommand;
+use crate::migrations;
+use crate::options::Options;
+use crate::portable::project;
+use crate::print::AsRelativeToCurrentDir;
+use crate::watch::wait_changes;
+
+/// JSON-RPC error code for requests received after `shutdown`.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for failures while processing a request.
+const INTERNAL_ERROR: i64 = -32603;
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "connection/params",
+    "schema/describe",
+    "migration/status",
+    "watch/subscribe",
+    "shutdown",
+    "exit",
+];
+
+struct ProxyContext {
+    connector: Connector,
+    project: project::Context,
+    migration: migrations::Context,
+    subscribed: bool,
+    /// Set by the `shutdown` request, after which only `exit` is accepted
+    shutdown: bool,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+}
+
+pub fn run(options: &Options, _cmd: &LspProxyCommand) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("lsp-proxy")
+        .enable_all()
+        .build()?;
+    let project = project::ensure_ctx(None)?;
+    let mut ctx = ProxyContext {
+        connector: options.block_on_create_connector()?,
+        migration: migrations::Context::for_project(&project)?,
+        project,
+        subscribed: false,
+        shutdown: false,
+    };
+    log::info!(
+        "Serving project dir {}",
+        ctx.project.location.root.as_relative().display()
+    );
+    let (tx, rx) = watch::channel(());
+    let mut watch = notify::recommended_watcher(move |res: Result<_, _>| {
+        res.map_err(|e| {
+            log::warn!("Error watching filesystem: {:#}", e);
+        })
+        .ok();
+        tx.send(()).ok();
+    })?;
+    watch.watch(&ctx.project.location.root, RecursiveMode::NonRecursive)?;
+    watch.watch(&ctx.migration.schema_dir, RecursiveMode::Recursive)?;
+
+    runtime.block_on(serve(rx, &mut ctx))
+}
+
+async fn serve(mut changes: watch::Receiver<()>, ctx: &mut ProxyContext) -> anyhow::Result<()> {
+    // Reading a message is not cancel-safe, so requests are read in
+    // a separate task and passed over the channel.
+    let (tx, mut requests) = mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut input = io::BufReader::new(io::stdin());
+        loop {
+            let msg = read_message(&mut input).await;
+            let eof = matches!(msg, Ok(None) | Err(_));
+            if tx.send(msg).await.is_err() || eof {
+                break;
+            }
+        }
+    });
+    let mut output = io::stdout();
+    loop {
+        tokio::select! {
+            msg = requests.recv() => {
+                let Some(msg) = msg else { return Ok(()) };
+                let Some(request) = msg? else { return Ok(()) };
+                if request.method == "exit" {
+                    // same as LSP: exiting without `shutdown` is an error
+                    if !ctx.shutdown {
+                        return Err(ExitCode::new(1).into());
+                    }
+                    return Ok(());
+                }
+                let Some(id) = request.id.clone() else {
+                    // Notifications from the client are ignored
+                    continue;
+                };
+                if ctx.shutdown {
+                    let response = json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": INVALID_REQUEST,
+                            "message": "server is shutting down",
+                        },
+                    });
+                    write_message(&mut output, &response).await?;
+                    continue;
+                }
+                let response = match ctx.handle(&request).await {
+                    Ok(Some(result)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": result,
+                    }),
+                    Ok(None) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": METHOD_NOT_FOUND,
+                            "message": format!("unknown method {:?}", request.method),
+                        },
+                    }),
+                    Err(e) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": INTERNAL_ERROR,
+                            "message": format!("{e:#}"),
+                        },
+                    }),
+                };
+                write_message(&mut output, &response).await?;
+            }
+            res = wait_changes(&mut changes, None) => {
+                res?;
+                if ctx.subscribed && !ctx.shutdown {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "watch/schemaChanged",
+                        "params": {
+                            "schemaDir": ctx.migration.schema_dir,
+                        },
+                    });
+                    write_message(&mut output, &notification).await?;
+                }
+            }
+        }
+    }
+}
+
+impl ProxyContext {
+    async fn handle(&mut self, request: &Request) -> anyhow::Result<Option<Value>> {
+        let result = match &request.method[..] {
+            "initialize" => json!({
+                "serverInfo": {
+                    "name": BRANDING_CLI_CMD,
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "methods": METHODS,
+                "projectRoot": self.project.location.root,
+                "schemaDir": self.migration.schema_dir,
+            }),
+            "connection/params" => {
+                let config = self.connector.get()?;
+                json!({
+                    "instanceName": config.instance_name().map(|n| n.to_string()),
+                    "address": config.display_addr().to_string(),
+                    "user": config.user(),
+                    "branch": config.branch(),
+                })
+            }
+            "schema/describe" => {
+                let mut cli = self.connector.connect().await?;
+                let sdl: String = cli
+                    .query_required_single("DESCRIBE SCHEMA AS SDL", &())
+                    .await?;
+                json!({ "sdl": sdl })
+            }
+            "migration/status" => {
+                let mut cli = self.connector.connect().await?;
+                let summary = migrations::status_summary(&mut cli, &self.migration).await?;
+                serde_json::to_value(summary)?
+            }
+            "watch/subscribe" => {
+                self.subscribed = true;
+                Value::Null
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(result))
+    }
+}
+
+async fn read_message<R: AsyncBufRead + Unpin>(input: &mut R) -> anyhow::Result<Option<Request>> {
+    let mut content_length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if input.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = content_length.context("message has no Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).await?;
+    Ok(Some(
+        serde_json::from_slice(&body).context("invalid JSON-RPC message")?,
+    ))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(output: &mut W, msg: &Value) -> anyhow::Result<()> {
+    let body = serde_json::to_vec(msg)?;
+    output
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    output.write_all(&body).await?;
+    output.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_message;
+
+    #[tokio::test]
+    async fn read_framed_message() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#;
+        let data = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let mut input = data.as_bytes();
+        let request = read_message(&mut input).await.unwrap().unwrap();
+        assert_eq!(request.method, "initialize");
+        assert_eq!(request.id, Some(1.into()));
+        assert!(read_message(&mut input).await.unwrap().is_none());
+    }
+}
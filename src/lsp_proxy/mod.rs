@@ -0,0 +1,5 @@
+pub mod options;
+
+mod main;
+
+pub use main::run;
@@ -0,0 +1,9 @@
+use crate::options::ConnectionOptions;
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct LspProxyCommand {
+    /// Connection options are global arguments, their values are used
+    /// through `Options::conn_options` like for the other commands
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+}
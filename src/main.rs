@@ -13,6 +13,8 @@ use crate::options::{Options, UsageError};
 
 mod analyze;
 mod async_util;
+mod auth;
+mod bench;
 mod branch;
 mod branding;
 mod browser;
@@ -26,13 +28,20 @@ mod completion;
 mod config;
 mod connect;
 mod credentials;
+mod doctor;
 mod error_display;
+mod fan_out;
+mod fetch;
 mod format;
 mod highlight;
 mod hint;
+mod hooks;
+mod http;
 mod interactive;
 mod interrupt;
+mod keychain;
 mod log_levels;
+mod lsp_proxy;
 mod markdown;
 mod migrations;
 mod non_interactive;
@@ -45,9 +54,13 @@ mod process;
 mod prompt;
 mod question;
 mod repl;
+mod seed;
+mod session_config;
+mod sql_statement;
 mod statement;
 mod table;
 mod tty_password;
+mod upload;
 mod variables;
 mod version_check;
 mod watch;
@@ -55,6 +68,9 @@ mod watch;
 fn main() {
     match _main() {
         Ok(()) => {}
+        Err(ref e) if print::structured::is_error_json() => {
+            exit(error_display::print_error_json(e));
+        }
         Err(ref e) => {
             let mut err = e;
             let mut code = 1;
@@ -78,23 +94,28 @@ fn main() {
                     print::error!(" <empty error message>");
                 }
                 for e in error_chain {
-                    eprintln!("  Caused by: {e}");
+                    error_detail(format_args!("  Caused by: {e}"));
                 }
             }
             for item in err.chain() {
                 if let Some(e) = item.downcast_ref::<hint::HintedError>() {
-                    eprintln!(
+                    error_detail(format_args!(
                         "  Hint: {}",
                         e.hint.lines().collect::<Vec<_>>().join("\n        ")
-                    );
+                    ));
+                } else if let Some(hint) = item
+                    .downcast_ref::<connect::ConnectionError>()
+                    .and_then(|e| e.hint())
+                {
+                    error_detail(format_args!("  Hint: {hint}"));
                 } else if item.is::<bug::Bug>() {
-                    eprintln!(
+                    error_detail(format_args!(
                         "  Hint: This is most likely a bug in {BRANDING} \
                         or command-line tools. Please consider opening an \
                         issue at \
                         https://github.com/edgedb/edgedb-cli/issues/new\
                         ?template=bug_report.md"
-                    );
+                    ));
                     code = 13;
                 } else if let Some(e) = e.downcast_ref::<commands::ExitCode>() {
                     code = e.code();
@@ -105,6 +126,14 @@ fn main() {
     }
 }
 
+fn error_detail(line: std::fmt::Arguments) {
+    if print::structured::is_json() {
+        print::structured::write_record("error", line.to_string().trim_start());
+    } else {
+        eprintln!("{line}");
+    }
+}
+
 fn is_cli_upgrade(cmd: &Option<options::Command>) -> bool {
     use cli::options::CliCommand;
     use cli::options::Command::Upgrade;
@@ -142,26 +171,33 @@ fn _main() -> anyhow::Result<()> {
     opt.conn_options.validate()?;
     let cfg = config::get_config();
 
+    print::structured::set_format(opt.log_format);
+    print::structured::set_error_format(opt.error_format);
+    hooks::set_skip(opt.skip_hooks);
     let mut builder =
         env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
     log_levels::init(&mut builder, &opt);
     builder.init();
 
-    let cfg = cfg.unwrap_or_else(|e| {
+    let (cfg, errors) = cfg;
+    for e in errors {
         log::warn!("Config error: {:#}", e);
-        Default::default()
-    });
+    }
 
     // Check the executable name and warn on older names, but not for self-install.
     if !is_cli_self_install(&opt.subcommand) && cfg!(feature = "gel") {
         cli::install::check_executables();
     }
 
+    http::init(opt.no_proxy, &cfg.http)?;
+
     if !is_cli_upgrade(&opt.subcommand) {
         version_check::check(opt.no_cli_update_check)?;
     }
 
-    if opt.subcommand.is_some() {
+    if let Some(pattern) = &opt.instances {
+        fan_out::run(&opt, pattern)
+    } else if opt.subcommand.is_some() {
         commands::cli::main(&opt)
     } else {
         cli::directory_check::check_and_warn();
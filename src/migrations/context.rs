@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::hooks::Hooks;
 use crate::migrations::options::MigrationConfig;
 use crate::portable::project;
 
@@ -9,6 +10,10 @@ pub struct Context {
     pub schema_dir: PathBuf,
 
     pub quiet: bool,
+
+    /// Project hooks, `None` if hooks are not configured or there is no
+    /// project
+    pub hooks: Option<Hooks>,
 }
 
 impl Context {
@@ -16,13 +21,21 @@ impl Context {
         cfg: &MigrationConfig,
         quiet: bool,
     ) -> anyhow::Result<Context> {
+        let mut hooks = None;
         let schema_dir = if let Some(schema_dir) = &cfg.schema_dir {
             schema_dir.clone()
         } else if let Some(manifest_path) = get_project_path(None, true).await? {
             let config = project::manifest::read(&manifest_path)?;
-            config
-                .project()
-                .resolve_schema_dir(manifest_path.parent().unwrap())?
+            let root = manifest_path.parent().unwrap();
+            let schema_dir = config.project().resolve_schema_dir(root)?;
+            hooks = Hooks::for_project(&project::Context {
+                location: project::Location {
+                    root: root.to_path_buf(),
+                    manifest: manifest_path.clone(),
+                },
+                manifest: config,
+            });
+            schema_dir
         } else {
             let default_dir: PathBuf = "./dbschema".into();
             if !default_dir.exists() {
@@ -31,7 +44,11 @@ impl Context {
             default_dir
         };
 
-        Ok(Context { schema_dir, quiet })
+        Ok(Context {
+            schema_dir,
+            quiet,
+            hooks,
+        })
     }
     pub fn for_project(project: &project::Context) -> anyhow::Result<Context> {
         Ok(Context {
@@ -40,6 +57,7 @@ impl Context {
                 .project()
                 .resolve_schema_dir(&project.location.root)?,
             quiet: false,
+            hooks: Hooks::for_project(project),
         })
     }
 }
@@ -27,6 +27,7 @@ use crate::commands::{ExitCode, Options};
 use crate::connect::Connection;
 use crate::error_display::print_query_error;
 use crate::highlight;
+use crate::hooks::{self, Action};
 use crate::migrations::context::Context;
 use crate::migrations::dev_mode;
 use crate::migrations::edb::{execute, execute_if_connected, query_row};
@@ -34,12 +35,17 @@ use crate::migrations::migration;
 use crate::migrations::options::CreateMigration;
 use crate::migrations::print_error::print_migration_error;
 use crate::migrations::prompt;
+use crate::migrations::reverse;
+use crate::migrations::snapshot;
 use crate::migrations::source_map::{Builder, SourceMap};
+use crate::migrations::split;
 use crate::migrations::squash;
+use crate::migrations::status;
 use crate::migrations::timeout;
+use crate::migrations::tui;
 use crate::platform::{is_legacy_schema_file, is_schema_file, tmp_file_name};
 use crate::print::style::Styler;
-use crate::print::{self, AsRelativeToCurrentDir};
+use crate::print::{self, msg, AsRelativeToCurrentDir};
 use crate::question;
 
 const SAFE_CONFIDENCE: f64 = 0.99999;
@@ -128,6 +134,7 @@ struct InteractiveMigration<'a> {
     save_point: usize,
     operations: Vec<Set<String>>,
     confirmed: Vec<String>,
+    tui: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -258,21 +265,15 @@ async fn choice(prompt: &str) -> anyhow::Result<Choice> {
     q.async_ask().await
 }
 
-#[context("could not read schema in {}", ctx.schema_dir.display())]
-async fn gen_start_migration(ctx: &Context) -> anyhow::Result<(String, SourceMap<SourceName>)> {
-    let mut bld = Builder::new();
-    bld.add_lines(SourceName::Prefix, "START MIGRATION TO {");
-    let mut dir = match fs::read_dir(&ctx.schema_dir).await {
+/// Lists schema files in the schema directory, sorted by name
+pub async fn schema_file_paths(schema_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut dir = match fs::read_dir(schema_dir).await {
         Ok(dir) => dir,
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {
-            bld.add_lines(SourceName::Suffix, "};");
-            return Ok(bld.done());
-        }
-        Err(e) => Err(e).context(format!("cannot read {:?}", ctx.schema_dir))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => Err(e).context(format!("cannot read {schema_dir:?}"))?,
     };
 
     let mut paths: Vec<PathBuf> = Vec::new();
-    let mut has_legacy_paths: bool = false;
     while let Some(item) = dir.next_entry().await? {
         let fname = item.file_name();
         let lossy_name = fname.to_string_lossy();
@@ -281,20 +282,28 @@ async fn gen_start_migration(ctx: &Context) -> anyhow::Result<(String, SourceMap
             && item.file_type().await?.is_file()
         {
             paths.push(item.path());
-            if cfg!(feature = "gel") && is_legacy_schema_file(&lossy_name) {
-                has_legacy_paths = true;
-            }
         }
     }
+    paths.sort();
+    Ok(paths)
+}
+
+#[context("could not read schema in {}", ctx.schema_dir.display())]
+async fn gen_start_migration(ctx: &Context) -> anyhow::Result<(String, SourceMap<SourceName>)> {
+    let mut bld = Builder::new();
+    bld.add_lines(SourceName::Prefix, "START MIGRATION TO {");
+    let paths = schema_file_paths(&ctx.schema_dir).await?;
 
+    let has_legacy_paths = paths.iter().any(|p| {
+        p.file_name()
+            .map_or(false, |n| is_legacy_schema_file(&n.to_string_lossy()))
+    });
     if cfg!(feature = "gel") && has_legacy_paths {
         print::warn!(
             "Legacy schema file extension '.esdl' detected. Consider renaming them to '.gel'."
         );
     }
 
-    paths.sort();
-
     for path in paths {
         let chunk = read_schema_file(&path).await?;
         bld.add_lines(SourceName::File(path.clone()), &chunk);
@@ -469,7 +478,7 @@ async fn non_interactive_populate(
             if proposal.confidence >= SAFE_CONFIDENCE {
                 if !proposal.required_user_input.is_empty() {
                     for input in proposal.required_user_input {
-                        eprintln!("Input required: {}", input.prompt);
+                        msg!("Input required: {}", input.prompt);
                     }
                     anyhow::bail!("Cannot apply migration without user input");
                 }
@@ -477,15 +486,16 @@ async fn non_interactive_populate(
                     execute(cli, &statement.text, None).await?;
                 }
             } else {
-                eprintln!("{BRANDING} intended to apply the following migration:");
+                msg!("{BRANDING} intended to apply the following migration:");
                 for statement in proposal.statements {
                     for line in statement.text.lines() {
-                        eprintln!("    {line}");
+                        msg!("    {line}");
                     }
                 }
-                eprintln!(
+                msg!(
                     "But confidence is {}, below minimum threshold of {}",
-                    proposal.confidence, SAFE_CONFIDENCE
+                    proposal.confidence,
+                    SAFE_CONFIDENCE
                 );
                 anyhow::bail!(
                     "{BRANDING} is unable to make a decision. Please run in \
@@ -530,6 +540,7 @@ impl InteractiveMigration<'_> {
             save_point: 0,
             operations: vec![Set::new()],
             confirmed: Vec::new(),
+            tui: false,
         }
     }
     async fn save_point(&mut self) -> Result<(), Error> {
@@ -549,6 +560,7 @@ impl InteractiveMigration<'_> {
         .await
     }
     async fn run(mut self, options: &CreateMigration) -> anyhow::Result<CurrentMigration> {
+        self.tui = options.interactive_tui;
         self.save_point().await?;
         loop {
             let descr =
@@ -583,6 +595,11 @@ impl InteractiveMigration<'_> {
             .as_ref()
             .map(|op| cur_oper.contains(op))
             .unwrap_or(false);
+        let mut statements = proposal
+            .statements
+            .iter()
+            .map(|s| s.text.clone())
+            .collect::<Vec<_>>();
         let input;
         if already_approved {
             input = loop {
@@ -609,13 +626,35 @@ impl InteractiveMigration<'_> {
         } else {
             let prompt = if let Some(prompt) = &proposal.prompt {
                 prompt
+            } else if self.tui {
+                "Apply the DDL statements?"
             } else {
                 println!("The following DDL statements will be applied:");
                 print_statements(proposal.statements.iter().map(|s| &s.text));
                 "Apply the DDL statements?"
             };
             loop {
-                match self.cli.ping_while(choice(prompt)).await? {
+                let decision = if self.tui {
+                    let answer = tui::ask(
+                        prompt.to_string(),
+                        statements.clone(),
+                        self.confirmed.clone(),
+                        self.save_point > 0,
+                    );
+                    match self.cli.ping_while(answer).await? {
+                        tui::Answer::Apply(edited) => {
+                            statements = edited;
+                            Yes
+                        }
+                        tui::Answer::Reject => No,
+                        tui::Answer::Back => Back,
+                        tui::Answer::Split => Split,
+                        tui::Answer::Quit => Quit,
+                    }
+                } else {
+                    self.cli.ping_while(choice(prompt)).await?
+                };
+                match decision {
                     Yes => {
                         let input_res = self
                             .cli
@@ -650,7 +689,7 @@ impl InteractiveMigration<'_> {
                     }
                     Back => {
                         if self.save_point == 0 {
-                            eprintln!("No EdgeQL statements confirmed, nothing to move back from");
+                            msg!("No EdgeQL statements confirmed, nothing to move back from");
                             continue;
                         }
                         self.save_point -= 1;
@@ -668,24 +707,24 @@ impl InteractiveMigration<'_> {
                 }
             }
         }
-        for statement in &proposal.statements {
-            let text = substitute_placeholders(&statement.text, &input)?;
+        for statement in &statements {
+            let text = substitute_placeholders(statement, &input)?;
             match execute(self.cli, &text, None).await {
                 Ok(()) => {}
                 Err(e) => {
                     if e.is::<QueryError>() {
                         print_query_error(&e, &text, false, "<statement>")?;
                     } else if print::use_color() {
-                        eprintln!(
+                        msg!(
                             "{}: {:#}",
                             "Error applying statement".bold().light_red(),
                             e.to_string().bold().white(),
                         );
                     } else {
-                        eprintln!("Error applying statement: {e:#}");
+                        msg!("Error applying statement: {e:#}");
                     }
                     if self.cli.is_consistent() {
-                        eprintln!("Rolling back last operation...");
+                        msg!("Rolling back last operation...");
                         self.rollback().await?;
                         return Ok(());
                     } else {
@@ -760,6 +799,69 @@ where
     _write_migration(descr, filename.as_ref(), verbose).await
 }
 
+/// Splits text into lines, marking the ones starting inside of a multi-line
+/// token (e.g. a string literal), which must be kept as is
+fn split_lines(text: &str) -> Vec<(bool, &str)> {
+    let mut multiline = Vec::new();
+    for token in Tokenizer::new(text) {
+        // tokenizer errors are reported when the migration is applied
+        let Ok(token) = token else { break };
+        let span = (token.span.start as usize, token.span.end as usize);
+        if text[span.0..span.1].contains('\n') {
+            multiline.push(span);
+        }
+    }
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            let inside = multiline.iter().any(|&(s, e)| s < start && start < e);
+            (inside, line.strip_suffix('\n').unwrap_or(line))
+        })
+        .collect()
+}
+
+/// Indents statement for the migration file, this doesn't change the hash
+fn indent(statement: &str) -> String {
+    let mut result = String::with_capacity(statement.len() * 2);
+    for (inside, line) in split_lines(statement) {
+        if !inside {
+            result.push_str("  ");
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+/// Removes common indentation and surrounding blank lines from the script
+/// recorded in the database, reverting what [`indent`] did
+pub fn dedent(script: &str) -> String {
+    let script = script.trim_end();
+    let first = script.len() - script.trim_start().len();
+    let script = &script[script[..first].rfind('\n').map_or(0, |i| i + 1)..];
+    let lines = split_lines(script);
+    let common = lines
+        .iter()
+        .filter(|(inside, line)| !inside && !line.trim().is_empty())
+        .map(|(_, line)| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut result = String::with_capacity(script.len());
+    for (inside, line) in lines {
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        if inside {
+            result.push_str(line);
+        } else {
+            result.push_str(line.get(common..).unwrap_or(line.trim_start()));
+        }
+    }
+    result
+}
+
 #[context("could not write migration file {}", filepath.display())]
 async fn _write_migration<'a, T>(
     descr: &'a impl MigrationToText<'a, T>,
@@ -783,9 +885,7 @@ where
         .await?;
     file.write_all(b"{\n").await?;
     for statement in descr.statements() {
-        for line in statement.lines() {
-            file.write_all(format!("  {line}\n").as_bytes()).await?;
-        }
+        file.write_all(indent(statement).as_bytes()).await?;
     }
     file.write_all(b"};\n").await?;
     file.flush().await?;
@@ -795,13 +895,13 @@ where
     let filepath = filepath.as_relative().display();
     if verbose {
         if print::use_color() {
-            eprintln!(
+            msg!(
                 "{} {}, id: {id}",
                 "Created".bold().light_green(),
                 filepath.to_string().bold().white(),
             );
         } else {
-            eprintln!("Created {filepath}, id: {id}");
+            msg!("Created {filepath}, id: {id}");
         }
     }
     Ok(())
@@ -828,8 +928,9 @@ async fn _create(
     create: &CreateMigration,
 ) -> anyhow::Result<()> {
     let ctx = Context::from_project_or_config(&create.cfg, false).await?;
+    hooks::run(ctx.hooks.as_ref(), Action::MigrationCreateBefore, cli, &[]).await?;
 
-    if dev_mode::check_client(cli).await? {
+    if create.from_ddl.is_none() && dev_mode::check_client(cli).await? {
         let dev_num = query_row::<i64>(
             cli,
             "SELECT count((
@@ -840,7 +941,20 @@ async fn _create(
         .await?;
         if dev_num > 0 {
             log::info!("Detected dev-mode migrations");
-            return dev_mode::create(cli, &ctx, options, create).await;
+            dev_mode::create(cli, &ctx, options, create).await?;
+            let last = migration::read_all(&ctx, true)
+                .await?
+                .keys()
+                .last()
+                .cloned();
+            let created = last.into_iter().collect::<Vec<_>>();
+            return hooks::run(
+                ctx.hooks.as_ref(),
+                Action::MigrationCreateAfter,
+                cli,
+                &created,
+            )
+            .await;
         }
     }
 
@@ -851,7 +965,11 @@ async fn _create(
             // This decision must be done early on because
             // of the bug in EdgeDB:
             //   https://github.com/edgedb/edgedb/issues/3958
-            if migrations.is_empty() {
+            if let Some(path) = &create.from_ddl {
+                let key = MigrationKey::Index((migrations.len() + 1) as u64);
+                let parent = migrations.keys().last().map(|x| &x[..]);
+                migration_from_ddl(cli, key, parent, path, create).await
+            } else if migrations.is_empty() {
                 first_migration(cli, &ctx, create).await
             } else {
                 let key = MigrationKey::Index((migrations.len() + 1) as u64);
@@ -863,10 +981,158 @@ async fn _create(
             timeout::restore_for_transaction(cli, old_timeout).await
         }
     }?;
-    write_migration(&ctx, &migration, !create.non_interactive).await?;
+    let parts = if create.split_by_module {
+        split_by_module(migration)?
+    } else {
+        vec![migration]
+    };
+    let mut created = Vec::with_capacity(parts.len());
+    for migration in &parts {
+        write_migration(&ctx, migration, !create.non_interactive).await?;
+        if create.with_down {
+            write_reverse(&ctx, migration, !create.non_interactive).await?;
+        }
+        created.push(migration.id()?.to_string());
+    }
+    // schema files don't necessarily match a hand-written migration
+    if let (Some(last), None) = (created.last(), &create.from_ddl) {
+        snapshot::write(&ctx, last).await?;
+    }
+    hooks::run(
+        ctx.hooks.as_ref(),
+        Action::MigrationCreateAfter,
+        cli,
+        &created,
+    )
+    .await?;
     Ok(())
 }
 
+/// Makes a migration of the statements in a hand-written DDL script, after
+/// checking that they apply on top of the last migration
+#[context("could not create migration from {}", path.display())]
+async fn migration_from_ddl(
+    cli: &mut Connection,
+    key: MigrationKey,
+    parent: Option<&str>,
+    path: &Path,
+    create: &CreateMigration,
+) -> anyhow::Result<FutureMigration> {
+    let script = fs::read_to_string(path).await?;
+    let statements = ddl_statements(&script)?;
+    if statements.is_empty() && !create.allow_empty {
+        print::warn!("No statements found in {}.", path.as_relative().display());
+        return Err(ExitCode::new(4))?;
+    }
+    if status::last_db_migration(cli).await?.as_deref() != parent {
+        anyhow::bail!(
+            "Database must be updated to the last migration \
+            on the filesystem for `migration create`. Run:\n  \
+            {BRANDING_CLI_CMD} migrate"
+        );
+    }
+    execute(cli, "START TRANSACTION", None).await?;
+    let res = execute(cli, &script, None).await;
+    execute_if_connected(cli, "ROLLBACK").await?;
+    if let Err(e) = res {
+        if e.is::<QueryError>() {
+            print_query_error(&e, &script, false, &path.display().to_string())?;
+            return Err(ExitCode::new(1))?;
+        }
+        return Err(e)?;
+    }
+    Ok(FutureMigration {
+        key,
+        parent: parent.unwrap_or("initial").to_string(),
+        statements,
+        id: OnceCell::new(),
+    })
+}
+
+/// Splits a DDL script into top-level statements, each ending with
+/// a semicolon
+fn ddl_statements(script: &str) -> anyhow::Result<Vec<String>> {
+    let mut result = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = None;
+    for token in Tokenizer::new(script) {
+        let token = token.map_err(|e| anyhow::anyhow!("{e}"))?;
+        match token.kind {
+            TokenKind::Semicolon if depth == 0 => {
+                if let Some(start) = start.take() {
+                    result.push(script[start..token.span.end as usize].to_string());
+                }
+                continue;
+            }
+            TokenKind::OpenBrace | TokenKind::OpenParen | TokenKind::OpenBracket => depth += 1,
+            TokenKind::CloseBrace | TokenKind::CloseParen | TokenKind::CloseBracket => {
+                depth = depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+        start.get_or_insert(token.span.start as usize);
+    }
+    if let Some(start) = start {
+        result.push(format!("{};", script[start..].trim_end()));
+    }
+    Ok(result)
+}
+
+fn split_by_module(migration: FutureMigration) -> anyhow::Result<Vec<FutureMigration>> {
+    let MigrationKey::Index(first_index) = migration.key else {
+        return Ok(vec![migration]);
+    };
+    let groups = match split::by_module(&migration.statements) {
+        Ok(groups) => groups,
+        Err(reason) => {
+            print::warn!("Cannot split migration by module: {reason}.");
+            return Ok(vec![migration]);
+        }
+    };
+    let mut parent = migration.parent;
+    let mut result = Vec::with_capacity(groups.len());
+    for (index, statements) in (first_index..).zip(groups) {
+        let part = FutureMigration {
+            key: MigrationKey::Index(index),
+            parent,
+            statements,
+            id: OnceCell::new(),
+        };
+        parent = part.id()?.to_string();
+        result.push(part);
+    }
+    Ok(result)
+}
+
+async fn write_reverse(
+    ctx: &Context,
+    migration: &FutureMigration,
+    interactive: bool,
+) -> anyhow::Result<()> {
+    let id = migration.id()?;
+    let statements = match reverse::derive(&migration.statements) {
+        Some(statements) => statements,
+        None if interactive => {
+            msg!("Reverse DDL for this migration could not be derived automatically.");
+            let input = question::String::new(
+                "Enter EdgeQL statements reverting the migration \
+                 (leave empty to mark it irreversible)",
+            )
+            .async_ask()
+            .await?;
+            if input.is_empty() {
+                return Ok(());
+            }
+            vec![input]
+        }
+        None => {
+            log::info!("Migration {id} is irreversible");
+            return Ok(());
+        }
+    };
+    reverse::write(ctx, id, &statements).await
+}
+
 pub async fn normal_migration(
     cli: &mut Connection,
     ctx: &Context,
@@ -1037,6 +1303,7 @@ async fn start_migration() {
     let ctx = Context {
         schema_dir,
         quiet: false,
+        hooks: None,
     };
 
     let res = gen_start_migration(&ctx).await.unwrap();
@@ -1050,3 +1317,35 @@ async fn start_migration() {
 
     assert_eq!(res_buf, expected_buf);
 }
+
+#[test]
+fn split_ddl() {
+    let script = "# comment\nCREATE TYPE Note {\n  CREATE PROPERTY text: str;\n};\n\
+        ALTER TYPE Note CREATE PROPERTY tag: str { SET default := 'a;b' }";
+    assert_eq!(
+        ddl_statements(script).unwrap(),
+        vec![
+            "CREATE TYPE Note {\n  CREATE PROPERTY text: str;\n};",
+            "ALTER TYPE Note CREATE PROPERTY tag: str { SET default := 'a;b' };",
+        ]
+    );
+    assert!(ddl_statements("  \n# nothing\n").unwrap().is_empty());
+}
+
+#[test]
+fn reindent() {
+    let script = "\n    CREATE TYPE Note {\n        CREATE PROPERTY text: str {\n            \
+                  SET default := 'two\n  lines';\n        };\n    };\n";
+    let plain = dedent(script);
+    assert_eq!(
+        plain,
+        "CREATE TYPE Note {\n    CREATE PROPERTY text: str {\n        \
+         SET default := 'two\n  lines';\n    };\n};"
+    );
+    assert_eq!(
+        indent(&plain),
+        "  CREATE TYPE Note {\n      CREATE PROPERTY text: str {\n          \
+         SET default := 'two\n  lines';\n      };\n  };\n"
+    );
+    assert_eq!(dedent(&indent(&plain)), plain);
+}
@@ -1,7 +1,12 @@
+use edgeql_parser::helpers::{quote_name, quote_string};
 use indexmap::IndexMap;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use crate::connect::Connection;
+use crate::migrations::edb::execute;
+
+/// Annotation holding the tag recorded by `migration apply --tag`
+pub(crate) const TAG_ANNOTATION: &str = "std::description";
 
 #[derive(Debug, Clone, gel_tokio::Queryable)]
 // TODO(tailhook) this has to be open-ended enumeration
@@ -10,6 +15,12 @@ pub(crate) enum MigrationGeneratedBy {
     DDLStatement,
 }
 
+#[derive(Debug, Clone, gel_tokio::Queryable)]
+struct MigrationTag {
+    name: String,
+    tag: Option<String>,
+}
+
 pub(crate) trait SortableMigration {
     type ParentsIter<'a>: Iterator<Item = &'a String>
     where
@@ -166,3 +177,38 @@ pub(crate) async fn find_by_prefix(
     }
     Ok(all_similar.pop())
 }
+
+pub(crate) async fn set_tag(cli: &mut Connection, name: &str, tag: &str) -> anyhow::Result<()> {
+    execute(
+        cli,
+        format!(
+            "ALTER MIGRATION {} {{ CREATE ANNOTATION {TAG_ANNOTATION} := {}; }}",
+            quote_name(name),
+            quote_string(tag),
+        ),
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn read_tags(cli: &mut Connection) -> anyhow::Result<HashMap<String, String>> {
+    let tags = cli
+        .query::<MigrationTag, _>(
+            r###"
+            SELECT schema::Migration {
+                name,
+                tag := assert_single((
+                    SELECT .annotations { value := @value }
+                    FILTER .name = <str>$0
+                ).value),
+            }
+            "###,
+            &(TAG_ANNOTATION,),
+        )
+        .await?;
+    Ok(tags
+        .into_iter()
+        .filter_map(|m| Some((m.name, m.tag?)))
+        .collect())
+}
@@ -1,6 +1,10 @@
 use anyhow::Context as _;
+use edgeql_parser::keywords::Keyword;
+use edgeql_parser::tokenizer::{Kind as TokenKind, Token, Tokenizer};
 use fs_err as fs;
+use std::io;
 use std::iter::Once;
+use std::path::Path;
 
 use crate::commands::{ExitCode, Options};
 use crate::connect::Connection;
@@ -56,17 +60,29 @@ pub async fn extract(
     let temp_ctx = Context {
         schema_dir: temp_dir.path().to_path_buf(),
         quiet: false,
+        hooks: None,
     };
     let mut to_delete = Vec::new();
+    let mut data = String::new();
 
     loop {
         match (disk_iter.next(), db_iter.next()) {
-            (existing, Some((i, migration))) => {
+            (existing, Some((i, (_, mut migration)))) => {
                 let key = MigrationKey::Index((i + 1) as u64);
-                let dm = DatabaseMigration {
-                    key,
-                    migration: migration.1,
-                };
+                migration.script = create::dedent(&migration.script);
+                let dm = DatabaseMigration { key, migration };
+                if params.squash_dml {
+                    let id = dm.id()?;
+                    let statements = dml_statements(&dm.migration.script)
+                        .with_context(|| format!("cannot parse migration {id}"))?;
+                    if !statements.is_empty() {
+                        data.push_str(&format!("# from migration {id}\n"));
+                        for statement in statements {
+                            data.push_str(statement);
+                            data.push('\n');
+                        }
+                    }
+                }
                 if let Some((id, migration_file)) = existing {
                     if dm.id()? != id {
                         if params.non_interactive {
@@ -144,9 +160,18 @@ pub async fn extract(
         }
     }
 
+    // make sure that extracted files hash to the names recorded in the
+    // database, otherwise they would fail to apply
+    let extracted = migration::read_names(&temp_ctx).await?;
+    for path in &extracted {
+        migration::read_file(path, true)
+            .await
+            .context("extracted migration does not match the database")?;
+    }
+
     // copy migration files
     let mut updated = false;
-    for from in migration::read_names(&temp_ctx).await? {
+    for from in extracted {
         let to = src_ctx
             .schema_dir
             .join("migrations")
@@ -161,6 +186,9 @@ pub async fn extract(
         fs::remove_file(path)?;
         updated = true;
     }
+    if params.squash_dml {
+        updated |= write_data(&src_ctx.schema_dir.join("data.edgeql"), &data, params)?;
+    }
     if !updated {
         print::success!(
             "Migration history in {:?} and in the database are in sync.",
@@ -169,3 +197,95 @@ pub async fn extract(
     }
     Ok(())
 }
+
+/// Writes squashed DML statements, returns false if the file is up to date
+fn write_data(path: &Path, data: &str, params: &ExtractMigrations) -> anyhow::Result<bool> {
+    match fs::read_to_string(path) {
+        Ok(old) if old == data => return Ok(false),
+        Ok(_) if params.non_interactive && !params.force => {
+            anyhow::bail!(
+                "\"{}\" does not match DML statements of the migrations, \
+                 use `--force` to overwrite it",
+                path.as_relative().display(),
+            )
+        }
+        Ok(_) if !params.force => {
+            let q = question::Confirm::new_dangerous(format!(
+                "\"{}\" does not match DML statements of the migrations, overwrite it?",
+                path.as_relative().display()
+            ));
+            if !q.ask()? {
+                print::error!("Canceled.");
+                return Err(ExitCode::new(exit_codes::NOT_CONFIRMED))?;
+            }
+        }
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if data.is_empty() {
+                return Ok(false);
+            }
+        }
+        Err(e) => return Err(e)?,
+    }
+    print::success_msg("Writing", path.as_relative().display());
+    fs::write(path, data)?;
+    Ok(true)
+}
+
+fn is_keyword(token: &Token, keywords: &[&str]) -> bool {
+    match token.kind {
+        TokenKind::Keyword(Keyword(kw)) => keywords.contains(&kw),
+        _ => false,
+    }
+}
+
+/// Top-level DML statements of the migration script
+fn dml_statements(script: &str) -> anyhow::Result<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = None;
+    let mut is_dml = false;
+    for token in Tokenizer::new(script) {
+        let token = token.map_err(|e| anyhow::anyhow!("{e}"))?;
+        if start.is_none() {
+            start = Some(token.span.start as usize);
+            is_dml = is_keyword(
+                &token,
+                &[
+                    "insert", "update", "delete", "for", "select", "group", "with",
+                ],
+            );
+        } else if depth == 0 && is_keyword(&token, &["create", "alter", "drop"]) {
+            // `WITH MODULE ... CREATE ...`
+            is_dml = false;
+        }
+        match &*token.text {
+            "{" | "(" | "[" => depth += 1,
+            "}" | ")" | "]" => depth = depth.saturating_sub(1),
+            ";" if depth == 0 => {
+                if let Some(start) = start.take() {
+                    if is_dml {
+                        result.push(&script[start..token.span.end as usize]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+#[test]
+fn dml() {
+    let script = "CREATE TYPE Note;\n\
+        INSERT Note { text := 'a;b' };\n\
+        WITH MODULE default CREATE TYPE Other;\n\
+        UPDATE Note SET { text := 'c' };";
+    assert_eq!(
+        dml_statements(script).unwrap(),
+        vec![
+            "INSERT Note { text := 'a;b' };",
+            "UPDATE Note SET { text := 'c' };",
+        ]
+    );
+}
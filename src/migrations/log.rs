@@ -35,15 +35,21 @@ async fn _log_db(
     options: &MigrationLog,
 ) -> Result<(), anyhow::Error> {
     let migrations = db_migration::read_all(cli, false, false).await?;
-    let limit = options.limit.unwrap_or(migrations.len());
+    let tags = db_migration::read_tags(cli).await?;
+    let revs = migrations
+        .keys()
+        .map(|name| (name, tags.get(name)))
+        .filter(|(_, tag)| options.tag.is_none() || *tag == options.tag.as_ref())
+        .collect::<Vec<_>>();
+    let limit = options.limit.unwrap_or(revs.len());
+    let print_rev = |(name, tag): &(&String, Option<&String>)| match tag {
+        Some(tag) => println!("{name} ({tag})"),
+        None => println!("{name}"),
+    };
     if options.newest_first {
-        for rev in migrations.iter().rev().take(limit) {
-            println!("{}", rev.0);
-        }
+        revs.iter().rev().take(limit).for_each(print_rev);
     } else {
-        for rev in migrations.iter().take(limit) {
-            println!("{}", rev.0);
-        }
+        revs.iter().take(limit).for_each(print_rev);
     }
     Ok(())
 }
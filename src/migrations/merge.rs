@@ -6,6 +6,7 @@ use crate::migrations::create::{write_migration, MigrationKey, MigrationToText};
 use crate::migrations::db_migration::{read_all, DBMigration};
 use crate::migrations::migration::MigrationFile;
 use crate::migrations::{migrate, migration, Context};
+use crate::print::msg;
 
 pub struct MergeMigrations {
     pub base_migrations: IndexMap<String, MergeMigration>,
@@ -88,14 +89,14 @@ pub async fn get_merge_migrations(
     }
 
     if !diverging_migrations.is_empty() {
-        eprintln!(
+        msg!(
             "\nThe migration history of {} diverges from {}:",
             target.database(),
             base.database()
         );
 
         for (index, expecting, actual) in &diverging_migrations {
-            eprintln!(
+            msg!(
                 "{}. Expecting {} but has {}",
                 index,
                 &expecting[..7],
@@ -103,7 +104,7 @@ pub async fn get_merge_migrations(
             )
         }
 
-        eprintln!();
+        msg!();
 
         anyhow::bail!("Cannot complete fast-forward merge, the histories of {0} and {1} are incompatible. Try rebasing {1} onto {0}", base.database(), target.database())
     }
@@ -149,6 +150,7 @@ pub async fn write_merge_migrations(
     let temp_ctx = Context {
         schema_dir: temp_dir.path().to_path_buf(),
         quiet: false,
+        hooks: None,
     };
 
     for (_, migration) in migrations.flatten() {
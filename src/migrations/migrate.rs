@@ -3,6 +3,7 @@ use std::path::Path;
 
 use anyhow::Context as _;
 use colorful::Colorful;
+use edgeql_parser::preparser;
 use gel_protocol::common::{
     Capabilities, Cardinality, CompilationOptions, InputLanguage, IoFormat,
 };
@@ -18,6 +19,7 @@ use crate::commands::Options;
 use crate::connect::{Connection, ResponseStream};
 use crate::error_display::print_query_error;
 use crate::hint::HintExt;
+use crate::hooks::{self, Action, Hooks};
 use crate::migrations::context::Context;
 use crate::migrations::db_migration;
 use crate::migrations::db_migration::{DBMigration, MigrationGeneratedBy};
@@ -25,8 +27,12 @@ use crate::migrations::dev_mode;
 use crate::migrations::edb::{execute, execute_if_connected};
 use crate::migrations::migration::{self, MigrationFile};
 use crate::migrations::options::Migrate;
+use crate::migrations::reverse;
 use crate::migrations::timeout;
-use crate::print;
+use crate::migrations::NULL_MIGRATION;
+use crate::print::{self, msg};
+use crate::session_config::{self, SessionConfig};
+use crate::table::{self, Cell, Row, Table};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Operation<'a> {
@@ -94,23 +100,61 @@ pub async fn migrate(
     migrate: &Migrate,
 ) -> Result<(), anyhow::Error> {
     let old_state = cli.set_ignore_error_state();
-    let res = _migrate(cli, options, migrate).await;
+    let settings = session_settings(migrate);
+    let res = async_try! {
+        async {
+            session_config::apply(cli, &settings).await?;
+            _migrate(cli, options, migrate).await
+        },
+        finally async {
+            session_config::reset(cli, &settings).await
+        }
+    };
     cli.restore_state(old_state);
     res
 }
 
+/// Session settings from the options of the command, followed by the
+/// ones passed by `--session-config`
+fn session_settings(migrate: &Migrate) -> Vec<SessionConfig> {
+    let mut settings = Vec::new();
+    let mut set = |name: &str, value: &str| {
+        settings.push(SessionConfig {
+            name: name.into(),
+            value: value.into(),
+        })
+    };
+    if let Some(value) = &migrate.statement_timeout {
+        set("query_execution_timeout", value);
+    }
+    if let Some(value) = &migrate.lock_timeout {
+        set("lock_timeout", value);
+    }
+    if migrate.force_unsafe_ddl {
+        set("force_unsafe_ddl", "true");
+    }
+    if migrate.no_ddl_locking {
+        set("ddl_locking", "false");
+    }
+    settings.extend(migrate.session_config.iter().cloned());
+    settings
+}
+
 async fn _migrate(
     cli: &mut Connection,
     _options: &Options,
     migrate: &Migrate,
 ) -> Result<(), anyhow::Error> {
-    let ctx = Context::from_project_or_config(&migrate.cfg, migrate.quiet).await?;
+    let ctx = Context::from_project_or_config(&migrate.cfg, migrate.quiet || migrate.json).await?;
     if migrate.dev_mode {
         // TODO(tailhook) figure out progressbar in non-quiet mode
         return dev_mode::migrate(cli, &ctx, &ProgressBar::hidden()).await;
     }
-    let migrations = migration::read_all(&ctx, true).await?;
     let db_migrations = db_migration::read_all(cli, false, true).await?;
+    if let Some(target) = &migrate.down_to {
+        return downgrade(cli, &ctx, &db_migrations, target).await;
+    }
+    let migrations = migration::read_all(&ctx, true).await?;
     let last_db_rev = db_migrations.last().map(|kv| kv.0);
 
     let target_rev = if let Some(prefix) = &migrate.to_revision {
@@ -141,9 +185,9 @@ async fn _migrate(
                     msg = format!("{}", msg.bold().light_green());
                 }
                 if Some(&db_rev.name) == last_db_rev {
-                    eprintln!("{} Revision {}", msg, db_rev.name);
+                    msg!("{} Revision {}", msg, db_rev.name);
                 } else {
-                    eprintln!(
+                    msg!(
                         "{} Revision {} is the ancestor of the latest {}",
                         msg,
                         db_rev.name,
@@ -181,10 +225,17 @@ async fn _migrate(
         }
     };
     let migrations = slice(&migrations, last_db_rev, target_rev.as_ref())?;
+    if migrate.plan {
+        let path = migrations
+            .values()
+            .map(PathElem::Normal)
+            .collect::<Vec<_>>();
+        return print_plan(migrate, last_db_rev, &path, true).await;
+    }
     if migrations.is_empty() {
         if !migrate.quiet {
             if print::use_color() {
-                eprintln!(
+                msg!(
                     "{} Revision {}",
                     "Everything is up to date.".bold().light_green(),
                     last_db_rev
@@ -194,7 +245,7 @@ async fn _migrate(
                         .white(),
                 );
             } else {
-                eprintln!(
+                msg!(
                     "Everything is up to date. Revision {}",
                     last_db_rev.map(|m| &m[..]).unwrap_or("initial"),
                 );
@@ -203,6 +254,9 @@ async fn _migrate(
         return Ok(());
     }
     apply_migrations(cli, migrations, &ctx, migrate.single_transaction).await?;
+    if let Some(tag) = &migrate.tag {
+        tag_migrations(cli, migrations.keys(), tag).await?;
+    }
     if db_migrations.is_empty() {
         disable_ddl(cli).await?;
     }
@@ -215,7 +269,7 @@ async fn fixup(
     migrations: &IndexMap<String, MigrationFile>,
     db_migrations: &IndexMap<String, DBMigration>,
     target: &String,
-    _options: &Migrate,
+    options: &Migrate,
 ) -> anyhow::Result<()> {
     let fixups = migration::read_fixups(ctx, true).await?;
     let last_db_migration = db_migrations
@@ -285,6 +339,10 @@ async fn fixup(
         }
     };
 
+    if options.plan {
+        return print_plan(options, Some(last_db_mname), &path, false).await;
+    }
+
     let mut operations = Vec::with_capacity(path.len() * 2);
     for path_elem in path {
         match path_elem {
@@ -314,7 +372,139 @@ async fn fixup(
         }
     }
 
-    apply_migrations(cli, &operations, ctx, _options.single_transaction).await?;
+    apply_migrations(cli, &operations, ctx, options.single_transaction).await?;
+    if let Some(tag) = &options.tag {
+        // fixups rewrite the history, so tag the resulting revisions
+        // which were not in the database before
+        let applied = slice(migrations, None, Some(target))?
+            .keys()
+            .filter(|name| !db_migrations.contains_key(*name));
+        tag_migrations(cli, applied, tag).await?;
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Plan<'a> {
+    current: &'a str,
+    target: &'a str,
+    /// `false` if the database history is rewritten by fixup migrations
+    fast_forward: bool,
+    migrations: Vec<PlannedMigration<'a>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PlannedMigration<'a> {
+    id: &'a str,
+    path: &'a Path,
+    statements: usize,
+    /// Revision which history is rewritten to, for fixup migrations
+    fixup_target: Option<&'a str>,
+}
+
+async fn print_plan(
+    options: &Migrate,
+    current: Option<&String>,
+    path: &[PathElem<'_>],
+    fast_forward: bool,
+) -> anyhow::Result<()> {
+    let current = current.map(|m| &m[..]).unwrap_or(NULL_MIGRATION);
+    let mut migrations = Vec::with_capacity(path.len());
+    for elem in path {
+        let (PathElem::Normal(file) | PathElem::Fixup(file)) = elem;
+        migrations.push(PlannedMigration {
+            id: &file.data.id,
+            path: &file.path,
+            statements: count_statements(file).await?,
+            fixup_target: file.fixup_target.as_deref(),
+        });
+    }
+    let target = migrations
+        .last()
+        .map(|m| m.fixup_target.unwrap_or(m.id))
+        .unwrap_or(current);
+    let plan = Plan {
+        current,
+        target,
+        fast_forward,
+        migrations,
+    };
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+    if plan.migrations.is_empty() {
+        msg!("Nothing to apply. Revision {current}");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Revision", "File", "Statements"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for m in &plan.migrations {
+        let id = match m.fixup_target {
+            Some(target) => format!("{} (fixup to {target})", m.id),
+            None => m.id.to_string(),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&id),
+            Cell::new(&m.path.display().to_string()),
+            Cell::new(&m.statements.to_string()),
+        ]));
+    }
+    table.printstd();
+    if plan.fast_forward {
+        msg!(
+            "{} migrations would be applied on top of revision {current}.",
+            plan.migrations.len(),
+        );
+    } else {
+        msg!(
+            "{} migrations would be applied, rewriting the history of revision {current} \
+             with fixup migrations.",
+            plan.migrations.len(),
+        );
+    }
+    Ok(())
+}
+
+async fn count_statements(migration: &MigrationFile) -> anyhow::Result<usize> {
+    let data = fs::read_to_string(&migration.path)
+        .await
+        .with_context(|| format!("cannot read {:?}", migration.path))?;
+    let (start, end) = migration.data.text_range;
+    let mut text = data.get(start..end).unwrap_or_default();
+    let mut count = 0;
+    while !preparser::is_empty(text) {
+        match preparser::full_statement(text.as_bytes(), None) {
+            Ok(len) => {
+                count += 1;
+                text = &text[len..];
+            }
+            // statement without trailing semicolon
+            Err(_) => return Ok(count + 1),
+        }
+    }
+    Ok(count)
+}
+
+async fn tag_migrations(
+    cli: &mut Connection,
+    names: impl IntoIterator<Item = &String>,
+    tag: &str,
+) -> anyhow::Result<()> {
+    for name in names {
+        db_migration::set_tag(cli, name, tag)
+            .await
+            .with_context(|| format!("cannot tag migration {name}"))?;
+    }
     Ok(())
 }
 
@@ -459,7 +649,7 @@ pub async fn apply_migrations(
                 execute(cli, "START TRANSACTION", None).await?;
                 async_try! {
                     async {
-                        apply_migrations_inner(cli, migrations, !ctx.quiet).await
+                        apply_migrations_inner(cli, migrations, !ctx.quiet, ctx.hooks.as_ref()).await
                     },
                     except async {
                         execute_if_connected(cli, "ROLLBACK").await
@@ -469,7 +659,7 @@ pub async fn apply_migrations(
                     }
                 }
             } else {
-                apply_migrations_inner(cli, migrations, !ctx.quiet).await
+                apply_migrations_inner(cli, migrations, !ctx.quiet, ctx.hooks.as_ref()).await
             }
         },
         finally async {
@@ -486,13 +676,13 @@ pub async fn apply_migration(
     if verbose {
         let file_name = migration.path.file_name().unwrap();
         if print::use_color() {
-            eprintln!(
+            msg!(
                 "Applying {} ({})",
                 migration.data.id[..].bold().white(),
                 Path::new(file_name).display(),
             );
         } else {
-            eprintln!(
+            msg!(
                 "Applying {} ({})",
                 migration.data.id,
                 Path::new(file_name).display(),
@@ -506,7 +696,7 @@ pub async fn apply_migration(
 
     let res = execute_with_parse_callback(cli, &data, || {
         if verbose {
-            eprintln!("... parsed");
+            msg!("... parsed");
         }
     })
     .await;
@@ -521,9 +711,9 @@ pub async fn apply_migration(
 
     if verbose {
         if print::use_color() {
-            eprintln!("... {}", "applied".bold().green());
+            msg!("... {}", "applied".bold().green());
         } else {
-            eprintln!("... applied");
+            msg!("... applied");
         }
     }
     Ok(())
@@ -556,11 +746,15 @@ pub async fn apply_migrations_inner(
     cli: &mut Connection,
     migrations: &(impl AsOperations + ?Sized),
     verbose: bool,
+    hooks: Option<&Hooks>,
 ) -> anyhow::Result<()> {
     for operation in migrations.as_operations() {
         match operation {
             Operation::Apply(migration) => {
+                let applied = [migration.data.id.clone()];
+                hooks::run(hooks, Action::MigrationApplyBefore, cli, &applied).await?;
                 apply_migration(cli, migration, verbose).await?;
+                hooks::run(hooks, Action::MigrationApplyAfter, cli, &applied).await?;
             }
             Operation::Rewrite(migrations) => {
                 execute(cli, "START MIGRATION REWRITE", None).await?;
@@ -586,6 +780,113 @@ pub async fn apply_migrations_inner(
     Ok(())
 }
 
+async fn downgrade(
+    cli: &mut Connection,
+    ctx: &Context,
+    db_migrations: &IndexMap<String, DBMigration>,
+    target: &str,
+) -> anyhow::Result<()> {
+    let start = if target == NULL_MIGRATION {
+        0
+    } else {
+        let matches = db_migrations
+            .keys()
+            .enumerate()
+            .filter(|(_, name)| name.starts_with(target))
+            .collect::<Vec<_>>();
+        match matches[..] {
+            [] => anyhow::bail!("No applied revision with prefix {:?} found", target),
+            [(idx, _)] => idx + 1,
+            _ => anyhow::bail!("More than one revision matches prefix {:?}", target),
+        }
+    };
+    let to_revert = db_migrations
+        .get_range(start..)
+        .ok_or_else(|| bug::error("slicing error"))?;
+    if to_revert.is_empty() {
+        if !ctx.quiet {
+            msg!("Nothing to revert. Revision {target} is the latest one.");
+        }
+        return Ok(());
+    }
+
+    // Make sure that every step is reversible before touching the database
+    let mut reverse_ddl = Vec::with_capacity(to_revert.len());
+    for name in to_revert.keys().rev() {
+        let Some(text) = reverse::read(ctx, name).await? else {
+            return Err(
+                anyhow::anyhow!("Migration {name} is irreversible.").with_hint(|| {
+                    format!(
+                        "Write EdgeQL statements reverting it to {:?} if it is \
+                     safe to downgrade.",
+                        reverse::down_path(ctx, name),
+                    )
+                }),
+            )?;
+        };
+        reverse_ddl.push((name, text));
+    }
+    let parent = start
+        .checked_sub(1)
+        .and_then(|i| db_migrations.get_index(i))
+        .map(|(name, _)| &name[..])
+        .unwrap_or(NULL_MIGRATION);
+
+    execute(cli, "START TRANSACTION", None).await?;
+    async_try! {
+        async {
+            for (name, text) in &reverse_ddl {
+                if !ctx.quiet {
+                    msg!("Reverting {name}");
+                }
+                execute(cli, text, None)
+                    .await
+                    .with_context(|| format!("cannot revert migration {name}"))?;
+            }
+            let reverted: String = cli
+                .query_required_single("DESCRIBE SCHEMA AS DDL", &())
+                .await?;
+            // the statements are recorded as DDL migrations, this also
+            // rewinds the migration history, so that the migrations can be
+            // applied again later
+            execute(cli, format!("RESET SCHEMA TO {parent}"), None).await?;
+            let expected: String = cli
+                .query_required_single("DESCRIBE SCHEMA AS DDL", &())
+                .await?;
+            if reverted != expected {
+                return Err(anyhow::anyhow!(
+                    "Reverse migrations don't restore the schema of revision {parent}"
+                ))
+                .with_hint(|| {
+                    format!(
+                        "Check the statements in {:?}.",
+                        ctx.schema_dir.join("migrations").join("down"),
+                    )
+                })?;
+            }
+            anyhow::Ok(())
+        },
+        except async {
+            execute_if_connected(cli, "ROLLBACK").await
+        },
+        else async {
+            execute(cli, "COMMIT", None).await
+        }
+    }?;
+    if !ctx.quiet {
+        if print::use_color() {
+            msg!(
+                "{} Revision {}",
+                "Downgrade complete.".bold().light_green(),
+                parent.bold().white(),
+            );
+        } else {
+            msg!("Downgrade complete. Revision {parent}");
+        }
+    }
+    Ok(())
+}
+
 pub async fn disable_ddl(cli: &mut Connection) -> Result<(), anyhow::Error> {
     let ddl_setting = cli
         .query_required_single(
@@ -10,6 +10,7 @@ use regex::Regex;
 use tokio::fs;
 use tokio::io;
 
+use crate::branding::BRANDING_CLI_CMD;
 use crate::migrations::context::Context;
 use crate::migrations::grammar::parse_migration;
 use crate::migrations::NULL_MIGRATION;
@@ -179,10 +180,12 @@ async fn _read_all(
                     "Two files {:?} and {:?} have the same \
                     parent revision {:?}. Multiple branches in revision \
                     history are not supported yet, please rebase one of the \
-                    branches on top of the other.",
+                    branches on top of the other (`{} migration rebase-files` \
+                    can renumber unapplied migrations automatically).",
                     path,
                     o.get().path,
-                    data.parent_id
+                    data.parent_id,
+                    BRANDING_CLI_CMD,
                 );
             }
         }
@@ -10,10 +10,14 @@ mod migrate;
 mod migration;
 mod print_error;
 mod prompt;
+mod reverse;
+mod snapshot;
 mod source_map;
+mod split;
 mod squash;
 mod status;
 mod timeout;
+mod tui;
 
 pub mod dev_mode;
 pub mod merge;
@@ -30,6 +34,7 @@ pub use create::create;
 pub use edit::{edit, edit_no_check};
 pub use extract::extract;
 pub use migrate::migrate;
-pub use status::status;
+pub use rebase::rebase_files;
+pub use status::{status, status_summary};
 pub use upgrade_check::upgrade_check;
 pub use upgrade_format::upgrade_format;
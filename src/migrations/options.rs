@@ -7,6 +7,7 @@ use crate::branding::BRANDING;
 use crate::options::ConnectionOptions;
 use crate::portable::repository::Channel;
 use crate::portable::ver;
+use crate::session_config::SessionConfig;
 
 use edgedb_cli_derive::IntoArgs;
 
@@ -49,6 +50,14 @@ pub enum MigrationCmd {
     Extract(ExtractMigrations),
     /// Upgrades the format of migration files.
     UpgradeFormat(MigrationUpgradeFormat),
+    /// Fix conflicting migration files after merging or rebasing a
+    /// version control branch.
+    ///
+    /// Detects duplicate migration indexes and mismatching parent revisions
+    /// in `<schema-dir>/migrations`, then renumbers the migrations which are
+    /// not yet applied to the database and rewrites their parent revisions so
+    /// they form a single chain on top of the applied ones.
+    RebaseFiles(MigrationRebaseFiles),
 }
 
 #[derive(clap::Args, IntoArgs, Clone, Debug)]
@@ -78,10 +87,32 @@ pub struct CreateMigration {
     /// is only useful in non-interactive mode.
     #[arg(long)]
     pub allow_unsafe: bool,
+    /// Review proposed statements in a full-screen interface, where each
+    /// statement can be edited and approved separately.
+    #[arg(long, conflicts_with = "non_interactive")]
+    pub interactive_tui: bool,
     /// Create a new migration even if there are no changes (use this for
     /// data-only migrations).
     #[arg(long)]
     pub allow_empty: bool,
+    /// Create one migration per module, when the changes to different
+    /// modules do not depend on each other. Otherwise a single migration
+    /// is created as usual.
+    #[arg(long, conflicts_with = "squash")]
+    pub split_by_module: bool,
+    /// Create the migration from a hand-written DDL script instead of the
+    /// schema files. The statements are checked against the schema of the
+    /// last migration before the file is written.
+    #[arg(long, value_name = "file", value_hint = ValueHint::FilePath)]
+    #[arg(conflicts_with_all = ["squash", "interactive_tui"])]
+    pub from_ddl: Option<PathBuf>,
+    /// Record reverse DDL of the migration in `migrations/down`, which
+    /// allows reverting it with `migrate --down-to`. It is derived
+    /// automatically if the migration only creates objects, otherwise
+    /// it is asked for (unless `--non-interactive` is used, in which
+    /// case the migration stays irreversible).
+    #[arg(long, conflicts_with = "squash")]
+    pub with_down: bool,
     /// Print queries executed.
     #[arg(long, hide = true)]
     pub debug_print_queries: bool,
@@ -112,6 +143,16 @@ pub struct Migrate {
     #[arg(long, conflicts_with = "dev_mode")]
     pub to_revision: Option<String>,
 
+    /// Downgrade to a specified revision (or `initial`).
+    ///
+    /// Every migration newer than the revision must have reverse DDL
+    /// recorded by `migration create --with-down`, otherwise nothing is
+    /// changed. The reverse DDL is executed in a single transaction,
+    /// which is only committed if the resulting schema is the schema of
+    /// the revision. The migration history is reset to the revision.
+    #[arg(long, conflicts_with_all = &["dev_mode", "to_revision", "single_transaction"])]
+    pub down_to: Option<String>,
+
     /// Dev mode is used to temporarily apply schema on top of those found in
     /// the migration history. Usually used for testing purposes, as well as
     /// `edgedb watch` which creates a dev mode migration script each time
@@ -130,6 +171,57 @@ pub struct Migrate {
     /// Runs the migration(s) in a single transaction.
     #[arg(long = "single-transaction")]
     pub single_transaction: bool,
+
+    /// Abort any statement of the migrations running longer than the
+    /// given duration (e.g. '5 minutes'), to avoid holding locks on a
+    /// busy database for too long. Sets `query_execution_timeout` for the
+    /// session applying the migrations.
+    #[arg(long, value_name = "DURATION")]
+    pub statement_timeout: Option<String>,
+
+    /// Give up waiting for a lock held by other sessions after the given
+    /// duration. Sets `lock_timeout` for the session applying the
+    /// migrations.
+    #[arg(long, value_name = "DURATION")]
+    pub lock_timeout: Option<String>,
+
+    /// Apply DDL which the server considers unsafe on a busy database,
+    /// e.g. DDL rewriting large tables. Sets `force_unsafe_ddl` for the
+    /// session applying the migrations.
+    #[arg(long)]
+    pub force_unsafe_ddl: bool,
+
+    /// Do not take the global DDL lock while applying the migrations.
+    /// Sets `ddl_locking` to `false` for the session applying the
+    /// migrations.
+    #[arg(long)]
+    pub no_ddl_locking: bool,
+
+    /// Set a session setting while applying the migrations, e.g.
+    /// `--session-config allow_user_specified_id=true`. Can be repeated.
+    /// Only settings which can be changed with `CONFIGURE SESSION` are
+    /// allowed.
+    #[arg(long, value_name = "key=value")]
+    pub session_config: Vec<SessionConfig>,
+
+    /// Record a deployment tag (e.g. a release name) on each migration
+    /// applied by this command.
+    ///
+    /// Tags are shown by `migration log --from-db` and can be used to
+    /// filter its output.
+    #[arg(long, conflicts_with_all = &["dev_mode", "down_to"])]
+    pub tag: Option<String>,
+
+    /// Print the migrations which would be applied, with the number of
+    /// statements in each of them, and whether the database history can
+    /// be fast-forwarded or has to be rewritten by fixup migrations.
+    /// Nothing is changed in the database.
+    #[arg(long, visible_alias = "dry-run", conflicts_with_all = &["dev_mode", "down_to"])]
+    pub plan: bool,
+
+    /// Print the plan as JSON
+    #[arg(long, requires = "plan")]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -138,8 +230,12 @@ pub struct ShowStatus {
     pub cfg: MigrationConfig,
 
     /// Do not print any messages, only indicate success by exit status.
-    #[arg(long)]
+    #[arg(long, conflicts_with = "json")]
     pub quiet: bool,
+
+    /// Print status of the database, migrations and schema files as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -165,6 +261,10 @@ pub struct MigrationLog {
     /// Show maximum N revisions (default: no limit).
     #[arg(long)]
     pub limit: Option<usize>,
+
+    /// Only show revisions applied with `migration apply --tag <TAG>`.
+    #[arg(long, conflicts_with = "from_fs")]
+    pub tag: Option<String>,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -213,6 +313,16 @@ pub struct UpgradeCheck {
     ])]
     pub to_channel: Option<Channel>,
 
+    /// Check against an already running server (e.g. a staging instance
+    /// of the next major version) instead of installing the target
+    /// version and starting a temporary server. All changes made by the
+    /// check are rolled back.
+    #[arg(long, value_name = "DSN")]
+    #[arg(conflicts_with_all=&[
+        "to_version", "to_nightly", "to_testing", "to_channel",
+    ])]
+    pub against: Option<String>,
+
     /// Monitor schema changes and check again on change.
     #[arg(long)]
     pub watch: bool,
@@ -231,6 +341,13 @@ pub struct ExtractMigrations {
     /// Force overwrite existing migration files.
     #[arg(long)]
     pub force: bool,
+    /// Also collect DML statements of all migrations (data migrations)
+    /// into a single `data.edgeql` file in the schema directory.
+    ///
+    /// Useful to keep data changes after `migration create --squash`,
+    /// which discards them.
+    #[arg(long)]
+    pub squash_dml: bool,
 }
 
 #[derive(clap::Args, IntoArgs, Clone, Debug)]
@@ -238,3 +355,12 @@ pub struct MigrationUpgradeFormat {
     #[command(flatten)]
     pub cfg: MigrationConfig,
 }
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct MigrationRebaseFiles {
+    #[command(flatten)]
+    pub cfg: MigrationConfig,
+    /// Only print the conflicts and planned renames, do not modify files.
+    #[arg(long)]
+    pub dry_run: bool,
+}
@@ -13,7 +13,7 @@ use gel_errors::{Error, InternalServerError};
 
 use crate::migrations::create::SourceName;
 use crate::migrations::source_map::SourceMap;
-use crate::print;
+use crate::print::{self, msg};
 
 fn end_of_last_token(data: &str) -> Option<u64> {
     let mut tokenizer = Tokenizer::new(data);
@@ -87,9 +87,9 @@ pub fn print_migration_error(
 
     if err.is::<InternalServerError>() {
         if let Some(traceback) = err.server_traceback() {
-            eprintln!("  Server traceback:");
+            msg!("  Server traceback:");
             for line in traceback.lines() {
-                eprintln!("      {line}");
+                msg!("      {line}");
             }
         }
     }
@@ -13,6 +13,7 @@ use rustyline::{error::ReadlineError, Cmd, KeyEvent, Modifiers};
 use rustyline::{Config, Editor, Helper};
 
 use crate::highlight;
+use crate::print::msg;
 use crate::print::style::Styler;
 use crate::prompt::{load_history, save_history};
 
@@ -79,7 +80,7 @@ pub fn expression(
     editor.bind_sequence(KeyEvent::new('\r', Modifiers::ALT), Cmd::AcceptLine);
     load_history(&mut editor, &history_name)
         .map_err(|e| {
-            eprintln!("Can't load history: {e:#}");
+            msg!("Can't load history: {e:#}");
         })
         .ok();
     editor.set_helper(Some(ExpressionHelper {
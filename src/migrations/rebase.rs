@@ -1,13 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 
 use fs_err as fs;
 
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::Options;
 use crate::connect::Connection;
 use crate::migrations::create::{MigrationKey, MigrationToText};
 use crate::migrations::db_migration::{read_all, DBMigration};
-use crate::migrations::migration::MigrationFile;
-use crate::migrations::{create, migrate, migration, Context};
-use crate::print;
+use crate::migrations::grammar::parse_migration;
+use crate::migrations::migration::{file_num, MigrationFile, SortKey};
+use crate::migrations::options::MigrationRebaseFiles;
+use crate::migrations::{create, migrate, migration, Context, NULL_MIGRATION};
+use crate::print::{self, msg, AsRelativeToCurrentDir};
 use anyhow::Context as _;
 use colorful::Colorful;
 use indexmap::IndexMap;
@@ -79,8 +84,8 @@ impl RebaseMigrations {
             }
         };
 
-        eprintln!("Last common migration is {last_common}");
-        eprintln!(
+        msg!("Last common migration is {last_common}");
+        msg!(
             "Since then, there are:\n- {} new {} on the target branch,\n- {} {} to rebase",
             self.target_migrations.len().to_string().green(),
             format_migration_on_length(self.target_migrations.len()),
@@ -263,6 +268,7 @@ pub async fn do_rebase(
     let temp_ctx = Context {
         schema_dir: temp_dir.path().to_path_buf(),
         quiet: false,
+        hooks: None,
     };
 
     // write all the migrations to disk.
@@ -296,10 +302,10 @@ pub async fn do_rebase(
             if last != Some(&migration.kind) {
                 match migration.kind {
                     RebaseMigrationKind::Target => {
-                        eprintln!("\nNew migrations on target branch:")
+                        msg!("\nNew migrations on target branch:")
                     }
                     RebaseMigrationKind::Source => {
-                        eprintln!("\nMigrations to rebase:")
+                        msg!("\nMigrations to rebase:")
                     }
                     RebaseMigrationKind::Base => {}
                 }
@@ -335,3 +341,237 @@ pub async fn write_rebased_migration_files(
 
     migrate::apply_migrations(connection, &migrations, context, true).await
 }
+
+/// A migration file which has to be renumbered or reparented.
+struct RenumberedFile {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    text: String,
+}
+
+pub async fn rebase_files(
+    cli: &mut Connection,
+    _opts: &Options,
+    params: &MigrationRebaseFiles,
+) -> anyhow::Result<()> {
+    let ctx = Context::from_project_or_config(&params.cfg, false).await?;
+    let db_migrations = read_all(cli, false, false).await?;
+
+    let mut applied = Vec::new();
+    let mut local = Vec::new();
+    for path in migration::read_names(&ctx).await? {
+        let data = migration::read_file(&path, false).await?;
+        let file = MigrationFile {
+            path,
+            fixup_target: None,
+            data,
+        };
+        if db_migrations.contains_key(&file.data.id) {
+            applied.push(file);
+        } else {
+            local.push(file);
+        }
+    }
+    applied.sort_by_key(|f| db_migrations.get_index_of(&f.data.id));
+    for (index, id) in db_migrations.keys().enumerate() {
+        if applied.get(index).map(|f| &f.data.id) != Some(id) {
+            anyhow::bail!(
+                "Migration {id} is applied to the database but \
+                 there is no file for it. \
+                 Run `{BRANDING_CLI_CMD} migration extract` first."
+            );
+        }
+    }
+    local.sort_by(|a, b| {
+        sort_key(a)
+            .cmp(&sort_key(b))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    let conflicts = find_conflicts(&applied, &local);
+    if conflicts.is_empty() {
+        print::success!("No conflicting migration files found.");
+        return Ok(());
+    }
+    for conflict in &conflicts {
+        print::warn!("{conflict}");
+    }
+
+    let renumbered = renumber(&applied, &local)?;
+    for file in &renumbered {
+        print::success_msg(
+            if params.dry_run {
+                "Would write"
+            } else {
+                "Writing"
+            },
+            format_args!(
+                "{} (was {})",
+                file.new_path.as_relative().display(),
+                file.old_path.as_relative().display(),
+            ),
+        );
+    }
+    if params.dry_run {
+        return Ok(());
+    }
+
+    // Remove all old files first, so that renamed files can't clash with
+    // the files that are not yet processed.
+    for file in &renumbered {
+        fs::remove_file(&file.old_path)?;
+    }
+    for file in &renumbered {
+        fs::write(&file.new_path, &file.text)?;
+    }
+
+    // Verify that the resulting chain is valid and starts with the
+    // revisions applied to the database.
+    let migrations = migration::read_all(&ctx, true).await?;
+    for (db_id, fs_id) in db_migrations.keys().zip(migrations.keys()) {
+        if db_id != fs_id {
+            anyhow::bail!(
+                "Migration history mismatch after renumbering: \
+                 expected {db_id}, found {fs_id}"
+            );
+        }
+    }
+    print::success!(
+        "Renumbered {} migration(s), history is consistent with the database.",
+        renumbered.len()
+    );
+    Ok(())
+}
+
+fn sort_key(file: &MigrationFile) -> SortKey {
+    match file_num(&file.path) {
+        Some(n) => SortKey::Numeric(n),
+        None => SortKey::Text(file.path.file_stem().unwrap()),
+    }
+}
+
+fn find_conflicts(applied: &[MigrationFile], local: &[MigrationFile]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut by_index = BTreeMap::new();
+    for file in applied.iter().chain(local) {
+        if let Some(index) = file_num(&file.path) {
+            by_index
+                .entry(index)
+                .or_insert_with(Vec::new)
+                .push(&file.path);
+        }
+    }
+    for (index, paths) in by_index {
+        if paths.len() > 1 {
+            result.push(format!(
+                "Duplicate migration index {index:05}: {}",
+                paths
+                    .iter()
+                    .map(|p| p.as_relative().display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    let mut parent = applied
+        .last()
+        .map(|f| f.data.id.as_str())
+        .unwrap_or(NULL_MIGRATION);
+    for file in local {
+        if file.data.parent_id != parent {
+            result.push(format!(
+                "File {} has parent revision {} instead of {}",
+                file.path.as_relative().display(),
+                file.data.parent_id,
+                parent,
+            ));
+        }
+        parent = &file.data.id;
+    }
+    result
+}
+
+fn renumber(
+    applied: &[MigrationFile],
+    local: &[MigrationFile],
+) -> anyhow::Result<Vec<RenumberedFile>> {
+    let mut result = Vec::new();
+    let mut parent = applied
+        .last()
+        .map(|f| f.data.id.clone())
+        .unwrap_or_else(|| NULL_MIGRATION.to_string());
+    for (offset, file) in local.iter().enumerate() {
+        let index = (applied.len() + offset + 1) as u64;
+        let mut text = fs::read_to_string(&file.path)?;
+        if file.data.parent_id != parent {
+            text = file.data.replace_parent_id(&text, &parent);
+        }
+        // Ranges are shifted if parent id has changed its length
+        // (i.e. `initial`), so parse the text again.
+        let data = parse_migration(&text)?;
+        let id = data.expected_id(&text)?;
+        if data.id != id {
+            text = data.replace_id(&text, &id);
+        }
+        let new_path = file
+            .path
+            .with_file_name(format!("{:05}-{}.edgeql", index, &id[..7]));
+        if new_path != file.path || data.id != id {
+            result.push(RenumberedFile {
+                old_path: file.path.clone(),
+                new_path,
+                text,
+            });
+        }
+        parent = id;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_conflicts, renumber};
+    use crate::migrations::grammar::parse_migration;
+    use crate::migrations::migration::{read_file, MigrationFile};
+    use crate::migrations::NULL_MIGRATION;
+    use fs_err as fs;
+
+    const FIRST: &str =
+        "CREATE MIGRATION m1tjyzfl33vvzwjd5izo5nyp4zdsekyvxpdm7zhtt5ufmqjzczopdq\n    \
+        ONTO initial\n{\n};\n";
+    const SECOND: &str =
+        "CREATE MIGRATION m154kc2cbzmzz2tzcjz5rpsspdew3azydwhwpkhcgkznpp6ibwhevq\n    \
+        ONTO initial\n{\n  CREATE TYPE Type1;\n};\n";
+
+    #[tokio::test]
+    async fn renumber_duplicate_index() {
+        let tmp_dir = tempfile::tempdir().expect("tmpdir");
+        let mut local = Vec::new();
+        for (name, text) in [
+            ("00001-m1tjyzf.edgeql", FIRST),
+            ("00001-m154kc2.edgeql", SECOND),
+        ] {
+            let path = tmp_dir.path().join(name);
+            fs::write(&path, text).unwrap();
+            local.push(MigrationFile {
+                data: parse_migration(text).unwrap(),
+                path,
+                fixup_target: None,
+            });
+        }
+
+        assert_eq!(find_conflicts(&[], &local).len(), 2);
+
+        let renumbered = renumber(&[], &local).unwrap();
+        assert_eq!(renumbered.len(), 1);
+        let file = &renumbered[0];
+        let new_name = file.new_path.file_name().unwrap().to_str().unwrap();
+        assert!(new_name.starts_with("00002-"));
+
+        fs::write(&file.new_path, &file.text).unwrap();
+        let data = read_file(&file.new_path, true).await.unwrap();
+        assert_eq!(data.parent_id, local[0].data.id);
+        assert_ne!(data.parent_id, NULL_MIGRATION);
+        assert!(new_name.ends_with(&format!("{}.edgeql", &data.id[..7])));
+    }
+}
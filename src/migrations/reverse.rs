@@ -0,0 +1,255 @@
+//! Reverse DDL ("down migrations") used by `migrate --down-to`.
+//!
+//! Reverse statements for the migration `<id>` are stored in
+//! `migrations/down/<id>.edgeql` by `migration create --with-down`. They
+//! can't be put into the migration file itself because they would change
+//! the migration id. A migration which has no such file is considered
+//! irreversible.
+//!
+//! On downgrade the statements are executed in a transaction. Executing
+//! them records new DDL migrations, so the history is then rewound with
+//! `RESET SCHEMA TO`, and the downgrade is only committed if the schema
+//! left by the statements is the same as the one of the target revision.
+
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use edgeql_parser::keywords::Keyword;
+use edgeql_parser::tokenizer::{Kind as TokenKind, Token, Tokenizer};
+use fn_error_context::context;
+use tokio::fs;
+use tokio::io::{self, AsyncWriteExt};
+
+use crate::migrations::context::Context;
+use crate::platform::tmp_file_name;
+
+pub fn down_path(ctx: &Context, id: &str) -> PathBuf {
+    ctx.schema_dir
+        .join("migrations")
+        .join("down")
+        .join(format!("{id}.edgeql"))
+}
+
+/// Reads the reverse DDL of the migration, `None` if there is none
+pub async fn read(ctx: &Context, id: &str) -> anyhow::Result<Option<String>> {
+    let path = down_path(ctx, id);
+    let text = match fs::read_to_string(&path).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", path.display())),
+    };
+    if text.trim().is_empty() {
+        anyhow::bail!("reverse migration file {} is empty", path.display());
+    }
+    Ok(Some(text))
+}
+
+pub async fn write(ctx: &Context, id: &str, statements: &[String]) -> anyhow::Result<()> {
+    _write(&down_path(ctx, id), statements).await
+}
+
+#[context("could not write reverse migration file {}", filepath.display())]
+async fn _write(filepath: &Path, statements: &[String]) -> anyhow::Result<()> {
+    let dir = filepath.parent().unwrap();
+    fs::create_dir_all(&dir).await?;
+    let tmp_file = filepath.with_file_name(tmp_file_name(filepath));
+    fs::remove_file(&tmp_file).await.ok();
+    let mut file = io::BufWriter::new(fs::File::create(&tmp_file).await?);
+    for statement in statements {
+        file.write_all(statement.trim_end().as_bytes()).await?;
+        file.write_all(b"\n").await?;
+    }
+    file.flush().await?;
+    drop(file);
+    fs::rename(&tmp_file, &filepath).await?;
+    Ok(())
+}
+
+/// Derives reverse DDL for the migration statements
+///
+/// Only statements which create new objects are reversed (by dropping them),
+/// for anything else `None` is returned.
+pub fn derive<'a>(statements: impl IntoIterator<Item = &'a String>) -> Option<Vec<String>> {
+    let mut result = statements
+        .into_iter()
+        .map(|s| reverse_statement(s))
+        .collect::<Option<Vec<_>>>()?;
+    result.reverse();
+    Some(result)
+}
+
+type Tokens<'t, 'a> = Peekable<std::slice::Iter<'t, Token<'a>>>;
+
+fn reverse_statement(statement: &str) -> Option<String> {
+    let tokens = Tokenizer::new(statement)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let mut tokens = tokens.iter().peekable();
+    let first = tokens.next()?;
+    if is_keyword(first, "create") {
+        skip_qualifiers(&mut tokens);
+        let kind = match keyword(tokens.next()?)? {
+            "type" => "TYPE",
+            "scalar" if is_keyword(tokens.next()?, "type") => "SCALAR TYPE",
+            "module" => "MODULE",
+            "alias" => "ALIAS",
+            "global" => "GLOBAL",
+            "extension" => "EXTENSION",
+            _ => return None,
+        };
+        let name = name(&mut tokens)?;
+        Some(format!("DROP {kind} {name};"))
+    } else if is_keyword(first, "alter") {
+        if !is_keyword(tokens.next()?, "type") {
+            return None;
+        }
+        let name = name(&mut tokens)?;
+        if tokens.next()?.kind != TokenKind::OpenBrace {
+            return None;
+        }
+        let mut drops = Vec::new();
+        loop {
+            let token = tokens.next()?;
+            if token.kind == TokenKind::CloseBrace {
+                break;
+            }
+            if !is_keyword(token, "create") {
+                return None;
+            }
+            skip_qualifiers(&mut tokens);
+            let pointer = match keyword(tokens.next()?)? {
+                "property" => "PROPERTY",
+                "link" => "LINK",
+                _ => return None,
+            };
+            let pointer_name = self::name(&mut tokens)?;
+            drops.push(format!("    DROP {pointer} {pointer_name};"));
+            skip_statement(&mut tokens)?;
+        }
+        if drops.is_empty() {
+            return None;
+        }
+        drops.reverse();
+        Some(format!("ALTER TYPE {name} {{\n{}\n}};", drops.join("\n")))
+    } else {
+        None
+    }
+}
+
+fn keyword(token: &Token) -> Option<&'static str> {
+    match token.kind {
+        TokenKind::Keyword(Keyword(kw)) => Some(kw),
+        _ => None,
+    }
+}
+
+fn is_keyword(token: &Token, kw: &str) -> bool {
+    keyword(token) == Some(kw)
+}
+
+fn skip_qualifiers(tokens: &mut Tokens) {
+    while let Some(token) = tokens.peek() {
+        match keyword(token) {
+            Some("abstract" | "required" | "optional" | "single" | "multi") => {
+                tokens.next();
+            }
+            _ => break,
+        }
+    }
+}
+
+/// Reads possibly qualified object name
+fn name(tokens: &mut Tokens) -> Option<String> {
+    let mut name = String::new();
+    while let Some(token) = tokens.peek() {
+        let expect_ident = name.is_empty() || name.ends_with("::");
+        match token.kind {
+            TokenKind::Ident if expect_ident => {}
+            _ if expect_ident && token.text.starts_with('`') => {}
+            _ if !expect_ident && token.text == "::" => {}
+            _ => break,
+        }
+        name.push_str(&token.text);
+        tokens.next();
+    }
+    if name.is_empty() || name.ends_with("::") {
+        return None;
+    }
+    Some(name)
+}
+
+/// Skips tokens up to and including the semicolon ending current
+/// nested statement
+fn skip_statement(tokens: &mut Tokens) -> Option<()> {
+    let mut depth = 0usize;
+    loop {
+        let token = tokens.next()?;
+        match token.kind {
+            TokenKind::OpenBrace | TokenKind::OpenParen | TokenKind::OpenBracket => depth += 1,
+            TokenKind::CloseBrace | TokenKind::CloseParen | TokenKind::CloseBracket => {
+                depth = depth.checked_sub(1)?;
+            }
+            TokenKind::Semicolon if depth == 0 => return Some(()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::derive;
+
+    fn reverse(statements: &[&str]) -> Option<Vec<String>> {
+        let statements = statements.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        derive(&statements)
+    }
+
+    #[test]
+    fn create_objects() {
+        assert_eq!(
+            reverse(&[
+                "CREATE MODULE blog IF NOT EXISTS;",
+                "CREATE ABSTRACT TYPE blog::Named {\n    \
+                    CREATE REQUIRED PROPERTY name: std::str;\n};",
+                "CREATE SCALAR TYPE blog::Status EXTENDING enum<Draft, Done>;",
+            ]),
+            Some(vec![
+                "DROP SCALAR TYPE blog::Status;".into(),
+                "DROP TYPE blog::Named;".into(),
+                "DROP MODULE blog;".into(),
+            ])
+        );
+    }
+
+    #[test]
+    fn alter_type() {
+        assert_eq!(
+            reverse(&["ALTER TYPE default::User {\n    \
+                CREATE PROPERTY email: std::str {\n        \
+                    CREATE CONSTRAINT std::exclusive;\n    };\n    \
+                CREATE MULTI LINK friends: default::User;\n};"]),
+            Some(vec!["ALTER TYPE default::User {\n    \
+                DROP LINK friends;\n    \
+                DROP PROPERTY email;\n};"
+                .into()])
+        );
+    }
+
+    #[test]
+    fn irreversible() {
+        assert_eq!(reverse(&["DROP TYPE default::User;"]), None);
+        assert_eq!(
+            reverse(&["ALTER TYPE default::User {\n    \
+                ALTER PROPERTY name {\n        SET REQUIRED;\n    };\n};"]),
+            None
+        );
+        assert_eq!(
+            reverse(&[
+                "CREATE TYPE default::Post;",
+                "ALTER TYPE default::User RENAME TO default::Person;",
+            ]),
+            None
+        );
+    }
+}
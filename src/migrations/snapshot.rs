@@ -0,0 +1,171 @@
+//! Hashes of schema files at the time a migration was created.
+//!
+//! Snapshot of the migration `<id>` is stored in
+//! `migrations/snapshots/<id>.json`. It is used by `migration status` to
+//! detect schema files modified after the last migration was created,
+//! without connecting to the database.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use fn_error_context::context;
+use tokio::fs;
+use tokio::io;
+
+use crate::migrations::context::Context;
+use crate::migrations::create::schema_file_paths;
+use crate::platform::tmp_file_name;
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    /// Hash of each schema file, by name relative to the schema dir
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileStatus {
+    Unchanged,
+    Modified,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub status: FileStatus,
+}
+
+fn snapshot_path(ctx: &Context, id: &str) -> PathBuf {
+    ctx.schema_dir
+        .join("migrations")
+        .join("snapshots")
+        .join(format!("{id}.json"))
+}
+
+pub async fn current(ctx: &Context) -> anyhow::Result<Snapshot> {
+    let mut files = BTreeMap::new();
+    for path in schema_file_paths(&ctx.schema_dir).await? {
+        let data = fs::read(&path)
+            .await
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        files.insert(relative_name(ctx, &path), hash(&data));
+    }
+    Ok(Snapshot { files })
+}
+
+fn relative_name(ctx: &Context, path: &Path) -> String {
+    path.strip_prefix(&ctx.schema_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn hash(data: &[u8]) -> String {
+    // schema files are compared regardless of line endings
+    let text = String::from_utf8_lossy(data).replace("\r\n", "\n");
+    blake3::hash(text.as_bytes()).to_hex().to_string()
+}
+
+/// Reads the snapshot of the migration, `None` if it wasn't recorded
+pub async fn read(ctx: &Context, id: &str) -> anyhow::Result<Option<Snapshot>> {
+    let path = snapshot_path(ctx, id);
+    match fs::read_to_string(&path).await {
+        Ok(text) => {
+            Ok(Some(serde_json::from_str(&text).with_context(|| {
+                format!("cannot decode {}", path.display())
+            })?))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+    }
+}
+
+/// Records current schema files as the snapshot of the migration
+pub async fn write(ctx: &Context, id: &str) -> anyhow::Result<()> {
+    let snapshot = current(ctx).await?;
+    _write(&snapshot_path(ctx, id), &snapshot).await
+}
+
+#[context("could not write schema snapshot {}", filepath.display())]
+async fn _write(filepath: &Path, snapshot: &Snapshot) -> anyhow::Result<()> {
+    let dir = filepath.parent().unwrap();
+    fs::create_dir_all(&dir).await?;
+    let tmp_file = filepath.with_file_name(tmp_file_name(filepath));
+    fs::remove_file(&tmp_file).await.ok();
+    fs::write(&tmp_file, serde_json::to_string_pretty(snapshot)?).await?;
+    fs::rename(&tmp_file, &filepath).await?;
+    Ok(())
+}
+
+/// Compares schema files with the snapshot of the migration. Returns `None`
+/// if there is no migration or no snapshot was recorded for it.
+pub async fn changes(ctx: &Context, id: Option<&str>) -> anyhow::Result<Option<Vec<FileChange>>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    let Some(old) = read(ctx, id).await? else {
+        return Ok(None);
+    };
+    Ok(Some(diff(&old, &current(ctx).await?)))
+}
+
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    for (path, hash) in &new.files {
+        let status = match old.files.get(path) {
+            Some(old_hash) if old_hash == hash => FileStatus::Unchanged,
+            Some(_) => FileStatus::Modified,
+            None => FileStatus::Added,
+        };
+        changes.push(FileChange {
+            path: path.clone(),
+            status,
+        });
+    }
+    for path in old.files.keys() {
+        if !new.files.contains_key(path) {
+            changes.push(FileChange {
+                path: path.clone(),
+                status: FileStatus::Removed,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, FileStatus, Snapshot};
+
+    fn snapshot(files: &[(&str, &str)]) -> Snapshot {
+        Snapshot {
+            files: files
+                .iter()
+                .map(|(p, h)| (p.to_string(), h.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn file_changes() {
+        let old = snapshot(&[("a.esdl", "1"), ("b.esdl", "2"), ("c.esdl", "3")]);
+        let new = snapshot(&[("a.esdl", "1"), ("b.esdl", "5"), ("d.esdl", "4")]);
+        let changes = diff(&old, &new)
+            .into_iter()
+            .map(|c| (c.path, c.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ("a.esdl".into(), FileStatus::Unchanged),
+                ("b.esdl".into(), FileStatus::Modified),
+                ("c.esdl".into(), FileStatus::Removed),
+                ("d.esdl".into(), FileStatus::Added),
+            ]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Splitting statements of a migration into one migration per module,
+//! used by `migration create --split-by-module`.
+//!
+//! Statements are grouped by the module of the object they change, groups
+//! are ordered by the first statement of each module. Splitting is refused
+//! if reordering would move a statement across another one from a different
+//! module which references it (judged by qualified names in the text).
+
+use std::collections::BTreeSet;
+
+use edgeql_parser::keywords::Keyword;
+use edgeql_parser::tokenizer::{Kind as TokenKind, Token, Tokenizer};
+
+struct Statement {
+    module: Option<String>,
+    references: BTreeSet<String>,
+}
+
+/// Returns statements split into groups, or the reason why they can't be
+/// split
+pub fn by_module(statements: &[String]) -> Result<Vec<Vec<String>>, String> {
+    let parsed = statements
+        .iter()
+        .map(|s| parse(s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut modules: Vec<&str> = Vec::new();
+    let mut group_of = Vec::with_capacity(parsed.len());
+    for (statement, text) in parsed.iter().zip(statements) {
+        let group = match &statement.module {
+            Some(module) => match modules.iter().position(|m| *m == module) {
+                Some(idx) => idx,
+                None => {
+                    modules.push(module);
+                    modules.len() - 1
+                }
+            },
+            // statements not belonging to a module (e.g. `CREATE EXTENSION`)
+            // are only allowed before the first module change
+            None if modules.is_empty() => 0,
+            None => {
+                return Err(format!(
+                    "statement {:?} does not belong to a module",
+                    first_line(text)
+                ))
+            }
+        };
+        group_of.push(group);
+    }
+    if modules.len() < 2 {
+        return Err("all changes are in a single module".into());
+    }
+
+    for (i, later) in parsed.iter().enumerate() {
+        for (j, earlier) in parsed[..i].iter().enumerate() {
+            if group_of[j] <= group_of[i] {
+                continue;
+            }
+            // `later` is going to be moved before `earlier`
+            let depends = |a: &Statement, b: &Statement| {
+                b.module.as_ref().is_some_and(|m| a.references.contains(m))
+            };
+            if depends(later, earlier) || depends(earlier, later) {
+                return Err(format!(
+                    "changes in modules `{}` and `{}` depend on each other",
+                    modules[group_of[i]], modules[group_of[j]],
+                ));
+            }
+        }
+    }
+
+    let mut groups = vec![Vec::new(); modules.len()];
+    for (text, group) in statements.iter().zip(group_of) {
+        groups[group].push(text.clone());
+    }
+    Ok(groups)
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+fn parse(statement: &str) -> Result<Statement, String> {
+    let tokens = Tokenizer::new(statement)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("cannot parse statement {:?}: {e}", first_line(statement)))?;
+    let names = qualified_names(&tokens);
+    let references = names
+        .iter()
+        .filter_map(|name| name.rsplit_once("::").map(|(module, _)| module.to_string()))
+        .collect::<BTreeSet<_>>();
+    let is_module_ddl = matches!(
+        &tokens[..],
+        [first, second, ..]
+            if is_keyword(first, &["create", "alter", "drop"])
+            && is_keyword(second, &["module"])
+    );
+    let module = if is_module_ddl {
+        Some(module_name(&tokens[2..]))
+    } else {
+        names
+            .first()
+            .and_then(|name| name.rsplit_once("::"))
+            .map(|(module, _)| module.to_string())
+    };
+    Ok(Statement { module, references })
+}
+
+fn is_keyword(token: &Token, keywords: &[&str]) -> bool {
+    match token.kind {
+        TokenKind::Keyword(Keyword(kw)) => keywords.contains(&kw),
+        _ => false,
+    }
+}
+
+fn is_name_part(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::Ident | TokenKind::Keyword(_)) || token.text.starts_with('`')
+}
+
+/// Name of the module in `CREATE MODULE <name>`, can be nested (`a::b`)
+fn module_name(tokens: &[Token]) -> String {
+    let mut name = String::new();
+    for token in tokens {
+        let expect_name = name.is_empty() || name.ends_with("::");
+        if (expect_name && is_name_part(token)) || (!expect_name && token.text == "::") {
+            name.push_str(&token.text);
+        } else {
+            break;
+        }
+    }
+    name.trim_end_matches("::").to_string()
+}
+
+/// All names containing a module part (`module::name`)
+fn qualified_names(tokens: &[Token]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let starts_name = is_name_part(&tokens[i])
+            && tokens.get(i + 1).is_some_and(|t| t.text == "::")
+            && (i == 0 || tokens[i - 1].text != "::");
+        if !starts_name {
+            i += 1;
+            continue;
+        }
+        let mut name = tokens[i].text.to_string();
+        i += 1;
+        while i + 1 < tokens.len() && tokens[i].text == "::" && is_name_part(&tokens[i + 1]) {
+            name.push_str("::");
+            name.push_str(&tokens[i + 1].text);
+            i += 2;
+        }
+        names.push(name);
+    }
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::by_module;
+
+    fn split(statements: &[&str]) -> Result<Vec<Vec<String>>, String> {
+        let statements = statements.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        by_module(&statements)
+    }
+
+    #[test]
+    fn independent_modules() {
+        assert_eq!(
+            split(&[
+                "CREATE MODULE blog IF NOT EXISTS;",
+                "CREATE TYPE default::User {\n    \
+                    CREATE REQUIRED PROPERTY name: std::str;\n};",
+                "CREATE TYPE blog::Post;",
+                "ALTER TYPE default::User {\n    \
+                    CREATE PROPERTY email: std::str;\n};",
+            ]),
+            Ok(vec![
+                vec![
+                    "CREATE MODULE blog IF NOT EXISTS;".to_string(),
+                    "CREATE TYPE blog::Post;".to_string(),
+                ],
+                vec![
+                    "CREATE TYPE default::User {\n    \
+                        CREATE REQUIRED PROPERTY name: std::str;\n};"
+                        .to_string(),
+                    "ALTER TYPE default::User {\n    \
+                        CREATE PROPERTY email: std::str;\n};"
+                        .to_string(),
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn dependent_modules() {
+        assert!(split(&[
+            "CREATE TYPE default::User;",
+            "CREATE TYPE blog::Post {\n    \
+                CREATE LINK author: default::User;\n};",
+            "ALTER TYPE default::User {\n    \
+                CREATE MULTI LINK posts: blog::Post;\n};",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn single_module() {
+        assert!(split(&["CREATE TYPE default::User;", "CREATE TYPE default::Post;"]).is_err());
+    }
+}
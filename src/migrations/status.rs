@@ -10,7 +10,8 @@ use crate::migrations::create::{execute_start_migration, CurrentMigration};
 use crate::migrations::edb::execute_if_connected;
 use crate::migrations::migration::{self, MigrationFile};
 use crate::migrations::options::ShowStatus;
-use crate::print;
+use crate::migrations::snapshot::{self, FileChange, FileStatus};
+use crate::print::{self, msg};
 
 async fn ensure_diff_is_empty(cli: &mut Connection, ctx: &Context) -> Result<(), anyhow::Error> {
     let data = cli
@@ -18,7 +19,7 @@ async fn ensure_diff_is_empty(cli: &mut Connection, ctx: &Context) -> Result<(),
         .await?;
     if !data.confirmed.is_empty() || !data.complete {
         if !ctx.quiet {
-            eprintln!(
+            msg!(
                 "Detected differences between \
                 database schema and schema source, \
                 in particular:"
@@ -29,14 +30,14 @@ async fn ensure_diff_is_empty(cli: &mut Connection, ctx: &Context) -> Result<(),
                     .flat_map(|p| p.statements.iter().map(|s| &s.text)),
             );
             for text in changes.take(3) {
-                eprintln!("    {}", text.lines().collect::<Vec<_>>().join("\n    "));
+                msg!("    {}", text.lines().collect::<Vec<_>>().join("\n    "));
             }
             let changes = data.confirmed.len() + data.proposed.map(|_| 1).unwrap_or(0);
             if changes > 3 {
-                eprintln!("... and {} more changes", changes - 3);
+                msg!("... and {} more changes", changes - 3);
             }
             print::error!("Some migrations are missing.");
-            eprintln!("  Use `{BRANDING_CLI_CMD} migration create`.");
+            msg!("  Use `{BRANDING_CLI_CMD} migration create`.");
         }
         return Err(ExitCode::new(2).into());
     }
@@ -48,19 +49,28 @@ pub async fn status(
     _options: &Options,
     status: &ShowStatus,
 ) -> Result<(), anyhow::Error> {
-    let ctx = Context::from_project_or_config(&status.cfg, status.quiet).await?;
+    let ctx = Context::from_project_or_config(&status.cfg, status.quiet || status.json).await?;
     let migrations = migration::read_all(&ctx, true).await?;
+    let changes = snapshot::changes(&ctx, migrations.keys().last().map(|k| &k[..])).await?;
+    if status.json {
+        return json_status(cli, &ctx, &migrations, changes).await;
+    }
+    if !status.quiet {
+        if let Some(changes) = &changes {
+            print_file_changes(changes);
+        }
+    }
     match up_to_date_check(cli, &ctx, &migrations).await? {
         Some(_) if status.quiet => Ok(()),
         Some(migration) => {
             if print::use_color() {
-                eprintln!(
+                msg!(
                     "{} Last migration: {}.",
                     "Database is up to date.".bold().light_green(),
                     migration.bold().white(),
                 );
             } else {
-                eprintln!("Database is up to date. Last migration: {migration}.",);
+                msg!("Database is up to date. Last migration: {migration}.",);
             }
             Ok(())
         }
@@ -68,12 +78,128 @@ pub async fn status(
     }
 }
 
-pub async fn migrations_applied(
+fn print_file_changes(changes: &[FileChange]) {
+    let changed = changes
+        .iter()
+        .filter(|c| c.status != FileStatus::Unchanged)
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        return;
+    }
+    print::warn!("Schema files were changed since the last migration was created:");
+    for change in changed {
+        let status = match change.status {
+            FileStatus::Modified => "modified",
+            FileStatus::Added => "added",
+            FileStatus::Removed => "removed",
+            FileStatus::Unchanged => unreachable!(),
+        };
+        msg!("    {status}: {}", change.path);
+    }
+}
+
+/// Three-way status: database vs migrations vs schema files.
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DriftStatus {
+    last_applied: Option<String>,
+    last_file: Option<String>,
+    /// Migrations not applied yet, `None` if the database revision is
+    /// not found in the filesystem
+    pending: Option<usize>,
+    /// Known only when all migrations are applied
+    database_matches_schema: Option<bool>,
+    /// `None` if schema files were not recorded for the last migration
+    schema_files_changed: Option<bool>,
+    files: Vec<FileChange>,
+}
+
+async fn json_status(
     cli: &mut Connection,
     ctx: &Context,
     migrations: &IndexMap<String, MigrationFile>,
-) -> Result<Option<String>, anyhow::Error> {
-    let (db_migration, _): (Option<String>, _) = cli
+    changes: Option<Vec<FileChange>>,
+) -> Result<(), anyhow::Error> {
+    let last_applied = last_db_migration(cli).await?;
+    let pending = match &last_applied {
+        Some(last) => migrations
+            .get_index_of(last)
+            .map(|index| migrations.len() - index - 1),
+        None => Some(migrations.len()),
+    };
+    let database_matches_schema = if pending == Some(0) {
+        execute_start_migration(ctx, cli).await?;
+        let res = async_try! {
+            async {
+                ensure_diff_is_empty(cli, ctx).await
+            },
+            finally async {
+                execute_if_connected(cli, "ABORT MIGRATION").await
+            }
+        };
+        match res {
+            Ok(()) => Some(true),
+            Err(e) if e.is::<ExitCode>() => Some(false),
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+    let status = DriftStatus {
+        last_applied,
+        last_file: migrations.keys().last().cloned(),
+        pending,
+        database_matches_schema,
+        schema_files_changed: changes
+            .as_ref()
+            .map(|c| c.iter().any(|c| c.status != FileStatus::Unchanged)),
+        files: changes.unwrap_or_default(),
+    };
+    println!("{}", serde_json::to_string_pretty(&status)?);
+    if status.pending != Some(0) {
+        return Err(ExitCode::new(3).into());
+    }
+    if status.database_matches_schema == Some(false) {
+        return Err(ExitCode::new(2).into());
+    }
+    Ok(())
+}
+
+/// Machine-readable migration state, used by editor integrations.
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusSummary {
+    pub last_applied: Option<String>,
+    pub last_file: Option<String>,
+    pub files: usize,
+    pub pending: usize,
+    pub up_to_date: bool,
+}
+
+pub async fn status_summary(
+    cli: &mut Connection,
+    ctx: &Context,
+) -> Result<StatusSummary, anyhow::Error> {
+    let migrations = migration::read_all(ctx, true).await?;
+    let last_applied = last_db_migration(cli).await?;
+    let pending = match &last_applied {
+        Some(last) => match migrations.get_index_of(last) {
+            Some(index) => migrations.len() - index - 1,
+            None => anyhow::bail!("Database revision {last} not found in the filesystem."),
+        },
+        None => migrations.len(),
+    };
+    Ok(StatusSummary {
+        last_applied,
+        last_file: migrations.keys().last().cloned(),
+        files: migrations.len(),
+        pending,
+        up_to_date: pending == 0,
+    })
+}
+
+pub async fn last_db_migration(cli: &mut Connection) -> Result<Option<String>, anyhow::Error> {
+    let (db_migration, _) = cli
         .query_single(
             r###"
             WITH Last := (SELECT schema::Migration
@@ -83,6 +209,15 @@ pub async fn migrations_applied(
             &(),
         )
         .await?;
+    Ok(db_migration)
+}
+
+pub async fn migrations_applied(
+    cli: &mut Connection,
+    ctx: &Context,
+    migrations: &IndexMap<String, MigrationFile>,
+) -> Result<Option<String>, anyhow::Error> {
+    let db_migration = last_db_migration(cli).await?;
     if db_migration.as_ref() != migrations.keys().last() {
         if !ctx.quiet {
             if let Some(db_migration) = &db_migration {
@@ -102,7 +237,7 @@ pub async fn migrations_applied(
                     );
                 } else {
                     print::error!("Database revision {db_migration} not found in the filesystem.");
-                    eprintln!("  Consider updating sources.");
+                    msg!("  Consider updating sources.");
                 }
             } else {
                 print::error!(
@@ -110,7 +245,7 @@ pub async fn migrations_applied(
                     have been found in the filesystem.",
                     migrations.len()
                 );
-                eprintln!("  Run `{BRANDING_CLI_CMD} migrate` to apply.");
+                msg!("  Run `{BRANDING_CLI_CMD} migrate` to apply.");
             }
         }
         return Ok(None);
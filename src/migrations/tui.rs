@@ -0,0 +1,262 @@
+//! Full-screen front-end of `migration create --interactive-tui`
+//!
+//! Shows all statements of the proposal at once, so they can be reviewed,
+//! edited and approved one by one instead of answering a single y/n prompt.
+
+use std::io::{stdout, Write};
+
+use termimad::crossterm::cursor::{Hide, MoveTo, Show};
+use termimad::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use termimad::crossterm::style::Stylize;
+use termimad::crossterm::terminal::{self, Clear, ClearType};
+use termimad::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use termimad::crossterm::{execute, queue};
+use tokio::task::spawn_blocking;
+
+use crate::highlight;
+use crate::print::style::Styler;
+use crate::prompt::spawn_editor;
+
+pub enum Answer {
+    /// Apply statements, possibly edited by user
+    Apply(Vec<String>),
+    Reject,
+    Back,
+    Split,
+    Quit,
+}
+
+struct State {
+    prompt: String,
+    statements: Vec<String>,
+    approved: Vec<bool>,
+    selected: usize,
+    confirmed: Vec<String>,
+    show_confirmed: bool,
+    can_go_back: bool,
+    message: Option<String>,
+}
+
+/// Restores the terminal on drop, including when the UI fails
+struct Screen;
+
+impl Screen {
+    fn enter() -> anyhow::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+    fn suspend(&self) -> anyhow::Result<()> {
+        execute!(stdout(), Show, LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        Ok(())
+    }
+    fn resume(&self) -> anyhow::Result<()> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        self.suspend()
+            .map_err(|e| log::warn!("Cannot restore terminal: {:#}", e))
+            .ok();
+    }
+}
+
+pub async fn ask(
+    prompt: String,
+    statements: Vec<String>,
+    confirmed: Vec<String>,
+    can_go_back: bool,
+) -> anyhow::Result<Answer> {
+    spawn_blocking(move || {
+        let mut state = State {
+            prompt,
+            approved: vec![false; statements.len()],
+            statements,
+            selected: 0,
+            confirmed,
+            show_confirmed: false,
+            can_go_back,
+            message: None,
+        };
+        run(&mut state)
+    })
+    .await?
+}
+
+fn run(state: &mut State) -> anyhow::Result<Answer> {
+    let screen = Screen::enter()?;
+    loop {
+        render(state)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        state.message = None;
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(Answer::Quit);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.selected = state.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if state.selected + 1 < state.statements.len() {
+                    state.selected += 1;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if state.statements.is_empty() {
+                    return Ok(Answer::Apply(Vec::new()));
+                }
+                state.approved[state.selected] = true;
+                match state.approved.iter().position(|a| !a) {
+                    Some(next) => state.selected = next,
+                    None => return Ok(Answer::Apply(state.statements.clone())),
+                }
+            }
+            KeyCode::Char('a') => return Ok(Answer::Apply(state.statements.clone())),
+            KeyCode::Char('e') if !state.statements.is_empty() => {
+                screen.suspend()?;
+                let res = spawn_editor(&state.statements[state.selected]);
+                screen.resume()?;
+                match res {
+                    Ok(text) if text.trim().is_empty() => {
+                        state.message = Some("Statement can't be empty, edit discarded.".into());
+                    }
+                    Ok(text) => {
+                        state.statements[state.selected] = text.trim().to_string();
+                        state.approved[state.selected] = false;
+                        state.message = Some("Statement edited, approve it to continue.".into());
+                    }
+                    Err(e) => state.message = Some(format!("Editor failed: {e:#}")),
+                }
+            }
+            KeyCode::Char('c') => state.show_confirmed = !state.show_confirmed,
+            KeyCode::Char('n') => return Ok(Answer::Reject),
+            KeyCode::Char('b') if state.can_go_back => return Ok(Answer::Back),
+            KeyCode::Char('b') => {
+                state.message = Some("No statements confirmed, nothing to move back from.".into());
+            }
+            KeyCode::Char('s') => return Ok(Answer::Split),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Answer::Quit),
+            _ => {}
+        }
+    }
+}
+
+fn render(state: &State) -> anyhow::Result<()> {
+    let (width, height) = terminal::size()?;
+    let width = width as usize;
+    let styler = Styler::dark_256();
+    let mut lines = Vec::new();
+
+    lines.push(state.prompt.clone().bold().to_string());
+    lines.push(String::new());
+    for (idx, statement) in state.statements.iter().enumerate() {
+        let mark = if state.approved[idx] { "[x]" } else { "[ ]" };
+        let first_line = statement.lines().next().unwrap_or("");
+        let mut line = format!("{mark} {first_line}");
+        if statement.lines().nth(1).is_some() {
+            line.push_str(" ...");
+        }
+        let line = truncate(&line, width.saturating_sub(2));
+        let line = if idx == state.selected {
+            format!("> {}", line.reverse())
+        } else {
+            format!("  {line}")
+        };
+        lines.push(line);
+        if let Some(warning) = data_loss_warning(statement) {
+            lines.push(format!("      {}", format!("! {warning}").yellow()));
+        }
+    }
+    lines.push("-".repeat(width.min(80)));
+
+    let details = if state.show_confirmed {
+        lines.push("Confirmed statements:".bold().to_string());
+        state.confirmed.clone()
+    } else {
+        state
+            .statements
+            .get(state.selected)
+            .cloned()
+            .into_iter()
+            .collect()
+    };
+    for statement in details {
+        let mut buf = String::new();
+        highlight::edgeql(&mut buf, &statement, &styler);
+        lines.extend(buf.lines().map(|l| format!("    {l}")));
+    }
+
+    let mut footer = Vec::new();
+    if let Some(message) = &state.message {
+        footer.push(message.clone().yellow().to_string());
+    }
+    footer.push(
+        "y/enter approve  a approve all  e edit  n reject  c confirmed  \
+         b back  s stop  q quit"
+            .dim()
+            .to_string(),
+    );
+
+    let available = (height as usize).saturating_sub(footer.len());
+    let mut out = stdout().lock();
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+    for line in lines.iter().take(available) {
+        write!(out, "{line}\r\n")?;
+    }
+    queue!(out, MoveTo(0, available as u16))?;
+    for line in &footer {
+        write!(out, "{line}\r\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        line.to_string()
+    } else {
+        let mut line = line
+            .chars()
+            .take(width.saturating_sub(3))
+            .collect::<String>();
+        line.push_str("...");
+        line
+    }
+}
+
+/// Rough classification of statements that may lose existing data
+fn data_loss_warning(statement: &str) -> Option<&'static str> {
+    let text = statement.to_uppercase();
+    if text.starts_with("DROP ") || text.contains(" DROP PROPERTY") || text.contains(" DROP LINK") {
+        Some("drops existing data")
+    } else if text.contains("SET TYPE") {
+        Some("converts existing data to a new type")
+    } else if text.contains("SET SINGLE") {
+        Some("fails or discards values if there are multiple ones")
+    } else if text.contains("SET REQUIRED") {
+        Some("fails if existing objects have no value")
+    } else {
+        None
+    }
+}
+
+#[test]
+fn data_loss() {
+    assert!(data_loss_warning("DROP TYPE default::User;").is_some());
+    assert!(data_loss_warning("ALTER TYPE default::User {\n    DROP PROPERTY name;\n};").is_some());
+    assert!(data_loss_warning(
+        "ALTER TYPE default::User {\n    ALTER PROPERTY age {\n        SET TYPE std::int64;\n    };\n};"
+    )
+    .is_some());
+    assert!(data_loss_warning("CREATE TYPE default::User;").is_none());
+}
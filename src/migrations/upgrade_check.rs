@@ -19,6 +19,7 @@ use crate::migrations::migrate::{apply_migration, ApplyMigrationError};
 use crate::migrations::migration;
 use crate::migrations::options::UpgradeCheck;
 use crate::migrations::timeout;
+use crate::options::connector_for;
 use crate::portable::local::InstallInfo;
 use crate::portable::project;
 use crate::portable::repository::{self, PackageInfo, Query};
@@ -43,6 +44,10 @@ enum CheckResult {
 pub fn upgrade_check(_options: &Options, options: &UpgradeCheck) -> anyhow::Result<()> {
     use crate::portable::windows;
 
+    if let Some(dsn) = &options.against {
+        return check_against(options, dsn);
+    }
+
     let status_path = tempfile::NamedTempFile::new()
         .context("tempfile failure")?
         .into_temp_path();
@@ -73,6 +78,10 @@ pub fn upgrade_check(_options: &Options, options: &UpgradeCheck) -> anyhow::Resu
 
     use crate::branding::BRANDING;
 
+    if let Some(dsn) = &options.against {
+        return check_against(options, dsn);
+    }
+
     let (version, _) = Query::from_options(
         repository::QueryOptions {
             nightly: options.to_nightly,
@@ -111,9 +120,43 @@ pub fn upgrade_check(_options: &Options, options: &UpgradeCheck) -> anyhow::Resu
     spawn_and_check(&info, ctx, options.watch)
 }
 
+/// Checks the project schema against the version of the package, the
+/// server is run in WSL
 #[cfg(windows)]
-pub fn to_version(_: &PackageInfo, _: &project::Context) -> anyhow::Result<()> {
-    unreachable!();
+pub fn to_version(pkg: &PackageInfo, project: &project::Context) -> anyhow::Result<()> {
+    use crate::migrations::options::MigrationConfig;
+    use crate::portable::windows;
+
+    let query = Query::from_version(&pkg.version.specific())?;
+    let nightly = query.is_nightly();
+    let status_path = tempfile::NamedTempFile::new()
+        .context("tempfile failure")?
+        .into_temp_path();
+    let ctx = Context::for_project(project)?;
+
+    let mut cmd = windows::ensure_wsl()?.edgedb();
+    cmd.arg("migration").arg("upgrade-check");
+    cmd.args(&UpgradeCheck {
+        cfg: MigrationConfig { schema_dir: None },
+        // nightly builds can't be selected by version
+        to_version: query.version.filter(|_| !nightly),
+        to_nightly: nightly,
+        to_testing: false,
+        to_channel: None,
+        against: None,
+        watch: false,
+        run_server_with_status: Some(windows::path_to_linux(&status_path)?.into()),
+    });
+    cmd.background_for(move || {
+        Ok(async move {
+            while let Ok(meta) = fs::metadata(&status_path).await {
+                if meta.len() > "READY={}".len() as u64 {
+                    break;
+                }
+            }
+            do_check(&ctx, &status_path, false).await
+        })
+    })
 }
 
 #[cfg(unix)]
@@ -161,9 +204,23 @@ fn spawn_and_check(info: &InstallInfo, ctx: Context, watch: bool) -> anyhow::Res
     })
 }
 
-async fn do_check(ctx: &Context, status_file: &Path, watch: bool) -> anyhow::Result<()> {
-    use CheckResult::*;
+/// Checks against an already running server, no package is installed
+#[tokio::main(flavor = "current_thread")]
+async fn check_against(options: &UpgradeCheck, dsn: &str) -> anyhow::Result<()> {
+    let ctx = Context::from_project_or_config(&options.cfg, false).await?;
+    let connector = connector_for(None, Some(dsn)).await?;
+    let cli = &mut connector
+        .connect()
+        .await
+        .context("cannot connect to the target server")?;
+    if !ctx.quiet {
+        let version = cli.get_version().await?.to_string();
+        msg!("Checking against server version {}", version.emphasize());
+    }
+    check(&ctx, cli, options.watch).await
+}
 
+async fn do_check(ctx: &Context, status_file: &Path, watch: bool) -> anyhow::Result<()> {
     let status_data = fs::read_to_string(&status_file)
         .await
         .context("error reading status")?;
@@ -185,6 +242,11 @@ async fn do_check(ctx: &Context, status_file: &Path, watch: bool) -> anyhow::Res
         .constrained_build()
         .context("cannot build connection params")?;
     let cli = &mut Connection::connect(&config, QUERY_TAG).await?;
+    check(ctx, cli, watch).await
+}
+
+async fn check(ctx: &Context, cli: &mut Connection, watch: bool) -> anyhow::Result<()> {
+    use CheckResult::*;
 
     if fs::metadata(&ctx.schema_dir).await.is_err() {
         anyhow::bail!("No schema dir found at {:?}", ctx.schema_dir);
@@ -207,7 +269,7 @@ async fn do_check(ctx: &Context, status_file: &Path, watch: bool) -> anyhow::Res
         if ok {
             print::success!("The schema is forward compatible. Ready for upgrade.");
         }
-        eprintln!("Monitoring {:?} for changes.", &ctx.schema_dir);
+        msg!("Monitoring {:?} for changes.", &ctx.schema_dir);
         watch_loop(rx, ctx, cli, ok).await?;
         Ok(())
     } else {
@@ -237,7 +299,7 @@ async fn do_check(ctx: &Context, status_file: &Path, watch: bool) -> anyhow::Res
 async fn single_check(ctx: &Context, cli: &mut Connection) -> anyhow::Result<CheckResult> {
     use CheckResult::*;
 
-    let bar = ProgressBar::new_spinner();
+    let bar = print::progress(ProgressBar::new_spinner());
     bar.enable_steady_tick(Duration::from_millis(100));
 
     bar.set_message("checking schema");
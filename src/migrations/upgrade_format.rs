@@ -3,7 +3,7 @@ use crate::connect::Connection;
 use crate::migrations::migration::{file_num, read_file, read_names};
 use crate::migrations::options::MigrationUpgradeFormat;
 use crate::migrations::Context;
-use crate::print;
+use crate::print::{self, msg};
 use regex::Regex;
 use std::fs;
 use std::path::PathBuf;
@@ -29,7 +29,7 @@ async fn _upgrade_format(context: &Context) -> anyhow::Result<()> {
 
         if old_filename.captures(fname).is_some() {
             // migrate to new filename
-            eprintln!("Upgrading migration file layout for {fname}.edgeql...");
+            msg!("Upgrading migration file layout for {fname}.edgeql...");
             upgrade_format_of_file(&name, file_num(&name).unwrap()).await?;
         } else if new_filename.captures(fname).is_some() {
             println!("Migration {fname} OK")
@@ -77,6 +77,7 @@ mod test {
         let ctx = Context {
             schema_dir,
             quiet: false,
+            hooks: None,
         };
 
         _upgrade_format(&ctx).await.unwrap();
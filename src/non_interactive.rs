@@ -1,34 +1,69 @@
-use std::io::{stdout, Write};
+use std::io::{self, stdout, Write};
+use std::path::Path;
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use anyhow::Context;
 use bytes::BytesMut;
+use indicatif::ProgressBar;
 use is_terminal::IsTerminal;
 use terminal_size::{terminal_size, Width};
 use tokio::fs::File as AsyncFile;
-use tokio::io::{stdin, AsyncRead};
+use tokio::io::{stdin, AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::time::timeout;
 
 use edgeql_parser::preparser;
+use gel_protocol::annotations::Warning;
 use gel_protocol::client_message::Cardinality;
 use gel_protocol::client_message::CompilationOptions;
-use gel_protocol::common::Capabilities;
+use gel_protocol::common::{Capabilities, IoFormat};
+use gel_protocol::descriptors::Typedesc;
+use gel_protocol::server_message::CommandDataDescription1;
 use gel_protocol::value::Value;
 use tokio_stream::StreamExt;
 
+use crate::analyze;
 use crate::branding::BRANDING_CLI_CMD;
 use crate::classify;
+use crate::commands::parser::{Analyze, AnalyzeFormat};
 use crate::commands::ExitCode;
-use crate::connect::Connection;
-use crate::error_display::print_query_error;
-use crate::options::Options;
+use crate::connect::{Connection, HttpConnection};
+use crate::error_display::{print_query_error, print_sql_query_error};
+use crate::fetch;
+use crate::interrupt::{Interrupt, InterruptError};
 use crate::options::Query;
-use crate::outputs::tab_separated;
-use crate::print::{self, PrintError};
+use crate::options::{http_dsn_scheme, Options};
+#[cfg(feature = "columnar")]
+use crate::outputs::columnar;
+use crate::outputs::{csv, tab_separated};
+use crate::print::{self, msg, PrintError};
 use crate::repl;
-use crate::statement::{read_statement, EndOfFile};
+use crate::session_config::{self, SessionConfig};
+use crate::sql_statement;
+use crate::statement::{read_sql_statement, read_statement, EndOfFile};
+use crate::variables;
+
+/// Exit code when a query is canceled with Ctrl-C (same as for SIGINT)
+const CANCELED: i32 = 130;
+
+/// Number of warnings reported by the server, for `--fail-on-warnings`
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
 
 #[tokio::main(flavor = "current_thread")]
 pub async fn noninteractive_main(q: &Query, options: &Options) -> Result<(), anyhow::Error> {
+    _noninteractive_main(q, options).await?;
+    let warnings = WARNINGS.load(Ordering::Relaxed);
+    if q.fail_on_warnings && warnings > 0 {
+        print::error!(
+            "The server reported {warnings} warning(s), failing due to `--fail-on-warnings`."
+        );
+        return Err(ExitCode::new(1).into());
+    }
+    Ok(())
+}
+
+async fn _noninteractive_main(q: &Query, options: &Options) -> Result<(), anyhow::Error> {
     // There's some extra complexity here due to the fact that we
     // have to support now deprecated top-level `--json` and
     // `--tab-separated` flags.
@@ -53,15 +88,74 @@ pub async fn noninteractive_main(q: &Query, options: &Options) -> Result<(), any
         repl::InputLanguage::EdgeQl
     };
 
-    if let Some(filename) = &q.file {
-        if filename == "-" {
-            interpret_file(&mut stdin(), options, fmt, lang).await?;
-        } else {
-            let mut file = AsyncFile::open(filename).await?;
-            interpret_file(&mut file, options, fmt, lang).await?;
+    let http_tls = match options
+        .conn_options
+        .dsn
+        .as_deref()
+        .and_then(http_dsn_scheme)
+    {
+        Some((tls, _)) => Some(tls),
+        None if q.http => Some(true),
+        None => None,
+    };
+    let mut cfg = print_config();
+    cfg.max_col_width(q.max_col_width);
+    cfg.vertical(q.vertical);
+    cfg.header(!q.no_header);
+    if let Some(null_as) = &q.null_as {
+        cfg.null_as(null_as);
+    }
+    cfg.html_styles(q.html_styles);
+
+    if q.explain {
+        // `--http`, `--file` and `--output-format` conflict in clap already
+        if http_tls.is_some() {
+            anyhow::bail!("`--explain` is not supported with HTTP DSNs");
+        }
+        if options.output_format.is_some() {
+            anyhow::bail!("`--explain` cannot be combined with `--json` or `--tab-separated`");
+        }
+        return explain(q, options, lang).await;
+    }
+
+    if q.stdin_params {
+        // `--http`, `--file` and `--output-format` conflict in clap already
+        if http_tls.is_some() {
+            anyhow::bail!("`--stdin-params` is not supported with HTTP DSNs");
         }
+        if options.output_format.is_some() {
+            anyhow::bail!("`--stdin-params` cannot be combined with `--json` or `--tab-separated`");
+        }
+        return stdin_params(q, options, lang).await;
+    }
+
+    if let Some(tls) = http_tls {
+        return http_main(q, options, fmt, lang, &cfg, tls).await;
+    }
+
+    if let Some(path) = &q.output_file {
+        let query = match q.queries.as_deref() {
+            Some([query]) => query,
+            _ => anyhow::bail!("`--output-file` requires exactly one query"),
+        };
+        let mut conn = connect(q, options).await?;
+        let ctrlc = Interrupt::ctrl_c();
+        let res = tokio::select! {
+            res = export_query(&mut conn, query, lang, path) => res,
+            res = ctrlc.wait_result() => res,
+        };
+        return match res {
+            Err(e) if e.is::<InterruptError>() => Err(cancel(conn).await),
+            res => res.map_err(|err| query_error(err, query, lang, "<query>")),
+        };
+    }
+
+    if let Some(filename) = &q.file {
+        let mut input = open_input(filename).await?;
+        interpret_file(&mut input, options, fmt, lang, &cfg, &q.session_config).await?;
     } else if let Some(queries) = &q.queries {
-        let mut conn = options.create_connector().await?.connect().await?;
+        let mut conn = connect(q, options).await?;
+        let ctrlc = Interrupt::ctrl_c();
         for query in queries {
             if classify::is_analyze(query) {
                 anyhow::bail!(
@@ -69,7 +163,84 @@ pub async fn noninteractive_main(q: &Query, options: &Options) -> Result<(), any
                                Use the dedicated `{BRANDING_CLI_CMD} analyze` command."
                 );
             }
-            run_query(&mut conn, query, options, fmt, lang).await?;
+            let res = tokio::select! {
+                res = run_query(&mut conn, query, options, fmt, lang, &cfg, "<query>") => res,
+                res = ctrlc.wait_result() => res,
+            };
+            match res {
+                Err(e) if e.is::<InterruptError>() => return Err(cancel(conn).await),
+                res => res?,
+            }
+        }
+    } else {
+        print::error!(
+            "either a --file option or \
+                     a <queries> positional argument is required."
+        );
+    }
+
+    Ok(())
+}
+
+type Input = Box<dyn AsyncRead + Unpin + Send>;
+
+/// Opens the `--file` argument: stdin, a URL or a local file
+async fn open_input(filename: &str) -> anyhow::Result<Input> {
+    if filename == "-" {
+        Ok(Box::new(stdin()))
+    } else if fetch::is_url(filename) {
+        let data = fetch::fetch(filename)
+            .await
+            .with_context(|| format!("cannot download {filename}"))?;
+        Ok(Box::new(io::Cursor::new(data)))
+    } else {
+        Ok(Box::new(AsyncFile::open(filename).await?))
+    }
+}
+
+/// Connects and applies `--session-config` settings
+async fn connect(q: &Query, options: &Options) -> anyhow::Result<Connection> {
+    let mut conn = options.create_connector().await?.connect().await?;
+    session_config::apply(&mut conn, &q.session_config).await?;
+    Ok(conn)
+}
+
+fn print_config() -> print::Config {
+    let mut cfg = print::Config::new();
+    if let Some((Width(w), _h)) = terminal_size() {
+        cfg.max_width(w.into());
+    }
+    cfg.colors(stdout().is_terminal());
+    cfg
+}
+
+async fn http_main(
+    q: &Query,
+    options: &Options,
+    fmt: repl::OutputFormat,
+    lang: repl::InputLanguage,
+    cfg: &print::Config,
+    tls: bool,
+) -> Result<(), anyhow::Error> {
+    use crate::repl::OutputFormat::*;
+
+    if lang != repl::InputLanguage::EdgeQl {
+        anyhow::bail!("only EdgeQL queries are supported over HTTP");
+    }
+    if !matches!(fmt, Json | JsonPretty | JsonLines | Table | Html) {
+        anyhow::bail!(
+            "only `json`, `json-pretty`, `json-lines`, `table` and `html` \
+             output formats are supported over HTTP"
+        );
+    }
+    let conn = options.create_connector().await?.connect_http(tls)?;
+    log::info!("Executing queries via {}", conn.url());
+    if let Some(filename) = &q.file {
+        let mut input = open_input(filename).await?;
+        http_interpret_file(&mut input, &conn, fmt, cfg).await?;
+    } else if let Some(queries) = &q.queries {
+        for query in queries {
+            run_http_query(&conn, query, fmt, cfg).await?;
         }
     } else {
         print::error!(
@@ -77,7 +248,78 @@ pub async fn noninteractive_main(q: &Query, options: &Options) -> Result<(), any
                      a <queries> positional argument is required."
         );
     }
+    Ok(())
+}
+
+async fn http_interpret_file<T>(
+    file: &mut T,
+    conn: &HttpConnection,
+    fmt: repl::OutputFormat,
+    cfg: &print::Config,
+) -> Result<(), anyhow::Error>
+where
+    T: AsyncRead + Unpin,
+{
+    let mut inbuf = BytesMut::with_capacity(8192);
+    loop {
+        let stmt = match read_statement(&mut inbuf, file).await {
+            Ok(chunk) => chunk,
+            Err(e) if e.is::<EndOfFile>() => break,
+            Err(e) => return Err(e),
+        };
+        let stmt = str::from_utf8(&stmt[..]).context("can't decode statement")?;
+        if preparser::is_empty(stmt) {
+            continue;
+        }
+        run_http_query(conn, stmt, fmt, cfg).await?;
+    }
+    Ok(())
+}
+
+async fn run_http_query(
+    conn: &HttpConnection,
+    stmt: &str,
+    fmt: repl::OutputFormat,
+    cfg: &print::Config,
+) -> Result<(), anyhow::Error> {
+    if classify::is_analyze(stmt) {
+        anyhow::bail!(
+            "Analyze queries are not allowed. \
+                       Use the dedicated `{BRANDING_CLI_CMD} analyze` command."
+        );
+    }
+    let items = conn.query_json(stmt).await?;
 
+    let mut data = String::new();
+    match fmt {
+        repl::OutputFormat::Json => {
+            data += &print::json_to_string(&items, cfg)?;
+            data += "\n";
+        }
+        repl::OutputFormat::JsonPretty => {
+            for item in &items {
+                data += &print::json_item_to_string(item, cfg)?;
+                data += "\n";
+            }
+        }
+        repl::OutputFormat::JsonLines => {
+            for item in &items {
+                data += &serde_json::to_string(item)?;
+                data += "\n";
+            }
+        }
+        repl::OutputFormat::Table => {
+            data += &print::json_table_to_string(&items, cfg);
+        }
+        repl::OutputFormat::Html => {
+            data += &print::json_html_to_string(&items, cfg);
+        }
+        repl::OutputFormat::Default
+        | repl::OutputFormat::TabSeparated
+        | repl::OutputFormat::Csv => unreachable!(),
+    }
+    // trying to make writes atomic if possible
+    stdout().lock().write_all(data.as_bytes())?;
     Ok(())
 }
 
@@ -87,7 +329,7 @@ pub async fn interpret_stdin(
     fmt: repl::OutputFormat,
     lang: repl::InputLanguage,
 ) -> Result<(), anyhow::Error> {
-    return interpret_file(&mut stdin(), options, fmt, lang).await;
+    return interpret_file(&mut stdin(), options, fmt, lang, &print_config(), &[]).await;
 }
 
 async fn interpret_file<T>(
@@ -95,52 +337,317 @@ async fn interpret_file<T>(
     options: &Options,
     fmt: repl::OutputFormat,
     lang: repl::InputLanguage,
+    cfg: &print::Config,
+    session_config: &[SessionConfig],
 ) -> Result<(), anyhow::Error>
 where
     T: AsyncRead + Unpin,
 {
     let mut conn = options.create_connector().await?.connect().await?;
+    session_config::apply(&mut conn, session_config).await?;
+    let ctrlc = Interrupt::ctrl_c();
     let mut inbuf = BytesMut::with_capacity(8192);
+    // statements are executed as soon as they are read, so that memory
+    // use doesn't depend on the size of the input
+    let mut index = 0;
+    let mut line = 1;
     loop {
-        let stmt = match read_statement(&mut inbuf, file).await {
+        let read = match lang {
+            repl::InputLanguage::EdgeQl => read_statement(&mut inbuf, file).await,
+            repl::InputLanguage::Sql => read_sql_statement(&mut inbuf, file).await,
+        };
+        let stmt = match read {
             Ok(chunk) => chunk,
             Err(e) if e.is::<EndOfFile>() => break,
             Err(e) => return Err(e),
         };
-        let stmt = str::from_utf8(&stmt[..]).context("can't decode statement")?;
-        if preparser::is_empty(stmt) {
+        let stmt = str::from_utf8(&stmt[..])
+            .with_context(|| format!("can't decode statement at line {line}"))?;
+        let empty = match lang {
+            repl::InputLanguage::EdgeQl => preparser::is_empty(stmt),
+            repl::InputLanguage::Sql => sql_statement::is_empty(stmt),
+        };
+        // position of the first non-whitespace character
+        let start_line = line
+            + stmt[..stmt.len() - stmt.trim_start().len()]
+                .matches('\n')
+                .count();
+        line += stmt.matches('\n').count();
+        if empty {
             continue;
         }
-        if classify::is_analyze(stmt) {
+        index += 1;
+        if lang == repl::InputLanguage::EdgeQl && classify::is_analyze(stmt) {
             anyhow::bail!(
                 "Analyze queries are not allowed. \
                            Use the dedicated `{BRANDING_CLI_CMD} analyze` command."
             );
         }
-        run_query(&mut conn, stmt, options, fmt, lang).await?;
+        let source = format!("<statement #{index}, line {start_line}>");
+        let res = tokio::select! {
+            res = run_query(&mut conn, stmt, options, fmt, lang, cfg, &source) => res,
+            res = ctrlc.wait_result() => res,
+        };
+        match res {
+            Err(e) if e.is::<InterruptError>() => return Err(cancel(conn).await),
+            res => {
+                res.with_context(|| format!("statement #{index} at line {start_line} failed"))?
+            }
+        }
     }
     Ok(())
 }
 
+/// Runs `query --explain`, a shortcut to the `analyze` command
+async fn explain(q: &Query, options: &Options, lang: repl::InputLanguage) -> anyhow::Result<()> {
+    let query = match q.queries.as_deref() {
+        Some([query]) => query,
+        _ => anyhow::bail!("`--explain` requires exactly one query"),
+    };
+    if lang != repl::InputLanguage::EdgeQl {
+        anyhow::bail!("`--explain` is supported for EdgeQL queries only");
+    }
+    let mut conn = connect(q, options).await?;
+    analyze::command(
+        &mut conn,
+        &Analyze {
+            conn: q.conn.clone(),
+            query: Some(query.clone()),
+            debug_output_file: None,
+            read_json: None,
+            expand: false,
+            format: AnalyzeFormat::Tree,
+            output_file: None,
+        },
+    )
+    .await
+}
+
+/// Runs `query --stdin-params`: executes the query for each line of stdin
+async fn stdin_params(
+    q: &Query,
+    options: &Options,
+    lang: repl::InputLanguage,
+) -> anyhow::Result<()> {
+    let query = match q.queries.as_deref() {
+        Some([query]) => query,
+        _ => anyhow::bail!("`--stdin-params` requires exactly one query"),
+    };
+    let mut conn = connect(q, options).await?;
+    let ctrlc = Interrupt::ctrl_c();
+    let res = tokio::select! {
+        res = execute_params(&mut conn, query, lang, q.batch_size) => res,
+        res = ctrlc.wait_result() => res,
+    };
+    match res {
+        Err(e) if e.is::<InterruptError>() => Err(cancel(conn).await),
+        res => res.map_err(|err| query_error(err, query, lang, "<query>")),
+    }
+}
+
+/// Executes the query once per parameter set, committing every
+/// `batch_size` executions, so that a failure loses at most one batch
+async fn execute_params(
+    conn: &mut Connection,
+    stmt: &str,
+    lang: repl::InputLanguage,
+    batch_size: u32,
+) -> anyhow::Result<()> {
+    let flags = CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: true,
+        allow_capabilities: Capabilities::ALL,
+        input_language: lang.into(),
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+    };
+    let data_description = conn.parse(&flags, stmt).await?;
+    let indesc = data_description.input()?;
+
+    let bar = print::progress(ProgressBar::new_spinner());
+    let mut lines = BufReader::new(stdin()).lines();
+    let mut line_no = 0;
+    let mut committed = 0;
+    let mut in_batch = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_no += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if in_batch == 0 {
+            conn.execute("START TRANSACTION", &()).await?;
+        }
+        // warnings are the same for every parameter set
+        let warn = committed + in_batch == 0;
+        let res = execute_line(conn, &flags, stmt, &data_description, &indesc, &line, warn).await;
+        if let Err(e) = res {
+            bar.finish_and_clear();
+            if let Err(e) = conn.execute("ROLLBACK", &()).await {
+                log::warn!("Error rolling back the transaction: {e:#}");
+            }
+            msg!("Failed on line {line_no}, {committed} parameter sets were committed");
+            return Err(e.context(format!("line {line_no}")));
+        }
+        in_batch += 1;
+        if in_batch == batch_size {
+            conn.execute("COMMIT", &()).await?;
+            committed += in_batch;
+            in_batch = 0;
+        }
+        bar.set_message(format!("Executed {} parameter sets", committed + in_batch));
+    }
+    if in_batch > 0 {
+        conn.execute("COMMIT", &()).await?;
+        committed += in_batch;
+    }
+    bar.finish_and_clear();
+    msg!("Executed the query for {committed} parameter sets");
+    Ok(())
+}
+
+async fn execute_line(
+    conn: &mut Connection,
+    flags: &CompilationOptions,
+    stmt: &str,
+    data_description: &CommandDataDescription1,
+    indesc: &Typedesc,
+    line: &str,
+    warn: bool,
+) -> anyhow::Result<()> {
+    let json: serde_json::Value = serde_json::from_str(line).context("invalid JSON")?;
+    let arguments = variables::json_arguments(indesc, &json)?;
+    let mut items = conn
+        .execute_stream::<Value, _>(flags, stmt, data_description, &arguments)
+        .await?;
+    if warn {
+        report_warnings(items.warnings(), stmt, "<query>")?;
+    }
+    while items.next().await.transpose()?.is_some() {}
+    items.complete().await?;
+    Ok(())
+}
+
+/// Closes the connection after the running query was interrupted
+///
+/// The protocol has no message to cancel a query, but the server aborts
+/// the query as soon as the client connection is closed.
+async fn cancel(conn: Connection) -> anyhow::Error {
+    if conn.is_consistent() {
+        timeout(Duration::from_secs(1), conn.terminate())
+            .await
+            .map_err(|e| log::warn!("Termination error: {:#}", e))
+            .ok();
+    }
+    msg!("Canceled.");
+    ExitCode::new(CANCELED).into()
+}
+
 async fn run_query(
     conn: &mut Connection,
     stmt: &str,
     options: &Options,
     fmt: repl::OutputFormat,
     lang: repl::InputLanguage,
+    cfg: &print::Config,
+    source_name: &str,
 ) -> Result<(), anyhow::Error> {
-    _run_query(conn, stmt, options, fmt, lang)
+    _run_query(conn, stmt, options, fmt, lang, cfg, source_name)
         .await
-        .map_err(|err| {
-            if let Some(err) = err.downcast_ref::<gel_errors::Error>() {
-                match print_query_error(err, stmt, false, "<query>") {
-                    Ok(()) => ExitCode::new(1).into(),
-                    Err(e) => e,
-                }
-            } else {
-                err
-            }
-        })
+        .map_err(|err| query_error(err, stmt, lang, source_name))
+}
+
+/// Prints warnings of the query to stderr and counts them for
+/// `--fail-on-warnings`
+fn report_warnings(warnings: &[Warning], stmt: &str, source_name: &str) -> anyhow::Result<()> {
+    WARNINGS.fetch_add(warnings.len(), Ordering::Relaxed);
+    for warning in warnings {
+        print::warning(warning, stmt, Some(source_name))?;
+    }
+    Ok(())
+}
+
+fn query_error(
+    err: anyhow::Error,
+    stmt: &str,
+    lang: repl::InputLanguage,
+    source_name: &str,
+) -> anyhow::Error {
+    if print::structured::is_error_json() {
+        // reported by `main` including the server traceback
+        err
+    } else if let Some(err) = err.downcast_ref::<gel_errors::Error>() {
+        let res = match lang {
+            repl::InputLanguage::EdgeQl => print_query_error(err, stmt, false, source_name),
+            repl::InputLanguage::Sql => print_sql_query_error(err, stmt, false, source_name),
+        };
+        match res {
+            Ok(()) => ExitCode::new(1).into(),
+            Err(e) => e,
+        }
+    } else {
+        err
+    }
+}
+
+#[cfg(not(feature = "columnar"))]
+async fn export_query(
+    _conn: &mut Connection,
+    _stmt: &str,
+    _lang: repl::InputLanguage,
+    _path: &Path,
+) -> Result<(), anyhow::Error> {
+    anyhow::bail!(
+        "writing Parquet and Arrow files is not supported by this build \
+         (built without the `columnar` feature)"
+    );
+}
+
+/// Writes results of a single query to a Parquet or Arrow file
+#[cfg(feature = "columnar")]
+async fn export_query(
+    conn: &mut Connection,
+    stmt: &str,
+    lang: repl::InputLanguage,
+    path: &Path,
+) -> Result<(), anyhow::Error> {
+    // check extension before running the query
+    columnar::Format::from_path(path)?;
+    let flags = CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: true,
+        allow_capabilities: Capabilities::ALL,
+        input_language: lang.into(),
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+    };
+    let data_description = conn.parse(&flags, stmt).await?;
+
+    let mut items = conn
+        .execute_stream(&flags, stmt, &data_description, &())
+        .await?;
+
+    report_warnings(items.warnings(), stmt, "<query>")?;
+
+    if !items.can_contain_data() {
+        let res = items.complete().await?;
+        print::completion(&res.status_data);
+        anyhow::bail!("the query does not return any data to write");
+    }
+
+    let mut writer = columnar::Writer::create(path)?;
+    while let Some(row) = items.next().await.transpose()? {
+        writer.push(row)?;
+    }
+    items.complete().await?;
+    let rows = writer
+        .finish()
+        .with_context(|| format!("cannot write {}", path.display()))?;
+    msg!("Wrote {rows} rows to {}", path.display());
+    Ok(())
 }
 
 async fn _run_query(
@@ -149,6 +656,8 @@ async fn _run_query(
     _options: &Options,
     fmt: repl::OutputFormat,
     lang: repl::InputLanguage,
+    cfg: &print::Config,
+    source_name: &str,
 ) -> Result<(), anyhow::Error> {
     use crate::repl::OutputFormat::*;
 
@@ -164,17 +673,11 @@ async fn _run_query(
     };
     let data_description = conn.parse(&flags, stmt).await?;
 
-    let mut cfg = print::Config::new();
-    if let Some((Width(w), _h)) = terminal_size() {
-        cfg.max_width(w.into());
-    }
-    cfg.colors(stdout().is_terminal());
-
     let mut items = conn
         .execute_stream(&flags, stmt, &data_description, &())
         .await?;
 
-    print::warnings(items.warnings(), stmt)?;
+    report_warnings(items.warnings(), stmt, source_name)?;
 
     if !items.can_contain_data() {
         let res = items.complete().await?;
@@ -185,13 +688,30 @@ async fn _run_query(
     match fmt {
         repl::OutputFormat::TabSeparated => {
             while let Some(row) = items.next().await.transpose()? {
-                let mut text = tab_separated::format_row(&row)?;
+                let mut text = tab_separated::format_row(&row, &cfg.null_as)?;
                 // trying to make writes atomic if possible
                 text += "\n";
                 stdout().lock().write_all(text.as_bytes())?;
             }
         }
-        repl::OutputFormat::Default => match print::native_to_stdout(&mut items, &cfg).await {
+        repl::OutputFormat::Csv => {
+            let mut first = true;
+            while let Some(row) = items.next().await.transpose()? {
+                let mut text = String::new();
+                if first && cfg.header {
+                    if let Some(header) = csv::format_header(&row) {
+                        text += &header;
+                        text += "\r\n";
+                    }
+                }
+                first = false;
+                text += &csv::format_row(&row, &cfg.null_as)?;
+                // trying to make writes atomic if possible
+                text += "\r\n";
+                stdout().lock().write_all(text.as_bytes())?;
+            }
+        }
+        repl::OutputFormat::Default => match print::native_to_stdout(&mut items, cfg).await {
             Ok(()) => {}
             Err(e) => {
                 match e {
@@ -221,7 +741,7 @@ async fn _run_query(
                 let value: serde_json::Value =
                     serde_json::from_str(&text).context("cannot decode json result")?;
                 // trying to make writes atomic if possible
-                let mut data = print::json_item_to_string(&value, &cfg)?;
+                let mut data = print::json_item_to_string(&value, cfg)?;
                 data += "\n";
                 stdout().lock().write_all(data.as_bytes())?;
             }
@@ -242,6 +762,27 @@ async fn _run_query(
                 stdout().lock().write_all(text.as_bytes())?;
             }
         }
+        repl::OutputFormat::Table | repl::OutputFormat::Html => {
+            let mut rows: Vec<serde_json::Value> = Vec::new();
+            while let Some(row) = items.next().await.transpose()? {
+                let text = match row {
+                    Value::Str(s) => s,
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "the server returned \
+                         a non-string value in JSON mode"
+                        ))
+                    }
+                };
+                rows.push(serde_json::from_str(&text).context("cannot decode json result")?);
+            }
+            let data = if fmt == repl::OutputFormat::Html {
+                print::json_html_to_string(&rows, cfg)
+            } else {
+                print::json_table_to_string(&rows, cfg)
+            };
+            stdout().lock().write_all(data.as_bytes())?;
+        }
         repl::OutputFormat::Json => {
             while let Some(row) = items.next().await.transpose()? {
                 let text = match row {
@@ -259,7 +800,7 @@ async fn _run_query(
                     anyhow::anyhow!("the server returned a non-array value in JSON mode")
                 })?;
                 // trying to make writes atomic if possible
-                let mut data = print::json_to_string(items, &cfg)?;
+                let mut data = print::json_to_string(items, cfg)?;
                 data += "\n";
                 stdout().lock().write_all(data.as_bytes())?;
             }
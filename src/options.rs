@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use std::env;
 use std::io::stdin;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -18,20 +20,31 @@ use edgedb_cli_derive::IntoArgs;
 use crate::cli;
 use crate::cli::options::CliCommand;
 
+use crate::auth;
 use crate::branch;
 use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD, MANIFEST_FILE_DISPLAY_NAME};
 use crate::cloud::options::CloudCommand;
 use crate::commands::parser::Common;
 use crate::commands::ExitCode;
-use crate::connect::Connector;
+use crate::config;
+use crate::connect::{self, Connector};
+use crate::credentials;
+use crate::doctor;
 use crate::hint::HintExt;
+use crate::hooks;
+use crate::keychain;
+use crate::lsp_proxy::options::LspProxyCommand;
 use crate::markdown;
 use crate::portable;
 use crate::portable::local::{instance_data_dir, runstate_dir};
 use crate::portable::options::InstanceName;
 use crate::portable::project;
+use crate::portable::windows;
 use crate::print;
+use crate::print::structured::{ErrorFormat, LogFormat};
 use crate::repl::{InputLanguage, OutputFormat};
+use crate::seed;
+use crate::session_config::SessionConfig;
 use crate::tty_password;
 use crate::watch::options::WatchCommand;
 
@@ -58,10 +71,17 @@ pub struct ConnectionOptions {
     /// Instance name (use [`BRANDING_CLI_CMD`] `instance list` to list local, remote and
     /// [`BRANDING_CLOUD`] instances available to you).
     #[arg(short='I', long, help_heading=Some(CONN_OPTIONS_GROUP))]
-    #[arg(value_hint=clap::ValueHint::Other)] // TODO complete instance name
+    #[arg(value_hint=clap::ValueHint::Other)] // completed by `_complete instance`
     #[arg(global = true)]
     pub instance: Option<InstanceName>,
 
+    /// Named instance from the `[instances]` table of the project
+    /// manifest, e.g. `staging` or `production`
+    #[arg(long, value_name="NAME", help_heading=Some(CONN_OPTIONS_GROUP))]
+    #[arg(conflicts_with_all=&["instance", "dsn", "credentials_file"])]
+    #[arg(global = true)]
+    pub env: Option<String>,
+
     /// DSN for [`BRANDING`] to connect to (overrides all other options
     /// except password)
     #[arg(long, help_heading=Some(CONN_OPTIONS_GROUP))]
@@ -215,15 +235,15 @@ pub struct ConnectionOptions {
     #[arg(global = true)]
     pub tls_server_name: Option<String>,
 
-    /// Retry up to WAIT_TIME (e.g. '30s') in case EdgeDB connection
-    /// cannot be established.
+    /// Retry up to WAIT_TIME (e.g. '30s') in case [`BRANDING`] connection
+    /// cannot be established. Useful for scripts connecting to an instance
+    /// that has just been started.
     #[arg(
         long,
         value_name="WAIT_TIME",
         help_heading=Some(CONN_OPTIONS_GROUP),
         value_parser=parse_duration,
     )]
-    #[arg(hide = true)]
     #[arg(global = true)]
     pub wait_until_available: Option<Duration>,
 
@@ -233,7 +253,7 @@ pub struct ConnectionOptions {
     #[arg(global = true)]
     pub admin: bool,
 
-    /// Fail when no response from EdgeDB for TIMEOUT (default '10s');
+    /// Fail when no response from [`BRANDING`] for TIMEOUT (default '10s');
     /// alternatively will retry if `--wait-until-available` is also specified.
     #[arg(
         long,
@@ -241,7 +261,6 @@ pub struct ConnectionOptions {
         help_heading=Some(CONN_OPTIONS_GROUP),
         value_parser=parse_duration,
     )]
-    #[arg(hide = true)]
     #[arg(global = true)]
     pub connect_timeout: Option<Duration>,
 }
@@ -337,6 +356,44 @@ pub struct RawOptions {
     #[arg(long)]
     pub no_cli_update_check: bool,
 
+    /// Don't use any proxy for HTTP requests to Cloud, package repository
+    /// and other external services
+    #[arg(long)]
+    pub no_proxy: bool,
+
+    /// Number of entries retained in the interactive shell history
+    #[arg(long, value_name = "entries")]
+    pub history_size: Option<usize>,
+
+    /// Format of messages, warnings and progress written to stderr.
+    /// `json` writes one JSON object per line, for use by wrapper tools.
+    #[arg(long, value_enum, default_value = "human", global = true)]
+    pub log_format: LogFormat,
+
+    /// Format of the error written to stderr when the command fails.
+    /// `json` writes a single JSON object with the error kind, message,
+    /// hint, server traceback and exit code.
+    #[arg(long, value_enum, default_value = "human", global = true)]
+    pub error_format: ErrorFormat,
+
+    /// Don't run project hooks. For emergencies, when a broken hook
+    /// blocks migrations, branch switching or project sync.
+    #[arg(long, global = true)]
+    pub skip_hooks: bool,
+
+    /// Run the command on every named instance matching the pattern, where
+    /// `*` matches any characters and `?` a single one, e.g. `tenant-*`.
+    /// Supported by `query`, `migrate` and `migration apply`.
+    #[arg(long, value_name = "pattern", global = true)]
+    #[arg(conflicts_with_all = ["instance", "dsn", "host", "port", "unix_path", "credentials_file"])]
+    pub instances: Option<String>,
+
+    /// Number of instances to run the command on at once
+    /// (`--instances` only).
+    #[arg(long, value_name = "count", default_value = "1", global = true)]
+    #[arg(requires = "instances")]
+    pub parallel: NonZeroUsize,
+
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
@@ -368,10 +425,16 @@ pub enum Command {
     Server(portable::server::Command),
     /// Manage local extensions
     Extension(portable::extension::Command),
+    /// Run commands against a throwaway local instance
+    Sandbox(portable::sandbox::Command),
     /// Generate shell completions
     #[command(name = "_gen_completions")]
     #[command(hide = true)]
     _GenCompletions(cli::install::GenCompletions),
+    /// Print dynamic values for shell completions
+    #[command(name = "_complete")]
+    #[command(hide = true)]
+    _Complete(cli::complete::Complete),
     /// Self-installation commands
     #[command(name = "cli")]
     Cli(CliCommand),
@@ -386,6 +449,22 @@ pub enum Command {
     Watch(WatchCommand),
     /// Manage branches
     Branch(branch::Command),
+    /// Run seed data files configured in the `[seed]` section of the
+    /// project manifest
+    Seed(seed::Command),
+    /// Inspect and configure the `auth` extension: providers, SMTP and
+    /// other settings
+    Auth(auth::Command),
+    /// Start a long-running JSON-RPC service over stdio that exposes
+    /// project connection parameters, schema, migration status and schema
+    /// file change events to editor integrations.
+    LspProxy(LspProxyCommand),
+    /// Inspect CLI configuration (`cli.toml` and project `[cli]` overrides)
+    Config(config::Command),
+    /// Diagnose connection problems
+    Connection(doctor::Command),
+    /// Run project hooks manually
+    Hook(hooks::command::Command),
     /// Generate a `SCRAM-SHA-256` hash for a password.
     HashPassword(HashPasswordCommand),
 }
@@ -395,8 +474,8 @@ pub struct Query {
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
-    /// Output format: `json`, `json-pretty`, `json-lines`, `tab-separated`.
-    /// Default is `json-pretty`.
+    /// Output format: `json`, `json-pretty`, `json-lines`, `tab-separated`,
+    /// `csv`, `table`, `html`. Default is `json-pretty`.
     // todo: can't use `arg(default='json-pretty')` just yet, as we
     // need to see if the user did actually specify some output
     // format or not. We need that to support the now deprecated
@@ -404,16 +483,88 @@ pub struct Query {
     #[arg(short = 'F', long)]
     pub output_format: Option<OutputFormat>,
 
+    /// Truncate cells wider than this number of characters
+    /// (`--output-format=table` only).
+    #[arg(long, value_name = "chars")]
+    pub max_col_width: Option<usize>,
+
+    /// Display each row as a separate record of `column | value` lines
+    /// (`--output-format=table` only).
+    #[arg(long)]
+    pub vertical: bool,
+
+    /// Do not print the names of the columns as the first row
+    /// (`--output-format=csv` only).
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// String to print in place of empty sets
+    /// (`--output-format=csv` and `tab-separated` only). Default is
+    /// an empty string.
+    #[arg(long, value_name = "string")]
+    pub null_as: Option<String>,
+
+    /// Add inline CSS styles (borders, padding) to the table, so it
+    /// renders nicely when pasted into an email
+    /// (`--output-format=html` only).
+    #[arg(long)]
+    pub html_styles: bool,
+
     /// Input language: `edgeql`, `sql`.
     /// Default is `edgeql`.
     #[arg(short = 'L', long)]
     pub input_language: Option<InputLanguage>,
 
     /// Filename to execute queries from.
-    /// Pass `--file -` to execute queries from stdin.
+    /// Pass `--file -` to execute queries from stdin. An `https://` or
+    /// `s3://` URL downloads the file first (up to 16 MiB).
     #[arg(short = 'f', long)]
     pub file: Option<String>,
 
+    /// Execute queries over the EdgeQL-over-HTTP endpoint of the server
+    /// (HTTPS), instead of the binary protocol. Also enabled by passing
+    /// an `http://` or `https://` DSN. Only JSON output formats are supported.
+    #[arg(long)]
+    pub http: bool,
+
+    /// Write results of the query to a file instead of stdout. The format
+    /// is chosen by the extension: `.parquet` for Parquet or `.arrow` for
+    /// Arrow IPC. Only a single query is allowed.
+    #[arg(long, value_name = "path")]
+    #[arg(conflicts_with_all=&["output_format", "file", "http"])]
+    pub output_file: Option<PathBuf>,
+
+    /// Show the query plan instead of the results, same as the
+    /// `analyze` command. Only a single query is allowed.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&["output_format", "file", "http", "output_file"])]
+    pub explain: bool,
+
+    /// Execute the query once for each line of JSON read from stdin, using
+    /// it as the query parameters, e.g. `{"name": "Alice"}`. An array can
+    /// be passed for positional parameters. Only a single query is allowed.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&["output_format", "file", "http", "output_file", "explain"])]
+    pub stdin_params: bool,
+
+    /// Number of parameter sets executed in a single transaction
+    /// (`--stdin-params` only).
+    #[arg(long, value_name = "rows", default_value_t = 100)]
+    #[arg(requires = "stdin_params", value_parser = clap::value_parser!(u32).range(1..))]
+    pub batch_size: u32,
+
+    /// Exit with a non-zero code if the server reported any warnings,
+    /// e.g. about use of deprecated features. Queries are still executed.
+    #[arg(long)]
+    pub fail_on_warnings: bool,
+
+    /// Set a session setting for the queries, e.g.
+    /// `--session-config apply_access_policies=false`. Can be repeated.
+    /// Only settings which can be changed with `CONFIGURE SESSION` are
+    /// allowed, the list is fetched from the server.
+    #[arg(long, value_name = "key=value", conflicts_with = "http")]
+    pub session_config: Vec<SessionConfig>,
+
     pub queries: Option<Vec<String>>,
 }
 
@@ -466,8 +617,24 @@ pub struct Options {
     pub debug_print_codecs: bool,
     pub input_language: Option<InputLanguage>,
     pub output_format: Option<OutputFormat>,
+    pub history_size: Option<usize>,
+    pub log_format: LogFormat,
+    pub error_format: ErrorFormat,
     pub no_cli_update_check: bool,
+    pub no_proxy: bool,
+    pub skip_hooks: bool,
+    /// Pattern of instance names given with `--instances`
+    pub instances: Option<String>,
+    pub parallel: NonZeroUsize,
     pub test_output_conn_params: bool,
+    /// Project instance selected with `--env`
+    pub environment: Option<Environment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Environment {
+    pub name: String,
+    pub confirm: bool,
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -484,6 +651,9 @@ impl UsageError {
             msg: msg.to_string(),
         }
     }
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
     pub fn exit(&self) -> ! {
         clap::Error::raw(self.kind, &self.msg).exit()
     }
@@ -774,7 +944,7 @@ impl Options {
     pub fn from_args_and_env() -> anyhow::Result<Options> {
         let app = Options::command();
         let matches = app.clone().get_matches();
-        let args = <RawOptions as clap::FromArgMatches>::from_arg_matches(&matches)?;
+        let mut args = <RawOptions as clap::FromArgMatches>::from_arg_matches(&matches)?;
         let cmd = <SubcommandOption as clap::FromArgMatches>::from_arg_matches(&matches)?;
 
         let subcommand = cmd.subcommand;
@@ -793,6 +963,8 @@ impl Options {
             anyhow::bail!("Option `-c` conflicts with specifying a subcommand");
         }
 
+        let environment = resolve_environment(&mut args.conn)?;
+
         // TODO(pc) add option to force interactive mode not on a tty (tests)
         let interactive = args.query.is_none() && subcommand.is_none() && stdin().is_terminal();
 
@@ -822,6 +994,18 @@ impl Options {
                 output_format,
                 input_language: Some(InputLanguage::EdgeQl),
                 file: None,
+                max_col_width: None,
+                vertical: false,
+                no_header: false,
+                null_as: None,
+                html_styles: false,
+                http: false,
+                output_file: None,
+                explain: false,
+                stdin_params: false,
+                batch_size: 100,
+                fail_on_warnings: false,
+                session_config: Vec::new(),
                 conn: args.conn.clone(),
             }))
         } else {
@@ -861,7 +1045,15 @@ impl Options {
             } else {
                 None
             },
+            history_size: args.history_size,
+            log_format: args.log_format,
+            error_format: args.error_format,
             no_cli_update_check,
+            no_proxy: args.no_proxy,
+            skip_hooks: args.skip_hooks,
+            instances: args.instances,
+            parallel: args.parallel,
+            environment,
             test_output_conn_params: args.test_output_conn_params,
         })
     }
@@ -877,6 +1069,8 @@ impl Options {
         }
         match builder.build_env().await {
             Ok(config) => {
+                let config = admin_over_tcp(&mut builder, config).await?;
+                let config = with_keychain_password(&self.conn_options, config).await;
                 let mut cfg = with_password(&self.conn_options, config).await?;
                 match (cfg.admin(), cfg.port(), cfg.local_instance_name()) {
                     (false, _, _) => {}
@@ -937,6 +1131,138 @@ impl Options {
     }
 }
 
+/// Replaces connection options with the ones of the project instance
+/// selected with `--env <name>`. `-I <name>` selects it too, if there is
+/// such an entry in the manifest.
+fn resolve_environment(conn: &mut ConnectionOptions) -> anyhow::Result<Option<Environment>> {
+    let name = match (&conn.env, &conn.instance) {
+        (Some(name), _) => name.clone(),
+        (None, Some(InstanceName::Local(name))) => name.clone(),
+        _ => return Ok(None),
+    };
+    let explicit = conn.env.is_some();
+    let manifest = match project::find_project(None) {
+        Ok(Some(location)) => project::manifest::read(&location.manifest)
+            .map(|manifest| (location.manifest, manifest)),
+        Ok(None) if explicit => Err(anyhow::anyhow!(
+            "`--env` requires a project, but no {MANIFEST_FILE_DISPLAY_NAME} was found"
+        )),
+        Ok(None) => return Ok(None),
+        Err(e) => Err(e),
+    };
+    let (path, manifest) = match manifest {
+        Ok(res) => res,
+        Err(e) if explicit => return Err(e),
+        Err(e) => {
+            log::debug!("Cannot read project manifest: {e:#}");
+            return Ok(None);
+        }
+    };
+    let Some(cfg) = manifest.instances.get(&name) else {
+        if !explicit {
+            return Ok(None);
+        }
+        let names = manifest.instances.keys().cloned().collect::<Vec<_>>();
+        return Err(anyhow::anyhow!(
+            "no `[instances.{name}]` table in {}",
+            path.display()
+        ))
+        .with_hint(|| {
+            if names.is_empty() {
+                "Add an `[instances.<name>]` table with `instance` or `dsn` to the manifest".into()
+            } else {
+                format!("Available instances: {}", names.join(", "))
+            }
+        })?;
+    };
+    log::info!("Using project instance {name:?}");
+    // the resolved options are passed to child processes as is
+    conn.env = None;
+    conn.instance = None;
+    conn.dsn = None;
+    match (&cfg.instance, &cfg.dsn) {
+        (Some(instance), None) => {
+            conn.instance = Some(instance.parse().map_err(|e: anyhow::Error| {
+                e.context(format!("invalid instance in `[instances.{name}]`"))
+            })?);
+        }
+        (None, Some(dsn)) => conn.dsn = Some(dsn.clone()),
+        _ => anyhow::bail!("`[instances.{name}]` must contain either `instance` or `dsn`"),
+    }
+    if conn.branch.is_none() && conn.database.is_none() {
+        conn.branch = cfg.branch.clone();
+    }
+    Ok(Some(Environment {
+        name,
+        confirm: cfg.confirm,
+    }))
+}
+
+/// Creates connector for `\connect` in REPL. Connection options from the
+/// command line are not carried over to the new instance.
+pub async fn connector_for(
+    instance: Option<&InstanceName>,
+    dsn: Option<&str>,
+) -> anyhow::Result<Connector> {
+    let mut builder = Builder::new();
+    if let Some(instance) = instance {
+        builder.instance(&instance.to_string())?;
+    }
+    if let Some(dsn) = dsn {
+        builder.dsn(dsn).context("invalid DSN")?;
+    }
+    let config = builder.build_env().await?;
+    Ok(Connector::new(Ok(keychain_password(config).await)))
+}
+
+/// Credentials files are read by the client library itself, so the password
+/// moved to the keychain must be added to the config here
+/// Unix sockets of the instances are inside WSL on Windows, so admin
+/// connections go over TCP loopback. The admin flag only selects the name
+/// of the unix socket, and is cleared so that the config describes the
+/// plain TCP connection which is made.
+async fn admin_over_tcp(builder: &mut Builder, config: Config) -> anyhow::Result<Config> {
+    if !cfg!(windows) || !config.admin() || config.port().is_none() {
+        return Ok(config);
+    }
+    match config.local_instance_name() {
+        Some(name) => windows::check_admin_config(name, &config)?,
+        None => connect::check_admin_over_tcp(&config)?,
+    }
+    builder.admin(false);
+    Ok(builder.build_env().await?)
+}
+
+async fn with_keychain_password(options: &ConnectionOptions, config: Config) -> Config {
+    if options.password || options.password_from_stdin || options.no_password {
+        return config;
+    }
+    keychain_password(config).await
+}
+
+async fn keychain_password(config: Config) -> Config {
+    if !keychain::enabled() {
+        return config;
+    }
+    let Some(name) = config.local_instance_name().map(|n| n.to_string()) else {
+        return config;
+    };
+    let creds = match credentials::path(&name) {
+        Ok(path) => credentials::read(&path).await,
+        Err(e) => Err(e),
+    };
+    match creds {
+        Ok(creds) => match creds.password {
+            Some(password) => config.with_password(&password),
+            None => config,
+        },
+        Err(e) => {
+            log::warn!("Cannot read credentials of {name:?}: {e:#}");
+            config
+        }
+    }
+}
+
 async fn with_password(options: &ConnectionOptions, config: Config) -> anyhow::Result<Config> {
     if options.password_from_stdin {
         let password = unblock(tty_password::read_stdin).await??;
@@ -955,6 +1281,16 @@ async fn with_password(options: &ConnectionOptions, config: Config) -> anyhow::R
     }
 }
 
+/// Splits an `http://` or `https://` DSN into whether TLS is used and
+/// the rest of the DSN.
+pub fn http_dsn_scheme(dsn: &str) -> Option<(bool, &str)> {
+    if let Some(rest) = dsn.strip_prefix("https://") {
+        Some((true, rest))
+    } else {
+        dsn.strip_prefix("http://").map(|rest| (false, rest))
+    }
+}
+
 pub fn prepare_conn_params(opts: &Options) -> anyhow::Result<Builder> {
     let tmp = &opts.conn_options;
     let mut bld = Builder::new();
@@ -977,7 +1313,21 @@ pub fn prepare_conn_params(opts: &Options) -> anyhow::Result<Builder> {
         bld.port(port)?;
     }
     if let Some(dsn) = &tmp.dsn {
-        bld.dsn(dsn).context("invalid DSN")?;
+        // HTTP DSNs select the HTTP transport of `query`, the rest of the
+        // DSN is interpreted the same way as for the binary protocol.
+        let dsn = match http_dsn_scheme(dsn) {
+            Some((_, rest)) if matches!(opts.subcommand, Some(Command::Query(_))) => {
+                Cow::Owned(format!("edgedb://{rest}"))
+            }
+            Some(_) => {
+                return Err(anyhow::anyhow!(
+                    "`http://` and `https://` DSNs are only supported by `query`"
+                ))
+                .hint("Use an `edgedb://` DSN for other commands.")?;
+            }
+            None => Cow::Borrowed(dsn.as_str()),
+        };
+        bld.dsn(&dsn).context("invalid DSN")?;
     }
     if let Some(instance) = &tmp.instance {
         bld.instance(&instance.to_string())?;
@@ -0,0 +1,379 @@
+//! Parquet and Arrow IPC files written by `query --output-file`
+//!
+//! Column types are detected from the values of the first batch of rows,
+//! columns containing only empty sets there are written as strings.
+//! Rows are written in batches, so that the whole result set doesn't need
+//! to fit in memory.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use arrow_array::builder::TimestampMicrosecondBuilder;
+use arrow_array::builder::{BinaryBuilder, BooleanBuilder, StringBuilder};
+use arrow_array::builder::{Float32Builder, Float64Builder};
+use arrow_array::builder::{Int16Builder, Int32Builder, Int64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use bigdecimal::BigDecimal;
+use fs_err as fs;
+use gel_protocol::value::Value;
+use num_bigint::BigInt;
+use parquet::arrow::ArrowWriter;
+
+use crate::hint::HintExt;
+use crate::outputs::tab_separated;
+
+/// Number of rows written at once
+const BATCH_ROWS: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Parquet,
+    Arrow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Bool,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Datetime,
+    Bytes,
+    Str,
+}
+
+enum Builder {
+    Bool(BooleanBuilder),
+    Int16(Int16Builder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    Float32(Float32Builder),
+    Float64(Float64Builder),
+    Datetime(TimestampMicrosecondBuilder),
+    Bytes(BinaryBuilder),
+    Str(StringBuilder),
+}
+
+enum Sink {
+    Parquet(ArrowWriter<fs::File>),
+    Arrow(FileWriter<fs::File>),
+}
+
+pub struct Writer {
+    format: Format,
+    file: Option<fs::File>,
+    names: Option<Vec<String>>,
+    /// Rows received before column types are known
+    pending: Vec<Vec<Option<Value>>>,
+    schema: Option<SchemaRef>,
+    sink: Option<Sink>,
+    builders: Vec<Builder>,
+    buffered: usize,
+    rows: u64,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> anyhow::Result<Format> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("parquet") => Ok(Format::Parquet),
+            Some("arrow" | "arrows" | "ipc" | "feather") => Ok(Format::Arrow),
+            _ => Err(anyhow::anyhow!(
+                "cannot determine output format of {path:?}"
+            ))
+            .hint("Use `.parquet` extension for Parquet or `.arrow` for Arrow IPC")?,
+        }
+    }
+}
+
+impl Writer {
+    pub fn create(path: &Path) -> anyhow::Result<Writer> {
+        let format = Format::from_path(path)?;
+        let file = fs::File::create(path)?;
+        Ok(Writer {
+            format,
+            file: Some(file),
+            names: None,
+            pending: Vec::new(),
+            schema: None,
+            sink: None,
+            builders: Vec::new(),
+            buffered: 0,
+            rows: 0,
+        })
+    }
+
+    pub fn push(&mut self, row: Value) -> anyhow::Result<()> {
+        if self.names.is_none() {
+            self.names = Some(column_names(&row));
+        }
+        let values = row_values(row)?;
+        if self.sink.is_none() {
+            self.pending.push(values);
+            if self.pending.len() >= BATCH_ROWS {
+                self.start()?;
+            }
+            return Ok(());
+        }
+        self.append(values)?;
+        if self.buffered >= BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes remaining rows and the file footer, returns number of rows
+    pub fn finish(mut self) -> anyhow::Result<u64> {
+        if self.sink.is_none() {
+            self.start()?;
+        }
+        self.flush()?;
+        match self.sink.take().expect("sink is started") {
+            Sink::Parquet(writer) => {
+                writer.close()?;
+            }
+            Sink::Arrow(mut writer) => {
+                writer.finish()?;
+            }
+        }
+        Ok(self.rows)
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        let names = self.names.clone().unwrap_or_default();
+        let mut kinds = vec![None; names.len()];
+        for row in &self.pending {
+            if row.len() != names.len() {
+                anyhow::bail!("rows have different number of columns");
+            }
+            for (kind, value) in kinds.iter_mut().zip(row) {
+                if kind.is_none() {
+                    if let Some(value) = value {
+                        *kind = value_kind(value)?;
+                    }
+                }
+            }
+        }
+        let kinds = kinds
+            .into_iter()
+            .map(|k| k.unwrap_or(Kind::Str))
+            .collect::<Vec<_>>();
+        let schema = Arc::new(Schema::new(
+            names
+                .iter()
+                .zip(&kinds)
+                .map(|(name, kind)| Field::new(name, kind.data_type(), true))
+                .collect::<Vec<_>>(),
+        ));
+        let file = self.file.take().expect("file is open");
+        self.sink = Some(match self.format {
+            Format::Parquet => Sink::Parquet(ArrowWriter::try_new(file, schema.clone(), None)?),
+            Format::Arrow => Sink::Arrow(FileWriter::try_new(file, &schema)?),
+        });
+        self.builders = kinds.into_iter().map(Builder::new).collect();
+        self.schema = Some(schema);
+        for row in std::mem::take(&mut self.pending) {
+            self.append(row)?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, values: Vec<Option<Value>>) -> anyhow::Result<()> {
+        if values.len() != self.builders.len() {
+            anyhow::bail!("rows have different number of columns");
+        }
+        for (builder, value) in self.builders.iter_mut().zip(values) {
+            builder.append(value)?;
+        }
+        self.buffered += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+        let schema = self.schema.clone().expect("schema is known");
+        let columns = self.builders.iter_mut().map(|b| b.finish()).collect();
+        let batch = RecordBatch::try_new(schema, columns).context("cannot build record batch")?;
+        match self.sink.as_mut().expect("sink is started") {
+            Sink::Parquet(writer) => writer.write(&batch)?,
+            Sink::Arrow(writer) => writer.write(&batch)?,
+        }
+        self.rows += self.buffered as u64;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+impl Kind {
+    fn data_type(&self) -> DataType {
+        match self {
+            Kind::Bool => DataType::Boolean,
+            Kind::Int16 => DataType::Int16,
+            Kind::Int32 => DataType::Int32,
+            Kind::Int64 => DataType::Int64,
+            Kind::Float32 => DataType::Float32,
+            Kind::Float64 => DataType::Float64,
+            Kind::Datetime => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            Kind::Bytes => DataType::Binary,
+            Kind::Str => DataType::Utf8,
+        }
+    }
+}
+
+impl Builder {
+    fn new(kind: Kind) -> Builder {
+        match kind {
+            Kind::Bool => Builder::Bool(BooleanBuilder::new()),
+            Kind::Int16 => Builder::Int16(Int16Builder::new()),
+            Kind::Int32 => Builder::Int32(Int32Builder::new()),
+            Kind::Int64 => Builder::Int64(Int64Builder::new()),
+            Kind::Float32 => Builder::Float32(Float32Builder::new()),
+            Kind::Float64 => Builder::Float64(Float64Builder::new()),
+            Kind::Datetime => Builder::Datetime(TimestampMicrosecondBuilder::new()),
+            Kind::Bytes => Builder::Bytes(BinaryBuilder::new()),
+            Kind::Str => Builder::Str(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: Option<Value>) -> anyhow::Result<()> {
+        use Value as V;
+
+        match (self, value) {
+            (b, None | Some(V::Nothing)) => b.append_null(),
+            (Builder::Bool(b), Some(V::Bool(v))) => b.append_value(v),
+            (Builder::Int16(b), Some(V::Int16(v))) => b.append_value(v),
+            (Builder::Int32(b), Some(V::Int32(v))) => b.append_value(v),
+            (Builder::Int64(b), Some(V::Int64(v))) => b.append_value(v),
+            (Builder::Float32(b), Some(V::Float32(v))) => b.append_value(v),
+            (Builder::Float64(b), Some(V::Float64(v))) => b.append_value(v),
+            (Builder::Datetime(b), Some(V::Datetime(v))) => b.append_value(v.to_unix_micros()),
+            (Builder::Bytes(b), Some(V::Bytes(v))) => b.append_value(v),
+            (Builder::Str(b), Some(v)) => b.append_value(value_to_string(&v)?),
+            (_, Some(v)) => anyhow::bail!("unexpected value {v:?} in a column of another type"),
+        }
+        Ok(())
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            Builder::Bool(b) => b.append_null(),
+            Builder::Int16(b) => b.append_null(),
+            Builder::Int32(b) => b.append_null(),
+            Builder::Int64(b) => b.append_null(),
+            Builder::Float32(b) => b.append_null(),
+            Builder::Float64(b) => b.append_null(),
+            Builder::Datetime(b) => b.append_null(),
+            Builder::Bytes(b) => b.append_null(),
+            Builder::Str(b) => b.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Builder::Bool(b) => Arc::new(b.finish()),
+            Builder::Int16(b) => Arc::new(b.finish()),
+            Builder::Int32(b) => Arc::new(b.finish()),
+            Builder::Int64(b) => Arc::new(b.finish()),
+            Builder::Float32(b) => Arc::new(b.finish()),
+            Builder::Float64(b) => Arc::new(b.finish()),
+            Builder::Datetime(b) => Arc::new(b.finish().with_timezone("UTC")),
+            Builder::Bytes(b) => Arc::new(b.finish()),
+            Builder::Str(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// Names of the columns, positions are used for unnamed tuples
+fn column_names(row: &Value) -> Vec<String> {
+    match row {
+        Value::Tuple(fields) => (0..fields.len()).map(|i| i.to_string()).collect(),
+        Value::Object { .. } | Value::NamedTuple { .. } => {
+            tab_separated::column_names(row).unwrap_or_default()
+        }
+        _ => vec!["value".into()],
+    }
+}
+
+/// Values of the columns of the row, `None` for empty sets
+fn row_values(row: Value) -> anyhow::Result<Vec<Option<Value>>> {
+    match row {
+        Value::Object { shape, fields } => Ok(shape
+            .elements
+            .iter()
+            .zip(fields)
+            .filter(|(s, _)| !s.flag_implicit)
+            .map(|(_, v)| v)
+            .collect()),
+        Value::NamedTuple { fields, .. } | Value::Tuple(fields) => {
+            Ok(fields.into_iter().map(Some).collect())
+        }
+        Value::SparseObject(_) | Value::SQLRow { .. } => {
+            anyhow::bail!("{row:?} cannot be written as a row")
+        }
+        v => Ok(vec![Some(v)]),
+    }
+}
+
+fn value_kind(v: &Value) -> anyhow::Result<Option<Kind>> {
+    use Value as V;
+
+    let kind = match v {
+        V::Nothing => return Ok(None),
+        V::Bool(_) => Kind::Bool,
+        V::Int16(_) => Kind::Int16,
+        V::Int32(_) => Kind::Int32,
+        V::Int64(_) => Kind::Int64,
+        V::Float32(_) => Kind::Float32,
+        V::Float64(_) => Kind::Float64,
+        V::Datetime(_) => Kind::Datetime,
+        V::Bytes(_) => Kind::Bytes,
+        V::Uuid(_)
+        | V::Str(_)
+        | V::Json(_)
+        | V::Enum(_)
+        | V::BigInt(_)
+        | V::Decimal(_)
+        | V::LocalDatetime(_)
+        | V::LocalDate(_)
+        | V::LocalTime(_)
+        | V::Duration(_)
+        | V::RelativeDuration(_)
+        | V::DateDuration(_)
+        | V::ConfigMemory(_) => Kind::Str,
+        _ => anyhow::bail!(
+            "Complex values like {v:?} cannot be written to Parquet or Arrow \
+             files, select scalar properties instead"
+        ),
+    };
+    Ok(Some(kind))
+}
+
+fn value_to_string(v: &Value) -> anyhow::Result<String> {
+    match v {
+        Value::BigInt(v) => Ok(BigInt::from(v).to_string()),
+        Value::Decimal(v) => Ok(BigDecimal::from(v).to_string()),
+        Value::LocalDatetime(v) => Ok(format!("{v:?}")),
+        Value::LocalDate(v) => Ok(format!("{v:?}")),
+        Value::LocalTime(v) => Ok(format!("{v:?}")),
+        v => tab_separated::value_to_string(v),
+    }
+}
+
+#[test]
+fn format_from_path() {
+    assert_eq!(
+        Format::from_path(Path::new("out.parquet")).unwrap(),
+        Format::Parquet
+    );
+    assert_eq!(
+        Format::from_path(Path::new("data/out.arrow")).unwrap(),
+        Format::Arrow
+    );
+    assert!(Format::from_path(Path::new("out.csv")).is_err());
+}
@@ -0,0 +1,33 @@
+use gel_protocol::value::Value;
+
+use crate::outputs::tab_separated::{column_names, row_values};
+
+/// Formats a row as RFC 4180 CSV record (without the line terminator)
+pub fn format_row(v: &Value, null_as: &str) -> Result<String, anyhow::Error> {
+    Ok(row_values(v)?
+        .iter()
+        .map(|v| quote(v.as_deref().unwrap_or(null_as)))
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Header record with names of the columns, if the row has a shape
+pub fn format_header(v: &Value) -> Option<String> {
+    column_names(v).map(|names| names.iter().map(|n| quote(n)).collect::<Vec<_>>().join(","))
+}
+
+fn quote(v: &str) -> String {
+    if v.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", v.replace('"', "\"\""))
+    } else {
+        v.to_string()
+    }
+}
+
+#[test]
+fn quoting() {
+    assert_eq!(quote("plain"), "plain");
+    assert_eq!(quote("a,b"), "\"a,b\"");
+    assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(quote("two\nlines"), "\"two\nlines\"");
+}
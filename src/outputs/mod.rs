@@ -1 +1,5 @@
+#[cfg(feature = "columnar")]
+pub mod columnar;
+pub mod csv;
 pub mod tab_separated;
+pub mod yaml;
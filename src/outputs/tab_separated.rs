@@ -1,23 +1,70 @@
 use gel_protocol::value::Value::{self, *};
 
-pub fn format_row(v: &Value) -> Result<String, anyhow::Error> {
-    match v {
-        Object { shape, fields } => Ok(shape
+/// Formats a row, escaping backslashes, tabs and newlines in values
+pub fn format_row(v: &Value, null_as: &str) -> Result<String, anyhow::Error> {
+    Ok(row_values(v)?
+        .iter()
+        .map(|v| match v {
+            Some(v) => escape(v),
+            None => null_as.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\t"))
+}
+
+/// Values of the columns of the row, `None` for empty sets
+pub fn row_values(v: &Value) -> Result<Vec<Option<String>>, anyhow::Error> {
+    let fields = match v {
+        Object { shape, fields } => shape
             .elements
             .iter()
             .zip(fields)
             .filter(|(s, _)| !s.flag_implicit)
-            .map(|(_, v)| match v {
-                Some(v) => value_to_string(v),
-                None => Ok(String::new()),
-            })
-            .collect::<Result<Vec<_>, _>>()?
-            .join("\t")),
-        _ => value_to_string(v),
+            .map(|(_, v)| v.as_ref())
+            .collect(),
+        NamedTuple { fields, .. } | Tuple(fields) => fields.iter().map(Some).collect(),
+        _ => vec![Some(v)],
+    };
+    fields
+        .into_iter()
+        .map(|v| match v {
+            Some(Nothing) | None => Ok(None),
+            Some(v) => value_to_string(v).map(Some),
+        })
+        .collect()
+}
+
+/// Names of the columns, if the row has a shape
+pub fn column_names(v: &Value) -> Option<Vec<String>> {
+    match v {
+        Object { shape, .. } => Some(
+            shape
+                .elements
+                .iter()
+                .filter(|s| !s.flag_implicit)
+                .map(|s| s.name.clone())
+                .collect(),
+        ),
+        NamedTuple { shape, .. } => Some(shape.elements.iter().map(|s| s.name.clone()).collect()),
+        _ => None,
     }
 }
 
-fn value_to_string(v: &Value) -> Result<String, anyhow::Error> {
+fn escape(v: &str) -> String {
+    let mut buf = String::with_capacity(v.len());
+    for c in v.chars() {
+        match c {
+            '\\' => buf.push_str("\\\\"),
+            '\t' => buf.push_str("\\t"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            c => buf.push(c),
+        }
+    }
+    buf
+}
+
+pub fn value_to_string(v: &Value) -> Result<String, anyhow::Error> {
     use gel_protocol::value::Value::*;
     match v {
         Nothing => Ok(String::new()),
@@ -57,8 +104,15 @@ fn value_to_string(v: &Value) -> Result<String, anyhow::Error> {
         | PostGisBox3d {..}
         => {
             Err(anyhow::anyhow!(
-                "Complex objects like {:?} cannot be printed tab-separated",
+                "Complex objects like {:?} cannot be printed in \
+                 tab-separated or csv format",
                 v))
         }
     }
 }
+
+#[test]
+fn escaping() {
+    assert_eq!(escape("a\tb\nc\\d"), "a\\tb\\nc\\\\d");
+    assert_eq!(escape("plain"), "plain");
+}
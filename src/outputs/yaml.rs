@@ -0,0 +1,155 @@
+//! YAML output of `--format yaml`
+//!
+//! Documents are serialized to JSON values first and emitted in block
+//! style. Strings which could be read back as something else (numbers,
+//! booleans, null) or contain special characters are double-quoted, using
+//! JSON escapes, which are valid in YAML.
+
+use serde_json::{Map, Value};
+
+/// Formats a value as a YAML document (with the trailing newline)
+pub fn to_string<T: serde::Serialize>(value: &T) -> anyhow::Result<String> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    match &value {
+        Value::Object(map) if !map.is_empty() => write_mapping(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => write_sequence(&mut out, items, 0),
+        _ => {
+            out.push_str(&scalar(&value));
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+fn write_mapping(out: &mut String, map: &Map<String, Value>, indent: usize) {
+    for (idx, (key, value)) in map.iter().enumerate() {
+        // the first key is on the line started by the caller
+        if idx > 0 {
+            push_indent(out, indent);
+        }
+        out.push_str(&string(key));
+        out.push(':');
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                out.push('\n');
+                push_indent(out, indent + 2);
+                write_mapping(out, map, indent + 2);
+            }
+            Value::Array(items) if !items.is_empty() => {
+                out.push('\n');
+                push_indent(out, indent);
+                write_sequence(out, items, indent);
+            }
+            _ => {
+                out.push(' ');
+                out.push_str(&scalar(value));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn write_sequence(out: &mut String, items: &[Value], indent: usize) {
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            push_indent(out, indent);
+        }
+        out.push_str("- ");
+        match item {
+            Value::Object(map) if !map.is_empty() => write_mapping(out, map, indent + 2),
+            Value::Array(items) if !items.is_empty() => write_sequence(out, items, indent + 2),
+            _ => {
+                out.push_str(&scalar(item));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    out.push_str(&" ".repeat(indent));
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".into(),
+        Value::Bool(val) => val.to_string(),
+        Value::Number(val) => val.to_string(),
+        Value::String(val) => string(val),
+        Value::Array(_) => "[]".into(),
+        Value::Object(_) => "{}".into(),
+    }
+}
+
+fn string(val: &str) -> String {
+    if is_plain(val) {
+        val.into()
+    } else {
+        Value::from(val).to_string()
+    }
+}
+
+/// Whether the string can be written unquoted and is read back as the
+/// same string
+fn is_plain(val: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n",
+    ];
+    let Some(first) = val.chars().next() else {
+        return false;
+    };
+    (first.is_alphabetic() || first == '_' || first == '/')
+        && val
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.' | '/' | '@' | '+'))
+        && !val.ends_with(' ')
+        && !RESERVED.contains(&val.to_lowercase().as_str())
+}
+
+#[test]
+fn yaml() {
+    use serde_json::json;
+
+    assert_eq!(
+        to_string(&json!({"name": "inst1", "port": 10700})).unwrap(),
+        "name: inst1\nport: 10700\n"
+    );
+    assert_eq!(to_string(&"inst1").unwrap(), "inst1\n");
+    assert_eq!(to_string(&None::<String>).unwrap(), "null\n");
+    assert_eq!(
+        to_string(&json!({
+            "version": "6.0",
+            "enabled": "true",
+            "dsn": "gel://admin@localhost:10700",
+            "path": "/home/user/.local/share",
+            "note": "two\nlines",
+            "empty": "",
+        }))
+        .unwrap(),
+        "version: \"6.0\"\n\
+         enabled: \"true\"\n\
+         dsn: \"gel://admin@localhost:10700\"\n\
+         path: /home/user/.local/share\n\
+         note: \"two\\nlines\"\n\
+         empty: \"\"\n"
+    );
+    assert_eq!(
+        to_string(&json!({
+            "instance": {"name": "inst1", "tags": []},
+            "projects": ["/srv/app", {"name": "web", "port": 1}],
+            "nested": [[1, 2]],
+        }))
+        .unwrap(),
+        "instance:\n  \
+           name: inst1\n  \
+           tags: []\n\
+         projects:\n\
+         - /srv/app\n\
+         - name: web\n  \
+           port: 1\n\
+         nested:\n\
+         - - 1\n  \
+           - 2\n"
+    );
+}
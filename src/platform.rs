@@ -10,6 +10,8 @@ use crate::branding::{
 };
 use crate::cli::env::Env;
 
+pub mod lock;
+
 #[cfg(windows)]
 pub type Uid = u32;
 
@@ -206,6 +208,20 @@ pub fn pager_path() -> anyhow::Result<String> {
     })
 }
 
+pub async fn spawn_pager(path: &Path) -> anyhow::Result<()> {
+    let pager = pager_path()?;
+    let mut items = pager.split_whitespace();
+    let mut cmd = tokio::process::Command::new(items.next().unwrap());
+    cmd.args(items);
+    cmd.arg(path);
+    let res = cmd.status().await?;
+    if res.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("pager exited with: {}", res))
+    }
+}
+
 pub async fn spawn_editor(path: &Path) -> anyhow::Result<()> {
     let editor = editor_path()?;
     let mut items = editor.split_whitespace();
@@ -0,0 +1,170 @@
+//! Advisory locks for files shared between concurrently running commands,
+//! such as the port mapping, credentials and project stash directories.
+//!
+//! Locks are held by the operating system and released when the process
+//! exits. The lock file additionally records the holder, so that a
+//! command waiting for the lock can tell what it waits for.
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+
+use crate::hint::HintExt;
+use crate::print;
+use crate::process;
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, PartialEq, Eq)]
+struct Holder {
+    pid: u32,
+    command: String,
+}
+
+/// Path of the lock file guarding `path`: a hidden `.<name>.lock` file
+/// next to it, so that it's skipped when listing directories
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+/// Runs `f` while holding an exclusive lock on `path`.
+///
+/// Waits for other processes holding the lock, up to a minute. If the file
+/// system doesn't support locking, `f` runs without the lock.
+pub fn with_lock<T>(path: &Path, f: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let lock_path = lock_path(path);
+    if let Some(parent) = lock_path.parent() {
+        fs_err::create_dir_all(parent)?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .read(true)
+        .open(&lock_path)
+        .with_context(|| format!("cannot open lock file {lock_path:?}"))?;
+    let mut lock = fd_lock::RwLock::new(file);
+    let started = Instant::now();
+    let mut reported = false;
+    loop {
+        match lock.try_write() {
+            Ok(mut guard) => {
+                if let Err(e) = write_holder(&mut guard) {
+                    log::debug!("Cannot write lock holder to {lock_path:?}: {e:#}");
+                }
+                let result = f();
+                guard.set_len(0).ok();
+                return result;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => {
+                log::warn!("Cannot lock {lock_path:?}, continuing without lock: {e:#}");
+                return f();
+            }
+        }
+        let holder = read_holder(&lock_path);
+        if started.elapsed() > LOCK_TIMEOUT {
+            return Err(timeout_error(&lock_path, holder.as_ref()));
+        }
+        if !reported {
+            match &holder {
+                Some(holder) => print::warn!(
+                    "Waiting for process {} (`{}`) to release {}...",
+                    holder.pid,
+                    holder.command,
+                    path.display(),
+                ),
+                None => print::warn!(
+                    "Waiting for another process to release {}...",
+                    path.display()
+                ),
+            }
+            reported = true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn write_holder(file: &mut fs::File) -> io::Result<()> {
+    let command = env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    file.set_len(0)?;
+    file.rewind()?;
+    write!(file, "{}\n{}", std::process::id(), command)?;
+    file.flush()
+}
+
+fn read_holder(lock_path: &Path) -> Option<Holder> {
+    // On Windows the locked file can't be read, so there is no holder info
+    let data = fs::read_to_string(lock_path).ok()?;
+    parse_holder(&data)
+}
+
+fn parse_holder(data: &str) -> Option<Holder> {
+    let (pid, command) = data.split_once('\n')?;
+    Some(Holder {
+        pid: pid.trim().parse().ok()?,
+        command: command.trim().to_string(),
+    })
+}
+
+fn timeout_error(lock_path: &Path, holder: Option<&Holder>) -> anyhow::Error {
+    match holder {
+        Some(holder) if process::exists(holder.pid) => anyhow::anyhow!(
+            "timed out waiting for process {} (`{}`) to release lock {lock_path:?}",
+            holder.pid,
+            holder.command,
+        )
+        .hint("Wait for the other command to finish (or stop it) and try again")
+        .into(),
+        Some(holder) => anyhow::anyhow!(
+            "lock {lock_path:?} is stale: process {} (`{}`) exited, \
+             but the lock is still held by a process it started",
+            holder.pid,
+            holder.command,
+        )
+        .with_hint(|| {
+            format!(
+                "Stop leftover processes of the command, \
+                 or remove {lock_path:?} if there are none"
+            )
+        })
+        .into(),
+        None => anyhow::anyhow!("timed out waiting for lock {lock_path:?} held by another process")
+            .hint("Wait for the other command to finish (or stop it) and try again")
+            .into(),
+    }
+}
+
+#[test]
+fn holder() {
+    assert_eq!(
+        parse_holder("1234\nedgedb project init\n"),
+        Some(Holder {
+            pid: 1234,
+            command: "edgedb project init".into(),
+        })
+    );
+    assert_eq!(parse_holder(""), None);
+    assert_eq!(parse_holder("x\ny"), None);
+}
+
+#[test]
+fn lock_path_suffix() {
+    assert_eq!(
+        lock_path(Path::new("/tmp/instance_ports.json")),
+        Path::new("/tmp/.instance_ports.json.lock")
+    );
+}
@@ -3,10 +3,12 @@ use std::path::Path;
 
 use anyhow::Context;
 use edgedb_cli_derive::IntoArgs;
+use edgeql_parser::helpers::quote_name;
 use log::trace;
 use prettytable::{row, Table};
 
-use crate::branding::BRANDING_CLOUD;
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::connect::Connector;
 use crate::hint::HintExt;
 use crate::options::Options;
 use crate::portable::local::InstanceInfo;
@@ -15,6 +17,7 @@ use crate::portable::platform::get_server;
 use crate::portable::repository::{get_platform_extension_packages, Channel};
 use crate::portable::server::install::download_package;
 use crate::portable::windows;
+use crate::print::{self, msg};
 use crate::table;
 
 pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
@@ -42,13 +45,19 @@ pub struct Command {
 
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum Subcommands {
-    /// List installed extensions for a local instance.
+    /// List installed extensions for an instance.
+    ///
+    /// For Cloud instances lists extensions enabled in the current branch.
     List(ExtensionList),
-    /// List available extensions for a local instance.
+    /// List available extensions for an instance.
     ListAvailable(ExtensionListAvailable),
-    /// Install an extension for a local instance.
+    /// Install an extension for an instance.
+    ///
+    /// For Cloud instances enables the extension in the current branch.
     Install(ExtensionInstall),
-    /// Uninstall an extension from a local instance.
+    /// Uninstall an extension from an instance.
+    ///
+    /// For Cloud instances disables the extension in the current branch.
     Uninstall(ExtensionUninstall),
 }
 
@@ -89,7 +98,7 @@ pub struct ExtensionInstall {
     #[arg(long, hide = true)]
     pub reinstall: bool,
 }
-/// Represents the options for uninstalling an extension from a local EdgeDB instance.
+/// Represents the options for uninstalling an extension from an instance.
 #[derive(clap::Args, IntoArgs, Debug, Clone)]
 pub struct ExtensionUninstall {
     #[arg(from_global)]
@@ -100,54 +109,106 @@ pub struct ExtensionUninstall {
     pub extension: String,
 }
 
-fn get_local_instance(instance: &Option<InstanceName>) -> Result<InstanceInfo, anyhow::Error> {
+enum Target {
+    Local(InstanceInfo),
+    /// Extensions are provided by the server, so they are managed by
+    /// enabling them in the schema
+    Cloud(InstanceName),
+}
+
+fn get_instance(instance: &Option<InstanceName>) -> Result<Target, anyhow::Error> {
     let name = match instance_arg(&None, instance)? {
         InstanceName::Local(name) => name,
-        inst_name => {
-            return Err(anyhow::anyhow!(
-                "cannot install extensions in {BRANDING_CLOUD} instance {}.",
-                inst_name
-            ))
-            .with_hint(|| {
-                format!("only local instances can install extensions ({inst_name} is remote)")
-            })?;
-        }
+        cloud_name => return Ok(Target::Cloud(cloud_name)),
     };
     let Some(inst) = InstanceInfo::try_read(&name)? else {
         return Err(anyhow::anyhow!(
-            "cannot install extensions in {BRANDING_CLOUD} instance {}.",
+            "cannot manage extensions in remote instance {}.",
             name
         ))
-        .with_hint(|| format!("only local instances can install extensions ({name} is remote)"))?;
+        .with_hint(|| {
+            format!(
+                "only local and {BRANDING_CLOUD} instances can install extensions \
+                 ({name} is remote)"
+            )
+        })?;
     };
-    Ok(inst)
+    Ok(Target::Local(inst))
 }
 
 type ExtensionInfo = (String, String);
 
-fn get_extensions(options: &Options) -> Result<Vec<ExtensionInfo>, anyhow::Error> {
-    // if remote or cloud instance, connect and query extension packages
-    let connector = options.block_on_create_connector()?;
-
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?;
-
-    let extensions = connector.run_single_query::<ExtensionInfo>(
-        "for ext in sys::ExtensionPackage union (
-            with
-                ver := ext.version,
-                ver_str := <str>ver.major++'.'++<str>ver.minor,
-            select (ext.name, ver_str)
-        );",
-    );
+const PACKAGES_QUERY: &str = "for ext in sys::ExtensionPackage union (
+    with
+        ver := ext.version,
+        ver_str := <str>ver.major++'.'++<str>ver.minor,
+    select (ext.name, ver_str)
+);";
+
+const ENABLED_QUERY: &str = "for ext in schema::Extension union (
+    with
+        ver := ext.package.version,
+        ver_str := <str>ver.major++'.'++<str>ver.minor,
+    select (ext.name, ver_str)
+);";
+
+fn connector(instance: Option<InstanceName>, options: &Options) -> anyhow::Result<Connector> {
+    let mut options = options.clone();
+    if instance.is_some() {
+        options.conn_options.instance = instance;
+    }
+    options.block_on_create_connector()
+}
 
-    rt.block_on(extensions)
+#[tokio::main(flavor = "current_thread")]
+async fn query_extensions(
+    connector: Connector,
+    query: &str,
+) -> Result<Vec<ExtensionInfo>, anyhow::Error> {
+    connector.run_single_query::<ExtensionInfo>(query).await
 }
 
-fn list(_: &ExtensionList, options: &Options) -> Result<(), anyhow::Error> {
-    let extensions = get_extensions(options)?;
+/// Migrations created from schema files, as opposed to ones recorded for
+/// DDL statements or in dev mode
+const HAS_SCHEMA_MIGRATIONS: &str = "\
+    SELECT EXISTS (SELECT schema::Migration FILTER NOT EXISTS .generated_by)";
+/// Servers before 3.0 have no `generated_by`, all migrations there come
+/// from schema files
+const HAS_MIGRATIONS: &str = "SELECT EXISTS schema::Migration";
+
+/// Runs extension DDL on a branch which isn't managed by migrations.
+///
+/// On a branch with migration history the DDL would make the schema differ
+/// from the schema files, so the next `migrate` would fail. Such branches
+/// must get the extension through `using extension` in the schema.
+#[tokio::main(flavor = "current_thread")]
+async fn execute_ddl(connector: Connector, ddl: &str, schema_line: &str) -> anyhow::Result<()> {
+    let mut conn = connector.connect().await?;
+    let has_generated_by = conn.get_version().await?.specific() >= "3.0-alpha.1".parse().unwrap();
+    let query = if has_generated_by {
+        HAS_SCHEMA_MIGRATIONS
+    } else {
+        HAS_MIGRATIONS
+    };
+    let managed: bool = conn.query_required_single(query, &()).await?;
+    if managed {
+        return Err(anyhow::anyhow!(
+            "Branch {:?} has migration history, the extension \
+             must be changed through a migration.",
+            conn.database()
+        ))
+        .with_hint(|| {
+            format!(
+                "{schema_line}, then run `{BRANDING_CLI_CMD} migration create` \
+                 and `{BRANDING_CLI_CMD} migrate`"
+            )
+        })?;
+    }
+    conn.execute(ddl, &()).await?;
+    Ok(())
+}
 
+fn print_extensions(extensions: Vec<ExtensionInfo>) {
     let mut table = Table::new();
     table.set_format(*table::FORMAT);
     table.set_titles(row!["Name", "Version"]);
@@ -155,12 +216,33 @@ fn list(_: &ExtensionList, options: &Options) -> Result<(), anyhow::Error> {
         table.add_row(row![name, version]);
     }
     table.printstd();
+}
 
+fn list(cmd: &ExtensionList, options: &Options) -> Result<(), anyhow::Error> {
+    let query = match instance_arg(&None, &cmd.instance) {
+        Ok(InstanceName::Cloud { .. }) => ENABLED_QUERY,
+        // if remote or local instance, connect and query extension packages
+        _ => PACKAGES_QUERY,
+    };
+    let extensions = query_extensions(connector(cmd.instance.clone(), options)?, query)?;
+    print_extensions(extensions);
     Ok(())
 }
 
-fn uninstall(cmd: &ExtensionUninstall, _options: &Options) -> Result<(), anyhow::Error> {
-    let inst = get_local_instance(&cmd.instance)?;
+fn uninstall(cmd: &ExtensionUninstall, options: &Options) -> Result<(), anyhow::Error> {
+    let inst = match get_instance(&cmd.instance)? {
+        Target::Local(inst) => inst,
+        Target::Cloud(name) => {
+            let ddl = format!("drop extension {};", quote_name(&cmd.extension));
+            let schema_line = format!(
+                "Remove `using extension {};` from the schema",
+                cmd.extension
+            );
+            execute_ddl(connector(Some(name.clone()), options)?, &ddl, &schema_line)?;
+            print::success!("Extension '{}' disabled in {name}.", cmd.extension);
+            return Ok(());
+        }
+    };
 
     if cfg!(windows) {
         return windows::extension_uninstall(cmd, inst.name);
@@ -174,8 +256,32 @@ fn uninstall(cmd: &ExtensionUninstall, _options: &Options) -> Result<(), anyhow:
     Ok(())
 }
 
-fn install(cmd: &ExtensionInstall, _options: &Options) -> Result<(), anyhow::Error> {
-    let inst = get_local_instance(&cmd.instance)?;
+fn install(cmd: &ExtensionInstall, options: &Options) -> Result<(), anyhow::Error> {
+    let inst = match get_instance(&cmd.instance)? {
+        Target::Local(inst) => inst,
+        Target::Cloud(name) => {
+            let connector = connector(Some(name.clone()), options)?;
+            let available = query_extensions(connector.clone(), PACKAGES_QUERY)?;
+            if !available.iter().any(|(ext, _)| ext == &cmd.extension) {
+                return Err(anyhow::anyhow!(
+                    "Extension '{}' is not available in {name}.",
+                    cmd.extension
+                ))
+                .with_hint(|| {
+                    format!(
+                        "run `{} extension list-available -I {name}` \
+                         to see available extensions",
+                        BRANDING_CLI_CMD
+                    )
+                })?;
+            }
+            let ddl = format!("create extension {};", quote_name(&cmd.extension));
+            let schema_line = format!("Add `using extension {};` to the schema", cmd.extension);
+            execute_ddl(connector, &ddl, &schema_line)?;
+            print::success!("Extension '{}' enabled in {name}.", cmd.extension);
+            return Ok(());
+        }
+    };
 
     if cfg!(windows) {
         return windows::extension_install(cmd, inst.name);
@@ -217,6 +323,38 @@ fn install(cmd: &ExtensionInstall, _options: &Options) -> Result<(), anyhow::Err
     Ok(())
 }
 
+/// Installs packages of `extensions` which are missing in the instance.
+///
+/// Returns the names of extensions that were installed.
+pub fn install_missing(
+    instance: &InstanceName,
+    extensions: &[String],
+    options: &Options,
+) -> Result<Vec<String>, anyhow::Error> {
+    let present = query_extensions(connector(Some(instance.clone()), options)?, PACKAGES_QUERY)?;
+    let mut installed = Vec::new();
+    for extension in extensions {
+        if present.iter().any(|(name, _)| name == extension) {
+            continue;
+        }
+        if let InstanceName::Cloud { .. } = instance {
+            anyhow::bail!("Extension '{extension}' is not available in {instance}.");
+        }
+        install(
+            &ExtensionInstall {
+                instance: Some(instance.clone()),
+                extension: extension.clone(),
+                channel: None,
+                slot: None,
+                reinstall: false,
+            },
+            options,
+        )?;
+        installed.push(extension.clone());
+    }
+    Ok(installed)
+}
+
 fn run_extension_loader(
     instance: &InstanceInfo,
     command: Option<impl AsRef<OsStr>>,
@@ -239,8 +377,8 @@ fn run_extension_loader(
         .with_context(|| format!("Failed to execute {}", ext_path.display()))?;
 
     if !output.status.success() {
-        eprintln!("STDOUT:\n{}", String::from_utf8_lossy(&output.stdout));
-        eprintln!("STDERR:\n{}", String::from_utf8_lossy(&output.stderr));
+        msg!("STDOUT:\n{}", String::from_utf8_lossy(&output.stdout));
+        msg!("STDERR:\n{}", String::from_utf8_lossy(&output.stderr));
         return Err(anyhow::anyhow!(
             "Extension installation failed with exit code: {}",
             output.status
@@ -253,8 +391,16 @@ fn run_extension_loader(
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-fn list_available(list: &ExtensionListAvailable, _options: &Options) -> Result<(), anyhow::Error> {
-    let inst = get_local_instance(&list.instance)?;
+fn list_available(list: &ExtensionListAvailable, options: &Options) -> Result<(), anyhow::Error> {
+    let inst = match get_instance(&list.instance)? {
+        Target::Local(inst) => inst,
+        Target::Cloud(name) => {
+            // Cloud instances provide all extensions they support
+            let extensions = query_extensions(connector(Some(name), options)?, PACKAGES_QUERY)?;
+            print_extensions(extensions);
+            return Ok(());
+        }
+    };
 
     let version = inst.get_version()?.specific();
     let channel = list.channel.unwrap_or(Channel::from_version(&version)?);
@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use anyhow::Context;
+use edgedb_cli_derive::IntoArgs;
+use fs_err as fs;
+use gel_tokio::credentials::Credentials;
+
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::commands::ExitCode;
+use crate::credentials;
+use crate::hint::HintExt;
+use crate::platform::{self, tmp_file_path};
+use crate::portable::exit_codes;
+use crate::portable::instance::control;
+use crate::portable::instance::create;
+use crate::portable::instance::status::{instance_status, Service};
+use crate::portable::local::{allocate_port, copy_dir, write_json};
+use crate::portable::local::{InstanceInfo, Paths};
+use crate::portable::options::InstanceName;
+use crate::portable::windows;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+
+pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    let source = match &cmd.source {
+        InstanceName::Local(name) => name.clone(),
+        InstanceName::Cloud { .. } => {
+            print::error!("Cloning {BRANDING_CLOUD} instances is not yet supported.");
+            return Err(ExitCode::new(1))?;
+        }
+    };
+    let target = match &cmd.target {
+        InstanceName::Local(name) => name.clone(),
+        InstanceName::Cloud { .. } => {
+            return Err(
+                anyhow::anyhow!("cannot clone into a {BRANDING_CLOUD} instance")
+                    .hint("Specify a name of a new local instance.")
+                    .into(),
+            );
+        }
+    };
+    if source == target {
+        anyhow::bail!("Cannot clone instance {source:?} into itself.");
+    }
+    if cfg!(windows) {
+        return windows::clone(cmd, &target);
+    }
+
+    let source_paths = Paths::get(&source)?;
+    let source_info = InstanceInfo::read(&source)?;
+    if source_paths.upgrade_marker.exists() {
+        anyhow::bail!("Instance {source:?} is being upgraded, cannot clone it.");
+    }
+    // keep a clone of an instance with a custom data directory on the same disk
+    let target_paths = match (&source_paths.data_link, source_paths.data_dir.parent()) {
+        (Some(_), Some(parent)) => Paths::with_data_dir(&target, &parent.join(&target))?,
+        _ => Paths::get(&target)?,
+    };
+    target_paths
+        .check_exists()
+        .with_context(|| format!("instance {target:?} detected"))
+        .hint("Choose another name for the new instance.")?;
+
+    let running = matches!(
+        instance_status(&source)?.service,
+        Service::Ready | Service::Running { .. }
+    );
+    if running {
+        if !cmd.non_interactive {
+            let q = question::Confirm::new(format!(
+                "Instance {source:?} is running. It will be stopped while \
+                 its data is copied and started again. Continue?"
+            ));
+            if !q.ask()? {
+                print::error!("Canceled.");
+                return Err(ExitCode::new(exit_codes::NOT_CONFIRMED))?;
+            }
+        }
+        msg!("Stopping {}...", source.emphasize());
+        control::do_stop(&source)?;
+    }
+
+    msg!("Copying data of {}...", source.emphasize());
+    let copied = copy_data(&source_paths.data_dir, &target_paths.data_dir);
+    if running {
+        msg!("Starting {}...", source.emphasize());
+        if let Err(e) = control::do_restart(&source_info) {
+            print::error!("Cannot start instance {source:?}: {e:#}");
+        }
+    }
+    copied?;
+
+    let port = cmd.port.map(Ok).unwrap_or_else(|| allocate_port(&target))?;
+    let info = InstanceInfo {
+        name: target.clone(),
+        port,
+        data_dir: target_paths
+            .data_link
+            .as_ref()
+            .map(|_| target_paths.data_dir.clone()),
+        ..source_info
+    };
+    write_json(
+        &target_paths.data_dir.join("instance_info.json"),
+        "metadata",
+        &info,
+    )?;
+    if let Some(link) = &target_paths.data_link {
+        platform::symlink_dir(&target_paths.data_dir, link)
+            .with_context(|| format!("linking {:?} -> {:?}", link, target_paths.data_dir))?;
+    }
+
+    let mut creds = read_credentials(&source_paths.credentials)?;
+    creds.port = port;
+    credentials::write_file(&target_paths.credentials, &creds)?;
+
+    if windows::is_wrapped() {
+        // service is created by the windows side
+        return Ok(());
+    }
+    if let Err(e) = create::create_service(&info) {
+        log::warn!("Error running {BRANDING} as a service: {e:#}");
+        print::warn!(
+            "{BRANDING} will not start on next login. \
+             Trying to start database in the background..."
+        );
+        control::do_start(&info)?;
+    }
+
+    print::success_msg("Cloned", format!("{source} into {target}"));
+    msg!("To connect to the new instance run:");
+    msg!("  {BRANDING_CLI_CMD} -I {target}");
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn read_credentials(path: &Path) -> anyhow::Result<Credentials> {
+    credentials::read(path)
+        .await
+        .with_context(|| format!("cannot read credentials {path:?}"))
+}
+
+/// Copies the data directory into a temporary directory first, so that
+/// an interrupted copy doesn't leave a half-copied instance behind
+fn copy_data(source: &Path, target: &Path) -> anyhow::Result<()> {
+    let tmp = tmp_file_path(target);
+    if tmp.exists() {
+        fs::remove_dir_all(&tmp)?;
+    }
+    copy_dir(source, &tmp).with_context(|| format!("cannot copy {source:?} to {tmp:?}"))?;
+    fs::rename(&tmp, target)?;
+    Ok(())
+}
+
+#[derive(clap::Args, IntoArgs, Debug, Clone)]
+pub struct Command {
+    /// Name of the local instance to clone.
+    #[arg(value_hint=clap::ValueHint::Other)] // TODO complete instance name
+    pub source: InstanceName,
+
+    /// Name of the new instance.
+    #[arg(value_hint=clap::ValueHint::Other)]
+    pub target: InstanceName,
+
+    /// Port of the new instance. Allocated automatically by default.
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Do not ask for confirmation before stopping the source instance.
+    #[arg(long)]
+    pub non_interactive: bool,
+}
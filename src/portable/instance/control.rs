@@ -1,6 +1,7 @@
 use std::fs;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::Context;
 use edgedb_cli_derive::IntoArgs;
@@ -12,6 +13,7 @@ use crate::commands::ExitCode;
 use crate::credentials;
 use crate::hint::HintExt;
 use crate::platform::current_exe;
+use crate::portable::instance::health;
 use crate::portable::local::{lock_file, open_lock, runstate_dir, InstanceInfo};
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::portable::ver;
@@ -55,6 +57,16 @@ pub struct Start {
     #[arg(value_parser=["systemd", "launchctl", "edgedb-cli"])]
     #[arg(conflicts_with = "auto_restart")]
     pub managed_by: Option<String>,
+
+    /// Wait until the instance accepts connections and executes queries,
+    /// rather than returning once the service manager starts it.
+    #[arg(long, alias = "wait-until-healthy")]
+    #[arg(conflicts_with_all = ["foreground", "managed_by"])]
+    pub wait: bool,
+
+    /// Maximum time to wait for the instance to become healthy, in seconds.
+    #[arg(long, default_value = "60", value_name = "SECONDS")]
+    pub wait_timeout: u64,
 }
 
 #[derive(clap::Args, IntoArgs, Debug, Clone)]
@@ -110,7 +122,7 @@ fn supervisor_start(inst: &InstanceInfo) -> anyhow::Result<()> {
     }
 }
 
-fn daemon_start(instance: &str) -> anyhow::Result<()> {
+pub fn daemon_start(instance: &str) -> anyhow::Result<()> {
     if cfg!(windows) {
         windows::daemon_start(instance)
     } else {
@@ -208,7 +220,7 @@ pub fn detect_supervisor(name: &str) -> bool {
     }
 }
 
-fn pid_file_path(instance: &str) -> anyhow::Result<PathBuf> {
+pub fn pid_file_path(instance: &str) -> anyhow::Result<PathBuf> {
     Ok(runstate_dir(instance)?.join("edgedb.pid"))
 }
 
@@ -301,7 +313,8 @@ pub fn start(options: &Start) -> anyhow::Result<()> {
     let name = match instance_arg(&options.name, &options.instance)? {
         InstanceName::Local(name) => {
             if cfg!(windows) {
-                return windows::start(options, &name);
+                windows::start(options, &name)?;
+                return wait_started(options, &name);
             } else {
                 name
             }
@@ -405,8 +418,19 @@ pub fn start(options: &Start) -> anyhow::Result<()> {
             Ok(res?)
         }
     } else {
-        do_start(&meta)
+        do_start(&meta)?;
+        wait_started(options, &name)
+    }
+}
+
+fn wait_started(options: &Start, name: &str) -> anyhow::Result<()> {
+    if options.wait {
+        health::wait_until_healthy(
+            &InstanceName::Local(name.into()),
+            Duration::from_secs(options.wait_timeout),
+        )?;
     }
+    Ok(())
 }
 
 fn supervisor_stop(name: &str) -> anyhow::Result<()> {
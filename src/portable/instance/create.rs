@@ -1,4 +1,6 @@
+use std::env;
 use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context;
@@ -101,7 +103,21 @@ pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()>
         ))?;
     }
 
-    let paths = Paths::get(&name)?;
+    let data_dir = match &cmd.data_dir {
+        Some(_) if cfg!(windows) => {
+            anyhow::bail!("The `--data-dir` option is not supported on Windows.");
+        }
+        Some(dir) => Some(
+            env::current_dir()
+                .context("cannot get current directory")?
+                .join(dir),
+        ),
+        None => None,
+    };
+    let paths = match &data_dir {
+        Some(dir) => Paths::with_data_dir(&name, dir)?,
+        None => Paths::get(&name)?,
+    };
     paths
         .check_exists()
         .with_context(|| format!("instance {name:?} detected"))
@@ -120,6 +136,7 @@ pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()>
             name: name.clone(),
             installation: None,
             port,
+            data_dir: None,
         }
     } else {
         let (query, _) = Query::from_options(
@@ -138,6 +155,7 @@ pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()>
             name: name.clone(),
             installation: Some(inst),
             port,
+            data_dir,
         };
         bootstrap(
             &paths,
@@ -171,6 +189,8 @@ pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()>
                 foreground: false,
                 auto_restart: false,
                 managed_by: None,
+                wait: false,
+                wait_timeout: 60,
             })?;
         }
     }
@@ -203,6 +223,10 @@ pub struct Command {
     /// Indicate port for instance to create.
     #[arg(long)]
     pub port: Option<u16>,
+    /// Store instance data in a custom directory (e.g. on a larger disk)
+    /// instead of the default location. Not supported on Windows.
+    #[arg(long, value_hint=clap::ValueHint::DirPath)]
+    pub data_dir: Option<PathBuf>,
 
     #[command(flatten)]
     pub cloud_params: CloudInstanceParams,
@@ -581,6 +605,13 @@ pub fn bootstrap(
     write_json(&tmp_data.join("instance_info.json"), "metadata", &info)?;
     fs::rename(&tmp_data, &paths.data_dir)
         .with_context(|| format!("renaming {:?} -> {:?}", tmp_data, paths.data_dir))?;
+    if let Some(link) = &paths.data_link {
+        if let Some(parent) = link.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("creating {parent:?}"))?;
+        }
+        platform::symlink_dir(&paths.data_dir, link)
+            .with_context(|| format!("linking {:?} -> {:?}", link, paths.data_dir))?;
+    }
 
     let mut creds = Credentials::default();
     creds.port = info.port;
@@ -1,7 +1,35 @@
+use std::fs;
 use std::io::{stdout, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
 use url::Url;
 
+use edgeql_parser::helpers::{quote_name, quote_string};
+
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD, QUERY_TAG};
+use crate::commands::ExitCode;
+use crate::connect::Connection;
+use crate::credentials;
+use crate::hint::HintExt;
 use crate::options::{ConnectionOptions, Options};
+use crate::portable::instance::reset_password::generate_password;
+use crate::portable::instance::status::{instance_status, Service};
+use crate::portable::instance::{control, health};
+use crate::portable::local::{InstanceInfo, Paths};
+use crate::portable::options::{instance_arg, InstanceName};
+use crate::print::{self, msg, Highlight};
+
+const TLS_FILES: &[&str] = &["edbtlscert.pem", "edbprivkey.pem"];
+const RESTART_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub fn run(options: &Options, c: &Command) -> anyhow::Result<()> {
+    match &c.subcommand {
+        Some(Subcommand::Rotate(cmd)) => rotate(cmd),
+        None => show_credentials(options, c),
+    }
+}
 
 pub fn show_credentials(options: &Options, c: &Command) -> anyhow::Result<()> {
     use gel_tokio::credentials::TlsSecurity;
@@ -81,4 +109,183 @@ pub struct Command {
     /// Output a DSN with password in cleartext.
     #[arg(long)]
     pub insecure_dsn: bool,
+
+    #[command(subcommand)]
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    /// Generate a new password (and optionally a new TLS certificate) for
+    /// a local instance and update its credentials file.
+    Rotate(Rotate),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Rotate {
+    #[arg(from_global)]
+    pub instance: Option<InstanceName>,
+
+    /// Also regenerate the self-signed TLS certificate. Restarts the
+    /// instance.
+    #[arg(long)]
+    pub tls_cert: bool,
+
+    /// Do not print any messages, only indicate success by exit status.
+    #[arg(long)]
+    pub quiet: bool,
+}
+
+fn rotate(cmd: &Rotate) -> anyhow::Result<()> {
+    let name = match instance_arg(&None, &cmd.instance)? {
+        InstanceName::Local(_) if cfg!(windows) => {
+            print::error!("Credentials rotation is not yet supported on Windows.");
+            return Err(ExitCode::new(1))?;
+        }
+        InstanceName::Local(name) => name,
+        InstanceName::Cloud { .. } => {
+            print::error!("This operation is not yet supported on {BRANDING_CLOUD} instances.");
+            return Err(ExitCode::new(1))?;
+        }
+    };
+    let inst = InstanceInfo::read(&name)?;
+    let paths = Paths::get(&name)?;
+    if !matches!(
+        instance_status(&name)?.service,
+        Service::Ready | Service::Running { .. }
+    ) {
+        return Err(anyhow::anyhow!("Instance {name:?} is not running.")
+            .with_hint(|| format!("Start it with `{BRANDING_CLI_CMD} instance start -I {name}`."))
+            .into());
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut creds = runtime
+        .block_on(credentials::read(&paths.credentials))
+        .with_context(|| format!("cannot read credentials {:?}", paths.credentials))?;
+
+    let mut changes = Vec::new();
+    let old_cert = creds.tls_ca.clone();
+    let old_password = creds.password.clone();
+    if cmd.tls_cert {
+        if !cmd.quiet {
+            msg!("Regenerating TLS certificate of {}...", name.emphasize());
+        }
+        creds.tls_ca = Some(regenerate_cert(&inst, &paths.data_dir)?);
+        changes.push(("TLS certificate", "regenerated, instance restarted".into()));
+    }
+
+    let password = generate_password();
+    if let Err(e) = change_password(&inst, &creds.user, &password) {
+        if cmd.tls_cert {
+            log::warn!("Password change failed, restoring TLS certificate");
+            restore_cert(&inst, &paths.data_dir)?;
+        }
+        return Err(e);
+    }
+    creds.password = Some(password);
+    changes.push(("Password", "regenerated".into()));
+
+    if let Err(e) = runtime.block_on(credentials::write(&paths.credentials, &creds)) {
+        // the credentials file still has the old password and certificate
+        log::warn!("Cannot save credentials, restoring the previous ones");
+        match &old_password {
+            Some(old_password) => change_password(&inst, &creds.user, old_password)?,
+            None => log::warn!("Previous password is unknown, it cannot be restored"),
+        }
+        if cmd.tls_cert {
+            restore_cert(&inst, &paths.data_dir)?;
+        }
+        return Err(e);
+    }
+    if cmd.tls_cert {
+        remove_backup_cert(&paths.data_dir);
+    }
+    if !cmd.quiet {
+        print::success_msg(
+            "Credentials rotated and saved to",
+            paths.credentials.display(),
+        );
+        if old_cert.is_some() && cmd.tls_cert {
+            msg!("Clients that pin the old TLS certificate need to be updated.");
+        }
+        crate::table::settings(&changes);
+    }
+    Ok(())
+}
+
+fn change_password(inst: &InstanceInfo, user: &str, password: &str) -> anyhow::Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            let conn_params = inst.admin_conn_params()?.constrained_build()?;
+            let mut cli = Connection::connect(&conn_params, QUERY_TAG).await?;
+            cli.execute(
+                &format!(
+                    "ALTER ROLE {name} {{ SET password := {password}; }}",
+                    name = quote_name(user),
+                    password = quote_string(password)
+                ),
+                &(),
+            )
+            .await?;
+            Ok::<_, anyhow::Error>(())
+        })
+}
+
+fn backup_name(file: &str) -> String {
+    format!("{file}.old")
+}
+
+/// Moves the certificate away and restarts the server, which generates a
+/// new self-signed one in the data directory
+fn regenerate_cert(inst: &InstanceInfo, data_dir: &Path) -> anyhow::Result<String> {
+    for file in TLS_FILES {
+        let path = data_dir.join(file);
+        fs::rename(&path, data_dir.join(backup_name(file)))
+            .with_context(|| format!("cannot move {path:?}"))?;
+    }
+    if let Err(e) = restart(inst) {
+        restore_cert(inst, data_dir)?;
+        return Err(e);
+    }
+    let cert_path = data_dir.join("edbtlscert.pem");
+    fs::read_to_string(&cert_path)
+        .with_context(|| format!("cannot read certificate: {cert_path:?}"))
+}
+
+fn restore_cert(inst: &InstanceInfo, data_dir: &Path) -> anyhow::Result<()> {
+    for file in TLS_FILES {
+        let backup = data_dir.join(backup_name(file));
+        fs::rename(&backup, data_dir.join(file))
+            .with_context(|| format!("cannot restore {backup:?}"))?;
+    }
+    restart(inst)
+}
+
+/// Restarts the server and waits until it accepts connections, which is
+/// when the certificate is written and the password can be changed.
+/// The admin socket is used, as the credentials don't have the new
+/// certificate yet.
+fn restart(inst: &InstanceInfo) -> anyhow::Result<()> {
+    control::do_restart(inst)?;
+    let config = inst.admin_conn_params()?.constrained_build()?;
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(health::poll(RESTART_TIMEOUT, || {
+            health::probe_config(&config)
+        }))
+        .map_err(|e| anyhow::anyhow!("instance {:?} is not ready after restart: {e}", inst.name))
+}
+
+fn remove_backup_cert(data_dir: &Path) {
+    for file in TLS_FILES {
+        let backup = data_dir.join(backup_name(file));
+        fs::remove_file(&backup)
+            .map_err(|e| log::warn!("Cannot remove {backup:?}: {e}"))
+            .ok();
+    }
 }
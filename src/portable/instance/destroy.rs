@@ -1,23 +1,34 @@
-use std::path::PathBuf;
+use std::env;
+use std::io::stdin;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
 
 use edgedb_cli_derive::IntoArgs;
 use fs_err as fs;
+use is_terminal::IsTerminal;
 
 use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
 use crate::commands::ExitCode;
+use crate::credentials;
+use crate::keychain::{self, Account};
 use crate::options::{CloudOptions, Options};
 use crate::portable::exit_codes;
 use crate::portable::instance::control;
-use crate::portable::local;
+use crate::portable::instance::upgrade;
+use crate::portable::local::{self, InstanceInfo};
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::portable::project;
 use crate::portable::windows;
-use crate::print::{self, msg, Highlight};
+use crate::print::{self, msg, AsRelativeToCurrentDir, Highlight};
 use crate::question;
 
 pub fn run(options: &Command, opts: &Options) -> anyhow::Result<()> {
     let name = instance_arg(&options.name, &options.instance)?;
     let name_str = name.to_string();
+    if !options.force && !options.non_interactive && stdin().is_terminal() {
+        resolve_projects(&name_str)?;
+    }
     with_projects(&name_str, options.force, print_warning, || {
         if !options.force && !options.non_interactive {
             let q = question::Confirm::new_dangerous(format!(
@@ -28,6 +39,9 @@ pub fn run(options: &Command, opts: &Options) -> anyhow::Result<()> {
                 return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
             }
         }
+        if options.backup_first {
+            backup(options, &name)?;
+        }
         match do_destroy(options, opts, &name) {
             Ok(()) => Ok(()),
             Err(e) if e.is::<InstanceNotFound>() => {
@@ -71,6 +85,17 @@ pub struct Command {
     /// Do not ask questions. Assume user wants to delete instance.
     #[arg(long)]
     pub non_interactive: bool,
+
+    /// Dump all branches of the instance before destroying it, so that
+    /// it can be restored later. Only supported for local instances.
+    #[arg(long)]
+    pub backup_first: bool,
+
+    /// Directory to write the backup to (default: current directory).
+    /// The dump is placed into a timestamped subdirectory.
+    #[arg(long, requires = "backup_first")]
+    #[arg(value_hint=clap::ValueHint::DirPath)]
+    pub backup_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -79,8 +104,80 @@ pub struct InstanceNotFound(#[source] pub anyhow::Error);
 
 pub fn print_warning(name: &str, project_dirs: &[PathBuf]) {
     project::print_instance_in_use_warning(name, project_dirs);
-    eprintln!("If you really want to destroy the instance, run:");
-    eprintln!("  {BRANDING_CLI_CMD} instance destroy -I {name:?} --force");
+    msg!("If you really want to destroy the instance, run:");
+    msg!("  {BRANDING_CLI_CMD} instance destroy -I {name:?} --force");
+}
+
+#[derive(Clone, Copy)]
+enum Resolution {
+    Relink,
+    Unlink,
+    Skip,
+}
+
+/// Interactively relinks or unlinks the projects using the instance,
+/// so that it can be destroyed without `--force`.
+fn resolve_projects(name: &str) -> anyhow::Result<()> {
+    let project_dirs = project::find_project_dirs_by_instance(name)?;
+    if project_dirs.is_empty() {
+        return Ok(());
+    }
+    project::print_instance_in_use_warning(name, &project_dirs);
+    for dir in &project_dirs {
+        let path = match project::read_project_path(dir) {
+            Ok(path) => path,
+            Err(_) => dir.clone(),
+        };
+        let mut q = question::Numeric::new(format!(
+            "What do you want to do with project {}?",
+            path.as_relative().display()
+        ));
+        q.option("Link it to another instance", Resolution::Relink);
+        q.option("Unlink it", Resolution::Unlink);
+        q.option(
+            "Keep it linked (instance will not be destroyed)",
+            Resolution::Skip,
+        );
+        match q.ask()? {
+            Resolution::Relink => relink_project(name, dir, &path)?,
+            Resolution::Unlink => {
+                msg!("Unlinking {}", path.as_relative().display());
+                fs::remove_dir_all(dir)?;
+            }
+            Resolution::Skip => {}
+        }
+    }
+    Ok(())
+}
+
+fn relink_project(old_name: &str, stash_dir: &Path, path: &Path) -> anyhow::Result<()> {
+    loop {
+        let new_name = question::String::new("Instance name to link the project to").ask()?;
+        let instance = match InstanceName::from_str(&new_name) {
+            Ok(instance) => instance,
+            Err(e) => {
+                print::error!("{e}");
+                continue;
+            }
+        };
+        if instance.to_string() == old_name {
+            print::error!("Project is already linked to {old_name:?}");
+            continue;
+        }
+        if let InstanceName::Local(local_name) = &instance {
+            if !credentials::all_instance_names()?.contains(local_name) {
+                print::error!("Instance {local_name:?} does not exist");
+                continue;
+            }
+        }
+        project::relink_stash_dir(stash_dir, &instance)?;
+        msg!(
+            "Project {} is now linked to {}.",
+            path.as_relative().display(),
+            instance.to_string().emphasize()
+        );
+        return Ok(());
+    }
 }
 
 pub fn with_projects(
@@ -97,14 +194,47 @@ pub fn with_projects(
     f()?;
     for dir in project_dirs {
         match project::read_project_path(&dir) {
-            Ok(path) => eprintln!("Unlinking {}", path.display()),
-            Err(_) => eprintln!("Cleaning {}", dir.display()),
+            Ok(path) => msg!("Unlinking {}", path.display()),
+            Err(_) => msg!("Cleaning {}", dir.display()),
         };
         fs::remove_dir_all(&dir)?;
     }
     Ok(())
 }
 
+/// Dumps the instance into a timestamped directory and prints how to
+/// restore it
+fn backup(options: &Command, name: &InstanceName) -> anyhow::Result<()> {
+    let InstanceName::Local(name) = name else {
+        anyhow::bail!("`--backup-first` is only supported for local instances");
+    };
+    if cfg!(windows) {
+        anyhow::bail!("`--backup-first` is not supported on Windows");
+    }
+    let Some(inst) = InstanceInfo::try_read(name)? else {
+        // reported as not found by `do_destroy`
+        return Ok(());
+    };
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now())
+        .to_string()
+        .replace(':', "");
+    let dir = match &options.backup_dir {
+        Some(dir) => dir.clone(),
+        None => env::current_dir()?,
+    };
+    let path = dir.join(format!("{name}-{timestamp}"));
+    upgrade::dump_and_stop(&inst, &path)?;
+    msg!(
+        "Instance {} is backed up to {}. To restore it, run:\n  \
+         {BRANDING_CLI_CMD} instance create {name}\n  \
+         {BRANDING_CLI_CMD} -I {name} restore --all {}",
+        name.emphasize(),
+        path.as_relative().display(),
+        path.as_relative().display(),
+    );
+    Ok(())
+}
+
 fn destroy_local(name: &str) -> anyhow::Result<()> {
     let paths = local::Paths::get(name)?;
     log::debug!("Paths {:?}", paths);
@@ -129,11 +259,19 @@ fn destroy_local(name: &str) -> anyhow::Result<()> {
         log::info!("Removing data directory {:?}", paths.data_dir);
         fs::remove_dir_all(&paths.data_dir)?;
     }
+    if let Some(link) = &paths.data_link {
+        if fs::symlink_metadata(link).is_ok() {
+            found = true;
+            log::info!("Removing data directory link {:?}", link);
+            fs::remove_file(link)?;
+        }
+    }
     if paths.credentials.exists() {
         found = true;
         log::info!("Removing credentials file {:?}", &paths.credentials);
         fs::remove_file(&paths.credentials)?;
     }
+    keychain::delete(&Account::Instance(name));
     for path in &paths.service_files {
         if path.exists() {
             found = true;
@@ -203,6 +341,8 @@ pub fn force_by_name(name: &InstanceName, options: &Options) -> anyhow::Result<(
             force: true,
             quiet: false,
             non_interactive: true,
+            backup_first: false,
+            backup_dir: None,
             cloud_opts: options.cloud_options.clone(),
         },
         options,
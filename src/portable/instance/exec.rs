@@ -0,0 +1,47 @@
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::hint::HintExt;
+use crate::portable::instance::control::get_server_cmd;
+use crate::portable::local::{open_lock, InstanceInfo};
+use crate::portable::options::{instance_arg, InstanceName};
+
+pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    let name = match instance_arg(&None, &cmd.instance)? {
+        InstanceName::Local(name) => name,
+        InstanceName::Cloud { .. } => {
+            anyhow::bail!("`instance exec` is not supported for {BRANDING_CLOUD} instances");
+        }
+    };
+    if cfg!(windows) {
+        anyhow::bail!("`instance exec` is not supported on Windows");
+    }
+    let meta = InstanceInfo::read(&name)?;
+    let lock = open_lock(&name)?;
+    if lock.try_read().is_err() {
+        return Err(anyhow::anyhow!("instance {name:?} is running")).with_hint(|| {
+            format!("stop it first with `{BRANDING_CLI_CMD} instance stop -I {name}`")
+        })?;
+    }
+    drop(lock);
+    log::info!(
+        "Running {BRANDING} server of instance {name:?} from {:?}",
+        meta.server_path()?
+    );
+    // Arguments given by the user come last, so they override the
+    // defaults of the instance (e.g. `--port`)
+    get_server_cmd(&meta, false)?
+        .env_default("EDGEDB_SERVER_LOG_LEVEL", "info")
+        .args(&cmd.args)
+        .no_proxy()
+        .run_and_exit()
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[arg(from_global)]
+    pub instance: Option<InstanceName>,
+
+    /// Arguments passed to the server after the instance's data directory,
+    /// runstate directory and port.
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
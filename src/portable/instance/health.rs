@@ -0,0 +1,92 @@
+//! Readiness probing of instances
+//!
+//! An instance is healthy once a connection can be established and a
+//! trivial query succeeds, which is stricter than the service manager
+//! reporting that the process is running.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use gel_tokio::{Builder, Config};
+use tokio::time::{sleep, timeout};
+
+use crate::branding::{BRANDING_CLI_CMD, QUERY_TAG};
+use crate::commands::ExitCode;
+use crate::connect::Connection;
+use crate::portable::exit_codes;
+use crate::portable::options::InstanceName;
+use crate::print::{self, msg, Highlight};
+
+const INITIAL_DELAY: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum WaitUntil {
+    /// Instance accepts connections and executes queries
+    Healthy,
+}
+
+/// Connects to the instance and runs `SELECT 1`
+pub async fn probe(name: &InstanceName) -> anyhow::Result<()> {
+    let mut builder = Builder::new();
+    builder.instance(&name.to_string())?;
+    probe_config(&builder.constrained_build()?).await
+}
+
+/// Connects using the config and runs `SELECT 1`
+pub async fn probe_config(config: &Config) -> anyhow::Result<()> {
+    let mut conn = Connection::connect(config, QUERY_TAG).await?;
+    conn.query_required_single::<i64, _>("SELECT 1", &())
+        .await?;
+    Ok(())
+}
+
+/// Runs the probe with exponential backoff until it succeeds. Returns the
+/// last probe error if it doesn't succeed within the limit.
+pub async fn poll<F, Fut>(limit: Duration, mut probe: F) -> Result<(), String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let deadline = Instant::now() + limit;
+    let mut delay = INITIAL_DELAY;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let err = match timeout(remaining.min(PROBE_TIMEOUT), probe()).await {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => format!("{e:#}"),
+            Err(_) => "connection timed out".into(),
+        };
+        log::debug!("Instance is not healthy yet: {err}");
+        if Instant::now() + delay >= deadline {
+            return Err(err);
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_DELAY);
+    }
+}
+
+/// Waits for the instance to become healthy, reporting the progress
+#[tokio::main(flavor = "current_thread")]
+pub async fn wait_until_healthy(name: &InstanceName, limit: Duration) -> anyhow::Result<()> {
+    msg!(
+        "Waiting for {} to become healthy...",
+        name.to_string().emphasize()
+    );
+    match poll(limit, || probe(name)).await {
+        Ok(()) => {
+            print::success_msg("Instance is healthy", name);
+            Ok(())
+        }
+        Err(err) => {
+            print::error!("Instance {name} is not healthy after {limit:?}: {err}");
+            msg!(
+                "  Hint: Check the server logs with \
+                 `{BRANDING_CLI_CMD} instance logs -I {name}`."
+            );
+            Err(ExitCode::new(exit_codes::NOT_HEALTHY).into())
+        }
+    }
+}
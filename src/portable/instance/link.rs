@@ -11,7 +11,7 @@ use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
 use rustls::{DigitallySignedStruct, SignatureScheme};
 
 use gel_errors::{ClientNoCredentialsError, Error, PasswordRequired};
-use gel_tokio::credentials::TlsSecurity;
+use gel_tokio::credentials::{Credentials, TlsSecurity};
 use gel_tokio::{tls, Client};
 use gel_tokio::{Builder, Config};
 use rustyline::error::ReadlineError;
@@ -25,7 +25,7 @@ use crate::options::{ConnectionOptions, Options};
 use crate::portable::local::is_valid_local_instance_name;
 use crate::portable::options::InstanceName;
 use crate::portable::ver::Build;
-use crate::print;
+use crate::print::{self, msg};
 use crate::question;
 use crate::tty_password;
 
@@ -41,67 +41,12 @@ pub fn run(cmd: &Link, opts: &Options) -> anyhow::Result<()> {
 
     let mut has_branch: bool = false;
     let config: Config = conn_params(cmd, opts, &mut has_branch)?;
-    let mut creds = config.as_credentials()?;
-    let root_cert_store = config.root_cert_store()?;
-    let inner = WebPkiServerVerifier::builder(Arc::new(root_cert_store)).build()?;
-    let verifier = Arc::new(InteractiveCertVerifier {
-        inner,
-        cert_out: Mutex::new(None),
-        tls_security: creds.tls_security,
-        system_ca_only: creds.tls_ca.is_none(),
-        non_interactive: cmd.non_interactive,
-        quiet: cmd.quiet,
-        trust_tls_cert: cmd.trust_tls_cert,
-    });
-    let mut config = config.with_cert_verifier(verifier.clone());
-    let mut connect_result = connect(&config);
-    if let Err(e) = connect_result {
-        if e.is::<PasswordRequired>() {
-            let password;
-
-            if opts.conn_options.password_from_stdin {
-                password = tty_password::read_stdin()?
-            } else if !cmd.non_interactive {
-                password = tty_password::read(format!(
-                    "Password for '{}': ",
-                    config.user().escape_default()
-                ))?;
-            } else {
-                return Err(e.into());
-            }
-
-            config = config.with_password(&password);
-            creds.password = Some(password);
-            if let Some(cert) = &*verifier.cert_out.lock().unwrap() {
-                let pem = pem::encode(&pem::Pem::new("CERTIFICATE", cert.to_vec()));
-                config = config.with_pem_certificates(&pem)?;
-            }
-            connect_result = Ok(connect(&config)?);
-        } else {
-            return Err(e.into());
-        }
-    }
-
-    let mut connection: Client = connect_result.unwrap();
-    let ver = get_server_version(&mut connection)?;
-
-    if !has_branch && opts.conn_options.branch.is_none() && opts.conn_options.database.is_none() {
-        config = config.with_database(&get_default_branch(&mut connection)?)?;
-
-        eprintln!(
-            "using the default {} '{}'",
-            if ver.specific().major >= 5 {
-                "branch"
-            } else {
-                "database"
-            },
-            config.database()
-        )
-    }
-
-    if let Some(cert) = &*verifier.cert_out.lock().unwrap() {
-        creds.tls_ca = Some(pem::encode(&pem::Pem::new("CERTIFICATE", cert.to_vec())));
-    }
+    let (config, creds) = if cmd.no_verify {
+        let creds = config.as_credentials()?;
+        (config, creds)
+    } else {
+        verify(cmd, opts, config, has_branch)?
+    };
 
     let (cred_path, instance_name) = match &cmd.name {
         Some(InstanceName::Local(name)) => (credentials::path(name)?, name.clone()),
@@ -110,7 +55,7 @@ pub fn run(cmd: &Link, opts: &Options) -> anyhow::Result<()> {
             let default = gen_default_instance_name(config.display_addr());
             if cmd.non_interactive {
                 if !cmd.quiet {
-                    eprintln!("Using generated instance name: {}", &default);
+                    msg!("Using generated instance name: {}", &default);
                 }
                 (credentials::path(&default)?, default)
             } else {
@@ -156,7 +101,7 @@ pub fn run(cmd: &Link, opts: &Options) -> anyhow::Result<()> {
         if print::use_color() {
             msg = format!("{}", msg.bold().light_green());
         }
-        eprintln!(
+        msg!(
             "{} To connect run:\
             \n  {BRANDING_CLI_CMD} -I {}",
             msg,
@@ -166,6 +111,76 @@ pub fn run(cmd: &Link, opts: &Options) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn verify(
+    cmd: &Link,
+    opts: &Options,
+    config: Config,
+    has_branch: bool,
+) -> anyhow::Result<(Config, Credentials)> {
+    let mut creds = config.as_credentials()?;
+    let root_cert_store = config.root_cert_store()?;
+    let inner = WebPkiServerVerifier::builder(Arc::new(root_cert_store)).build()?;
+    let verifier = Arc::new(InteractiveCertVerifier {
+        inner,
+        cert_out: Mutex::new(None),
+        tls_security: creds.tls_security,
+        system_ca_only: creds.tls_ca.is_none(),
+        non_interactive: cmd.non_interactive,
+        quiet: cmd.quiet,
+        trust_tls_cert: cmd.trust_tls_cert,
+    });
+    let mut config = config.with_cert_verifier(verifier.clone());
+    let mut connect_result = connect(&config);
+    if let Err(e) = connect_result {
+        if e.is::<PasswordRequired>() {
+            let password;
+
+            if opts.conn_options.password_from_stdin {
+                password = tty_password::read_stdin()?
+            } else if !cmd.non_interactive {
+                password = tty_password::read(format!(
+                    "Password for '{}': ",
+                    config.user().escape_default()
+                ))?;
+            } else {
+                return Err(e.into());
+            }
+
+            config = config.with_password(&password);
+            creds.password = Some(password);
+            if let Some(cert) = &*verifier.cert_out.lock().unwrap() {
+                let pem = pem::encode(&pem::Pem::new("CERTIFICATE", cert.to_vec()));
+                config = config.with_pem_certificates(&pem)?;
+            }
+            connect_result = Ok(connect(&config)?);
+        } else {
+            return Err(e.into());
+        }
+    }
+
+    let mut connection: Client = connect_result.unwrap();
+    let ver = get_server_version(&mut connection)?;
+
+    if !has_branch && opts.conn_options.branch.is_none() && opts.conn_options.database.is_none() {
+        config = config.with_database(&get_default_branch(&mut connection)?)?;
+
+        msg!(
+            "using the default {} '{}'",
+            if ver.specific().major >= 5 {
+                "branch"
+            } else {
+                "database"
+            },
+            config.database()
+        )
+    }
+
+    if let Some(cert) = &*verifier.cert_out.lock().unwrap() {
+        creds.tls_ca = Some(pem::encode(&pem::Pem::new("CERTIFICATE", cert.to_vec())));
+    }
+    Ok((config, creds))
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct Link {
     #[command(flatten)]
@@ -194,6 +209,11 @@ pub struct Link {
     /// Overwrite existing credential file if any.
     #[arg(long)]
     pub overwrite: bool,
+
+    /// Save credentials without connecting to the server. Only
+    /// allowed together with `--dsn` or `--credentials-file`.
+    #[arg(long)]
+    pub no_verify: bool,
 }
 
 #[derive(Debug)]
@@ -376,8 +396,16 @@ async fn prompt_conn_params(
     if link.non_interactive && options.password {
         anyhow::bail!("--password and --non-interactive are mutually exclusive.")
     }
+    let imported = options.dsn.is_some() || options.credentials_file.is_some();
+    if link.no_verify && !imported {
+        return Err(
+            anyhow::anyhow!("`--no-verify` requires connection parameters")
+                .hint("Specify `--dsn` or `--credentials-file` to import.")
+                .into(),
+        );
+    }
 
-    if link.non_interactive {
+    if link.non_interactive || imported {
         let config = match builder.build_env().await {
             Ok(config) => config,
             Err(e) if e.is::<ClientNoCredentialsError>() => {
@@ -393,8 +421,8 @@ async fn prompt_conn_params(
             }
             Err(e) => return Err(e)?,
         };
-        if !link.quiet {
-            eprintln!(
+        if !link.quiet && !link.no_verify {
+            msg!(
                 "Authenticating to edgedb://{}@{}/{}",
                 config.user(),
                 config.display_addr(),
@@ -402,7 +430,7 @@ async fn prompt_conn_params(
             );
         }
         Ok(config)
-    } else if options.dsn.is_none() {
+    } else {
         let (_, config, _) = builder.build_no_fail().await;
         if options.host.is_none() {
             builder.host(
@@ -440,8 +468,6 @@ async fn prompt_conn_params(
             };
         }
 
-        Ok(builder.build_env().await?)
-    } else {
         Ok(builder.build_env().await?)
     }
 }
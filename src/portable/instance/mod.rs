@@ -1,12 +1,17 @@
 pub mod backup;
+pub mod clone;
 pub mod control;
 pub mod create;
 pub mod credentials;
 pub mod destroy;
+pub mod exec;
+pub mod health;
 pub mod link;
 pub mod reset_password;
 pub mod resize;
+pub mod restore_from_cloud;
 pub mod revert;
+pub mod side_by_side;
 pub mod status;
 pub mod unlink;
 pub mod upgrade;
@@ -22,6 +27,7 @@ pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
 
     match &cmd.subcommand {
         Create(c) => create::run(c, options),
+        Clone(c) => clone::run(c),
         Destroy(c) => destroy::run(c, options),
         ResetPassword(c) => reset_password::run(c),
         Link(c) => link::run(c, options),
@@ -31,6 +37,7 @@ pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
         Backup(c) => backup::backup(c, options),
         Restore(c) => backup::restore(c, options),
         ListBackups(c) => backup::list(c, options),
+        RestoreFromCloud(c) => restore_from_cloud::run(c, options),
         Upgrade(c) => upgrade::run(c, options),
         Start(c) => control::start(c),
         Stop(c) => control::stop(c),
@@ -42,7 +49,8 @@ pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
         Unlink(c) => unlink::run(c),
         Status(c) if cfg!(windows) => windows::status(c),
         Status(c) => status::run(c, options),
-        Credentials(c) => credentials::show_credentials(options, c),
+        Credentials(c) => credentials::run(options, c),
+        Exec(c) => exec::run(c),
     }
 }
 
@@ -64,6 +72,11 @@ pub struct Command {
 pub enum Subcommands {
     /// Initialize a new [`BRANDING`] instance.
     Create(create::Command),
+    /// Copy a local instance into a new one, including its data and
+    /// credentials.
+    ///
+    /// A running instance is stopped while its data is copied.
+    Clone(clone::Command),
     /// Show all instances.
     List(status::List),
     /// Show status of an instance.
@@ -93,6 +106,8 @@ pub enum Subcommands {
     Restore(backup::Restore),
     /// Restore an instance from a backup ([`BRANDING_CLOUD`] only).
     ListBackups(backup::ListBackups),
+    /// Copy a [`BRANDING_CLOUD`] instance into a new local instance.
+    RestoreFromCloud(restore_from_cloud::Command),
     /// Upgrade installations and instances.
     Upgrade(upgrade::Command),
     /// Revert a major instance upgrade.
@@ -101,4 +116,7 @@ pub enum Subcommands {
     ResetPassword(reset_password::Command),
     /// Display instance credentials (add `--json` for verbose).
     Credentials(credentials::Command),
+    /// Run the server binary of a local instance with extra arguments
+    /// (for debugging, the instance must be stopped).
+    Exec(exec::Command),
 }
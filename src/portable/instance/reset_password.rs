@@ -6,6 +6,8 @@ use base64::display::Base64Display;
 use edgedb_cli_derive::IntoArgs;
 use fn_error_context::context;
 use rand::{Rng, SeedableRng};
+use shell_escape::unix::escape;
+use url::Url;
 
 use edgeql_parser::helpers::{quote_name, quote_string};
 use gel_tokio::credentials::Credentials;
@@ -14,7 +16,7 @@ use crate::branding::{BRANDING_CLOUD, QUERY_TAG};
 use crate::commands::ExitCode;
 use crate::connect::Connection;
 use crate::credentials;
-use crate::portable::local::InstanceInfo;
+use crate::portable::local::{InstanceInfo, Paths};
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::print;
 use crate::tty_password;
@@ -23,6 +25,11 @@ const PASSWORD_LENGTH: usize = 24;
 const PASSWORD_CHARS: &[u8] = b"0123456789\
     abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 const HASH_ITERATIONS: u32 = 4096;
+const ENV_PREFIX: &str = if cfg!(feature = "gel") {
+    "GEL"
+} else {
+    "EDGEDB"
+};
 const SALT_LENGTH: usize = 16;
 
 pub fn generate_password() -> String {
@@ -58,6 +65,18 @@ pub struct Command {
     /// Do not save generated password into a credentials file even if user name matches.
     #[arg(long)]
     pub no_save_credentials: bool,
+    /// Generate a strong random password. This is the default unless
+    /// `--password` or `--password-from-stdin` is used.
+    #[arg(long, conflicts_with_all = ["password", "password_from_stdin"])]
+    pub generate: bool,
+    /// Print a DSN with the new password to stdout, e.g. for provisioning
+    /// scripts.
+    #[arg(long, conflicts_with = "print_env")]
+    pub print_dsn: bool,
+    /// Print shell `export` statements setting the connection environment
+    /// variables, including the new password, to stdout.
+    #[arg(long)]
+    pub print_env: bool,
     /// Do not print any messages, only indicate success by exit status.
     #[arg(long)]
     pub quiet: bool,
@@ -133,10 +152,14 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
             Ok::<_, anyhow::Error>(())
         })?;
 
+    let new_file = creds.is_none();
+    let mut creds = creds.unwrap_or_default();
+    if new_file {
+        creds.port = inst.port;
+    }
+    creds.user = user;
+    creds.password = Some(password);
     if save {
-        let mut creds = creds.unwrap_or_else(Default::default);
-        creds.user = user;
-        creds.password = Some(password);
         credentials::write(&credentials_file, &creds)?;
     }
     if !options.quiet {
@@ -149,9 +172,56 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
             print::success!("Password was successfully changed.");
         }
     }
+    if options.print_dsn || options.print_env {
+        let cert = Paths::get(&name)?.data_dir.join("edbtlscert.pem");
+        let cert = cert.exists().then_some(cert);
+        if options.print_dsn {
+            println!("{}", dsn(&creds, cert.as_deref())?);
+        } else {
+            for (name, value) in env_vars(&creds, cert.as_deref()) {
+                println!("export {ENV_PREFIX}_{name}={}", escape(value.into()));
+            }
+        }
+    }
     Ok(())
 }
 
+fn dsn(creds: &Credentials, cert: Option<&Path>) -> anyhow::Result<String> {
+    let mut url = Url::parse(&format!(
+        "edgedb://{}:{}",
+        creds.host.as_deref().unwrap_or("localhost"),
+        creds.port,
+    ))?;
+    url.set_username(&creds.user).ok();
+    url.set_password(creds.password.as_deref()).ok();
+    if let Some(branch) = creds.branch.as_ref().or(creds.database.as_ref()) {
+        url.set_path(branch);
+    }
+    if let Some(cert) = cert {
+        url.query_pairs_mut()
+            .append_pair("tls_ca_file", &cert.display().to_string());
+    }
+    Ok(url.to_string())
+}
+
+fn env_vars(creds: &Credentials, cert: Option<&Path>) -> Vec<(&'static str, String)> {
+    let mut vars = vec![
+        ("HOST", creds.host.clone().unwrap_or("localhost".into())),
+        ("PORT", creds.port.to_string()),
+        ("USER", creds.user.clone()),
+    ];
+    if let Some(password) = &creds.password {
+        vars.push(("PASSWORD", password.clone()));
+    }
+    if let Some(branch) = creds.branch.as_ref().or(creds.database.as_ref()) {
+        vars.push(("BRANCH", branch.clone()));
+    }
+    if let Some(cert) = cert {
+        vars.push(("TLS_CA_FILE", cert.display().to_string()));
+    }
+    vars
+}
+
 #[context("error reading credentials at {}", path.display())]
 fn read_credentials(path: &Path) -> anyhow::Result<Credentials> {
     let data = fs::read(path)?;
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use gel_tokio::Builder;
+
+use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
+use crate::cloud::client::CloudClient;
+use crate::cloud::ops::{find_cloud_instance_by_name, CloudInstance};
+use crate::commands::parser::Restore as RestoreCmd;
+use crate::commands::{self, dump_branch, get_databases, restore_all};
+use crate::connect::Connector;
+use crate::hint::HintExt;
+use crate::options::{CloudOptions, Options};
+use crate::platform::cache_dir;
+use crate::portable::instance::create;
+use crate::portable::local::{is_valid_local_instance_name, Paths};
+use crate::portable::options::{CloudInstanceBillables, CloudInstanceParams, InstanceName};
+use crate::portable::ver;
+use crate::print::{self, msg, Highlight};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[command(flatten)]
+    pub cloud_opts: CloudOptions,
+
+    /// [`BRANDING_CLOUD`] instance to copy, in `<org>/<name>` format.
+    #[arg(from_global)]
+    pub instance: Option<InstanceName>,
+
+    /// Name of the local instance to create. Defaults to the name of the
+    /// [`BRANDING_CLOUD`] instance.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Discard branches downloaded by a previous interrupted run instead
+    /// of resuming the download.
+    #[arg(long)]
+    pub fresh: bool,
+
+    /// Keep downloaded dumps after the instance is restored.
+    #[arg(long)]
+    pub keep_dump: bool,
+}
+
+pub fn run(cmd: &Command, opts: &Options) -> anyhow::Result<()> {
+    let Some(InstanceName::Cloud { org_slug, name }) = &cmd.instance else {
+        return Err(
+            anyhow::anyhow!("a {BRANDING_CLOUD} instance to copy is required")
+                .with_hint(|| {
+                    format!(
+                        "Specify it as `{BRANDING_CLI_CMD} instance \
+                         restore-from-cloud -I <org>/<name>`"
+                    )
+                })
+                .into(),
+        );
+    };
+    let local_name = cmd.name.clone().unwrap_or_else(|| name.clone());
+    if !is_valid_local_instance_name(&local_name) {
+        return Err(
+            anyhow::anyhow!("invalid local instance name {local_name:?}")
+                .hint("Specify a valid name with `--name`.")
+                .into(),
+        );
+    }
+    Paths::get(&local_name)?
+        .check_exists()
+        .with_context(|| format!("instance {local_name:?} detected"))
+        .hint("Specify another name with `--name`.")?;
+
+    let client = CloudClient::new(&opts.cloud_options)?;
+    client.ensure_authenticated()?;
+    let instance = find_instance(org_slug, name, &client)?;
+    // cloud reports versions like `5.6+b3c8c4c`
+    let version = instance.version.split('+').next().unwrap_or_default();
+    let version: ver::Filter = version
+        .parse()
+        .with_context(|| format!("unsupported version {:?}", instance.version))?;
+
+    let dump_dir = dump_dir(org_slug, name)?;
+    if cmd.fresh && dump_dir.exists() {
+        fs::remove_dir_all(&dump_dir)?;
+    }
+    download(&client, org_slug, name, &dump_dir)?;
+
+    msg!(
+        "Creating local instance {} with version {}...",
+        local_name.emphasize(),
+        version
+    );
+    create::run(
+        &create::Command {
+            cloud_opts: cmd.cloud_opts.clone(),
+            name: Some(InstanceName::Local(local_name.clone())),
+            nightly: false,
+            version: Some(version),
+            channel: None,
+            port: None,
+            data_dir: None,
+            cloud_params: CloudInstanceParams {
+                region: None,
+                billables: CloudInstanceBillables {
+                    tier: None,
+                    compute_size: None,
+                    storage_size: None,
+                },
+            },
+            cloud_backup_source: create::CloudBackupSourceParams {
+                from_backup_id: None,
+                from_instance: None,
+            },
+            start_conf: None,
+            default_user: None,
+            default_branch: None,
+            non_interactive: true,
+        },
+        opts,
+    )?;
+
+    restore(&local_name, &dump_dir)?;
+    if cmd.keep_dump {
+        msg!("Dumps are kept in {}", dump_dir.display());
+    } else {
+        fs::remove_dir_all(&dump_dir)
+            .map_err(|e| log::warn!("Cannot remove {dump_dir:?}: {e}"))
+            .ok();
+    }
+    print::success_msg(
+        "Restored",
+        format!("{org_slug}/{name} into local instance {local_name}"),
+    );
+    msg!(
+        "Server configuration and roles were not copied. To connect run:\
+        \n  {BRANDING_CLI_CMD} -I {local_name}"
+    );
+    Ok(())
+}
+
+fn dump_dir(org: &str, name: &str) -> anyhow::Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("cloud-dumps")
+        .join(format!("{org}.{name}")))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn find_instance(
+    org: &str,
+    name: &str,
+    client: &CloudClient,
+) -> anyhow::Result<CloudInstance> {
+    find_cloud_instance_by_name(name, org, client)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("instance {org}/{name} not found"))
+}
+
+/// Dumps every branch of the cloud instance into `dir`
+///
+/// Dump files are only renamed into place once complete, so branches
+/// dumped by a previous interrupted run are skipped.
+#[tokio::main(flavor = "current_thread")]
+async fn download(client: &CloudClient, org: &str, name: &str, dir: &Path) -> anyhow::Result<()> {
+    let mut builder = Builder::new();
+    builder.instance(&format!("{org}/{name}"))?;
+    if let Some(secret_key) = &client.secret_key {
+        builder.secret_key(secret_key);
+    }
+    let connector = Connector::new(builder.build_env().await.map_err(Into::into));
+    let mut cli = connector.connect().await?;
+    let options = commands::Options {
+        command_line: true,
+        styler: None,
+        conn_params: connector,
+    };
+
+    fs::create_dir_all(dir)?;
+    // system config and roles of the cloud are not applicable locally,
+    // but `restore --all` expects the file
+    fs::write(dir.join("init.edgeql"), "")?;
+    for branch in get_databases(&mut cli).await? {
+        let filename = dir.join(urlencoding::encode(&branch).into_owned() + ".dump");
+        if filename.exists() {
+            msg!(
+                "Branch {} is already downloaded, skipping.",
+                branch.emphasize()
+            );
+            continue;
+        }
+        dump_branch(&options, dir, &branch, false, None).await?;
+    }
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn restore(name: &str, dir: &Path) -> anyhow::Result<()> {
+    let mut builder = Builder::new();
+    builder.instance(name)?;
+    let mut connector = Connector::new(builder.build_env().await.map_err(Into::into));
+    connector.wait_until_available(Duration::from_secs(30));
+    let mut cli = connector.connect().await?;
+    let options = commands::Options {
+        command_line: true,
+        styler: None,
+        conn_params: connector,
+    };
+    let params = RestoreCmd {
+        conn: None,
+        path: dir.into(),
+        all: true,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        force_overwrite: false,
+        into_new_branch: None,
+        non_interactive: false,
+        verbose: false,
+    };
+    restore_all(&mut cli, &options, &params).await
+}
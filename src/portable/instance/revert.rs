@@ -11,7 +11,7 @@ use crate::portable::exit_codes;
 use crate::portable::instance::control;
 use crate::portable::instance::create;
 use crate::portable::instance::status::{instance_status, BackupStatus, DataDirectory};
-use crate::portable::local::Paths;
+use crate::portable::local::{self, Paths};
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::portable::server::install;
 use crate::print::{self, msg, Highlight};
@@ -73,7 +73,7 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
         }
     }
     if !options.no_confirm {
-        eprintln!();
+        msg!();
         msg!(
             "Currently stored data {} and overwritten by the backup.",
             "will be lost".emphasize()
@@ -102,7 +102,7 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
     let paths = Paths::get(&name)?;
     let tmp_path = tmp_file_path(&paths.data_dir);
     fs::rename(&paths.data_dir, &tmp_path)?;
-    fs::rename(&paths.backup_dir, &paths.data_dir)?;
+    local::move_dir(&paths.backup_dir, &paths.data_dir)?;
 
     let inst = old_inst;
     msg!("Starting {} {:?}...", BRANDING, inst.get_version());
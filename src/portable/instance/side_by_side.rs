@@ -0,0 +1,294 @@
+//! Side-by-side upgrade: `instance upgrade --keep-old` and `--rollback`.
+//!
+//! The dump is restored into a separate instance running the new version
+//! while the old one keeps serving. Then data directories of the two are
+//! swapped, so the upgraded data is served under the original name and
+//! port, and the old data is kept as instance `<name>_previous`.
+//! Rollback is the same swap done in the other direction.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use const_format::concatcp;
+use fn_error_context::context;
+use fs_err as fs;
+
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, QUERY_TAG};
+use crate::commands::{self, ExitCode};
+use crate::connect::{Connection, Connector};
+use crate::credentials;
+use crate::hint::HintExt;
+use crate::platform::{self, tmp_file_path};
+use crate::portable::exit_codes;
+use crate::portable::instance::clone::read_credentials;
+use crate::portable::instance::{control, create, upgrade};
+use crate::portable::local::{allocate_port, release_port, write_json, InstanceInfo, Paths};
+use crate::portable::repository::PackageInfo;
+use crate::portable::server::install;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+
+/// Name of the instance keeping the other side of the swap
+pub fn previous_name(name: &str) -> String {
+    format!("{name}_previous")
+}
+
+/// Paths of the previous instance, kept on the same filesystem as the
+/// data of the instance so that they can be renamed into each other
+fn previous_paths(paths: &Paths, previous: &str) -> anyhow::Result<Paths> {
+    match (&paths.data_link, paths.data_dir.parent()) {
+        (Some(_), Some(parent)) => Paths::with_data_dir(previous, &parent.join(previous)),
+        _ => Paths::get(previous),
+    }
+}
+
+pub fn upgrade(inst: InstanceInfo, pkg: PackageInfo, non_interactive: bool) -> anyhow::Result<()> {
+    let name = inst.name.clone();
+    let previous = previous_name(&name);
+    let paths = Paths::get(&name)?;
+    let side_paths = previous_paths(&paths, &previous)?;
+    if paths.upgrade_marker.exists() {
+        anyhow::bail!("Upgrade of instance {name:?} is already in progress");
+    }
+    side_paths
+        .check_exists()
+        .with_context(|| format!("instance {previous:?} is kept from the previous upgrade"))
+        .with_hint(|| {
+            format!(
+                "Run `{BRANDING_CLI_CMD} instance destroy -I {previous}` to remove it, \
+                 or `{BRANDING_CLI_CMD} instance upgrade --rollback -I {name}` \
+                 to switch back to it."
+            )
+        })?;
+
+    msg!(
+        "Upgrading to version {} side by side",
+        pkg.version.emphasize()
+    );
+    let old_version = inst.get_version()?.clone();
+    let install = install::package(&pkg).context(concatcp!("error installing ", BRANDING))?;
+
+    msg!("Dumping the database...");
+    control::do_start(&inst)?;
+    upgrade::block_on_dump_instance(&inst, &paths.dump_path)?;
+    upgrade::rename_default_branch_dump(
+        &old_version,
+        &install.version,
+        &paths.dump_path,
+        non_interactive,
+    )?;
+
+    let new_inst = InstanceInfo {
+        name: previous.clone(),
+        installation: Some(install),
+        port: allocate_port(&previous)?,
+        data_dir: side_paths
+            .data_link
+            .as_ref()
+            .map(|_| side_paths.data_dir.clone()),
+    };
+    if let Err(e) = restore(&new_inst, &paths, &side_paths) {
+        if let Err(e) = remove(&side_paths).and_then(|()| release_port(&previous)) {
+            log::warn!("Error removing instance {previous:?}: {e:#}");
+        }
+        return Err(e);
+    }
+    let mut creds = read_credentials(&paths.credentials)?;
+    creds.port = new_inst.port;
+    credentials::write_file(&side_paths.credentials, &creds)?;
+
+    swap(&name, &previous)?;
+    if let Err(e) = fs::remove_dir_all(&paths.dump_path) {
+        log::warn!("Error removing dump {:?}: {e:#}", paths.dump_path);
+    }
+    msg!(
+        "Instance {} successfully upgraded to {}. The previous version \
+         is kept as instance {}.",
+        name.emphasize(),
+        pkg.version.emphasize(),
+        previous.emphasize()
+    );
+    msg!("To switch back run:");
+    msg!("  {BRANDING_CLI_CMD} instance upgrade --rollback -I {name}");
+    Ok(())
+}
+
+pub fn rollback(name: &str, non_interactive: bool) -> anyhow::Result<()> {
+    let previous = previous_name(name);
+    let paths = Paths::get(name)?;
+    if paths.upgrade_marker.exists() {
+        anyhow::bail!("Upgrade of instance {name:?} is in progress, cannot roll back");
+    }
+    if !Paths::get(&previous)?.data_dir.exists() {
+        return Err(anyhow::anyhow!(
+            "No previous version of instance {name:?} is kept"
+        ))
+        .with_hint(|| {
+            format!(
+                "Only upgrades done with `{BRANDING_CLI_CMD} instance upgrade --keep-old` \
+                 can be rolled back. Use `{BRANDING_CLI_CMD} instance revert` \
+                 for other upgrades."
+            )
+        })?;
+    }
+    let side_info = InstanceInfo::read(&previous)?;
+    let side_version = side_info.get_version()?;
+    if !non_interactive {
+        let q = question::Confirm::new(format!(
+            "Instance {name:?} will be switched back to version {side_version}. \
+             Changes made since the upgrade will only be kept in instance \
+             {previous:?}. Continue?"
+        ));
+        if !q.ask()? {
+            print::error!("Canceled.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED))?;
+        }
+    }
+    install::specific(&side_version.specific())
+        .context(concatcp!("error installing old ", BRANDING))?;
+
+    swap(name, &previous)?;
+    msg!(
+        "Instance {} is switched back to {}. The upgraded version is kept \
+         as instance {}.",
+        name.emphasize(),
+        side_version.emphasize(),
+        previous.emphasize()
+    );
+    Ok(())
+}
+
+#[context("cannot restore into instance {:?}", inst.name)]
+fn restore(inst: &InstanceInfo, source: &Paths, target: &Paths) -> anyhow::Result<()> {
+    fs::create_dir_all(&target.data_dir)?;
+    if let Some(link) = &target.data_link {
+        platform::symlink_dir(&target.data_dir, link)
+            .with_context(|| format!("linking {:?} -> {:?}", link, target.data_dir))?;
+    }
+
+    msg!("Restoring the database into {}...", inst.name.emphasize());
+    control::ensure_runstate_dir(&inst.name)?;
+    let mut cmd = control::get_server_cmd(inst, false)?;
+    control::self_signed_arg(&mut cmd, inst.get_version()?);
+    cmd.background_for(|| Ok(restore_dump(inst, &source.dump_path)))?;
+
+    write_json(
+        &target.data_dir.join("instance_info.json"),
+        "new instance metadata",
+        inst,
+    )?;
+    // clients keep trusting the same certificate after the swap
+    for file in ["edbtlscert.pem", "edbprivkey.pem"] {
+        fs::copy(source.data_dir.join(file), target.data_dir.join(file))?;
+    }
+    Ok(())
+}
+
+async fn restore_dump(inst: &InstanceInfo, dump_path: &Path) -> anyhow::Result<()> {
+    use crate::commands::parser::Restore;
+
+    let mut conn_params = inst.admin_conn_params()?;
+    conn_params.wait_until_available(Duration::from_secs(300));
+    let cfg = conn_params.build_env().await?;
+    let mut cli = Connection::connect(&cfg, QUERY_TAG).await?;
+    let options = commands::Options {
+        command_line: true,
+        styler: None,
+        conn_params: Connector::new(Ok(cfg)),
+    };
+    commands::restore_all_resumable(
+        &mut cli,
+        &options,
+        &Restore {
+            path: dump_path.to_path_buf(),
+            all: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            force_overwrite: false,
+            into_new_branch: None,
+            non_interactive: false,
+            verbose: false,
+            conn: None,
+        },
+        &[],
+        |_| Ok(()),
+    )
+    .await
+}
+
+/// Exchanges two directories. Every step is rolled back on failure, so
+/// both directories are either swapped or left in place.
+fn exchange(a: &Path, b: &Path) -> anyhow::Result<()> {
+    let tmp = tmp_file_path(a);
+    fs::rename(a, &tmp)?;
+    if let Err(e) = fs::rename(b, a) {
+        fs::rename(&tmp, a)?;
+        return Err(e.into());
+    }
+    if let Err(e) = fs::rename(&tmp, b) {
+        fs::rename(a, b)?;
+        fs::rename(&tmp, a)?;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+fn write_info(paths: &Paths, info: &InstanceInfo) -> anyhow::Result<()> {
+    write_json(&paths.data_dir.join("instance_info.json"), "metadata", info)
+}
+
+fn remove(paths: &Paths) -> anyhow::Result<()> {
+    if let Some(link) = &paths.data_link {
+        if fs::symlink_metadata(link).is_ok() {
+            fs::remove_file(link)?;
+        }
+    }
+    if paths.data_dir.exists() {
+        fs::remove_dir_all(&paths.data_dir)?;
+    }
+    Ok(())
+}
+
+/// Exchanges the data of the instance and the previous instance. Names,
+/// ports and credentials stay where they were.
+fn swap(name: &str, previous: &str) -> anyhow::Result<()> {
+    let paths = Paths::get(name)?;
+    let side_paths = Paths::get(previous)?;
+    let info = InstanceInfo::read(name)?;
+    let side_info = InstanceInfo::read(previous)?;
+
+    msg!("Stopping {}...", name.emphasize());
+    control::do_stop(name)?;
+    // only running if started manually to inspect it
+    if let Err(e) = control::do_stop(previous) {
+        log::warn!("Error stopping {previous:?}: {e:#}");
+    }
+
+    exchange(&paths.data_dir, &side_paths.data_dir)?;
+    let new_info = InstanceInfo {
+        installation: side_info.installation.clone(),
+        ..info.clone()
+    };
+    let new_side_info = InstanceInfo {
+        installation: info.installation.clone(),
+        ..side_info.clone()
+    };
+    if let Err(e) =
+        write_info(&paths, &new_info).and_then(|()| write_info(&side_paths, &new_side_info))
+    {
+        exchange(&paths.data_dir, &side_paths.data_dir)
+            .and_then(|()| write_info(&paths, &info))
+            .and_then(|()| write_info(&side_paths, &side_info))
+            .with_context(|| format!("cannot roll back swap after error: {e:#}"))?;
+        return Err(e);
+    }
+
+    create::create_service(&new_info)
+        .map_err(|e| {
+            log::warn!("Error running {BRANDING} as a service: {e:#}");
+        })
+        .ok();
+    control::do_restart(&new_info)?;
+    Ok(())
+}
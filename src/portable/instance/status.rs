@@ -1,17 +1,19 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::fs;
 use std::future::{pending, Future};
 use std::io;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
 use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Context;
 use edgedb_cli_derive::IntoArgs;
 use fn_error_context::context;
 use humantime::format_duration;
+use indicatif::HumanBytes;
 use is_terminal::IsTerminal;
 use tokio::join;
 use tokio::time::sleep;
@@ -27,13 +29,15 @@ use crate::collect::Collector;
 use crate::commands::ExitCode;
 use crate::credentials;
 use crate::format;
-use crate::platform::data_dir;
+use crate::platform::{cache_dir, data_dir};
 use crate::portable::exit_codes;
 use crate::portable::instance::control;
-use crate::portable::instance::upgrade::{BackupMeta, UpgradeMeta};
+use crate::portable::instance::health::{self, WaitUntil};
+use crate::portable::instance::upgrade::{dir_size, BackupMeta, UpgradeMeta};
 use crate::portable::local::{is_valid_local_instance_name, lock_file, read_ports};
 use crate::portable::local::{InstanceInfo, Paths};
-use crate::portable::options::{instance_arg, InstanceName};
+use crate::portable::options::{instance_arg, InstanceName, OutputFormat};
+use crate::portable::project;
 use crate::portable::{linux, macos, windows};
 use crate::print::{self, msg, Highlight};
 use crate::process;
@@ -57,15 +61,37 @@ pub struct List {
     #[arg(long, conflicts_with_all=&["extended", "debug"])]
     pub json: bool,
 
+    /// Output as an HTML table, e.g. for including in reports.
+    #[arg(long, conflicts_with_all=&["extended", "debug", "json"])]
+    pub html: bool,
+
+    /// Add inline CSS styles to the HTML table.
+    #[arg(long, requires = "html")]
+    pub html_styles: bool,
+
     /// Query remote instances.
     //  Currently needed for WSL.
     #[arg(long, hide = true)]
     pub no_remote: bool,
 
+    /// Do not connect to remote instances to check whether they are
+    /// reachable.
+    #[arg(long)]
+    pub no_probe: bool,
+
     /// Do not show warnings on no instances.
     //  Currently needed for WSL.
     #[arg(long, hide = true)]
     pub quiet: bool,
+
+    /// Wait until the instance reaches the given state before showing
+    /// the status.
+    #[arg(long, value_enum, value_name = "state")]
+    pub wait_until: Option<WaitUntil>,
+
+    /// Maximum time to wait with `--wait-until`, in seconds.
+    #[arg(long, default_value = "60", value_name = "SECONDS")]
+    pub wait_timeout: u64,
 }
 
 #[derive(clap::Args, IntoArgs, Debug, Clone)]
@@ -94,10 +120,15 @@ pub struct Status {
     #[arg(conflicts_with_all=&["extended", "json", "service"])]
     pub debug: bool,
 
-    /// Output in JSON format.
-    #[arg(long, conflicts_with_all=&["extended", "debug", "service"])]
+    /// Output in JSON format (shortcut to `--format json`).
+    #[arg(long, conflicts_with_all=&["extended", "debug", "service", "format"])]
     pub json: bool,
 
+    /// Output format.
+    #[arg(long, value_enum)]
+    #[arg(conflicts_with_all=&["extended", "debug", "service"])]
+    pub format: Option<OutputFormat>,
+
     /// Do not print error on "No instance found", only indicate by error code.
     //  Currently needed for WSL.
     #[arg(long, hide = true)]
@@ -140,9 +171,19 @@ pub struct FullStatus {
     pub backup: BackupStatus,
     pub credentials_file_exists: bool,
     pub service_exists: bool,
+    /// Only collected for a single instance status, as it's slow to
+    /// compute for the whole list
+    pub details: Option<Details>,
     // TODO(tailhook) add linked projects
 }
 
+#[derive(Debug)]
+pub struct Details {
+    pub data_size: anyhow::Result<u64>,
+    pub uptime: Option<Duration>,
+    pub port_latency: Option<anyhow::Result<Duration>>,
+}
+
 #[derive(Debug)]
 pub enum ConnectionStatus {
     Connected,
@@ -166,6 +207,9 @@ pub struct RemoteStatus {
     pub connection: Option<ConnectionStatus>,
     pub instance_status: Option<String>,
     pub location: String,
+    pub projects: Vec<PathBuf>,
+    /// Last time the instance was successfully connected to
+    pub last_reachable: Option<SystemTime>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -182,9 +226,27 @@ pub struct JsonStatus {
     pub instance_status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cloud_instance_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "humantime_serde")]
+    pub last_reachable: Option<SystemTime>,
+    /// Size of the data directory in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "humantime_serde")]
+    pub uptime: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "humantime_serde")]
+    pub port_latency: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "humantime_serde")]
+    pub last_backup: Option<SystemTime>,
 }
 
 pub fn run(cmd: &Status, opts: &crate::options::Options) -> anyhow::Result<()> {
+    wait_until(cmd)?;
     if cmd.service {
         external_status(cmd)
     } else {
@@ -192,6 +254,16 @@ pub fn run(cmd: &Status, opts: &crate::options::Options) -> anyhow::Result<()> {
     }
 }
 
+pub fn wait_until(cmd: &Status) -> anyhow::Result<()> {
+    match cmd.wait_until {
+        Some(WaitUntil::Healthy) => health::wait_until_healthy(
+            &instance_arg(&cmd.name, &cmd.instance)?,
+            Duration::from_secs(cmd.wait_timeout),
+        ),
+        None => Ok(()),
+    }
+}
+
 fn external_status(options: &Status) -> anyhow::Result<()> {
     let name = match instance_arg(&options.name, &options.instance)? {
         InstanceName::Local(name) => name,
@@ -282,7 +354,51 @@ fn status_from_meta(
         backup,
         credentials_file_exists,
         service_exists,
+        details: None,
+    }
+}
+
+fn details(status: &FullStatus) -> Details {
+    let data_size = dir_size(&status.data_dir)
+        .with_context(|| format!("cannot compute size of {:?}", status.data_dir));
+    let (uptime, port_latency) = match (&status.service, &status.instance) {
+        (Service::Running { pid }, Ok(inst)) => (
+            process_started(&status.name, *pid).and_then(|t| t.elapsed().ok()),
+            Some(probe_port(inst.port)),
+        ),
+        _ => (None, None),
+    };
+    Details {
+        data_size,
+        uptime,
+        port_latency,
+    }
+}
+
+/// Process start time, approximated by the modification time of the pid
+/// file written by the server on startup.
+fn process_started(name: &str, pid: u32) -> Option<SystemTime> {
+    if let Ok(Some(file_pid)) = control::read_pid(name) {
+        if file_pid == pid {
+            let path = control::pid_file_path(name).ok()?;
+            return fs::metadata(path).and_then(|m| m.modified()).ok();
+        }
     }
+    if cfg!(target_os = "linux") {
+        // procfs entries are created when the process starts
+        return fs::metadata(format!("/proc/{pid}"))
+            .and_then(|m| m.modified())
+            .ok();
+    }
+    None
+}
+
+fn probe_port(port: u16) -> anyhow::Result<Duration> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let start = std::time::Instant::now();
+    TcpStream::connect_timeout(&addr, Duration::from_secs(2))
+        .with_context(|| format!("cannot connect to port {port}"))?;
+    Ok(start.elapsed())
 }
 
 pub fn instance_status(name: &str) -> anyhow::Result<FullStatus> {
@@ -304,14 +420,18 @@ fn normal_status(cmd: &Status, opts: &crate::options::Options) -> anyhow::Result
     let meta = InstanceInfo::try_read(&name).transpose();
     if let Some(meta) = meta {
         let paths = Paths::get(&name)?;
-        let status = status_from_meta(&name, &paths, meta);
+        let mut status = status_from_meta(&name, &paths, meta);
+        let format = OutputFormat::resolve(cmd.format, cmd.json);
+        if cmd.extended || cmd.debug || format != OutputFormat::Table {
+            status.details = Some(details(&status));
+        }
         if cmd.debug {
             println!("{status:#?}");
             Ok(())
         } else if cmd.extended {
             status.print_extended_and_exit();
-        } else if cmd.json {
-            status.print_json_and_exit();
+        } else if format != OutputFormat::Table {
+            status.print_structured_and_exit(format);
         } else {
             status.print_and_exit();
         }
@@ -334,10 +454,11 @@ fn cloud_status(
 
     let status = cloud::ops::get_status(&client, &inst)?;
 
+    let format = OutputFormat::resolve(cmd.format, cmd.json);
     if cmd.extended {
         status.print_extended_and_exit();
-    } else if cmd.json {
-        status.print_json_and_exit();
+    } else if format != OutputFormat::Table {
+        status.print_structured_and_exit(format);
     } else {
         status.print_and_exit();
     }
@@ -372,10 +493,10 @@ pub async fn try_connect(creds: &Credentials) -> (Option<String>, ConnectionStat
 
 #[tokio::main(flavor = "current_thread")]
 async fn remote_status_with_feedback(name: &str, quiet: bool) -> anyhow::Result<RemoteStatus> {
-    intermediate_feedback(_remote_status(name, quiet), || "Trying to connect...").await
+    intermediate_feedback(_remote_status(name, quiet, true), || "Trying to connect...").await
 }
 
-async fn _remote_status(name: &str, quiet: bool) -> anyhow::Result<RemoteStatus> {
+async fn _remote_status(name: &str, quiet: bool, probe: bool) -> anyhow::Result<RemoteStatus> {
     let cred_path = credentials::path(name)?;
     if !cred_path.exists() {
         if !quiet {
@@ -389,7 +510,12 @@ async fn _remote_status(name: &str, quiet: bool) -> anyhow::Result<RemoteStatus>
     }
     let cred_data = tokio::fs::read(cred_path).await?;
     let credentials = serde_json::from_slice(&cred_data)?;
-    let (version, connection) = try_connect(&credentials).await;
+    let (version, connection) = if probe {
+        let (version, connection) = try_connect(&credentials).await;
+        (version, Some(connection))
+    } else {
+        (None, None)
+    };
     let location = format!(
         "{}:{}",
         credentials.host.as_deref().unwrap_or("localhost"),
@@ -400,9 +526,11 @@ async fn _remote_status(name: &str, quiet: bool) -> anyhow::Result<RemoteStatus>
         type_: RemoteType::Remote,
         credentials,
         version,
-        connection: Some(connection),
+        connection,
         instance_status: None,
         location,
+        projects: Vec::new(),
+        last_reachable: None,
     })
 }
 
@@ -416,7 +544,7 @@ where
         _ = async {
             sleep(Duration::from_millis(300)).await;
             if std::io::stderr().is_terminal() {
-                eprintln!("{}", text());
+                msg!("{}", text());
             }
             pending().await
         } => unreachable!(),
@@ -430,17 +558,15 @@ pub fn remote_status(options: &Status) -> anyhow::Result<()> {
     };
 
     let status = remote_status_with_feedback(&name, options.quiet)?;
+    let format = OutputFormat::resolve(options.format, options.json);
     if options.service {
         println!("Remote instance");
     } else if options.debug {
         println!("{status:#?}");
     } else if options.extended {
         status.print_extended();
-    } else if options.json {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&status.json()).expect("status is json-serializable"),
-        );
+    } else if format != OutputFormat::Table {
+        println!("{}", format.document(&status.json())?);
     } else if let Some(inst_status) = &status.instance_status {
         println!("{inst_status}");
     } else if let Some(ConnectionStatus::Error(e)) = &status.connection {
@@ -478,6 +604,7 @@ pub fn list_local(
 
 async fn get_remote_async(
     instances: Vec<String>,
+    probe: bool,
     errors: &Collector<anyhow::Error>,
 ) -> anyhow::Result<Vec<RemoteStatus>> {
     let sem = Arc::new(tokio::sync::Semaphore::new(100));
@@ -487,7 +614,7 @@ async fn get_remote_async(
         let permit = sem.clone().acquire_owned().await.expect("semaphore is ok");
         tasks.spawn(async move {
             let _permit = permit;
-            match _remote_status(&name, false).await {
+            match _remote_status(&name, false, probe).await {
                 Ok(status) => {
                     if let Some(ConnectionStatus::Error(e)) = &status.connection {
                         errors.add(
@@ -517,11 +644,12 @@ async fn get_remote_async(
 
 async fn get_remote_and_cloud(
     instances: Vec<String>,
+    probe: bool,
     cloud_client: CloudClient,
     errors: &Collector<anyhow::Error>,
 ) -> anyhow::Result<Vec<RemoteStatus>> {
     match join!(
-        get_remote_async(instances, errors),
+        get_remote_async(instances, probe, errors),
         crate::cloud::ops::list(cloud_client, errors),
     ) {
         (Ok(remote), Ok(cloud)) => Ok(remote.into_iter().chain(cloud.into_iter()).collect()),
@@ -543,14 +671,16 @@ async fn get_remote_and_cloud(
 #[tokio::main(flavor = "current_thread")]
 pub async fn get_remote(
     visited: &BTreeSet<String>,
+    probe: bool,
     opts: &crate::options::Options,
     errors: &Collector<anyhow::Error>,
 ) -> anyhow::Result<Vec<RemoteStatus>> {
-    _get_remote(visited, opts, errors).await
+    _get_remote(visited, probe, opts, errors).await
 }
 
 async fn _get_remote(
     visited: &BTreeSet<String>,
+    probe: bool,
     opts: &crate::options::Options,
     errors: &Collector<anyhow::Error>,
 ) -> anyhow::Result<Vec<RemoteStatus>> {
@@ -562,7 +692,7 @@ async fn _get_remote(
     let num = instances.len();
     if cloud_client.is_logged_in {
         intermediate_feedback(
-            get_remote_and_cloud(instances, cloud_client, errors),
+            get_remote_and_cloud(instances, probe, cloud_client, errors),
             || {
                 if num > 0 {
                     format!("Checking {BRANDING_CLOUD} and {num} remote instance(s)...")
@@ -573,7 +703,7 @@ async fn _get_remote(
         )
         .await
     } else if num > 0 {
-        intermediate_feedback(get_remote_async(instances, errors), || {
+        intermediate_feedback(get_remote_async(instances, probe, errors), || {
             format!("Checking {num} remote instance(s)...")
         })
         .await
@@ -617,10 +747,10 @@ pub fn list(options: &List, opts: &crate::options::Options) -> anyhow::Result<()
         }
     };
 
-    let remote = if options.no_remote {
+    let mut remote = if options.no_remote {
         Vec::new()
     } else {
-        match get_remote(&visited, opts, &errors) {
+        match get_remote(&visited, !options.no_probe, opts, &errors) {
             Ok(remote) => remote,
             Err(e) => {
                 errors.add(e);
@@ -628,6 +758,8 @@ pub fn list(options: &List, opts: &crate::options::Options) -> anyhow::Result<()
             }
         }
     };
+    let mut local_json = local.iter().map(|s| s.json()).collect::<Vec<_>>();
+    add_details(&mut local_json, &mut remote);
 
     if local.is_empty() && remote.is_empty() {
         return if print_errors(&errors.list(), false) {
@@ -649,8 +781,9 @@ pub fn list(options: &List, opts: &crate::options::Options) -> anyhow::Result<()
             println!("{status:#?}");
         }
     } else if options.extended {
-        for status in local {
+        for (status, json) in local.iter().zip(&local_json) {
             status.print_extended();
+            print_projects(&json.projects);
         }
         for status in remote {
             status.print_extended();
@@ -659,16 +792,16 @@ pub fn list(options: &List, opts: &crate::options::Options) -> anyhow::Result<()
         println!(
             "{}",
             serde_json::to_string_pretty(
-                &local
-                    .iter()
-                    .map(|status| status.json())
+                &local_json
+                    .into_iter()
                     .chain(remote.iter().map(|status| status.json()))
                     .collect::<Vec<_>>()
             )?
         );
+    } else if options.html {
+        print_html(&local_json, &remote, options.html_styles);
     } else {
         // using always JSON because we need that for windows impl
-        let local_json = local.iter().map(|s| s.json()).collect::<Vec<_>>();
         print_table(&local_json, &remote);
     }
 
@@ -679,6 +812,108 @@ pub fn list(options: &List, opts: &crate::options::Options) -> anyhow::Result<()
     }
 }
 
+/// Project directories linked to each instance.
+fn linked_projects() -> BTreeMap<String, Vec<PathBuf>> {
+    let stash_dirs = match project::find_project_stash_dirs("instance-name", |_| true, false) {
+        Ok(stash_dirs) => stash_dirs,
+        Err(e) => {
+            log::warn!("Cannot list projects: {:#}", e);
+            return BTreeMap::new();
+        }
+    };
+    stash_dirs
+        .into_iter()
+        .map(|(name, dirs)| {
+            let paths = dirs
+                .iter()
+                .filter_map(|dir| project::read_project_path(dir).ok())
+                .collect();
+            (name, paths)
+        })
+        .collect()
+}
+
+fn reachable_cache_path() -> anyhow::Result<PathBuf> {
+    Ok(cache_dir()?.join("instance_reachable.json"))
+}
+
+/// Reads the last times remote instances were reachable, in seconds since
+/// unix epoch.
+fn read_reachable() -> anyhow::Result<BTreeMap<String, u64>> {
+    let path = reachable_cache_path()?;
+    match fs::read(&path) {
+        Ok(data) => Ok(serde_json::from_slice(&data)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e).with_context(|| format!("cannot read {path:?}")),
+    }
+}
+
+#[context("cannot write reachability cache")]
+fn write_reachable(reachable: &BTreeMap<String, u64>) -> anyhow::Result<()> {
+    let path = reachable_cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_vec_pretty(reachable)?)?;
+    Ok(())
+}
+
+/// Fills in linked projects and last reachable time of the instances.
+///
+/// Successful connections made while probing remote instances are recorded,
+/// so that the time is known when the instance is down later.
+pub fn add_details(local: &mut [JsonStatus], remote: &mut [RemoteStatus]) {
+    let projects = linked_projects();
+    for status in local {
+        status.projects = projects.get(&status.name).cloned().unwrap_or_default();
+    }
+    let mut reachable = read_reachable()
+        .map_err(|e| log::warn!("{:#}", e))
+        .unwrap_or_default();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut changed = false;
+    for status in remote.iter_mut() {
+        status.projects = projects.get(&status.name).cloned().unwrap_or_default();
+        if let Some(ConnectionStatus::Connected) = status.connection {
+            reachable.insert(status.name.clone(), now);
+            changed = true;
+        }
+        status.last_reachable = reachable
+            .get(&status.name)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(*secs));
+    }
+    if changed {
+        write_reachable(&reachable)
+            .map_err(|e| log::warn!("{:#}", e))
+            .ok();
+    }
+}
+
+fn print_projects(projects: &[PathBuf]) {
+    for path in projects {
+        println!("  Project: {}", path.display());
+    }
+}
+
+fn format_reachable(status: &RemoteStatus) -> String {
+    match (&status.connection, status.last_reachable) {
+        (Some(ConnectionStatus::Connected), _) => "now".into(),
+        (_, Some(time)) => match time.elapsed() {
+            Ok(elapsed) if elapsed.as_secs() < 60 => "just now".into(),
+            Ok(elapsed) => {
+                let min = Duration::from_secs(elapsed.as_secs() / 60 * 60);
+                format!("{} ago", format_duration(min))
+            }
+            Err(_) => "now".into(),
+        },
+        (Some(_), None) => "never".into(),
+        (None, None) => "-".into(),
+    }
+}
+
 pub fn print_errors(errs: &[anyhow::Error], is_warning: bool) -> bool {
     for e in errs {
         if is_warning {
@@ -690,20 +925,23 @@ pub fn print_errors(errs: &[anyhow::Error], is_warning: bool) -> bool {
     !errs.is_empty()
 }
 
-pub fn print_table(local: &[JsonStatus], remote: &[RemoteStatus]) {
-    let mut table = Table::new();
-    table.set_format(*table::FORMAT);
-    table.set_titles(Row::new(
-        ["Kind", "Name", "Location", "Version", "Status"]
-            .iter()
-            .map(|x| table::header_cell(x))
-            .collect(),
-    ));
+const LIST_COLUMNS: &[&str] = &[
+    "Kind",
+    "Name",
+    "Location",
+    "Version",
+    "Status",
+    "Projects",
+    "Reachable",
+];
+
+fn list_rows(local: &[JsonStatus], remote: &[RemoteStatus]) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
     for status in local {
-        table.add_row(Row::new(vec![
-            Cell::new("local"),
-            Cell::new(&status.name),
-            Cell::new(&format!(
+        rows.push(vec![
+            "local".into(),
+            status.name.clone(),
+            format!(
                 "localhost:{}",
                 status
                     .port
@@ -711,39 +949,67 @@ pub fn print_table(local: &[JsonStatus], remote: &[RemoteStatus]) {
                     .map(ToString::to_string)
                     .as_deref()
                     .unwrap_or("?")
-            )),
-            Cell::new(status.version.as_deref().unwrap_or("?")),
-            Cell::new(status.service_status.as_deref().unwrap_or("?")),
-        ]));
+            ),
+            status.version.as_deref().unwrap_or("?").into(),
+            status.service_status.as_deref().unwrap_or("?").into(),
+            format_projects(&status.projects),
+            "-".into(),
+        ]);
     }
     for status in remote {
-        table.add_row(Row::new(vec![
-            Cell::new(match status.type_ {
+        rows.push(vec![
+            match status.type_ {
                 RemoteType::Cloud { instance_id: _ } => "cloud",
                 RemoteType::Remote => "remote",
-            }),
-            Cell::new(&status.name),
-            Cell::new(&status.location),
-            Cell::new(
-                status
-                    .version
-                    .as_ref()
-                    .map(|m| m.to_string())
-                    .as_deref()
-                    .unwrap_or("?"),
-            ),
-            Cell::new(
-                status
-                    .instance_status
-                    .as_deref()
-                    .or(status.connection.as_ref().map(|s| s.as_str()))
-                    .unwrap_or("unknown"),
-            ),
-        ]));
+            }
+            .into(),
+            status.name.clone(),
+            status.location.clone(),
+            status
+                .version
+                .as_ref()
+                .map(|m| m.to_string())
+                .unwrap_or_else(|| "?".into()),
+            status
+                .instance_status
+                .as_deref()
+                .or(status.connection.as_ref().map(|s| s.as_str()))
+                .unwrap_or("unknown")
+                .into(),
+            format_projects(&status.projects),
+            format_reachable(status),
+        ]);
+    }
+    rows
+}
+
+pub fn print_table(local: &[JsonStatus], remote: &[RemoteStatus]) {
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        LIST_COLUMNS.iter().map(|x| table::header_cell(x)).collect(),
+    ));
+    for row in list_rows(local, remote) {
+        table.add_row(Row::new(row.iter().map(|v| Cell::new(v)).collect()));
     }
     table.printstd();
 }
 
+pub fn print_html(local: &[JsonStatus], remote: &[RemoteStatus], styles: bool) {
+    print!(
+        "{}",
+        print::html_table(LIST_COLUMNS, &list_rows(local, remote), styles)
+    );
+}
+
+fn format_projects(projects: &[PathBuf]) -> String {
+    projects
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl FullStatus {
     pub fn print_extended_and_exit(&self) -> ! {
         self.print_extended();
@@ -846,9 +1112,36 @@ impl FullStatus {
                 }
             }
         );
+        if let Some(details) = &self.details {
+            match &details.data_size {
+                Ok(size) => println!("  Data size: {}", HumanBytes(*size)),
+                Err(e) => println!("  Data size: unknown ({e:#})"),
+            }
+            if let Some(uptime) = details.uptime {
+                let uptime = Duration::from_secs(uptime.as_secs());
+                println!("  Uptime: {}", format_duration(uptime));
+            }
+            match &details.port_latency {
+                Some(Ok(latency)) => {
+                    let latency = Duration::from_micros(latency.as_micros() as u64);
+                    println!("  Port latency: {}", format_duration(latency));
+                }
+                Some(Err(e)) => println!("  Port latency: unreachable ({e:#})"),
+                None => {}
+            }
+        }
+    }
+    fn last_backup(&self) -> Option<SystemTime> {
+        match &self.backup {
+            BackupStatus::Exists {
+                backup_meta: Ok(b), ..
+            } => Some(b.timestamp),
+            _ => None,
+        }
     }
     pub fn json(&self) -> JsonStatus {
         let meta = self.instance.as_ref().ok();
+        let details = self.details.as_ref();
         JsonStatus {
             name: self.name.clone(),
             port: meta.map(|m| m.port),
@@ -859,12 +1152,22 @@ impl FullStatus {
             remote_status: None,
             instance_status: None,
             cloud_instance_id: None,
+            projects: Vec::new(),
+            last_reachable: None,
+            data_size: details.and_then(|d| d.data_size.as_ref().ok().copied()),
+            uptime: details.and_then(|d| d.uptime),
+            port_latency: details
+                .and_then(|d| d.port_latency.as_ref())
+                .and_then(|l| l.as_ref().ok().copied()),
+            last_backup: self.last_backup(),
         }
     }
-    pub fn print_json_and_exit(&self) -> ! {
+    pub fn print_structured_and_exit(&self, format: OutputFormat) -> ! {
         println!(
             "{}",
-            serde_json::to_string_pretty(&self.json()).expect("status is not json-serializable")
+            format
+                .document(&self.json())
+                .expect("status is serializable")
         );
         self.exit()
     }
@@ -872,7 +1175,7 @@ impl FullStatus {
         use Service::*;
         match &self.service {
             Ready => {
-                eprintln!("Ready in socket activation mode, not running");
+                msg!("Ready in socket activation mode, not running");
             }
             Running { pid } => {
                 eprint!("Running, pid ");
@@ -881,13 +1184,13 @@ impl FullStatus {
             Failed {
                 exit_code: Some(code),
             } => {
-                eprintln!("Stopped, exit code {code}");
+                msg!("Stopped, exit code {code}");
             }
             Failed { exit_code: None } => {
-                eprintln!("Not running");
+                msg!("Not running");
             }
             Inactive { .. } => {
-                eprintln!("Inactive");
+                msg!("Inactive");
             }
         }
         // TODO(tailhook) print more information in case some error is found:
@@ -946,6 +1249,10 @@ impl RemoteStatus {
         if let Some(ConnectionStatus::Error(e)) = &self.connection {
             println!("  Connection error: {e:#}");
         }
+        if self.last_reachable.is_some() {
+            println!("  Last reachable: {}", format_reachable(self));
+        }
+        print_projects(&self.projects);
     }
 
     pub fn print_extended_and_exit(&self) -> ! {
@@ -966,19 +1273,27 @@ impl RemoteStatus {
             } else {
                 None
             },
+            projects: self.projects.clone(),
+            last_reachable: self.last_reachable,
+            data_size: None,
+            uptime: None,
+            port_latency: None,
+            last_backup: None,
         }
     }
 
-    pub fn print_json_and_exit(&self) -> ! {
+    pub fn print_structured_and_exit(&self, format: OutputFormat) -> ! {
         println!(
             "{}",
-            serde_json::to_string_pretty(&self.json()).expect("status is not json-serializable")
+            format
+                .document(&self.json())
+                .expect("status is serializable")
         );
         self.exit()
     }
 
     pub fn print_and_exit(&self) -> ! {
-        eprintln!("{}", self.instance_status.as_deref().unwrap_or("<unknown>"));
+        msg!("{}", self.instance_status.as_deref().unwrap_or("<unknown>"));
         self.exit()
     }
 
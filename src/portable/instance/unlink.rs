@@ -6,10 +6,12 @@ use anyhow::Context;
 use crate::branding::{BRANDING_CLI_CMD, BRANDING_CLOUD};
 use crate::credentials;
 use crate::hint::HintExt;
+use crate::keychain::{self, Account};
 use crate::portable::instance::destroy::with_projects;
 use crate::portable::local::InstanceInfo;
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::portable::project;
+use crate::print::msg;
 
 pub fn run(cmd: &Command) -> anyhow::Result<()> {
     let name = match instance_arg(&cmd.name, &cmd.instance)? {
@@ -37,7 +39,9 @@ pub fn run(cmd: &Command) -> anyhow::Result<()> {
     with_projects(&name, cmd.force, print_warning, || {
         let path = credentials::path(&name)?;
         fs::remove_file(&path)
-            .with_context(|| format!("Credentials for {name} missing from {path:?}"))
+            .with_context(|| format!("Credentials for {name} missing from {path:?}"))?;
+        keychain::delete(&Account::Instance(&name));
+        Ok(())
     })?;
     Ok(())
 }
@@ -59,6 +63,6 @@ pub struct Command {
 
 pub fn print_warning(name: &str, project_dirs: &[PathBuf]) {
     project::print_instance_in_use_warning(name, project_dirs);
-    eprintln!("If you really want to unlink the instance, run:");
-    eprintln!("  {BRANDING_CLI_CMD} instance unlink -I {name:?} --force");
+    msg!("If you really want to unlink the instance, run:");
+    msg!("  {BRANDING_CLI_CMD} instance unlink -I {name:?} --force");
 }
@@ -7,16 +7,22 @@ use anyhow::Context;
 use const_format::concatcp;
 use edgedb_cli_derive::IntoArgs;
 use fn_error_context::context;
+use indicatif::HumanBytes;
 
 use crate::branding::{BRANDING, BRANDING_CLI_CMD, BRANDING_CLOUD, QUERY_TAG};
 use crate::cloud;
 use crate::commands::{self, ExitCode};
 use crate::connect::{Connection, Connector};
+use crate::hint::HintExt;
+use crate::migrations;
 use crate::options::CloudOptions;
+use crate::platform::{self, tmp_file_path};
 use crate::portable::exit_codes;
 use crate::portable::instance::control;
 use crate::portable::instance::create;
-use crate::portable::local::{write_json, InstallInfo, InstanceInfo, Paths};
+use crate::portable::instance::side_by_side;
+use crate::portable::instance::status::read_upgrade;
+use crate::portable::local::{self, write_json, InstanceInfo, Paths};
 use crate::portable::options::{instance_arg, InstanceName};
 use crate::portable::project;
 use crate::portable::repository::{self, Channel, PackageInfo, Query, QueryOptions};
@@ -24,7 +30,9 @@ use crate::portable::server::install;
 use crate::portable::ver;
 use crate::portable::windows;
 use crate::print::{self, msg, Highlight};
+use crate::process;
 use crate::question;
+use crate::table;
 
 pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()> {
     match instance_arg(&cmd.name, &cmd.instance)? {
@@ -101,6 +109,46 @@ pub struct Command {
     /// Do not ask questions. Assume user wants to upgrade instance.
     #[arg(long)]
     pub non_interactive: bool,
+
+    /// Print what the upgrade would do without changing the instance.
+    ///
+    /// Only the package metadata is downloaded.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Resume an upgrade that failed midway from the last completed step.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&[
+        "to_version", "to_latest", "to_nightly", "to_testing", "to_channel",
+        "abort_upgrade", "dry_run",
+    ])]
+    pub resume: bool,
+
+    /// Abort an upgrade that failed midway and bring the instance back
+    /// to the version it had before.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&[
+        "to_version", "to_latest", "to_nightly", "to_testing", "to_channel",
+        "resume", "dry_run",
+    ])]
+    pub abort_upgrade: bool,
+
+    /// Upgrade side by side: restore the data into a new instance on the
+    /// target version while the old one keeps running, then swap them.
+    ///
+    /// The old instance is kept as `<name>_previous`, run
+    /// `instance upgrade --rollback` to switch back to it.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&["resume", "abort_upgrade", "dry_run"])]
+    pub keep_old: bool,
+
+    /// Switch back to the instance kept by `--keep-old`.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&[
+        "to_version", "to_latest", "to_nightly", "to_testing", "to_channel",
+        "resume", "abort_upgrade", "dry_run", "keep_old",
+    ])]
+    pub rollback: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -110,6 +158,23 @@ pub struct UpgradeMeta {
     #[serde(with = "humantime_serde")]
     pub started: SystemTime,
     pub pid: u32,
+    #[serde(default)]
+    pub phase: UpgradePhase,
+    /// Branches already restored into the new data directory
+    #[serde(default)]
+    pub completed_branches: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpgradePhase {
+    /// Dump is written and the instance is stopped, data directory is intact
+    #[default]
+    Dumped,
+    /// Data directory is moved to the backup location
+    BackedUp,
+    /// Branches are being restored into the new data directory
+    Restoring,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -138,7 +203,7 @@ pub fn print_project_upgrade_command(
     current_project: &Option<PathBuf>,
     project_dir: &Path,
 ) {
-    eprintln!(
+    msg!(
         "  {BRANDING_CLI_CMD} project upgrade {}{}",
         match version.channel {
             Channel::Stable =>
@@ -167,12 +232,12 @@ fn check_project(name: &str, force: bool, ver_query: &Query) -> anyhow::Result<(
     project::print_instance_in_use_warning(name, &project_dirs);
 
     if force {
-        eprintln!(
+        msg!(
             "To update the project{} after the instance upgrade, run:",
             if project_dirs.len() > 1 { "s" } else { "" }
         );
     } else {
-        eprintln!("To continue with the upgrade, run:");
+        msg!("To continue with the upgrade, run:");
     }
     for pd in project_dirs {
         let pd = project::read_project_path(&pd)?;
@@ -185,6 +250,19 @@ fn check_project(name: &str, force: bool, ver_query: &Query) -> anyhow::Result<(
 }
 
 fn upgrade_local_cmd(cmd: &Command, name: &str) -> anyhow::Result<()> {
+    if cfg!(windows) && (cmd.resume || cmd.abort_upgrade || cmd.rollback) {
+        return windows::upgrade(cmd, name);
+    }
+    if cmd.resume {
+        return resume_upgrade(name);
+    }
+    if cmd.abort_upgrade {
+        return abort_upgrade(name);
+    }
+    if cmd.rollback {
+        return side_by_side::rollback(name, cmd.non_interactive);
+    }
+
     let inst = InstanceInfo::read(name)?;
     let inst_ver = inst.get_version()?.specific();
     let (ver_query, ver_option) = Query::from_options(
@@ -197,10 +275,22 @@ fn upgrade_local_cmd(cmd: &Command, name: &str) -> anyhow::Result<()> {
         },
         || Query::from_version(&inst_ver),
     )?;
-    check_project(name, cmd.force, &ver_query)?;
+    if !cmd.dry_run {
+        check_project(name, cmd.force, &ver_query)?;
+    }
 
     if cfg!(windows) {
-        return windows::upgrade(cmd, name);
+        windows::upgrade(cmd, name)?;
+        if cmd.dry_run {
+            // projects are on the Windows side, so they are checked here
+            let pkg = repository::get_server_package(&ver_query)?
+                .context("no package found according to your criteria")?;
+            if !pkg.version.specific().is_compatible(&inst_ver) || cmd.force_dump_restore {
+                check_linked_projects(name, &pkg)
+                    .context("schema is not compatible with the target version")?;
+            }
+        }
+        return Ok(());
     }
 
     let pkg = repository::get_server_package(&ver_query)?
@@ -222,13 +312,154 @@ fn upgrade_local_cmd(cmd: &Command, name: &str) -> anyhow::Result<()> {
     // we rely on presence of the version specifying options instead to
     // define how we want upgrade to be performed. This is mostly useful
     // for tests.
-    if pkg_ver.is_compatible(&inst_ver) && !(cmd.force && ver_option) && !cmd.force_dump_restore {
+    let compatible =
+        pkg_ver.is_compatible(&inst_ver) && !(cmd.force && ver_option) && !cmd.force_dump_restore;
+    if cmd.dry_run {
+        let schema_check = if compatible {
+            Ok("not required")
+        } else {
+            check_linked_projects(name, &pkg).map(|checked| {
+                if checked > 0 {
+                    "passed"
+                } else {
+                    "no linked projects to check"
+                }
+            })
+        };
+        print_dry_run(
+            &inst,
+            &pkg,
+            !compatible,
+            schema_check.as_ref().copied().unwrap_or("failed"),
+        )?;
+        schema_check.context("schema is not compatible with the target version")?;
+        msg!("Dry run: no changes were made.");
+        Ok(())
+    } else if cmd.keep_old {
+        side_by_side::upgrade(inst, pkg, cmd.non_interactive)
+    } else if compatible {
         upgrade_compatible(inst, pkg)
     } else {
         upgrade_incompatible(inst, pkg, cmd.non_interactive)
     }
 }
 
+/// Runs the upgrade check of the schema of every project linked to the
+/// instance against the package. Returns the number of projects checked.
+fn check_linked_projects(name: &str, pkg: &PackageInfo) -> anyhow::Result<usize> {
+    let mut checked = 0;
+    for stash_dir in project::find_project_dirs_by_instance(name)? {
+        let project_dir = project::read_project_path(&stash_dir)?;
+        msg!("Checking schema of {}...", project_dir.display());
+        let project = project::ensure_ctx(Some(&project_dir))?;
+        migrations::upgrade_check::to_version(pkg, &project)
+            .with_context(|| format!("check of project {project_dir:?} failed"))?;
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+/// Prints what the upgrade of a local instance would do.
+pub fn print_dry_run(
+    inst: &InstanceInfo,
+    pkg: &PackageInfo,
+    dump_restore: bool,
+    schema_check: &str,
+) -> anyhow::Result<()> {
+    let paths = Paths::get(&inst.name)?;
+    let installed = platform::portable_dir()?
+        .join(pkg.version.specific().to_string())
+        .exists();
+    let projects = project::find_project_dirs_by_instance(&inst.name)?
+        .iter()
+        .filter_map(|pd| project::read_project_path(pd).ok())
+        .map(|pd| pd.display().to_string())
+        .collect::<Vec<_>>();
+    let data_size = match dir_size(&paths.data_dir) {
+        Ok(size) => HumanBytes(size).to_string(),
+        Err(e) => {
+            log::warn!("Cannot compute size of {:?}: {:#}", paths.data_dir, e);
+            "unknown".into()
+        }
+    };
+
+    table::settings(&[
+        ("Instance", inst.name.clone()),
+        ("Current version", inst.get_version()?.to_string()),
+        ("Target version", pkg.version.to_string()),
+        (
+            "Package",
+            if installed {
+                "already downloaded".into()
+            } else {
+                format!("would be downloaded from {}", pkg.url)
+            },
+        ),
+        (
+            "Upgrade kind",
+            if dump_restore {
+                "major (dump and restore)".into()
+            } else {
+                "minor (in place)".into()
+            },
+        ),
+        ("Data directory size", data_size),
+        ("Schema check", schema_check.into()),
+        (
+            "Linked projects",
+            if projects.is_empty() {
+                "none".into()
+            } else {
+                projects.join("\n")
+            },
+        ),
+    ]);
+
+    msg!("The upgrade would:");
+    let mut steps = Vec::new();
+    if !installed {
+        steps.push(format!("download and install {BRANDING} {}", pkg.version));
+    }
+    if dump_restore {
+        steps.push(format!(
+            "dump all branches to {}",
+            paths.dump_path.display()
+        ));
+        steps.push(format!(
+            "stop the instance and move its data directory to {}",
+            paths.backup_dir.display()
+        ));
+        steps.push("initialize a new data directory and restore the dump".into());
+    } else {
+        steps.push("switch the instance to the new server binary".into());
+    }
+    steps.push("restart the instance".into());
+    for (i, step) in steps.iter().enumerate() {
+        msg!("  {}. {}", i + 1, step);
+    }
+    if dump_restore {
+        msg!(
+            "A dump of comparable size to the data directory will be written, \
+            and the old data directory is kept for `{BRANDING_CLI_CMD} instance revert`."
+        );
+    }
+    Ok(())
+}
+
+pub fn dir_size(path: &Path) -> anyhow::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_dir() {
+            total += dir_size(&entry.path())?;
+        } else {
+            total += meta.len();
+        }
+    }
+    Ok(total)
+}
+
 fn upgrade_cloud_cmd(
     cmd: &Command,
     org: &str,
@@ -255,7 +486,10 @@ fn upgrade_cloud_cmd(
     let result = upgrade_cloud(org, name, &query, &client, cmd.force, |target_ver| {
         let target_ver_str = target_ver.to_string();
         ver::print_version_hint(target_ver, &query);
-        if !cmd.non_interactive {
+        if cmd.dry_run {
+            msg!("{BRANDING_CLOUD} instance {inst_name} would be upgraded to version {target_ver_str}.");
+            Ok(false)
+        } else if !cmd.non_interactive {
             question::Confirm::new(format!(
                 "This will upgrade {inst_name} to version {target_ver_str}.\
                     \nConfirm?",
@@ -275,6 +509,9 @@ fn upgrade_cloud_cmd(
                 upgraded to version {target_ver_str}."
             );
         }
+        UpgradeAction::Cancelled if cmd.dry_run => {
+            msg!("Dry run: no changes were made.");
+        }
         UpgradeAction::Cancelled => {
             msg!("Canceled.");
         }
@@ -367,49 +604,92 @@ pub fn upgrade_incompatible(
     let install = install::package(&pkg).context(concatcp!("error installing ", BRANDING))?;
 
     let paths = Paths::get(&inst.name)?;
+    if paths.upgrade_marker.exists() {
+        return Err(anyhow::anyhow!("Upgrade is already in progress")).with_hint(|| {
+            format!(
+                "Run `{BRANDING_CLI_CMD} instance upgrade --resume -I {0}` to continue it, \
+                 or `{BRANDING_CLI_CMD} instance upgrade --abort-upgrade -I {0}` to revert",
+                inst.name
+            )
+        })?;
+    }
     dump_and_stop(&inst, &paths.dump_path)?;
 
-    backup(&inst, &install, &paths)?;
+    rename_default_branch_dump(
+        &old_version,
+        &pkg.version,
+        &paths.dump_path,
+        non_interactive,
+    )?;
+
+    let meta = UpgradeMeta {
+        source: old_version,
+        target: install.version.clone(),
+        started: SystemTime::now(),
+        pid: std::process::id(),
+        phase: UpgradePhase::Dumped,
+        completed_branches: Vec::new(),
+    };
+    write_json(&paths.upgrade_marker, "upgrade marker", &meta)?;
 
     inst.installation = Some(install);
+    finish_upgrade(inst, &paths, meta)
+}
 
-    if old_version.specific().major <= 4 && pkg.version.specific().major >= 5 {
-        let dump_files = fs::read_dir(&paths.dump_path)?;
+/// When upgrading from 4.x or earlier, offers to restore the `edgedb`
+/// database as the `main` branch
+pub fn rename_default_branch_dump(
+    old_version: &ver::Build,
+    new_version: &ver::Build,
+    dump_path: &Path,
+    non_interactive: bool,
+) -> anyhow::Result<()> {
+    if old_version.specific().major > 4 || new_version.specific().major < 5 {
+        return Ok(());
+    }
+    let dump_files = fs::read_dir(dump_path)?;
 
-        let mut has_edgedb_dump = false;
-        let mut has_main_dump = false;
+    let mut has_edgedb_dump = false;
+    let mut has_main_dump = false;
 
-        for file in dump_files.flatten() {
-            has_edgedb_dump |= file.file_name() == "edgedb.dump";
-            has_main_dump |= file.file_name() == "main.dump";
-        }
+    for file in dump_files.flatten() {
+        has_edgedb_dump |= file.file_name() == "edgedb.dump";
+        has_main_dump |= file.file_name() == "main.dump";
+    }
 
-        if has_main_dump {
-            print::warn!("The database 'main' will now become the default database");
-        } else if has_edgedb_dump
-            && (non_interactive
-                || question::Confirm::new(
-                    "Would you like to rename the database 'edgedb' to 'main'?",
-                )
+    if has_main_dump {
+        print::warn!("The database 'main' will now become the default database");
+    } else if has_edgedb_dump
+        && (non_interactive
+            || question::Confirm::new("Would you like to rename the database 'edgedb' to 'main'?")
                 .default(true)
                 .ask()?)
-        {
-            // print info about the rename for non-prompt
-            if non_interactive {
-                eprintln!("Renaming 'edgedb' to 'main'");
-            }
-
-            fs::rename(
-                paths.dump_path.join("edgedb.dump"),
-                paths.dump_path.join("main.dump"),
-            )?;
+    {
+        // print info about the rename for non-prompt
+        if non_interactive {
+            msg!("Renaming 'edgedb' to 'main'");
         }
+
+        fs::rename(dump_path.join("edgedb.dump"), dump_path.join("main.dump"))?;
     }
+    Ok(())
+}
 
-    reinit_and_restore(&inst, &paths).map_err(|e| {
+/// Continues the upgrade from the phase recorded in `meta`
+fn finish_upgrade(inst: InstanceInfo, paths: &Paths, mut meta: UpgradeMeta) -> anyhow::Result<()> {
+    if meta.phase == UpgradePhase::Dumped {
+        backup(paths)?;
+        meta.phase = UpgradePhase::BackedUp;
+        write_json(&paths.upgrade_marker, "upgrade marker", &meta)?;
+    }
+
+    reinit_and_restore(&inst, paths, &mut meta).map_err(|e| {
         print::error!("{e:#}");
-        eprintln!(
-            "To undo run:\n  {BRANDING_CLI_CMD} instance revert -I {:?}",
+        msg!(
+            "To retry from the failed step run:\n  \
+             {BRANDING_CLI_CMD} instance upgrade --resume -I {0:?}\n\
+             To undo run:\n  \
+             {BRANDING_CLI_CMD} instance upgrade --abort-upgrade -I {0:?}",
             inst.name
         );
         ExitCode::new(exit_codes::NEEDS_REVERT)
@@ -428,12 +708,92 @@ pub fn upgrade_incompatible(
     msg!(
         "Instance {} successfully upgraded to {}",
         inst.name.emphasize(),
-        pkg.version.emphasize()
+        meta.target.emphasize()
     );
 
     Ok(())
 }
 
+fn read_marker(name: &str, paths: &Paths) -> anyhow::Result<UpgradeMeta> {
+    if !paths.upgrade_marker.exists() {
+        anyhow::bail!("No upgrade of instance {name:?} is in progress");
+    }
+    let meta = read_upgrade(&paths.upgrade_marker)?;
+    if meta.pid != std::process::id() && process::exists(meta.pid) {
+        anyhow::bail!(
+            "Upgrade of instance {name:?} is still running with pid {}",
+            meta.pid
+        );
+    }
+    Ok(meta)
+}
+
+fn resume_upgrade(name: &str) -> anyhow::Result<()> {
+    let paths = Paths::get(name)?;
+    let mut meta = read_marker(name, &paths)?;
+    msg!(
+        "Resuming upgrade of instance {} to {}",
+        name.emphasize(),
+        meta.target.emphasize()
+    );
+    // Metadata of the old instance stays in the data directory until
+    // it's moved to the backup location
+    let info_dir = if meta.phase == UpgradePhase::Dumped && paths.data_dir.exists() {
+        &paths.data_dir
+    } else {
+        &paths.backup_dir
+    };
+    let mut inst = InstanceInfo::read_at(name, &info_dir.join("instance_info.json"))?;
+    let install = install::specific(&meta.target.specific())
+        .context(concatcp!("error installing ", BRANDING))?;
+    inst.installation = Some(install);
+    meta.pid = std::process::id();
+    write_json(&paths.upgrade_marker, "upgrade marker", &meta)?;
+    finish_upgrade(inst, &paths, meta)
+}
+
+fn abort_upgrade(name: &str) -> anyhow::Result<()> {
+    let paths = Paths::get(name)?;
+    let meta = read_marker(name, &paths)?;
+    if let Err(e) = control::do_stop(name) {
+        log::warn!("Error stopping service: {e:#}");
+    }
+    if meta.phase != UpgradePhase::Dumped || !paths.data_dir.exists() {
+        if !paths.backup_dir.exists() {
+            anyhow::bail!("cannot find backup directory {:?}", paths.backup_dir);
+        }
+        let tmp_path = tmp_file_path(&paths.data_dir);
+        if paths.data_dir.exists() {
+            fs_err::rename(&paths.data_dir, &tmp_path)?;
+        }
+        local::move_dir(&paths.backup_dir, &paths.data_dir)?;
+        if tmp_path.exists() {
+            fs_err::remove_dir_all(&tmp_path)?;
+        }
+    }
+    let backup_meta = paths.data_dir.join("backup.json");
+    if backup_meta.exists() {
+        fs_err::remove_file(&backup_meta)?;
+    }
+    fs_err::remove_file(&paths.upgrade_marker)?;
+
+    let inst = InstanceInfo::read(name)?;
+    install::specific(&inst.get_version()?.specific())
+        .context(concatcp!("error installing old ", BRANDING))?;
+    create::create_service(&inst)
+        .map_err(|e| {
+            log::warn!("Error running {BRANDING} as a service: {e:#}");
+        })
+        .ok();
+    control::do_restart(&inst)?;
+    msg!(
+        "Upgrade aborted, instance {} is back to {}",
+        inst.name.emphasize(),
+        meta.source.emphasize()
+    );
+    Ok(())
+}
+
 #[context("cannot dump {:?} -> {}", inst.name, path.display())]
 pub fn dump_and_stop(inst: &InstanceInfo, path: &Path) -> anyhow::Result<()> {
     // in case not started for now
@@ -457,7 +817,7 @@ pub fn dump_and_stop(inst: &InstanceInfo, path: &Path) -> anyhow::Result<()> {
 }
 
 #[tokio::main(flavor = "current_thread")]
-async fn block_on_dump_instance(inst: &InstanceInfo, destination: &Path) -> anyhow::Result<()> {
+pub async fn block_on_dump_instance(inst: &InstanceInfo, destination: &Path) -> anyhow::Result<()> {
     dump_instance(inst, destination).await
 }
 
@@ -484,26 +844,18 @@ pub async fn dump_instance(inst: &InstanceInfo, destination: &Path) -> anyhow::R
         &options,
         destination,
         true, /*include_secrets*/
+        1,    /*concurrency*/
     )
     .await?;
     Ok(())
 }
 
-fn backup(inst: &InstanceInfo, new_inst: &InstallInfo, paths: &Paths) -> anyhow::Result<()> {
-    if paths.upgrade_marker.exists() {
-        anyhow::bail!("Upgrade is already in progress");
+fn backup(paths: &Paths) -> anyhow::Result<()> {
+    if !paths.data_dir.exists() {
+        // moved already, but the upgrade was interrupted before the
+        // marker was updated
+        return Ok(());
     }
-    write_json(
-        &paths.upgrade_marker,
-        "upgrade marker",
-        &UpgradeMeta {
-            source: inst.get_version()?.clone(),
-            target: new_inst.version.clone(),
-            started: SystemTime::now(),
-            pid: std::process::id(),
-        },
-    )?;
-
     write_json(
         &paths.data_dir.join("backup.json"),
         "backup metadata",
@@ -514,13 +866,24 @@ fn backup(inst: &InstanceInfo, new_inst: &InstallInfo, paths: &Paths) -> anyhow:
     if paths.backup_dir.exists() {
         fs_err::remove_dir_all(&paths.backup_dir)?;
     }
-    fs_err::rename(&paths.data_dir, &paths.backup_dir)?;
+    local::move_dir(&paths.data_dir, &paths.backup_dir)?;
 
     Ok(())
 }
 
 #[context("cannot restore {:?}", inst.name)]
-fn reinit_and_restore(inst: &InstanceInfo, paths: &Paths) -> anyhow::Result<()> {
+fn reinit_and_restore(
+    inst: &InstanceInfo,
+    paths: &Paths,
+    meta: &mut UpgradeMeta,
+) -> anyhow::Result<()> {
+    if meta.completed_branches.is_empty() && paths.data_dir.exists() {
+        // left from the interrupted attempt, nothing worth keeping there
+        fs_err::remove_dir_all(&paths.data_dir)?;
+    }
+    meta.phase = UpgradePhase::Restoring;
+    write_json(&paths.upgrade_marker, "upgrade marker", &*meta)?;
+
     fs::create_dir_all(&paths.data_dir)
         .with_context(|| format!("cannot create {:?}", paths.data_dir))?;
 
@@ -529,8 +892,8 @@ fn reinit_and_restore(inst: &InstanceInfo, paths: &Paths) -> anyhow::Result<()>
     let mut cmd = control::get_server_cmd(inst, false)?;
     control::self_signed_arg(&mut cmd, inst.get_version()?);
     cmd.background_for(|| {
-        Ok(async {
-            restore_instance(inst, &paths.dump_path).await?;
+        Ok(async move {
+            restore_instance(inst, paths, meta).await?;
             log::info!(
                 "Restarting instance {:?} to apply \
                    changes from `restore --all`",
@@ -555,7 +918,11 @@ fn reinit_and_restore(inst: &InstanceInfo, paths: &Paths) -> anyhow::Result<()>
     Ok(())
 }
 
-async fn restore_instance(inst: &InstanceInfo, path: &Path) -> anyhow::Result<()> {
+async fn restore_instance(
+    inst: &InstanceInfo,
+    paths: &Paths,
+    meta: &mut UpgradeMeta,
+) -> anyhow::Result<()> {
     use crate::commands::parser::Restore;
     let mut conn_params = inst.admin_conn_params()?;
     conn_params.wait_until_available(Duration::from_secs(300));
@@ -569,15 +936,26 @@ async fn restore_instance(inst: &InstanceInfo, path: &Path) -> anyhow::Result<()
         styler: None,
         conn_params: Connector::new(Ok(cfg)),
     };
-    commands::restore_all(
+    let completed = meta.completed_branches.clone();
+    commands::restore_all_resumable(
         &mut cli,
         &options,
         &Restore {
-            path: path.into(),
+            path: paths.dump_path.clone(),
             all: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            force_overwrite: false,
+            into_new_branch: None,
+            non_interactive: false,
             verbose: false,
             conn: None,
         },
+        &completed,
+        |branch| {
+            meta.completed_branches.push(branch.into());
+            write_json(&paths.upgrade_marker, "upgrade marker", &*meta)
+        },
     )
     .await?;
     Ok(())
@@ -16,7 +16,8 @@ use crate::branding::BRANDING;
 use crate::bug;
 use crate::credentials;
 use crate::hint::HintExt;
-use crate::platform::{cache_dir, config_dir, data_dir, portable_dir};
+use crate::platform::lock;
+use crate::platform::{cache_dir, config_dir, data_dir, portable_dir, tmp_file_path};
 use crate::portable::repository::PackageHash;
 use crate::portable::ver;
 use crate::portable::{linux, macos, windows};
@@ -32,6 +33,8 @@ pub struct Paths {
     pub backup_dir: PathBuf,
     pub upgrade_marker: PathBuf,
     pub runstate_dir: PathBuf,
+    /// Symlink in the default location pointing to the custom data directory
+    pub data_link: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -40,6 +43,9 @@ pub struct InstanceInfo {
     pub name: String,
     pub installation: Option<InstallInfo>,
     pub port: u16,
+    /// Custom data directory set by `instance create --data-dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -151,36 +157,63 @@ impl Iterator for NextMinPort {
 
 pub fn allocate_port(name: &str) -> anyhow::Result<u16> {
     let port_file = port_file()?;
-    let mut port_map = _read_ports(&port_file)?;
-    if let Some(port) = port_map.get(name) {
-        return Ok(*port);
-    }
-    for port in NextMinPort::search(&port_map) {
-        match TcpListener::bind(("127.0.0.1", port)) {
-            Ok(_) => {}
-            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
-                log::debug!("Address 127.0.0.1:{} is already in use", port);
-                continue;
-            }
-            Err(e) => {
-                log::warn!("Error checking port 127.0.0.1:{}: {:#}", port, e);
-            }
+    lock::with_lock(&port_file, || {
+        let mut port_map = _read_ports(&port_file)?;
+        if let Some(port) = port_map.get(name) {
+            return Ok(*port);
         }
-        match TcpListener::bind(("::1", port)) {
-            Ok(_) => {}
-            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
-                log::debug!("Address [::1]:{} is already in use", port);
-                continue;
+        for port in NextMinPort::search(&port_map) {
+            match TcpListener::bind(("127.0.0.1", port)) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                    log::debug!("Address 127.0.0.1:{} is already in use", port);
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("Error checking port 127.0.0.1:{}: {:#}", port, e);
+                }
             }
-            Err(e) => {
-                log::warn!("Error checking port [::1]:{}: {:#}", port, e);
+            match TcpListener::bind(("::1", port)) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                    log::debug!("Address [::1]:{} is already in use", port);
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("Error checking port [::1]:{}: {:#}", port, e);
+                }
             }
+            port_map.insert(name.to_string(), port);
+            write_json(&port_file, "ports mapping", &port_map)?;
+            return Ok(port);
         }
-        port_map.insert(name.to_string(), port);
-        write_json(&port_file, "ports mapping", &port_map)?;
-        return Ok(port);
-    }
-    anyhow::bail!("Cannot find unused port");
+        anyhow::bail!("Cannot find unused port");
+    })
+}
+
+/// Moves the port allocated for the instance to its new name
+pub fn rename_port(old_name: &str, new_name: &str) -> anyhow::Result<()> {
+    let port_file = port_file()?;
+    lock::with_lock(&port_file, || {
+        let mut port_map = _read_ports(&port_file)?;
+        if let Some(port) = port_map.remove(old_name) {
+            port_map.insert(new_name.to_string(), port);
+            write_json(&port_file, "ports mapping", &port_map)?;
+        }
+        Ok(())
+    })
+}
+
+/// Frees the port allocated for a removed instance
+pub fn release_port(name: &str) -> anyhow::Result<()> {
+    let port_file = port_file()?;
+    lock::with_lock(&port_file, || {
+        let mut port_map = _read_ports(&port_file)?;
+        if port_map.remove(name).is_some() {
+            write_json(&port_file, "ports mapping", &port_map)?;
+        }
+        Ok(())
+    })
 }
 
 #[context("cannot write {} file {}", title, path.display())]
@@ -262,10 +295,30 @@ pub fn instance_data_dir(name: &str) -> anyhow::Result<PathBuf> {
 
 impl Paths {
     pub fn get(name: &str) -> anyhow::Result<Paths> {
+        let link = data_dir()?.join(name);
+        match fs::symlink_metadata(&link) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                let target =
+                    fs::read_link(&link).with_context(|| format!("cannot read link {link:?}"))?;
+                Paths::with_data_dir(name, &target)
+            }
+            _ => Paths::_get(name, None),
+        }
+    }
+    /// Paths of an instance with data directory in a custom location
+    pub fn with_data_dir(name: &str, data_dir: &Path) -> anyhow::Result<Paths> {
+        Paths::_get(name, Some(data_dir))
+    }
+    fn _get(name: &str, custom_data_dir: Option<&Path>) -> anyhow::Result<Paths> {
         let base = data_dir()?;
         Ok(Paths {
             credentials: credentials::path(name)?,
-            data_dir: base.join(name),
+            data_dir: custom_data_dir
+                .map(|d| d.to_path_buf())
+                .unwrap_or_else(|| base.join(name)),
+            data_link: custom_data_dir.map(|_| base.join(name)),
+            // not next to a custom data directory: nothing is written
+            // outside of the directory chosen by the user
             dump_path: base.join(format!("{name}.dump")),
             backup_dir: base.join(format!("{name}.backup")),
             upgrade_marker: base.join(format!("{name}.UPGRADE_IN_PROGRESS")),
@@ -288,6 +341,11 @@ impl Paths {
         if self.data_dir.exists() {
             anyhow::bail!("Data directory {:?} already exists", self.data_dir);
         }
+        if let Some(link) = &self.data_link {
+            if fs::symlink_metadata(link).is_ok() {
+                anyhow::bail!("Data directory {:?} already exists", link);
+            }
+        }
         for path in &self.service_files {
             if path.exists() {
                 anyhow::bail!("Service file {:?} already exists", path);
@@ -526,6 +584,46 @@ pub fn is_valid_cloud_org_name(name: &str) -> bool {
 #[error("Not a local instance")]
 pub struct NonLocalInstance;
 
+/// Renames a directory, or copies it and removes the source if it can't
+/// be renamed, i.e. the backup of an instance with a custom data directory
+/// is on another filesystem
+pub fn move_dir(source: &Path, target: &Path) -> anyhow::Result<()> {
+    if let Err(e) = fs::rename(source, target) {
+        log::info!("Cannot rename {source:?} to {target:?} ({e}), copying instead");
+        let tmp = tmp_file_path(target);
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)?;
+        }
+        copy_dir(source, &tmp).with_context(|| format!("cannot copy {source:?} to {tmp:?}"))?;
+        fs::rename(&tmp, target)?;
+        fs::remove_dir_all(source).with_context(|| format!("cannot remove {source:?}"))?;
+    }
+    Ok(())
+}
+
+pub fn copy_dir(source: &Path, target: &Path) -> anyhow::Result<()> {
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        let dest = target.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(&path)?, &dest)?;
+            #[cfg(windows)]
+            anyhow::bail!("cannot copy symlink {path:?}");
+        } else {
+            fs::copy(&path, &dest)?;
+        }
+    }
+    // the server refuses to start with a data directory accessible by others
+    fs::set_permissions(target, fs::metadata(source)?.permissions())?;
+    Ok(())
+}
+
 #[test]
 fn test_min_port() {
     assert_eq!(
@@ -12,6 +12,7 @@ pub mod windows;
 pub mod extension;
 pub mod instance;
 pub mod project;
+pub mod sandbox;
 pub mod server;
 
 pub use instance::reset_password::password_hash;
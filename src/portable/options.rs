@@ -6,6 +6,7 @@ use edgedb_cli_derive::IntoArgs;
 use crate::branding::BRANDING_CLOUD;
 use crate::cloud::ops::CloudTier;
 use crate::commands::ExitCode;
+use crate::outputs::yaml;
 use crate::portable::local::{
     is_valid_cloud_instance_name, is_valid_cloud_org_name, is_valid_local_instance_name,
 };
@@ -157,7 +158,7 @@ pub struct CloudInstanceBillables {
     pub storage_size: Option<String>,
 }
 
-fn billable_unit(s: &str) -> Result<String, String> {
+pub fn billable_unit(s: &str) -> Result<String, String> {
     let (numerator, denominator) = match s.split_once('/') {
         Some(v) => v,
         None => (s, "1"),
@@ -179,3 +180,92 @@ fn billable_unit(s: &str) -> Result<String, String> {
         Ok(s.to_string())
     }
 }
+
+/// Output format of the `info` and `status` commands
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl OutputFormat {
+    /// Combines `--format` with the `--json` shortcut
+    pub fn resolve(format: Option<OutputFormat>, json: bool) -> OutputFormat {
+        if json {
+            OutputFormat::Json
+        } else {
+            format.unwrap_or(OutputFormat::Table)
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Toml => "toml",
+        }
+    }
+
+    /// Serializes a whole document. Tables are rendered by the commands
+    /// themselves, so `Table` falls back to JSON here.
+    pub fn document<T: serde::Serialize>(&self, value: &T) -> anyhow::Result<String> {
+        let text = match self {
+            OutputFormat::Table | OutputFormat::Json => serde_json::to_string_pretty(value)?,
+            OutputFormat::Yaml => yaml::to_string(value)?,
+            OutputFormat::Toml => toml::to_string_pretty(value)?,
+        };
+        Ok(text.trim_end().to_string())
+    }
+
+    /// Serializes a single value, e.g. the one requested with `--get`.
+    /// TOML has no null, so missing values are printed as empty string.
+    pub fn value<T: serde::Serialize>(&self, value: &T) -> anyhow::Result<String> {
+        let text = match self {
+            OutputFormat::Table | OutputFormat::Json => serde_json::to_string(value)?,
+            OutputFormat::Yaml => yaml::to_string(value)?,
+            OutputFormat::Toml => {
+                let value = serde_json::to_value(value)?;
+                if value.is_null() {
+                    String::new()
+                } else {
+                    toml::Value::try_from(value)?.to_string()
+                }
+            }
+        };
+        Ok(text.trim_end().to_string())
+    }
+}
+
+impl IntoArg for &OutputFormat {
+    fn add_arg(self, process: &mut process::Native) {
+        process.arg(self.as_str());
+    }
+}
+
+#[test]
+fn output_format() {
+    #[derive(serde::Serialize)]
+    struct Info {
+        name: &'static str,
+        port: Option<u16>,
+    }
+    let info = Info {
+        name: "inst1",
+        port: Some(10700),
+    };
+    assert_eq!(
+        OutputFormat::Yaml.document(&info).unwrap(),
+        "name: inst1\nport: 10700"
+    );
+    assert_eq!(
+        OutputFormat::Toml.document(&info).unwrap(),
+        "name = \"inst1\"\nport = 10700"
+    );
+    assert_eq!(OutputFormat::Json.value(&"inst1").unwrap(), "\"inst1\"");
+    assert_eq!(OutputFormat::Yaml.value(&"inst1").unwrap(), "inst1");
+    assert_eq!(OutputFormat::Toml.value(&"inst1").unwrap(), "\"inst1\"");
+    assert_eq!(OutputFormat::Toml.value(&None::<String>).unwrap(), "");
+}
@@ -0,0 +1,351 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueHint;
+
+use crate::branding::MANIFEST_FILE_DISPLAY_NAME;
+use crate::commands::ExitCode;
+use crate::platform::is_schema_file;
+use crate::portable::exit_codes;
+use crate::portable::project::{self, manifest};
+use crate::print::{self, msg, Highlight};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Explicitly set a root directory for the project
+    #[arg(long, value_hint=ValueHint::DirPath)]
+    pub project_dir: Option<PathBuf>,
+}
+
+/// Keys allowed in the manifest
+enum Keys {
+    /// Any value, its type is checked when deserializing
+    Value,
+    /// Table with a fixed set of keys
+    Table(&'static [(&'static str, Keys)]),
+    /// Table with arbitrary keys, e.g. `[instances.<name>]`
+    Map(&'static Keys),
+}
+
+const BEFORE_AFTER: Keys = Keys::Table(&[("before", Keys::Value), ("after", Keys::Value)]);
+
+const INSTANCE: Keys = Keys::Table(&[("server-version", Keys::Value)]);
+
+const MANIFEST: Keys = Keys::Table(&[
+    ("instance", INSTANCE),
+    ("edgedb", INSTANCE),
+    (
+        "project",
+        Keys::Table(&[
+            ("schema-dir", Keys::Value),
+            ("branch-from-git", Keys::Value),
+        ]),
+    ),
+    (
+        "cli",
+        // fields of `ShellConfig`
+        Keys::Table(&[
+            ("expand-strings", Keys::Value),
+            ("history-size", Keys::Value),
+            ("implicit-properties", Keys::Value),
+            ("input-mode", Keys::Value),
+            ("limit", Keys::Value),
+            ("idle-transaction-timeout", Keys::Value),
+            ("input-language", Keys::Value),
+            ("output-format", Keys::Value),
+            ("display-typenames", Keys::Value),
+            ("print-stats", Keys::Value),
+            ("pager", Keys::Value),
+            ("stats", Keys::Value),
+            ("verbose-errors", Keys::Value),
+        ]),
+    ),
+    (
+        "sync",
+        Keys::Table(&[("extensions", Keys::Value), ("post-sync", Keys::Value)]),
+    ),
+    (
+        "seed",
+        Keys::Table(&[("files", Keys::Value), ("dir", Keys::Value)]),
+    ),
+    (
+        "hooks",
+        Keys::Table(&[
+            (
+                "migration",
+                Keys::Table(&[("create", BEFORE_AFTER), ("apply", BEFORE_AFTER)]),
+            ),
+            (
+                "branch",
+                Keys::Table(&[("merge", BEFORE_AFTER), ("switch", BEFORE_AFTER)]),
+            ),
+        ]),
+    ),
+    (
+        "instances",
+        Keys::Map(&Keys::Table(&[
+            ("instance", Keys::Value),
+            ("dsn", Keys::Value),
+            ("branch", Keys::Value),
+            ("confirm", Keys::Value),
+        ])),
+    ),
+    // array of tables, checked when deserializing
+    ("watch", Keys::Value),
+]);
+
+pub fn run(options: &Command) -> anyhow::Result<()> {
+    let Some(project) = project::find_project(options.project_dir.as_deref())? else {
+        anyhow::bail!("`{MANIFEST_FILE_DISPLAY_NAME}` not found, unable to check project.");
+    };
+    let problems = check(&project);
+    let file_name = project
+        .manifest
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    if problems.is_empty() {
+        print::success!("No problems found in `{file_name}`.");
+        return Ok(());
+    }
+    for problem in &problems {
+        print::error!("{problem}");
+    }
+    msg!(
+        "{} found in `{file_name}`.",
+        if problems.len() == 1 {
+            "1 problem".to_string()
+        } else {
+            format!("{} problems", problems.len())
+        }
+        .emphasize()
+    );
+    Err(ExitCode::new(exit_codes::INVALID_CONFIG).into())
+}
+
+/// Returns the list of problems found in the project
+pub fn check(project: &project::Location) -> Vec<String> {
+    let mut problems = Vec::new();
+    let text = match manifest::read_effective(&project.manifest) {
+        Ok(text) => text,
+        Err(e) => {
+            problems.push(format!("cannot read config: {e:#}"));
+            return problems;
+        }
+    };
+    let table: toml::Table = match toml::from_str(&text) {
+        Ok(table) => table,
+        Err(e) => {
+            problems.push(format!("invalid TOML: {e}"));
+            return problems;
+        }
+    };
+    unknown_keys(&table, &MANIFEST, "", &mut problems);
+    let manifest = match manifest::read(&project.manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            problems.push(format!("{e:#}"));
+            return problems;
+        }
+    };
+    check_scripts(&manifest, &project.root, &mut problems);
+    check_schema_dir(&manifest, &project.root, &mut problems);
+    problems
+}
+
+fn unknown_keys(table: &toml::Table, keys: &Keys, prefix: &str, problems: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = format!("{prefix}{key}");
+        let inner = match keys {
+            Keys::Value => continue,
+            Keys::Map(inner) => *inner,
+            Keys::Table(known) => match known.iter().find(|(k, _)| *k == key.as_str()) {
+                Some((_, inner)) => inner,
+                None => {
+                    problems.push(match suggest(key, known) {
+                        Some(s) => format!("unknown key `{path}`, did you mean `{prefix}{s}`?"),
+                        None => format!("unknown key `{path}`"),
+                    });
+                    continue;
+                }
+            },
+        };
+        // values of a wrong type are reported when deserializing
+        if let toml::Value::Table(value) = value {
+            unknown_keys(value, inner, &format!("{path}."), problems);
+        }
+    }
+}
+
+fn suggest(key: &str, known: &[(&'static str, Keys)]) -> Option<&'static str> {
+    known
+        .iter()
+        .map(|(k, _)| (strsim::jaro_winkler(key, k), *k))
+        .filter(|(confidence, _)| *confidence > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, k)| k)
+}
+
+/// Checks that scripts referred to by hooks, `post-sync` and seeds exist
+fn check_scripts(manifest: &manifest::Manifest, root: &Path, problems: &mut Vec<String>) {
+    let mut commands = Vec::new();
+    if let Some(hooks) = &manifest.hooks {
+        let migration = &hooks.migration;
+        let branch = &hooks.branch;
+        for (name, hook) in [
+            ("migration.create", &migration.create),
+            ("migration.apply", &migration.apply),
+            ("branch.merge", &branch.merge),
+            ("branch.switch", &branch.switch),
+        ] {
+            if let Some(cmd) = &hook.before {
+                commands.push((format!("hooks.{name}.before"), cmd));
+            }
+            if let Some(cmd) = &hook.after {
+                commands.push((format!("hooks.{name}.after"), cmd));
+            }
+        }
+    }
+    if let Some(cmd) = manifest.sync.as_ref().and_then(|s| s.post_sync.as_ref()) {
+        commands.push(("sync.post-sync".into(), cmd));
+    }
+    for (key, cmd) in commands {
+        if let Some(script) = script_path(cmd) {
+            if !root.join(script).exists() {
+                problems.push(format!(
+                    "`{key}` runs `{}` which does not exist",
+                    script.display()
+                ));
+            }
+        }
+    }
+
+    let Some(seed) = &manifest.seed else {
+        return;
+    };
+    for file in &seed.files {
+        let path = Path::new(file);
+        let is_query = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("edgeql" | "sql")
+        );
+        let path = if is_query {
+            Some(path)
+        } else {
+            script_path(file)
+        };
+        if let Some(path) = path {
+            if !root.join(path).exists() {
+                problems.push(format!(
+                    "`seed.files` refers to `{}` which does not exist",
+                    path.display()
+                ));
+            }
+        }
+    }
+    if let Some(dir) = &seed.dir {
+        if !root.join(dir).is_dir() {
+            problems.push(format!(
+                "`seed.dir` refers to `{}` which is not a directory",
+                dir.display()
+            ));
+        }
+    }
+}
+
+/// Returns the path of the script run by a shell command, if the command
+/// starts with a path rather than with a command looked up in `PATH`
+fn script_path(command: &str) -> Option<&Path> {
+    let program = command.split_whitespace().next()?;
+    if program.contains('/') || (cfg!(windows) && program.contains('\\')) {
+        Some(Path::new(program))
+    } else {
+        None
+    }
+}
+
+fn check_schema_dir(manifest: &manifest::Manifest, root: &Path, problems: &mut Vec<String>) {
+    let rel_dir = manifest.project().get_schema_dir();
+    let schema_dir = root.join(&rel_dir);
+    if !schema_dir.is_dir() {
+        problems.push(format!(
+            "schema directory `{}` does not exist",
+            rel_dir.display()
+        ));
+        return;
+    }
+    let entries = match fs::read_dir(&schema_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            problems.push(format!(
+                "cannot read schema directory `{}`: {e}",
+                rel_dir.display()
+            ));
+            return;
+        }
+    };
+    let mut has_schema = false;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_schema_file(&name) && !is_dir {
+            has_schema = true;
+        } else if (name == "migrations" || name == "fixups") && !is_dir {
+            problems.push(format!(
+                "`{}` must be a directory",
+                rel_dir.join(&*name).display()
+            ));
+        }
+    }
+    if !has_schema {
+        problems.push(format!(
+            "schema directory `{}` contains no schema files",
+            rel_dir.display()
+        ));
+    }
+}
+
+#[test]
+fn unknown_key_suggestions() {
+    let table: toml::Table = toml::from_str(
+        "\
+        [instance]\n\
+        server-version = \"6.0\"\n\
+        [project]\n\
+        schema_dir = \"dbschema\"\n\
+        [hooks]\n\
+        migration.create.afer = \"./gen.sh\"\n\
+        [instances.staging]\n\
+        dsn = \"gel://staging\"\n\
+        [deploy]\n\
+        target = \"prod\"\n\
+    ",
+    )
+    .unwrap();
+    let mut problems = Vec::new();
+    unknown_keys(&table, &MANIFEST, "", &mut problems);
+    assert_eq!(
+        problems,
+        vec![
+            "unknown key `deploy`",
+            "unknown key `hooks.migration.create.afer`, \
+             did you mean `hooks.migration.create.after`?",
+            "unknown key `project.schema_dir`, did you mean `project.schema-dir`?",
+        ]
+    );
+}
+
+#[test]
+fn script_paths() {
+    assert_eq!(
+        script_path("./notify.sh --all"),
+        Some(Path::new("./notify.sh"))
+    );
+    assert_eq!(
+        script_path("scripts/gen.sh"),
+        Some(Path::new("scripts/gen.sh"))
+    );
+    assert_eq!(script_path("npm run generate"), None);
+    assert_eq!(script_path(""), None);
+}
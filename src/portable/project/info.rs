@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use anyhow::Context;
 use clap::ValueHint;
 use const_format::concatcp;
 use gel_tokio::get_stash_path;
@@ -8,7 +9,8 @@ use gel_tokio::get_stash_path;
 use crate::branding::BRANDING_CLOUD;
 use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
 use crate::commands::ExitCode;
-use crate::portable::project;
+use crate::portable::options::OutputFormat;
+use crate::portable::project::{self, manifest};
 use crate::print::{self, msg, Highlight};
 use crate::table;
 
@@ -16,6 +18,16 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
     let Some(project) = project::find_project(options.project_dir.as_deref())? else {
         anyhow::bail!("`{MANIFEST_FILE_DISPLAY_NAME}` not found, unable to get project info.");
     };
+    if options.effective_manifest {
+        let text = manifest::read_effective(&project.manifest).with_context(|| {
+            format!(
+                "error reading project config `{}`",
+                project.manifest.display()
+            )
+        })?;
+        print!("{text}");
+        return Ok(());
+    }
     let stash_dir = get_stash_path(&project.root)?;
     if !stash_dir.exists() {
         msg!(
@@ -32,32 +44,31 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
         .then(|| fs::read_to_string(cloud_profile_file))
         .transpose()?;
 
+    let format = OutputFormat::resolve(options.format, options.json);
     let item = options
         .get
         .as_deref()
         .or(options.instance_name.then_some("instance-name"));
     if let Some(item) = item {
         match item {
-            "instance-name" => {
-                if options.json {
-                    println!("{}", serde_json::to_string(&instance_name)?);
-                } else {
-                    println!("{instance_name}");
-                }
-            }
-            "cloud-profile" => {
-                if options.json {
-                    println!("{}", serde_json::to_string(&cloud_profile)?);
-                } else if let Some(profile) = cloud_profile {
-                    println!("{profile}");
+            "instance-name" => match format {
+                OutputFormat::Table => println!("{instance_name}"),
+                format => println!("{}", format.value(&instance_name)?),
+            },
+            "cloud-profile" => match format {
+                OutputFormat::Table => {
+                    if let Some(profile) = cloud_profile {
+                        println!("{profile}");
+                    }
                 }
-            }
+                format => println!("{}", format.value(&cloud_profile)?),
+            },
             _ => unreachable!(),
         }
-    } else if options.json {
+    } else if format != OutputFormat::Table {
         println!(
             "{}",
-            serde_json::to_string_pretty(&JsonInfo {
+            format.document(&JsonInfo {
                 instance_name: &instance_name,
                 cloud_profile: cloud_profile.as_deref(),
                 root: &project.root,
@@ -85,10 +96,14 @@ pub struct Command {
     #[arg(long)]
     pub instance_name: bool,
 
-    /// Output in JSON format
-    #[arg(long)]
+    /// Output in JSON format (shortcut to `--format json`)
+    #[arg(long, conflicts_with = "format")]
     pub json: bool,
 
+    /// Output format
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
     #[arg(long, value_parser=[
         "instance-name",
         "cloud-profile",
@@ -97,6 +112,11 @@ pub struct Command {
     ///
     /// * `instance-name` -- Name of the listance the project is linked to
     pub get: Option<String>,
+
+    /// Print the project manifest with the local overrides from
+    /// `gel.local.toml` (or `edgedb.local.toml`) applied.
+    #[arg(long, conflicts_with_all=&["instance_name", "json", "format", "get"])]
+    pub effective_manifest: bool,
 }
 
 #[derive(serde::Serialize)]
@@ -17,6 +17,7 @@ use crate::branding::QUERY_TAG;
 use crate::branding::{BRANDING, BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
 use crate::cloud;
 use crate::cloud::client::CloudClient;
+use crate::cloud::ops::CloudTier;
 use crate::commands::ExitCode;
 use crate::connect::Connection;
 use crate::connect::Connector;
@@ -28,7 +29,7 @@ use crate::portable::instance::control;
 use crate::portable::instance::create;
 use crate::portable::local::{allocate_port, InstanceInfo, Paths};
 use crate::portable::options::InstanceName;
-use crate::portable::options::{CloudInstanceBillables, CloudInstanceParams};
+use crate::portable::options::{billable_unit, CloudInstanceBillables, CloudInstanceParams};
 use crate::portable::platform::optional_docker_check;
 use crate::portable::project;
 use crate::portable::project::manifest;
@@ -56,6 +57,11 @@ pub fn run(options: &Command, opts: &crate::options::Options) -> anyhow::Result<
         );
     }
 
+    let mut options = options.clone();
+    options.non_interactive |= options.unattended_verify;
+    let options = &options;
+    options.check_flags()?;
+
     let project = project::find_project(options.project_dir.as_deref())?;
 
     if let Some(project) = project {
@@ -113,6 +119,25 @@ pub struct Command {
     #[arg(long, short = 'd')]
     pub database: Option<String>,
 
+    /// Specify the default branch for the project to use on that instance
+    /// (same as `--database` for EdgeDB 5 and later)
+    #[arg(long, conflicts_with = "database")]
+    pub branch: Option<String>,
+
+    /// Subscription tier of the new Cloud instance
+    #[arg(long, value_enum, value_name = "tier", conflicts_with = "link")]
+    pub cloud_tier: Option<CloudTier>,
+
+    /// Region of the new Cloud instance (default: the closest one)
+    #[arg(long, value_name = "region", conflicts_with = "link")]
+    pub cloud_region: Option<String>,
+
+    /// The size of compute to be allocated for the new Cloud instance in
+    /// Compute Units
+    #[arg(long, value_name = "number", value_parser = billable_unit)]
+    #[arg(conflicts_with = "link")]
+    pub cloud_compute_size: Option<String>,
+
     /// Deprecated parameter, does nothing.
     #[arg(long, hide = true)]
     pub server_start_conf: Option<create::StartConf>,
@@ -128,6 +153,56 @@ pub struct Command {
     /// Initialize in in non-interactive mode (accepting all defaults)
     #[arg(long)]
     pub non_interactive: bool,
+
+    /// Fail instead of warning if the linked instance doesn't satisfy
+    /// the manifest: its version must match, the extensions listed in
+    /// the `[sync]` table must be installed and the branch must exist.
+    /// Prints a JSON report of the checks to stdout. Implies
+    /// `--non-interactive`.
+    #[arg(long, requires = "link")]
+    pub unattended_verify: bool,
+}
+
+impl Command {
+    /// Branch or database name specified on the command line.
+    fn database(&self) -> Option<&String> {
+        self.branch.as_ref().or(self.database.as_ref())
+    }
+
+    fn has_cloud_params(&self) -> bool {
+        self.cloud_tier.is_some()
+            || self.cloud_region.is_some()
+            || self.cloud_compute_size.is_some()
+    }
+
+    /// Checks that flags are consistent, and that in non-interactive mode
+    /// every decision which has no default is supplied by a flag.
+    fn check_flags(&self) -> anyhow::Result<()> {
+        let is_cloud = matches!(self.server_instance, Some(InstanceName::Cloud { .. }));
+        if self.has_cloud_params() && self.server_instance.is_some() && !is_cloud {
+            anyhow::bail!(
+                "`--cloud-tier`, `--cloud-region` and `--cloud-compute-size` \
+                 are only applicable to {BRANDING_CLOUD} instances"
+            );
+        }
+        if self.cloud_tier == Some(CloudTier::Free) && self.cloud_compute_size.is_some() {
+            anyhow::bail!("`--cloud-compute-size` can only be specified for Pro instances");
+        }
+        if !self.non_interactive {
+            return Ok(());
+        }
+        let mut missing = Vec::new();
+        if self.server_instance.is_none() && (self.link || self.has_cloud_params()) {
+            missing.push("--server-instance");
+        }
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "the following options are required in non-interactive mode: {}",
+                missing.join(", ")
+            );
+        }
+        Ok(())
+    }
 }
 
 pub fn init_existing(
@@ -170,19 +245,13 @@ pub fn init_existing(
         inst.check_version(&ver_query);
 
         if matches!(name, InstanceName::Cloud { .. }) {
-            if options.non_interactive {
-                inst.database = Some(options.database.clone().unwrap_or(
-                    get_default_branch_or_database(specific_version, &project.root),
-                ));
-            } else {
-                inst.database = Some(ask_database_or_branch(
-                    specific_version,
-                    &project.root,
-                    options,
-                )?);
-            }
+            inst.database = Some(ask_database_or_branch(
+                specific_version,
+                &project.root,
+                options,
+            )?);
         } else {
-            inst.database.clone_from(&options.database);
+            inst.database = options.database().cloned();
         }
         return do_link(&inst, options, &stash_dir);
     }
@@ -194,7 +263,7 @@ pub fn init_existing(
             let ver = cloud::versions::get_version(&ver_query, &client)
                 .with_context(|| "could not initialize project")?;
             ver::print_version_hint(&ver, &ver_query);
-            let database = ask_database(&project.root, options)?;
+            let database = ask_database_or_branch(&ver, &project.root, options)?;
 
             table::settings(&[
                 ("Project directory", project.root.display().to_string()),
@@ -250,8 +319,11 @@ pub fn init_existing(
             let specific_version = &pkg.version.specific();
             ver::print_version_hint(specific_version, &ver_query);
 
-            let mut branch: Option<String> = None;
-            if !options.non_interactive && specific_version.major >= 5 {
+            let mut branch = options
+                .database()
+                .filter(|_| specific_version.major >= 5)
+                .cloned();
+            if branch.is_none() && !options.non_interactive && specific_version.major >= 5 {
                 branch = Some(ask_branch()?);
             }
 
@@ -339,6 +411,7 @@ fn do_init(
                     from_instance: None,
                 },
                 port: Some(port),
+                data_dir: None,
                 start_conf: None,
                 default_user: None,
                 non_interactive: true,
@@ -353,6 +426,7 @@ fn do_init(
             name: name.into(),
             installation: None,
             port,
+            data_dir: None,
         })?;
         project::InstanceKind::Wsl
     } else {
@@ -362,6 +436,7 @@ fn do_init(
             name: name.into(),
             installation: Some(inst),
             port,
+            data_dir: None,
         };
         create::bootstrap(
             &paths,
@@ -383,6 +458,8 @@ fn do_init(
                     foreground: false,
                     auto_restart: false,
                     managed_by: None,
+                    wait: false,
+                    wait_timeout: 60,
                 })?;
             }
         }
@@ -394,7 +471,7 @@ fn do_init(
         project_dir: project_dir.into(),
         schema_dir: schema_dir.into(),
         instance,
-        database: options.database.clone(),
+        database: options.database().cloned(),
     };
 
     let mut stash = project::StashDir::new(project_dir, name);
@@ -428,9 +505,14 @@ fn do_cloud_init(
         name: name.clone(),
         org: org.clone(),
         version: version.to_string(),
-        region: None,
-        tier: None,
-        requested_resources: None,
+        region: options.cloud_region.clone(),
+        tier: options.cloud_tier,
+        requested_resources: options.cloud_compute_size.as_ref().map(|size| {
+            vec![crate::cloud::ops::CloudInstanceResourceRequest {
+                name: "compute".to_string(),
+                value: size.clone(),
+            }]
+        }),
         source_instance_id: None,
         source_backup_id: None,
     };
@@ -504,17 +586,21 @@ fn link(
         if options.non_interactive {
             inst.database = Some(
                 options
-                    .database
-                    .clone()
+                    .database()
+                    .cloned()
                     .unwrap_or(directory_to_name(&project.root, "edgedb").to_owned()),
             )
         } else {
             inst.database = Some(ask_database(&project.root, options)?);
         }
     } else {
-        inst.database.clone_from(&options.database);
+        inst.database = options.database().cloned();
+    }
+    if options.unattended_verify {
+        project::verify::verify(&inst, &manifest)?;
+    } else {
+        inst.check_version(ver_query);
     }
-    inst.check_version(ver_query);
     do_link(&inst, options, &stash_dir)
 }
 
@@ -539,13 +625,13 @@ fn do_link(
 
     print::success!("Project linked");
     if let Some(dir) = &options.project_dir {
-        eprintln!(
+        msg!(
             "To connect to {}, navigate to {} and run `{BRANDING_CLI_CMD}`",
             inst.name,
             dir.display()
         );
     } else {
-        eprintln!("To connect to {}, run `{BRANDING_CLI_CMD}`", inst.name);
+        msg!("To connect to {}, run `{BRANDING_CLI_CMD}`", inst.name);
     }
 
     Ok(project::ProjectInfo {
@@ -571,7 +657,7 @@ fn init_new(
     config_path: PathBuf,
     opts: &crate::options::Options,
 ) -> anyhow::Result<project::ProjectInfo> {
-    eprintln!(
+    msg!(
         "No {MANIFEST_FILE_DISPLAY_NAME} found in `{}` or above",
         project_dir.display()
     );
@@ -587,7 +673,7 @@ fn init_new(
     }
 
     if options.non_interactive {
-        eprintln!("Initializing new project...");
+        msg!("Initializing new project...");
     } else {
         let mut q = question::Confirm::new("Do you want to initialize a new project?");
         q.default(true);
@@ -614,25 +700,25 @@ fn init_new(
                 server_version: version_query,
             },
             project: Default::default(),
+            cli: None,
+            sync: None,
+            seed: None,
+            hooks: None,
+            instances: Default::default(),
+            watch: Vec::new(),
         };
         project::manifest::write(&config_path, &manifest)?;
         if !schema_files {
             project::write_schema_default(&schema_dir_path, &manifest.instance.server_version)?;
         }
         if matches!(inst_name, InstanceName::Cloud { .. }) {
-            if options.non_interactive {
-                inst.database = Some(options.database.clone().unwrap_or(
-                    get_default_branch_or_database(specific_version, project_dir),
-                ));
-            } else {
-                inst.database = Some(ask_database_or_branch(
-                    specific_version,
-                    project_dir,
-                    options,
-                )?);
-            }
+            inst.database = Some(ask_database_or_branch(
+                specific_version,
+                project_dir,
+                options,
+            )?);
         } else {
-            inst.database.clone_from(&options.database);
+            inst.database = options.database().cloned();
         }
         return do_link(&inst, options, &stash_dir);
     };
@@ -676,6 +762,12 @@ fn init_new(
                     server_version: ver_query,
                 },
                 project: Default::default(),
+                cli: None,
+                sync: None,
+                seed: None,
+                hooks: None,
+                instances: Default::default(),
+                watch: Vec::new(),
             };
             project::manifest::write(&config_path, &manifest)?;
             if !schema_files {
@@ -700,8 +792,11 @@ fn init_new(
             let specific_version = &pkg.version.specific();
             ver::print_version_hint(specific_version, &ver_query);
 
-            let mut branch: Option<String> = None;
-            if !options.non_interactive && specific_version.major >= 5 {
+            let mut branch = options
+                .database()
+                .filter(|_| specific_version.major >= 5)
+                .cloned();
+            if branch.is_none() && !options.non_interactive && specific_version.major >= 5 {
                 branch = Some(ask_branch()?);
             }
 
@@ -740,6 +835,12 @@ fn init_new(
                     server_version: ver_query,
                 },
                 project: Default::default(),
+                cli: None,
+                sync: None,
+                seed: None,
+                hooks: None,
+                instances: Default::default(),
+                watch: Vec::new(),
             };
 
             project::manifest::write(&config_path, &manifest)?;
@@ -863,6 +964,12 @@ fn ask_database_or_branch(
     project_dir: &Path,
     options: &Command,
 ) -> anyhow::Result<String> {
+    if let Some(name) = options.database() {
+        return Ok(name.clone());
+    }
+    if options.non_interactive {
+        return Ok(get_default_branch_or_database(version, project_dir));
+    }
     if version.major >= 5 {
         return ask_branch();
     }
@@ -871,7 +978,7 @@ fn ask_database_or_branch(
 }
 
 fn ask_database(project_dir: &Path, options: &Command) -> anyhow::Result<String> {
-    if let Some(name) = &options.database {
+    if let Some(name) = options.database() {
         return Ok(name.clone());
     }
     let default = directory_to_name(project_dir, "edgedb");
@@ -1152,7 +1259,7 @@ async fn create_database_async(inst: &project::Handle<'_>) -> anyhow::Result<()>
 }
 
 #[tokio::main(flavor = "current_thread")]
-async fn migrate(inst: &project::Handle<'_>, ask_for_running: bool) -> anyhow::Result<()> {
+pub async fn migrate(inst: &project::Handle<'_>, ask_for_running: bool) -> anyhow::Result<()> {
     migrate_async(inst, ask_for_running).await
 }
 
@@ -1232,8 +1339,17 @@ async fn migrate_async(inst: &project::Handle<'_>, ask_for_running: bool) -> any
             },
             quiet: false,
             to_revision: None,
+            down_to: None,
             dev_mode: false,
             single_transaction: false,
+            statement_timeout: None,
+            lock_timeout: None,
+            force_unsafe_ddl: false,
+            no_ddl_locking: false,
+            session_config: Vec::new(),
+            tag: None,
+            plan: false,
+            json: false,
             conn: None,
         },
     )
@@ -1,6 +1,6 @@
 //# Project manifest
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -9,8 +9,10 @@ use fn_error_context::context;
 
 use toml::Spanned;
 
-use crate::branding::MANIFEST_FILE_DISPLAY_NAME;
+use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
 use crate::commands::ExitCode;
+use crate::config::ShellConfig;
+use crate::hint::HintExt;
 use crate::platform::tmp_file_path;
 use crate::portable::exit_codes;
 use crate::portable::repository::{Channel, Query};
@@ -20,6 +22,24 @@ use crate::print::{self, msg, Highlight};
 pub struct Manifest {
     pub instance: Instance,
     pub project: Option<Project>,
+    /// Project-specific overrides of the CLI configuration (`[cli]` table).
+    #[serde(skip)]
+    pub cli: Option<ShellConfig>,
+    /// Steps performed by `project sync` (`[sync]` table).
+    #[serde(skip)]
+    pub sync: Option<SyncConfig>,
+    /// Seed data run by `seed run` (`[seed]` table).
+    #[serde(skip)]
+    pub seed: Option<SeedConfig>,
+    /// Shell commands run around CLI actions (`[hooks]` table).
+    #[serde(skip)]
+    pub hooks: Option<HooksConfig>,
+    /// Named remote instances selected with `--env` (`[instances.<name>]`).
+    #[serde(skip)]
+    pub instances: BTreeMap<String, EnvironmentConfig>,
+    /// Targets updated by `watch` (`[[watch]]` entries).
+    #[serde(skip)]
+    pub watch: Vec<WatchTarget>,
 }
 
 impl Manifest {
@@ -39,6 +59,131 @@ pub struct Instance {
 #[serde(rename_all = "kebab-case")]
 pub struct Project {
     pub schema_dir: Option<PathBuf>,
+    /// Derive the database branch name from the current git branch in
+    /// `branch switch`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub branch_from_git: bool,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyncConfig {
+    /// Extensions that must be installed in the instance.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Shell command run in the project directory after syncing.
+    #[serde(default)]
+    pub post_sync: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SeedConfig {
+    /// Seed files (`*.edgeql`, `*.sql`) and shell commands, in the order
+    /// they are run. Paths are relative to the project root.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Directory with `*.edgeql` and `*.sql` seed files, run in order of
+    /// their names. Used when `files` is empty. Defaults to `<schema-dir>/seeds`.
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EnvironmentConfig {
+    /// Instance name, credentials of which are used to connect.
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// DSN to connect to, instead of `instance`.
+    #[serde(default)]
+    pub dsn: Option<String>,
+    /// Branch to connect to, unless `--branch` is specified.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Ask for confirmation before commands modifying the instance.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WatchTarget {
+    /// Name used in `needs` of other entries. Defaults to the name of the
+    /// generator.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Generator of the `@gel/generate` (`@edgedb/generate`) npm package,
+    /// run after each schema update.
+    pub generate: Generator,
+    /// Entries which must complete successfully before this one is run.
+    #[serde(default)]
+    pub needs: Vec<String>,
+}
+
+impl WatchTarget {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.generate.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Generator {
+    Queries,
+    Interfaces,
+    EdgeqlJs,
+}
+
+impl Generator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::Queries => "queries",
+            Generator::Interfaces => "interfaces",
+            Generator::EdgeqlJs => "edgeql-js",
+        }
+    }
+}
+
+/// Hooks are written as dotted keys, e.g. `migration.create.after = "..."`.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub migration: MigrationHooks,
+    #[serde(default)]
+    pub branch: BranchHooks,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MigrationHooks {
+    /// Run around `migration create`.
+    #[serde(default)]
+    pub create: BeforeAfter,
+    /// Run around each migration applied.
+    #[serde(default)]
+    pub apply: BeforeAfter,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BranchHooks {
+    /// Run around `branch merge`.
+    #[serde(default)]
+    pub merge: BeforeAfter,
+    /// Run around `branch switch`.
+    #[serde(default)]
+    pub switch: BeforeAfter,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BeforeAfter {
+    #[serde(default)]
+    pub before: Option<String>,
+    #[serde(default)]
+    pub after: Option<String>,
 }
 
 impl Project {
@@ -60,11 +205,144 @@ impl Project {
     }
 }
 
+/// Path of the local override manifest for `path`, e.g. `gel.local.toml`
+/// next to `gel.toml`. It is meant to be kept out of version control.
+pub fn local_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.local.toml"))
+}
+
+/// Reads the manifest text with the local override manifest (if any)
+/// merged over it.
+///
+/// Tables are merged recursively, any other value (including arrays)
+/// from the local manifest replaces the one in the main manifest.
+pub fn read_effective(path: &Path) -> anyhow::Result<String> {
+    let text = fs::read_to_string(path)?;
+    let local = local_path(path);
+    if !local.exists() {
+        return Ok(text);
+    }
+    let mut base = parse_table(&text)?;
+    let over = fs::read_to_string(&local)
+        .map_err(anyhow::Error::from)
+        .and_then(|text| parse_table(&text))
+        .with_context(|| format!("error reading local config `{}`", local.display()))?;
+    merge_tables(&mut base, over);
+    Ok(toml::to_string(&base)?)
+}
+
+fn parse_table(text: &str) -> anyhow::Result<toml::Table> {
+    let mut table: toml::Table = toml::from_str(text)?;
+    // `[edgedb]` is an alias of `[instance]`, make sure both files
+    // refer to the same table before merging
+    if let Some(instance) = table.remove("edgedb") {
+        table.entry("instance").or_insert(instance);
+    }
+    Ok(table)
+}
+
+fn merge_tables(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(dest)), toml::Value::Table(src)) => {
+                merge_tables(dest, src);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Orders `[[watch]]` entries so that every entry comes after the entries
+/// it `needs`. Otherwise the order of the manifest is kept.
+fn sort_watch(targets: Vec<WatchTarget>) -> anyhow::Result<Vec<WatchTarget>> {
+    let mut names = BTreeSet::new();
+    for target in &targets {
+        if !names.insert(target.name()) {
+            return Err(anyhow::anyhow!(
+                "duplicate `[[watch]]` entry {:?}",
+                target.name()
+            ))
+            .hint("Set distinct `name` for the entries.")?;
+        }
+    }
+    for target in &targets {
+        if let Some(need) = target.needs.iter().find(|n| !names.contains(&n[..])) {
+            anyhow::bail!(
+                "`[[watch]]` entry {:?} needs unknown entry {:?}",
+                target.name(),
+                need
+            );
+        }
+    }
+
+    let mut pending = targets;
+    let mut sorted = Vec::with_capacity(pending.len());
+    let mut done = BTreeSet::new();
+    while !pending.is_empty() {
+        let Some(idx) = pending
+            .iter()
+            .position(|t| t.needs.iter().all(|n| done.contains(n)))
+        else {
+            let names = pending.iter().map(|t| t.name()).collect::<Vec<_>>();
+            anyhow::bail!(
+                "dependency cycle in `needs` of `[[watch]]` entries: {}",
+                names.join(", ")
+            );
+        };
+        let target = pending.remove(idx);
+        done.insert(target.name().to_string());
+        sorted.push(target);
+    }
+    Ok(sorted)
+}
+
+/// Deserializes the effective manifest. Errors are reported against the
+/// file they come from, rather than the merged text.
+fn deserialize_effective(path: &Path) -> anyhow::Result<SrcManifest> {
+    let text = fs::read_to_string(path)?;
+    if !local_path(path).exists() {
+        return Ok(serde_path_to_error::deserialize(
+            toml::de::Deserializer::new(&text),
+        )?);
+    }
+    let merged = read_effective(path)?;
+    match serde_path_to_error::deserialize(toml::de::Deserializer::new(&merged)) {
+        Ok(val) => Ok(val),
+        Err(e) => {
+            // the error is in the main manifest if it fails on its own,
+            // line numbers are only meaningful in that case
+            serde_path_to_error::deserialize::<_, SrcManifest>(toml::de::Deserializer::new(&text))?;
+            let local = local_path(path);
+            Err(anyhow::anyhow!("{}: {}", e.path(), e.inner().message()))
+                .with_context(|| format!("invalid value in local config `{}`", local.display()))
+        }
+    }
+}
+
+/// Reads only the `[cli]` table of the manifest, so that problems in the
+/// other tables aren't reported by every command run in the project.
+#[context("error reading project config `{}`", path.display())]
+pub fn read_cli(path: &Path) -> anyhow::Result<Option<ShellConfig>> {
+    cli_table(&read_effective(path)?)
+}
+
+fn cli_table(text: &str) -> anyhow::Result<Option<ShellConfig>> {
+    #[derive(serde::Deserialize)]
+    struct CliOnly {
+        #[serde(default)]
+        cli: Option<ShellConfig>,
+    }
+    let val: CliOnly = serde_path_to_error::deserialize(toml::de::Deserializer::new(text))?;
+    Ok(val.cli)
+}
+
 #[context("error reading project config `{}`", path.display())]
 pub fn read(path: &Path) -> anyhow::Result<Manifest> {
-    let text = fs::read_to_string(path)?;
-    let toml = toml::de::Deserializer::new(&text);
-    let val: SrcManifest = serde_path_to_error::deserialize(toml)?;
+    let val = deserialize_effective(path)
+        .with_hint(|| format!("Run `{BRANDING_CLI_CMD} project check` to list all problems"))?;
     warn_extra(&val.extra, "");
     warn_extra(&val.instance.extra, "instance.");
 
@@ -80,11 +358,22 @@ pub fn read(path: &Path) -> anyhow::Result<Manifest> {
                 }),
         },
         project: Some(Project {
+            branch_from_git: val
+                .project
+                .as_ref()
+                .and_then(|p| p.branch_from_git)
+                .unwrap_or(false),
             schema_dir: val
                 .project
                 .and_then(|p| p.schema_dir)
                 .map(|s| PathBuf::from(s.into_inner())),
         }),
+        cli: val.cli,
+        sync: val.sync,
+        seed: val.seed,
+        hooks: val.hooks,
+        instances: val.instances,
+        watch: sort_watch(val.watch)?,
     });
 }
 
@@ -167,8 +456,38 @@ where
     }
 }
 
+/// Fails if the local override manifest sets `server-version`: the
+/// version is written to the main manifest, which is under version
+/// control, and wouldn't take effect anyway.
+pub fn check_server_ver_not_overridden(path: &Path) -> anyhow::Result<()> {
+    let local = local_path(path);
+    if !local.exists() {
+        return Ok(());
+    }
+    let table = parse_table(&fs::read_to_string(&local)?)
+        .with_context(|| format!("error reading local config `{}`", local.display()))?;
+    let overridden = table
+        .get("instance")
+        .and_then(|instance| instance.get("server-version"))
+        .is_some();
+    if overridden {
+        return Err(anyhow::anyhow!(
+            "`server-version` is overridden in `{}`",
+            local.display()
+        ))
+        .with_hint(|| {
+            format!(
+                "Remove `server-version` from the local config \
+                 to change the version in {MANIFEST_FILE_DISPLAY_NAME}."
+            )
+        })?;
+    }
+    Ok(())
+}
+
 #[context("cannot modify `{}`", config.display())]
 pub fn modify_server_ver(config: &Path, ver: &Query) -> anyhow::Result<bool> {
+    check_server_ver_not_overridden(config)?;
     msg!(
         "Setting `server-version = {}` in `{}`",
         format_args!("{:?}", ver.as_config_value()).emphasize(),
@@ -202,6 +521,14 @@ pub struct SrcManifest {
     #[serde(alias = "edgedb")]
     pub instance: SrcInstance,
     pub project: Option<SrcProject>,
+    pub cli: Option<ShellConfig>,
+    pub sync: Option<SyncConfig>,
+    pub seed: Option<SeedConfig>,
+    pub hooks: Option<HooksConfig>,
+    #[serde(default)]
+    pub instances: BTreeMap<String, EnvironmentConfig>,
+    #[serde(default)]
+    pub watch: Vec<WatchTarget>,
     #[serde(flatten)]
     pub extra: BTreeMap<String, toml::Value>,
 }
@@ -220,6 +547,8 @@ pub struct SrcInstance {
 pub struct SrcProject {
     #[serde(default)]
     pub schema_dir: Option<toml::Spanned<String>>,
+    #[serde(default)]
+    pub branch_from_git: Option<bool>,
     #[serde(flatten)]
     #[allow(dead_code)]
     pub extra: BTreeMap<String, toml::Value>,
@@ -369,4 +698,265 @@ mod test {
     fn modify(src: &str, ver: &str) -> Option<String> {
         set_toml_version(src, &ver.parse().unwrap()).unwrap()
     }
+
+    #[test]
+    fn local_override() {
+        let mut base = super::parse_table(
+            "\
+            [edgedb]\n\
+            server-version = \"6.0\"\n\
+            [project]\n\
+            schema-dir = \"dbschema\"\n\
+            [sync]\n\
+            extensions = [\"pgvector\", \"auth\"]\n\
+        ",
+        )
+        .unwrap();
+        let over = super::parse_table(
+            "\
+            [instance]\n\
+            server-version = \"nightly\"\n\
+            [sync]\n\
+            extensions = [\"auth\"]\n\
+            [hooks]\n\
+            migration.apply.after = \"./notify.sh\"\n\
+        ",
+        )
+        .unwrap();
+        super::merge_tables(&mut base, over);
+        let merged = toml::to_string(&base).unwrap();
+        let toml = toml::de::Deserializer::new(&merged);
+        let parsed: super::SrcManifest = serde_path_to_error::deserialize(toml).unwrap();
+        assert_eq!(
+            parsed.instance.server_version.unwrap().into_inner(),
+            "nightly".parse().unwrap()
+        );
+        assert_eq!(
+            parsed.project.unwrap().schema_dir.unwrap().into_inner(),
+            "dbschema"
+        );
+        assert_eq!(parsed.sync.unwrap().extensions, vec!["auth"]);
+        assert_eq!(
+            parsed.hooks.unwrap().migration.apply.after.as_deref(),
+            Some("./notify.sh")
+        );
+    }
+
+    #[test]
+    fn local_override_errors() {
+        let tmp_dir = tempfile::tempdir().expect("tmpdir");
+        let path = tmp_dir.path().join("gel.toml");
+        let local = tmp_dir.path().join("gel.local.toml");
+        std::fs::write(&path, "[instance]\nserver-version = \"6.0\"\n").unwrap();
+        std::fs::write(&local, "[project]\nschema-dir = 1\n").unwrap();
+        let err = super::read(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("invalid value in local config"));
+        assert!(format!("{err:#}").contains("project.schema-dir"));
+        super::check_server_ver_not_overridden(&path).unwrap();
+
+        std::fs::write(&local, "[instance]\nserver-version = \"nightly\"\n").unwrap();
+        assert!(super::check_server_ver_not_overridden(&path).is_err());
+        assert!(super::modify_server_ver(&path, &"6.1".parse().unwrap()).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[instance]\nserver-version = \"6.0\"\n"
+        );
+    }
+
+    #[test]
+    fn local_path() {
+        assert_eq!(
+            super::local_path(std::path::Path::new("/proj/gel.toml")),
+            std::path::Path::new("/proj/gel.local.toml")
+        );
+    }
+
+    #[test]
+    fn hooks() {
+        let data = "\
+            [instance]\n\
+            server-version = \"6.0\"\n\
+            [hooks]\n\
+            migration.create.after = \"npm run generate\"\n\
+            branch.merge.before = \"./check.sh\"\n\
+        ";
+        let toml = toml::de::Deserializer::new(data);
+        let parsed: super::SrcManifest = serde_path_to_error::deserialize(toml).unwrap();
+        let hooks = parsed.hooks.unwrap();
+        assert_eq!(
+            hooks.migration.create.after.as_deref(),
+            Some("npm run generate")
+        );
+        assert_eq!(hooks.migration.create.before, None);
+        assert_eq!(hooks.migration.apply.after, None);
+        assert_eq!(hooks.branch.merge.before.as_deref(), Some("./check.sh"));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn instances() {
+        let data = "\
+            [instance]\n\
+            server-version = \"6.0\"\n\
+            [instances.staging]\n\
+            instance = \"myorg/staging\"\n\
+            branch = \"main\"\n\
+            [instances.production]\n\
+            dsn = \"gel://prod.example.com\"\n\
+            confirm = true\n\
+        ";
+        let toml = toml::de::Deserializer::new(data);
+        let parsed: super::SrcManifest = serde_path_to_error::deserialize(toml).unwrap();
+        let staging = &parsed.instances["staging"];
+        assert_eq!(staging.instance.as_deref(), Some("myorg/staging"));
+        assert_eq!(staging.branch.as_deref(), Some("main"));
+        assert!(!staging.confirm);
+        let production = &parsed.instances["production"];
+        assert_eq!(production.dsn.as_deref(), Some("gel://prod.example.com"));
+        assert!(production.confirm);
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn watch() {
+        use super::Generator;
+
+        let data = "\
+            [instance]\n\
+            server-version = \"6.0\"\n\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            [[watch]]\n\
+            generate = \"edgeql-js\"\n\
+        ";
+        let toml = toml::de::Deserializer::new(data);
+        let parsed: super::SrcManifest = serde_path_to_error::deserialize(toml).unwrap();
+        let generators: Vec<_> = parsed.watch.iter().map(|w| w.generate).collect();
+        assert_eq!(generators, vec![Generator::Queries, Generator::EdgeqlJs]);
+        assert!(parsed.extra.is_empty());
+
+        let data = "\
+            [instance]\n\
+            [[watch]]\n\
+            generate = \"types\"\n\
+        ";
+        let toml = toml::de::Deserializer::new(data);
+        assert!(serde_path_to_error::deserialize::<_, super::SrcManifest>(toml).is_err());
+    }
+
+    fn watch_order(data: &str) -> anyhow::Result<Vec<String>> {
+        let data = format!("[instance]\n{data}");
+        let toml = toml::de::Deserializer::new(&data);
+        let parsed: super::SrcManifest = serde_path_to_error::deserialize(toml).unwrap();
+        let sorted = super::sort_watch(parsed.watch)?;
+        Ok(sorted.iter().map(|t| t.name().to_string()).collect())
+    }
+
+    #[test]
+    fn watch_needs() {
+        let order = watch_order(
+            "\
+            [[watch]]\n\
+            name = \"check\"\n\
+            generate = \"queries\"\n\
+            needs = [\"edgeql-js\", \"interfaces\"]\n\
+            [[watch]]\n\
+            generate = \"interfaces\"\n\
+            needs = [\"edgeql-js\"]\n\
+            [[watch]]\n\
+            generate = \"edgeql-js\"\n\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+        ",
+        )
+        .unwrap();
+        assert_eq!(order, vec!["edgeql-js", "interfaces", "check", "queries"]);
+
+        // no dependencies: manifest order is kept
+        let order = watch_order(
+            "\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            [[watch]]\n\
+            generate = \"edgeql-js\"\n\
+        ",
+        )
+        .unwrap();
+        assert_eq!(order, vec!["queries", "edgeql-js"]);
+    }
+
+    #[test]
+    fn watch_needs_errors() {
+        let err = watch_order(
+            "\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            needs = [\"interfaces\"]\n\
+            [[watch]]\n\
+            generate = \"interfaces\"\n\
+            needs = [\"queries\"]\n\
+            [[watch]]\n\
+            generate = \"edgeql-js\"\n\
+        ",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "dependency cycle in `needs` of `[[watch]]` entries: queries, interfaces"
+        );
+
+        let err = watch_order(
+            "\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            needs = [\"queries\"]\n\
+        ",
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("dependency cycle"));
+
+        let err = watch_order(
+            "\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            needs = [\"codegen\"]\n\
+        ",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`[[watch]]` entry \"queries\" needs unknown entry \"codegen\""
+        );
+
+        let err = watch_order(
+            "\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+        ",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "duplicate `[[watch]]` entry \"queries\"");
+    }
+
+    #[test]
+    fn cli_table() {
+        let cli = super::cli_table(
+            "\
+            [cli]\n\
+            limit = 10\n\
+            [hooks]\n\
+            project.init.after = 1\n\
+            [[watch]]\n\
+            generate = \"queries\"\n\
+            needs = [\"queries\"]\n\
+        ",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(cli.limit, Some(10));
+        assert!(super::cli_table("[project]\n").unwrap().is_none());
+        assert!(super::cli_table("[cli]\nlimit = \"ten\"\n").is_err());
+    }
 }
@@ -1,8 +1,12 @@
+pub mod check;
 pub mod info;
 pub mod init;
 pub mod manifest;
+pub mod relink;
+pub mod sync;
 pub mod unlink;
 pub mod upgrade;
+pub mod verify;
 
 use std::collections::HashMap;
 use std::fs;
@@ -14,18 +18,20 @@ use fn_error_context::context;
 
 use gel_tokio::Builder;
 
+use crate::branding::BRANDING_CLI_CMD;
 use crate::branding::QUERY_TAG;
 use crate::branding::{BRANDING_SCHEMA_FILE_EXT, MANIFEST_FILE_DISPLAY_NAME};
 use crate::cloud::client::CloudClient;
 use crate::connect::Connection;
+use crate::hint::HintExt;
 use crate::platform::{bytes_to_path, path_bytes};
-use crate::platform::{config_dir, is_schema_file, symlink_dir, tmp_file_path};
+use crate::platform::{config_dir, is_schema_file, lock, symlink_dir, tmp_file_path};
 use crate::portable::local::InstanceInfo;
 use crate::portable::options::InstanceName;
 use crate::portable::repository::Query;
 use crate::portable::ver;
-use crate::print;
 use crate::print::AsRelativeToCurrentDir;
+use crate::print::{self, msg};
 
 pub fn run(cmd: &Command, options: &crate::options::Options) -> anyhow::Result<()> {
     use crate::portable::project::Subcommands::*;
@@ -33,8 +39,11 @@ pub fn run(cmd: &Command, options: &crate::options::Options) -> anyhow::Result<(
     match &cmd.subcommand {
         Init(c) => init::run(c, options),
         Unlink(c) => unlink::run(c, options),
+        Relink(c) => relink::run(c),
         Info(c) => info::run(c),
+        Check(c) => check::run(c),
         Upgrade(c) => upgrade::run(c, options),
+        Sync(c) => sync::run(c, options),
     }
 }
 
@@ -54,8 +63,17 @@ pub enum Subcommands {
     ///
     /// Use [`BRANDING_CLI_CMD`] project init to relink.
     Unlink(unlink::Command),
+    /// Link the project to its instance again after the project directory
+    /// was moved or renamed
+    Relink(relink::Command),
     /// Get various metadata about project instance
     Info(info::Command),
+    /// Validate `{gel,edgedb}.toml` and the project layout
+    ///
+    /// Reports unknown keys, invalid values, missing hook and seed scripts
+    /// and problems with the schema directory. Exits with a non-zero code
+    /// if any problems are found, for use in CI.
+    Check(check::Command),
     /// Upgrade [`BRANDING`] instance used for current project
     ///
     /// Data is preserved using a dump/restore mechanism.
@@ -64,6 +82,12 @@ pub enum Subcommands {
     ///
     /// Note: May fail if lower version is specified (e.g. moving from nightly to stable).
     Upgrade(upgrade::Command),
+    /// Bring the project instance up to date after pulling changes
+    ///
+    /// Starts the instance if needed, installs extensions listed in the
+    /// `[sync]` section of `{gel,edgedb}.toml`, applies migrations and runs
+    /// the `post-sync` hook.
+    Sync(sync::Command),
 }
 
 const DEFAULT_SCHEMA: &str = "\
@@ -133,7 +157,25 @@ impl<'a> StashDir<'a> {
     }
     #[context("error writing project dir {:?}", dir)]
     fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        lock::with_lock(dir, || self.write_locked(dir))
+    }
+
+    fn write_locked(&self, dir: &Path) -> anyhow::Result<()> {
+        if dir.exists() {
+            return Err(anyhow::anyhow!(
+                "project {:?} has been initialized by another process",
+                self.project_dir
+            ))
+            .with_hint(|| {
+                format!("Run `{BRANDING_CLI_CMD} project info` to see the linked instance")
+            })?;
+        }
         let tmp = tmp_file_path(dir);
+        // leftover of an interrupted run, nobody else writes it while
+        // the lock is held
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)?;
+        }
         fs::create_dir_all(&tmp)?;
         fs::write(tmp.join("project-path"), path_bytes(self.project_dir)?)?;
         fs::write(tmp.join("instance-name"), self.instance_name.as_bytes())?;
@@ -422,10 +464,44 @@ pub fn print_instance_in_use_warning(name: &str, project_dirs: &[PathBuf]) {
                 continue;
             }
         };
-        eprintln!("  {}", dest.as_relative().display());
+        msg!("  {}", dest.as_relative().display());
     }
 }
 
+/// Points an existing project stash directory to another instance.
+///
+/// The stored branch is reset, as it most likely doesn't exist in the new
+/// instance.
+#[context("cannot relink project dir {:?}", stash_dir)]
+pub fn relink_stash_dir(stash_dir: &Path, instance: &InstanceName) -> anyhow::Result<()> {
+    write_instance_name(stash_dir, instance)?;
+    match fs::remove_file(stash_dir.join("database")) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e)?,
+    }
+}
+
+/// Replaces instance name in the project stash directory, keeping the branch.
+#[context("cannot write instance name to {:?}", stash_dir)]
+pub fn write_instance_name(stash_dir: &Path, instance: &InstanceName) -> anyhow::Result<()> {
+    let path = stash_dir.join("instance-name");
+    let tmp = tmp_file_path(&path);
+    fs::write(&tmp, instance.to_string().as_bytes())?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Replaces the branch stored in the project stash directory.
+#[context("cannot write database name to {:?}", stash_dir)]
+pub fn write_database_name(stash_dir: &Path, branch: &str) -> anyhow::Result<()> {
+    let path = stash_dir.join("database");
+    let tmp = tmp_file_path(&path);
+    fs::write(&tmp, branch.as_bytes())?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
 #[context("cannot read {:?}", project_dir)]
 pub fn read_project_path(project_dir: &Path) -> anyhow::Result<PathBuf> {
     let bytes = fs::read(project_dir.join("project-path"))?;
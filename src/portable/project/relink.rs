@@ -0,0 +1,170 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::ValueHint;
+use gel_tokio::get_stash_path;
+
+use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
+use crate::commands::ExitCode;
+use crate::hint::HintExt;
+use crate::portable::options::InstanceName;
+use crate::portable::project;
+use crate::print::{self, msg, AsRelativeToCurrentDir, Highlight};
+use crate::question;
+
+pub fn run(options: &Command) -> anyhow::Result<()> {
+    let Some(project) = project::find_project(options.project_dir.as_deref())? else {
+        anyhow::bail!("`{MANIFEST_FILE_DISPLAY_NAME}` not found, unable to relink project.");
+    };
+    let canon = fs::canonicalize(&project.root)
+        .with_context(|| format!("failed to canonicalize dir {:?}", project.root))?;
+    let stash_dir = get_stash_path(&canon)?;
+    if stash_dir.exists() {
+        let inst = project::instance_name(&stash_dir)?;
+        msg!(
+            "Project is already linked to instance {}.",
+            inst.to_string().emphasize()
+        );
+        return Ok(());
+    }
+
+    let candidates = find_candidates(&canon, options.instance.as_ref())?;
+    let old = match &candidates[..] {
+        [] => {
+            return Err(
+                anyhow::anyhow!("No previous link found for this project.").with_hint(|| {
+                    format!(
+                        "Use `{BRANDING_CLI_CMD} project relink -I <instance>` to pick \
+                         the instance explicitly or `{BRANDING_CLI_CMD} project init` \
+                         to link the project to an existing instance."
+                    )
+                }),
+            )?;
+        }
+        [single] => single,
+        _ if options.non_interactive => {
+            print::error!("Multiple previous links found for this project:");
+            for candidate in &candidates {
+                msg!("  {}", candidate.describe());
+            }
+            msg!("Use `--instance` to pick one of them.");
+            return Err(ExitCode::new(1).into());
+        }
+        _ => {
+            let mut q = question::Numeric::new("Which link do you want to restore?");
+            for candidate in &candidates {
+                q.option(candidate.describe(), candidate);
+            }
+            q.ask()?
+        }
+    };
+
+    let database = project::database_name(&old.stash_dir)?;
+    let cloud_profile = read_optional(&old.stash_dir.join("cloud-profile"))?;
+    let instance_name = old.instance.to_string();
+    let mut stash = project::StashDir::new(&canon, &instance_name);
+    stash.database = database.as_deref();
+    stash.cloud_profile = cloud_profile.as_deref();
+    stash.write(&stash_dir)?;
+
+    if old.moved {
+        fs::remove_dir_all(&old.stash_dir)
+            .with_context(|| format!("cannot remove {:?}", old.stash_dir))?;
+    }
+    msg!(
+        "Project {} is now linked to {}.",
+        canon.as_relative().display(),
+        instance_name.emphasize()
+    );
+    Ok(())
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Explicitly set a root directory for the project
+    #[arg(long, value_hint=ValueHint::DirPath)]
+    pub project_dir: Option<PathBuf>,
+
+    /// Restore the link to this instance, even if the project was
+    /// previously linked from a directory with a different name
+    #[arg(short = 'I', long)]
+    pub instance: Option<InstanceName>,
+
+    /// Do not ask questions, fail if the previous link is ambiguous
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    stash_dir: PathBuf,
+    project_path: PathBuf,
+    instance: InstanceName,
+    /// The old stash directory is stale and is removed after relinking
+    moved: bool,
+}
+
+impl Candidate {
+    fn describe(&self) -> String {
+        format!(
+            "{} (instance {})",
+            self.project_path.display(),
+            self.instance
+        )
+    }
+}
+
+/// Finds stash directories that might have belonged to the project
+/// before it was moved.
+///
+/// These are either directories pointing to the same canonical path, or
+/// directories of projects which no longer exist and had the same directory
+/// name. With `instance` specified, any directory linked to the instance
+/// matches.
+fn find_candidates(
+    canon: &Path,
+    instance: Option<&InstanceName>,
+) -> anyhow::Result<Vec<Candidate>> {
+    let instance = instance.map(|i| i.to_string());
+    let stash_dirs = project::find_project_stash_dirs(
+        "instance-name",
+        |name| instance.as_deref().map_or(true, |i| i == name),
+        false,
+    )?;
+    let mut candidates = Vec::new();
+    for stash_dir in stash_dirs.into_values().flatten() {
+        let project_path = match project::read_project_path(&stash_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                log::info!("Skipping {:?}: {:#}", stash_dir, e);
+                continue;
+            }
+        };
+        let same_dir = fs::canonicalize(&project_path)
+            .map(|p| p == canon)
+            .unwrap_or(false);
+        let moved = same_dir || !project_path.exists();
+        let same_name = project_path.file_name() == canon.file_name();
+        if instance.is_none() && !(same_dir || (moved && same_name)) {
+            continue;
+        }
+        candidates.push(Candidate {
+            instance: project::instance_name(&stash_dir)?,
+            stash_dir,
+            project_path,
+            moved,
+        });
+    }
+    candidates.sort_by(|a, b| a.project_path.cmp(&b.project_path));
+    Ok(candidates)
+}
+
+fn read_optional(path: &Path) -> anyhow::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text.trim().into())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("cannot read {path:?}")),
+    }
+}
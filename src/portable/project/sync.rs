@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use clap::ValueHint;
+use gel_tokio::get_stash_path;
+
+use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
+use crate::cloud::client::CloudClient;
+use crate::commands::ExitCode;
+use crate::hooks::{self, Action, Env};
+use crate::portable::extension;
+use crate::portable::instance::control;
+use crate::portable::instance::status::{instance_status, Service};
+use crate::portable::project;
+use crate::portable::windows;
+use crate::print::{self, msg, Highlight};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Explicitly set a root directory for the project
+    #[arg(long, value_hint=ValueHint::DirPath)]
+    pub project_dir: Option<PathBuf>,
+
+    /// Do not apply migrations
+    #[arg(long)]
+    pub skip_migrations: bool,
+}
+
+pub fn run(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()> {
+    let project = project::ensure_ctx(cmd.project_dir.as_deref())?;
+    let stash_dir = get_stash_path(&project.location.root)?;
+    if !stash_dir.exists() {
+        msg!(
+            "{} {} Run `{BRANDING_CLI_CMD} project init`.",
+            print::err_marker(),
+            "Project is not initialized.".emphasize()
+        );
+        return Err(ExitCode::new(1).into());
+    }
+    let schema_dir = project
+        .manifest
+        .project()
+        .resolve_schema_dir(&project.location.root)?;
+    let sync = project.manifest.sync.clone().unwrap_or_default();
+
+    let instance_name = project::instance_name(&stash_dir)?;
+    let client = CloudClient::new(&opts.cloud_options)?;
+    let mut inst =
+        project::Handle::probe(&instance_name, &project.location.root, &schema_dir, &client)?;
+    inst.database = project::database_name(&stash_dir)?;
+
+    ensure_running(&inst)?;
+
+    if !sync.extensions.is_empty() {
+        let installed = extension::install_missing(&instance_name, &sync.extensions, opts)?;
+        if installed.is_empty() {
+            msg!("Extensions are up to date.");
+        }
+    }
+
+    if cmd.skip_migrations {
+        msg!("Skipping migrations.");
+    } else {
+        project::init::migrate(&inst, false)?;
+    }
+
+    if let Some(hook) = &sync.post_sync {
+        hooks::run_command(
+            hook,
+            &project.location.root,
+            Action::ProjectSyncAfter,
+            &Env {
+                branch: inst.database.as_deref(),
+                instance: Some(&instance_name),
+                migrations: &[],
+            },
+        )?;
+    }
+
+    msg!(
+        "Project is in sync with {}.",
+        MANIFEST_FILE_DISPLAY_NAME.emphasize()
+    );
+    Ok(())
+}
+
+fn ensure_running(inst: &project::Handle) -> anyhow::Result<()> {
+    match &inst.instance {
+        project::InstanceKind::Portable(info) => {
+            if let Service::Running { .. } = instance_status(&info.name)?.service {
+                return Ok(());
+            }
+            msg!("Starting instance {}...", inst.name.emphasize());
+            control::do_start(info)
+        }
+        project::InstanceKind::Wsl => windows::daemon_start(&inst.name),
+        // remote and cloud instances can't be started from here,
+        // connection errors are reported when migrating
+        project::InstanceKind::Remote | project::InstanceKind::Cloud { .. } => Ok(()),
+    }
+}
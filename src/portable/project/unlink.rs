@@ -1,16 +1,22 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use clap::ValueHint;
 use gel_tokio::get_stash_path;
 
-use crate::branding::MANIFEST_FILE_DISPLAY_NAME;
+use crate::branding::{BRANDING, MANIFEST_FILE_DISPLAY_NAME};
 use crate::commands::ExitCode;
 use crate::options::CloudOptions;
+use crate::platform;
 use crate::portable::exit_codes;
+use crate::portable::instance::control;
+use crate::portable::instance::create::create_service;
 use crate::portable::instance::destroy;
+use crate::portable::local::{self, write_json, InstanceInfo};
+use crate::portable::options::InstanceName;
 use crate::portable::project;
+use crate::portable::windows;
 use crate::print::{self, msg, Highlight};
 use crate::question;
 
@@ -23,6 +29,12 @@ pub fn run(options: &Command, opts: &crate::options::Options) -> anyhow::Result<
     let stash_path = get_stash_path(&canon)?;
 
     if stash_path.exists() {
+        if let Some(new_name) = &options.rename_instance {
+            let inst = project::instance_name(&stash_path)?;
+            if !rename_instance(&inst, new_name, options)? {
+                return Ok(());
+            }
+        }
         if options.destroy_server_instance {
             let inst = project::instance_name(&stash_path)?;
             if !options.non_interactive {
@@ -55,7 +67,7 @@ pub fn run(options: &Command, opts: &crate::options::Options) -> anyhow::Result<
                 }
                 Err(e) => {
                     print::error!("Cannot read instance name: {e}");
-                    eprintln!("Removing project configuration directory...");
+                    msg!("Removing project configuration directory...");
                 }
             };
         }
@@ -80,7 +92,115 @@ pub struct Command {
     #[arg(long, short = 'D')]
     pub destroy_server_instance: bool,
 
+    /// Rename the associated local instance before unlinking.
+    ///
+    /// Credentials, data directory and service are renamed, other
+    /// projects using the instance are relinked to the new name.
+    #[arg(long, value_name = "NEW_NAME")]
+    #[arg(conflicts_with = "destroy_server_instance")]
+    pub rename_instance: Option<InstanceName>,
+
     /// Unlink in in non-interactive mode (accepting all defaults)
     #[arg(long)]
     pub non_interactive: bool,
 }
+
+/// Returns `false` if the user has canceled the operation
+fn rename_instance(
+    inst: &InstanceName,
+    new: &InstanceName,
+    options: &Command,
+) -> anyhow::Result<bool> {
+    let (InstanceName::Local(old_name), InstanceName::Local(new_name)) = (inst, new) else {
+        anyhow::bail!("Only local instances can be renamed.");
+    };
+    if cfg!(windows) || windows::is_wrapped() {
+        anyhow::bail!("Renaming instances is not supported on Windows.");
+    }
+    if old_name == new_name {
+        anyhow::bail!("Instance is already named {old_name:?}.");
+    }
+    let old_paths = local::Paths::get(old_name)?;
+    let new_paths = match (&old_paths.data_link, old_paths.data_dir.parent()) {
+        (Some(_), Some(parent)) => local::Paths::with_data_dir(new_name, &parent.join(new_name))?,
+        _ => local::Paths::get(new_name)?,
+    };
+    new_paths.check_exists()?;
+    if old_paths.upgrade_marker.exists() {
+        anyhow::bail!("Instance {old_name:?} is being upgraded, cannot rename it.");
+    }
+    let info = InstanceInfo::read(old_name)?;
+    if !options.non_interactive {
+        let q = question::Confirm::new(format!(
+            "Do you want to rename instance {old_name:?} to {new_name:?}? \
+             The instance will be restarted."
+        ));
+        if !q.ask()? {
+            print::error!("Canceled.");
+            return Ok(false);
+        }
+    }
+
+    if let Err(e) = control::stop_and_disable(old_name) {
+        log::warn!("Error unloading service: {:#}", e);
+    }
+    for path in &old_paths.service_files {
+        if path.exists() {
+            log::info!("Removing service file {:?}", path);
+            fs::remove_file(path)?;
+        }
+    }
+    if old_paths.runstate_dir.exists() {
+        fs::remove_dir_all(&old_paths.runstate_dir)?;
+    }
+    move_path(&old_paths.data_dir, &new_paths.data_dir)?;
+    move_path(&old_paths.credentials, &new_paths.credentials)?;
+    move_path(&old_paths.backup_dir, &new_paths.backup_dir)?;
+    move_path(&old_paths.dump_path, &new_paths.dump_path)?;
+    if let (Some(old_link), Some(new_link)) = (&old_paths.data_link, &new_paths.data_link) {
+        fs::remove_file(old_link)?;
+        platform::symlink_dir(&new_paths.data_dir, new_link)?;
+    }
+    local::rename_port(old_name, new_name)?;
+    for dir in project::find_project_dirs_by_instance(old_name)? {
+        project::write_instance_name(&dir, new)?;
+    }
+
+    let info = InstanceInfo {
+        name: new_name.clone(),
+        data_dir: new_paths
+            .data_link
+            .as_ref()
+            .map(|_| new_paths.data_dir.clone()),
+        ..info
+    };
+    if info.data_dir.is_some() {
+        write_json(
+            &new_paths.data_dir.join("instance_info.json"),
+            "metadata",
+            &info,
+        )?;
+    }
+    if let Err(e) = create_service(&info) {
+        log::warn!("Error running {BRANDING} as a service: {e:#}");
+        print::warn!(
+            "{BRANDING} will not start on next login. \
+             Trying to start database in the background..."
+        );
+        control::do_start(&info)?;
+    }
+    msg!(
+        "Instance {} is renamed to {}.",
+        old_name.emphasize(),
+        new_name.emphasize()
+    );
+    Ok(true)
+}
+
+fn move_path(old: &Path, new: &Path) -> anyhow::Result<()> {
+    if old.exists() {
+        log::info!("Moving {:?} to {:?}", old, new);
+        fs::rename(old, new).with_context(|| format!("cannot rename {old:?} to {new:?}"))?;
+    }
+    Ok(())
+}
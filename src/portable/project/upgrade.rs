@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -8,7 +9,9 @@ use gel_tokio::get_stash_path;
 use crate::branding::{BRANDING, BRANDING_CLI_CMD};
 use crate::cloud;
 use crate::cloud::client::CloudClient;
+use crate::commands::ExitCode;
 use crate::migrations;
+use crate::portable::exit_codes;
 use crate::portable::instance;
 use crate::portable::instance::upgrade;
 use crate::portable::local::InstanceInfo;
@@ -20,9 +23,23 @@ use crate::portable::ver;
 use crate::portable::windows;
 use crate::print::{self, msg, AsRelativeToCurrentDir, Highlight};
 use crate::question;
+use crate::table::{self, Cell, Row, Table};
 
 pub fn run(options: &Command, opts: &crate::options::Options) -> anyhow::Result<()> {
-    let (query, version_set) = Query::from_options(
+    if options.all_projects {
+        return upgrade_all(options, opts);
+    }
+    let (query, version_set) = target_query(options)?;
+    if version_set {
+        update_toml(options, opts, query)?;
+    } else {
+        upgrade_instance(options, opts)?;
+    }
+    Ok(())
+}
+
+fn target_query(options: &Command) -> anyhow::Result<(Query, bool)> {
+    Query::from_options(
         repository::QueryOptions {
             nightly: options.to_nightly,
             stable: options.to_latest,
@@ -31,20 +48,24 @@ pub fn run(options: &Command, opts: &crate::options::Options) -> anyhow::Result<
             channel: options.to_channel,
         },
         || Ok(Query::stable()),
-    )?;
-    if version_set {
-        update_toml(options, opts, query)
-    } else {
-        upgrade_instance(options, opts)
-    }
+    )
 }
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct Command {
     /// Explicitly set a root directory for the project
     #[arg(long, value_hint=ValueHint::DirPath)]
+    #[arg(conflicts_with = "all_projects")]
     pub project_dir: Option<PathBuf>,
 
+    /// Upgrade all initialized projects on this machine.
+    ///
+    /// Projects are grouped by instance: each instance is upgraded once,
+    /// and the manifests of the other projects using it are updated to
+    /// the same version.
+    #[arg(long)]
+    pub all_projects: bool,
+
     /// Upgrade specified instance to latest version
     #[arg(long)]
     #[arg(conflicts_with_all=&[
@@ -93,14 +114,25 @@ pub struct Command {
     /// Do not ask questions, assume user wants to upgrade instance
     #[arg(long)]
     pub non_interactive: bool,
+
+    /// Print what the upgrade would do without changing the instance or
+    /// the manifest.
+    ///
+    /// For major upgrades the schema is checked against the target
+    /// version, which requires downloading its server package.
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 pub fn update_toml(
     options: &Command,
     opts: &crate::options::Options,
     query: Query,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<upgrade::UpgradeAction> {
     let project = project::ensure_ctx(options.project_dir.as_deref())?;
+    // checked before the instance is upgraded, the version is written
+    // to the manifest afterwards
+    manifest::check_server_ver_not_overridden(&project.location.manifest)?;
     let schema_dir = project
         .manifest
         .project()
@@ -119,6 +151,14 @@ pub fn update_toml(
     if !stash_dir.exists() {
         log::warn!("No associated instance found.");
 
+        if options.dry_run {
+            msg!(
+                "Dry run: server version in `{}` would be set to {}.",
+                project.location.manifest.as_relative().display(),
+                query.display()
+            );
+            return Ok(upgrade::UpgradeAction::Cancelled);
+        }
         if manifest::modify_server_ver(&project.location.manifest, &query)? {
             print::success!("Config updated successfully.");
         } else {
@@ -129,6 +169,7 @@ pub fn update_toml(
             BRANDING_CLI_CMD,
             " project init".command_hint()
         );
+        Ok(upgrade::UpgradeAction::None)
     } else {
         let name = project::instance_name(&stash_dir)?;
         let database = project::database_name(&stash_dir)?;
@@ -149,13 +190,7 @@ pub fn update_toml(
 
         match result.action {
             upgrade::UpgradeAction::Upgraded => {
-                let config_version = if query.is_nightly() {
-                    query.clone()
-                } else {
-                    // on `--to-latest` which is equivalent to `server-version="*"`
-                    // we put specific version instead
-                    Query::from_version(&pkg_ver)?
-                };
+                let config_version = config_version(&query, &pkg_ver)?;
 
                 if manifest::modify_server_ver(&project.location.manifest, &config_version)? {
                     msg!("Remember to commit it to version control.");
@@ -163,6 +198,14 @@ pub fn update_toml(
                 let name_str = name.to_string();
                 print_other_project_warning(&name_str, &project.location.root, &query)?;
             }
+            upgrade::UpgradeAction::Cancelled if options.dry_run => {
+                msg!(
+                    "Server version in `{}` would be set to {}.",
+                    project.location.manifest.as_relative().display(),
+                    query.display()
+                );
+                msg!("Dry run: no changes were made.");
+            }
             upgrade::UpgradeAction::Cancelled => {
                 msg!("Canceled.");
             }
@@ -170,9 +213,19 @@ pub fn update_toml(
                 msg!("Already up to date.\nRequested upgrade version is {} current instance version is {}", result.requested_version.emphasize().to_string() + ",", result.prior_version.emphasize().to_string() + ".");
             }
         }
-    };
+        Ok(result.action)
+    }
+}
 
-    Ok(())
+/// Version written to the manifest after upgrading to `query`
+fn config_version(query: &Query, pkg_ver: &ver::Specific) -> anyhow::Result<Query> {
+    if query.is_nightly() {
+        Ok(query.clone())
+    } else {
+        // on `--to-latest` which is equivalent to `server-version="*"`
+        // we put specific version instead
+        Query::from_version(pkg_ver)
+    }
 }
 
 fn print_other_project_warning(
@@ -199,9 +252,9 @@ fn print_other_project_warning(
             projects:"
         );
         for pd in &project_dirs {
-            eprintln!("  {}", pd.as_relative().display());
+            msg!("  {}", pd.as_relative().display());
         }
-        eprintln!("Run the following commands to update them:");
+        msg!("Run the following commands to update them:");
         for pd in &project_dirs {
             instance::upgrade::print_project_upgrade_command(to_version, &None, pd);
         }
@@ -209,7 +262,10 @@ fn print_other_project_warning(
     Ok(())
 }
 
-pub fn upgrade_instance(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()> {
+pub fn upgrade_instance(
+    cmd: &Command,
+    opts: &crate::options::Options,
+) -> anyhow::Result<upgrade::UpgradeAction> {
     let project = project::ensure_ctx(cmd.project_dir.as_deref())?;
     let cfg_ver = &project.manifest.instance.server_version;
     let schema_dir = project
@@ -242,6 +298,9 @@ pub fn upgrade_instance(cmd: &Command, opts: &crate::options::Options) -> anyhow
             // When upgrade attempt was made, implementations
             // would have already printed a message.
         }
+        upgrade::UpgradeAction::Cancelled if cmd.dry_run => {
+            msg!("Dry run: no changes were made.");
+        }
         upgrade::UpgradeAction::Cancelled => {
             msg!("Canceled.");
         }
@@ -273,9 +332,137 @@ pub fn upgrade_instance(cmd: &Command, opts: &crate::options::Options) -> anyhow
         }
     }
 
+    Ok(result.action)
+}
+
+fn upgrade_all(cmd: &Command, opts: &crate::options::Options) -> anyhow::Result<()> {
+    let (query, version_set) = target_query(cmd)?;
+    let mut groups = BTreeMap::new();
+    for (instance, stash_dirs) in project::find_project_stash_dirs("instance-name", |_| true, true)?
+    {
+        let mut projects = Vec::new();
+        for stash_dir in stash_dirs {
+            match project::read_project_path(&stash_dir) {
+                Ok(path) if path.exists() => projects.push(path),
+                Ok(path) => log::warn!("Project directory {path:?} no longer exists, skipping."),
+                Err(e) => print::error!("{e:#}"),
+            }
+        }
+        if !projects.is_empty() {
+            projects.sort();
+            groups.insert(instance, projects);
+        }
+    }
+    if groups.is_empty() {
+        msg!("No initialized projects found.");
+        return Ok(());
+    }
+
+    msg!("Projects to upgrade:");
+    for (instance, projects) in &groups {
+        msg!("  {}", instance.emphasize());
+        for path in projects {
+            msg!("    {}", path.as_relative().display());
+        }
+    }
+    if !cmd.non_interactive && !cmd.dry_run {
+        let q = question::Confirm::new(format!(
+            "Upgrade {} instance(s) used by {} project(s)?",
+            groups.len(),
+            groups.values().map(|p| p.len()).sum::<usize>(),
+        ));
+        if !q.ask()? {
+            msg!("Canceled.");
+            return Ok(());
+        }
+    }
+
+    // manifests of the projects sharing an instance get the same version
+    let manifest_version = if version_set {
+        let pkg = repository::get_server_package(&query)?
+            .with_context(|| format!("cannot find package matching {}", query.display()))?;
+        Some(config_version(&query, &pkg.version.specific())?)
+    } else {
+        None
+    };
+
+    let mut report = Vec::new();
+    for (instance, projects) in &groups {
+        let mut instance_failed = false;
+        for (idx, path) in projects.iter().enumerate() {
+            msg!(
+                "\nUpgrading project {} (instance {})...",
+                path.as_relative().display(),
+                instance.emphasize()
+            );
+            let project_cmd = Command {
+                project_dir: Some(path.clone()),
+                all_projects: false,
+                ..cmd.clone()
+            };
+            let result = match &manifest_version {
+                _ if instance_failed => Err(anyhow::anyhow!("instance upgrade failed")),
+                Some(_) if idx == 0 => update_toml(&project_cmd, opts, query.clone()),
+                Some(_) if cmd.dry_run => Ok(upgrade::UpgradeAction::Cancelled),
+                Some(version) => update_manifest(path, version),
+                None => upgrade_instance(&project_cmd, opts),
+            };
+            let outcome = match result {
+                Ok(upgrade::UpgradeAction::Upgraded) => "upgraded".into(),
+                Ok(upgrade::UpgradeAction::None) => "up to date".into(),
+                Ok(upgrade::UpgradeAction::Cancelled) if cmd.dry_run => "dry run".into(),
+                Ok(upgrade::UpgradeAction::Cancelled) => "canceled".into(),
+                Err(e) => {
+                    print::error!("{e:#}");
+                    instance_failed |= idx == 0 && manifest_version.is_some();
+                    format!("failed: {e:#}")
+                }
+            };
+            report.push((instance, path, outcome));
+        }
+    }
+
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Instance", "Project", "Result"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for (instance, path, outcome) in &report {
+        table.add_row(Row::new(vec![
+            Cell::new(instance),
+            Cell::new(&path.as_relative().display().to_string()),
+            Cell::new(outcome),
+        ]));
+    }
+    msg!();
+    table.printstd();
+
+    if report
+        .iter()
+        .any(|(_, _, outcome)| outcome.starts_with("failed"))
+    {
+        return Err(ExitCode::new(exit_codes::PARTIAL_SUCCESS).into());
+    }
     Ok(())
 }
 
+fn update_manifest(project_dir: &Path, version: &Query) -> anyhow::Result<upgrade::UpgradeAction> {
+    let project = project::ensure_ctx(Some(project_dir))?;
+    if manifest::modify_server_ver(&project.location.manifest, version)? {
+        msg!(
+            "Server version in `{}` set to {}.",
+            project.location.manifest.as_relative().display(),
+            version.display()
+        );
+        Ok(upgrade::UpgradeAction::Upgraded)
+    } else {
+        Ok(upgrade::UpgradeAction::None)
+    }
+}
+
 fn upgrade_local(
     cmd: &Command,
     project: &project::Context,
@@ -295,23 +482,43 @@ fn upgrade_local(
     })?;
     let pkg_ver = pkg.version.specific();
 
-    if pkg_ver > inst_ver || cmd.force {
+    if (pkg_ver > inst_ver || cmd.force) && cmd.dry_run {
+        let compatible = pkg_ver.is_compatible(&inst_ver) && !cmd.force;
         if cfg!(windows) {
+            // the report needs the data directory, which is in WSL
             windows::upgrade(
-                &instance::upgrade::Command {
-                    to_latest: false,
-                    to_version: to_version.version.clone(),
-                    to_channel: None,
-                    to_nightly: false,
-                    to_testing: false,
-                    name: None,
-                    instance: Some(instance_name),
-                    verbose: false,
-                    force: cmd.force,
-                    force_dump_restore: cmd.force,
-                    non_interactive: true,
-                    cloud_opts: opts.cloud_options.clone(),
-                },
+                &wsl_command(cmd, to_version, instance_name, opts, true),
+                &inst.name,
+            )?;
+            if !compatible {
+                migrations::upgrade_check::to_version(&pkg, project)
+                    .context("schema is not compatible with the target version")?;
+            }
+        } else {
+            ver::print_version_hint(&pkg_ver, to_version);
+            let schema_check = if compatible {
+                Ok("not required")
+            } else {
+                migrations::upgrade_check::to_version(&pkg, project).map(|()| "passed")
+            };
+            upgrade::print_dry_run(
+                &inst,
+                &pkg,
+                !compatible,
+                schema_check.as_ref().copied().unwrap_or("failed"),
+            )?;
+            schema_check.context("schema is not compatible with the target version")?;
+        }
+        Ok(upgrade::UpgradeResult {
+            action: upgrade::UpgradeAction::Cancelled,
+            prior_version: inst_ver,
+            requested_version: pkg_ver,
+            available_upgrade: None,
+        })
+    } else if pkg_ver > inst_ver || cmd.force {
+        if cfg!(windows) {
+            windows::upgrade(
+                &wsl_command(cmd, to_version, instance_name, opts, false),
                 &inst.name,
             )?;
         } else {
@@ -353,6 +560,35 @@ fn upgrade_local(
     }
 }
 
+/// Command run by the CLI in WSL to upgrade the instance
+fn wsl_command(
+    cmd: &Command,
+    to_version: &Query,
+    instance_name: InstanceName,
+    opts: &crate::options::Options,
+    dry_run: bool,
+) -> instance::upgrade::Command {
+    instance::upgrade::Command {
+        to_latest: false,
+        to_version: to_version.version.clone(),
+        to_channel: None,
+        to_nightly: false,
+        to_testing: false,
+        name: None,
+        instance: Some(instance_name),
+        verbose: false,
+        force: cmd.force,
+        force_dump_restore: cmd.force,
+        non_interactive: true,
+        dry_run,
+        resume: false,
+        abort_upgrade: false,
+        keep_old: false,
+        rollback: false,
+        cloud_opts: opts.cloud_options.clone(),
+    }
+}
+
 fn upgrade_cloud(
     cmd: &Command,
     org: &str,
@@ -367,7 +603,10 @@ fn upgrade_cloud(
         let target_ver_str = target_ver.to_string();
         let _inst_name = format!("{org}/{name}");
         let inst_name = _inst_name.emphasize();
-        if !cmd.non_interactive {
+        if cmd.dry_run {
+            msg!("Instance {inst_name} would be upgraded to version {target_ver_str}.");
+            Ok(false)
+        } else if !cmd.non_interactive {
             question::Confirm::new(format!(
                 "This will upgrade {inst_name} to version {target_ver_str}.\
                     \nConfirm upgrade?",
@@ -0,0 +1,94 @@
+//! Strict checks of an existing instance done by
+//! `project init --link --unattended-verify`.
+
+use crate::branding::MANIFEST_FILE_DISPLAY_NAME;
+use crate::commands::{get_databases, ExitCode};
+use crate::portable::project::{manifest::Manifest, Handle};
+use crate::print::{self, msg};
+
+#[derive(Debug, serde::Serialize)]
+pub struct Report {
+    pub instance: String,
+    pub ok: bool,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+/// Checks that the instance satisfies the manifest and the requested
+/// branch exists. The report is printed to stdout as JSON; an error is
+/// returned if any of the checks fails.
+pub fn verify(inst: &Handle, manifest: &Manifest) -> anyhow::Result<()> {
+    let report = run_checks(inst, manifest)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    for check in report.checks.iter().filter(|c| !c.ok) {
+        match &check.actual {
+            Some(actual) => print::error!(
+                "Check {} failed: expected {}, found {}",
+                check.name,
+                check.expected,
+                actual
+            ),
+            None => print::error!("Check {} failed: {} is missing", check.name, check.expected),
+        }
+    }
+    if !report.ok {
+        return Err(ExitCode::new(1).into());
+    }
+    msg!(
+        "Instance {} satisfies {MANIFEST_FILE_DISPLAY_NAME}.",
+        inst.name
+    );
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn run_checks(inst: &Handle, manifest: &Manifest) -> anyhow::Result<Report> {
+    let mut conn = inst.get_default_connection().await?;
+    let mut checks = Vec::new();
+
+    let ver_query = &manifest.instance.server_version;
+    let version = conn.get_version().await?.clone();
+    checks.push(Check {
+        name: "server-version",
+        ok: ver_query.matches(&version),
+        expected: ver_query.display().to_string(),
+        actual: Some(version.to_string()),
+    });
+
+    let required = manifest.sync.as_ref().map(|s| &s.extensions[..]);
+    if let Some(required) = required.filter(|r| !r.is_empty()) {
+        let present: Vec<String> = conn.query("SELECT sys::ExtensionPackage.name", &()).await?;
+        for extension in required {
+            let ok = present.contains(extension);
+            checks.push(Check {
+                name: "extension",
+                ok,
+                expected: extension.clone(),
+                actual: ok.then(|| extension.clone()),
+            });
+        }
+    }
+
+    if let Some(branch) = &inst.database {
+        let ok = get_databases(&mut conn).await?.contains(branch);
+        checks.push(Check {
+            name: "branch",
+            ok,
+            expected: branch.clone(),
+            actual: ok.then(|| branch.clone()),
+        });
+    }
+
+    Ok(Report {
+        instance: inst.name.clone(),
+        ok: checks.iter().all(|c| c.ok),
+        checks,
+    })
+}
@@ -2,11 +2,12 @@ use std::cmp::min;
 use std::collections::HashMap;
 use std::fmt;
 use std::future;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::Context;
 use fn_error_context::context;
+use futures_util::future::try_join_all;
 use indicatif::{ProgressBar, ProgressStyle};
 use is_terminal::IsTerminal;
 use once_cell::sync::OnceCell;
@@ -18,12 +19,19 @@ use url::Url;
 use crate::async_util::timeout;
 use crate::branding::{BRANDING, BRANDING_CLI};
 use crate::cli::env::Env;
+use crate::http;
 use crate::portable::windows;
 use crate::portable::{platform, ver};
+use crate::print::{self, msg};
 use crate::process::IntoArg;
 
 pub const USER_AGENT: &str = BRANDING_CLI;
 pub const DEFAULT_TIMEOUT: Duration = Duration::new(60, 0);
+/// Attempts to download a file, each resuming where the previous one stopped
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+/// Files of this size and larger are downloaded in parallel chunks
+const PARALLEL_MIN_SIZE: u64 = 64 << 20;
+const PARALLEL_PARTS: u64 = 4;
 static PKG_ROOT: OnceCell<Url> = OnceCell::new();
 
 #[derive(thiserror::Error, Debug)]
@@ -88,6 +96,8 @@ pub struct PackageData {
     pub slot: String,
     #[serde(default)]
     pub tags: HashMap<String, String>,
+    #[serde(default)]
+    pub build_date: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -106,6 +116,7 @@ pub struct PackageInfo {
     pub kind: PackageType,
     pub slot: String,
     pub tags: HashMap<String, String>,
+    pub build_date: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -178,11 +189,12 @@ where
     T: serde::de::DeserializeOwned,
 {
     log::info!("Fetching JSON at {}", url);
-    let body_bytes = reqwest::Client::new()
+    let body_bytes = http::client()?
         .get(url.clone())
         .header(reqwest::header::USER_AGENT, USER_AGENT)
         .send()
-        .await?
+        .await
+        .context("package repository request failed")?
         .error_for_status()?
         .bytes()
         .await?;
@@ -202,13 +214,13 @@ where
         _ = async {
             tokio::time::sleep(Duration::from_secs(2)).await;
             if std::io::stderr().is_terminal() {
-                eprintln!("Timeout expired to fetch {url}. Common reasons are:");
-                eprintln!("  1. Internet connectivity is slow");
-                eprintln!("  2. A firewall is blocking internet access to this resource");
+                msg!("Timeout expired to fetch {url}. Common reasons are:");
+                msg!("  1. Internet connectivity is slow");
+                msg!("  2. A firewall is blocking internet access to this resource");
                 if windows::is_in_wsl() {
-                    eprintln!("Note: The {BRANDING} CLI tool is running under \
+                    msg!("Note: The {BRANDING} CLI tool is running under \
                                Windows Subsystem for Linux (WSL).");
-                    eprintln!("  Consider adding a Windows Defender Firewall \
+                    msg!("  Consider adding a Windows Defender Firewall \
                               rule for WSL.");
                 }
             }
@@ -288,6 +300,7 @@ fn _filter_package(
         size: iref.verification.size,
         slot: pkg.slot.clone(),
         tags: pkg.tags.clone(),
+        build_date: pkg.build_date.clone(),
     })
 }
 
@@ -462,24 +475,37 @@ pub async fn download(
     url: &Url,
     quiet: bool,
 ) -> Result<blake2b_simd::Hash, anyhow::Error> {
-    let dest = dest.as_ref();
+    _download(dest.as_ref(), url, None, quiet).await
+}
+
+/// Downloads a file of known size, in parallel chunks if it's large and
+/// the server supports ranged requests
+#[context("failed to download file at URL: {}", url)]
+#[tokio::main(flavor = "current_thread")]
+pub async fn download_sized(
+    dest: impl AsRef<Path>,
+    url: &Url,
+    size: u64,
+    quiet: bool,
+) -> Result<blake2b_simd::Hash, anyhow::Error> {
+    _download(dest.as_ref(), url, Some(size), quiet).await
+}
+
+async fn _download(
+    dest: &Path,
+    url: &Url,
+    size: Option<u64>,
+    quiet: bool,
+) -> anyhow::Result<blake2b_simd::Hash> {
     log::info!("Downloading {} -> {}", url, dest.display());
-    let mut req = reqwest::Client::new()
-        .get(url.clone())
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .send()
-        .await?
-        .error_for_status()?;
-    let mut out = fs::File::create(dest)
-        .await
-        .with_context(|| format!("writing {:?}", dest.display()))?;
+    let client = http::client()?;
 
     let bar = if quiet {
         ProgressBar::hidden()
-    } else if let Some(len) = req.content_length() {
-        ProgressBar::new(len)
+    } else if let Some(len) = size {
+        print::progress(ProgressBar::new(len))
     } else {
-        ProgressBar::new_spinner()
+        print::progress(ProgressBar::new_spinner())
     };
     bar.set_style(
         ProgressStyle::default_bar()
@@ -491,14 +517,197 @@ pub async fn download(
             .expect("template is ok")
             .progress_chars("=> "),
     );
-    let mut hasher = blake2b_simd::State::new();
-    while let Some(chunk) = req.chunk().await? {
+    let ranges = match size {
+        Some(size) if size >= PARALLEL_MIN_SIZE && accepts_ranges(&client, url).await => {
+            split_ranges(size, PARALLEL_PARTS)
+        }
+        _ => vec![(0, None)],
+    };
+    let parts = (0..ranges.len())
+        .map(|idx| part_path(dest, idx))
+        .collect::<Vec<_>>();
+    try_join_all(
+        ranges
+            .iter()
+            .zip(&parts)
+            .map(|(&(start, end), path)| download_part(&client, url, path, start, end, &bar)),
+    )
+    .await?;
+    bar.finish();
+
+    if let [part] = &parts[..] {
+        fs::rename(part, dest).await?;
+    } else {
+        let mut out = fs::File::create(dest)
+            .await
+            .with_context(|| format!("writing {:?}", dest.display()))?;
+        for part in &parts {
+            tokio::io::copy(&mut fs::File::open(part).await?, &mut out).await?;
+        }
+        out.flush().await?;
+        for part in &parts {
+            fs::remove_file(part).await?;
+        }
+    }
+    file_hash(dest)
+}
+
+/// Path of the partially downloaded chunk of `dest`, kept between runs
+/// to resume the download
+pub fn part_path(dest: &Path, idx: usize) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".part{idx}"));
+    dest.with_file_name(name)
+}
+
+/// Splits `0..size` into inclusive ranges for the `Range` header
+fn split_ranges(size: u64, parts: u64) -> Vec<(u64, Option<u64>)> {
+    let chunk = size.div_ceil(parts);
+    (0..parts)
+        .map(|i| (i * chunk, min((i + 1) * chunk, size)))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| (start, Some(end - 1)))
+        .collect()
+}
+
+async fn accepts_ranges(client: &reqwest::Client, url: &Url) -> bool {
+    let response = client
+        .head(url.clone())
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await;
+    match response {
+        Ok(response) => response
+            .headers()
+            .get(reqwest::header::ACCEPT_RANGES)
+            .map_or(false, |value| value == "bytes"),
+        Err(e) => {
+            log::debug!("Cannot check range support: {e:#}");
+            false
+        }
+    }
+}
+
+/// Downloads `start..=end` bytes of the file into `path`, resuming from
+/// the data already there, including after connection errors
+async fn download_part(
+    client: &reqwest::Client,
+    url: &Url,
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+    bar: &ProgressBar,
+) -> anyhow::Result<()> {
+    let existing = match fs::metadata(path).await {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => Err(e)?,
+    };
+    if existing > 0 {
+        log::info!("Resuming download of {:?} at {} bytes", path, existing);
+    }
+    bar.inc(existing);
+    let mut attempt = 1;
+    loop {
+        match try_download_part(client, url, path, start, end, bar).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < DOWNLOAD_ATTEMPTS && is_transient(&e) => {
+                log::warn!("Download interrupted: {e:#}. Resuming...");
+                tokio::time::sleep(Duration::from_secs(attempt.into())).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn try_download_part(
+    client: &reqwest::Client,
+    url: &Url,
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+    bar: &ProgressBar,
+) -> anyhow::Result<()> {
+    let mut done = match fs::metadata(path).await {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => Err(e)?,
+    };
+    if let Some(end) = end {
+        let len = end + 1 - start;
+        if done == len {
+            return Ok(());
+        } else if done > len {
+            // left from a download split differently
+            bar.set_position(bar.position().saturating_sub(done));
+            done = 0;
+        }
+    }
+    let mut req = client
+        .get(url.clone())
+        .header(reqwest::header::USER_AGENT, USER_AGENT);
+    let offset = start + done;
+    if offset > 0 || end.is_some() {
+        let end = end.map(|e| e.to_string()).unwrap_or_default();
+        req = req.header(reqwest::header::RANGE, format!("bytes={offset}-{end}"));
+    }
+    let mut resp = req
+        .send()
+        .await
+        .context("package repository request failed")?;
+    let append = match resp.status() {
+        reqwest::StatusCode::PARTIAL_CONTENT => done > 0,
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE if end.is_none() && done > 0 => {
+            // already complete, the hash is checked by the caller
+            return Ok(());
+        }
+        status if status.is_success() && start == 0 && end.is_none() => {
+            if done > 0 {
+                log::info!("Server doesn't support resuming downloads, restarting");
+                bar.set_position(bar.position().saturating_sub(done));
+            }
+            false
+        }
+        status if status.is_success() => {
+            anyhow::bail!("server ignored ranged request");
+        }
+        _ => {
+            resp.error_for_status_ref()?;
+            anyhow::bail!("unexpected response status {}", resp.status());
+        }
+    };
+    if end.is_none() && bar.length().is_none() {
+        if let Some(len) = resp.content_length() {
+            bar.set_length(offset + len);
+        }
+    }
+    let mut out = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .await
+        .with_context(|| format!("writing {:?}", path.display()))?;
+    while let Some(chunk) = resp.chunk().await? {
         out.write_all(&chunk[..]).await?;
-        hasher.update(&chunk[..]);
         bar.inc(chunk.len() as u64);
     }
-    bar.finish();
+    out.flush().await?;
+    Ok(())
+}
+
+/// Network errors and server errors are worth retrying
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .map_or(false, |e| e.status().map_or(true, |s| s.is_server_error()))
+}
 
+pub fn file_hash(path: &Path) -> anyhow::Result<blake2b_simd::Hash> {
+    let mut file = std::fs::File::open(path).with_context(|| format!("reading {path:?}"))?;
+    let mut hasher = blake2b_simd::State::new();
+    std::io::copy(&mut file, &mut hasher).with_context(|| format!("reading {path:?}"))?;
     Ok(hasher.finalize())
 }
 
@@ -0,0 +1,204 @@
+//! `sandbox run`: a throwaway local instance for tests and experiments.
+//!
+//! The instance is created with its data in a temporary directory and
+//! started as a daemon, like `instance create` does when no service
+//! manager is available. It is destroyed when the command (or the REPL)
+//! exits, whatever the outcome.
+
+use std::path::Path;
+use std::process;
+
+use anyhow::Context;
+use const_format::concatcp;
+use gel_tokio::Builder;
+use rand::{thread_rng, Rng};
+
+use crate::branding::{BRANDING, BRANDING_CLI_CMD, QUERY_TAG};
+use crate::commands::{self, ExitCode};
+use crate::connect::{Connection, Connector};
+use crate::interrupt;
+use crate::migrations;
+use crate::migrations::options::{Migrate, MigrationConfig};
+use crate::options::Options;
+use crate::platform::current_exe;
+use crate::portable::exit_codes;
+use crate::portable::instance::{control, create, destroy};
+use crate::portable::local::{allocate_port, release_port, InstanceInfo, Paths};
+use crate::portable::options::InstanceName;
+use crate::portable::platform::optional_docker_check;
+use crate::portable::project::{self, manifest};
+use crate::portable::repository::Query;
+use crate::portable::server::install;
+use crate::print::{self, msg, Highlight};
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Command {
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    /// Create a temporary instance, apply the migrations of the project
+    /// and run a command (or the REPL) against it. The instance is
+    /// destroyed on exit.
+    Run(Run),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct Run {
+    /// Server version of the instance (default: the version required by
+    /// the project, or the latest stable one outside of a project)
+    #[arg(long)]
+    pub server_version: Option<Query>,
+
+    /// Don't apply the migrations of the project in the current directory
+    #[arg(long)]
+    pub no_migrations: bool,
+
+    /// Command to run with `GEL_INSTANCE` set to the sandbox instance.
+    /// The REPL is opened if no command is given.
+    #[arg(last = true)]
+    pub command: Vec<String>,
+}
+
+pub fn run(cmd: &Command, options: &Options) -> anyhow::Result<()> {
+    match &cmd.subcommand {
+        Subcommand::Run(c) => run_sandbox(c, options),
+    }
+}
+
+fn run_sandbox(cmd: &Run, options: &Options) -> anyhow::Result<()> {
+    if cfg!(windows) {
+        anyhow::bail!("`{BRANDING_CLI_CMD} sandbox` is not supported on Windows");
+    }
+    if optional_docker_check()? {
+        print::error!("`{BRANDING_CLI_CMD} sandbox` is not supported in Docker containers.");
+        Err(ExitCode::new(exit_codes::DOCKER_CONTAINER))?;
+    }
+    let project = if cmd.no_migrations {
+        None
+    } else {
+        project::find_project(None)?
+    };
+    let manifest = project
+        .as_ref()
+        .map(|p| manifest::read(&p.manifest))
+        .transpose()?;
+    let query = match (&cmd.server_version, &manifest) {
+        (Some(query), _) => query.clone(),
+        (None, Some(manifest)) => manifest.instance.server_version.clone(),
+        (None, None) => Query::stable(),
+    };
+    let installation =
+        install::version(&query).context(concatcp!("error installing ", BRANDING))?;
+
+    let name = format!("sandbox_{:08x}", thread_rng().gen::<u32>());
+    let tmp_dir = tempfile::tempdir().context("cannot create temporary directory")?;
+    let data_dir = tmp_dir.path().join("data");
+    let paths = Paths::with_data_dir(&name, &data_dir)?;
+    paths.check_exists()?;
+    let version = installation.version.specific();
+    let info = InstanceInfo {
+        name: name.clone(),
+        installation: Some(installation),
+        port: allocate_port(&name)?,
+        data_dir: Some(data_dir),
+    };
+
+    let result = create::bootstrap(
+        &paths,
+        &info,
+        create::get_default_user_name(&version),
+        &create::get_default_branch_name(&version),
+    )
+    .and_then(|()| control::daemon_start(&name))
+    .and_then(|()| {
+        msg!(
+            "Sandbox instance {} ({}) is up and running.",
+            name.emphasize(),
+            version
+        );
+        if let (Some(project), Some(manifest)) = (&project, &manifest) {
+            let schema_dir = manifest.project().resolve_schema_dir(&project.root)?;
+            migrate(&name, &schema_dir)?;
+        }
+        run_command(&name, &cmd.command)
+    });
+
+    msg!("Destroying sandbox instance {}...", name.emphasize());
+    let destroyed = destroy::force_by_name(&InstanceName::Local(name.clone()), options)
+        .and_then(|()| release_port(&name));
+    if let Err(e) = destroyed {
+        print::warn!(
+            "Cannot remove sandbox instance: {e:#}. \
+             Use `{BRANDING_CLI_CMD} instance destroy -I {name} --force` to remove it."
+        );
+    }
+    drop(tmp_dir);
+    result
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn migrate(name: &str, schema_dir: &Path) -> anyhow::Result<()> {
+    msg!("Applying migrations...");
+    let mut builder = Builder::new();
+    builder.instance(name)?;
+    let cfg = builder.build_env().await?;
+    let mut conn = Connection::connect(&cfg, QUERY_TAG).await?;
+    migrations::migrate(
+        &mut conn,
+        &commands::Options {
+            command_line: true,
+            styler: None,
+            conn_params: Connector::new(Ok(cfg)),
+        },
+        &Migrate {
+            cfg: MigrationConfig {
+                schema_dir: Some(schema_dir.to_path_buf()),
+            },
+            quiet: false,
+            to_revision: None,
+            down_to: None,
+            dev_mode: false,
+            single_transaction: false,
+            statement_timeout: None,
+            lock_timeout: None,
+            force_unsafe_ddl: false,
+            no_ddl_locking: false,
+            session_config: Vec::new(),
+            tag: None,
+            plan: false,
+            json: false,
+            conn: None,
+        },
+    )
+    .await
+}
+
+fn run_command(name: &str, command: &[String]) -> anyhow::Result<()> {
+    let mut cmd = match command.split_first() {
+        Some((program, args)) => {
+            let mut cmd = process::Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        None => {
+            let mut cmd = process::Command::new(current_exe()?);
+            cmd.arg("--no-cli-update-check").arg("-I").arg(name);
+            cmd
+        }
+    };
+    cmd.env("GEL_INSTANCE", name).env("EDGEDB_INSTANCE", name);
+
+    // Ctrl+C is handled by the command, the instance is destroyed when
+    // it exits
+    let _trap = interrupt::Trap::new(&[interrupt::Signal::Interrupt]);
+    let status = cmd
+        .status()
+        .with_context(|| format!("cannot run {:?}", cmd.get_program()))?;
+    if !status.success() {
+        return Err(ExitCode::new(status.code().unwrap_or(1)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use edgedb_cli_derive::IntoArgs;
+use fs_err as fs;
+use indicatif::HumanBytes;
+
+use crate::platform;
+use crate::print::{self, msg, Highlight};
+use crate::table::{self, Cell, Row, Table};
+
+/// Prefix of the package files, see `PackageInfo::cache_file_name`
+const PACKAGE_PREFIX: &str = "edgedb-server_";
+
+pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    match &cmd.subcommand {
+        Subcommand::List(c) => list(c),
+        Subcommand::Clear(c) => clear(c),
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    /// List downloaded server packages, including partial downloads.
+    List(List),
+    /// Remove downloaded server packages.
+    Clear(Clear),
+}
+
+#[derive(clap::Args, IntoArgs, Debug, Clone)]
+pub struct List {
+    /// Output in JSON format.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, IntoArgs, Debug, Clone)]
+pub struct Clear {
+    /// Only remove partial downloads.
+    #[arg(long)]
+    pub partial: bool,
+}
+
+#[derive(serde::Serialize)]
+struct Entry {
+    #[serde(skip)]
+    path: PathBuf,
+    name: String,
+    size: u64,
+    #[serde(with = "humantime_serde")]
+    modified: SystemTime,
+    partial: bool,
+}
+
+pub fn download_dir() -> anyhow::Result<PathBuf> {
+    Ok(platform::cache_dir()?.join("downloads"))
+}
+
+fn entries() -> anyhow::Result<Vec<Entry>> {
+    let dir = match fs::read_dir(download_dir()?) {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut result = Vec::new();
+    for item in dir {
+        let item = item?;
+        let name = item.file_name().to_string_lossy().into_owned();
+        let meta = item.metadata()?;
+        if !name.starts_with(PACKAGE_PREFIX) || !meta.is_file() {
+            continue;
+        }
+        let partial = name
+            .rsplit_once(".part")
+            .map_or(false, |(_, idx)| idx.parse::<usize>().is_ok());
+        result.push(Entry {
+            path: item.path(),
+            name,
+            size: meta.len(),
+            modified: meta.modified()?,
+            partial,
+        });
+    }
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(result)
+}
+
+fn list(cmd: &List) -> anyhow::Result<()> {
+    let entries = entries()?;
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        msg!("No packages downloaded.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.add_row(Row::new(vec![
+        table::header_cell("Package"),
+        table::header_cell("Size"),
+        table::header_cell("Downloaded"),
+    ]));
+    for entry in &entries {
+        table.add_row(Row::new(vec![
+            Cell::new(&entry.name),
+            Cell::new(&HumanBytes(entry.size).to_string()),
+            Cell::new(&if entry.partial {
+                "partially".to_string()
+            } else {
+                humantime::format_rfc3339_seconds(entry.modified).to_string()
+            }),
+        ]));
+    }
+    table.printstd();
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    msg!("Total size: {}", HumanBytes(total).to_string().emphasize());
+    Ok(())
+}
+
+fn clear(cmd: &Clear) -> anyhow::Result<()> {
+    let mut removed = 0;
+    let mut size = 0;
+    for entry in entries()? {
+        if cmd.partial && !entry.partial {
+            continue;
+        }
+        log::info!("Removing {:?}", entry.path);
+        fs::remove_file(&entry.path)?;
+        removed += 1;
+        size += entry.size;
+    }
+    if removed > 0 {
+        msg!(
+            "Removed {} files, freed {}.",
+            removed.emphasize(),
+            HumanBytes(size).to_string().emphasize()
+        );
+    } else {
+        print::success!("Nothing to remove.");
+    }
+    Ok(())
+}
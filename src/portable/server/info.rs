@@ -1,13 +1,45 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
 use anyhow::Context;
 use edgedb_cli_derive::IntoArgs;
+use indicatif::HumanBytes;
 
-use crate::portable::local;
+use crate::branding::BRANDING_CLI_CMD;
+use crate::platform::data_dir;
+use crate::portable::instance::status;
+use crate::portable::instance::upgrade::dir_size;
+use crate::portable::local::{self, InstanceInfo};
+use crate::portable::options::OutputFormat;
 use crate::portable::repository::{Channel, Query, QueryOptions};
 use crate::portable::ver;
-use crate::print::AsRelativeToCurrentDir;
-use crate::table;
+use crate::print::{msg, AsRelativeToCurrentDir};
+use crate::table::{self, Cell, Row, Table};
+
+/// A locally installed server version and the instances using it
+#[derive(serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Installation {
+    pub version: ver::Build,
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(with = "humantime_serde")]
+    pub installed_at: SystemTime,
+    pub instances: Vec<String>,
+    /// Not used by any instance, so can be removed by `server prune`
+    pub unused: bool,
+}
+
+#[derive(serde::Serialize)]
+struct JsonInventory<'a> {
+    installations: &'a [Installation],
+}
 
 pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    if cmd.all {
+        return inventory(cmd);
+    }
     // note this assumes that latest is set if no nightly and version
     let (query, _) = Query::from_options(
         QueryOptions {
@@ -31,32 +63,33 @@ pub fn run(cmd: &Command) -> anyhow::Result<()> {
         .max_by_key(|item| item.version.specific())
         .context("cannot find installed packages maching your criteria")?;
 
+    let format = OutputFormat::resolve(cmd.format, cmd.json);
     let item = cmd.get.as_deref().or(cmd.bin_path.then_some("bin-path"));
     if let Some(item) = item {
         match item {
             "bin-path" => {
                 let path = inst.server_path()?;
-                if cmd.json {
-                    let path = path.to_str().context("cannot convert path to a string")?;
-                    println!("{}", serde_json::to_string(path)?);
-                } else {
+                if format == OutputFormat::Table {
                     println!("{}", path.as_relative().display());
+                } else {
+                    let path = path.to_str().context("cannot convert path to a string")?;
+                    println!("{}", format.value(&path)?);
                 }
             }
             "version" => {
                 let version = &inst.version;
-                if cmd.json {
-                    println!("{}", serde_json::to_string(version)?);
-                } else {
+                if format == OutputFormat::Table {
                     println!("{version}");
+                } else {
+                    println!("{}", format.value(version)?);
                 }
             }
             _ => unreachable!(),
         }
-    } else if cmd.json {
+    } else if format != OutputFormat::Table {
         println!(
             "{}",
-            serde_json::to_string_pretty(&JsonInfo {
+            format.document(&JsonInfo {
                 version: &inst.version,
                 binary_path: inst.server_path()?.to_str(),
             })?
@@ -78,9 +111,18 @@ pub struct Command {
     /// Display only the server binary path (shortcut to `--get bin-path`).
     #[arg(long)]
     pub bin_path: bool,
-    /// Output in JSON format.
-    #[arg(long)]
+    /// Output in JSON format (shortcut to `--format json`).
+    #[arg(long, conflicts_with = "format")]
     pub json: bool,
+    /// Output format.
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Display all installed versions with their paths, sizes and the
+    /// instances using them.
+    #[arg(long)]
+    #[arg(conflicts_with_all=&["latest", "nightly", "version", "channel", "get", "bin_path"])]
+    pub all: bool,
 
     // Display info for latest version.
     #[arg(long)]
@@ -113,3 +155,85 @@ struct JsonInfo<'a> {
     version: &'a ver::Build,
     binary_path: Option<&'a str>,
 }
+
+/// Lists installed server versions, oldest first
+pub fn installations() -> anyhow::Result<Vec<Installation>> {
+    let mut used: BTreeMap<ver::Specific, Vec<String>> = BTreeMap::new();
+    let data_dir = data_dir()?;
+    if data_dir.exists() {
+        for pair in status::list_local(&data_dir)? {
+            let (name, _) = pair?;
+            if let Some(info) = InstanceInfo::try_read(&name)? {
+                used.entry(info.get_version()?.specific())
+                    .or_default()
+                    .push(info.name);
+            }
+        }
+    }
+    let mut installed = local::get_installed()?;
+    installed.sort_by_key(|item| item.version.specific());
+    let mut result = Vec::with_capacity(installed.len());
+    for item in installed {
+        let path = item.base_path()?;
+        let instances = used.remove(&item.version.specific()).unwrap_or_default();
+        result.push(Installation {
+            size: dir_size(&path)?,
+            path,
+            installed_at: item.installed_at,
+            unused: instances.is_empty(),
+            instances,
+            version: item.version,
+        });
+    }
+    Ok(result)
+}
+
+fn inventory(cmd: &Command) -> anyhow::Result<()> {
+    let installations = installations()?;
+    let format = OutputFormat::resolve(cmd.format, cmd.json);
+    if format != OutputFormat::Table {
+        println!(
+            "{}",
+            format.document(&JsonInventory {
+                installations: &installations,
+            })?
+        );
+        return Ok(());
+    }
+    if installations.is_empty() {
+        msg!("No server versions installed.");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.add_row(Row::new(vec![
+        table::header_cell("Version"),
+        table::header_cell("Path"),
+        table::header_cell("Size"),
+        table::header_cell("Instances"),
+    ]));
+    for item in &installations {
+        table.add_row(Row::new(vec![
+            Cell::new(&item.version.to_string()),
+            Cell::new(&item.path.as_relative().display().to_string()),
+            Cell::new(&HumanBytes(item.size).to_string()),
+            Cell::new(&if item.unused {
+                "unused".to_string()
+            } else {
+                item.instances.join(", ")
+            }),
+        ]));
+    }
+    table.printstd();
+    let unused: Vec<_> = installations.iter().filter(|i| i.unused).collect();
+    if !unused.is_empty() {
+        let size: u64 = unused.iter().map(|i| i.size).sum();
+        msg!(
+            "{} unused versions take {}, remove them with \
+             `{BRANDING_CLI_CMD} server prune`.",
+            unused.len(),
+            HumanBytes(size)
+        );
+    }
+    Ok(())
+}
@@ -19,8 +19,9 @@ use crate::portable::local::{write_json, InstallInfo};
 use crate::portable::platform::optional_docker_check;
 use crate::portable::repository::Channel;
 use crate::portable::repository::QueryOptions;
-use crate::portable::repository::{download, PackageHash, PackageInfo, Query};
+use crate::portable::repository::{download_sized, file_hash, PackageHash, PackageInfo, Query};
 use crate::portable::repository::{get_server_package, get_specific_package};
+use crate::portable::server::cache;
 use crate::portable::ver::{self, Build};
 use crate::print::{self, msg, Highlight};
 
@@ -89,7 +90,6 @@ pub fn package(pkg_info: &PackageInfo) -> anyhow::Result<InstallInfo> {
         return Ok(meta);
     }
 
-    msg!("Downloading package...");
     let cache_path = download_package(pkg_info)?;
     let tmp_target = platform::tmp_file_path(&target_dir);
     unpack_package(&cache_path, &tmp_target)?;
@@ -103,7 +103,6 @@ pub fn package(pkg_info: &PackageInfo) -> anyhow::Result<InstallInfo> {
     write_json(&tmp_target.join("install_info.json"), "metadata", &info)?;
     fs::rename(&tmp_target, &target_dir)
         .with_context(|| format!("cannot rename {tmp_target:?} -> {target_dir:?}"))?;
-    unlink_cache(&cache_path);
     msg!("Successfully installed {}", pkg_info.version.emphasize());
     INSTALLED_VERSIONS
         .lock()
@@ -135,12 +134,32 @@ fn check_metadata(dir: &Path, pkg_info: &PackageInfo) -> anyhow::Result<InstallI
 
 #[context("failed to download {}", pkg_info)]
 pub fn download_package(pkg_info: &PackageInfo) -> anyhow::Result<PathBuf> {
-    let cache_dir = platform::cache_dir()?;
-    let download_dir = cache_dir.join("downloads");
+    let download_dir = cache::download_dir()?;
     fs::create_dir_all(&download_dir)?;
     let cache_path = download_dir.join(pkg_info.cache_file_name());
-    let hash = download(&cache_path, &pkg_info.url, false)?;
-    match &pkg_info.hash {
+    if cache_path.exists() {
+        match check_hash(&file_hash(&cache_path)?, &pkg_info.hash) {
+            Ok(()) => {
+                msg!("Using previously downloaded package");
+                return Ok(cache_path);
+            }
+            Err(e) => {
+                log::warn!("Downloading again, cached package is invalid: {e:#}");
+                unlink_cache(&cache_path);
+            }
+        }
+    }
+    msg!("Downloading package...");
+    let hash = download_sized(&cache_path, &pkg_info.url, pkg_info.size, false)?;
+    if let Err(e) = check_hash(&hash, &pkg_info.hash) {
+        unlink_cache(&cache_path);
+        return Err(e);
+    }
+    Ok(cache_path)
+}
+
+fn check_hash(hash: &blake2b_simd::Hash, expected: &PackageHash) -> anyhow::Result<()> {
+    match expected {
         PackageHash::Blake2b(hex) => {
             if hash.to_hex()[..] != hex[..] {
                 anyhow::bail!("hash mismatch {} != {}", hash.to_hex(), hex);
@@ -150,7 +169,7 @@ pub fn download_package(pkg_info: &PackageInfo) -> anyhow::Result<PathBuf> {
             log::warn!("Cannot verify hash, unknown hash format {:?}", val);
         }
     }
-    Ok(cache_path)
+    Ok(())
 }
 
 fn build_path(base: &Path, path: &Path) -> anyhow::Result<Option<PathBuf>> {
@@ -210,7 +229,7 @@ fn unpack_package(cache_file: &Path, target_dir: &Path) -> anyhow::Result<()> {
     let target_dir = target_dir.canonicalize()?;
 
     let file = fs::File::open(cache_file)?;
-    let bar = ProgressBar::new(file.metadata()?.len());
+    let bar = print::progress(ProgressBar::new(file.metadata()?.len()));
     bar.set_style(
         ProgressStyle::default_bar()
             .template("Unpacking [{bar}] {bytes:>7.dim}/{total_bytes:7}")
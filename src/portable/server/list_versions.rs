@@ -3,94 +3,92 @@ use std::path::PathBuf;
 
 use edgedb_cli_derive::IntoArgs;
 
+use crate::branding::BRANDING_CLOUD;
+use crate::cloud::client::CloudClient;
+use crate::cloud::ops::get_versions;
+use crate::options::Options;
 use crate::portable::local::{self, InstallInfo};
 use crate::portable::repository::{get_server_packages, Channel, PackageInfo};
 use crate::portable::ver;
 use crate::table::{self, Cell, Row, Table};
 
-pub fn run(cmd: &Command) -> Result<(), anyhow::Error> {
-    let mut installed = local::get_installed()?;
-    if cmd.installed_only {
-        if cmd.json {
-            print!(
-                "{}",
-                serde_json::to_string_pretty(
-                    &installed
-                        .into_iter()
-                        .map(|v| JsonVersionInfo {
-                            channel: Channel::from_version(&v.version.specific())
-                                .unwrap_or(Channel::Nightly),
-                            version: v.version.clone(),
-                            installed: true,
-                            debug_info: DebugInfo {
-                                install: Some(DebugInstall::from(v)),
-                                package: None,
-                            },
-                        })
-                        .collect::<Vec<_>>()
-                )?
-            );
-        } else {
-            installed.sort_by(|a, b| a.version.specific().cmp(&b.version.specific()));
-            print_table(installed.into_iter().map(|p| (p.version, true)));
-        }
-    } else {
-        let mut version_set = BTreeMap::new();
+pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
+    let mut version_set: BTreeMap<ver::Specific, Pair> = BTreeMap::new();
+    if !cmd.installed_only {
         for package in all_packages() {
-            version_set.insert(
-                package.version.specific(),
-                Pair {
-                    package: Some(package),
-                    install: None,
-                },
-            );
+            version_set
+                .entry(package.version.specific())
+                .or_default()
+                .package = Some(package);
         }
-        for install in installed {
-            let _ = version_set
-                .entry(install.version.specific())
-                .or_insert_with(|| Pair {
-                    package: None,
-                    install: None,
-                })
-                .install
-                .insert(install);
-        }
-        if cmd.json {
-            print!(
-                "{}",
-                serde_json::to_string_pretty(
-                    &version_set
-                        .into_iter()
-                        .map(|(ver, vp)| JsonVersionInfo {
-                            channel: Channel::from_version(&ver).unwrap_or(Channel::Nightly),
-                            version: vp.install.as_ref().map_or_else(
-                                || vp.package.as_ref().unwrap().version.clone(),
-                                |v| v.version.clone(),
-                            ),
-                            installed: vp.install.is_some(),
-                            debug_info: DebugInfo {
-                                install: vp.install.map(DebugInstall::from),
-                                package: vp.package,
-                            },
-                        })
-                        .collect::<Vec<_>>()
-                )?
-            );
-        } else {
-            print_table(version_set.into_values().map(|vp| match vp.install {
-                Some(v) => (v.version, true),
-                None => (vp.package.unwrap().version, false),
-            }));
+    }
+    for install in local::get_installed()? {
+        version_set
+            .entry(install.version.specific())
+            .or_default()
+            .install = Some(install);
+    }
+    if cmd.include_cloud {
+        let client = CloudClient::new(&options.cloud_options)?;
+        client.ensure_authenticated()?;
+        for version in get_versions(&client)? {
+            match version.version.parse::<ver::Specific>() {
+                Ok(ver) => version_set.entry(ver).or_default().cloud = true,
+                Err(e) => log::warn!(
+                    "Skipping {BRANDING_CLOUD} version {:?}: {e:#}",
+                    version.version
+                ),
+            }
         }
     }
+
+    let latest_stable = version_set
+        .keys()
+        .filter(|ver| ver.is_stable())
+        .map(|ver| ver.major)
+        .max();
+    let versions = version_set
+        .into_iter()
+        .map(|(ver, vp)| JsonVersionInfo {
+            channel: Channel::from_version(&ver).unwrap_or(Channel::Nightly),
+            version: match (&vp.install, &vp.package) {
+                (Some(install), _) => install.version.to_string(),
+                (None, Some(package)) => package.version.to_string(),
+                (None, None) => ver.to_string(),
+            },
+            installed: vp.install.is_some(),
+            cloud: cmd.include_cloud.then_some(vp.cloud),
+            release_date: vp.package.as_ref().and_then(|p| p.build_date.clone()),
+            eol: is_eol(&ver, latest_stable),
+            debug_info: DebugInfo {
+                install: vp.install.map(DebugInstall::from),
+                package: vp.package,
+            },
+        })
+        .collect::<Vec<_>>();
+    if cmd.json {
+        print!("{}", serde_json::to_string_pretty(&versions)?);
+    } else {
+        print_table(&versions);
+    }
     Ok(())
 }
 
+/// Only the two latest major versions receive updates
+fn is_eol(ver: &ver::Specific, latest_stable: Option<u32>) -> bool {
+    ver.is_stable() && latest_stable.is_some_and(|latest| ver.major + 1 < latest)
+}
+
 #[derive(clap::Args, IntoArgs, Debug, Clone)]
 pub struct Command {
     #[arg(long)]
     pub installed_only: bool,
 
+    /// Also list versions available in Cloud, with a column showing
+    /// which of them can be used for Cloud instances.
+    #[arg(long, conflicts_with = "installed_only")]
+    pub include_cloud: bool,
+
     /// Single column output.
     #[arg(long, value_parser=[
         "major-version", "installed", "available",
@@ -118,17 +116,25 @@ pub struct DebugInfo {
     install: Option<DebugInstall>,
 }
 
+#[derive(Default)]
 pub struct Pair {
     package: Option<PackageInfo>,
     install: Option<InstallInfo>,
+    cloud: bool,
 }
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct JsonVersionInfo {
     channel: Channel,
-    version: ver::Build,
+    version: String,
     installed: bool,
+    /// Only set with `--include-cloud`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cloud: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    release_date: Option<String>,
+    eol: bool,
     debug_info: DebugInfo,
 }
 
@@ -149,21 +155,34 @@ pub fn all_packages() -> Vec<PackageInfo> {
     pkgs
 }
 
-fn print_table(items: impl Iterator<Item = (ver::Build, bool)>) {
+fn print_table(versions: &[JsonVersionInfo]) {
+    let with_cloud = versions.iter().any(|v| v.cloud.is_some());
     let mut table = Table::new();
     table.set_format(*table::FORMAT);
-    table.add_row(Row::new(vec![
+    let mut header = vec![
         table::header_cell("Channel"),
         table::header_cell("Version"),
+        table::header_cell("Released"),
+        table::header_cell("EOL"),
         table::header_cell("Installed"),
-    ]));
-    for (ver, installed) in items {
-        let channel = Channel::from_version(&ver.specific());
-        table.add_row(Row::new(vec![
-            Cell::new(channel.as_ref().map_or("nightly", |x| x.as_str())),
-            Cell::new(&ver.to_string()),
-            Cell::new(if installed { "✓" } else { "" }),
-        ]));
+    ];
+    if with_cloud {
+        header.push(table::header_cell("Cloud"));
+    }
+    table.add_row(Row::new(header));
+    let check = |flag: bool| Cell::new(if flag { "✓" } else { "" });
+    for ver in versions {
+        let mut row = vec![
+            Cell::new(ver.channel.as_str()),
+            Cell::new(&ver.version),
+            Cell::new(ver.release_date.as_deref().unwrap_or("")),
+            check(ver.eol),
+            check(ver.installed),
+        ];
+        if with_cloud {
+            row.push(check(ver.cloud == Some(true)));
+        }
+        table.add_row(Row::new(row));
     }
     table.printstd();
 }
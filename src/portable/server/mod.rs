@@ -1,9 +1,13 @@
+pub mod cache;
 pub mod info;
 pub mod install;
 pub mod list_versions;
+pub mod prune;
 pub mod uninstall;
 
-pub fn run(cmd: &Command) -> Result<(), anyhow::Error> {
+use crate::options::Options;
+
+pub fn run(cmd: &Command, options: &Options) -> Result<(), anyhow::Error> {
     use crate::portable::windows;
     use Subcommands::*;
 
@@ -12,10 +16,14 @@ pub fn run(cmd: &Command) -> Result<(), anyhow::Error> {
         Install(c) => install::run(c),
         Uninstall(c) if cfg!(windows) => windows::uninstall(c),
         Uninstall(c) => uninstall::run(c),
+        Prune(c) if cfg!(windows) => windows::server_prune(c),
+        Prune(c) => prune::run(c),
         ListVersions(c) if cfg!(windows) => windows::list_versions(c),
-        ListVersions(c) => list_versions::run(c),
+        ListVersions(c) => list_versions::run(c, options),
         Info(c) if cfg!(windows) => windows::info(c),
         Info(c) => info::run(c),
+        Cache(c) if cfg!(windows) => windows::server_cache(c),
+        Cache(c) => cache::run(c),
     }
 }
 
@@ -33,6 +41,10 @@ pub enum Subcommands {
     Install(install::Command),
     /// Uninstall a server version locally.
     Uninstall(uninstall::Command),
+    /// Uninstall server versions not used by any instance.
+    Prune(prune::Command),
     /// List available and installed versions of the server.
     ListVersions(list_versions::Command),
+    /// Manage downloaded server packages.
+    Cache(cache::Command),
 }
@@ -0,0 +1,62 @@
+use edgedb_cli_derive::IntoArgs;
+use indicatif::HumanBytes;
+
+use crate::commands::ExitCode;
+use crate::portable::exit_codes;
+use crate::portable::server::info::installations;
+use crate::portable::server::uninstall;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+
+pub fn run(cmd: &Command) -> anyhow::Result<()> {
+    let unused: Vec<_> = installations()?
+        .into_iter()
+        .filter(|item| item.unused)
+        .collect();
+    if unused.is_empty() {
+        print::success!("Nothing to prune, all versions are in use.");
+        return Ok(());
+    }
+    let size: u64 = unused.iter().map(|item| item.size).sum();
+    msg!("Versions not used by any instance:");
+    for item in &unused {
+        msg!("  {} ({})", item.version, HumanBytes(item.size));
+    }
+    if cmd.dry_run {
+        msg!(
+            "Would free {}, run without `--dry-run` to remove.",
+            HumanBytes(size).to_string().emphasize()
+        );
+        return Ok(());
+    }
+    if !cmd.non_interactive {
+        let q = question::Confirm::new(format!(
+            "Remove {} versions, freeing {}?",
+            unused.len(),
+            HumanBytes(size)
+        ));
+        if !q.ask()? {
+            print::error!("Canceled by user.");
+            return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+        }
+    }
+    for item in &unused {
+        uninstall::remove(&item.version)?;
+    }
+    print::success!(
+        "Removed {} versions, freed {}.",
+        unused.len(),
+        HumanBytes(size)
+    );
+    Ok(())
+}
+
+#[derive(clap::Args, IntoArgs, Debug, Clone)]
+pub struct Command {
+    /// Only list the versions that would be removed.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Remove without asking for confirmation.
+    #[arg(long)]
+    pub non_interactive: bool,
+}
@@ -56,14 +56,7 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
     });
     let mut uninstalled = 0;
     for cand in candidates {
-        log::info!("Uninstalling {}", cand.version);
-        let path = portable_dir()?.join(cand.version.specific().to_string());
-        let tmp_dir = tmp_file_path(&path);
-        if tmp_dir.exists() {
-            fs::remove_dir_all(&tmp_dir)?;
-        }
-        fs::rename(path, &tmp_dir)?;
-        fs::remove_dir_all(&tmp_dir)?;
+        remove(&cand.version)?;
         uninstalled += 1;
     }
 
@@ -82,6 +75,19 @@ pub fn run(options: &Command) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Removes the installation directory of the version
+pub fn remove(version: &ver::Build) -> anyhow::Result<()> {
+    log::info!("Uninstalling {}", version);
+    let path = portable_dir()?.join(version.specific().to_string());
+    let tmp_dir = tmp_file_path(&path);
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::rename(path, &tmp_dir)?;
+    fs::remove_dir_all(&tmp_dir)?;
+    Ok(())
+}
+
 #[derive(clap::Args, IntoArgs, Debug, Clone)]
 pub struct Command {
     /// Uninstall all versions.
@@ -10,6 +10,7 @@ use std::time::{Duration, SystemTime};
 
 use anyhow::Context;
 use fn_error_context::context;
+use gel_tokio::Config;
 use libflate::gzip;
 use once_cell::sync::{Lazy, OnceCell};
 use url::Url;
@@ -21,6 +22,7 @@ use crate::cli::env::Env;
 use crate::cli::upgrade::{self, self_version};
 use crate::collect::Collector;
 use crate::commands::ExitCode;
+use crate::connect;
 use crate::credentials;
 use crate::hint::HintExt;
 use crate::platform::{cache_dir, config_dir, tmp_file_path, wsl_dir};
@@ -30,7 +32,7 @@ use crate::portable::instance::control;
 use crate::portable::instance::create;
 use crate::portable::instance::destroy;
 use crate::portable::instance::status;
-use crate::portable::local::{write_json, InstanceInfo, NonLocalInstance, Paths};
+use crate::portable::local::{allocate_port, write_json, InstanceInfo, NonLocalInstance, Paths};
 use crate::portable::options;
 use crate::portable::project;
 use crate::portable::repository::{self, download, PackageHash, PackageInfo};
@@ -768,6 +770,42 @@ pub fn list_versions(options: &server::list_versions::Command) -> anyhow::Result
     Ok(())
 }
 
+pub fn server_prune(options: &server::prune::Command) -> anyhow::Result<()> {
+    if let Some(wsl) = get_wsl()? {
+        wsl.edgedb()
+            .arg("server")
+            .arg("prune")
+            .args(options)
+            .run()?;
+    } else {
+        log::warn!(
+            "WSL distribution is not installed, \
+                   so no {BRANDING} server versions are present."
+        );
+    }
+    Ok(())
+}
+
+pub fn server_cache(options: &server::cache::Command) -> anyhow::Result<()> {
+    use server::cache::Subcommand;
+
+    if let Some(wsl) = get_wsl()? {
+        let mut cmd = wsl.edgedb();
+        cmd.arg("server").arg("cache");
+        match &options.subcommand {
+            Subcommand::List(c) => cmd.arg("list").args(c),
+            Subcommand::Clear(c) => cmd.arg("clear").args(c),
+        };
+        cmd.run()?;
+    } else {
+        log::warn!(
+            "WSL distribution is not installed, \
+                   so no {BRANDING} server packages are downloaded."
+        );
+    }
+    Ok(())
+}
+
 pub fn info(options: &server::info::Command) -> anyhow::Result<()> {
     if let Some(wsl) = get_wsl()? {
         wsl.edgedb().arg("server").arg("info").args(options).run()?;
@@ -873,6 +911,7 @@ pub fn logs(options: &control::Logs) -> anyhow::Result<()> {
 }
 
 pub fn status(options: &status::Status) -> anyhow::Result<()> {
+    status::wait_until(options)?;
     if options.service {
         if let Some(wsl) = get_wsl()? {
             wsl.edgedb()
@@ -890,6 +929,7 @@ pub fn status(options: &status::Status) -> anyhow::Result<()> {
     } else {
         let inner_opts = status::Status {
             quiet: true,
+            wait_until: None,
             ..options.clone()
         };
         if let Some(wsl) = get_wsl()? {
@@ -931,6 +971,8 @@ fn list_local(options: &status::List) -> anyhow::Result<Vec<status::JsonStatus>>
         extended: false,
         debug: false,
         json: true,
+        html: false,
+        html_styles: false,
         ..options.clone()
     };
     let local: Vec<status::JsonStatus> = if let Some(wsl) = get_wsl()? {
@@ -950,7 +992,7 @@ fn list_local(options: &status::List) -> anyhow::Result<Vec<status::JsonStatus>>
 
 pub fn list(options: &status::List, opts: &crate::Options) -> anyhow::Result<()> {
     let errors = Collector::new();
-    let local = match list_local(options) {
+    let mut local = match list_local(options) {
         Ok(local) => local,
         Err(e) => {
             errors.add(e);
@@ -962,10 +1004,10 @@ pub fn list(options: &status::List, opts: &crate::Options) -> anyhow::Result<()>
         .map(|v| v.name.clone())
         .collect::<BTreeSet<_>>();
 
-    let remote = if options.no_remote {
+    let mut remote = if options.no_remote {
         Vec::new()
     } else {
-        match status::get_remote(&visited, opts, &errors) {
+        match status::get_remote(&visited, !options.no_probe, opts, &errors) {
             Ok(remote) => remote,
             Err(e) => {
                 errors.add(e);
@@ -973,6 +1015,7 @@ pub fn list(options: &status::List, opts: &crate::Options) -> anyhow::Result<()>
             }
         }
     };
+    status::add_details(&mut local, &mut remote);
 
     if local.is_empty() && remote.is_empty() {
         if status::print_errors(&errors.list(), false) {
@@ -1004,6 +1047,8 @@ pub fn list(options: &status::List, opts: &crate::Options) -> anyhow::Result<()>
                     .collect::<Vec<_>>()
             )?
         );
+    } else if options.html {
+        status::print_html(&local, &remote, options.html_styles);
     } else {
         status::print_table(&local, &remote);
     }
@@ -1027,6 +1072,31 @@ pub fn upgrade(options: &instance::upgrade::Command, name: &str) -> anyhow::Resu
     Ok(())
 }
 
+pub fn clone(options: &instance::clone::Command, target: &str) -> anyhow::Result<()> {
+    let wsl = try_get_wsl()?;
+    let port = options
+        .port
+        .map(Ok)
+        .unwrap_or_else(|| allocate_port(target))?;
+    let inner_options = instance::clone::Command {
+        port: Some(port),
+        ..options.clone()
+    };
+    wsl.edgedb()
+        .arg("instance")
+        .arg("clone")
+        .args(&inner_options)
+        .run()?;
+    let credentials = credentials::path(target)?;
+    if let Some(dir) = credentials.parent() {
+        fs_err::create_dir_all(dir)?;
+    }
+    wsl.copy_out(credentials_linux(target), &credentials)?;
+    create_and_start(wsl, target)?;
+    msg!("Instance {} is up and running.", target.emphasize());
+    Ok(())
+}
+
 pub fn revert(options: &instance::revert::Command, name: &str) -> anyhow::Result<()> {
     let wsl = try_get_wsl()?;
     wsl.edgedb()
@@ -1072,6 +1142,14 @@ pub fn read_jose_keys_legacy(name: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
     ))
 }
 
+/// Unix sockets of the instances are inside WSL, so admin connections
+/// use TCP loopback with the credentials of the instance instead
+pub fn check_admin_config(name: &str, cfg: &Config) -> anyhow::Result<()> {
+    let wsl = try_get_wsl()?;
+    get_instance_data_dir(name, wsl)?;
+    connect::check_admin_over_tcp(cfg)
+}
+
 pub fn get_instance_info(name: &str) -> anyhow::Result<String> {
     let wsl = try_get_wsl()?;
     wsl.read_text_file(format!(
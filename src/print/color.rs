@@ -4,7 +4,7 @@ use colorful::core::ColorInterface;
 use colorful::Color;
 
 static THEME: once_cell::sync::Lazy<Theme> = once_cell::sync::Lazy::new(|| {
-    if concolor::get(concolor::Stream::Stdout).color() {
+    if !super::structured::is_json() && concolor::get(concolor::Stream::Stdout).color() {
         Theme {
             fade: Some(Style {
                 color: Color::Grey37,
@@ -149,7 +149,10 @@ impl<T: fmt::Display> fmt::Display for Colored<&T> {
 
 #[macro_export]
 macro_rules! msg {
+    () => {
+        $crate::print::write_msg(format_args!(""))
+    };
     ($($tt:tt)*) => {
-        eprintln!($($tt)*);
-    }
+        $crate::print::write_msg(format_args!($($tt)*))
+    };
 }
@@ -0,0 +1,79 @@
+use std::fmt::Write;
+
+use crate::print::table::{cell_value, columns};
+use crate::print::Config;
+
+const TABLE_STYLE: &str = "border-collapse: collapse; font-family: sans-serif; font-size: 14px";
+const HEADER_STYLE: &str = "border: 1px solid #ccc; padding: 4px 8px; \
+                            background-color: #f2f2f2; text-align: left";
+const CELL_STYLE: &str = "border: 1px solid #ccc; padding: 4px 8px; vertical-align: top";
+
+/// Renders JSON query results as a standalone HTML table.
+///
+/// Columns are chosen the same way as for `--output-format=table`.
+pub fn json_html_to_string(items: &[serde_json::Value], config: &Config) -> String {
+    let columns = columns(items);
+    let rows = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|col| cell_value(item, col))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    html_table(&columns, &rows, config.html_styles)
+}
+
+/// Renders a `<table>` element with a header row. All text is escaped.
+///
+/// With `styles` enabled, borders and padding are set using inline
+/// `style` attributes, so the table looks reasonable when pasted into an
+/// email which can't reference a stylesheet.
+pub fn html_table<S: AsRef<str>>(columns: &[&str], rows: &[Vec<S>], styles: bool) -> String {
+    let style = |css: &str| {
+        if styles {
+            format!(" style=\"{css}\"")
+        } else {
+            String::new()
+        }
+    };
+    let mut out = String::new();
+    writeln!(out, "<table{}>", style(TABLE_STYLE)).unwrap();
+    out += "<thead>\n<tr>";
+    for col in columns {
+        write!(out, "<th{}>{}</th>", style(HEADER_STYLE), escape(col)).unwrap();
+    }
+    out += "</tr>\n</thead>\n<tbody>\n";
+    for row in rows {
+        out += "<tr>";
+        for value in row {
+            write!(
+                out,
+                "<td{}>{}</td>",
+                style(CELL_STYLE),
+                escape(value.as_ref())
+            )
+            .unwrap();
+        }
+        out += "</tr>\n";
+    }
+    out += "</tbody>\n</table>\n";
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' => out.push_str("<br>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
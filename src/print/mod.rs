@@ -6,6 +6,7 @@ use std::sync::OnceLock;
 
 use colorful::{Color, Colorful};
 use const_format::concatcp;
+use indicatif::{ProgressBar, ProgressDrawTarget};
 use is_terminal::IsTerminal;
 use snafu::{AsErrorSource, ResultExt, Snafu};
 use terminal_size::{terminal_size, Width};
@@ -21,10 +22,13 @@ pub use crate::msg;
 mod buffer;
 mod color;
 mod formatter;
+mod html;
 mod json;
 mod native;
 mod stream;
+pub mod structured;
 pub mod style;
+mod table;
 #[cfg(test)]
 mod tests;
 
@@ -35,8 +39,11 @@ use buffer::{Delim, Exception, UnwrapExc, WrapErr};
 pub use color::Highlight;
 use formatter::ColorfulExt;
 pub(in crate::print) use formatter::Formatter;
+pub use html::{html_table, json_html_to_string};
 pub(in crate::print) use native::FormatExt;
 use stream::Output;
+pub use stream::{start_pager, write_stdout};
+pub use table::json_table_to_string;
 
 #[derive(Snafu, Debug)]
 #[snafu(context(suffix(false)))]
@@ -56,6 +63,11 @@ pub struct Config {
     pub implicit_properties: bool,
     pub max_items: Option<usize>,
     pub max_vector_length: VectorLimit,
+    pub max_col_width: Option<usize>,
+    pub vertical: bool,
+    pub header: bool,
+    pub null_as: String,
+    pub html_styles: bool,
     pub styler: style::Styler,
 }
 
@@ -96,6 +108,11 @@ impl Config {
             implicit_properties: false,
             max_items: None,
             max_vector_length: VectorLimit::Unlimited,
+            max_col_width: None,
+            vertical: false,
+            header: true,
+            null_as: String::new(),
+            html_styles: false,
             styler: style::Styler::dark_256(),
         }
     }
@@ -124,6 +141,26 @@ impl Config {
         self.implicit_properties = value;
         self
     }
+    pub fn max_col_width(&mut self, value: Option<usize>) -> &mut Config {
+        self.max_col_width = value;
+        self
+    }
+    pub fn vertical(&mut self, value: bool) -> &mut Config {
+        self.vertical = value;
+        self
+    }
+    pub fn header(&mut self, value: bool) -> &mut Config {
+        self.header = value;
+        self
+    }
+    pub fn null_as(&mut self, value: &str) -> &mut Config {
+        self.null_as = value.to_string();
+        self
+    }
+    pub fn html_styles(&mut self, value: bool) -> &mut Config {
+        self.html_styles = value;
+        self
+    }
 }
 
 pub fn completion<B: AsRef<[u8]>>(res: B) {
@@ -238,6 +275,25 @@ where
     _native_format(rows, config, w, colors, Stdout {}).await
 }
 
+/// Formats rows in the native format into a string, e.g. to write
+/// them into a file
+pub async fn native_to_string<I>(items: &[I], config: &Config) -> String
+where
+    I: FormatExt + Clone + Send + Sync,
+{
+    let mut out = String::new();
+    let rows = tokio_stream::iter(items.iter().cloned().map(Ok::<_, Infallible>));
+    let max_width = config.max_width.unwrap_or(80);
+    let colors = config.colors.unwrap_or(false);
+    if let Err(e) = _native_format(rows, config, max_width, colors, &mut out).await {
+        match e {
+            PrintError::StreamErr { source } => match source {},
+            PrintError::PrintErr { source } => match source {},
+        }
+    }
+    out
+}
+
 async fn _native_format<S, I, E, O>(
     mut rows: S,
     config: &Config,
@@ -401,7 +457,7 @@ pub fn use_utf8() -> bool {
 
 /// Does this terminal support ANSI colors?
 pub fn use_color() -> bool {
-    concolor::get(concolor::Stream::Stdout).ansi_color()
+    !structured::is_json() && concolor::get(concolor::Stream::Stdout).ansi_color()
 }
 
 pub fn prompt(line: impl fmt::Display) {
@@ -416,9 +472,31 @@ pub fn err_marker() -> impl fmt::Display {
     concatcp!(BRANDING_CLI_CMD, " error:").err_marker()
 }
 
+/// Hides the progress bar with `--log-format json`, as it redraws the
+/// terminal line. Progress is reported by the messages around it instead.
+pub fn progress(bar: ProgressBar) -> ProgressBar {
+    if structured::is_json() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar
+}
+
+#[doc(hidden)]
+pub fn write_msg(line: fmt::Arguments) {
+    if structured::is_json() {
+        structured::write_record("info", line);
+    } else {
+        eprintln!("{line}");
+    }
+}
+
 #[doc(hidden)]
 pub fn write_error(line: impl fmt::Display) {
     let text = format!("{line:#}");
+    if structured::is_json() {
+        structured::write_record("error", text);
+        return;
+    }
     if text.len() > 60 {
         msg!("{} {}", err_marker(), text);
     } else {
@@ -428,6 +506,10 @@ pub fn write_error(line: impl fmt::Display) {
 }
 
 pub fn edgedb_error(err: &gel_errors::Error, verbose: bool) {
+    if structured::is_json() {
+        structured::write_record("error", display_error(err, verbose));
+        return;
+    }
     // Note: not using `error()` as display_error has markup inside
     msg!("{} {}", err_marker(), display_error(err, verbose));
 }
@@ -455,7 +537,9 @@ pub fn success_msg(title: impl fmt::Display, msg: impl fmt::Display) {
 
 #[doc(hidden)]
 pub fn write_warn(line: impl fmt::Display) {
-    if use_color() {
+    if structured::is_json() {
+        structured::write_record("warn", line);
+    } else if use_color() {
         msg!("{}", line.to_string().bold().yellow());
     } else {
         msg!("{line}");
@@ -1,7 +1,17 @@
 use std::convert::Infallible;
+use std::env;
 use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use is_terminal::IsTerminal;
+use terminal_size::{terminal_size, Height};
 
 use super::Stdout;
+use crate::interrupt;
+
+/// Pager of the currently running REPL query, see [`start_pager`]
+static PAGER: Mutex<Option<Pager>> = Mutex::new(None);
 
 pub(in crate::print) trait Output {
     type Error;
@@ -19,7 +29,151 @@ impl<'a> Output for &'a mut String {
 impl Output for Stdout {
     type Error = io::Error;
     fn write(&mut self, data: &str) -> Result<(), io::Error> {
-        io::stdout().lock().write_all(data.as_bytes())?;
+        write_stdout(data)
+    }
+}
+
+struct Pager {
+    always: bool,
+    max_lines: usize,
+    lines: usize,
+    buffer: String,
+    child: Option<Child>,
+    /// Pager can't be started, output is written directly
+    direct: bool,
+    /// User has quit the pager
+    closed: bool,
+}
+
+/// Sends output to the pager until dropped
+pub struct PagerGuard {
+    _private: (),
+}
+
+/// Starts collecting output written via [`write_stdout`]
+///
+/// The output is piped to `$PAGER` (`less` by default) as soon as it exceeds
+/// the screen, or when finished if `always` is set. Nothing is done if stdout
+/// isn't a terminal.
+pub fn start_pager(always: bool) -> Option<PagerGuard> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let max_lines = terminal_size()
+        .map(|(_w, Height(h))| h.into())
+        .unwrap_or(24usize)
+        // leave space for the prompt
+        .saturating_sub(1);
+    *PAGER.lock().unwrap() = Some(Pager {
+        always,
+        max_lines,
+        lines: 0,
+        buffer: String::new(),
+        child: None,
+        direct: false,
+        closed: false,
+    });
+    Some(PagerGuard { _private: () })
+}
+
+/// Writes data to stdout or to the pager, if one is active
+pub fn write_stdout(data: &str) -> io::Result<()> {
+    match &mut *PAGER.lock().unwrap() {
+        Some(pager) => pager.write(data),
+        None => write_direct(data),
+    }
+}
+
+fn write_direct(data: &str) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    out.write_all(data.as_bytes())?;
+    out.flush()
+}
+
+impl Pager {
+    fn write(&mut self, data: &str) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        if self.direct {
+            return write_direct(data);
+        }
+        if let Some(stdin) = self.child.as_mut().and_then(|c| c.stdin.as_mut()) {
+            return match stdin.write_all(data.as_bytes()) {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                    // user has quit the pager, skip the rest of the output
+                    self.closed = true;
+                    Ok(())
+                }
+                res => res,
+            };
+        }
+        self.buffer.push_str(data);
+        self.lines += data.matches('\n').count();
+        if self.lines > self.max_lines {
+            self.spawn()?;
+        }
+        Ok(())
+    }
+
+    fn spawn(&mut self) -> io::Result<()> {
+        let command = env::var("PAGER")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| if cfg!(windows) { "more" } else { "less" }.into());
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        cmd.arg(&command).stdin(Stdio::piped());
+        if env::var_os("LESS").is_none() {
+            // keep colors, quit if output fits the screen, don't clear it
+            cmd.env("LESS", "FRX");
+        }
+        let buffer = std::mem::take(&mut self.buffer);
+        match cmd.spawn() {
+            Ok(child) => {
+                self.child = Some(child);
+                self.write(&buffer)
+            }
+            Err(e) => {
+                log::warn!("Cannot run pager {command:?}: {e}");
+                self.direct = true;
+                write_direct(&buffer)
+            }
+        }
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if self.child.is_none() && self.always && !self.buffer.is_empty() {
+            self.spawn()?;
+        }
+        match self.child.take() {
+            Some(mut child) => {
+                drop(child.stdin.take());
+                child.wait()?;
+                // Ctrl+C pressed in the pager is not meant for the query
+                interrupt::discard_pending();
+            }
+            None => write_direct(&self.buffer)?,
+        }
         Ok(())
     }
 }
+
+impl Drop for PagerGuard {
+    fn drop(&mut self) {
+        let pager = PAGER.lock().unwrap().take();
+        if let Some(pager) = pager {
+            pager
+                .finish()
+                .map_err(|e| log::warn!("Error running pager: {e}"))
+                .ok();
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Structured output of messages for wrapper tooling (`--log-format json`)
+//!
+//! In JSON mode every message, warning, error and log record is written to
+//! stderr as a single-line JSON object. Colors are disabled in this mode
+//! and progress bars are hidden.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static ERROR_JSON: AtomicBool = AtomicBool::new(false);
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable output (default)
+    #[default]
+    Human,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Human-readable error, hints and causes (default)
+    #[default]
+    Human,
+    /// Single JSON object describing the failure
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    level: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<&'a str>,
+    message: &'a str,
+}
+
+/// Must be called before anything is printed
+pub fn set_format(format: LogFormat) {
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+pub fn set_error_format(format: ErrorFormat) {
+    ERROR_JSON.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+/// Whether the final error of the command is written as JSON
+pub fn is_error_json() -> bool {
+    ERROR_JSON.load(Ordering::Relaxed)
+}
+
+pub fn format_record(level: &str, target: Option<&str>, message: impl fmt::Display) -> String {
+    let message = message.to_string();
+    serde_json::to_string(&Record {
+        level,
+        target,
+        message: message.trim_end(),
+    })
+    .expect("record is serializable")
+}
+
+pub fn write_record(level: &str, message: impl fmt::Display) {
+    let message = message.to_string();
+    if message.trim().is_empty() {
+        return;
+    }
+    eprintln!("{}", format_record(level, None, message));
+}
@@ -0,0 +1,114 @@
+use indexmap::IndexSet;
+use prettytable::{Cell, Row, Table};
+use unicode_width::UnicodeWidthStr;
+
+use crate::print::{use_utf8, Config};
+use crate::table;
+
+const VALUE_COLUMN: &str = "value";
+
+/// Renders JSON query results as a grid.
+///
+/// Top-level properties of objects become columns, nested shapes, arrays
+/// and tuples are rendered as compact JSON. Non-object results are put into
+/// a single `value` column.
+pub fn json_table_to_string(items: &[serde_json::Value], config: &Config) -> String {
+    let columns = columns(items);
+    let rows = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|col| cell(item, col, config.max_col_width))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if config.vertical {
+        vertical(&columns, &rows)
+    } else {
+        grid(&columns, &rows)
+    }
+}
+
+pub(in crate::print) fn columns(items: &[serde_json::Value]) -> Vec<&str> {
+    let mut columns = IndexSet::new();
+    for item in items {
+        match item {
+            serde_json::Value::Object(map) => columns.extend(map.keys().map(|k| k.as_str())),
+            _ => {
+                columns.insert(VALUE_COLUMN);
+            }
+        }
+    }
+    columns.into_iter().collect()
+}
+
+fn cell(item: &serde_json::Value, column: &str, max_width: Option<usize>) -> String {
+    let text = cell_value(item, column).replace('\n', "\\n");
+    match max_width {
+        Some(width) => truncate(text, width),
+        None => text,
+    }
+}
+
+/// Text of a single cell: strings are used as is, other values are
+/// rendered as compact JSON.
+pub(in crate::print) fn cell_value(item: &serde_json::Value, column: &str) -> String {
+    use serde_json::Value as V;
+
+    let value = match item {
+        V::Object(map) => map.get(column),
+        _ if column == VALUE_COLUMN => Some(item),
+        _ => None,
+    };
+    match value {
+        None | Some(V::Null) => String::new(),
+        Some(V::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn truncate(text: String, width: usize) -> String {
+    if text.width() <= width {
+        return text;
+    }
+    let ellipsis = if use_utf8() { "…" } else { "..." };
+    let limit = width.saturating_sub(ellipsis.width());
+    let mut result = String::with_capacity(width);
+    let mut cur = 0;
+    for c in text.chars() {
+        let w = c.to_string().width();
+        if cur + w > limit {
+            break;
+        }
+        cur += w;
+        result.push(c);
+    }
+    result.push_str(ellipsis);
+    result
+}
+
+fn grid(columns: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = Table::new();
+    out.set_format(*table::FORMAT);
+    out.set_titles(Row::new(
+        columns.iter().map(|c| table::header_cell(c)).collect(),
+    ));
+    for row in rows {
+        out.add_row(Row::new(row.iter().map(|v| Cell::new(v)).collect()));
+    }
+    out.to_string()
+}
+
+fn vertical(columns: &[&str], rows: &[Vec<String>]) -> String {
+    let key_width = columns.iter().map(|c| c.width()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (idx, row) in rows.iter().enumerate() {
+        out += &format!("-[ RECORD {} ]-\n", idx + 1);
+        for (col, value) in columns.iter().zip(row) {
+            let pad = key_width - col.width();
+            out += &format!("{col}{:pad$} | {value}\n", "");
+        }
+    }
+    out
+}
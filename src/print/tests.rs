@@ -73,6 +73,8 @@ fn test_format<I: FormatExt + Clone + Send + Sync>(items: &[I]) -> Result<String
             implicit_properties: false,
             max_items: None,
             max_vector_length: VectorLimit::Unlimited,
+            max_col_width: None,
+            vertical: false,
             styler: Styler::dark_256(),
         },
     )
@@ -725,3 +727,54 @@ fn postgis_box_3d() {
         r###"{POLYGON((1 1 3,2 1 3,2 2 3,1 2 3,1 1 3))}"###
     );
 }
+
+#[test]
+fn table_vertical() {
+    let items = serde_json::from_str::<serde_json::Value>(
+        r#"[{"name": "alice", "friends": [{"name": "bob"}]}, {"name": "carol", "age": 7}]"#,
+    )
+    .unwrap();
+    let mut cfg = Config::new();
+    cfg.vertical(true);
+    assert_eq!(
+        print::json_table_to_string(items.as_array().unwrap(), &cfg),
+        "\
+-[ RECORD 1 ]-
+name    | alice
+friends | [{\"name\":\"bob\"}]
+age     | \n\
+-[ RECORD 2 ]-
+name    | carol
+friends | \n\
+age     | 7
+"
+    );
+}
+
+#[test]
+fn html_table() {
+    let items = serde_json::json!([
+        {"name": "<b>alice</b>", "note": "a & b\nc"},
+        {"name": "bob", "age": 7},
+    ]);
+    let cfg = Config::new();
+    assert_eq!(
+        print::json_html_to_string(items.as_array().unwrap(), &cfg),
+        "\
+<table>
+<thead>
+<tr><th>name</th><th>note</th><th>age</th></tr>
+</thead>
+<tbody>
+<tr><td>&lt;b&gt;alice&lt;/b&gt;</td><td>a &amp; b<br>c</td><td></td></tr>
+<tr><td>bob</td><td></td><td>7</td></tr>
+</tbody>
+</table>
+"
+    );
+    let mut cfg = Config::new();
+    cfg.html_styles(true);
+    let html = print::json_html_to_string(items.as_array().unwrap(), &cfg);
+    assert!(html.starts_with("<table style=\"border-collapse: collapse;"));
+    assert!(html.contains("<td style=\"border: 1px solid #ccc;"));
+}
@@ -139,6 +139,12 @@ impl IntoArg for &u16 {
     }
 }
 
+impl IntoArg for &u64 {
+    fn add_arg(self, process: &mut Native) {
+        process.arg(self.to_string());
+    }
+}
+
 impl IntoArg for &usize {
     fn add_arg(self, process: &mut Native) {
         process.arg(self.to_string());
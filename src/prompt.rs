@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fs;
 use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Context as _Context;
 use dirs::data_local_dir;
@@ -13,7 +15,9 @@ use rustyline::hint::Hinter;
 use rustyline::history::{FileHistory, History};
 use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::{self, error::ReadlineError, Cmd, KeyEvent, Modifiers};
+use rustyline::{ConditionalEventHandler, Event, EventContext, EventHandler};
 use rustyline::{Config, Context, Editor, Helper};
+use rustyline::{Movement, RepeatCount};
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::oneshot::Sender;
 
@@ -26,6 +30,7 @@ use crate::print::style::Styler;
 use crate::print::Highlight;
 use crate::prompt::variable::{InputFlags, VariableInput};
 use crate::repl::{FAILURE_MARKER, TX_MARKER};
+use crate::sql_statement;
 use edgeql_parser::preparser::full_statement;
 use gel_protocol::value::Value;
 
@@ -37,6 +42,7 @@ pub enum Control {
     EdgeqlInput {
         prompt: String,
         initial: String,
+        sql: bool,
         response: Sender<Input>,
     },
     ParameterInput {
@@ -47,6 +53,7 @@ pub enum Control {
         response: Sender<VarInput>,
     },
     ShowHistory {
+        pattern: Option<String>,
         ack: Sender<()>,
     },
     SpawnEditor {
@@ -72,6 +79,8 @@ pub enum VarInput {
 
 pub struct EdgeqlHelper {
     styler: Styler,
+    /// Input is SQL rather than EdgeQL
+    sql: bool,
 }
 
 impl Helper for EdgeqlHelper {}
@@ -88,18 +97,20 @@ impl Highlighter for EdgeqlHelper {
         prompt: &'p str,
         _default: bool,
     ) -> Cow<'b, str> {
-        if prompt.ends_with("> ") {
-            let content = &prompt[..prompt.len() - 2];
+        // SQL prompt ends with `=> `, EdgeQL one with `> `
+        let suffix = if prompt.ends_with("=> ") { "=> " } else { "> " };
+        if prompt.ends_with(suffix) {
+            let content = &prompt[..prompt.len() - suffix.len()];
             if content.ends_with(TX_MARKER) {
                 format!(
-                    "{}{}> ",
+                    "{}{}{suffix}",
                     &content[..content.len() - TX_MARKER.len()],
                     TX_MARKER.green()
                 )
                 .into()
             } else if content.ends_with(FAILURE_MARKER) {
                 return format!(
-                    "{}{}> ",
+                    "{}{}{suffix}",
                     &content[..content.len() - FAILURE_MARKER.len()],
                     FAILURE_MARKER.red()
                 )
@@ -123,6 +134,10 @@ impl Highlighter for EdgeqlHelper {
                 let bytes = backslash::full_statement(data);
                 highlight::backslash(&mut buf, &data[..bytes], &self.styler);
                 data = &data[bytes..];
+            } else if self.sql {
+                let bytes = sql_statement::full_statement(data).unwrap_or(data.len());
+                highlight::sql(&mut buf, &data[..bytes], &self.styler);
+                data = &data[bytes..];
             } else {
                 match full_statement(data.as_bytes(), None) {
                     Ok(bytes) => {
@@ -166,6 +181,13 @@ impl Highlighter for EdgeqlHelper {
 impl Validator for EdgeqlHelper {
     fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult, ReadlineError> {
         let input = ctx.input();
+        if self.sql {
+            return if sql_statement::is_complete(input) {
+                Ok(ValidationResult::Valid(None))
+            } else {
+                Ok(ValidationResult::Incomplete)
+            };
+        }
         let complete = match completion::current(input, input.len()).1 {
             completion::Current::EdgeQL { complete, .. } => complete,
             completion::Current::Empty => true,
@@ -195,13 +217,112 @@ impl Completer for EdgeqlHelper {
     }
 }
 
+/// Ctrl+R handler searching history for the text entered so far.
+///
+/// Entries are matched fuzzily and repeated presses cycle through the
+/// matches, best first. On an empty line falls back to the regular
+/// incremental reverse search.
+struct FuzzySearch {
+    entries: Arc<Mutex<Vec<String>>>,
+    state: Mutex<SearchState>,
+}
+
+#[derive(Default)]
+struct SearchState {
+    query: String,
+    shown: Option<String>,
+    skip: usize,
+}
+
+impl ConditionalEventHandler for FuzzySearch {
+    fn handle(
+        &self,
+        _evt: &Event,
+        _n: RepeatCount,
+        _positive: bool,
+        ctx: &EventContext,
+    ) -> Option<Cmd> {
+        let mut state = self.state.lock().unwrap();
+        if state.shown.as_deref() == Some(ctx.line()) {
+            state.skip += 1;
+        } else {
+            *state = SearchState {
+                query: ctx.line().to_string(),
+                shown: None,
+                skip: 0,
+            };
+        }
+        if state.query.trim().is_empty() {
+            return Some(Cmd::ReverseSearchHistory);
+        }
+        let entries = self.entries.lock().unwrap();
+        let matches = fuzzy_matches(&state.query, &entries);
+        if matches.is_empty() {
+            return Some(Cmd::Noop);
+        }
+        let found = matches[state.skip % matches.len()].to_string();
+        state.shown = Some(found.clone());
+        Some(Cmd::Replace(Movement::WholeBuffer, Some(found)))
+    }
+}
+
+/// Returns history entries matching `query`, best and most recent first.
+fn fuzzy_matches<'a>(query: &str, entries: &'a [String]) -> Vec<&'a str> {
+    let mut matches = Vec::new();
+    let mut seen = HashSet::new();
+    for entry in entries.iter().rev() {
+        if !seen.insert(entry.as_str()) {
+            continue;
+        }
+        if let Some(score) = fuzzy_score(query, entry) {
+            matches.push((score, entry.as_str()));
+        }
+    }
+    // stable sort keeps more recent entries first among equal scores
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Scores `candidate` if it contains all non-whitespace characters of
+/// `query` in order (case-insensitive). Consecutive characters and exact
+/// substrings score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<u64> {
+    let query_lower = query.trim().to_lowercase();
+    let candidate_lower = candidate.to_lowercase();
+    let mut score = 0;
+    if candidate_lower.contains(&query_lower) {
+        score += 100;
+    }
+    let mut chars = candidate_lower.chars().enumerate();
+    let mut prev = None;
+    for q in query_lower.chars().filter(|c| !c.is_whitespace()) {
+        let (idx, _) = chars.find(|(_, c)| *c == q)?;
+        score += match prev {
+            Some(p) if p + 1 == idx => 5,
+            _ => 1,
+        };
+        prev = Some(idx);
+    }
+    Some(score)
+}
+
+pub fn history_path(name: &str) -> anyhow::Result<PathBuf> {
+    let dir = data_local_dir().context("cannot find local data dir")?;
+    Ok(dir.join("edgedb").join(format!("{name}.history")))
+}
+
 pub fn load_history<H: rustyline::Helper, I: History>(
     ed: &mut Editor<H, I>,
     name: &str,
 ) -> Result<(), anyhow::Error> {
-    let dir = data_local_dir().context("cannot find local data dir")?;
-    let app_dir = dir.join("edgedb");
-    match ed.load_history(&app_dir.join(format!("{name}.history"))) {
+    load_history_file(ed, &history_path(name)?)
+}
+
+fn load_history_file<H: rustyline::Helper, I: History>(
+    ed: &mut Editor<H, I>,
+    path: &Path,
+) -> Result<(), anyhow::Error> {
+    match ed.load_history(path) {
         Err(ReadlineError::Io(e)) if e.kind() == ErrorKind::NotFound => {}
         Err(e) => return Err(e).context("error loading history")?,
         Ok(()) => {}
@@ -211,27 +332,41 @@ pub fn load_history<H: rustyline::Helper, I: History>(
 
 fn _save_history<H: Helper, I: History>(
     ed: &mut Editor<H, I>,
-    name: &str,
+    path: &Path,
 ) -> Result<(), anyhow::Error> {
-    let dir = data_local_dir().context("cannot find local data dir")?;
-    let app_dir = dir.join("edgedb");
-    if !app_dir.exists() {
-        fs::create_dir_all(&app_dir).context("cannot create application dir")?;
+    if let Some(dir) = path.parent() {
+        if !dir.exists() {
+            fs::create_dir_all(dir).context("cannot create application dir")?;
+        }
     }
-    ed.save_history(&app_dir.join(format!("{name}.history")))
+    ed.save_history(path)
         .context("error writing history file")?;
     Ok(())
 }
 
 pub fn save_history<H: Helper, I: History>(ed: &mut Editor<H, I>, name: &str) {
-    _save_history(ed, name)
+    match history_path(name) {
+        Ok(path) => save_history_file(ed, &path),
+        Err(e) => log::warn!("Cannot save history: {:#}", e),
+    }
+}
+
+fn save_history_file<H: Helper, I: History>(ed: &mut Editor<H, I>, path: &Path) {
+    _save_history(ed, path)
         .map_err(|e| {
             log::warn!("Cannot save history: {:#}", e);
         })
         .ok();
 }
 
-pub fn create_editor(config: &ConfigBuilder) -> anyhow::Result<Editor<EdgeqlHelper, FileHistory>> {
+pub struct EdgeqlEditor {
+    editor: Editor<EdgeqlHelper, FileHistory>,
+    history_file: PathBuf,
+    /// Copy of history entries for the Ctrl+R handler
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+pub fn create_editor(config: &ConfigBuilder, history_file: &Path) -> anyhow::Result<EdgeqlEditor> {
     let mut editor = Editor::<EdgeqlHelper, FileHistory>::with_config(config.clone().build())?;
     editor.bind_sequence(
         KeyEvent::new('\r', Modifiers::NONE),
@@ -240,15 +375,38 @@ pub fn create_editor(config: &ConfigBuilder) -> anyhow::Result<Editor<EdgeqlHelp
         },
     );
     editor.bind_sequence(KeyEvent::new('\r', Modifiers::ALT), Cmd::AcceptLine);
-    load_history(&mut editor, "edgeql")
+    load_history_file(&mut editor, history_file)
         .map_err(|e| {
             log::warn!("Cannot load history: {:#}", e);
         })
         .ok();
+    let history = editor.history();
+    let entries = (0..history.len())
+        .filter_map(|i| {
+            history
+                .get(i, rustyline::history::SearchDirection::Forward)
+                .ok()
+                .flatten()
+                .map(|r| r.entry.into_owned())
+        })
+        .collect();
+    let entries = Arc::new(Mutex::new(entries));
+    editor.bind_sequence(
+        KeyEvent::ctrl('R'),
+        EventHandler::Conditional(Box::new(FuzzySearch {
+            entries: entries.clone(),
+            state: Mutex::new(SearchState::default()),
+        })),
+    );
     editor.set_helper(Some(EdgeqlHelper {
         styler: Styler::dark_256(),
+        sql: false,
     }));
-    Ok(editor)
+    Ok(EdgeqlEditor {
+        editor,
+        history_file: history_file.to_owned(),
+        entries,
+    })
 }
 
 pub fn var_editor(
@@ -269,11 +427,11 @@ pub fn var_editor(
 
 pub fn edgeql_input(
     prompt: &str,
-    editor: &mut Editor<EdgeqlHelper, FileHistory>,
+    ed: &mut EdgeqlEditor,
     response: Sender<Input>,
     initial: &str,
 ) -> anyhow::Result<()> {
-    let text = match editor.readline_with_initial(prompt, (initial, "")) {
+    let text = match ed.editor.readline_with_initial(prompt, (initial, "")) {
         Ok(text) => text,
         Err(ReadlineError::Eof) => {
             response.send(Input::Eof).ok();
@@ -288,37 +446,54 @@ pub fn edgeql_input(
             return Ok(());
         }
     };
-    editor.add_history_entry(&text)?;
+    if ed.editor.add_history_entry(&text)? {
+        ed.entries.lock().unwrap().push(text.clone());
+    }
     response.send(Input::Text(text)).ok();
-    save_history(editor, "edgeql");
+    save_history_file(&mut ed.editor, &ed.history_file);
     Ok(())
 }
 
-pub fn main(mut control: Receiver<Control>) -> Result<(), anyhow::Error> {
+/// Runs the input thread.
+///
+/// `history_file` overrides the location of the query history, which is
+/// used for per-project history.
+pub fn main(
+    mut control: Receiver<Control>,
+    history_file: Option<PathBuf>,
+) -> Result<(), anyhow::Error> {
+    let history_file = match history_file {
+        Some(path) => path,
+        None => history_path("edgeql")?,
+    };
     let config = Config::builder();
     let config = config.edit_mode(EditMode::Emacs);
     let mut config = config.completion_type(CompletionType::List);
-    let mut editor = create_editor(&config)?;
+    let mut editor = create_editor(&config, &history_file)?;
     'outer: loop {
         match control.blocking_recv() {
             None => break 'outer,
             Some(Control::ViMode) => {
                 config = config.edit_mode(EditMode::Vi);
-                editor = create_editor(&config)?;
+                editor = create_editor(&config, &history_file)?;
             }
             Some(Control::EmacsMode) => {
                 config = config.edit_mode(EditMode::Emacs);
-                editor = create_editor(&config)?;
+                editor = create_editor(&config, &history_file)?;
             }
             Some(Control::SetHistoryLimit(h)) => {
                 config = config.max_history_size(h)?;
-                editor = create_editor(&config)?;
+                editor = create_editor(&config, &history_file)?;
             }
             Some(Control::EdgeqlInput {
                 prompt,
                 initial,
+                sql,
                 response,
             }) => {
+                if let Some(helper) = editor.editor.helper_mut() {
+                    helper.sql = sql;
+                }
                 edgeql_input(&prompt, &mut editor, response, &initial)?;
             }
             Some(Control::ParameterInput {
@@ -384,8 +559,8 @@ pub fn main(mut control: Receiver<Control>) -> Result<(), anyhow::Error> {
                 save_history(&mut editor, &format!("var_{}", &var_type.type_name()));
                 response.send(VarInput::Value(value)).ok();
             }
-            Some(Control::ShowHistory { ack }) => {
-                match show_history(editor.history()) {
+            Some(Control::ShowHistory { pattern, ack }) => {
+                match show_history(editor.editor.history(), pattern.as_deref()) {
                     Ok(()) => {}
                     Err(e) => {
                         eprintln!("Error displaying history: {e}");
@@ -394,7 +569,7 @@ pub fn main(mut control: Receiver<Control>) -> Result<(), anyhow::Error> {
                 ack.send(()).ok();
             }
             Some(Control::SpawnEditor { entry, response }) => {
-                let h = editor.history();
+                let h = editor.editor.history();
                 let e = entry.unwrap_or(-1);
                 let normal = if e < 0 {
                     (h.len() as isize)
@@ -433,11 +608,12 @@ pub fn main(mut control: Receiver<Control>) -> Result<(), anyhow::Error> {
             }
         }
     }
-    save_history(&mut editor, "edgeql");
+    save_history_file(&mut editor.editor, &editor.history_file);
     Ok(())
 }
 
-fn show_history(history: &dyn History) -> Result<(), anyhow::Error> {
+fn show_history(history: &dyn History, pattern: Option<&str>) -> Result<(), anyhow::Error> {
+    let pattern = pattern.map(|p| p.to_lowercase());
     let pager = pager_path()?;
     let mut items = pager.split_whitespace();
     let mut cmd = Command::new(items.next().unwrap());
@@ -447,6 +623,11 @@ fn show_history(history: &dyn History) -> Result<(), anyhow::Error> {
     let mut childin = child.stdin.take().expect("stdin is piped");
     for index in (0..history.len()).rev() {
         if let Ok(Some(s)) = history.get(index, rustyline::history::SearchDirection::Forward) {
+            if let Some(pattern) = &pattern {
+                if !s.entry.to_lowercase().contains(pattern) {
+                    continue;
+                }
+            }
             let prefix = format!("[-{}] ", history.len() - index);
             let mut lines = s.entry.lines();
             if let Some(first) = lines.next() {
@@ -465,7 +646,7 @@ fn show_history(history: &dyn History) -> Result<(), anyhow::Error> {
     }
 }
 
-fn spawn_editor(data: &str) -> Result<String, anyhow::Error> {
+pub fn spawn_editor(data: &str) -> Result<String, anyhow::Error> {
     let mut temp_file = tempfile::Builder::new().suffix(".edgeql").tempfile()?;
     temp_file.write_all(data.as_bytes())?;
     let temp_path = temp_file.into_temp_path();
@@ -481,3 +662,34 @@ fn spawn_editor(data: &str) -> Result<String, anyhow::Error> {
         Err(anyhow::anyhow!("editor exited with: {}", res))
     }
 }
+
+#[test]
+fn fuzzy_score_matching() {
+    assert_eq!(fuzzy_score("sel", "select 1"), Some(111));
+    assert_eq!(fuzzy_score("slt", "select"), Some(3));
+    assert_eq!(
+        fuzzy_score("  sel ", "select"),
+        fuzzy_score("sel", "select")
+    );
+    assert_eq!(fuzzy_score("xyz", "select"), None);
+    assert_eq!(fuzzy_score("tcel", "select"), None);
+}
+
+#[test]
+fn fuzzy_score_case() {
+    assert_eq!(fuzzy_score("SEL", "select"), Some(111));
+    assert_eq!(fuzzy_score("sel", "SELECT"), Some(111));
+    assert_eq!(fuzzy_score("sel", "SeLeCt"), fuzzy_score("SEL", "select"));
+}
+
+#[test]
+fn fuzzy_matches_order() {
+    let entries = ["s_e_l", "select 1", "insert", "sel", "select 1"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(
+        fuzzy_matches("sel", &entries),
+        vec!["select 1", "sel", "s_e_l"]
+    );
+    assert!(fuzzy_matches("update", &entries).is_empty());
+}
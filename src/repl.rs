@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
@@ -23,6 +24,7 @@ use crate::async_util::timeout;
 use crate::branding::{BRANDING, REPL_QUERY_TAG};
 use crate::connect::Connection;
 use crate::connect::Connector;
+use crate::outputs::{csv, tab_separated};
 use crate::portable::ver;
 use crate::print::{self, msg, Highlight};
 use crate::prompt::variable::VariableInput;
@@ -46,6 +48,9 @@ pub enum OutputFormat {
     JsonPretty,
     JsonLines,
     TabSeparated,
+    Csv,
+    Table,
+    Html,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -55,6 +60,14 @@ pub enum InputMode {
     Emacs,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Pager {
+    Off,
+    On,
+    Always,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 #[value(rename_all = "kebab-case")]
 pub enum PrintStats {
@@ -80,12 +93,19 @@ pub struct LastAnalyze {
     pub output: analyze::Analysis,
 }
 
+/// Rows of the last query result, kept for `\view`
+pub struct LastResult {
+    pub format: OutputFormat,
+    pub rows: Vec<Value>,
+}
+
 pub struct State {
     pub prompt: PromptRpc,
     pub print: print::Config,
     pub verbose_errors: bool,
     pub last_error: Option<anyhow::Error>,
     pub last_analyze: Option<LastAnalyze>,
+    pub last_result: Option<LastResult>,
     pub implicit_limit: Option<usize>,
     pub idle_transaction_timeout: EdbDuration,
     pub input_language: InputLanguage,
@@ -93,7 +113,12 @@ pub struct State {
     pub output_format: OutputFormat,
     pub display_typenames: bool,
     pub print_stats: PrintStats,
+    pub pager: Pager,
+    pub stats: bool,
     pub history_limit: usize,
+    /// Where the values of settings that aren't defaults come from, by
+    /// setting name: a file, the command line or the session
+    pub setting_origins: BTreeMap<&'static str, String>,
     pub conn_params: Connector,
     pub branch: String,
     pub connection: Option<Connection>,
@@ -104,6 +129,60 @@ pub struct State {
     pub current_branch: Option<String>,
 }
 
+impl LastResult {
+    /// Renders all rows in the format they were received in. Returns the
+    /// file extension matching the format and the text.
+    pub async fn render(&self, config: &print::Config) -> anyhow::Result<(&'static str, String)> {
+        use OutputFormat::*;
+
+        let json = || {
+            self.rows
+                .iter()
+                .map(|row| match row {
+                    Value::Str(s) => serde_json::from_str(s).context("cannot decode json result"),
+                    _ => anyhow::bail!("non-string value in JSON mode"),
+                })
+                .collect::<anyhow::Result<Vec<serde_json::Value>>>()
+        };
+        match self.format {
+            Default => Ok(("txt", print::native_to_string(&self.rows, config).await)),
+            Json => {
+                // every row is a whole JSON array
+                let mut text = String::new();
+                for value in json()? {
+                    text += &serde_json::to_string_pretty(&value)?;
+                    text += "\n";
+                }
+                Ok(("json", text))
+            }
+            JsonPretty | JsonLines => {
+                let text = serde_json::to_string_pretty(&json()?)? + "\n";
+                Ok(("json", text))
+            }
+            Table => Ok(("txt", print::json_table_to_string(&json()?, config))),
+            Html => Ok(("html", print::json_html_to_string(&json()?, config))),
+            TabSeparated | Csv => {
+                let mut text = String::new();
+                for (index, row) in self.rows.iter().enumerate() {
+                    if self.format == Csv {
+                        if index == 0 && config.header {
+                            if let Some(header) = csv::format_header(row) {
+                                text += &header;
+                                text += "\n";
+                            }
+                        }
+                        text += &csv::format_row(row, &config.null_as)?;
+                    } else {
+                        text += &tab_separated::format_row(row, &config.null_as)?;
+                    }
+                    text += "\n";
+                }
+                Ok((if self.format == Csv { "csv" } else { "tsv" }, text))
+            }
+        }
+    }
+}
+
 impl PromptRpc {
     pub async fn variable_input(
         &mut self,
@@ -199,6 +278,28 @@ impl State {
         self.set_idle_transaction_timeout().await?;
         Ok(())
     }
+    /// Connects to a different instance, keeping the current connection
+    /// if that fails
+    pub async fn switch_connector(
+        &mut self,
+        conn_params: Connector,
+        branch: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let branch = match branch {
+            Some(branch) => branch.to_owned(),
+            None => conn_params.get()?.branch().to_owned(),
+        };
+        let old_params = std::mem::replace(&mut self.conn_params, conn_params);
+        if let Err(e) = self.try_connect(&branch).await {
+            self.conn_params = old_params;
+            return Err(e);
+        }
+        // results and errors of the previous instance are meaningless now
+        self.last_error = None;
+        self.last_analyze = None;
+        self.last_result = None;
+        Ok(())
+    }
     pub async fn soft_reconnect(&mut self) -> anyhow::Result<()> {
         if self.in_transaction() {
             let is_closed = self
@@ -300,11 +401,17 @@ impl State {
             InputLanguage::Sql => "[sql]",
         };
 
-        let prompt = format!("{location}{lang}{txstate}> ");
+        let suffix = match self.input_language {
+            InputLanguage::EdgeQl => "> ",
+            InputLanguage::Sql => "=> ",
+        };
+
+        let prompt = format!("{location}{lang}{txstate}{suffix}");
 
         self.editor_cmd(|response| prompt::Control::EdgeqlInput {
             prompt,
             initial: initial.to_owned(),
+            sql: self.input_language == InputLanguage::Sql,
             response,
         })
         .await
@@ -323,8 +430,9 @@ impl State {
             .ok()
             .context("cannot send to input thread")
     }
-    pub async fn show_history(&mut self) -> anyhow::Result<()> {
-        self.editor_cmd(|ack| Control::ShowHistory { ack }).await
+    pub async fn show_history(&mut self, pattern: Option<String>) -> anyhow::Result<()> {
+        self.editor_cmd(|ack| Control::ShowHistory { pattern, ack })
+            .await
     }
     pub async fn spawn_editor(&mut self, entry: Option<isize>) -> anyhow::Result<prompt::Input> {
         self.editor_cmd(|response| Control::SpawnEditor { entry, response })
@@ -441,6 +549,9 @@ impl std::str::FromStr for OutputFormat {
             "json-pretty" => Ok(OutputFormat::JsonPretty),
             "json-lines" => Ok(OutputFormat::JsonLines),
             "tab-separated" => Ok(OutputFormat::TabSeparated),
+            "csv" => Ok(OutputFormat::Csv),
+            "table" => Ok(OutputFormat::Table),
+            "html" => Ok(OutputFormat::Html),
             "default" => Ok(OutputFormat::Default),
             _ => Err(anyhow::anyhow!("unsupported output mode {:?}", s)),
         }
@@ -450,13 +561,30 @@ impl std::str::FromStr for OutputFormat {
 impl From<OutputFormat> for IoFormat {
     fn from(val: OutputFormat) -> Self {
         match val {
-            OutputFormat::Default | OutputFormat::TabSeparated => IoFormat::Binary,
-            OutputFormat::JsonLines | OutputFormat::JsonPretty => IoFormat::JsonElements,
+            OutputFormat::Default | OutputFormat::TabSeparated | OutputFormat::Csv => {
+                IoFormat::Binary
+            }
+            OutputFormat::JsonLines
+            | OutputFormat::JsonPretty
+            | OutputFormat::Table
+            | OutputFormat::Html => IoFormat::JsonElements,
             OutputFormat::Json => IoFormat::Json,
         }
     }
 }
 
+impl std::str::FromStr for Pager {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Pager, anyhow::Error> {
+        match s {
+            "off" => Ok(Pager::Off),
+            "on" => Ok(Pager::On),
+            "always" => Ok(Pager::Always),
+            _ => Err(anyhow::anyhow!("unsupported pager mode {:?}", s)),
+        }
+    }
+}
+
 impl std::str::FromStr for PrintStats {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<PrintStats, anyhow::Error> {
@@ -498,6 +626,20 @@ impl OutputFormat {
             JsonPretty => "json-pretty",
             JsonLines => "json-lines",
             TabSeparated => "tab-separated",
+            Csv => "csv",
+            Table => "table",
+            Html => "html",
+        }
+    }
+}
+
+impl Pager {
+    pub fn as_str(&self) -> &'static str {
+        use Pager::*;
+        match self {
+            Off => "off",
+            On => "on",
+            Always => "always",
         }
     }
 }
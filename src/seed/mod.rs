@@ -0,0 +1,149 @@
+mod run;
+mod state;
+mod status;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use clap::ValueHint;
+use gel_tokio::get_stash_path;
+
+use crate::branding::{BRANDING_CLI_CMD, MANIFEST_FILE_DISPLAY_NAME};
+use crate::commands::Options;
+use crate::hint::HintExt;
+use crate::options::ConnectionOptions;
+use crate::portable::project;
+
+#[tokio::main(flavor = "current_thread")]
+pub async fn run(options: &Options, cmd: &Command) -> anyhow::Result<()> {
+    let project = SeedProject::load(cmd.project_dir.as_deref()).await?;
+    match &cmd.subcommand {
+        Subcommand::Run(c) => run::run(c, options, &project).await,
+        Subcommand::Status(c) => status::run(c, options, &project).await,
+    }
+}
+
+/// Populate the project branch with seed data.
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    #[command(flatten)]
+    pub conn: ConnectionOptions,
+
+    /// Explicitly set a root directory for the project
+    #[arg(long, value_hint=ValueHint::DirPath, global=true)]
+    pub project_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub subcommand: Subcommand,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum Subcommand {
+    Run(run::Command),
+    Status(status::Command),
+}
+
+pub struct SeedProject {
+    root: PathBuf,
+    stash_dir: PathBuf,
+    seeds: Vec<Seed>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Seed {
+    /// Entry as written in the manifest, or a path relative to the project
+    pub name: String,
+    pub kind: SeedKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum SeedKind {
+    EdgeQl(PathBuf),
+    Sql(PathBuf),
+    Script(String),
+}
+
+impl SeedProject {
+    async fn load(project_dir: Option<&Path>) -> anyhow::Result<SeedProject> {
+        let Some(ctx) = project::load_ctx(project_dir).await? else {
+            anyhow::bail!("`{MANIFEST_FILE_DISPLAY_NAME}` not found, seeds require a project.");
+        };
+        let stash_dir = get_stash_path(&ctx.location.root)?;
+        if !stash_dir.exists() {
+            return Err(anyhow::anyhow!("Project is not initialized.")
+                .with_hint(|| format!("Run `{BRANDING_CLI_CMD} project init`."))
+                .into());
+        }
+        let root = ctx.location.root.clone();
+        let config = ctx.manifest.seed.clone().unwrap_or_default();
+        let seeds = if config.files.is_empty() {
+            let dir = config
+                .dir
+                .clone()
+                .unwrap_or_else(|| ctx.manifest.project().get_schema_dir().join("seeds"));
+            scan_dir(&root, &dir)?
+        } else {
+            config
+                .files
+                .iter()
+                .map(|f| Seed::from_entry(&root, f))
+                .collect()
+        };
+        Ok(SeedProject {
+            root,
+            stash_dir,
+            seeds,
+        })
+    }
+}
+
+impl Seed {
+    fn from_entry(root: &Path, entry: &str) -> Seed {
+        let kind = match Path::new(entry).extension().and_then(|e| e.to_str()) {
+            Some("edgeql") => SeedKind::EdgeQl(root.join(entry)),
+            Some("sql") => SeedKind::Sql(root.join(entry)),
+            _ => SeedKind::Script(entry.into()),
+        };
+        Seed {
+            name: entry.into(),
+            kind,
+        }
+    }
+
+    /// Hash of the seed contents, used to detect seeds modified after
+    /// they were run
+    pub fn hash(&self) -> anyhow::Result<String> {
+        let data = match &self.kind {
+            SeedKind::EdgeQl(path) | SeedKind::Sql(path) => {
+                fs::read(path).with_context(|| format!("cannot read seed file {path:?}"))?
+            }
+            SeedKind::Script(cmd) => cmd.as_bytes().to_vec(),
+        };
+        Ok(blake3::hash(&data).to_hex().to_string())
+    }
+}
+
+fn scan_dir(root: &Path, dir: &Path) -> anyhow::Result<Vec<Seed>> {
+    let full_dir = root.join(dir);
+    if !full_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for item in fs::read_dir(&full_dir).with_context(|| format!("cannot read {full_dir:?}"))? {
+        let path = item?.path();
+        if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("edgeql" | "sql")
+        ) {
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names
+        .iter()
+        .map(|name| Seed::from_entry(root, &dir.join(name).to_string_lossy()))
+        .collect())
+}
@@ -0,0 +1,205 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use gel_protocol::client_message::{Cardinality, CompilationOptions, IoFormat};
+use gel_protocol::common::Capabilities;
+use gel_protocol::value::Value;
+
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::{ExitCode, Options};
+use crate::connect::Connection;
+use crate::migrations;
+use crate::migrations::options::{Migrate, MigrationConfig};
+use crate::portable::exit_codes;
+use crate::print::{self, msg, Highlight};
+use crate::question;
+use crate::repl::InputLanguage;
+use crate::seed::state::State;
+use crate::seed::{Seed, SeedKind, SeedProject};
+use crate::sql_statement;
+
+/// Run seeds that were not run on the current branch yet
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {
+    /// Wipe the branch, reapply migrations and run all seeds again
+    #[arg(long)]
+    pub reset: bool,
+
+    /// Do not ask for confirmation on `--reset`
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+pub async fn run(cmd: &Command, options: &Options, project: &SeedProject) -> anyhow::Result<()> {
+    if project.seeds.is_empty() {
+        msg!("No seeds found.");
+        return Ok(());
+    }
+    let mut conn = options.conn_params.connect().await?;
+    let branch = conn.get_current_branch().await?.to_string();
+    let mut state = State::read(&project.stash_dir)?;
+
+    if cmd.reset {
+        if !cmd.non_interactive {
+            let q = question::Confirm::new_dangerous(format!(
+                "Do you really want to wipe the contents of the branch {branch:?} \
+                 and run all seeds again?"
+            ));
+            if !conn.ping_while(q.async_ask()).await? {
+                print::error!("Canceled by user.");
+                return Err(ExitCode::new(exit_codes::NOT_CONFIRMED).into());
+            }
+        }
+        reset(&mut conn, options, project).await?;
+        state.branches.remove(&branch);
+        state.write(&project.stash_dir)?;
+    }
+
+    let mut ran = 0;
+    for seed in &project.seeds {
+        let hash = seed.hash()?;
+        match state.find(&branch, &seed.name) {
+            Some(applied) if applied.hash == hash => continue,
+            Some(_) => {
+                print::warn!(
+                    "Seed {} has changed since it was run, skipping. \
+                     Use `{BRANDING_CLI_CMD} seed run --reset` to run all seeds again.",
+                    seed.name
+                );
+                continue;
+            }
+            None => {}
+        }
+        msg!("Running seed {}...", seed.name.emphasize());
+        run_seed(&mut conn, seed, &project.root, &branch)
+            .await
+            .with_context(|| format!("seed {:?} failed", seed.name))?;
+        state.add(&branch, &seed.name, hash);
+        state.write(&project.stash_dir)?;
+        ran += 1;
+    }
+    if ran == 0 {
+        msg!("Branch {} is already seeded.", branch.emphasize());
+    } else {
+        msg!("Ran {ran} seed(s) on branch {}.", branch.emphasize());
+    }
+    Ok(())
+}
+
+async fn reset(
+    conn: &mut Connection,
+    options: &Options,
+    project: &SeedProject,
+) -> anyhow::Result<()> {
+    msg!("Wiping branch...");
+    conn.execute("RESET SCHEMA TO initial", &()).await?;
+    migrations::migrate(
+        conn,
+        options,
+        &Migrate {
+            cfg: MigrationConfig { schema_dir: None },
+            quiet: false,
+            to_revision: None,
+            down_to: None,
+            dev_mode: false,
+            single_transaction: false,
+            statement_timeout: None,
+            lock_timeout: None,
+            force_unsafe_ddl: false,
+            no_ddl_locking: false,
+            session_config: Vec::new(),
+            tag: None,
+            plan: false,
+            json: false,
+            conn: None,
+        },
+    )
+    .await
+    .with_context(|| format!("cannot apply migrations in {:?}", project.root))
+}
+
+async fn run_seed(
+    conn: &mut Connection,
+    seed: &Seed,
+    root: &Path,
+    branch: &str,
+) -> anyhow::Result<()> {
+    match &seed.kind {
+        SeedKind::EdgeQl(path) => {
+            let text = read(path)?;
+            conn.execute(&text, &()).await?;
+        }
+        SeedKind::Sql(path) => {
+            let text = read(path)?;
+            conn.execute("START TRANSACTION", &()).await?;
+            match run_sql(conn, &text).await {
+                Ok(()) => {
+                    conn.execute("COMMIT", &()).await?;
+                }
+                Err(e) => {
+                    conn.execute("ROLLBACK", &()).await.ok();
+                    return Err(e);
+                }
+            }
+        }
+        SeedKind::Script(script) => run_script(script, root, branch)?,
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> anyhow::Result<String> {
+    fs::read_to_string(path).with_context(|| format!("cannot read seed file {path:?}"))
+}
+
+async fn run_sql(conn: &mut Connection, text: &str) -> anyhow::Result<()> {
+    let flags = CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: true,
+        allow_capabilities: Capabilities::ALL,
+        input_language: InputLanguage::Sql.into(),
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+    };
+    let mut tail = text;
+    while !sql_statement::is_empty(tail) {
+        let len = sql_statement::full_statement(tail).unwrap_or(tail.len());
+        let statement = &tail[..len];
+        tail = &tail[len..];
+        if sql_statement::is_empty(statement) {
+            continue;
+        }
+        let desc = conn.parse(&flags, statement).await?;
+        let mut items = conn
+            .execute_stream::<Value, _>(&flags, statement, &desc, &())
+            .await?;
+        while items.next_element().await.is_some() {}
+        items.complete().await?;
+    }
+    Ok(())
+}
+
+fn run_script(script: &str, root: &Path, branch: &str) -> anyhow::Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let status = cmd
+        .arg(script)
+        .current_dir(root)
+        .env("EDGEDB_BRANCH", branch)
+        .env("GEL_BRANCH", branch)
+        .status()
+        .map_err(|e| anyhow::anyhow!("cannot run seed script: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("seed script failed: {status}");
+    }
+    Ok(())
+}
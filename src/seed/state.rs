@@ -0,0 +1,70 @@
+//! Record of the seeds that were run on each branch
+//!
+//! Kept in the project stash directory rather than in the database, so that
+//! it does not become a part of the schema managed by migrations.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Context;
+
+use crate::platform::tmp_file_path;
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct State {
+    /// Applied seeds, by branch name
+    #[serde(default)]
+    pub branches: BTreeMap<String, Vec<Applied>>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Applied {
+    pub name: String,
+    pub hash: String,
+    #[serde(with = "serde_millis")]
+    pub applied_at: SystemTime,
+}
+
+fn path(stash_dir: &Path) -> PathBuf {
+    stash_dir.join("seeds.json")
+}
+
+impl State {
+    pub fn read(stash_dir: &Path) -> anyhow::Result<State> {
+        let path = path(stash_dir);
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                serde_json::from_str(&text).with_context(|| format!("cannot decode {path:?}"))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(State::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {path:?}")),
+        }
+    }
+
+    pub fn write(&self, stash_dir: &Path) -> anyhow::Result<()> {
+        let path = path(stash_dir);
+        let tmp = tmp_file_path(&path);
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("cannot write {tmp:?}"))?;
+        fs::rename(&tmp, &path).with_context(|| format!("cannot write {path:?}"))?;
+        Ok(())
+    }
+
+    pub fn find(&self, branch: &str, name: &str) -> Option<&Applied> {
+        self.branches.get(branch)?.iter().find(|a| a.name == name)
+    }
+
+    pub fn add(&mut self, branch: &str, name: &str, hash: String) {
+        self.branches
+            .entry(branch.into())
+            .or_default()
+            .push(Applied {
+                name: name.into(),
+                hash,
+                applied_at: SystemTime::now(),
+            });
+    }
+}
@@ -0,0 +1,48 @@
+use crate::commands::Options;
+use crate::print::msg;
+use crate::seed::state::State;
+use crate::seed::SeedProject;
+use crate::table::{self, Cell, Row, Table};
+
+/// Show which seeds were run on the current branch
+#[derive(clap::Args, Debug, Clone)]
+pub struct Command {}
+
+pub async fn run(_cmd: &Command, options: &Options, project: &SeedProject) -> anyhow::Result<()> {
+    let mut conn = options.conn_params.connect().await?;
+    let branch = conn.get_current_branch().await?.to_string();
+    let state = State::read(&project.stash_dir)?;
+
+    let mut table = Table::new();
+    table.set_format(*table::FORMAT);
+    table.set_titles(Row::new(
+        ["Seed", "Status", "Run At"]
+            .iter()
+            .map(|x| table::header_cell(x))
+            .collect(),
+    ));
+    for seed in &project.seeds {
+        let (status, run_at) = match state.find(&branch, &seed.name) {
+            Some(applied) if applied.hash == seed.hash()? => (
+                "applied",
+                humantime::format_rfc3339_seconds(applied.applied_at).to_string(),
+            ),
+            Some(applied) => (
+                "changed",
+                humantime::format_rfc3339_seconds(applied.applied_at).to_string(),
+            ),
+            None => ("pending", String::new()),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&seed.name),
+            Cell::new(status),
+            Cell::new(&run_at),
+        ]));
+    }
+    if table.is_empty() {
+        msg!("No seeds found.");
+    } else {
+        table.printstd();
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! `--session-config key=value`: session settings applied with
+//! `CONFIGURE SESSION` before running queries or migrations.
+
+use std::fmt;
+use std::str::FromStr;
+
+use edgeql_parser::helpers::{quote_name, quote_string};
+
+use crate::connect::Connection;
+use crate::hint::HintExt;
+
+/// Session-level settings with a scalar type. Settings marked as
+/// `cfg::system` can only be changed for the whole instance.
+const SETTINGS_QUERY: &str = "\
+    FOR ptr IN (
+        SELECT (
+            SELECT schema::ObjectType FILTER .name = 'cfg::AbstractConfig'
+        ).pointers
+        FILTER .target IS schema::ScalarType
+            AND .name NOT IN {'id', '__type__'}
+            AND NOT EXISTS (
+                .annotations FILTER .name = 'cfg::system' AND @value = 'true'
+            )
+    )
+    UNION (ptr.name, ptr.target.name)
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionConfig {
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for SessionConfig {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<SessionConfig> {
+        let Some((name, value)) = s.split_once('=') else {
+            anyhow::bail!("expected `key=value`, got {s:?}");
+        };
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("invalid setting name {name:?}");
+        }
+        Ok(SessionConfig {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+}
+
+impl fmt::Display for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// Applies the settings to the session. The value is cast from a string
+/// to the type of the setting, so `key=true` or `key=10s` work without
+/// EdgeQL quoting.
+pub async fn apply(cli: &mut Connection, settings: &[SessionConfig]) -> anyhow::Result<()> {
+    if settings.is_empty() {
+        return Ok(());
+    }
+    let allowed: Vec<(String, String)> = cli.query(SETTINGS_QUERY, &()).await?;
+    for setting in settings {
+        let Some((_, type_name)) = allowed.iter().find(|(name, _)| name == &setting.name) else {
+            let mut names = allowed
+                .iter()
+                .map(|(name, _)| &name[..])
+                .collect::<Vec<_>>();
+            names.sort();
+            return Err(anyhow::anyhow!(
+                "{:?} is not a session setting",
+                setting.name
+            ))
+            .with_hint(|| format!("Allowed settings: {}", names.join(", ")))?;
+        };
+        log::info!("Setting session config {setting}");
+        cli.execute(
+            &format!(
+                "CONFIGURE SESSION SET {} := <{}>{}",
+                quote_name(&setting.name),
+                type_name,
+                quote_string(&setting.value),
+            ),
+            &(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Resets the settings changed by [`apply`], for connections which are
+/// used further.
+pub async fn reset(cli: &mut Connection, settings: &[SessionConfig]) -> anyhow::Result<()> {
+    if !cli.is_consistent() {
+        return Ok(());
+    }
+    for setting in settings {
+        cli.execute(
+            &format!("CONFIGURE SESSION RESET {}", quote_name(&setting.name)),
+            &(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        "apply_access_policies=false"
+            .parse::<SessionConfig>()
+            .unwrap(),
+        SessionConfig {
+            name: "apply_access_policies".into(),
+            value: "false".into(),
+        }
+    );
+    assert_eq!(
+        "query_execution_timeout=1 minute"
+            .parse::<SessionConfig>()
+            .unwrap()
+            .value,
+        "1 minute"
+    );
+    assert!("allow_user_specified_id".parse::<SessionConfig>().is_err());
+    assert!("a b=c".parse::<SessionConfig>().is_err());
+}
@@ -0,0 +1,207 @@
+//! Splitting of SQL input into statements
+//!
+//! This is a counterpart of `edgeql_parser::preparser` for the SQL input
+//! language. It knows about SQL comments (`--` and nested `/* */`),
+//! string literals (including `E'...'` escapes), quoted identifiers and
+//! dollar-quoted strings.
+
+use crate::commands::backslash;
+
+enum Token {
+    Space,
+    Comment,
+    Semicolon,
+    Other,
+}
+
+fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// Returns the kind and the end of the token starting at `pos`, or `None`
+/// if the token is not terminated.
+fn next_token(data: &[u8], pos: usize) -> Option<(Token, usize)> {
+    let tail = &data[pos..];
+    match tail[0] {
+        b';' => Some((Token::Semicolon, pos + 1)),
+        c if c.is_ascii_whitespace() => Some((Token::Space, pos + 1)),
+        b'-' if tail.get(1) == Some(&b'-') => {
+            let end = tail
+                .iter()
+                .position(|&c| c == b'\n')
+                .map(|idx| pos + idx + 1)
+                .unwrap_or(data.len());
+            Some((Token::Comment, end))
+        }
+        b'/' if tail.get(1) == Some(&b'*') => {
+            let mut depth = 0;
+            let mut idx = 0;
+            while idx + 1 < tail.len() {
+                match &tail[idx..idx + 2] {
+                    b"/*" => {
+                        depth += 1;
+                        idx += 2;
+                    }
+                    b"*/" => {
+                        depth -= 1;
+                        idx += 2;
+                        if depth == 0 {
+                            return Some((Token::Comment, pos + idx));
+                        }
+                    }
+                    _ => idx += 1,
+                }
+            }
+            None
+        }
+        b'\'' => {
+            let escapes = pos > 0
+                && matches!(data[pos - 1], b'E' | b'e')
+                && (pos < 2 || !is_ident_char(data[pos - 2]));
+            let mut idx = 1;
+            while idx < tail.len() {
+                match tail[idx] {
+                    b'\\' if escapes => idx += 2,
+                    b'\'' if tail.get(idx + 1) == Some(&b'\'') => idx += 2,
+                    b'\'' => return Some((Token::Other, pos + idx + 1)),
+                    _ => idx += 1,
+                }
+            }
+            None
+        }
+        b'"' => {
+            let mut idx = 1;
+            while idx < tail.len() {
+                match tail[idx] {
+                    b'"' if tail.get(idx + 1) == Some(&b'"') => idx += 2,
+                    b'"' => return Some((Token::Other, pos + idx + 1)),
+                    _ => idx += 1,
+                }
+            }
+            None
+        }
+        b'$' if pos == 0 || !is_ident_char(data[pos - 1]) => {
+            let tag_len = tail[1..].iter().take_while(|&&c| is_ident_char(c)).count();
+            if tail.get(1).map_or(false, |c| c.is_ascii_digit())
+                || tail.get(tag_len + 1) != Some(&b'$')
+            {
+                // positional parameter or a plain dollar sign
+                return Some((Token::Other, pos + 1));
+            }
+            let tag = &tail[..tag_len + 2];
+            let body = &tail[tag.len()..];
+            body.windows(tag.len())
+                .position(|w| w == tag)
+                .map(|idx| (Token::Other, pos + tag.len() + idx + tag.len()))
+        }
+        _ => Some((Token::Other, pos + 1)),
+    }
+}
+
+/// Returns the length of the first statement including the semicolon,
+/// or `None` if the statement is not complete yet.
+pub fn full_statement(data: &str) -> Option<usize> {
+    scan_statement(data).ok()
+}
+
+/// Same as [`full_statement`] but on incomplete statement returns the
+/// position where scanning can be resumed once more data is available.
+///
+/// Only whitespace outside of literals and comments is a safe point, as
+/// other tokens at the end of the data may continue into the next chunk.
+/// Scanning the data after that point gives the same tokens as scanning
+/// from the start.
+pub fn scan_statement(data: &str) -> Result<usize, usize> {
+    let bytes = data.as_bytes();
+    let mut pos = 0;
+    let mut continuation = 0;
+    while pos < bytes.len() {
+        let Some((token, end)) = next_token(bytes, pos) else {
+            return Err(continuation);
+        };
+        match token {
+            Token::Semicolon => return Ok(end),
+            Token::Space => continuation = end,
+            Token::Comment | Token::Other => {}
+        }
+        pos = end;
+    }
+    Err(continuation)
+}
+
+/// Returns `true` if there is nothing to execute in the data: only
+/// whitespace, comments and semicolons.
+pub fn is_empty(data: &str) -> bool {
+    let bytes = data.as_bytes();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match next_token(bytes, pos) {
+            Some((Token::Space | Token::Comment | Token::Semicolon, end)) => pos = end,
+            Some((Token::Other, _)) | None => return false,
+        }
+    }
+    true
+}
+
+/// Returns `true` if the REPL input can be submitted: every statement
+/// is terminated by a semicolon.
+pub fn is_complete(data: &str) -> bool {
+    let mut tail = data;
+    loop {
+        tail = tail.trim_start();
+        if is_empty(tail) {
+            return true;
+        }
+        if tail.starts_with('\\') {
+            tail = &tail[backslash::full_statement(tail)..];
+        } else {
+            match full_statement(tail) {
+                Some(len) => tail = &tail[len..],
+                None => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{full_statement, is_complete, is_empty, scan_statement};
+
+    #[test]
+    fn statements() {
+        assert_eq!(full_statement("SELECT 1; SELECT 2;"), Some(9));
+        assert_eq!(full_statement("SELECT ';'; x"), Some(11));
+        assert_eq!(full_statement("SELECT 'it''s;';"), Some(16));
+        assert_eq!(full_statement(r"SELECT E'\';';"), Some(14));
+        assert_eq!(full_statement(r#"SELECT 1 AS "a;b";"#), Some(18));
+        assert_eq!(full_statement("SELECT 1 -- ;\n;"), Some(15));
+        assert_eq!(full_statement("SELECT /* /* ; */ ; */ 1;"), Some(25));
+        assert_eq!(full_statement("SELECT $$;$$;"), Some(13));
+        assert_eq!(full_statement("SELECT $fn$ $$; $fn$;"), Some(21));
+        assert_eq!(full_statement("SELECT $1;"), Some(10));
+        assert_eq!(full_statement("SELECT 1"), None);
+        assert_eq!(full_statement("SELECT 'a;"), None);
+        assert_eq!(full_statement("SELECT $$;"), None);
+    }
+
+    #[test]
+    fn continuation() {
+        assert_eq!(scan_statement("SELECT 1"), Err(7));
+        assert_eq!(scan_statement("SELECT 'a; b"), Err(7));
+        assert_eq!(scan_statement("'a; b';"), Ok(7));
+        // a dollar sign at the end can start a dollar-quoted string
+        assert_eq!(scan_statement("SELECT $"), Err(7));
+        assert_eq!(scan_statement("$$;$$;"), Ok(6));
+        assert_eq!(scan_statement("SELECT 1 -- x"), Err(9));
+        assert_eq!(scan_statement("-- x;\n;"), Ok(7));
+    }
+
+    #[test]
+    fn empty_and_complete() {
+        assert!(is_empty(" -- comment\n /* x */ ;"));
+        assert!(!is_empty("-- comment\nSELECT"));
+        assert!(is_complete("SELECT 1;\n\\dt"));
+        assert!(is_complete("SELECT 1; -- done"));
+        assert!(!is_complete("SELECT 1; SELECT $$ ;"));
+    }
+}
@@ -1,6 +1,7 @@
 use std::error;
 use std::fmt;
 use std::pin::Pin;
+use std::str;
 
 use anyhow::Context as _;
 use bytes::{Bytes, BytesMut};
@@ -8,6 +9,8 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 
 use edgeql_parser::preparser::full_statement;
 
+use crate::sql_statement;
+
 #[derive(Debug)]
 pub struct EndOfFile;
 
@@ -21,22 +24,60 @@ where
             Ok(len) => break len,
             Err(cont) => continuation = Some(cont),
         };
-        buf.reserve(8192);
-        let bytes_read = Pin::new(&mut *stream)
-            .read_buf(buf)
-            .await
-            .context("error reading query")?;
-        if bytes_read == 0 {
-            if buf.iter().any(|x| !x.is_ascii_whitespace()) {
-                return Ok(buf.split_to(buf.len()).freeze());
-            }
-            return Err(EndOfFile.into());
+        if !read_more(buf, stream).await? {
+            return rest(buf);
         }
     };
     let data = buf.split_to(statement_len).freeze();
     Ok(data)
 }
 
+/// Same as [`read_statement`] but splits SQL statements
+pub async fn read_sql_statement<T>(buf: &mut BytesMut, stream: &mut T) -> anyhow::Result<Bytes>
+where
+    T: Unpin + AsyncRead,
+{
+    // everything before `offset` is scanned and known not to contain
+    // the end of the statement
+    let mut offset = 0;
+    let statement_len = loop {
+        // the buffer may end in the middle of a multi-byte character
+        let text = match str::from_utf8(&buf[offset..]) {
+            Ok(text) => text,
+            Err(e) => str::from_utf8(&buf[offset..][..e.valid_up_to()]).expect("valid prefix"),
+        };
+        match sql_statement::scan_statement(text) {
+            Ok(len) => break offset + len,
+            Err(cont) => offset += cont,
+        }
+        if !read_more(buf, stream).await? {
+            return rest(buf);
+        }
+    };
+    let data = buf.split_to(statement_len).freeze();
+    Ok(data)
+}
+
+/// Returns `false` on end of file
+async fn read_more<T>(buf: &mut BytesMut, stream: &mut T) -> anyhow::Result<bool>
+where
+    T: Unpin + AsyncRead,
+{
+    buf.reserve(8192);
+    let bytes_read = Pin::new(&mut *stream)
+        .read_buf(buf)
+        .await
+        .context("error reading query")?;
+    Ok(bytes_read > 0)
+}
+
+fn rest(buf: &mut BytesMut) -> anyhow::Result<Bytes> {
+    if buf.iter().any(|x| !x.is_ascii_whitespace()) {
+        return Ok(buf.split_to(buf.len()).freeze());
+    }
+    Err(EndOfFile.into())
+}
+
 impl fmt::Display for EndOfFile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "end of file".fmt(f)
@@ -3,8 +3,10 @@ use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
 
+use anyhow::Context;
+
 use crate::prompt;
-use crate::prompt::variable::{self, VariableInput};
+use crate::prompt::variable::{self, InputFlags, VariableInput};
 use crate::repl;
 use gel_protocol::codec;
 use gel_protocol::descriptors::{Descriptor, Typedesc};
@@ -72,6 +74,100 @@ pub async fn input_variables(
     }
 }
 
+/// Converts a JSON object (or an array, for positional parameters) into
+/// the query arguments described by `desc`
+pub fn json_arguments(desc: &Typedesc, json: &serde_json::Value) -> anyhow::Result<Value> {
+    // only for protocol < 0.12
+    if desc.is_empty_tuple() {
+        return Ok(Value::Tuple(Vec::new()));
+    }
+    match desc.root() {
+        Some(Descriptor::ObjectShape(obj)) => {
+            let mut fields = Vec::with_capacity(obj.elements.len());
+            let shape = obj.elements[..].into();
+            for (idx, el) in obj.elements.iter().enumerate() {
+                let optional = el.cardinality.map(|c| c.is_optional()).unwrap_or(false);
+                let item = match json {
+                    serde_json::Value::Object(map) => map.get(&el.name),
+                    serde_json::Value::Array(items) => items.get(idx),
+                    _ => anyhow::bail!("parameters must be a JSON object or array"),
+                };
+                let value = match item {
+                    None | Some(serde_json::Value::Null) if optional => None,
+                    None | Some(serde_json::Value::Null) => {
+                        anyhow::bail!("missing value of parameter ${}", el.name)
+                    }
+                    Some(item) => Some(
+                        json_item(desc.get(el.type_pos)?, desc, item)
+                            .with_context(|| format!("invalid value of parameter ${}", el.name))?,
+                    ),
+                };
+                fields.push(value);
+            }
+            Ok(Value::Object { shape, fields })
+        }
+        Some(root) => Err(anyhow::anyhow!("Unknown input type descriptor: {:?}", root)),
+        // Since protocol 0.12
+        None => Ok(Value::Nothing),
+    }
+}
+
+fn json_item(item: &Descriptor, all: &Typedesc, json: &serde_json::Value) -> anyhow::Result<Value> {
+    use serde_json::Value as Json;
+
+    match (item.normalize_to_base(&all.as_query_arg_context())?, json) {
+        (Descriptor::Array(arr), Json::Array(items)) => {
+            let element = all.get(arr.type_pos)?;
+            let items = items
+                .iter()
+                .map(|i| json_item(element, all, i))
+                .collect::<anyhow::Result<_>>()?;
+            Ok(Value::Array(items))
+        }
+        (Descriptor::Tuple(tuple), Json::Array(items))
+            if items.len() == tuple.element_types.len() =>
+        {
+            let items = tuple
+                .element_types
+                .iter()
+                .zip(items)
+                .map(|(el, i)| json_item(all.get(*el)?, all, i))
+                .collect::<anyhow::Result<_>>()?;
+            Ok(Value::Tuple(items))
+        }
+        (Descriptor::NamedTuple(tuple), Json::Object(map)) => {
+            let fields = tuple
+                .elements
+                .iter()
+                .map(|el| match map.get(&el.name) {
+                    Some(i) => json_item(all.get(el.type_pos)?, all, i),
+                    None => anyhow::bail!("missing tuple element {:?}", el.name),
+                })
+                .collect::<anyhow::Result<_>>()?;
+            Ok(Value::NamedTuple {
+                shape: tuple.elements[..].into(),
+                fields,
+            })
+        }
+        (Descriptor::BaseScalar(_), _) => {
+            let var_type = get_descriptor_type(item, all)?;
+            let type_name = var_type.type_name();
+            // scalars are parsed the same way as entered in the REPL
+            let text = match (&*type_name, json) {
+                ("json", _) => json.to_string(),
+                (_, Json::String(s)) => s.clone(),
+                ("str", _) => anyhow::bail!("expected a string, got {json}"),
+                (_, _) => json.to_string(),
+            };
+            match var_type.parse(&text, InputFlags::NONE) {
+                Ok(("", value)) => Ok(value),
+                _ => anyhow::bail!("expected {type_name}, got {json}"),
+            }
+        }
+        (_, _) => anyhow::bail!("unexpected value {json}"),
+    }
+}
+
 fn get_descriptor_type<'a>(
     desc: &'a Descriptor,
     all: &'a Typedesc,
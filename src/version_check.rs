@@ -14,6 +14,7 @@ use crate::cli::env::Env;
 use crate::platform;
 use crate::portable::repository;
 use crate::portable::ver;
+use crate::print::msg;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Cache {
@@ -57,14 +58,14 @@ fn write_cache(dir: &Path, data: &Cache) -> anyhow::Result<()> {
 
 fn newer_warning(ver: &ver::Semver) {
     if cli::upgrade::can_upgrade() {
-        eprintln!(
+        msg!(
             "Newer version of {BRANDING_CLI_CMD} tool exists {} (current {}). \
                 To upgrade run `{BRANDING_CLI_CMD} cli upgrade`",
             ver,
             env!("CARGO_PKG_VERSION")
         );
     } else {
-        eprintln!(
+        msg!(
             "Newer version of {BRANDING_CLI_CMD} tool exists {} (current {})",
             ver,
             env!("CARGO_PKG_VERSION")
@@ -1,4 +1,5 @@
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
 use const_format::concatcp;
 
@@ -10,20 +11,32 @@ use tokio::sync::watch;
 use tokio::time::timeout;
 
 use crate::branding::{BRANDING, BRANDING_CLI_CMD};
+use crate::commands;
 use crate::connect::{Connection, Connector};
 use crate::interrupt::Interrupt;
+use crate::migrations::options::{CreateMigration, MigrationConfig};
 use crate::migrations::{self, dev_mode};
 use crate::options::Options;
 use crate::portable::project;
-use crate::print::AsRelativeToCurrentDir;
-use crate::watch::options::WatchCommand;
+use crate::print::{self, msg, AsRelativeToCurrentDir};
+use crate::watch::options::{WatchCommand, WatchSubcommand};
+use crate::watch::scripts::Generators;
+use crate::watch::status::{self, WatchStatus};
 
 const STABLE_TIME: Duration = Duration::from_millis(100);
+/// Time the schema must be unchanged before a migration is created
+const AUTO_CREATE_DELAY: Duration = Duration::from_secs(5);
 
 struct WatchContext {
     connector: Connector,
     migration: migrations::Context,
+    auto_create: bool,
+    /// Set after the schema is updated, if `--auto-create` is enabled
+    create_deadline: Option<Instant>,
     last_error: bool,
+    status: WatchStatus,
+    status_file: PathBuf,
+    generators: Generators,
 }
 
 #[derive(serde::Serialize)]
@@ -48,17 +61,33 @@ struct ErrorJson {
     context: Option<ErrorContext>,
 }
 
-pub fn watch(options: &Options, _watch: &WatchCommand) -> anyhow::Result<()> {
+pub fn watch(options: &Options, cmd: &WatchCommand) -> anyhow::Result<()> {
+    if let Some(WatchSubcommand::Status(status_cmd)) = &cmd.subcommand {
+        return status::status(status_cmd);
+    }
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .thread_name("watch")
         .enable_all()
         .build()?;
     let project = project::ensure_ctx(None)?;
+    let migration = migrations::Context::for_project(&project)?;
+    let watched = vec![
+        project.location.manifest.display().to_string(),
+        migration.schema_dir.join("**").display().to_string(),
+    ];
+    let connector = options.block_on_create_connector()?;
+    let generators = Generators::new(&project, &connector)?;
     let mut ctx = WatchContext {
-        connector: options.block_on_create_connector()?,
-        migration: migrations::Context::for_project(&project)?,
+        connector,
+        migration,
+        auto_create: cmd.auto_create,
+        create_deadline: None,
         last_error: false,
+        status: WatchStatus::new(watched),
+        status_file: status::status_file(&project.location.root)?,
+        generators,
     };
+    ctx.write_status();
     log::info!(
         "Initialized in project dir {}",
         project.location.root.as_relative().display()
@@ -76,9 +105,17 @@ pub fn watch(options: &Options, _watch: &WatchCommand) -> anyhow::Result<()> {
 
     runtime.block_on(ctx.do_update())?;
 
-    eprintln!("{BRANDING} Watch initialized.");
-    eprintln!("  Hint: Use `{BRANDING_CLI_CMD} migration create` and `{BRANDING_CLI_CMD} migrate --dev-mode` to apply changes once done.");
-    eprintln!(
+    msg!("{BRANDING} Watch initialized.");
+    if cmd.auto_create {
+        msg!(
+            "  Migrations are created automatically once the schema \
+            is unchanged for {}s.",
+            AUTO_CREATE_DELAY.as_secs()
+        );
+    } else {
+        msg!("  Hint: Use `{BRANDING_CLI_CMD} migration create` and `{BRANDING_CLI_CMD} migrate --dev-mode` to apply changes once done.");
+    }
+    msg!(
         "Monitoring {}.",
         project.location.root.as_relative().display()
     );
@@ -87,6 +124,10 @@ pub fn watch(options: &Options, _watch: &WatchCommand) -> anyhow::Result<()> {
         .block_on(ctx.try_connect_and_clear_error())
         .map_err(|e| log::error!("Cannot clear error: {:#}", e))
         .ok();
+    fs_err::remove_file(&ctx.status_file)
+        .map_err(|e| log::warn!("Cannot remove watch status: {:#}", e))
+        .ok();
+    ctx.generators.cleanup();
     res
 }
 
@@ -128,6 +169,13 @@ pub async fn wait_changes(
     Ok(())
 }
 
+async fn wait_create(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
 async fn watch_loop(mut rx: watch::Receiver<()>, ctx: &mut WatchContext) -> anyhow::Result<()> {
     let mut retry_deadline = None::<Instant>;
     loop {
@@ -135,6 +183,11 @@ async fn watch_loop(mut rx: watch::Receiver<()>, ctx: &mut WatchContext) -> anyh
             let ctrl_c = Interrupt::ctrl_c();
             tokio::select! {
                 _ = wait_changes(&mut rx, retry_deadline) => (),
+                _ = wait_create(ctx.create_deadline) => {
+                    ctx.create_deadline = None;
+                    ctx.auto_create().await;
+                    continue;
+                }
                 res = ctrl_c.wait_result() => res?,
             };
         }
@@ -152,7 +205,7 @@ async fn watch_loop(mut rx: watch::Receiver<()>, ctx: &mut WatchContext) -> anyh
 
 impl WatchContext {
     async fn do_update(&mut self) -> anyhow::Result<()> {
-        let bar = ProgressBar::new_spinner();
+        let bar = print::progress(ProgressBar::new_spinner());
         bar.enable_steady_tick(Duration::from_millis(100));
         // TODO(tailhook) check gel/edgedb version
         bar.set_message("connecting");
@@ -163,16 +216,24 @@ impl WatchContext {
         cli.restore_state(old_state);
 
         bar.finish_and_clear();
+        self.status.last_sync = Some(SystemTime::now());
+        self.status.last_error = result.as_ref().err().map(|e| format!("{e:#}"));
+        self.write_status();
         match result {
             Ok(()) => {
+                if self.auto_create {
+                    self.create_deadline = Some(Instant::now() + AUTO_CREATE_DELAY);
+                }
                 if self.last_error {
                     clear_error(&mut cli).await;
                     self.last_error = false;
-                    eprintln!("Resolved. Schema is up to date now.");
+                    msg!("Resolved. Schema is up to date now.");
                 }
+                self.generators.run().await;
             }
             Err(e) => {
-                eprintln!("Schema migration error: {e:#}");
+                self.create_deadline = None;
+                print::error!("Schema migration error: {e:#}");
                 set_error(&mut cli, e).await;
                 // TODO(tailhook) probably only print if error doesn't match
                 self.last_error = true;
@@ -180,6 +241,69 @@ impl WatchContext {
         }
         Ok(())
     }
+    /// Creates a migration from the dev mode migrations applied so far.
+    /// Errors are only reported, as the schema itself is up to date.
+    async fn auto_create(&mut self) {
+        match self.try_auto_create().await {
+            Ok(false) => {}
+            Ok(true) => {
+                // apply the created migration in place of dev mode ones
+                if let Err(e) = self.do_update().await {
+                    log::error!("Error updating database: {:#}", e);
+                }
+                // nothing changed since
+                self.create_deadline = None;
+            }
+            Err(e) => {
+                print::warn!("Migration was not created automatically: {e:#}");
+                msg!("  Hint: Run `{BRANDING_CLI_CMD} migration create` to resolve.");
+            }
+        }
+    }
+    async fn try_auto_create(&mut self) -> anyhow::Result<bool> {
+        let mut cli = self.connector.connect().await?;
+        let dev_num = cli
+            .query_required_single::<i64, _>(
+                "SELECT count((
+                    SELECT schema::Migration
+                    FILTER .generated_by = schema::MigrationGeneratedBy.DevMode
+                ))",
+                &(),
+            )
+            .await?;
+        if dev_num == 0 {
+            log::debug!("No dev mode migrations, nothing to create");
+            return Ok(false);
+        }
+        let options = commands::Options {
+            command_line: true,
+            styler: None,
+            conn_params: self.connector.clone(),
+        };
+        let create = CreateMigration {
+            cfg: MigrationConfig {
+                schema_dir: Some(self.migration.schema_dir.clone()),
+            },
+            squash: false,
+            non_interactive: true,
+            allow_unsafe: false,
+            interactive_tui: false,
+            allow_empty: false,
+            split_by_module: false,
+            from_ddl: None,
+            with_down: false,
+            debug_print_queries: false,
+            debug_print_err: false,
+        };
+        migrations::create(&mut cli, &options, &create).await?;
+        Ok(true)
+    }
+    fn write_status(&self) {
+        self.status
+            .write(&self.status_file)
+            .map_err(|e| log::warn!("{:#}", e))
+            .ok();
+    }
     async fn try_connect_and_clear_error(&mut self) -> anyhow::Result<()> {
         if self.last_error {
             let mut cli = self.connector.connect().await?;
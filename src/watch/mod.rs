@@ -1,6 +1,8 @@
 pub mod options;
 
 mod main;
+mod scripts;
+mod status;
 
 pub use main::wait_changes;
 pub use main::watch;
@@ -2,10 +2,32 @@ use crate::options::ConnectionOptions;
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct WatchCommand {
+    #[command(subcommand)]
+    pub subcommand: Option<WatchSubcommand>,
+
     #[command(flatten)]
     pub conn: ConnectionOptions,
 
     /// Print DDLs applied to the schema.
     #[arg(short = 'v', long)]
     pub verbose: bool,
+
+    /// Create a migration file once the schema has not changed for a few
+    /// seconds, like `migration create --non-interactive` does. Changes that
+    /// need user input are reported and left for `migration create`.
+    #[arg(long)]
+    pub auto_create: bool,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum WatchSubcommand {
+    /// Show the state of the `watch` process running for the current project.
+    Status(StatusCommand),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct StatusCommand {
+    /// Output in JSON format.
+    #[arg(long)]
+    pub json: bool,
 }
@@ -0,0 +1,115 @@
+//! Code generators run by `watch` for the `[[watch]]` entries of the
+//! manifest, e.g. `generate = "queries"`.
+//!
+//! Generators are run with `npx` in the project directory after each
+//! successful schema update. They connect to the same instance and branch
+//! as `watch` itself: a named instance is passed in `GEL_INSTANCE`, other
+//! connections via a credentials file in `GEL_CREDENTIALS_FILE` (plus the
+//! `EDGEDB_*` variants for older generators).
+//!
+//! Entries are run one at a time, after the entries listed in their
+//! `needs`. An entry is skipped if any of those fails.
+
+use std::path::PathBuf;
+
+use gel_tokio::get_stash_path;
+use tokio::process::Command;
+
+use crate::connect::Connector;
+use crate::credentials;
+use crate::portable::project;
+use crate::portable::project::manifest::{Generator, WatchTarget};
+use crate::print::{self, msg, Highlight};
+
+/// The npm package providing the generators.
+const GENERATE_PACKAGE: &str = if cfg!(feature = "gel") {
+    "@gel/generate"
+} else {
+    "@edgedb/generate"
+};
+
+pub struct Generators {
+    project_dir: PathBuf,
+    targets: Vec<WatchTarget>,
+    env: Vec<(String, String)>,
+    credentials_file: Option<PathBuf>,
+}
+
+impl Generators {
+    pub fn new(project: &project::Context, connector: &Connector) -> anyhow::Result<Generators> {
+        let mut result = Generators {
+            project_dir: project.location.root.clone(),
+            targets: project.manifest.watch.clone(),
+            env: Vec::new(),
+            credentials_file: None,
+        };
+        if result.targets.is_empty() {
+            return Ok(result);
+        }
+        let config = connector.get()?;
+        if let Some(name) = config.local_instance_name() {
+            result.set_env("INSTANCE", name);
+        } else {
+            let path = get_stash_path(&result.project_dir)?.join("watch-credentials.json");
+            credentials::write_file(&path, &config.as_credentials()?)?;
+            result.set_env("CREDENTIALS_FILE", &path.display().to_string());
+            result.credentials_file = Some(path);
+        }
+        if config.branch() != "__default__" {
+            result.set_env("BRANCH", config.branch());
+        }
+        Ok(result)
+    }
+
+    fn set_env(&mut self, suffix: &str, value: &str) {
+        for prefix in ["GEL", "EDGEDB"] {
+            self.env
+                .push((format!("{prefix}_{suffix}"), value.to_string()));
+        }
+    }
+
+    /// Runs all the generators. Failures are only reported, as the schema
+    /// itself is up to date.
+    pub async fn run(&self) {
+        // entries are sorted when reading the manifest, so prerequisites
+        // are run first
+        let mut failed = Vec::new();
+        for target in &self.targets {
+            if let Some(need) = target.needs.iter().find(|n| failed.contains(n)) {
+                print::warn!("Skipping {}: {need} failed", target.name());
+                failed.push(target.name().to_string());
+                continue;
+            }
+            if let Err(e) = self.run_one(target.generate).await {
+                print::error!("Generator {} failed: {e:#}", target.name());
+                failed.push(target.name().to_string());
+            }
+        }
+    }
+
+    async fn run_one(&self, generator: Generator) -> anyhow::Result<()> {
+        msg!("Running {} generator...", generator.name().emphasize());
+        let npx = if cfg!(windows) { "npx.cmd" } else { "npx" };
+        let status = Command::new(npx)
+            .arg(GENERATE_PACKAGE)
+            .arg(generator.name())
+            .current_dir(&self.project_dir)
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .status()
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot run `{npx}`: {e}"))?;
+        if !status.success() {
+            anyhow::bail!("{status}");
+        }
+        Ok(())
+    }
+
+    /// Removes the credentials file written for the generators
+    pub fn cleanup(&self) {
+        if let Some(path) = &self.credentials_file {
+            fs_err::remove_file(path)
+                .map_err(|e| log::warn!("Cannot remove watch credentials: {:#}", e))
+                .ok();
+        }
+    }
+}
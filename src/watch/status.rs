@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use fn_error_context::context;
+use gel_tokio::get_stash_path;
+
+use crate::branding::BRANDING_CLI_CMD;
+use crate::commands::ExitCode;
+use crate::platform::tmp_file_path;
+use crate::portable::project;
+use crate::print::{self, msg, Highlight};
+use crate::process;
+use crate::table;
+use crate::watch::options::StatusCommand;
+
+/// State of the running `watch` process, stored in the project stash dir.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct WatchStatus {
+    pub pid: u32,
+    #[serde(with = "humantime_serde")]
+    pub started_at: SystemTime,
+    #[serde(with = "humantime_serde")]
+    pub last_sync: Option<SystemTime>,
+    pub last_error: Option<String>,
+    pub watched: Vec<String>,
+}
+
+pub fn status_file(project_root: &Path) -> anyhow::Result<PathBuf> {
+    Ok(get_stash_path(project_root)?.join("watch-status.json"))
+}
+
+impl WatchStatus {
+    pub fn new(watched: Vec<String>) -> WatchStatus {
+        WatchStatus {
+            pid: std::process::id(),
+            started_at: SystemTime::now(),
+            last_sync: None,
+            last_error: None,
+            watched,
+        }
+    }
+
+    #[context("cannot write watch status to {:?}", path)]
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp = tmp_file_path(path);
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    #[context("cannot read watch status from {:?}", path)]
+    pub fn read(path: &Path) -> anyhow::Result<Option<WatchStatus>> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e)?,
+        };
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+}
+
+pub fn status(cmd: &StatusCommand) -> anyhow::Result<()> {
+    let project = project::ensure_ctx(None)?;
+    let path = status_file(&project.location.root)?;
+    let status = WatchStatus::read(&path)?.filter(|s| process::exists(s.pid));
+    let Some(status) = status else {
+        if cmd.json {
+            println!("null");
+        } else {
+            msg!(
+                "{} {} Run `{BRANDING_CLI_CMD} watch` to start it.",
+                print::err_marker(),
+                "Watch is not running for this project.".emphasize()
+            );
+        }
+        return Err(ExitCode::new(3).into());
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        let state = if status.last_error.is_some() {
+            "error"
+        } else if status.last_sync.is_some() {
+            "synced"
+        } else {
+            "starting"
+        };
+        let mut rows: Vec<(&str, String)> = vec![
+            ("State", state.into()),
+            ("Process id", status.pid.to_string()),
+            (
+                "Started at",
+                humantime::format_rfc3339_seconds(status.started_at).to_string(),
+            ),
+            (
+                "Last sync",
+                status
+                    .last_sync
+                    .map(|t| humantime::format_rfc3339_seconds(t).to_string())
+                    .unwrap_or_else(|| "never".into()),
+            ),
+            ("Watching", status.watched.join("\n")),
+        ];
+        if let Some(error) = &status.last_error {
+            rows.push(("Last error", error.clone()));
+        }
+        table::settings(rows.as_slice());
+    }
+    if status.last_error.is_some() {
+        return Err(ExitCode::new(2).into());
+    }
+    Ok(())
+}
@@ -123,3 +123,105 @@ fn dump_restore_all() {
         .stdout("\"world\"\n");
     new_instance.0.stop();
 }
+
+#[test]
+fn dump_restore_exclude() {
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    SERVER
+        .admin_cmd()
+        .arg("database")
+        .arg("create")
+        .arg("dump_03")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_03")
+        .arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("CREATE TYPE AuditLog { CREATE REQUIRED PROPERTY event -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .arg("INSERT AuditLog { event := 'created' }")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_03")
+        .arg("dump")
+        .arg("./tmp/dump_03.dump")
+        .assert()
+        .success();
+    SERVER
+        .admin_cmd()
+        .arg("database")
+        .arg("create")
+        .arg("restore_03")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("restore_03")
+        .arg("restore")
+        .arg("--exclude=default::AuditLog")
+        .arg("./tmp/dump_03.dump")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("restore_03")
+        .arg("query")
+        .arg("SELECT Hello.name")
+        .arg("SELECT count(AuditLog)")
+        .assert()
+        .success()
+        .stdout("\"world\"\n0\n");
+}
+
+#[test]
+fn restore_into_non_empty() {
+    std::fs::create_dir_all("./tmp").expect("can create directory");
+    SERVER
+        .admin_cmd()
+        .arg("database")
+        .arg("create")
+        .arg("dump_04")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_04")
+        .arg("query")
+        .arg("CREATE TYPE Hello { CREATE REQUIRED PROPERTY name -> str; }")
+        .arg("INSERT Hello { name := 'world' }")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_04")
+        .arg("dump")
+        .arg("./tmp/dump_04.dump")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_04")
+        .arg("restore")
+        .arg("./tmp/dump_04.dump")
+        .assert()
+        .code(1);
+    SERVER
+        .database_cmd("dump_04")
+        .arg("restore")
+        .arg("--force-overwrite")
+        .arg("--non-interactive")
+        .arg("./tmp/dump_04.dump")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("dump_04")
+        .arg("restore")
+        .arg("--into-new-branch=restore_04")
+        .arg("./tmp/dump_04.dump")
+        .assert()
+        .success();
+    SERVER
+        .database_cmd("restore_04")
+        .arg("query")
+        .arg("SELECT Hello.name")
+        .assert()
+        .success()
+        .stdout("\"world\"\n");
+}
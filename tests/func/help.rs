@@ -8,6 +8,8 @@ fn help_connect() {
     let out = String::from_utf8(cmd.get_output().stdout.clone()).unwrap();
 
     assert!(out.contains("--host"));
+    assert!(out.contains("--connect-timeout"));
+    assert!(out.contains("--wait-until-available"));
 }
 
 #[test]
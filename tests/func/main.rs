@@ -131,6 +131,8 @@ impl ServerGuard {
         cmd.arg("--admin");
         cmd.arg("--unix-path").arg(&self.0.info.socket_dir);
         cmd.arg("--port").arg(self.0.info.port.to_string());
+        // long outputs must not stop in a pager
+        cmd.env("PAGER", "cat");
         spawn_command(cmd, Some(10000)).expect("start interactive")
     }
     #[cfg(not(windows))]
@@ -148,10 +150,22 @@ impl ServerGuard {
         cmd.arg("--port").arg(self.0.info.port.to_string());
         cmd.arg("--tls-ca-file").arg(&self.0.info.tls_cert_file);
         cmd.env("CLICOLOR", "0");
+        cmd.env("PAGER", "cat");
         f(&mut cmd);
         spawn_command(cmd, Some(10000)).expect("start interactive")
     }
 
+    /// Command connecting with a `<scheme>://` DSN over TCP, the server
+    /// certificate is verified with the CA file of the server
+    pub fn dsn_cmd(&self, scheme: &str) -> Command {
+        let mut cmd = edgedb_cli_cmd();
+        cmd.arg("--dsn")
+            .arg(format!("{scheme}://127.0.0.1:{}", self.0.info.port));
+        cmd.arg("--tls-ca-file").arg(&self.0.info.tls_cert_file);
+        cmd.env("CLICOLOR", "0");
+        cmd
+    }
+
     pub fn database_cmd(&self, database_name: &str) -> Command {
         let mut cmd = self.admin_cmd();
         cmd.arg("--tls-ca-file").arg(&self.0.info.tls_cert_file);
@@ -193,6 +207,10 @@ fn rm_migration_files(schema_dir: &str, migration_indexes: &[u16]) {
             continue;
         };
 
+        // skip `down` and `snapshots` dirs
+        if !entry.file_type().unwrap().is_file() {
+            continue;
+        }
         let file_name = entry.file_name().into_string().unwrap();
         let mig_index = file_name.split('-').next().unwrap();
 
@@ -1230,3 +1230,75 @@ fn unsafe_migrations() {
         .assert()
         .success();
 }
+
+#[test]
+fn tagged_migrations() {
+    SERVER
+        .admin_cmd()
+        .arg("database")
+        .arg("create")
+        .arg("db_tagged")
+        .assert()
+        .success();
+    SERVER
+        .admin_cmd()
+        .arg("--branch=db_tagged")
+        .arg("migrate")
+        .arg("--schema-dir=tests/migrations/db1/initial")
+        .arg("--tag=v1.0")
+        .assert()
+        .success();
+    SERVER
+        .admin_cmd()
+        .arg("--branch=db_tagged")
+        .arg("migration")
+        .arg("log")
+        .arg("--from-db")
+        .assert()
+        .success()
+        .stdout("m12bulrbounwj3oj5xsspa7gj676azrog6ndi45iyuwrwzvawkxraa (v1.0)\n");
+    SERVER
+        .admin_cmd()
+        .arg("--branch=db_tagged")
+        .arg("migration")
+        .arg("log")
+        .arg("--from-db")
+        .arg("--tag=v2.0")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn migration_plan() {
+    SERVER
+        .admin_cmd()
+        .arg("database")
+        .arg("create")
+        .arg("db_plan")
+        .assert()
+        .success();
+    SERVER
+        .admin_cmd()
+        .arg("--branch=db_plan")
+        .arg("migrate")
+        .arg("--schema-dir=tests/migrations/db1/initial")
+        .arg("--plan")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(
+            contains(r#""id": "m12bulrbounwj3oj5xsspa7gj676azrog6ndi45iyuwrwzvawkxraa""#)
+                .and(contains(r#""statements": 1"#))
+                .and(contains(r#""fastForward": true"#)),
+        );
+    SERVER
+        .admin_cmd()
+        .arg("--branch=db_plan")
+        .arg("migration")
+        .arg("log")
+        .arg("--from-db")
+        .assert()
+        .success()
+        .stdout("");
+}
@@ -361,3 +361,49 @@ fn warnings() {
         )
         .context("warnings", "print warnings from migrations");
 }
+
+#[test]
+fn session_config() {
+    SERVER
+        .admin_cmd()
+        .arg("query")
+        .arg("--output-format=tab-separated")
+        .arg("--session-config=allow_user_specified_id=true")
+        .arg("SELECT assert_single(cfg::Config.allow_user_specified_id)")
+        .assert()
+        .context("set", "setting is applied to the session")
+        .stdout("true\n")
+        .success();
+
+    SERVER
+        .admin_cmd()
+        .arg("query")
+        .arg("--session-config=listen_port=1")
+        .arg("SELECT 1")
+        .assert()
+        .context("system", "instance-level settings are rejected")
+        .stderr(predicates::str::contains("is not a session setting"))
+        .failure();
+}
+
+#[test]
+fn http_dsn() {
+    SERVER
+        .dsn_cmd("https")
+        .arg("query")
+        .arg("--output-format=json")
+        .arg("SELECT 1+7")
+        .assert()
+        .context("query", "query runs over HTTPS verifying the server CA")
+        .stdout(predicates::str::contains("8"))
+        .success();
+
+    SERVER
+        .dsn_cmd("https")
+        .arg("list")
+        .arg("indexes")
+        .assert()
+        .context("other", "other commands reject HTTP DSNs")
+        .stderr(predicates::str::contains("only supported by `query`"))
+        .failure();
+}
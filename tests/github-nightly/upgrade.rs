@@ -61,3 +61,55 @@ fn package(tagname: &str, dockerfile: &str) -> anyhow::Result<()> {
     .success();
     Ok(())
 }
+
+#[test_case("test_keep_old_bookworm", &dock_debian("bookworm"))]
+fn keep_old(tagname: &str, dockerfile: &str) -> anyhow::Result<()> {
+    let _tm = Time::measure();
+    let context = Context::new()
+        .add_file("Dockerfile", dockerfile)?
+        .add_sudoers()?
+        .add_bin()?;
+    build_image(context, tagname)?;
+    run_systemd(
+        tagname,
+        r###"
+        edgedb server install --version=4.8
+        edgedb instance create test1 --version=4.8
+
+        edgedb --wait-until-available=60s -Itest1 query '
+            CREATE TYPE Type1 {
+                CREATE PROPERTY prop1 -> str;
+            }
+        ' 'INSERT Type1 { prop1 := "value1" }'
+        if ! edgedb instance upgrade -I test1 --to-version=5 --keep-old --non-interactive; then
+            res=$?
+            journalctl -xe
+            exit $res
+        fi
+        ver2=$(edgedb -Itest1 --wait-until-available=60s query --output-format=tab-separated \
+            'SELECT sys::get_version_as_str()')
+        [[ $ver2 =~ ^5\.[0-9]+\+ ]]
+
+        val=$(edgedb -Itest1 --wait-until-available=60s query --output-format=tab-separated \
+            'SELECT Type1 { prop1 }')
+        test "$val" = "value1"
+
+        edgedb instance list | grep test1_previous
+
+        if ! edgedb instance upgrade -I test1 --rollback --non-interactive; then
+            res=$?
+            journalctl -xe
+            exit $res
+        fi
+        ver3=$(edgedb -Itest1 --wait-until-available=60s query --output-format=tab-separated \
+            'SELECT sys::get_version_as_str()')
+        [[ $ver3 =~ ^4\.8 ]]
+
+        val=$(edgedb -Itest1 --wait-until-available=60s query --output-format=tab-separated \
+            'SELECT Type1 { prop1 }')
+        test "$val" = "value1"
+    "###,
+    )
+    .success();
+    Ok(())
+}